@@ -1,11 +1,17 @@
 //! 缓存模块
 //!
-//! 提供查询结果缓存，支持 LRU 淘汰和 TTL 过期。
+//! 提供查询结果缓存，支持 LRU 淘汰、TTL 过期和可选的磁盘持久化。
 
+mod persist;
 mod query_cache;
+mod tuning;
 mod warmup;
 
-pub use query_cache::{CacheConfig, CacheKey, CacheStats, QueryCache};
+pub use persist::default_snapshot_path;
+pub use query_cache::{
+    CACHE_SCHEMA_VERSION, CacheConfig, CacheEvent, CacheKey, CacheStats, QueryCache,
+};
+pub use tuning::{CACHE_MEMORY_BUDGET_BYTES, CacheConfigSuggestion, CacheUsage};
 pub use warmup::{
     CacheWarmer, FixedTimeRangeStrategy, RecentTimeRangeStrategy, WarmupProgress, WarmupStrategy,
     WarmupTask,
@@ -0,0 +1,170 @@
+//! 查询缓存持久化
+//!
+//! 退出时将未过期的缓存写入磁盘，启动时恢复。文件带数据格式版本号，
+//! 与当前 `CACHE_SCHEMA_VERSION` 不符时整体丢弃，避免升级后命中旧格式数据。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use super::{CACHE_SCHEMA_VERSION, CacheKey, QueryCache};
+use crate::error::AppResult;
+use crate::models::HistoryRecord;
+
+/// 持久化的缓存条目
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct PersistedEntry {
+    pub key: CacheKey,
+    pub data: Vec<HistoryRecord>,
+    /// 保存时条目已存在的秒数（恢复后继续按 TTL 计算过期）
+    pub age_secs: u64,
+    /// 查询提示（旧快照无此字段）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// 持久化文件头（先只解析版本号，版本不符时不解析条目）
+#[derive(Deserialize)]
+struct SnapshotHeader {
+    version: u32,
+}
+
+/// 持久化文件
+#[derive(Serialize, Deserialize)]
+struct CacheSnapshot {
+    version: u32,
+    entries: Vec<PersistedEntry>,
+}
+
+/// 默认的缓存持久化文件路径
+pub fn default_snapshot_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|d| d.join("IndustryVis").join("query_cache.json"))
+}
+
+impl QueryCache {
+    /// 将未过期的缓存写入文件，返回写入的条目数
+    pub async fn save_snapshot(&self, path: &Path) -> AppResult<usize> {
+        let entries = self.export_entries().await;
+        let count = entries.len();
+        let snapshot = CacheSnapshot {
+            version: CACHE_SCHEMA_VERSION,
+            entries,
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_vec(&snapshot)?)?;
+
+        info!(target: "industry_vis::cache",
+            "缓存已持久化 - {} 个条目, 路径: {}", count, path.display()
+        );
+        Ok(count)
+    }
+
+    /// 从文件恢复缓存，返回恢复的条目数
+    ///
+    /// 文件不存在、版本号不符或格式无法解析时丢弃整个文件，返回 0。
+    pub async fn load_snapshot(&self, path: &Path) -> AppResult<usize> {
+        let content = match fs::read(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+
+        let version = serde_json::from_slice::<SnapshotHeader>(&content).map(|h| h.version);
+        if version.as_ref().ok() != Some(&CACHE_SCHEMA_VERSION) {
+            info!(target: "industry_vis::cache",
+                "持久化缓存版本不符（文件: {:?}, 当前: {}），已丢弃",
+                version.ok(), CACHE_SCHEMA_VERSION
+            );
+            let _ = fs::remove_file(path);
+            return Ok(0);
+        }
+
+        let snapshot: CacheSnapshot = match serde_json::from_slice(&content) {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                warn!(target: "industry_vis::cache", "持久化缓存解析失败，已丢弃: {}", e);
+                let _ = fs::remove_file(path);
+                return Ok(0);
+            }
+        };
+
+        let count = self.import_entries(snapshot.entries).await;
+        debug!(target: "industry_vis::cache",
+            "已恢复持久化缓存 - {} 个条目, 路径: {}", count, path.display()
+        );
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheConfig;
+
+    fn snapshot_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "industry_vis_cache_{}_{}.json",
+            name,
+            std::process::id()
+        ))
+    }
+
+    fn sample_key() -> CacheKey {
+        CacheKey::new("History", "2024-01-01", "2024-01-02", None, None)
+    }
+
+    fn sample_records() -> Vec<HistoryRecord> {
+        vec![HistoryRecord::new(
+            "2024-01-01T00:00:00.000".to_string(),
+            "Tag1".to_string(),
+            1.5,
+            "Good".to_string(),
+        )]
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_with_matching_version_is_loaded() {
+        let path = snapshot_path("match");
+        let cache = QueryCache::new(CacheConfig::default());
+        cache.put(sample_key(), sample_records()).await;
+        assert_eq!(cache.save_snapshot(&path).await.unwrap(), 1);
+
+        let restored = QueryCache::new(CacheConfig::default());
+        assert_eq!(restored.load_snapshot(&path).await.unwrap(), 1);
+        let _ = fs::remove_file(&path);
+
+        let data = restored.get(&sample_key()).await.unwrap();
+        assert_eq!(data[0].tag_val, 1.5);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_with_other_version_is_discarded() {
+        let path = snapshot_path("mismatch");
+        let cache = QueryCache::new(CacheConfig::default());
+        cache.put(sample_key(), sample_records()).await;
+        cache.save_snapshot(&path).await.unwrap();
+
+        // 模拟旧版本写出的文件
+        let content = fs::read_to_string(&path).unwrap().replacen(
+            &format!("\"version\":{}", CACHE_SCHEMA_VERSION),
+            &format!("\"version\":{}", CACHE_SCHEMA_VERSION + 1),
+            1,
+        );
+        fs::write(&path, content).unwrap();
+
+        let restored = QueryCache::new(CacheConfig::default());
+        assert_eq!(restored.load_snapshot(&path).await.unwrap(), 0);
+        assert!(!path.exists());
+        assert!(restored.get(&sample_key()).await.is_none());
+
+        // 无法解析的旧格式同样丢弃
+        fs::write(&path, "[1, 2, 3]").unwrap();
+        assert_eq!(restored.load_snapshot(&path).await.unwrap(), 0);
+        assert!(!path.exists());
+    }
+}
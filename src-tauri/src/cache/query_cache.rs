@@ -2,17 +2,32 @@
 //!
 //! 使用 LRU 缓存 + TTL 过期策略缓存查询结果。
 
+use std::collections::VecDeque;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 use lru::LruCache;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
+use tokio::sync::mpsc::UnboundedSender;
+// 使用 tokio 时钟计时，测试中可通过暂停的时钟推进过期时间
+use tokio::time::Instant;
 use tracing::{debug, info};
 
-use crate::models::{DataProcessingConfig, HistoryRecord};
+use super::persist::PersistedEntry;
+use super::tuning::{CacheConfigSuggestion, CacheUsage};
+use crate::models::{DataProcessingConfig, HistoryRecord, QueryParams};
+
+/// 缓存数据格式版本
+///
+/// `HistoryRecord` 结构或处理算法语义变更时递增：缓存键带上版本号，
+/// 持久化的旧版本缓存在加载时整体丢弃。
+pub const CACHE_SCHEMA_VERSION: u32 = 1;
+
+/// 保留的重复访问间隔样本数
+const MAX_REACCESS_SAMPLES: usize = 256;
 
 /// 缓存配置
 #[derive(Clone, Debug)]
@@ -21,6 +36,8 @@ pub struct CacheConfig {
     pub max_entries: usize,
     /// 缓存过期时间（秒）
     pub ttl_seconds: u64,
+    /// 滑动过期：命中时重新计算过期时间（默认关闭，按写入时刻绝对过期）
+    pub sliding_expiration: bool,
 }
 
 impl Default for CacheConfig {
@@ -28,6 +45,7 @@ impl Default for CacheConfig {
         Self {
             max_entries: 200,  // 最多缓存 200 个查询结果（历史数据查询场景）
             ttl_seconds: 1800, // 30 分钟过期（历史数据不变，长 TTL 安全）
+            sliding_expiration: false,
         }
     }
 }
@@ -38,26 +56,41 @@ impl CacheConfig {
         Self {
             max_entries,
             ttl_seconds,
+            sliding_expiration: false,
         }
     }
+
+    /// 设置是否启用滑动过期
+    pub fn with_sliding_expiration(mut self, sliding: bool) -> Self {
+        self.sliding_expiration = sliding;
+        self
+    }
 }
 
 /// 缓存键
 ///
-/// 基于表名、时间范围、标签列表、处理配置生成唯一键
-#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+/// 基于数据格式版本、表名、时间范围、标签列表、处理配置生成唯一键
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub struct CacheKey {
+    /// 缓存数据格式版本（见 `CACHE_SCHEMA_VERSION`）
+    pub schema_version: u32,
     pub table: String,
     pub start_time: String,
     pub end_time: String,
-    pub tags: Vec<String>,           // 已排序
+    /// 标签范围：`None` 表示全部标签（全表查询），`Some` 为已排序去重的标签子集
+    pub tags: Option<Vec<String>>,
     pub processing_config_hash: u64, // 处理配置的哈希值
+    /// 每标签最新点数上限（限量查询与完整查询结果不同，需区分）
+    pub per_tag_limit: Option<usize>,
 }
 
 impl CacheKey {
     /// 创建缓存键
     ///
-    /// 标签列表会自动排序，确保顺序无关
+    /// 标签列表会自动排序去重，确保顺序无关。
+    ///
+    /// 与数据源的标签过滤保持一致：`None` 和 `Some(&[])` 都表示"全部标签"，
+    /// 生成相同的键，且不会与任何指定标签子集的查询混淆。
     pub fn new(
         table: &str,
         start_time: &str,
@@ -65,53 +98,160 @@ impl CacheKey {
         tags: Option<&[String]>,
         processing_config: Option<&DataProcessingConfig>,
     ) -> Self {
-        let mut sorted_tags: Vec<String> = tags.map(|t| t.to_vec()).unwrap_or_default();
-        sorted_tags.sort();
+        let sorted_tags = tags.filter(|t| !t.is_empty()).map(|t| {
+            let mut sorted = t.to_vec();
+            sorted.sort();
+            sorted.dedup();
+            sorted
+        });
 
         let processing_config_hash = processing_config
             .map(|c| {
                 let mut hasher = DefaultHasher::new();
                 c.outlier_removal.enabled.hash(&mut hasher);
                 c.outlier_removal.method.hash(&mut hasher);
+                c.outlier_removal.window.hash(&mut hasher);
+                c.outlier_removal.mode.hash(&mut hasher);
                 c.resample.enabled.hash(&mut hasher);
                 c.resample.interval.hash(&mut hasher);
                 c.resample.method.hash(&mut hasher);
+                c.resample.methods.hash(&mut hasher);
+                c.resample.dst_ambiguous.hash(&mut hasher);
                 c.smoothing.enabled.hash(&mut hasher);
                 c.smoothing.method.hash(&mut hasher);
                 c.smoothing.window.hash(&mut hasher);
+                c.std_band.enabled.hash(&mut hasher);
+                c.std_band.window.hash(&mut hasher);
+                c.std_band.multiplier.to_bits().hash(&mut hasher);
+                c.dedup.enabled.hash(&mut hasher);
+                c.dedup.keep.hash(&mut hasher);
+                c.range_limit.enabled.hash(&mut hasher);
+                c.range_limit.mode.hash(&mut hasher);
+                for range in &c.range_limit.ranges {
+                    range.tag.hash(&mut hasher);
+                    range.min.to_bits().hash(&mut hasher);
+                    range.max.to_bits().hash(&mut hasher);
+                }
+                c.baseline.hash(&mut hasher);
                 hasher.finish()
             })
             .unwrap_or(0);
 
         Self {
+            schema_version: CACHE_SCHEMA_VERSION,
             table: table.to_string(),
             start_time: start_time.to_string(),
             end_time: end_time.to_string(),
             tags: sorted_tags,
             processing_config_hash,
+            per_tag_limit: None,
         }
     }
+
+    /// 设置每标签最新点数上限
+    pub fn with_per_tag_limit(mut self, per_tag_limit: Option<usize>) -> Self {
+        self.per_tag_limit = per_tag_limit;
+        self
+    }
+
+    /// 历史查询的缓存键
+    ///
+    /// 不含分页参数：缓存保存整个时间范围的处理结果，各页都从同一条目切分，
+    /// 返回第一页后翻页直接命中缓存，无需额外预取。
+    pub fn for_query(
+        table: &str,
+        params: &QueryParams,
+        processing_config: Option<&DataProcessingConfig>,
+    ) -> Self {
+        Self::new(
+            table,
+            &params.start_time,
+            &params.end_time,
+            params.tags.as_deref(),
+            processing_config,
+        )
+        .with_per_tag_limit(params.per_tag_limit)
+    }
+
+    /// 简短描述，用于事件通知和调试输出
+    pub fn summary(&self) -> String {
+        let tags = match &self.tags {
+            Some(tags) => format!("{} tags", tags.len()),
+            None => "all tags".to_string(),
+        };
+        format!(
+            "{} [{} ~ {}] {}",
+            self.table, self.start_time, self.end_time, tags
+        )
+    }
+}
+
+/// 缓存访问事件（命中/未命中）
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheEvent {
+    /// 缓存键摘要
+    pub key_summary: String,
+    /// 是否命中
+    pub hit: bool,
 }
 
 /// 缓存条目
 struct CacheEntry {
     data: Vec<HistoryRecord>,
     created_at: Instant,
+    /// 最近一次访问（或写入）时刻，用于统计重复访问间隔
+    last_access: Instant,
     ttl: Duration,
+    /// 与数据一同缓存的查询提示（如采样不均匀告警）
+    warnings: Vec<String>,
 }
 
 impl CacheEntry {
-    fn new(data: Vec<HistoryRecord>, ttl: Duration) -> Self {
+    fn new(data: Vec<HistoryRecord>, ttl: Duration, warnings: Vec<String>) -> Self {
+        let now = Instant::now();
         Self {
             data,
-            created_at: Instant::now(),
+            created_at: now,
+            last_access: now,
             ttl,
+            warnings,
         }
     }
 
     fn is_expired(&self) -> bool {
         self.created_at.elapsed() > self.ttl
     }
+
+    /// 按已存在时长恢复条目（用于加载持久化缓存）
+    fn with_age(
+        data: Vec<HistoryRecord>,
+        ttl: Duration,
+        age: Duration,
+        warnings: Vec<String>,
+    ) -> Self {
+        let now = Instant::now();
+        Self {
+            data,
+            created_at: now.checked_sub(age).unwrap_or(now),
+            last_access: now,
+            ttl,
+            warnings,
+        }
+    }
+
+    /// 重置过期计时（滑动过期模式下命中时调用）
+    fn touch(&mut self) {
+        self.created_at = Instant::now();
+    }
+
+    /// 记录一次访问，返回距上次访问的间隔
+    fn record_access(&mut self) -> Duration {
+        let now = Instant::now();
+        let interval = now.duration_since(self.last_access);
+        self.last_access = now;
+        interval
+    }
 }
 
 /// 缓存统计信息
@@ -138,11 +278,29 @@ pub struct QueryCache {
     cache: Arc<RwLock<LruCache<CacheKey, CacheEntry>>>,
     config: CacheConfig,
     stats: Arc<RwLock<CacheStatsInternal>>,
+    /// 可选的访问事件通道（默认关闭）
+    event_sender: parking_lot::RwLock<Option<UnboundedSender<CacheEvent>>>,
 }
 
+#[derive(Default)]
 struct CacheStatsInternal {
     hits: u64,
     misses: u64,
+    /// 因条目过期导致的未命中
+    expired_misses: u64,
+    /// 因容量不足被淘汰的条目
+    evictions: u64,
+    /// 最近的重复访问间隔（秒）
+    reaccess_intervals: VecDeque<u64>,
+}
+
+impl CacheStatsInternal {
+    fn record_reaccess(&mut self, interval: Duration) {
+        if self.reaccess_intervals.len() == MAX_REACCESS_SAMPLES {
+            self.reaccess_intervals.pop_front();
+        }
+        self.reaccess_intervals.push_back(interval.as_secs());
+    }
 }
 
 impl QueryCache {
@@ -156,7 +314,34 @@ impl QueryCache {
         Self {
             cache: Arc::new(RwLock::new(cache)),
             config,
-            stats: Arc::new(RwLock::new(CacheStatsInternal { hits: 0, misses: 0 })),
+            stats: Arc::new(RwLock::new(CacheStatsInternal::default())),
+            event_sender: parking_lot::RwLock::new(None),
+        }
+    }
+
+    /// 设置访问事件通道
+    ///
+    /// 设置后每次 `get` 都会发送 `CacheEvent`；传入 `None` 关闭通知。
+    pub fn set_event_sender(&self, sender: Option<UnboundedSender<CacheEvent>>) {
+        *self.event_sender.write() = sender;
+    }
+
+    /// 是否已开启访问事件通知
+    pub fn events_enabled(&self) -> bool {
+        self.event_sender.read().is_some()
+    }
+
+    /// 发送访问事件（接收端已关闭时自动关闭通知）
+    fn notify(&self, key: &CacheKey, hit: bool) {
+        let mut sender = self.event_sender.write();
+        if let Some(tx) = sender.as_ref() {
+            let event = CacheEvent {
+                key_summary: key.summary(),
+                hit,
+            };
+            if tx.send(event).is_err() {
+                *sender = None;
+            }
         }
     }
 
@@ -167,30 +352,50 @@ impl QueryCache {
 
     /// 获取缓存数据
     ///
-    /// 如果缓存命中且未过期，返回 Some(data)；否则返回 None
+    /// 如果缓存命中且未过期，返回 Some(data)；否则返回 None。
+    /// 启用滑动过期时，命中会重置条目的过期计时。
     pub async fn get(&self, key: &CacheKey) -> Option<Vec<HistoryRecord>> {
+        self.get_with_warnings(key).await.map(|(data, _)| data)
+    }
+
+    /// 获取缓存数据及写入时附带的查询提示
+    pub async fn get_with_warnings(
+        &self,
+        key: &CacheKey,
+    ) -> Option<(Vec<HistoryRecord>, Vec<String>)> {
         let mut cache = self.cache.write().await;
 
-        if let Some(entry) = cache.get(key) {
+        if let Some(entry) = cache.get_mut(key) {
+            let interval = entry.record_access();
             if entry.is_expired() {
                 // 过期了，移除并返回 None
                 cache.pop(key);
                 let mut stats = self.stats.write().await;
                 stats.misses += 1;
+                stats.expired_misses += 1;
+                stats.record_reaccess(interval);
                 debug!(target: "industry_vis::cache",
                     "缓存过期 - table={}, tags={:?}",
                     key.table, key.tags
                 );
+                self.notify(key, false);
                 None
             } else {
                 // 命中
+                if self.config.sliding_expiration {
+                    entry.touch();
+                }
                 let mut stats = self.stats.write().await;
                 stats.hits += 1;
+                stats.record_reaccess(interval);
                 debug!(target: "industry_vis::cache",
                     "缓存命中 - table={}, tags={:?}, records={}",
                     key.table, key.tags, entry.data.len()
                 );
-                Some(entry.data.clone())
+                let data = entry.data.clone();
+                let warnings = entry.warnings.clone();
+                self.notify(key, true);
+                Some((data, warnings))
             }
         } else {
             let mut stats = self.stats.write().await;
@@ -199,17 +404,32 @@ impl QueryCache {
                 "缓存未命中 - table={}, tags={:?}",
                 key.table, key.tags
             );
+            self.notify(key, false);
             None
         }
     }
 
     /// 存入缓存
     pub async fn put(&self, key: CacheKey, data: Vec<HistoryRecord>) {
+        self.put_with_warnings(key, data, Vec::new()).await;
+    }
+
+    /// 存入缓存，并附带查询提示（命中时随数据一起返回）
+    pub async fn put_with_warnings(
+        &self,
+        key: CacheKey,
+        data: Vec<HistoryRecord>,
+        warnings: Vec<String>,
+    ) {
         let ttl = Duration::from_secs(self.config.ttl_seconds);
-        let entry = CacheEntry::new(data.clone(), ttl);
+        let entry = CacheEntry::new(data.clone(), ttl, warnings);
 
         let mut cache = self.cache.write().await;
-        cache.put(key.clone(), entry);
+        if let Some((evicted, _)) = cache.push(key.clone(), entry)
+            && evicted != key
+        {
+            self.stats.write().await.evictions += 1;
+        }
 
         debug!(target: "industry_vis::cache",
             "缓存写入 - table={}, tags={:?}, records={}",
@@ -222,9 +442,7 @@ impl QueryCache {
         let mut cache = self.cache.write().await;
         cache.clear();
 
-        let mut stats = self.stats.write().await;
-        stats.hits = 0;
-        stats.misses = 0;
+        *self.stats.write().await = CacheStatsInternal::default();
 
         info!(target: "industry_vis::cache", "缓存已清空");
     }
@@ -241,8 +459,12 @@ impl QueryCache {
             0.0
         };
 
-        // 估算内存使用
-        let estimated_memory_bytes = cache.iter().map(|(_, entry)| entry.data.len() * 100).sum();
+        // 按记录实际内容估算内存使用
+        let estimated_memory_bytes = cache
+            .iter()
+            .flat_map(|(_, entry)| entry.data.iter())
+            .map(HistoryRecord::estimated_size)
+            .sum();
 
         CacheStats {
             hits: stats.hits,
@@ -254,6 +476,57 @@ impl QueryCache {
         }
     }
 
+    /// 根据命中率和重复访问间隔给出缓存配置建议（仅供参考，不修改配置）
+    pub async fn suggest_config(&self) -> CacheConfigSuggestion {
+        let estimated_memory_bytes = self.get_stats().await.estimated_memory_bytes;
+        let stats = self.stats.read().await;
+        CacheConfigSuggestion::from_usage(&CacheUsage {
+            hits: stats.hits,
+            misses: stats.misses,
+            expired_misses: stats.expired_misses,
+            evictions: stats.evictions,
+            max_entries: self.config.max_entries,
+            ttl_seconds: self.config.ttl_seconds,
+            estimated_memory_bytes,
+            reaccess_intervals_secs: stats.reaccess_intervals.iter().copied().collect(),
+        })
+    }
+
+    /// 导出未过期的条目（用于持久化），按最近使用到最久未使用排列
+    pub(crate) async fn export_entries(&self) -> Vec<PersistedEntry> {
+        let cache = self.cache.read().await;
+        cache
+            .iter()
+            .filter(|(_, entry)| !entry.is_expired())
+            .map(|(key, entry)| PersistedEntry {
+                key: key.clone(),
+                data: entry.data.clone(),
+                age_secs: entry.created_at.elapsed().as_secs(),
+                warnings: entry.warnings.clone(),
+            })
+            .collect()
+    }
+
+    /// 导入持久化条目，跳过已过期和键版本不符的条目，返回导入数量
+    pub(crate) async fn import_entries(&self, entries: Vec<PersistedEntry>) -> usize {
+        let ttl = Duration::from_secs(self.config.ttl_seconds);
+        let mut cache = self.cache.write().await;
+        let mut imported = 0;
+        // 逆序写入，保持原有的最近使用顺序
+        for entry in entries.into_iter().rev() {
+            let age = Duration::from_secs(entry.age_secs);
+            if entry.key.schema_version != CACHE_SCHEMA_VERSION || age > ttl {
+                continue;
+            }
+            cache.put(
+                entry.key,
+                CacheEntry::with_age(entry.data, ttl, age, entry.warnings),
+            );
+            imported += 1;
+        }
+        imported
+    }
+
     /// 移除过期条目
     pub async fn evict_expired(&self) {
         let mut cache = self.cache.write().await;
@@ -278,6 +551,33 @@ impl QueryCache {
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_cache_events() {
+        let cache = QueryCache::with_defaults();
+        let key = CacheKey::new("History", "2024-01-01", "2024-01-02", None, None);
+
+        // 默认关闭，不发送事件
+        assert!(!cache.events_enabled());
+        cache.get(&key).await;
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        cache.set_event_sender(Some(tx));
+
+        cache.get(&key).await;
+        let miss = rx.try_recv().unwrap();
+        assert!(!miss.hit);
+        assert_eq!(miss.key_summary, key.summary());
+
+        cache.put(key.clone(), vec![]).await;
+        cache.get(&key).await;
+        assert!(rx.try_recv().unwrap().hit);
+
+        // 关闭后不再发送
+        cache.set_event_sender(None);
+        cache.get(&key).await;
+        assert!(rx.try_recv().is_err());
+    }
+
     #[test]
     fn test_cache_key_creation() {
         let key1 = CacheKey::new(
@@ -300,6 +600,33 @@ mod tests {
         assert_eq!(key1, key2);
     }
 
+    #[test]
+    fn test_cache_key_all_tags() {
+        let all = CacheKey::new("History", "2024-01-01", "2024-01-02", None, None);
+        let empty = CacheKey::new("History", "2024-01-01", "2024-01-02", Some(&[]), None);
+        let subset = CacheKey::new(
+            "History",
+            "2024-01-01",
+            "2024-01-02",
+            Some(&["a".to_string()]),
+            None,
+        );
+        let duplicated = CacheKey::new(
+            "History",
+            "2024-01-01",
+            "2024-01-02",
+            Some(&["a".to_string(), "a".to_string()]),
+            None,
+        );
+
+        // None 与 Some(空) 均表示全部标签
+        assert_eq!(all, empty);
+        assert!(all.tags.is_none());
+        // 全表查询不与标签子集混淆
+        assert_ne!(all, subset);
+        assert_eq!(subset, duplicated);
+    }
+
     #[test]
     fn test_cache_key_different_configs() {
         use crate::models::{OutlierRemovalConfig, ResampleConfig, SmoothingConfig};
@@ -308,24 +635,41 @@ mod tests {
             outlier_removal: OutlierRemovalConfig {
                 enabled: true,
                 method: "3sigma".to_string(),
+                window: None,
+                mode: "drop".to_string(),
             },
             resample: ResampleConfig::default(),
             smoothing: SmoothingConfig::default(),
+            std_band: Default::default(),
+            dedup: Default::default(),
+            range_limit: Default::default(),
+            baseline: None,
         };
 
         let config2 = DataProcessingConfig {
             outlier_removal: OutlierRemovalConfig {
                 enabled: false,
                 method: "3sigma".to_string(),
+                window: None,
+                mode: "drop".to_string(),
             },
             resample: ResampleConfig::default(),
             smoothing: SmoothingConfig::default(),
+            std_band: Default::default(),
+            dedup: Default::default(),
+            range_limit: Default::default(),
+            baseline: None,
         };
 
         let key1 = CacheKey::new("History", "2024-01-01", "2024-01-02", None, Some(&config1));
         let key2 = CacheKey::new("History", "2024-01-01", "2024-01-02", None, Some(&config2));
 
         assert_ne!(key1, key2);
+
+        // 基准值不同的结果不能共用缓存
+        let config3 = config1.clone().with_baseline("first");
+        let key3 = CacheKey::new("History", "2024-01-01", "2024-01-02", None, Some(&config3));
+        assert_ne!(key1, key3);
     }
 
     #[tokio::test]
@@ -379,6 +723,62 @@ mod tests {
         assert_eq!(stats.hit_rate, 50.0);
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn test_suggest_config_counts_expired_and_evicted() {
+        // TTL 为 0：写入后的访问总是过期；容量 1：写入第二个键会淘汰第一个
+        let cache = QueryCache::new(CacheConfig::new(1, 0));
+        let key = CacheKey::new("History", "2024-01-01", "2024-01-02", None, None);
+        let other = CacheKey::new("History", "2024-01-02", "2024-01-03", None, None);
+
+        for _ in 0..20 {
+            cache.put(key.clone(), vec![]).await;
+            tokio::time::advance(Duration::from_millis(1)).await;
+            assert!(cache.get(&key).await.is_none());
+        }
+        cache.put(key.clone(), vec![]).await;
+        cache.put(other.clone(), vec![]).await;
+
+        let stats = cache.stats.read().await;
+        assert_eq!(stats.expired_misses, 20);
+        assert_eq!(stats.evictions, 1);
+        assert_eq!(stats.reaccess_intervals.len(), 20);
+        drop(stats);
+
+        let suggestion = cache.suggest_config().await;
+        assert_eq!(suggestion.samples, 20);
+        assert!(suggestion.suggested_ttl_seconds > 0);
+    }
+
+    #[tokio::test]
+    async fn test_cache_memory_estimate_uses_content() {
+        let record = |tag: &str, quality: &str| {
+            HistoryRecord::new(
+                "2024-01-01T00:00:00.000".to_string(),
+                tag.to_string(),
+                1.0,
+                quality.to_string(),
+            )
+        };
+
+        let short_cache = QueryCache::with_defaults();
+        let key = CacheKey::new("History", "2024-01-01", "2024-01-02", None, None);
+        short_cache
+            .put(key.clone(), vec![record("T1", "G"); 10])
+            .await;
+
+        let long_cache = QueryCache::with_defaults();
+        let long_tag = "Plant1.Unit2.Boiler.Drum.Level.Transmitter.PV".repeat(4);
+        let long_quality = "Good, Non-Specific, Not Limited".repeat(4);
+        long_cache
+            .put(key, vec![record(&long_tag, &long_quality); 10])
+            .await;
+
+        let short = short_cache.get_stats().await.estimated_memory_bytes;
+        let long = long_cache.get_stats().await.estimated_memory_bytes;
+        assert_eq!(short, 10 * record("T1", "G").estimated_size());
+        assert!(long > short * 3, "long={} short={}", long, short);
+    }
+
     #[tokio::test]
     async fn test_cache_lru_eviction() {
         let config = CacheConfig::new(3, 300);
@@ -405,7 +805,7 @@ mod tests {
         assert!(result.is_none(), "第一个条目应该被 LRU 淘汰");
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn test_cache_ttl_expiration() {
         let config = CacheConfig::new(10, 1); // 1 秒过期
         let cache = QueryCache::new(config);
@@ -428,10 +828,40 @@ mod tests {
         assert!(result.is_some(), "立即获取应该命中缓存");
 
         // 等待 TTL 过期
-        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        tokio::time::advance(Duration::from_secs(2)).await;
 
         // 过期后应该未命中
         let result = cache.get(&key).await;
         assert!(result.is_none(), "TTL 过期后应该未命中缓存");
     }
+
+    /// 每 600ms 访问一次，累计超过 1 秒 TTL，返回最后一次是否命中
+    async fn access_repeatedly(config: CacheConfig) -> bool {
+        let cache = QueryCache::new(config);
+        let key = CacheKey::new("History", "2024-01-01", "2024-01-02", None, None);
+        cache.put(key.clone(), vec![]).await;
+
+        let mut hit = true;
+        for _ in 0..3 {
+            tokio::time::advance(Duration::from_millis(600)).await;
+            hit = cache.get(&key).await.is_some();
+        }
+        hit
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cache_sliding_expiration_keeps_hot_entry() {
+        let config = CacheConfig::new(10, 1).with_sliding_expiration(true);
+        assert!(
+            access_repeatedly(config).await,
+            "滑动过期模式下持续访问的条目不应过期"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cache_absolute_expiration_ignores_access() {
+        let config = CacheConfig::new(10, 1);
+        assert!(!config.sliding_expiration);
+        assert!(!access_repeatedly(config).await, "绝对过期模式下应到点过期");
+    }
 }
@@ -0,0 +1,278 @@
+//! 缓存配置调优建议
+//!
+//! 根据命中率、过期/淘汰原因和同一查询的重复间隔，给出 `max_entries` 与
+//! `ttl_seconds` 的建议值。仅供参考，不会自动修改配置。
+
+use serde::Serialize;
+
+use crate::config::CachePerformanceConfig;
+
+/// 给出建议所需的最少访问次数
+const MIN_SAMPLES: u64 = 20;
+/// 命中率低于该值（百分比）时考虑放宽缓存
+const LOW_HIT_RATE: f64 = 50.0;
+/// 缓存内存预算（字节），估计占用低于一半视为内存有余
+pub const CACHE_MEMORY_BUDGET_BYTES: usize = 512 * 1024 * 1024;
+/// 估计占用超过预算该比例时建议缩小缓存
+const HIGH_MEMORY_RATIO: f64 = 0.8;
+/// 建议 TTL 范围（秒），不超出 `CachePerformanceConfig::validate` 的允许范围
+const MIN_SUGGESTED_TTL_SECS: u64 = CachePerformanceConfig::MIN_TTL_SECONDS;
+const MAX_SUGGESTED_TTL_SECS: u64 = CachePerformanceConfig::MAX_TTL_SECONDS;
+/// 建议条目数范围
+const MIN_SUGGESTED_ENTRIES: usize = 20;
+const MAX_SUGGESTED_ENTRIES: usize = CachePerformanceConfig::MAX_ENTRIES;
+
+/// 缓存使用统计快照
+#[derive(Debug, Clone, Default)]
+pub struct CacheUsage {
+    pub hits: u64,
+    pub misses: u64,
+    /// 因条目过期导致的未命中次数
+    pub expired_misses: u64,
+    /// 因容量不足被 LRU 淘汰的条目数
+    pub evictions: u64,
+    pub max_entries: usize,
+    pub ttl_seconds: u64,
+    pub estimated_memory_bytes: usize,
+    /// 同一查询两次访问之间的间隔（秒）
+    pub reaccess_intervals_secs: Vec<u64>,
+}
+
+impl CacheUsage {
+    /// 命中率（百分比）
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64 * 100.0
+        }
+    }
+
+    /// 重复访问间隔的 P90（秒），无样本时返回 None
+    fn reaccess_p90(&self) -> Option<u64> {
+        let mut intervals = self.reaccess_intervals_secs.clone();
+        if intervals.is_empty() {
+            return None;
+        }
+        intervals.sort_unstable();
+        let index = (intervals.len() * 9).div_ceil(10).saturating_sub(1);
+        Some(intervals[index])
+    }
+}
+
+/// 缓存配置建议
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheConfigSuggestion {
+    /// 当前最大条目数
+    pub current_max_entries: usize,
+    /// 建议最大条目数
+    pub suggested_max_entries: usize,
+    /// 当前过期时间（秒）
+    pub current_ttl_seconds: u64,
+    /// 建议过期时间（秒）
+    pub suggested_ttl_seconds: u64,
+    /// 命中率（百分比）
+    pub hit_rate: f64,
+    /// 估计内存使用（字节）
+    pub estimated_memory_bytes: usize,
+    /// 统计样本数（访问次数）
+    pub samples: u64,
+    /// 建议原因
+    pub reason: String,
+}
+
+impl CacheConfigSuggestion {
+    /// 根据使用统计计算建议值
+    ///
+    /// - 内存占用接近预算：缩小条目数；
+    /// - 命中率低且内存有余：过期导致的未命中为主时延长 TTL（覆盖重复访问间隔的 P90），
+    ///   容量淘汰为主时增大条目数；
+    /// - 其他情况保持当前配置。
+    pub fn from_usage(usage: &CacheUsage) -> Self {
+        let current_entries = usage.max_entries;
+        let current_ttl = usage.ttl_seconds;
+        let samples = usage.hits + usage.misses;
+        let hit_rate = usage.hit_rate();
+        let memory_ratio = usage.estimated_memory_bytes as f64 / CACHE_MEMORY_BUDGET_BYTES as f64;
+
+        let (entries, ttl, reason) = if samples < MIN_SAMPLES {
+            (
+                current_entries,
+                current_ttl,
+                format!("访问次数不足 {}，保持当前配置", MIN_SAMPLES),
+            )
+        } else if memory_ratio > HIGH_MEMORY_RATIO {
+            (
+                (current_entries * 3 / 4).max(MIN_SUGGESTED_ENTRIES),
+                current_ttl,
+                format!(
+                    "估计内存占用已达预算的 {:.0}%，建议减少缓存条目",
+                    memory_ratio * 100.0
+                ),
+            )
+        } else if hit_rate < LOW_HIT_RATE && memory_ratio < 0.5 {
+            if usage.expired_misses > 0 && usage.expired_misses >= usage.evictions {
+                let target = usage
+                    .reaccess_p90()
+                    .unwrap_or(0)
+                    .max(current_ttl.saturating_mul(2))
+                    .max(MIN_SUGGESTED_TTL_SECS);
+                (
+                    current_entries,
+                    target.clamp(
+                        current_ttl.min(MAX_SUGGESTED_TTL_SECS),
+                        MAX_SUGGESTED_TTL_SECS,
+                    ),
+                    format!(
+                        "命中率 {:.1}%，{} 次未命中因条目过期且内存有余，建议延长 TTL",
+                        hit_rate, usage.expired_misses
+                    ),
+                )
+            } else if usage.evictions > 0 {
+                (
+                    (current_entries * 2).clamp(MIN_SUGGESTED_ENTRIES, MAX_SUGGESTED_ENTRIES),
+                    current_ttl,
+                    format!(
+                        "命中率 {:.1}%，{} 个条目因容量不足被淘汰且内存有余，建议增大条目数",
+                        hit_rate, usage.evictions
+                    ),
+                )
+            } else {
+                (
+                    current_entries,
+                    current_ttl,
+                    format!(
+                        "命中率 {:.1}%，查询很少重复，调整缓存配置无明显收益",
+                        hit_rate
+                    ),
+                )
+            }
+        } else {
+            (
+                current_entries,
+                current_ttl,
+                format!("命中率 {:.1}%，保持当前配置", hit_rate),
+            )
+        };
+
+        Self {
+            current_max_entries: current_entries,
+            suggested_max_entries: entries.clamp(
+                CachePerformanceConfig::MIN_ENTRIES,
+                CachePerformanceConfig::MAX_ENTRIES,
+            ),
+            current_ttl_seconds: current_ttl,
+            suggested_ttl_seconds: ttl.clamp(MIN_SUGGESTED_TTL_SECS, MAX_SUGGESTED_TTL_SECS),
+            hit_rate,
+            estimated_memory_bytes: usage.estimated_memory_bytes,
+            samples,
+            reason,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(hits: u64, misses: u64) -> CacheUsage {
+        CacheUsage {
+            hits,
+            misses,
+            max_entries: 200,
+            ttl_seconds: 1800,
+            estimated_memory_bytes: 10 * 1024 * 1024,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_low_hit_rate_with_spare_memory_suggests_longer_ttl() {
+        // 同一查询约每小时重复一次，30 分钟 TTL 总是过期
+        let usage = CacheUsage {
+            expired_misses: 60,
+            reaccess_intervals_secs: vec![3000, 3600, 3600, 3900, 4000],
+            ..usage(20, 80)
+        };
+
+        let suggestion = CacheConfigSuggestion::from_usage(&usage);
+        assert!(suggestion.suggested_ttl_seconds > 1800);
+        assert!(suggestion.suggested_ttl_seconds >= 4000);
+        assert_eq!(suggestion.suggested_max_entries, 200);
+        assert_eq!(suggestion.hit_rate, 20.0);
+    }
+
+    #[test]
+    fn test_evictions_suggest_more_entries() {
+        let usage = CacheUsage {
+            evictions: 50,
+            ..usage(10, 90)
+        };
+        let suggestion = CacheConfigSuggestion::from_usage(&usage);
+        assert_eq!(suggestion.suggested_max_entries, 400);
+        assert_eq!(suggestion.suggested_ttl_seconds, 1800);
+    }
+
+    #[test]
+    fn test_high_memory_or_few_samples_never_grows() {
+        let crowded = CacheUsage {
+            expired_misses: 60,
+            estimated_memory_bytes: CACHE_MEMORY_BUDGET_BYTES,
+            ..usage(20, 80)
+        };
+        let suggestion = CacheConfigSuggestion::from_usage(&crowded);
+        assert_eq!(suggestion.suggested_max_entries, 150);
+        assert_eq!(suggestion.suggested_ttl_seconds, 1800);
+
+        let fresh = CacheConfigSuggestion::from_usage(&usage(1, 5));
+        assert_eq!(fresh.suggested_max_entries, 200);
+        assert_eq!(fresh.suggested_ttl_seconds, 1800);
+    }
+
+    #[test]
+    fn test_every_suggestion_passes_validate() {
+        let entry_options = [10, 200, 800, 1000];
+        let ttl_options = [60, 1800, 5000, 7200];
+        let intervals = vec![3600, 20_000, 90_000];
+        for &max_entries in &entry_options {
+            for &ttl_seconds in &ttl_options {
+                let variants = [
+                    CacheUsage {
+                        expired_misses: 80,
+                        reaccess_intervals_secs: intervals.clone(),
+                        ..usage(10, 90)
+                    },
+                    CacheUsage {
+                        evictions: 80,
+                        ..usage(10, 90)
+                    },
+                    CacheUsage {
+                        estimated_memory_bytes: CACHE_MEMORY_BUDGET_BYTES,
+                        ..usage(10, 90)
+                    },
+                    usage(90, 10),
+                    usage(1, 1),
+                ];
+                for variant in variants {
+                    let suggestion = CacheConfigSuggestion::from_usage(&CacheUsage {
+                        max_entries,
+                        ttl_seconds,
+                        ..variant
+                    });
+                    let config = CachePerformanceConfig {
+                        max_entries: suggestion.suggested_max_entries,
+                        ttl_seconds: suggestion.suggested_ttl_seconds,
+                        ..Default::default()
+                    };
+                    assert!(
+                        config.validate().is_ok(),
+                        "建议值 {:?} 未通过校验",
+                        suggestion
+                    );
+                }
+            }
+        }
+    }
+}
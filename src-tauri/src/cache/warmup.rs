@@ -160,7 +160,8 @@ impl CacheWarmer {
 
     /// 执行预热任务
     ///
-    /// `data_fetcher` 是一个异步函数，用于获取数据
+    /// `data_fetcher` 是一个异步函数，返回原始记录和是否按行数上限截断；
+    /// 截断的结果不写入缓存，避免后续查询命中不完整的数据。
     pub async fn warmup<F, Fut>(
         &self,
         tasks: Vec<WarmupTask>,
@@ -168,7 +169,7 @@ impl CacheWarmer {
     ) -> AppResult<WarmupProgress>
     where
         F: Fn(WarmupTask) -> Fut,
-        Fut: std::future::Future<Output = AppResult<Vec<HistoryRecord>>>,
+        Fut: std::future::Future<Output = AppResult<(Vec<HistoryRecord>, bool)>>,
     {
         let total = tasks.len();
         let mut progress = WarmupProgress::new(total);
@@ -191,7 +192,12 @@ impl CacheWarmer {
 
             // 执行数据获取
             match data_fetcher(task).await {
-                Ok(records) => {
+                Ok((_, true)) => {
+                    warn!(target: "industry_vis::cache::warmup",
+                        "预热结果超过行数上限，不写入缓存: {}", description);
+                    progress.update(&description, false);
+                }
+                Ok((records, false)) => {
                     self.cache.put(cache_key, records).await;
                     debug!(target: "industry_vis::cache::warmup",
                         "预热成功: {}", description);
@@ -387,4 +393,27 @@ mod tests {
         assert_eq!(tasks.len(), 2);
         assert_eq!(strategy.name(), "FixedTimeRange");
     }
+
+    #[tokio::test]
+    async fn test_warmup_skips_truncated_result() {
+        let cache = Arc::new(QueryCache::with_defaults());
+        let task = |start: &str, end: &str, description: &str| {
+            WarmupTask::new("历史表", start, end, None, description)
+        };
+        let complete = task("2024-01-01T00:00:00", "2024-01-02T00:00:00", "完整");
+        let truncated = task("2024-01-02T00:00:00", "2024-01-03T00:00:00", "截断");
+        let (complete_key, truncated_key) = (complete.to_cache_key(), truncated.to_cache_key());
+
+        let progress = CacheWarmer::new(Arc::clone(&cache))
+            .warmup(vec![complete, truncated], |task| async move {
+                Ok((Vec::new(), task.description == "截断"))
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(progress.success_count, 1);
+        assert_eq!(progress.failure_count, 1);
+        assert!(cache.get(&complete_key).await.is_some());
+        assert!(cache.get(&truncated_key).await.is_none());
+    }
 }
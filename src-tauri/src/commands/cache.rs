@@ -1,40 +1,81 @@
 //! 缓存管理命令
 
-use std::sync::Arc;
-use tauri::State;
-use tokio::sync::RwLock;
-use tracing::{debug, info};
+use tauri::Emitter;
+use tracing::{debug, info, warn};
 
-use crate::cache::CacheStats;
+use crate::cache::{CacheConfigSuggestion, CacheEvent, CacheStats};
 use crate::error::AppResult;
-use crate::state::AppState;
+use crate::processing;
+use crate::state::WindowState;
 
-/// 清空查询缓存
+/// 清空查询缓存（同时清空处理管道中间结果）
 #[tauri::command]
-pub async fn clear_cache(state: State<'_, Arc<RwLock<AppState>>>) -> AppResult<()> {
+pub async fn clear_cache(state: WindowState) -> AppResult<()> {
     info!(target: "industry_vis::commands", "清空查询缓存");
     let state = state.read().await;
     state.cache().evict_expired().await;
     state.cache().clear().await;
+    processing::PipelineCache::global().clear();
+    state.group_audit_cache().clear();
     Ok(())
 }
 
 /// 获取缓存统计信息
 #[tauri::command]
-pub async fn get_cache_stats(state: State<'_, Arc<RwLock<AppState>>>) -> AppResult<CacheStats> {
+pub async fn get_cache_stats(state: WindowState) -> AppResult<CacheStats> {
     debug!(target: "industry_vis::commands", "获取缓存统计");
     let state = state.read().await;
     Ok(state.cache().get_stats().await)
 }
 
+/// 根据命中率和查询重复间隔给出缓存配置建议
+///
+/// 仅供参考，不会修改当前配置；采纳时修改性能配置中的 `max_entries` / `ttl_seconds`。
+#[tauri::command]
+pub async fn suggest_cache_config(state: WindowState) -> AppResult<CacheConfigSuggestion> {
+    debug!(target: "industry_vis::commands", "获取缓存配置建议");
+    let state = state.read().await;
+    Ok(state.cache().suggest_config().await)
+}
+
+/// 开启/关闭缓存访问事件通知（调试用，默认关闭）
+///
+/// 开启后每次缓存查询都会向前端发送 `cache-access` 事件（`CacheEvent`）。
+#[tauri::command]
+pub async fn set_cache_events(state: WindowState, enabled: bool) -> AppResult<()> {
+    info!(target: "industry_vis::commands", "缓存访问事件通知: {}", enabled);
+    let state = state.read().await;
+
+    if !enabled {
+        state.cache().set_event_sender(None);
+        return Ok(());
+    }
+    if state.cache().events_enabled() {
+        return Ok(());
+    }
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<CacheEvent>();
+    state.cache().set_event_sender(Some(tx));
+
+    // 通道关闭（通知被关闭）时任务自动结束
+    tokio::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            if let Some(handle) = crate::get_app_handle()
+                && let Err(e) = handle.emit("cache-access", event)
+            {
+                warn!(target: "industry_vis::commands", "发送缓存访问事件失败: {}", e);
+            }
+        }
+    });
+
+    Ok(())
+}
+
 /// 预热指定分组的缓存（1天数据）
 ///
 /// 异步执行，不阻塞前端。用于进入分组时提前加载数据。
 #[tauri::command]
-pub async fn warmup_group(
-    state: State<'_, Arc<RwLock<AppState>>>,
-    group_id: String,
-) -> AppResult<()> {
+pub async fn warmup_group(state: WindowState, group_id: String) -> AppResult<()> {
     debug!(target: "industry_vis::commands", "预热分组缓存: {}", group_id);
     let state = state.read().await;
     state.warmup_group(&group_id).await
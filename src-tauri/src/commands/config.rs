@@ -1,19 +1,18 @@
 //! 配置相关命令
 
-use std::sync::Arc;
+use std::time::Duration;
 use tauri::State;
-use tokio::sync::RwLock;
 use tracing::{error, info};
 
 use crate::config::{AppConfig, DatabaseConfig};
-use crate::datasource::{ConnectionPool, PoolConfig};
-use crate::error::AppResult;
+use crate::datasource::{ConnectionPool, PROBE_TIMEOUT_SECS};
+use crate::error::{AppResult, ErrorInfo};
 use crate::models::ConnectionTestResult;
-use crate::state::AppState;
+use crate::state::{AppStateManager, WindowState};
 
 /// 加载配置
 #[tauri::command]
-pub async fn load_config(state: State<'_, Arc<RwLock<AppState>>>) -> AppResult<AppConfig> {
+pub async fn load_config(state: WindowState) -> AppResult<AppConfig> {
     info!(target: "industry_vis::commands", "加载配置");
     let state = state.read().await;
     Ok(state.config().app_config())
@@ -21,10 +20,7 @@ pub async fn load_config(state: State<'_, Arc<RwLock<AppState>>>) -> AppResult<A
 
 /// 保存配置
 #[tauri::command]
-pub async fn save_config(
-    config: AppConfig,
-    state: State<'_, Arc<RwLock<AppState>>>,
-) -> AppResult<()> {
+pub async fn save_config(config: AppConfig, state: WindowState) -> AppResult<()> {
     info!(target: "industry_vis::commands",
         "保存配置 - 服务器: {}:{}",
         config.database.server, config.database.port
@@ -41,23 +37,14 @@ pub async fn test_connection(config: DatabaseConfig) -> AppResult<ConnectionTest
         config.server, config.port, config.database
     );
 
-    // 创建临时连接池测试连接
-    match ConnectionPool::new(config, PoolConfig::for_desktop()).await {
-        Ok(pool) => {
-            // 尝试获取连接
-            match pool.get().await {
-                Ok(_) => {
-                    info!(target: "industry_vis::commands", "连接测试成功");
-                    Ok(ConnectionTestResult::success())
-                }
-                Err(e) => {
-                    error!(target: "industry_vis::commands", "连接测试失败: {}", e);
-                    Ok(ConnectionTestResult::failure(e.to_string()))
-                }
-            }
+    // 使用独立的短超时探测，避免地址不可达时界面长时间无响应
+    match ConnectionPool::probe(config, Duration::from_secs(PROBE_TIMEOUT_SECS)).await {
+        Ok(()) => {
+            info!(target: "industry_vis::commands", "连接测试成功");
+            Ok(ConnectionTestResult::success())
         }
         Err(e) => {
-            error!(target: "industry_vis::commands", "创建连接池失败: {}", e);
+            error!(target: "industry_vis::commands", "连接测试失败: {}", e);
             Ok(ConnectionTestResult::failure(e.to_string()))
         }
     }
@@ -65,16 +52,86 @@ pub async fn test_connection(config: DatabaseConfig) -> AppResult<ConnectionTest
 
 /// 获取当前连接状态（是否已初始化连接池）
 #[tauri::command]
-pub async fn get_connection_status(state: State<'_, Arc<RwLock<AppState>>>) -> AppResult<bool> {
+pub async fn get_connection_status(state: WindowState) -> AppResult<bool> {
     let state = state.read().await;
     Ok(state.is_pool_initialized())
 }
 
 /// 获取连接池状态
 #[tauri::command]
-pub async fn get_pool_state(
-    state: State<'_, Arc<RwLock<AppState>>>,
-) -> AppResult<Option<crate::datasource::PoolState>> {
+pub async fn get_pool_state(state: WindowState) -> AppResult<Option<crate::datasource::PoolState>> {
     let state = state.read().await;
     Ok(state.get_pool_state())
 }
+
+/// 获取数据库连接断路器状态
+#[tauri::command]
+pub async fn get_circuit_breaker_status(
+    state: WindowState,
+) -> AppResult<crate::services::CircuitBreakerStatus> {
+    let state = state.read().await;
+    Ok(state.circuit_breaker_status())
+}
+
+/// 获取数据库连通性状态（状态变化时另有 `connection-health` 事件推送）
+#[tauri::command]
+pub async fn get_connection_health(
+    state: WindowState,
+) -> AppResult<crate::services::ConnectionHealthStatus> {
+    let state = state.read().await;
+    Ok(state.connection_health())
+}
+
+/// 以 Prometheus exposition 文本格式导出运行指标
+///
+/// 包括连接池状态、缓存命中、数据库查询计数、断路器和连通性状态，供企业监控体系采集。
+#[tauri::command]
+pub async fn export_metrics(state: WindowState) -> AppResult<String> {
+    let state = state.read().await;
+    Ok(crate::services::render_prometheus(
+        &state.metrics_snapshot().await,
+    ))
+}
+
+/// 获取连接池大小建议（基于最近的排队等待情况）
+#[tauri::command]
+pub async fn suggest_pool_size(
+    state: WindowState,
+) -> AppResult<Option<crate::datasource::PoolSizeSuggestion>> {
+    let state = state.read().await;
+    Ok(state.suggest_pool_size())
+}
+
+/// 获取最近一次连接池初始化失败的原因（带错误分类代码）
+#[tauri::command]
+pub async fn get_last_pool_error(state: WindowState) -> AppResult<Option<ErrorInfo>> {
+    let state = state.read().await;
+    Ok(state.last_pool_error())
+}
+
+/// 为调用窗口创建独立的应用状态（连接 `config` 指定的数据库，配置不写盘）
+///
+/// 之后该窗口发起的命令路由到独立实例，返回连接池是否初始化成功。
+#[tauri::command]
+pub async fn open_window_state(
+    webview: tauri::Webview,
+    manager: State<'_, AppStateManager>,
+    config: AppConfig,
+) -> AppResult<bool> {
+    info!(target: "industry_vis::commands",
+        "窗口 {} 创建独立状态 - 服务器: {}:{}, 数据库: {}",
+        webview.label(), config.database.server, config.database.port, config.database.database
+    );
+    let state = manager.open(webview.label(), config).await?;
+    let initialized = state.read().await.is_pool_initialized();
+    Ok(initialized)
+}
+
+/// 释放调用窗口的独立应用状态，之后该窗口使用主实例
+#[tauri::command]
+pub async fn close_window_state(
+    webview: tauri::Webview,
+    manager: State<'_, AppStateManager>,
+) -> AppResult<bool> {
+    Ok(manager.remove(webview.label()).is_some())
+}
@@ -1,19 +1,25 @@
 //! 数据查询命令
 
-use std::fs::File;
-use std::io::Write;
 use std::sync::Arc;
-use tauri::State;
-use tokio::sync::RwLock;
-use tracing::info;
+use std::time::Duration;
+use tauri::Emitter;
+use tauri::ipc::Response;
+use tracing::{debug, info, warn};
 
-use crate::error::AppResult;
-use crate::models::{DataProcessingConfig, HistoryRecord, QueryParams, QueryResult, QueryResultV2};
-use crate::state::AppState;
+use crate::datasource::TagTableCandidate;
+use crate::error::{AppError, AppResult};
+use crate::export::{self, CsvExportRequest, ExportHistory, ExportHistoryItem, ScheduledExport};
+use crate::models::{
+    self, ChartSeriesData, DataProcessingConfig, EventBatch, HistoryRecord, QueryEstimate,
+    QueryParams, QueryResult, QueryResultV2, SamplingInterval, TagTreeNode,
+};
+use crate::processing;
+use crate::services::{ExportTaskInfo, REALTIME_EVENT, ScheduledExportInfo, SeriesChunk};
+use crate::state::{AppState, WindowState};
 
 /// 获取可用标签列表
 #[tauri::command]
-pub async fn get_available_tags(state: State<'_, Arc<RwLock<AppState>>>) -> AppResult<Vec<String>> {
+pub async fn get_available_tags(state: WindowState) -> AppResult<Vec<String>> {
     info!(target: "industry_vis::commands", "获取可用标签列表");
     let state = state.read().await;
     match state.query_service() {
@@ -25,12 +31,62 @@ pub async fn get_available_tags(state: State<'_, Arc<RwLock<AppState>>>) -> AppR
     }
 }
 
+/// 自动发现标签表
+///
+/// 返回包含标签列的候选表（按可能性降序），前端选择后用 `save_tag_table` 保存到 `schema.tag_table`。
+#[tauri::command]
+pub async fn discover_tag_table(state: WindowState) -> AppResult<Vec<TagTableCandidate>> {
+    info!(target: "industry_vis::commands", "自动发现标签表");
+    let state = state.read().await;
+    match state.query_service() {
+        Some(service) => service.discover_tag_table().await,
+        None => Err(crate::error::AppError::DatabaseNotConnected),
+    }
+}
+
+/// 保存自动发现选中的标签表到 `schema.tag_table`（`schema.name` 形式）
+///
+/// 保存后新的查询立即使用该表搜索标签。
+#[tauri::command]
+pub async fn save_tag_table(candidate: TagTableCandidate, state: WindowState) -> AppResult<()> {
+    let tag_table = candidate.config_name();
+    info!(target: "industry_vis::commands", "保存标签表: {}", tag_table);
+    let state = state.read().await;
+    let mut config = state.config().app_config();
+    config.schema.tag_table = Some(tag_table);
+    state.config().update_app_config(config)
+}
+
+/// 获取标签层级树
+///
+/// 基于全部可用标签按 `separator`（如 `"."`）拆分构建，不含分隔符的标签作为根节点叶子。
+#[tauri::command]
+pub async fn get_tag_tree(separator: String, state: WindowState) -> AppResult<TagTreeNode> {
+    info!(target: "industry_vis::commands", "获取标签层级树 - 分隔符: {:?}", separator);
+    if separator.is_empty() {
+        return Err(AppError::Validation("分隔符不能为空".to_string()));
+    }
+
+    let state = state.read().await;
+    let tags = match state.query_service() {
+        Some(service) => service.get_available_tags().await?,
+        None => {
+            info!(target: "industry_vis::commands", "数据库未连接，返回空标签树");
+            Vec::new()
+        }
+    };
+
+    Ok(TagTreeNode::build(&tags, &separator))
+}
+
 /// 模糊搜索标签
+///
+/// 关键词按空格分隔且需全部包含，`-` 前缀的词为排除词（如 `Temp -Setpoint`）。
 #[tauri::command]
 pub async fn search_tags(
     keyword: String,
     limit: Option<u32>,
-    state: State<'_, Arc<RwLock<AppState>>>,
+    state: WindowState,
 ) -> AppResult<Vec<String>> {
     info!(target: "industry_vis::commands", "搜索标签 - 关键词: {}", keyword);
     let limit = limit.unwrap_or(50) as usize;
@@ -44,13 +100,25 @@ pub async fn search_tags(
     }
 }
 
+/// 记录分组访问（统计失败不影响查询）
+fn record_group_access(state: &AppState, group_id: Option<&str>) {
+    if let Some(id) = group_id
+        && let Err(e) = state.tag_group_service().record_access(id)
+    {
+        warn!(target: "industry_vis::commands", "记录分组访问失败 - ID: {}, 错误: {}", id, e);
+    }
+}
+
 /// 查询历史数据
+///
+/// `group_id` 为发起查询的分组（可选），用于统计分组使用情况。
 #[tauri::command]
 pub async fn query_history(
     params: QueryParams,
     processing_config: Option<DataProcessingConfig>,
     force_refresh: Option<bool>,
-    state: State<'_, Arc<RwLock<AppState>>>,
+    group_id: Option<String>,
+    state: WindowState,
 ) -> AppResult<QueryResult> {
     let tag_count = params.tags.as_ref().map(|t| t.len()).unwrap_or(0);
     let force_refresh = force_refresh.unwrap_or(false);
@@ -61,6 +129,7 @@ pub async fn query_history(
     );
 
     let state = state.read().await;
+    record_group_access(&state, group_id.as_deref());
     match state.query_service() {
         Some(service) => {
             service
@@ -75,12 +144,16 @@ pub async fn query_history(
 }
 
 /// 查询历史数据 V2 (预分组格式)
+///
+/// `group_id` 为发起查询的分组（可选），用于统计分组使用情况，
+/// 并在结果中附带该分组与查询范围重叠的标注区间，以及配置了值映射的标签的文本标签。
 #[tauri::command]
 pub async fn query_history_v2(
     params: QueryParams,
     processing_config: Option<DataProcessingConfig>,
     force_refresh: Option<bool>,
-    state: State<'_, Arc<RwLock<AppState>>>,
+    group_id: Option<String>,
+    state: WindowState,
 ) -> AppResult<QueryResultV2> {
     let tag_count = params.tags.as_ref().map(|t| t.len()).unwrap_or(0);
     let force_refresh = force_refresh.unwrap_or(false);
@@ -91,44 +164,645 @@ pub async fn query_history_v2(
     );
 
     let state = state.read().await;
-    match state.query_service() {
+    record_group_access(&state, group_id.as_deref());
+    let mut result = match state.query_service() {
         Some(service) => {
             service
                 .query_history_v2(&params, processing_config.as_ref(), force_refresh)
-                .await
+                .await?
         }
         None => {
             info!(target: "industry_vis::commands", "数据库未连接，无法查询历史数据");
-            Err(crate::error::AppError::DatabaseNotConnected)
+            return Err(crate::error::AppError::DatabaseNotConnected);
+        }
+    };
+
+    // 附带分组中与查询范围重叠的标注和单位提示
+    if let Some(group) = group_id
+        .as_deref()
+        .and_then(|id| state.tag_group_service().get_group(id))
+    {
+        result.annotations = group.annotations_in_range(&params.start_time, &params.end_time);
+        // 离散状态标签附带值映射文本
+        for series in &mut result.series {
+            if let Some(value_map) = group.value_map(&series.tag_name) {
+                series.apply_value_map(value_map);
+            }
         }
+        // 同一图表同一 Y 轴的标签单位不一致时提示
+        result
+            .warnings
+            .extend(group.unit_warnings(params.tags.as_deref()));
+    }
+
+    // 设置分片大小时，大结果暂存在服务端分片返回
+    if let Some(chunk_size) = params.chunk_size {
+        result = state
+            .chunk_store()
+            .chunk_result(result, chunk_size, params.columnar);
+    }
+    if params.columnar {
+        result
+            .series
+            .iter_mut()
+            .for_each(ChartSeriesData::to_columnar);
     }
+
+    Ok(result)
 }
 
-/// 导出数据到 CSV
+/// 拉取 V2 查询结果的一个分片
+///
+/// `query_token` 和分片数由设置了 `chunk_size` 的 `query_history_v2` 返回，
+/// 前端按序拉取全部分片后按标签拼接数据点。
+#[tauri::command]
+pub async fn fetch_series_chunk(
+    query_token: String,
+    chunk_index: usize,
+    state: WindowState,
+) -> AppResult<SeriesChunk> {
+    debug!(target: "industry_vis::commands", "拉取结果分片 - {} #{}", query_token, chunk_index);
+    let state = state.read().await;
+    state.chunk_store().fetch_chunk(&query_token, chunk_index)
+}
+
+/// 查询历史数据 V2（二进制编码返回）
+///
+/// 与 `query_history_v2` 参数相同，结果按 `export::encode_series_binary` 的布局
+/// 编码为字节返回，前端以 `ArrayBuffer` 接收并用 `Float64Array` 解码数据点，
+/// 避免大结果集的 JSON 序列化与解析开销。
 #[tauri::command]
-pub async fn export_to_csv(records: Vec<HistoryRecord>, file_path: String) -> AppResult<()> {
+pub async fn query_history_v2_compressed(
+    params: QueryParams,
+    processing_config: Option<DataProcessingConfig>,
+    force_refresh: Option<bool>,
+    group_id: Option<String>,
+    state: WindowState,
+) -> AppResult<Response> {
+    // 二进制编码一次性返回，不分片，按行式数据点编码
+    let params = QueryParams {
+        chunk_size: None,
+        columnar: false,
+        ..params
+    };
+    let result =
+        query_history_v2(params, processing_config, force_refresh, group_id, state).await?;
+    let bytes = export::encode_series_binary(&result)?;
+    debug!(target: "industry_vis::commands",
+        "V2 结果二进制编码 - 系列数: {}, 字节数: {}",
+        result.series.len(), bytes.len()
+    );
+    Ok(Response::new(bytes))
+}
+
+/// 获取单个标签在某时刻的值
+///
+/// 取该时刻前后最近的采样点线性插值；缺少前点或后点时返回 `None`。
+#[tauri::command]
+pub async fn get_value_at(tag: String, time: String, state: WindowState) -> AppResult<Option<f64>> {
+    debug!(target: "industry_vis::commands", "时刻取值 - {} @ {}", tag, time);
+    let state = state.read().await;
+    match state.query_service() {
+        Some(service) => service.get_value_at(&tag, &time).await,
+        None => Err(AppError::DatabaseNotConnected),
+    }
+}
+
+/// 订阅标签实时数据（后端轮询）
+///
+/// 后端每隔 `interval_secs` 秒查询一次最新数据，有新数据点时通过 `realtime-data`
+/// 事件推送增量（`RealtimeUpdate`）。返回订阅 ID，用 `unsubscribe_realtime` 停止。
+#[tauri::command]
+pub async fn subscribe_realtime(
+    tags: Vec<String>,
+    interval_secs: u64,
+    state: WindowState,
+) -> AppResult<String> {
+    if tags.is_empty() {
+        return Err(AppError::Validation("实时订阅至少需要一个标签".to_string()));
+    }
+    if interval_secs == 0 {
+        return Err(AppError::Validation(
+            "实时订阅间隔必须大于 0 秒".to_string(),
+        ));
+    }
+
+    let state = state.read().await;
+    let service = Arc::new(
+        state
+            .query_service()
+            .ok_or(AppError::DatabaseNotConnected)?,
+    );
+    let tag_count = tags.len();
+    let tags = Arc::new(tags);
+
+    let id = state.realtime().subscribe(
+        Duration::from_secs(interval_secs),
+        move |start, end| {
+            let service = Arc::clone(&service);
+            let tags = Arc::clone(&tags);
+            async move { service.query_recent(&tags, &start, &end).await }
+        },
+        |update| {
+            if let Some(handle) = crate::get_app_handle()
+                && let Err(e) = handle.emit(REALTIME_EVENT, update)
+            {
+                warn!(target: "industry_vis::commands", "发送实时数据事件失败: {}", e);
+            }
+        },
+    );
+
     info!(target: "industry_vis::commands",
-        "导出CSV - 路径: {}, 记录数: {}",
-        file_path, records.len()
+        "实时订阅 - ID: {}, 标签数: {}, 间隔: {}s", id, tag_count, interval_secs
     );
+    Ok(id)
+}
 
-    let mut file = File::create(&file_path)?;
+/// 停止实时数据订阅
+#[tauri::command]
+pub async fn unsubscribe_realtime(subscription_id: String, state: WindowState) -> AppResult<()> {
+    info!(target: "industry_vis::commands", "取消实时订阅 - ID: {}", subscription_id);
+    let state = state.read().await;
+    if state.realtime().unsubscribe(&subscription_id) {
+        Ok(())
+    } else {
+        Err(AppError::NotFound(format!(
+            "实时订阅 '{}' 不存在",
+            subscription_id
+        )))
+    }
+}
 
-    // Write header
-    writeln!(file, "DateTime,TagName,TagVal,TagQuality")?;
+/// 预估查询数据量和耗时（执行前提示用户）
+#[tauri::command]
+pub async fn estimate_query(params: QueryParams, state: WindowState) -> AppResult<QueryEstimate> {
+    debug!(target: "industry_vis::commands",
+        "查询预估 - {} ~ {}", params.start_time, params.end_time
+    );
+    let state = state.read().await;
+    match state.query_service() {
+        Some(service) => service.estimate_query(&params).await,
+        None => Err(AppError::DatabaseNotConnected),
+    }
+}
 
-    // Write records
-    for record in records {
-        writeln!(
-            file,
-            "{},{},{},{}",
-            record.date_time,
-            record.tag_name.replace(',', ";"),
-            record.tag_val,
-            record.tag_quality.replace(',', ";")
-        )?;
+/// 检测各标签的正常采样周期
+///
+/// 查询时间范围内的原始数据，对相邻时间差取众数（不规则采样时取中位数），
+/// 用于自动配置断采阈值、死区等。
+#[tauri::command]
+pub async fn detect_sampling_interval(
+    params: QueryParams,
+    state: WindowState,
+) -> AppResult<Vec<SamplingInterval>> {
+    debug!(target: "industry_vis::commands",
+        "采样周期检测 - {} ~ {}", params.start_time, params.end_time
+    );
+    let state = state.read().await;
+    let service = state
+        .query_service()
+        .ok_or(AppError::DatabaseNotConnected)?;
+    // 检测需要解析记录时间，保持默认时间格式
+    let params = QueryParams {
+        output_time_format: None,
+        ..params
+    };
+    // 降采样后的相邻间隔是抽样步长而不是采样周期，必须用原始数据检测
+    let records = service
+        .query_history_raw(&params, None)
+        .await?
+        .into_complete_records()?;
+    Ok(processing::detect_sampling_interval(&records))
+}
+
+/// 计算两个标签的差值序列（A 减 B）
+///
+/// `series` 为前端当前的查询结果；两个标签时间不一致时先插值对齐再相减。
+#[tauri::command]
+pub async fn compute_difference(
+    series_a_tag: String,
+    series_b_tag: String,
+    series: Vec<ChartSeriesData>,
+) -> AppResult<ChartSeriesData> {
+    debug!(target: "industry_vis::commands", "差值序列 - {} - {}", series_a_tag, series_b_tag);
+    let find = |tag: &str| {
+        series
+            .iter()
+            .find(|s| s.tag_name == tag)
+            .ok_or_else(|| AppError::NotFound(format!("结果中不存在标签 '{}'", tag)))
+    };
+
+    Ok(processing::difference_series(
+        find(&series_a_tag)?,
+        find(&series_b_tag)?,
+    ))
+}
+
+/// 提取各标签的关键拐点（局部极大/极小）
+///
+/// `prominence` 为显著性阈值，低于该值的小波动不输出。
+#[tauri::command]
+pub async fn extract_extrema(
+    series: Vec<ChartSeriesData>,
+    prominence: f64,
+) -> AppResult<Vec<ChartSeriesData>> {
+    debug!(target: "industry_vis::commands", "峰谷检测 - 标签数: {}, prominence: {}", series.len(), prominence);
+    if !prominence.is_finite() || prominence < 0.0 {
+        return Err(AppError::Validation(format!(
+            "prominence 必须为非负数，当前 {}",
+            prominence
+        )));
+    }
+
+    Ok(processing::extract_extrema(&series, prominence))
+}
+
+/// 运行处理性能基准（现场诊断）
+///
+/// 生成 `rows` 条、`tags` 个标签的模拟数据，返回各处理阶段的耗时，
+/// 与查询耗时对比判断是数据处理慢还是数据库慢。
+#[tauri::command]
+pub async fn run_benchmark(rows: usize, tags: usize) -> AppResult<processing::BenchmarkResult> {
+    info!(target: "industry_vis::commands", "运行处理性能基准 - 行数: {}, 标签数: {}", rows, tags);
+    tokio::task::spawn_blocking(move || processing::run_benchmark(rows, tags))
+        .await
+        .map_err(|e| AppError::Internal(format!("基准任务执行失败: {}", e)))?
+}
+
+/// 校验原生与 Polars 两套处理实现的一致性（维护诊断）
+///
+/// 对同一样本分别运行两套实现，返回差异点数、最大偏差和差异点明细；
+/// `tolerance` 为允许的绝对偏差（默认 1e-6）。
+#[tauri::command]
+pub async fn verify_processing_consistency(
+    sample: Vec<HistoryRecord>,
+    config: DataProcessingConfig,
+    tolerance: Option<f64>,
+) -> AppResult<processing::ConsistencyReport> {
+    info!(target: "industry_vis::commands", "校验处理实现一致性 - 样本数: {}", sample.len());
+    tokio::task::spawn_blocking(move || {
+        processing::verify_processing_consistency(sample, &config, tolerance)
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("一致性校验任务执行失败: {}", e)))?
+}
+
+/// 按事件标签（批次开始/结束标记）把数据切成批次
+///
+/// 事件标签的值变为 `start_value` 时开始、变为 `end_value` 时结束，返回每批的时间范围和数据。
+#[tauri::command]
+pub async fn split_by_events(
+    data: Vec<ChartSeriesData>,
+    event_tag: String,
+    start_value: f64,
+    end_value: f64,
+) -> AppResult<Vec<EventBatch>> {
+    debug!(target: "industry_vis::commands",
+        "批次切分 - 事件标签: {}, 开始值: {}, 结束值: {}", event_tag, start_value, end_value
+    );
+    processing::split_by_events(&data, &event_tag, start_value, end_value)
+}
+
+/// 执行自定义 SQL 查询（高级用户）
+///
+/// 需在配置中开启 `query.allow_custom_sql`。仅允许 SELECT 语句，
+/// 结果必须依次返回 DateTime/TagName/TagVal/TagQuality 四列。
+/// 结果超过 `query.max_result_rows` 时截断，返回值的 `truncated` 为 true。
+#[tauri::command]
+pub async fn run_custom_query(
+    sql: String,
+    processing_config: Option<DataProcessingConfig>,
+    state: WindowState,
+) -> AppResult<QueryResult> {
+    info!(target: "industry_vis::commands", "执行自定义 SQL 查询");
+    let state = state.read().await;
+    if !state.config().app_config().query.allow_custom_sql {
+        return Err(AppError::Validation(
+            "自定义 SQL 查询未启用，请在配置中开启 allow_custom_sql".to_string(),
+        ));
+    }
+
+    match state.query_service() {
+        Some(service) => {
+            service
+                .run_custom_query(&sql, processing_config.as_ref())
+                .await
+        }
+        None => Err(AppError::DatabaseNotConnected),
+    }
+}
+
+/// 处理配置实时预览
+///
+/// 对前端传入的小样本应用处理配置，不走数据库和缓存。
+#[tauri::command]
+pub async fn preview_processing(
+    sample: Vec<HistoryRecord>,
+    config: DataProcessingConfig,
+) -> AppResult<Vec<HistoryRecord>> {
+    debug!(target: "industry_vis::commands", "处理预览 - 样本数: {}", sample.len());
+    processing::preview_processing(sample, &config)
+}
+
+/// 获取 API 数据结构的 JSON Schema（`type_name` 如 `QueryResultV2`、`QueryParams`）
+#[tauri::command]
+pub async fn get_api_schema(type_name: String) -> AppResult<serde_json::Value> {
+    debug!(target: "industry_vis::commands", "获取 JSON Schema - 类型: {}", type_name);
+    models::api_schema(&type_name)
+}
+
+/// 导出数据到 CSV
+///
+/// `encoding` 可选 `"utf8"`（默认）或 `"gbk"`（兼容老旧 MES 系统）。
+/// `file_path` 可包含 `{date}`、`{time}`、`{group}`（需传 `group_name`）、`{tags}` 占位符，
+/// 返回渲染后的实际路径。
+/// `extra_columns` 可附加按标签计算的 `"cumsum"`（累积和）、`"delta"`（相邻差值）列。
+/// 每次导出都记录导出历史；传入 `params`（及 `processing_config`）时可按原查询重新导出。
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn export_to_csv(
+    records: Vec<HistoryRecord>,
+    file_path: String,
+    encoding: Option<String>,
+    group_name: Option<String>,
+    extra_columns: Option<Vec<String>>,
+    params: Option<QueryParams>,
+    processing_config: Option<DataProcessingConfig>,
+) -> AppResult<String> {
+    let file_path = if export::has_file_name_placeholders(&file_path) {
+        let tag_count = records
+            .iter()
+            .map(|r| r.tag_name.as_str())
+            .collect::<std::collections::HashSet<_>>()
+            .len();
+        export::render_file_name_template(
+            &file_path,
+            &export::FileNameContext {
+                group: group_name.as_deref(),
+                tag_count,
+                now: chrono::Local::now().naive_local(),
+            },
+        )?
+    } else {
+        file_path
+    };
+    info!(target: "industry_vis::commands",
+        "导出CSV - 路径: {}, 记录数: {}, 编码: {:?}",
+        file_path, records.len(), encoding
+    );
+
+    let request = CsvExportRequest {
+        params,
+        processing_config,
+        file_path,
+        encoding,
+        extra_columns: extra_columns.unwrap_or_default(),
+    };
+    request.execute(&records)?;
+
+    // 历史记录失败不影响导出结果
+    let file_path = request.file_path.clone();
+    if let Err(e) =
+        ExportHistory::load().and_then(|mut history| history.record(request, records.len()))
+    {
+        warn!(target: "industry_vis::commands", "记录导出历史失败: {}", e);
     }
 
     info!(target: "industry_vis::commands", "CSV导出完成");
+    Ok(file_path)
+}
+
+/// 导出数据到 Parquet
+///
+/// `partition_by_tag` 为 true 时 `file_path` 视为目录，按 `tag_name=xxx/` 分区写出。
+/// 返回写出的文件路径。
+#[tauri::command]
+pub async fn export_to_parquet(
+    records: Vec<HistoryRecord>,
+    file_path: String,
+    partition_by_tag: Option<bool>,
+) -> AppResult<Vec<String>> {
+    let partition_by_tag = partition_by_tag.unwrap_or(false);
+    info!(target: "industry_vis::commands",
+        "导出Parquet - 路径: {}, 记录数: {}, 按标签分区: {}",
+        file_path, records.len(), partition_by_tag
+    );
+
+    let written = export::write_parquet(&records, &file_path, partition_by_tag)?;
+
+    info!(target: "industry_vis::commands", "Parquet导出完成 - 文件数: {}", written.len());
+    Ok(written
+        .into_iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect())
+}
+
+/// 导出数据到 Arrow IPC（Feather v2）文件
+#[tauri::command]
+pub async fn export_to_arrow(records: Vec<HistoryRecord>, file_path: String) -> AppResult<()> {
+    info!(target: "industry_vis::commands",
+        "导出Arrow - 路径: {}, 记录数: {}",
+        file_path, records.len()
+    );
+
+    export::write_arrow(&records, &file_path)?;
+
+    info!(target: "industry_vis::commands", "Arrow导出完成");
     Ok(())
 }
+
+/// 导出处理前后对比 CSV
+///
+/// 同一查询分别取原始数据与按 `processing_config` 处理后的数据，按时间对齐为
+/// `raw_value` / `processed_value` 两列。重采样时窗口内的原始行沿用该窗口的处理后值；
+/// OHLC 重采样取收盘值、多统计重采样取第一个统计量对比，波动带不参与对比。返回写出的行数。
+#[tauri::command]
+pub async fn export_comparison(
+    params: QueryParams,
+    processing_config: DataProcessingConfig,
+    file_path: String,
+    encoding: Option<String>,
+    state: WindowState,
+) -> AppResult<usize> {
+    info!(target: "industry_vis::commands",
+        "导出处理前后对比 - 时间: {} ~ {}, 路径: {}",
+        params.start_time, params.end_time, file_path
+    );
+    let encoding = export::CsvEncoding::parse(encoding.as_deref())?;
+
+    let raw = {
+        let state = state.read().await;
+        let service = state
+            .query_service()
+            .ok_or(AppError::DatabaseNotConnected)?;
+        // 对比需要完整时间范围的未降采样数据与可解析的时间格式
+        let params = QueryParams {
+            output_time_format: None,
+            ..params
+        };
+        service
+            .query_history_raw(&params, None)
+            .await?
+            .into_complete_records()?
+    };
+
+    let processing_config = DataProcessingConfig {
+        std_band: Default::default(),
+        ..processing_config
+    };
+    let mut processed = processing::process_data(raw.clone(), &processing_config)?;
+    let resample = &processing_config.resample;
+    let hold_ms = if resample.enabled {
+        i64::from(resample.interval) * 1000
+    } else {
+        0
+    };
+    // 派生序列只取一个与原始数据对比：OHLC 取收盘值，多统计取第一个统计量
+    let derived_suffix = if !resample.enabled {
+        None
+    } else if resample.is_multi_stat() {
+        Some(format!("_{}", resample.methods[0].to_ascii_lowercase()))
+    } else if resample.is_ohlc() {
+        Some(".close".to_string())
+    } else {
+        None
+    };
+    if let Some(suffix) = derived_suffix {
+        processed.retain_mut(
+            |record| match record.tag_name.strip_suffix(suffix.as_str()) {
+                Some(tag) => {
+                    record.tag_name = tag.into();
+                    true
+                }
+                None => false,
+            },
+        );
+    }
+
+    let rows = export::build_comparison_rows(&raw, &processed, hold_ms);
+    let count = export::write_comparison_csv(&rows, &file_path, encoding)?;
+
+    info!(target: "industry_vis::commands", "对比导出完成 - 行数: {}", count);
+    Ok(count)
+}
+
+/// 提交后台导出任务
+///
+/// 立即返回任务 ID，任务按提交顺序在后台执行：查询 `params` 时间范围的全部数据
+/// （忽略分页），按 `processing_config` 处理后以 `format`（csv / parquet / arrow）写出。
+/// 进度通过 `export-progress` 事件（`ExportTaskInfo`）上报。
+#[tauri::command]
+pub async fn start_export(
+    params: QueryParams,
+    processing_config: Option<DataProcessingConfig>,
+    file_path: String,
+    format: String,
+    encoding: Option<String>,
+    state: WindowState,
+) -> AppResult<String> {
+    let format = export::BundleFormat::parse(&format)?;
+    let encoding = export::CsvEncoding::parse(encoding.as_deref())?;
+    if let Some(config) = &processing_config {
+        config.validate()?;
+    }
+
+    let time_range = format!("{} ~ {}", params.start_time, params.end_time);
+    let state = state.read().await;
+    let task_id = state.submit_export(params, processing_config, file_path, format, encoding)?;
+
+    info!(target: "industry_vis::commands",
+        "提交导出任务 - ID: {}, 时间: {}, 格式: {:?}",
+        task_id, time_range, format
+    );
+    Ok(task_id)
+}
+
+/// 查询后台导出任务状态
+#[tauri::command]
+pub async fn get_export_task(task_id: String, state: WindowState) -> AppResult<ExportTaskInfo> {
+    let state = state.read().await;
+    state
+        .export_tasks()
+        .get(&task_id)
+        .ok_or_else(|| AppError::NotFound(format!("导出任务 '{}' 不存在", task_id)))
+}
+
+/// 取消后台导出任务
+#[tauri::command]
+pub async fn cancel_export(task_id: String, state: WindowState) -> AppResult<ExportTaskInfo> {
+    info!(target: "industry_vis::commands", "取消导出任务 - ID: {}", task_id);
+    let state = state.read().await;
+    state.export_tasks().cancel(&task_id)
+}
+
+/// 注册定时导出任务
+///
+/// `cron_or_interval` 为固定间隔（如 `30s` / `15m` / `2h` / `1d`）或 5 段 cron（分 时 日 月 周）。
+/// 每次执行时查询时间范围平移到以执行时刻为终点（保持 `params` 的时长），
+/// 输出路径由 `output_template` 渲染（支持 `{date}`、`{time}`、`{tags}`），
+/// 提交到后台导出任务队列。任务定义持久化到配置目录，应用运行期间按计划执行。
+#[tauri::command]
+pub async fn schedule_export(
+    cron_or_interval: String,
+    params: QueryParams,
+    output_template: String,
+    processing_config: Option<DataProcessingConfig>,
+    format: Option<String>,
+    encoding: Option<String>,
+    state: WindowState,
+) -> AppResult<ScheduledExportInfo> {
+    let scheduled = ScheduledExport {
+        id: String::new(),
+        schedule: cron_or_interval,
+        params,
+        processing_config,
+        output_template,
+        format: format.unwrap_or_else(|| "csv".to_string()),
+        encoding,
+        created_at: String::new(),
+    };
+    let state = state.read().await;
+    state.export_scheduler().add(scheduled)
+}
+
+/// 获取定时导出任务（含下一次 / 最近一次执行时刻）
+#[tauri::command]
+pub async fn list_scheduled_exports(state: WindowState) -> AppResult<Vec<ScheduledExportInfo>> {
+    let state = state.read().await;
+    Ok(state.export_scheduler().list())
+}
+
+/// 移除定时导出任务
+#[tauri::command]
+pub async fn remove_scheduled_export(id: String, state: WindowState) -> AppResult<()> {
+    let state = state.read().await;
+    state.export_scheduler().remove(&id)
+}
+
+/// 获取导出历史（最新在前）
+#[tauri::command]
+pub async fn list_export_history() -> AppResult<Vec<ExportHistoryItem>> {
+    debug!(target: "industry_vis::commands", "获取导出历史");
+    Ok(ExportHistory::load()?.list())
+}
+
+/// 按历史记录的原参数重新查询并导出到原路径
+#[tauri::command]
+pub async fn repeat_export(history_id: String, state: WindowState) -> AppResult<()> {
+    info!(target: "industry_vis::commands", "重新导出 - 历史: {}", history_id);
+    let history = ExportHistory::load()?;
+    let request = history.request(&history_id)?;
+
+    let state = state.read().await;
+    let service = state
+        .query_service()
+        .ok_or(AppError::DatabaseNotConnected)?;
+    let records = service
+        .query_history_raw(
+            &request.full_query_params()?,
+            request.processing_config.as_ref(),
+        )
+        .await?
+        .into_complete_records()?;
+    request.execute(&records)
+}
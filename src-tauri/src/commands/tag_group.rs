@@ -1,17 +1,22 @@
 //! 标签分组命令
 
-use std::sync::Arc;
-use tauri::State;
-use tokio::sync::RwLock;
+use std::collections::HashMap;
 use tracing::{debug, info};
 
-use crate::error::AppResult;
-use crate::models::{ChartConfig, DataProcessingConfig, TagGroup};
-use crate::state::AppState;
+use crate::config::GroupSnapshotItem;
+use crate::error::{AppError, AppResult};
+use crate::export::{self, BundleFormat, DashboardDefinition, GroupExportResult, PdfReportResult};
+use crate::models::{
+    AlarmStats, Annotation, BatchApplyResult, ChartConfig, ChartSeriesData, DataProcessingConfig,
+    GroupImportResult, GroupUsageStats, QueryParams, TagGroup, TagMetadata, TagUsageSummary,
+};
+use crate::processing;
+use crate::services::{DEFAULT_AUDIT_RECENT_HOURS, GroupAuditReport};
+use crate::state::WindowState;
 
 /// 获取所有标签分组
 #[tauri::command]
-pub async fn list_tag_groups(state: State<'_, Arc<RwLock<AppState>>>) -> AppResult<Vec<TagGroup>> {
+pub async fn list_tag_groups(state: WindowState) -> AppResult<Vec<TagGroup>> {
     debug!(target: "industry_vis::commands", "获取标签分组列表");
     let state = state.read().await;
     Ok(state.tag_group_service().list_groups())
@@ -22,7 +27,7 @@ pub async fn list_tag_groups(state: State<'_, Arc<RwLock<AppState>>>) -> AppResu
 pub async fn create_tag_group(
     name: String,
     charts: Vec<ChartConfig>,
-    state: State<'_, Arc<RwLock<AppState>>>,
+    state: WindowState,
 ) -> AppResult<TagGroup> {
     info!(target: "industry_vis::commands",
         "创建分组 - 名称: {}, 图表数: {}",
@@ -39,7 +44,7 @@ pub async fn update_tag_group(
     name: String,
     charts: Vec<ChartConfig>,
     processing_config: Option<DataProcessingConfig>,
-    state: State<'_, Arc<RwLock<AppState>>>,
+    state: WindowState,
 ) -> AppResult<TagGroup> {
     info!(target: "industry_vis::commands",
         "更新分组 - ID: {}, 名称: {}, 图表数: {}",
@@ -51,13 +56,355 @@ pub async fn update_tag_group(
         .update_group(&id, name, charts, processing_config)
 }
 
-/// 删除标签分组
+/// 部分更新标签分组
+///
+/// 只更新提供的字段，未提供的字段保持不变，避免覆盖并发修改。
 #[tauri::command]
-pub async fn delete_tag_group(
+pub async fn patch_tag_group(
     id: String,
-    state: State<'_, Arc<RwLock<AppState>>>,
+    name: Option<String>,
+    charts: Option<Vec<ChartConfig>>,
+    processing_config: Option<DataProcessingConfig>,
+    state: WindowState,
+) -> AppResult<TagGroup> {
+    info!(target: "industry_vis::commands", "部分更新分组 - ID: {}", id);
+    let state = state.read().await;
+    state
+        .tag_group_service()
+        .patch_group(&id, name, charts, processing_config)
+}
+
+/// 按分组报警线配置统计越限情况
+///
+/// `series` 为前端当前的查询结果，未配置报警线的标签会被跳过。
+#[tauri::command]
+pub async fn compute_alarm_stats(
+    group_id: String,
+    series: Vec<ChartSeriesData>,
+    state: WindowState,
+) -> AppResult<Vec<AlarmStats>> {
+    debug!(target: "industry_vis::commands", "越限统计 - 分组: {}", group_id);
+    let state = state.read().await;
+    let group = state
+        .tag_group_service()
+        .get_group(&group_id)
+        .ok_or_else(|| AppError::NotFound(format!("分组 '{}' 不存在", group_id)))?;
+
+    Ok(processing::compute_alarm_stats(
+        &series,
+        &group.tag_alarms(),
+    ))
+}
+
+/// 导出单个分组在时间范围内的所有标签数据（打包为一个文件）
+///
+/// `format` 可选 `csv` / `parquet` / `arrow`；文件写入 `output_dir`，文件名含分组名和时间范围。
+/// 导出全部原始数据点（不降采样），不应用分组的处理配置。
+/// 不支持 xlsx（工作表行数上限约 104 万，大时间范围会被截断），需要时导出 csv 再用 Excel 打开。
+#[tauri::command]
+pub async fn export_group_data(
+    group_id: String,
+    start_time: String,
+    end_time: String,
+    format: String,
+    output_dir: String,
+    state: WindowState,
+) -> AppResult<GroupExportResult> {
+    let format = BundleFormat::parse(&format)?;
+    let state = state.read().await;
+    let group = state
+        .tag_group_service()
+        .get_group(&group_id)
+        .ok_or_else(|| AppError::NotFound(format!("分组 '{}' 不存在", group_id)))?;
+    let tags = group.all_tags();
+    if tags.is_empty() {
+        return Err(AppError::Validation(format!(
+            "分组 '{}' 没有标签，无需导出",
+            group.name
+        )));
+    }
+
+    info!(target: "industry_vis::commands",
+        "分组打包导出 - 分组: {}, 时间: {} ~ {}, 标签数: {}, 格式: {:?}",
+        group.name, start_time, end_time, tags.len(), format
+    );
+
+    let service = state
+        .query_service()
+        .ok_or(AppError::DatabaseNotConnected)?;
+    let params = QueryParams::new(start_time.clone(), end_time.clone()).with_tags(tags.clone());
+    let records = service
+        .query_history_raw(&params, None)
+        .await?
+        .into_complete_records()?;
+
+    let file_name = export::group_export_file_name(&group.name, &start_time, &end_time, format);
+    let path = std::path::Path::new(&output_dir).join(file_name);
+    let result = export::write_group_bundle(&records, &tags, &path, format)?;
+
+    info!(target: "industry_vis::commands",
+        "分组打包导出完成 - 文件: {}, 记录数: {}",
+        result.file_path, result.record_count
+    );
+    Ok(result)
+}
+
+/// 将分组导出为 PDF 报告（每个图表一页：图表 + 各标签统计表）
+///
+/// 按分组的处理配置查询数据，报告写入 `file_path`。统计表由全部数据点计算，
+/// 图表绘制降采样后的数据。
+#[tauri::command]
+pub async fn export_report_pdf(
+    group_id: String,
+    start_time: String,
+    end_time: String,
+    file_path: String,
+    state: WindowState,
+) -> AppResult<PdfReportResult> {
+    let state = state.read().await;
+    let group = state
+        .tag_group_service()
+        .get_group(&group_id)
+        .ok_or_else(|| AppError::NotFound(format!("分组 '{}' 不存在", group_id)))?;
+
+    info!(target: "industry_vis::commands",
+        "导出 PDF 报告 - 分组: {}, 时间: {} ~ {}, 图表数: {}",
+        group.name, start_time, end_time, group.charts.len()
+    );
+
+    let tags = group.all_tags();
+    let records = if tags.is_empty() {
+        Vec::new()
+    } else {
+        let service = state
+            .query_service()
+            .ok_or(AppError::DatabaseNotConnected)?;
+        let params = QueryParams::new(start_time.clone(), end_time.clone()).with_tags(tags);
+        service
+            .query_history_raw(&params, Some(&group.processing_config))
+            .await?
+            .into_complete_records()?
+    };
+    drop(state);
+
+    let result = tokio::task::spawn_blocking(move || {
+        let full_series = processing::records_to_series(&records);
+        let drawn = processing::downsample(records, processing::CHART_MAX_POINTS_PER_TAG)?;
+        let series = processing::records_to_series(&drawn);
+        let charts = export::build_report_charts(&group, &series, &full_series);
+        export::write_report_pdf(&file_path, &group.name, &start_time, &end_time, &charts)
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("PDF 报告生成失败: {}", e)))??;
+
+    info!(target: "industry_vis::commands",
+        "PDF 报告导出完成 - 文件: {}, 页数: {}",
+        result.file_path, result.page_count
+    );
+    Ok(result)
+}
+
+/// 将分组导出为仪表盘定义
+///
+/// 返回自包含的仪表盘 JSON（布局、标签、别名、报警线、处理配置、时间范围、标注），
+/// 由前端保存为文件或交给其他实例加载。
+#[tauri::command]
+pub async fn export_group_dashboard(
+    group_id: String,
+    start_time: String,
+    end_time: String,
+    state: WindowState,
+) -> AppResult<DashboardDefinition> {
+    let state = state.read().await;
+    let group = state
+        .tag_group_service()
+        .get_group(&group_id)
+        .ok_or_else(|| AppError::NotFound(format!("分组 '{}' 不存在", group_id)))?;
+
+    info!(target: "industry_vis::commands",
+        "导出仪表盘定义 - 分组: {}, 时间: {} ~ {}, 图表数: {}",
+        group.name, start_time, end_time, group.charts.len()
+    );
+    Ok(export::build_dashboard(&group, &start_time, &end_time))
+}
+
+/// 由仪表盘定义导入为新分组
+#[tauri::command]
+pub async fn import_group_dashboard(
+    dashboard: DashboardDefinition,
+    state: WindowState,
+) -> AppResult<TagGroup> {
+    info!(target: "industry_vis::commands",
+        "导入仪表盘定义 - 名称: {}, 版本: {}, 图表数: {}",
+        dashboard.name, dashboard.format_version, dashboard.charts.len()
+    );
+    let group = export::dashboard_to_group(&dashboard)?;
+    let state = state.read().await;
+    state.tag_group_service().import_group(group)
+}
+
+/// 批量导入分组（如从其他机器导出的分组）
+///
+/// `tag_mapping` 为旧标签名 → 本库标签名；未映射的标签保留原名，在返回结果中列出。
+/// 名称与已有分组重复时整批不导入。
+#[tauri::command]
+pub async fn import_groups(
+    groups: Vec<TagGroup>,
+    tag_mapping: Option<HashMap<String, String>>,
+    state: WindowState,
+) -> AppResult<GroupImportResult> {
+    let state = state.read().await;
+    state
+        .tag_group_service()
+        .import_groups(groups, &tag_mapping.unwrap_or_default())
+}
+
+/// 批量检查所有分组的健康状况（标签不存在、最近无数据）
+///
+/// `recent_hours` 为判断"最近无数据"的时间窗口，默认 24 小时；
+/// 结果缓存 5 分钟，`force_refresh` 时重新检查。只返回有问题的分组。
+#[tauri::command]
+pub async fn audit_all_groups(
+    recent_hours: Option<u64>,
+    force_refresh: Option<bool>,
+    state: WindowState,
+) -> AppResult<GroupAuditReport> {
+    info!(target: "industry_vis::commands", "分组健康检查");
+    let state = state.read().await;
+    state
+        .audit_all_groups(
+            recent_hours.unwrap_or(DEFAULT_AUDIT_RECENT_HOURS),
+            force_refresh.unwrap_or(false),
+        )
+        .await
+}
+
+/// 批量应用处理配置到多个分组
+///
+/// 锁定的分组跳过，不存在的分组忽略，均在返回结果中列出。
+#[tauri::command]
+pub async fn apply_processing_to_groups(
+    group_ids: Vec<String>,
+    config: DataProcessingConfig,
+    state: WindowState,
+) -> AppResult<BatchApplyResult> {
+    info!(target: "industry_vis::commands", "批量应用处理配置 - 分组数: {}", group_ids.len());
+    let state = state.read().await;
+    state
+        .tag_group_service()
+        .apply_processing_to_groups(&group_ids, &config)
+}
+
+/// 设置分组锁定状态（锁定的分组不参与批量修改）
+#[tauri::command]
+pub async fn set_tag_group_locked(
+    id: String,
+    locked: bool,
+    state: WindowState,
+) -> AppResult<TagGroup> {
+    info!(target: "industry_vis::commands", "设置分组锁定 - ID: {}, 锁定: {}", id, locked);
+    let state = state.read().await;
+    state.tag_group_service().set_group_locked(&id, locked)
+}
+
+/// 设置分组的标签元数据（单位不一致时历史查询会给出提示）
+#[tauri::command]
+pub async fn set_tag_metadata(
+    id: String,
+    metadata: Vec<TagMetadata>,
+    state: WindowState,
+) -> AppResult<TagGroup> {
+    info!(target: "industry_vis::commands", "设置标签元数据 - ID: {}, 标签数: {}", id, metadata.len());
+    let state = state.read().await;
+    state.tag_group_service().set_tag_metadata(&id, metadata)
+}
+
+/// 为分组新增标注区间
+#[tauri::command]
+pub async fn add_annotation(
+    group_id: String,
+    annotation: Annotation,
+    state: WindowState,
+) -> AppResult<Annotation> {
+    info!(target: "industry_vis::commands", "新增标注 - 分组: {}", group_id);
+    let state = state.read().await;
+    state
+        .tag_group_service()
+        .add_annotation(&group_id, annotation)
+}
+
+/// 更新分组的标注区间（按 ID 替换）
+#[tauri::command]
+pub async fn update_annotation(
+    group_id: String,
+    annotation: Annotation,
+    state: WindowState,
+) -> AppResult<Annotation> {
+    info!(target: "industry_vis::commands", "更新标注 - 分组: {}, 标注: {}", group_id, annotation.id);
+    let state = state.read().await;
+    state
+        .tag_group_service()
+        .update_annotation(&group_id, annotation)
+}
+
+/// 删除分组的标注区间
+#[tauri::command]
+pub async fn remove_annotation(
+    group_id: String,
+    annotation_id: String,
+    state: WindowState,
 ) -> AppResult<()> {
+    info!(target: "industry_vis::commands", "删除标注 - 分组: {}, 标注: {}", group_id, annotation_id);
+    let state = state.read().await;
+    state
+        .tag_group_service()
+        .remove_annotation(&group_id, &annotation_id)
+}
+
+/// 获取分组使用统计（按访问次数降序）
+#[tauri::command]
+pub async fn get_group_usage_stats(state: WindowState) -> AppResult<Vec<GroupUsageStats>> {
+    debug!(target: "industry_vis::commands", "获取分组使用统计");
+    let state = state.read().await;
+    Ok(state.tag_group_service().usage_stats())
+}
+
+/// 统计所有分组引用的标签（唯一标签数、各标签被多少分组/图表引用）
+#[tauri::command]
+pub async fn tag_usage_across_groups(state: WindowState) -> AppResult<TagUsageSummary> {
+    debug!(target: "industry_vis::commands", "统计分组标签引用");
+    let state = state.read().await;
+    Ok(state.tag_group_service().tag_usage())
+}
+
+/// 删除标签分组
+#[tauri::command]
+pub async fn delete_tag_group(id: String, state: WindowState) -> AppResult<()> {
     info!(target: "industry_vis::commands", "删除分组 - ID: {}", id);
     let state = state.read().await;
     state.tag_group_service().delete_group(&id)
 }
+
+/// 获取分组的版本快照列表（最新在前）
+///
+/// 每次更新/删除分组前自动保存快照，每个分组保留最近若干个。
+#[tauri::command]
+pub async fn list_group_snapshots(
+    group_id: String,
+    state: WindowState,
+) -> AppResult<Vec<GroupSnapshotItem>> {
+    debug!(target: "industry_vis::commands", "获取分组快照 - 分组: {}", group_id);
+    let state = state.read().await;
+    state.tag_group_service().list_snapshots(&group_id)
+}
+
+/// 从快照恢复分组（分组已删除时重新创建）
+#[tauri::command]
+pub async fn restore_group_snapshot(
+    snapshot_id: String,
+    state: WindowState,
+) -> AppResult<TagGroup> {
+    info!(target: "industry_vis::commands", "恢复分组快照 - 快照: {}", snapshot_id);
+    let state = state.read().await;
+    state.tag_group_service().restore_snapshot(&snapshot_id)
+}
@@ -7,16 +7,30 @@ use tracing::{debug, info};
 
 use crate::error::{AppError, AppResult};
 
-use super::PerformanceConfig;
+use super::{ConfigMode, PerformanceConfig};
 
 /// 数据库配置
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+///
+/// `Debug` 输出会将密码脱敏为 `***`，避免 `{:?}` 日志泄露明文密码。
+#[derive(Clone, Serialize, Deserialize, PartialEq)]
 pub struct DatabaseConfig {
     pub server: String,
     pub port: u16,
     pub database: String,
     pub username: String,
     pub password: String,
+    /// 连接意图（`ReadOnly` 可路由到 AlwaysOn 只读副本），默认不设置
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub application_intent: Option<ApplicationIntent>,
+}
+
+/// 连接意图（TDS 登录时的 ApplicationIntent）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ApplicationIntent {
+    /// 只读（AlwaysOn 可用性组可路由到只读副本）
+    ReadOnly,
+    /// 读写（SQL Server 默认）
+    ReadWrite,
 }
 
 impl Default for DatabaseConfig {
@@ -27,10 +41,27 @@ impl Default for DatabaseConfig {
             database: "控制器数据库".to_string(),
             username: "sa".to_string(),
             password: String::new(),
+            application_intent: None,
         }
     }
 }
 
+/// 敏感字段在日志中的占位符
+const REDACTED: &str = "***";
+
+impl std::fmt::Debug for DatabaseConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DatabaseConfig")
+            .field("server", &self.server)
+            .field("port", &self.port)
+            .field("database", &self.database)
+            .field("username", &self.username)
+            .field("password", &REDACTED)
+            .field("application_intent", &self.application_intent)
+            .finish()
+    }
+}
+
 impl DatabaseConfig {
     /// 获取连接字符串（用于显示，隐藏密码）
     pub fn connection_string_masked(&self) -> String {
@@ -39,6 +70,11 @@ impl DatabaseConfig {
             self.server, self.port, self.database, self.username
         )
     }
+
+    /// 是否声明只读连接意图（未设置视为 ReadWrite）
+    pub fn read_only_intent(&self) -> bool {
+        self.application_intent == Some(ApplicationIntent::ReadOnly)
+    }
 }
 
 /// 查询配置
@@ -46,12 +82,80 @@ impl DatabaseConfig {
 #[serde(rename_all = "camelCase")]
 pub struct QueryConfig {
     pub default_table: String,
+    /// 是否允许执行自定义 SQL 查询（高级用户，默认关闭）
+    #[serde(default)]
+    pub allow_custom_sql: bool,
+    /// 单次历史查询返回的最大原始行数（0 表示不限制），超出部分截断并标记
+    #[serde(default = "QueryConfig::default_max_result_rows")]
+    pub max_result_rows: usize,
+    /// 归档库（可选）：主库只保留近期数据时，更早的历史查询自动路由到归档库
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive: Option<ArchiveConfig>,
+    /// 查询会话的事务隔离级别（可选）：获取连接后、执行查询前发送
+    /// `SET TRANSACTION ISOLATION LEVEL ...`，未设置时沿用连接默认级别
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub isolation_level: Option<IsolationLevel>,
+}
+
+/// 查询会话的事务隔离级别
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IsolationLevel {
+    /// 读未提交（脏读，等效于全部表加 NOLOCK，不加共享锁）
+    ReadUncommitted,
+    /// 读已提交（SQL Server 默认）
+    ReadCommitted,
+    /// 快照隔离（需数据库开启 `ALLOW_SNAPSHOT_ISOLATION`）
+    Snapshot,
+}
+
+impl IsolationLevel {
+    /// 设置会话隔离级别的语句
+    pub fn set_statement(&self) -> &'static str {
+        match self {
+            Self::ReadUncommitted => "SET TRANSACTION ISOLATION LEVEL READ UNCOMMITTED",
+            Self::ReadCommitted => "SET TRANSACTION ISOLATION LEVEL READ COMMITTED",
+            Self::Snapshot => "SET TRANSACTION ISOLATION LEVEL SNAPSHOT",
+        }
+    }
+}
+
+/// 归档库配置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveConfig {
+    /// 归档库连接
+    pub database: DatabaseConfig,
+    /// 主库保留的天数：早于“当前时间 - 保留天数”的数据从归档库查询
+    #[serde(default = "ArchiveConfig::default_primary_retention_days")]
+    pub primary_retention_days: u32,
+}
+
+impl ArchiveConfig {
+    fn default_primary_retention_days() -> u32 {
+        30
+    }
+}
+
+impl QueryConfig {
+    fn default_max_result_rows() -> usize {
+        2_000_000
+    }
+
+    /// 结果行数上限（未限制时为 `None`）
+    pub fn result_row_limit(&self) -> Option<usize> {
+        (self.max_result_rows > 0).then_some(self.max_result_rows)
+    }
 }
 
 impl Default for QueryConfig {
     fn default() -> Self {
         Self {
             default_table: "历史表".to_string(),
+            allow_custom_sql: false,
+            max_result_rows: Self::default_max_result_rows(),
+            archive: None,
+            isolation_level: None,
         }
     }
 }
@@ -65,10 +169,29 @@ pub struct SchemaConfig {
     ///
     /// 可选值：
     /// - `"default"` - 默认 Profile（当前厂商）
+    /// - `"wide"` - 宽表 Profile（一行多个测点列，需配置 `wide_columns`）
     ///
     /// 后续可扩展更多厂商配置。
     #[serde(default = "SchemaConfig::default_profile")]
     pub profile: String,
+    /// 标签表名（可通过自动发现保存，未设置时使用 Profile 默认值）
+    ///
+    /// 非默认架构的表写为 `schema.table`（如 `sales.Tags`）。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag_table: Option<String>,
+    /// 宽表模式的测点列名（`profile = "wide"` 时必填）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wide_columns: Option<Vec<String>>,
+    /// 历史表索引提示（索引名，生成 `WITH (NOLOCK, INDEX([...]))`，仅 default profile）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index_hint: Option<String>,
+    /// 将 `TagName IN (...)` 放在 `DateTime BETWEEN` 之前（适配 TagName 在前的复合索引）
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub tag_filter_first: bool,
+    /// 时间列为 DATETIMEOFFSET 时换算到的时区（UTC 偏移如 `+08:00`，或 `UTC`），
+    /// 未设置时使用系统本地时区；DATETIME / DATETIME2 列不带时区，原样读取
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
 }
 
 impl SchemaConfig {
@@ -81,6 +204,11 @@ impl Default for SchemaConfig {
     fn default() -> Self {
         Self {
             profile: Self::default_profile(),
+            tag_table: None,
+            wide_columns: None,
+            index_hint: None,
+            tag_filter_first: false,
+            time_zone: None,
         }
     }
 }
@@ -116,48 +244,19 @@ impl AppConfig {
     }
 
     /// 获取配置文件路径
+    ///
+    /// 可通过环境变量 `IV_CONFIG_MODE=portable|appdata` 强制指定，未设置时自动探测。
     pub fn config_path() -> AppResult<PathBuf> {
-        // 优先使用 exe 同目录（便携模式）
-        if let Some(portable_path) = Self::portable_config_path()
-            && portable_path.exists()
-        {
-            return Ok(portable_path);
-        }
-
-        // 检查 AppData 是否已有配置
-        if let Some(appdata_path) = Self::appdata_config_path()
-            && appdata_path.exists()
-        {
-            return Ok(appdata_path);
-        }
-
-        // 都不存在时，默认使用 exe 同目录
-        Self::portable_config_path()
-            .ok_or_else(|| AppError::Config("无法确定配置文件路径".to_string()))
+        ConfigMode::from_env().load_path(Self::portable_config_path(), Self::appdata_config_path())
     }
 
     /// 获取保存配置的路径
     fn save_config_path() -> AppResult<PathBuf> {
-        // 优先尝试 exe 同目录（便携模式）
-        if let Some(portable_path) = Self::portable_config_path()
-            && let Some(parent) = portable_path.parent()
-        {
-            let test_file = parent.join(".config_write_test");
-            if fs::write(&test_file, "test").is_ok() {
-                let _ = fs::remove_file(&test_file);
-                return Ok(portable_path);
-            }
-        }
-
-        // 不可写时使用 AppData
-        if let Some(appdata_path) = Self::appdata_config_path() {
-            if let Some(parent) = appdata_path.parent() {
-                let _ = fs::create_dir_all(parent);
-            }
-            return Ok(appdata_path);
-        }
-
-        Err(AppError::Config("无法找到可写的配置文件路径".to_string()))
+        ConfigMode::from_env().save_path(
+            Self::portable_config_path(),
+            Self::appdata_config_path(),
+            ".config_write_test",
+        )
     }
 
     /// 从文件加载配置
@@ -228,6 +327,89 @@ mod tests {
         assert_eq!(parsed, config);
     }
 
+    #[test]
+    fn test_schema_profile_from_config() {
+        let toml_str = r#"
+            [database]
+            server = "localhost"
+            port = 1433
+            database = "TestDB"
+            username = "sa"
+            password = ""
+
+            [query]
+            defaultTable = "历史表"
+
+            [schema]
+            profile = "vendor_b"
+        "#;
+        let config: AppConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.schema.profile, "vendor_b");
+    }
+
+    #[test]
+    fn test_schema_profile_missing_falls_back() {
+        let toml_str = r#"
+            [database]
+            server = "localhost"
+            port = 1433
+            database = "TestDB"
+            username = "sa"
+            password = ""
+
+            [query]
+            defaultTable = "历史表"
+        "#;
+        let config: AppConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.schema.profile, "default");
+
+        let toml_str = format!("{}\n[schema]\n", toml_str);
+        let config: AppConfig = toml::from_str(&toml_str).unwrap();
+        assert_eq!(config.schema.profile, "default");
+    }
+
+    #[test]
+    fn test_application_intent_parsed_at_load() {
+        let toml_str = r#"
+            [database]
+            server = "localhost"
+            port = 1433
+            database = "TestDB"
+            username = "sa"
+            password = ""
+            application_intent = "ReadOnly"
+
+            [query]
+            defaultTable = "历史表"
+        "#;
+        let config: AppConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            config.database.application_intent,
+            Some(ApplicationIntent::ReadOnly)
+        );
+        assert!(config.database.read_only_intent());
+        assert!(!AppConfig::default().database.read_only_intent());
+
+        // 无效值在加载配置时即报错，而不是等到连接时
+        let invalid = toml_str.replace("\"ReadOnly\"", "\"Secondary\"");
+        assert!(toml::from_str::<AppConfig>(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_debug_redacts_password() {
+        let mut config = AppConfig::default();
+        config.database.password = "S3cret!Pwd".to_string();
+
+        let output = format!("{:?}", config.database);
+        assert!(!output.contains("S3cret!Pwd"));
+        assert!(output.contains(r#"password: "***""#));
+        assert!(output.contains("localhost"));
+
+        // 整个应用配置的 Debug 输出同样脱敏
+        let output = format!("{:#?}", config);
+        assert!(!output.contains("S3cret!Pwd"));
+    }
+
     #[test]
     fn test_database_config_masked() {
         let config = DatabaseConfig {
@@ -236,6 +418,7 @@ mod tests {
             database: "TestDB".to_string(),
             username: "admin".to_string(),
             password: "secret123".to_string(),
+            application_intent: None,
         };
         let masked = config.connection_string_masked();
         assert!(masked.contains("192.168.1.1"));
@@ -0,0 +1,229 @@
+//! 标签分组版本快照
+//!
+//! 分组被修改或删除前保存修改前的完整分组（保存到配置目录的 JSON），
+//! 每个分组只保留最近 `MAX_SNAPSHOTS_PER_GROUP` 个，可从快照恢复。
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+use super::TagGroupConfigManager;
+use crate::error::{AppError, AppResult};
+use crate::models::TagGroup;
+
+/// 每个分组最多保留的快照数
+pub const MAX_SNAPSHOTS_PER_GROUP: usize = 10;
+
+/// 快照文件名
+const SNAPSHOTS_FILENAME: &str = "tag_group_snapshots.json";
+
+/// 分组快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupSnapshot {
+    /// 快照 ID
+    pub id: String,
+    /// 保存时间
+    pub created_at: String,
+    /// 触发快照的操作（update / patch / delete / restore）
+    pub action: String,
+    /// 操作前的完整分组
+    pub group: TagGroup,
+}
+
+/// 快照摘要（返回前端，不含分组内容）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupSnapshotItem {
+    pub id: String,
+    pub group_id: String,
+    pub group_name: String,
+    pub created_at: String,
+    pub action: String,
+    pub chart_count: usize,
+}
+
+impl From<&GroupSnapshot> for GroupSnapshotItem {
+    fn from(snapshot: &GroupSnapshot) -> Self {
+        Self {
+            id: snapshot.id.clone(),
+            group_id: snapshot.group.id.clone(),
+            group_name: snapshot.group.name.clone(),
+            created_at: snapshot.created_at.clone(),
+            action: snapshot.action.clone(),
+            chart_count: snapshot.group.charts.len(),
+        }
+    }
+}
+
+/// 分组快照存储
+#[derive(Debug)]
+pub struct GroupSnapshotStore {
+    snapshots: Vec<GroupSnapshot>,
+    path: PathBuf,
+}
+
+impl GroupSnapshotStore {
+    /// 快照文件路径（与分组配置同目录）
+    pub fn snapshots_path() -> AppResult<PathBuf> {
+        let config_path = TagGroupConfigManager::config_path()?;
+        let dir = config_path
+            .parent()
+            .ok_or_else(|| AppError::Config("无法确定配置目录".to_string()))?;
+        Ok(dir.join(SNAPSHOTS_FILENAME))
+    }
+
+    /// 从默认位置加载
+    pub fn load() -> AppResult<Self> {
+        Self::load_from(&Self::snapshots_path()?)
+    }
+
+    /// 从指定路径加载，文件不存在时返回空存储
+    pub fn load_from(path: &Path) -> AppResult<Self> {
+        let snapshots = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(path)?)?
+        } else {
+            Vec::new()
+        };
+        debug!(target: "industry_vis::tag_group", "加载分组快照: {} 个", snapshots.len());
+
+        Ok(Self {
+            snapshots,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// 保存到文件
+    pub fn save(&self) -> AppResult<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string(&self.snapshots)?)?;
+        Ok(())
+    }
+
+    /// 保存分组快照，该分组超过上限时丢弃最早的快照
+    pub fn record(&mut self, group: &TagGroup, action: &str) -> AppResult<GroupSnapshotItem> {
+        let mut millis = Local::now().timestamp_millis();
+        while self.get(&format!("s{}", millis)).is_some() {
+            millis += 1;
+        }
+        let snapshot = GroupSnapshot {
+            id: format!("s{}", millis),
+            created_at: Local::now().format("%Y-%m-%dT%H:%M:%S").to_string(),
+            action: action.to_string(),
+            group: group.clone(),
+        };
+        let item = GroupSnapshotItem::from(&snapshot);
+        self.snapshots.push(snapshot);
+
+        let count = self
+            .snapshots
+            .iter()
+            .filter(|s| s.group.id == group.id)
+            .count();
+        let mut overflow = count.saturating_sub(MAX_SNAPSHOTS_PER_GROUP);
+        self.snapshots.retain(|s| {
+            if overflow > 0 && s.group.id == group.id {
+                overflow -= 1;
+                false
+            } else {
+                true
+            }
+        });
+        self.save()?;
+
+        Ok(item)
+    }
+
+    /// 分组的快照摘要（最新在前）
+    pub fn list(&self, group_id: &str) -> Vec<GroupSnapshotItem> {
+        self.snapshots
+            .iter()
+            .rev()
+            .filter(|s| s.group.id == group_id)
+            .map(GroupSnapshotItem::from)
+            .collect()
+    }
+
+    /// 获取快照
+    pub fn get(&self, snapshot_id: &str) -> Option<&GroupSnapshot> {
+        self.snapshots.iter().find(|s| s.id == snapshot_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ChartConfig;
+
+    fn sample_group() -> TagGroup {
+        let chart = ChartConfig::with_id("c1".to_string(), "温度".to_string())
+            .with_tags(vec!["TT_101".to_string(), "TT_102".to_string()]);
+        TagGroup::with_id(
+            "g1".to_string(),
+            "1#锅炉".to_string(),
+            vec![chart],
+            "2024-01-01T00:00:00".to_string(),
+            "2024-01-01T00:00:00".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_restore_to_state_before_update() {
+        let path = std::env::temp_dir().join("industry_vis_test_group_snapshots_restore.json");
+        let _ = fs::remove_file(&path);
+
+        let mut group = sample_group();
+        let before = group.clone();
+        let mut store = GroupSnapshotStore::load_from(&path).unwrap();
+        let item = store.record(&group, "update").unwrap();
+
+        // 改坏分组
+        group
+            .update("改坏的分组".to_string(), Vec::new(), None)
+            .unwrap();
+        assert_ne!(group, before);
+
+        // 重新加载后快照仍在，内容为修改前状态
+        let store = GroupSnapshotStore::load_from(&path).unwrap();
+        assert_eq!(store.list("g1"), vec![item.clone()]);
+        assert_eq!(item.group_name, "1#锅炉");
+        assert_eq!(store.get(&item.id).unwrap().group, before);
+        assert!(store.list("g2").is_empty());
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_snapshots_capped_per_group() {
+        let path = std::env::temp_dir().join("industry_vis_test_group_snapshots_cap.json");
+        let _ = fs::remove_file(&path);
+
+        let mut store = GroupSnapshotStore::load_from(&path).unwrap();
+        let mut other = sample_group();
+        other.id = "g2".to_string();
+        store.record(&other, "delete").unwrap();
+
+        let mut group = sample_group();
+        for i in 0..MAX_SNAPSHOTS_PER_GROUP + 3 {
+            group.name = format!("版本{}", i);
+            store.record(&group, "update").unwrap();
+        }
+
+        let items = store.list("g1");
+        assert_eq!(items.len(), MAX_SNAPSHOTS_PER_GROUP);
+        // 最新在前，最早的 3 个被丢弃
+        assert_eq!(
+            items[0].group_name,
+            format!("版本{}", MAX_SNAPSHOTS_PER_GROUP + 2)
+        );
+        assert_eq!(items.last().unwrap().group_name, "版本3");
+        // 其他分组的快照不受影响
+        assert_eq!(store.list("g2").len(), 1);
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -3,20 +3,31 @@
 //! 提供配置加载、保存、热更新功能。
 
 mod app;
+mod group_snapshots;
+mod mode;
 mod performance;
 mod tag_groups;
 mod watcher;
 
-pub use app::{AppConfig, DatabaseConfig, QueryConfig, SchemaConfig};
+pub use app::{
+    AppConfig, ApplicationIntent, ArchiveConfig, DatabaseConfig, IsolationLevel, QueryConfig,
+    SchemaConfig,
+};
+pub use group_snapshots::{
+    GroupSnapshot, GroupSnapshotItem, GroupSnapshotStore, MAX_SNAPSHOTS_PER_GROUP,
+};
+pub use mode::{CONFIG_MODE_ENV, ConfigMode};
 pub use performance::{
-    CachePerformanceConfig, ChartPerformanceConfig, PerformanceConfig, PoolPerformanceConfig,
-    ProcessingPerformanceConfig,
+    CachePerformanceConfig, ChartPerformanceConfig, CircuitBreakerPerformanceConfig,
+    ConnectionMonitorPerformanceConfig, PerformanceConfig, PoolPerformanceConfig,
+    ProcessingPerformanceConfig, ThrottlePerformanceConfig,
 };
 pub use tag_groups::TagGroupConfigManager;
-pub use watcher::ConfigWatcher;
+pub use watcher::{CONFIG_TYPE_APP, CONFIG_TYPE_TAG_GROUPS, ConfigChangeEvent, ConfigWatcher};
 
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use std::sync::Arc;
+use tokio::sync::mpsc::UnboundedReceiver;
 
 /// 配置状态，支持热更新
 pub struct ConfigState {
@@ -26,6 +37,10 @@ pub struct ConfigState {
     tag_group_manager: Arc<RwLock<TagGroupConfigManager>>,
     /// 配置监听器
     _watcher: Option<ConfigWatcher>,
+    /// 热重载事件接收端（由应用启动时取走并转发给前端）
+    change_events: Mutex<Option<UnboundedReceiver<ConfigChangeEvent>>>,
+    /// 更新配置时是否写回配置文件（独立窗口实例的配置只保存在内存）
+    persistent: bool,
 }
 
 impl ConfigState {
@@ -38,9 +53,27 @@ impl ConfigState {
             app_config: Arc::new(RwLock::new(app_config)),
             tag_group_manager: Arc::new(RwLock::new(tag_group_manager)),
             _watcher: None,
+            change_events: Mutex::new(None),
+            persistent: true,
         })
     }
 
+    /// 创建仅在内存中的配置状态（不监听文件、更新时不写盘）
+    ///
+    /// 用于窗口独立连接其他数据库，标签分组管理器可与主实例共享。
+    pub fn isolated(
+        app_config: AppConfig,
+        tag_group_manager: Arc<RwLock<TagGroupConfigManager>>,
+    ) -> Self {
+        Self {
+            app_config: Arc::new(RwLock::new(app_config)),
+            tag_group_manager,
+            _watcher: None,
+            change_events: Mutex::new(None),
+            persistent: false,
+        }
+    }
+
     /// 创建带热更新的配置状态
     pub fn with_hot_reload() -> crate::error::AppResult<Self> {
         let app_config = AppConfig::load()?;
@@ -50,15 +83,24 @@ impl ConfigState {
         let tag_group_manager = Arc::new(RwLock::new(tag_group_manager));
 
         // 设置配置文件监听
-        let watcher = ConfigWatcher::new(Arc::clone(&app_config), Arc::clone(&tag_group_manager))?;
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let watcher =
+            ConfigWatcher::new(Arc::clone(&app_config), Arc::clone(&tag_group_manager), tx)?;
 
         Ok(Self {
             app_config,
             tag_group_manager,
             _watcher: Some(watcher),
+            change_events: Mutex::new(Some(rx)),
+            persistent: true,
         })
     }
 
+    /// 取走热重载事件接收端（只能取一次，未启用热更新时为 `None`）
+    pub fn take_change_events(&self) -> Option<UnboundedReceiver<ConfigChangeEvent>> {
+        self.change_events.lock().take()
+    }
+
     /// 获取应用配置（读取）
     pub fn app_config(&self) -> AppConfig {
         self.app_config.read().clone()
@@ -71,7 +113,9 @@ impl ConfigState {
 
     /// 更新应用配置
     pub fn update_app_config(&self, config: AppConfig) -> crate::error::AppResult<()> {
-        config.save()?;
+        if self.persistent {
+            config.save()?;
+        }
         *self.app_config.write() = config;
         Ok(())
     }
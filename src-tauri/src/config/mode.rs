@@ -0,0 +1,156 @@
+//! 配置文件位置模式（便携 / AppData）
+
+use std::fs;
+use std::path::PathBuf;
+use tracing::warn;
+
+use crate::error::{AppError, AppResult};
+
+/// 显式指定配置模式的环境变量（`portable` / `appdata`）
+pub const CONFIG_MODE_ENV: &str = "IV_CONFIG_MODE";
+
+/// 配置文件位置模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConfigMode {
+    /// 自动探测：exe 同目录已有配置则便携，其次 AppData，都没有时优先便携
+    #[default]
+    Auto,
+    /// 强制使用 exe 同目录
+    Portable,
+    /// 强制使用 AppData 目录
+    AppData,
+}
+
+impl ConfigMode {
+    /// 解析模式名称（不区分大小写），无法识别时返回 `None`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "auto" => Some(Self::Auto),
+            "portable" => Some(Self::Portable),
+            "appdata" => Some(Self::AppData),
+            _ => None,
+        }
+    }
+
+    /// 从环境变量 `IV_CONFIG_MODE` 读取，未设置或无效时使用自动探测
+    pub fn from_env() -> Self {
+        let Ok(value) = std::env::var(CONFIG_MODE_ENV) else {
+            return Self::Auto;
+        };
+        Self::parse(&value).unwrap_or_else(|| {
+            warn!(target: "industry_vis::config",
+                "{} 取值无效: '{}'（可选 portable / appdata），使用自动探测",
+                CONFIG_MODE_ENV, value
+            );
+            Self::Auto
+        })
+    }
+
+    /// 决定读取配置的路径
+    pub fn load_path(
+        self,
+        portable: Option<PathBuf>,
+        appdata: Option<PathBuf>,
+    ) -> AppResult<PathBuf> {
+        let path = match self {
+            Self::Portable => portable,
+            Self::AppData => appdata,
+            Self::Auto => {
+                // 优先使用已存在的配置：exe 同目录 > AppData，都不存在时默认 exe 同目录
+                if portable.as_ref().is_some_and(|p| p.exists()) {
+                    portable
+                } else if appdata.as_ref().is_some_and(|p| p.exists()) {
+                    appdata
+                } else {
+                    portable
+                }
+            }
+        };
+        path.ok_or_else(|| AppError::Config("无法确定配置文件路径".to_string()))
+    }
+
+    /// 决定保存配置的路径
+    ///
+    /// 自动模式下 exe 同目录可写（用 `write_test_name` 试写）则便携，否则 AppData。
+    pub fn save_path(
+        self,
+        portable: Option<PathBuf>,
+        appdata: Option<PathBuf>,
+        write_test_name: &str,
+    ) -> AppResult<PathBuf> {
+        if self != Self::AppData
+            && let Some(portable_path) = portable
+            && let Some(parent) = portable_path.parent()
+        {
+            if self == Self::Portable {
+                return Ok(portable_path);
+            }
+            let test_file = parent.join(write_test_name);
+            if fs::write(&test_file, "test").is_ok() {
+                let _ = fs::remove_file(&test_file);
+                return Ok(portable_path);
+            }
+        }
+
+        // 不可写或强制 AppData 时使用 AppData
+        if self != Self::Portable
+            && let Some(appdata_path) = appdata
+        {
+            if let Some(parent) = appdata_path.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            return Ok(appdata_path);
+        }
+
+        Err(AppError::Config("无法找到可写的配置文件路径".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config_mode() {
+        assert_eq!(ConfigMode::parse("Portable"), Some(ConfigMode::Portable));
+        assert_eq!(ConfigMode::parse(" APPDATA "), Some(ConfigMode::AppData));
+        assert_eq!(ConfigMode::parse(""), Some(ConfigMode::Auto));
+        assert_eq!(ConfigMode::parse("usb"), None);
+    }
+
+    #[test]
+    fn test_appdata_mode_ignores_portable_config() {
+        let root = std::env::temp_dir().join(format!("iv_config_mode_{}", std::process::id()));
+        let exe_dir = root.join("exe");
+        fs::create_dir_all(&exe_dir).unwrap();
+        let portable = exe_dir.join("config.toml");
+        fs::write(&portable, "").unwrap();
+        let appdata = root.join("appdata").join("IndustryVis").join("config.toml");
+
+        // 自动探测：exe 目录已有配置，使用便携路径
+        let auto = ConfigMode::Auto
+            .load_path(Some(portable.clone()), Some(appdata.clone()))
+            .unwrap();
+        assert_eq!(auto, portable);
+
+        // 强制 AppData：即使 exe 目录有配置也使用 AppData
+        let forced = ConfigMode::AppData
+            .load_path(Some(portable.clone()), Some(appdata.clone()))
+            .unwrap();
+        assert_eq!(forced, appdata);
+        let save = ConfigMode::AppData
+            .save_path(Some(portable.clone()), Some(appdata.clone()), ".write_test")
+            .unwrap();
+        assert_eq!(save, appdata);
+        assert!(appdata.parent().unwrap().exists());
+
+        // 强制便携：AppData 不参与
+        let save = ConfigMode::Portable
+            .save_path(Some(portable.clone()), Some(appdata.clone()), ".write_test")
+            .unwrap();
+        assert_eq!(save, portable);
+        assert!(ConfigMode::Portable.load_path(None, Some(appdata)).is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
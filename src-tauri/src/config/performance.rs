@@ -18,6 +18,12 @@ pub struct CachePerformanceConfig {
     /// 是否启用缓存预热
     #[serde(default)]
     pub warmup_enabled: bool,
+    /// 是否在退出时将缓存持久化到磁盘，启动时恢复
+    #[serde(default)]
+    pub persist_enabled: bool,
+    /// 滑动过期：命中时重新计算过期时间（默认按写入时刻绝对过期）
+    #[serde(default)]
+    pub sliding_expiration: bool,
 }
 
 impl CachePerformanceConfig {
@@ -29,19 +35,29 @@ impl CachePerformanceConfig {
         1800 // 30 分钟
     }
 
+    /// 允许的最大缓存条目数范围
+    pub const MIN_ENTRIES: usize = 10;
+    pub const MAX_ENTRIES: usize = 1000;
+    /// 允许的缓存过期时间范围（秒）
+    pub const MIN_TTL_SECONDS: u64 = 60;
+    pub const MAX_TTL_SECONDS: u64 = 7200;
+
     /// 验证配置有效性
     pub fn validate(&self) -> Result<(), String> {
-        if self.max_entries < 10 {
-            return Err("max_entries 最小值为 10".to_string());
+        if self.max_entries < Self::MIN_ENTRIES {
+            return Err(format!("max_entries 最小值为 {}", Self::MIN_ENTRIES));
         }
-        if self.max_entries > 1000 {
-            return Err("max_entries 最大值为 1000".to_string());
+        if self.max_entries > Self::MAX_ENTRIES {
+            return Err(format!("max_entries 最大值为 {}", Self::MAX_ENTRIES));
         }
-        if self.ttl_seconds < 60 {
-            return Err("ttl_seconds 最小值为 60 秒".to_string());
+        if self.ttl_seconds < Self::MIN_TTL_SECONDS {
+            return Err(format!("ttl_seconds 最小值为 {} 秒", Self::MIN_TTL_SECONDS));
         }
-        if self.ttl_seconds > 7200 {
-            return Err("ttl_seconds 最大值为 7200 秒（2小时）".to_string());
+        if self.ttl_seconds > Self::MAX_TTL_SECONDS {
+            return Err(format!(
+                "ttl_seconds 最大值为 {} 秒（2小时）",
+                Self::MAX_TTL_SECONDS
+            ));
         }
         Ok(())
     }
@@ -53,6 +69,8 @@ impl Default for CachePerformanceConfig {
             max_entries: Self::default_max_entries(),
             ttl_seconds: Self::default_ttl_seconds(),
             warmup_enabled: false,
+            persist_enabled: false,
+            sliding_expiration: false,
         }
     }
 }
@@ -76,6 +94,12 @@ pub struct PoolPerformanceConfig {
     /// 最大生命周期（秒）
     #[serde(default = "PoolPerformanceConfig::default_max_lifetime_secs")]
     pub max_lifetime_secs: u64,
+    /// 历史查询按标签拆分的并行路数（1 表示不拆分，实际并发度不超过 max_size）
+    #[serde(default = "PoolPerformanceConfig::default_query_parallelism")]
+    pub query_parallelism: u32,
+    /// 同时建立新连接的最大数量（0 表示不限制），冷启动或重连时建连排队进行
+    #[serde(default = "PoolPerformanceConfig::default_max_concurrent_connects")]
+    pub max_concurrent_connects: u32,
 }
 
 impl PoolPerformanceConfig {
@@ -99,6 +123,14 @@ impl PoolPerformanceConfig {
         900 // 15 分钟
     }
 
+    fn default_query_parallelism() -> u32 {
+        1 // 默认不拆分，避免增加数据库负载
+    }
+
+    fn default_max_concurrent_connects() -> u32 {
+        1 // 串行建连，避免冷启动惊群
+    }
+
     /// 验证配置有效性
     pub fn validate(&self) -> Result<(), String> {
         if self.max_size < 1 {
@@ -116,6 +148,12 @@ impl PoolPerformanceConfig {
         if self.connection_timeout_secs > 60 {
             return Err("connection_timeout_secs 最大值为 60 秒".to_string());
         }
+        if self.query_parallelism < 1 {
+            return Err("query_parallelism 最小值为 1".to_string());
+        }
+        if self.max_concurrent_connects > self.max_size {
+            return Err("max_concurrent_connects 不能大于 max_size（0 表示不限制）".to_string());
+        }
         Ok(())
     }
 }
@@ -128,6 +166,8 @@ impl Default for PoolPerformanceConfig {
             connection_timeout_secs: Self::default_connection_timeout_secs(),
             idle_timeout_secs: Self::default_idle_timeout_secs(),
             max_lifetime_secs: Self::default_max_lifetime_secs(),
+            query_parallelism: Self::default_query_parallelism(),
+            max_concurrent_connects: Self::default_max_concurrent_connects(),
         }
     }
 }
@@ -221,6 +261,199 @@ impl Default for ChartPerformanceConfig {
     }
 }
 
+/// 查询限流配置
+///
+/// 限制同时执行的数据库查询数，高峰时段（如白天生产）可使用更低的限额。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ThrottlePerformanceConfig {
+    /// 是否启用限流（默认关闭）
+    #[serde(default)]
+    pub enabled: bool,
+    /// 平时最大并发查询数
+    #[serde(default = "ThrottlePerformanceConfig::default_max_concurrent")]
+    pub max_concurrent: usize,
+    /// 高峰时段最大并发查询数
+    #[serde(default = "ThrottlePerformanceConfig::default_peak_max_concurrent")]
+    pub peak_max_concurrent: usize,
+    /// 高峰开始小时（0-23，含），未设置时不区分时段
+    #[serde(default)]
+    pub peak_start_hour: Option<u32>,
+    /// 高峰结束小时（0-23，不含），小于开始小时表示跨零点
+    #[serde(default)]
+    pub peak_end_hour: Option<u32>,
+    /// 排队超时（秒），超时返回"请稍后"；0 表示一直等待
+    #[serde(default = "ThrottlePerformanceConfig::default_queue_timeout_secs")]
+    pub queue_timeout_secs: u64,
+}
+
+impl ThrottlePerformanceConfig {
+    fn default_max_concurrent() -> usize {
+        3
+    }
+
+    fn default_peak_max_concurrent() -> usize {
+        1
+    }
+
+    fn default_queue_timeout_secs() -> u64 {
+        30
+    }
+
+    /// 指定小时是否处于高峰时段
+    pub fn is_peak_hour(&self, hour: u32) -> bool {
+        match (self.peak_start_hour, self.peak_end_hour) {
+            (Some(start), Some(end)) if start <= end => hour >= start && hour < end,
+            (Some(start), Some(end)) => hour >= start || hour < end,
+            _ => false,
+        }
+    }
+
+    /// 验证配置有效性
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_concurrent < 1 || self.peak_max_concurrent < 1 {
+            return Err("max_concurrent / peak_max_concurrent 最小值为 1".to_string());
+        }
+        if self.peak_start_hour.is_some() != self.peak_end_hour.is_some() {
+            return Err("peak_start_hour 和 peak_end_hour 需同时设置".to_string());
+        }
+        if self.peak_start_hour.is_some_and(|h| h > 23)
+            || self.peak_end_hour.is_some_and(|h| h > 23)
+        {
+            return Err("高峰时段小时取值范围为 0-23".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Default for ThrottlePerformanceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_concurrent: Self::default_max_concurrent(),
+            peak_max_concurrent: Self::default_peak_max_concurrent(),
+            peak_start_hour: None,
+            peak_end_hour: None,
+            queue_timeout_secs: Self::default_queue_timeout_secs(),
+        }
+    }
+}
+
+/// 数据库连接断路器配置
+///
+/// 连续多次连接失败后断开一段时间，期间查询直接失败，不再等待连接超时。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CircuitBreakerPerformanceConfig {
+    /// 是否启用断路器
+    #[serde(default = "CircuitBreakerPerformanceConfig::default_enabled")]
+    pub enabled: bool,
+    /// 连续连接失败多少次后断开
+    #[serde(default = "CircuitBreakerPerformanceConfig::default_failure_threshold")]
+    pub failure_threshold: u32,
+    /// 断开后的冷却时间（秒），冷却结束后放行一次试探查询
+    #[serde(default = "CircuitBreakerPerformanceConfig::default_cooldown_secs")]
+    pub cooldown_secs: u64,
+}
+
+impl CircuitBreakerPerformanceConfig {
+    fn default_enabled() -> bool {
+        true
+    }
+
+    fn default_failure_threshold() -> u32 {
+        3
+    }
+
+    fn default_cooldown_secs() -> u64 {
+        30
+    }
+
+    /// 验证配置有效性
+    pub fn validate(&self) -> Result<(), String> {
+        if self.failure_threshold < 1 {
+            return Err("failure_threshold 最小值为 1".to_string());
+        }
+        if self.cooldown_secs < 1 || self.cooldown_secs > 600 {
+            return Err("cooldown_secs 取值范围为 1-600 秒".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Default for CircuitBreakerPerformanceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: Self::default_enabled(),
+            failure_threshold: Self::default_failure_threshold(),
+            cooldown_secs: Self::default_cooldown_secs(),
+        }
+    }
+}
+
+/// 数据库连通性监控配置
+///
+/// 后台定期执行 `SELECT 1` 检查连通性；检查失败后改用较短的间隔重试，尽快发现恢复。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionMonitorPerformanceConfig {
+    /// 是否启用连通性监控
+    #[serde(default = "ConnectionMonitorPerformanceConfig::default_enabled")]
+    pub enabled: bool,
+    /// 连通时的检查间隔（秒）
+    #[serde(default = "ConnectionMonitorPerformanceConfig::default_interval_secs")]
+    pub interval_secs: u64,
+    /// 检查失败后的重试间隔（秒）
+    #[serde(default = "ConnectionMonitorPerformanceConfig::default_retry_interval_secs")]
+    pub retry_interval_secs: u64,
+    /// 连续失败多少次后判定为断开（之前为降级）
+    #[serde(default = "ConnectionMonitorPerformanceConfig::default_failure_threshold")]
+    pub failure_threshold: u32,
+}
+
+impl ConnectionMonitorPerformanceConfig {
+    fn default_enabled() -> bool {
+        true
+    }
+
+    fn default_interval_secs() -> u64 {
+        60
+    }
+
+    fn default_retry_interval_secs() -> u64 {
+        5
+    }
+
+    fn default_failure_threshold() -> u32 {
+        3
+    }
+
+    /// 验证配置有效性
+    pub fn validate(&self) -> Result<(), String> {
+        if self.interval_secs < 5 || self.interval_secs > 3600 {
+            return Err("interval_secs 取值范围为 5-3600 秒".to_string());
+        }
+        if self.retry_interval_secs < 1 || self.retry_interval_secs > self.interval_secs {
+            return Err("retry_interval_secs 取值范围为 1 秒到 interval_secs".to_string());
+        }
+        if self.failure_threshold < 1 {
+            return Err("failure_threshold 最小值为 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Default for ConnectionMonitorPerformanceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: Self::default_enabled(),
+            interval_secs: Self::default_interval_secs(),
+            retry_interval_secs: Self::default_retry_interval_secs(),
+            failure_threshold: Self::default_failure_threshold(),
+        }
+    }
+}
+
 /// 综合性能配置
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -237,6 +470,15 @@ pub struct PerformanceConfig {
     /// 图表渲染配置
     #[serde(default)]
     pub chart: ChartPerformanceConfig,
+    /// 查询限流配置
+    #[serde(default)]
+    pub throttle: ThrottlePerformanceConfig,
+    /// 数据库连接断路器配置
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerPerformanceConfig,
+    /// 数据库连通性监控配置
+    #[serde(default)]
+    pub connection_monitor: ConnectionMonitorPerformanceConfig,
 }
 
 impl PerformanceConfig {
@@ -246,6 +488,9 @@ impl PerformanceConfig {
         self.pool.validate()?;
         self.processing.validate()?;
         self.chart.validate()?;
+        self.throttle.validate()?;
+        self.circuit_breaker.validate()?;
+        self.connection_monitor.validate()?;
         debug!(target: "industry_vis::config", "性能配置验证通过");
         Ok(())
     }
@@ -262,6 +507,8 @@ impl PerformanceConfig {
                 max_entries: 500,
                 ttl_seconds: 3600,
                 warmup_enabled: true,
+                persist_enabled: true,
+                sliding_expiration: false,
             },
             pool: PoolPerformanceConfig {
                 max_size: 5,
@@ -269,6 +516,8 @@ impl PerformanceConfig {
                 connection_timeout_secs: 10,
                 idle_timeout_secs: 600,
                 max_lifetime_secs: 1800,
+                query_parallelism: 3,
+                max_concurrent_connects: 2,
             },
             processing: ProcessingPerformanceConfig {
                 use_unified_pipeline: true,
@@ -279,6 +528,9 @@ impl PerformanceConfig {
                 large_threshold: 1000,
                 progressive_threshold: 2000,
             },
+            throttle: ThrottlePerformanceConfig::default(),
+            circuit_breaker: CircuitBreakerPerformanceConfig::default(),
+            connection_monitor: ConnectionMonitorPerformanceConfig::default(),
         }
     }
 
@@ -289,6 +541,8 @@ impl PerformanceConfig {
                 max_entries: 50,
                 ttl_seconds: 600,
                 warmup_enabled: false,
+                persist_enabled: false,
+                sliding_expiration: false,
             },
             pool: PoolPerformanceConfig {
                 max_size: 1,
@@ -296,6 +550,8 @@ impl PerformanceConfig {
                 connection_timeout_secs: 30,
                 idle_timeout_secs: 120,
                 max_lifetime_secs: 300,
+                query_parallelism: 1,
+                max_concurrent_connects: 1,
             },
             processing: ProcessingPerformanceConfig {
                 use_unified_pipeline: true,
@@ -306,6 +562,9 @@ impl PerformanceConfig {
                 large_threshold: 5000,
                 progressive_threshold: 8000,
             },
+            throttle: ThrottlePerformanceConfig::default(),
+            circuit_breaker: CircuitBreakerPerformanceConfig::default(),
+            connection_monitor: ConnectionMonitorPerformanceConfig::default(),
         }
     }
 }
@@ -349,6 +608,34 @@ mod tests {
         config.max_size = 3;
         config.min_idle = 5;
         assert!(config.validate().is_err());
+
+        config.min_idle = 1;
+        config.max_concurrent_connects = 4;
+        assert!(config.validate().is_err());
+        config.max_concurrent_connects = 0;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_throttle_peak_hours() {
+        let mut config = ThrottlePerformanceConfig {
+            peak_start_hour: Some(8),
+            peak_end_hour: Some(18),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+        assert!(config.is_peak_hour(8));
+        assert!(!config.is_peak_hour(18));
+
+        // 跨零点的夜班时段
+        config.peak_start_hour = Some(22);
+        config.peak_end_hour = Some(6);
+        assert!(config.is_peak_hour(23) && config.is_peak_hour(2));
+        assert!(!config.is_peak_hour(12));
+
+        config.peak_end_hour = None;
+        assert!(config.validate().is_err());
+        assert!(!ThrottlePerformanceConfig::default().is_peak_hour(10));
     }
 
     #[test]
@@ -357,6 +644,7 @@ mod tests {
         assert_eq!(config.cache.max_entries, 500);
         assert_eq!(config.pool.max_size, 5);
         assert!(config.cache.warmup_enabled);
+        assert!(!config.cache.sliding_expiration);
     }
 
     #[test]
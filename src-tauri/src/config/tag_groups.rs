@@ -3,16 +3,25 @@
 use chrono::Local;
 use std::fs;
 use std::path::PathBuf;
-use tracing::{debug, info};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
 
+use super::ConfigMode;
 use crate::error::{AppError, AppResult};
-use crate::models::{ChartConfig, DataProcessingConfig, TagGroup, TagGroupConfig};
+use crate::models::{
+    Annotation, BatchApplyResult, ChartConfig, DataProcessingConfig, GroupUsageStats, TagGroup,
+    TagGroupConfig, TagMetadata,
+};
 
 /// 标签分组配置管理器
 #[derive(Debug)]
 pub struct TagGroupConfigManager {
     config: TagGroupConfig,
     config_path: PathBuf,
+    /// 最近一次因使用统计写盘的时间（用于节流）
+    last_usage_save: Option<Instant>,
+    /// 是否有尚未写盘的使用统计
+    usage_dirty: bool,
 }
 
 impl TagGroupConfigManager {
@@ -20,6 +29,50 @@ impl TagGroupConfigManager {
     const CONFIG_FILENAME: &'static str = "tag_groups_v2.toml";
     /// 旧版配置文件名
     const LEGACY_CONFIG_FILENAME: &'static str = "tag_groups.toml";
+    /// 使用统计文件名（与分组配置同目录，单独保存以免统计写盘触发配置热更新）
+    const USAGE_FILENAME: &'static str = "tag_group_usage.json";
+    /// 使用统计写盘的最小间隔
+    const USAGE_SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+    fn with_config(config: TagGroupConfig, config_path: PathBuf) -> Self {
+        let mut manager = Self {
+            config,
+            config_path,
+            last_usage_save: None,
+            usage_dirty: false,
+        };
+        manager.load_usage();
+        manager
+    }
+
+    /// 使用统计文件路径
+    fn usage_path(&self) -> PathBuf {
+        self.config_path.with_file_name(Self::USAGE_FILENAME)
+    }
+
+    /// 从使用统计文件恢复各分组的访问次数和最近访问时间（文件不存在或损坏时保留配置中的值）
+    fn load_usage(&mut self) {
+        let path = self.usage_path();
+        if !path.exists() {
+            return;
+        }
+        let stats: Vec<GroupUsageStats> = match fs::read_to_string(&path)
+            .map_err(AppError::from)
+            .and_then(|content| Ok(serde_json::from_str::<Vec<GroupUsageStats>>(&content)?))
+        {
+            Ok(stats) => stats,
+            Err(e) => {
+                warn!(target: "industry_vis::tag_group", "读取分组使用统计失败: {:?}, {}", path, e);
+                return;
+            }
+        };
+        for stat in stats {
+            if let Some(group) = self.get_group_mut(&stat.id) {
+                group.access_count = stat.access_count;
+                group.last_accessed = stat.last_accessed;
+            }
+        }
+    }
 
     /// 获取 exe 同目录的配置路径（便携模式）
     fn portable_config_path() -> Option<PathBuf> {
@@ -55,49 +108,18 @@ impl TagGroupConfigManager {
         None
     }
 
-    /// 获取配置文件路径
+    /// 获取配置文件路径（遵循 `IV_CONFIG_MODE`，与应用配置一致）
     pub fn config_path() -> AppResult<PathBuf> {
-        // 优先检查 exe 同目录是否已有配置（便携模式）
-        if let Some(portable_path) = Self::portable_config_path()
-            && portable_path.exists()
-        {
-            return Ok(portable_path);
-        }
-
-        // 检查 AppData 是否已有配置
-        if let Some(appdata_path) = Self::appdata_config_path()
-            && appdata_path.exists()
-        {
-            return Ok(appdata_path);
-        }
-
-        // 都不存在时，默认使用 exe 同目录
-        Self::portable_config_path()
-            .ok_or_else(|| AppError::Config("无法确定配置文件路径".to_string()))
+        ConfigMode::from_env().load_path(Self::portable_config_path(), Self::appdata_config_path())
     }
 
     /// 获取保存配置的路径
     fn save_config_path() -> AppResult<PathBuf> {
-        // 优先尝试 exe 同目录（便携模式）
-        if let Some(portable_path) = Self::portable_config_path()
-            && let Some(parent) = portable_path.parent()
-        {
-            let test_file = parent.join(".tag_groups_write_test");
-            if fs::write(&test_file, "test").is_ok() {
-                let _ = fs::remove_file(&test_file);
-                return Ok(portable_path);
-            }
-        }
-
-        // 不可写时使用 AppData
-        if let Some(appdata_path) = Self::appdata_config_path() {
-            if let Some(parent) = appdata_path.parent() {
-                let _ = fs::create_dir_all(parent);
-            }
-            return Ok(appdata_path);
-        }
-
-        Err(AppError::Config("无法找到可写的配置文件路径".to_string()))
+        ConfigMode::from_env().save_path(
+            Self::portable_config_path(),
+            Self::appdata_config_path(),
+            ".tag_groups_write_test",
+        )
     }
 
     /// 从文件加载配置
@@ -114,10 +136,7 @@ impl TagGroupConfigManager {
             // 尝试从旧版配置迁移
             if let Some(migrated) = Self::migrate_from_legacy()? {
                 info!(target: "industry_vis::tag_group", "从旧版配置迁移了 {} 个分组", migrated.groups.len());
-                let manager = Self {
-                    config: migrated,
-                    config_path: Self::save_config_path()?,
-                };
+                let manager = Self::with_config(migrated, Self::save_config_path()?);
                 manager.save()?;
                 return Ok(manager);
             }
@@ -125,10 +144,7 @@ impl TagGroupConfigManager {
             TagGroupConfig::new()
         };
 
-        Ok(Self {
-            config,
-            config_path: path,
-        })
+        Ok(Self::with_config(config, path))
     }
 
     /// 从指定路径加载
@@ -136,15 +152,9 @@ impl TagGroupConfigManager {
         if path.exists() {
             let content = fs::read_to_string(path)?;
             let config: TagGroupConfig = toml::from_str(&content)?;
-            Ok(Self {
-                config,
-                config_path: path.clone(),
-            })
+            Ok(Self::with_config(config, path.clone()))
         } else {
-            Ok(Self {
-                config: TagGroupConfig::new(),
-                config_path: path.clone(),
-            })
+            Ok(Self::with_config(TagGroupConfig::new(), path.clone()))
         }
     }
 
@@ -204,7 +214,10 @@ impl TagGroupConfigManager {
             })
             .collect();
 
-        Ok(Some(TagGroupConfig { version: 1, groups }))
+        Ok(Some(TagGroupConfig {
+            groups,
+            ..TagGroupConfig::new()
+        }))
     }
 
     /// 保存配置到文件
@@ -217,11 +230,68 @@ impl TagGroupConfigManager {
         Ok(())
     }
 
-    /// 重新加载配置
+    /// 记录分组被访问（查询）
+    ///
+    /// 使用统计写入单独的统计文件且有节流：距上次统计写盘不足 `USAGE_SAVE_INTERVAL` 时
+    /// 只更新内存，下一次 `flush_usage`（包括退出时）一并保存。
+    pub fn record_access(&mut self, id: &str) -> AppResult<()> {
+        let group = self
+            .get_group_mut(id)
+            .ok_or_else(|| AppError::NotFound(format!("分组 '{}' 不存在", id)))?;
+        group.record_access();
+        self.usage_dirty = true;
+
+        let due = self
+            .last_usage_save
+            .is_none_or(|t| t.elapsed() >= Self::USAGE_SAVE_INTERVAL);
+        if due {
+            self.flush_usage()?;
+        }
+        Ok(())
+    }
+
+    /// 将尚未写盘的使用统计保存到使用统计文件
+    pub fn flush_usage(&mut self) -> AppResult<()> {
+        if self.usage_dirty {
+            let path = self.usage_path();
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, serde_json::to_string(&self.usage_stats())?)?;
+            self.usage_dirty = false;
+            self.last_usage_save = Some(Instant::now());
+        }
+        Ok(())
+    }
+
+    /// 获取分组使用统计（按访问次数降序，次数相同按最近访问时间降序）
+    pub fn usage_stats(&self) -> Vec<GroupUsageStats> {
+        let mut stats: Vec<GroupUsageStats> = self
+            .config
+            .groups
+            .iter()
+            .map(GroupUsageStats::from)
+            .collect();
+        stats.sort_by(|a, b| {
+            b.access_count
+                .cmp(&a.access_count)
+                .then_with(|| b.last_accessed.cmp(&a.last_accessed))
+        });
+        stats
+    }
+
+    /// 重新加载配置（保留内存中的使用统计）
     pub fn reload(&mut self) -> AppResult<()> {
         if self.config_path.exists() {
             let content = fs::read_to_string(&self.config_path)?;
-            self.config = toml::from_str(&content)?;
+            let mut config: TagGroupConfig = toml::from_str(&content)?;
+            for group in &mut config.groups {
+                if let Some(current) = self.get_group(&group.id) {
+                    group.access_count = current.access_count;
+                    group.last_accessed = current.last_accessed.clone();
+                }
+            }
+            self.config = config;
             info!(target: "industry_vis::tag_group", "重新加载配置，{} 个分组", self.config.groups.len());
         }
         Ok(())
@@ -242,15 +312,28 @@ impl TagGroupConfigManager {
         self.config.groups.iter_mut().find(|g| g.id == id)
     }
 
-    /// 创建新分组
-    pub fn create_group(&mut self, name: String, charts: Vec<ChartConfig>) -> AppResult<TagGroup> {
-        // 检查名称是否重复
-        if self.config.groups.iter().any(|g| g.name == name.trim()) {
+    /// 检查分组名称是否与其他分组重复
+    ///
+    /// `exclude_id` 为正在更新的分组 ID（不与自身比较）。
+    fn ensure_unique_name(&self, name: &str, exclude_id: Option<&str>) -> AppResult<()> {
+        if self
+            .config
+            .groups
+            .iter()
+            .any(|g| Some(g.id.as_str()) != exclude_id && self.config.names_conflict(&g.name, name))
+        {
             return Err(AppError::Validation(format!(
                 "分组名称 '{}' 已存在",
                 name.trim()
             )));
         }
+        Ok(())
+    }
+
+    /// 创建新分组
+    pub fn create_group(&mut self, name: String, charts: Vec<ChartConfig>) -> AppResult<TagGroup> {
+        // 检查名称是否重复
+        self.ensure_unique_name(&name, None)?;
 
         let group = TagGroup::new(name, charts).map_err(AppError::Validation)?;
         let result = group.clone();
@@ -260,6 +343,62 @@ impl TagGroupConfigManager {
         Ok(result)
     }
 
+    /// 导入完整分组（如由仪表盘定义还原），名称不能与已有分组重复
+    pub fn import_group(&mut self, group: TagGroup) -> AppResult<TagGroup> {
+        self.ensure_unique_name(&group.name, None)?;
+
+        let result = group.clone();
+        self.config.groups.push(group);
+        self.save()?;
+
+        Ok(result)
+    }
+
+    /// 批量导入分组（如从其他机器导出的分组）
+    ///
+    /// 名称不能与已有分组或同批分组重复，任一冲突时整批不导入；
+    /// ID 与已有分组冲突时重新生成。
+    pub fn import_groups(&mut self, groups: Vec<TagGroup>) -> AppResult<Vec<TagGroup>> {
+        let mut imported: Vec<TagGroup> = Vec::with_capacity(groups.len());
+        for mut group in groups {
+            if group.charts.len() > 10 {
+                return Err(AppError::Validation(format!(
+                    "分组 '{}' 超过 10 个图表",
+                    group.name
+                )));
+            }
+            for chart in &group.charts {
+                chart.validate().map_err(AppError::Validation)?;
+            }
+            self.ensure_unique_name(&group.name, None)?;
+            if imported
+                .iter()
+                .any(|g| self.config.names_conflict(&g.name, &group.name))
+            {
+                return Err(AppError::Validation(format!(
+                    "导入的分组名称 '{}' 重复",
+                    group.name.trim()
+                )));
+            }
+
+            let id_taken =
+                |id: &str| self.get_group(id).is_some() || imported.iter().any(|g| g.id == id);
+            if group.id.is_empty() || id_taken(&group.id) {
+                let mut millis = Local::now().timestamp_millis();
+                while id_taken(&format!("g{}", millis)) {
+                    millis += 1;
+                }
+                group.id = format!("g{}", millis);
+            }
+            imported.push(group);
+        }
+
+        self.config.groups.extend(imported.iter().cloned());
+        self.save()?;
+
+        Ok(imported)
+    }
+
     /// 更新分组
     pub fn update_group(
         &mut self,
@@ -269,17 +408,7 @@ impl TagGroupConfigManager {
         processing_config: Option<DataProcessingConfig>,
     ) -> AppResult<TagGroup> {
         // 检查名称是否与其他分组重复
-        if self
-            .config
-            .groups
-            .iter()
-            .any(|g| g.id != id && g.name == name.trim())
-        {
-            return Err(AppError::Validation(format!(
-                "分组名称 '{}' 已存在",
-                name.trim()
-            )));
-        }
+        self.ensure_unique_name(&name, Some(id))?;
 
         let group = self
             .get_group_mut(id)
@@ -294,6 +423,182 @@ impl TagGroupConfigManager {
         Ok(result)
     }
 
+    /// 部分更新分组（只更新提供的字段）
+    pub fn patch_group(
+        &mut self,
+        id: &str,
+        name: Option<String>,
+        charts: Option<Vec<ChartConfig>>,
+        processing_config: Option<DataProcessingConfig>,
+    ) -> AppResult<TagGroup> {
+        if let Some(ref name) = name {
+            self.ensure_unique_name(name, Some(id))?;
+        }
+
+        let group = self
+            .get_group_mut(id)
+            .ok_or_else(|| AppError::NotFound(format!("分组 '{}' 不存在", id)))?;
+
+        group
+            .patch(name, charts, processing_config)
+            .map_err(AppError::Validation)?;
+        let result = group.clone();
+        self.save()?;
+
+        Ok(result)
+    }
+
+    /// 批量应用处理配置到多个分组（锁定的分组跳过），有更新时一次性保存
+    pub fn apply_processing_to_groups(
+        &mut self,
+        group_ids: &[String],
+        config: &DataProcessingConfig,
+    ) -> AppResult<BatchApplyResult> {
+        let result = self.apply_processing_in_memory(group_ids, config);
+        if !result.updated.is_empty() {
+            self.save()?;
+        }
+        Ok(result)
+    }
+
+    /// 批量应用处理配置（仅修改内存）
+    fn apply_processing_in_memory(
+        &mut self,
+        group_ids: &[String],
+        config: &DataProcessingConfig,
+    ) -> BatchApplyResult {
+        let now = Local::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+        let mut result = BatchApplyResult::default();
+
+        for id in group_ids {
+            // 同一 ID 重复出现只处理一次
+            if result.updated.contains(id)
+                || result.skipped_locked.contains(id)
+                || result.not_found.contains(id)
+            {
+                continue;
+            }
+            match self.get_group_mut(id) {
+                Some(group) if group.locked => result.skipped_locked.push(id.clone()),
+                Some(group) => {
+                    group.processing_config = config.clone();
+                    group.updated_at = now.clone();
+                    result.updated.push(id.clone());
+                }
+                None => result.not_found.push(id.clone()),
+            }
+        }
+
+        result
+    }
+
+    /// 设置分组锁定状态
+    pub fn set_group_locked(&mut self, id: &str, locked: bool) -> AppResult<TagGroup> {
+        let group = self
+            .get_group_mut(id)
+            .ok_or_else(|| AppError::NotFound(format!("分组 '{}' 不存在", id)))?;
+        group.locked = locked;
+        let result = group.clone();
+        self.save()?;
+        Ok(result)
+    }
+
+    /// 设置分组的标签元数据（单位等）
+    pub fn set_tag_metadata(
+        &mut self,
+        id: &str,
+        metadata: Vec<TagMetadata>,
+    ) -> AppResult<TagGroup> {
+        let group = self
+            .get_group_mut(id)
+            .ok_or_else(|| AppError::NotFound(format!("分组 '{}' 不存在", id)))?;
+        group
+            .set_tag_metadata(metadata)
+            .map_err(AppError::Validation)?;
+        let result = group.clone();
+        self.save()?;
+        Ok(result)
+    }
+
+    /// 为分组新增标注区间
+    pub fn add_annotation(
+        &mut self,
+        group_id: &str,
+        annotation: Annotation,
+    ) -> AppResult<Annotation> {
+        let group = self
+            .get_group_mut(group_id)
+            .ok_or_else(|| AppError::NotFound(format!("分组 '{}' 不存在", group_id)))?;
+        let result = group
+            .add_annotation(annotation)
+            .map_err(AppError::Validation)?;
+        self.save()?;
+        Ok(result)
+    }
+
+    /// 更新分组的标注区间
+    pub fn update_annotation(
+        &mut self,
+        group_id: &str,
+        annotation: Annotation,
+    ) -> AppResult<Annotation> {
+        let group = self
+            .get_group_mut(group_id)
+            .ok_or_else(|| AppError::NotFound(format!("分组 '{}' 不存在", group_id)))?;
+        if !group.annotations.iter().any(|a| a.id == annotation.id) {
+            return Err(AppError::NotFound(format!(
+                "标注 '{}' 不存在",
+                annotation.id
+            )));
+        }
+        let result = group
+            .update_annotation(annotation)
+            .map_err(AppError::Validation)?;
+        self.save()?;
+        Ok(result)
+    }
+
+    /// 删除分组的标注区间
+    pub fn remove_annotation(&mut self, group_id: &str, annotation_id: &str) -> AppResult<()> {
+        let group = self
+            .get_group_mut(group_id)
+            .ok_or_else(|| AppError::NotFound(format!("分组 '{}' 不存在", group_id)))?;
+        if !group.remove_annotation(annotation_id) {
+            return Err(AppError::NotFound(format!(
+                "标注 '{}' 不存在",
+                annotation_id
+            )));
+        }
+        self.save()?;
+        Ok(())
+    }
+
+    /// 用快照内容恢复分组
+    ///
+    /// 分组仍存在时整体替换（保留当前的使用统计），已删除时重新加入；
+    /// 名称不能与其他分组重复。
+    pub fn restore_group(&mut self, group: TagGroup) -> AppResult<TagGroup> {
+        let result = self.restore_group_in_memory(group)?;
+        self.save()?;
+        Ok(result)
+    }
+
+    /// 用快照内容恢复分组（仅修改内存）
+    fn restore_group_in_memory(&mut self, mut group: TagGroup) -> AppResult<TagGroup> {
+        self.ensure_unique_name(&group.name, Some(&group.id))?;
+        group.updated_at = Local::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+
+        match self.get_group_mut(&group.id) {
+            Some(current) => {
+                group.access_count = current.access_count;
+                group.last_accessed = current.last_accessed.clone();
+                *current = group.clone();
+            }
+            None => self.config.groups.push(group.clone()),
+        }
+        Ok(group)
+    }
+
     /// 删除分组
     pub fn delete_group(&mut self, id: &str) -> AppResult<()> {
         let idx = self
@@ -315,10 +620,10 @@ mod tests {
     use super::*;
 
     fn create_test_manager() -> TagGroupConfigManager {
-        TagGroupConfigManager {
-            config: TagGroupConfig::new(),
-            config_path: PathBuf::from("/tmp/test_tag_groups.toml"),
-        }
+        TagGroupConfigManager::with_config(
+            TagGroupConfig::new(),
+            PathBuf::from("/tmp/test_tag_groups.toml"),
+        )
     }
 
     #[test]
@@ -345,4 +650,341 @@ mod tests {
         let result = manager.create_group("已存在".to_string(), vec![]);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_duplicate_name_normalized() {
+        let mut manager = create_test_manager();
+        let group = TagGroup::new("Boiler  Room".to_string(), vec![]).unwrap();
+        manager.config.groups.push(group);
+
+        assert!(manager.ensure_unique_name("boiler room", None).is_err());
+        assert!(manager.ensure_unique_name(" BOILER ROOM ", None).is_err());
+        assert!(manager.ensure_unique_name("Boiler Room 2", None).is_ok());
+
+        // 大小写敏感模式下仅规范化空白
+        manager.config.case_insensitive_names = false;
+        assert!(manager.ensure_unique_name("boiler room", None).is_ok());
+        assert!(manager.ensure_unique_name("Boiler Room", None).is_err());
+    }
+
+    #[test]
+    fn test_update_duplicate_name_ignores_case() {
+        let mut manager = create_test_manager();
+        let first = TagGroup::with_id(
+            "g1".to_string(),
+            "Boiler".to_string(),
+            vec![],
+            String::new(),
+            String::new(),
+        );
+        let second = TagGroup::with_id(
+            "g2".to_string(),
+            "Turbine".to_string(),
+            vec![],
+            String::new(),
+            String::new(),
+        );
+        manager.config.groups.extend([first, second]);
+
+        let result = manager.update_group("g2", "boiler".to_string(), vec![], None);
+        assert!(matches!(result, Err(AppError::Validation(_))));
+        // 自身改名为大小写变体不算重名
+        assert!(manager.ensure_unique_name("BOILER", Some("g1")).is_ok());
+    }
+
+    #[test]
+    fn test_patch_duplicate_name_validation() {
+        let mut manager = create_test_manager();
+        let first = TagGroup::with_id(
+            "g1".to_string(),
+            "分组A".to_string(),
+            vec![],
+            String::new(),
+            String::new(),
+        );
+        let second = TagGroup::with_id(
+            "g2".to_string(),
+            "分组B".to_string(),
+            vec![],
+            String::new(),
+            String::new(),
+        );
+        manager.config.groups.extend([first, second]);
+
+        let result = manager.patch_group("g2", Some("分组A".to_string()), None, None);
+        assert!(matches!(result, Err(AppError::Validation(_))));
+
+        let result = manager.patch_group("missing", None, None, None);
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_record_access() {
+        let mut manager = create_test_manager();
+        manager.config.groups.extend([
+            TagGroup::with_id(
+                "g1".to_string(),
+                "分组A".to_string(),
+                vec![],
+                String::new(),
+                String::new(),
+            ),
+            TagGroup::with_id(
+                "g2".to_string(),
+                "分组B".to_string(),
+                vec![],
+                String::new(),
+                String::new(),
+            ),
+        ]);
+        // 刚写过盘，后续访问只更新内存（节流）
+        manager.last_usage_save = Some(Instant::now());
+
+        manager.record_access("g2").unwrap();
+        let first_access = manager.get_group("g2").unwrap().last_accessed.clone();
+        assert!(first_access.is_some());
+
+        manager.record_access("g2").unwrap();
+        manager.record_access("g2").unwrap();
+        manager.record_access("g1").unwrap();
+
+        let group = manager.get_group("g2").unwrap();
+        assert_eq!(group.access_count, 3);
+        assert!(group.last_accessed >= first_access);
+        assert!(manager.usage_dirty);
+
+        let stats = manager.usage_stats();
+        assert_eq!(stats[0].id, "g2");
+        assert_eq!(stats[0].access_count, 3);
+        assert_eq!(stats[1].access_count, 1);
+
+        let result = manager.record_access("missing");
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_update_missing_annotation_not_found() {
+        let mut manager = create_test_manager();
+        manager.config.groups.push(TagGroup::with_id(
+            "g1".to_string(),
+            "分组A".to_string(),
+            vec![],
+            String::new(),
+            String::new(),
+        ));
+
+        let result = manager.update_annotation(
+            "g1",
+            Annotation {
+                id: "missing".to_string(),
+                start: "2024-01-01T00:00:00".to_string(),
+                end: "2024-01-01T01:00:00".to_string(),
+                label: "批次".to_string(),
+                color: None,
+            },
+        );
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_usage_saved_to_separate_file() {
+        let dir = std::env::temp_dir().join(format!(
+            "industry_vis_usage_{}_{}",
+            std::process::id(),
+            Local::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("tag_groups_v2.toml");
+        let mut config = TagGroupConfig::new();
+        config.groups.push(TagGroup::with_id(
+            "g1".to_string(),
+            "分组A".to_string(),
+            vec![],
+            String::new(),
+            String::new(),
+        ));
+        let content = toml::to_string_pretty(&config).unwrap();
+        fs::write(&config_path, &content).unwrap();
+
+        let mut manager = TagGroupConfigManager::load_from(&config_path).unwrap();
+        manager.record_access("g1").unwrap();
+        manager.record_access("g1").unwrap();
+        manager.flush_usage().unwrap();
+
+        // 统计不写入分组配置文件，重新加载后从统计文件恢复
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), content);
+        let reloaded = TagGroupConfigManager::load_from(&config_path).unwrap();
+        assert_eq!(reloaded.get_group("g1").unwrap().access_count, 2);
+
+        // 配置热更新保留内存中的统计
+        manager.reload().unwrap();
+        assert_eq!(manager.get_group("g1").unwrap().access_count, 2);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_restore_group_in_memory() {
+        let mut manager = create_test_manager();
+        let chart = ChartConfig::with_id("c1".to_string(), "温度".to_string())
+            .with_tags(vec!["TT_101".to_string()]);
+        let original = TagGroup::with_id(
+            "g1".to_string(),
+            "分组1".to_string(),
+            vec![chart],
+            "2024-01-01T00:00:00".to_string(),
+            "2024-01-01T00:00:00".to_string(),
+        );
+        manager.config.groups.push(original.clone());
+
+        // 修改分组，期间被访问过
+        let group = manager.get_group_mut("g1").unwrap();
+        group
+            .update("改坏的分组".to_string(), Vec::new(), None)
+            .unwrap();
+        group.access_count = 5;
+
+        let restored = manager.restore_group_in_memory(original.clone()).unwrap();
+        let current = manager.get_group("g1").unwrap();
+        assert_eq!(current, &restored);
+        assert_eq!(current.name, "分组1");
+        assert_eq!(current.charts, original.charts);
+        // 使用统计沿用当前值
+        assert_eq!(current.access_count, 5);
+
+        // 已删除的分组重新加入；名称与其他分组冲突时拒绝
+        manager.config.groups.clear();
+        manager.restore_group_in_memory(original.clone()).unwrap();
+        assert_eq!(manager.list_groups().len(), 1);
+        let mut conflicting = original.clone();
+        conflicting.id = "g2".to_string();
+        assert!(manager.restore_group_in_memory(conflicting).is_err());
+    }
+
+    #[test]
+    fn test_apply_processing_to_groups() {
+        let mut manager = create_test_manager();
+        let group = |id: &str| {
+            TagGroup::with_id(
+                id.to_string(),
+                format!("分组{}", id),
+                vec![],
+                String::new(),
+                String::new(),
+            )
+        };
+        let mut locked = group("g3");
+        locked.locked = true;
+        manager
+            .config
+            .groups
+            .extend([group("g1"), group("g2"), locked]);
+
+        let config = DataProcessingConfig::new()
+            .with_outlier_removal("3sigma")
+            .with_resample(300, "mean");
+        let ids: Vec<String> = ["g1", "g2", "g3", "missing", "g1"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let result = manager.apply_processing_in_memory(&ids, &config);
+
+        assert_eq!(result.updated, vec!["g1", "g2"]);
+        assert_eq!(result.skipped_locked, vec!["g3"]);
+        assert_eq!(result.not_found, vec!["missing"]);
+
+        assert_eq!(manager.get_group("g1").unwrap().processing_config, config);
+        assert_eq!(manager.get_group("g2").unwrap().processing_config, config);
+        assert_eq!(
+            manager.get_group("g3").unwrap().processing_config,
+            DataProcessingConfig::default()
+        );
+        assert!(!manager.get_group("g1").unwrap().updated_at.is_empty());
+    }
+
+    #[test]
+    fn test_import_groups_with_tag_mapping() {
+        use crate::models::{TagAlarm, TagRange};
+        use std::collections::{BTreeSet, HashMap};
+
+        let mut manager = create_test_manager();
+        manager.config.groups.push(TagGroup::with_id(
+            "g1".to_string(),
+            "本地分组".to_string(),
+            vec![],
+            String::new(),
+            String::new(),
+        ));
+
+        let chart = ChartConfig::with_id("c1".to_string(), "温度".to_string())
+            .with_tags(vec![
+                "REMOTE_T1".to_string(),
+                "REMOTE_T2".to_string(),
+                "SHARED".to_string(),
+            ])
+            .with_alarms(vec![TagAlarm {
+                tag: "REMOTE_T1".to_string(),
+                alarm_high: Some(90.0),
+                alarm_low: None,
+            }]);
+        let mut remote = TagGroup::with_id(
+            "g1".to_string(),
+            "远程分组".to_string(),
+            vec![chart],
+            String::new(),
+            String::new(),
+        );
+        remote.processing_config = DataProcessingConfig::new().with_range_limit(
+            vec![TagRange {
+                tag: "REMOTE_T2".to_string(),
+                min: 0.0,
+                max: 100.0,
+            }],
+            "drop",
+        );
+
+        let mapping = HashMap::from([
+            ("REMOTE_T1".to_string(), "TT_101".to_string()),
+            ("REMOTE_T2".to_string(), "TT_102".to_string()),
+        ]);
+        let unmapped = remote.remap_tags(&mapping);
+        assert_eq!(unmapped, vec!["SHARED".to_string()]);
+
+        let imported = manager.import_groups(vec![remote]).unwrap();
+        let group = &imported[0];
+        // ID 与本地分组冲突时重新生成
+        assert_ne!(group.id, "g1");
+        assert_eq!(group.charts[0].tags, vec!["TT_101", "TT_102", "SHARED"]);
+        assert_eq!(group.charts[0].alarms[0].tag, "TT_101");
+        assert_eq!(group.processing_config.range_limit.ranges[0].tag, "TT_102");
+        assert_eq!(manager.config.groups.len(), 2);
+
+        // 名称冲突时整批不导入
+        let duplicate = |name: &str| {
+            TagGroup::with_id(
+                String::new(),
+                name.to_string(),
+                vec![],
+                String::new(),
+                String::new(),
+            )
+        };
+        assert!(
+            manager
+                .import_groups(vec![duplicate("新分组"), duplicate("远程分组")])
+                .is_err()
+        );
+        assert!(
+            manager
+                .import_groups(vec![duplicate("新分组"), duplicate("新分组")])
+                .is_err()
+        );
+        let names: BTreeSet<&str> = manager
+            .config
+            .groups
+            .iter()
+            .map(|g| g.name.as_str())
+            .collect();
+        assert_eq!(names, BTreeSet::from(["本地分组", "远程分组"]));
+    }
 }
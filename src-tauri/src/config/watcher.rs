@@ -6,14 +6,16 @@ use serde::Serialize;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
-use tauri::Emitter;
+use tokio::sync::mpsc::UnboundedSender;
 use tracing::{debug, error, info, warn};
 
 use super::{AppConfig, TagGroupConfigManager};
 use crate::error::{AppError, AppResult};
 
 /// Config change event payload
-#[derive(Clone, Serialize)]
+///
+/// 由命令层转发为 Tauri event `config-changed`。
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct ConfigChangeEvent {
     /// Type of config that changed
     pub config_type: String,
@@ -23,35 +25,59 @@ pub struct ConfigChangeEvent {
     pub error: Option<String>,
 }
 
+/// 配置变更事件类型：应用配置
+pub const CONFIG_TYPE_APP: &str = "app";
+/// 配置变更事件类型：标签分组配置
+pub const CONFIG_TYPE_TAG_GROUPS: &str = "tag_groups";
+
 /// 配置文件监听器
 pub struct ConfigWatcher {
     _watcher: RecommendedWatcher,
 }
 
 impl ConfigWatcher {
-    /// Emit config change event to frontend
-    fn emit_config_change(config_type: &str, success: bool, error: Option<String>) {
-        if let Some(handle) = crate::get_app_handle() {
-            let event = ConfigChangeEvent {
-                config_type: config_type.to_string(),
-                success,
-                error,
-            };
-            if let Err(e) = handle.emit("config-changed", event) {
-                warn!(target: "industry_vis::config_watcher", "Failed to emit config change event: {}", e);
-            }
+    /// 发送配置变更事件（接收端已关闭时仅记录日志）
+    fn emit_config_change(
+        events: &UnboundedSender<ConfigChangeEvent>,
+        config_type: &str,
+        success: bool,
+        error: Option<String>,
+    ) {
+        let event = ConfigChangeEvent {
+            config_type: config_type.to_string(),
+            success,
+            error,
+        };
+        if events.send(event).is_err() {
+            warn!(target: "industry_vis::config_watcher", "配置变更事件接收端已关闭");
         }
     }
 
     /// 创建新的配置监听器
+    ///
+    /// 热重载结果通过 `events` 发出；构造时尚无 AppHandle，由调用方负责转发给前端。
     pub fn new(
         app_config: Arc<RwLock<AppConfig>>,
         tag_group_manager: Arc<RwLock<TagGroupConfigManager>>,
+        events: UnboundedSender<ConfigChangeEvent>,
     ) -> AppResult<Self> {
-        // 获取需要监听的路径
-        let app_config_path = AppConfig::config_path().ok();
-        let tag_group_path = TagGroupConfigManager::config_path().ok();
+        Self::with_paths(
+            app_config,
+            tag_group_manager,
+            AppConfig::config_path().ok(),
+            TagGroupConfigManager::config_path().ok(),
+            events,
+        )
+    }
 
+    /// 使用指定的配置文件路径创建监听器
+    pub fn with_paths(
+        app_config: Arc<RwLock<AppConfig>>,
+        tag_group_manager: Arc<RwLock<TagGroupConfigManager>>,
+        app_config_path: Option<PathBuf>,
+        tag_group_path: Option<PathBuf>,
+        events: UnboundedSender<ConfigChangeEvent>,
+    ) -> AppResult<Self> {
         // 为闭包克隆路径
         let app_config_path_clone = app_config_path.clone();
         let tag_group_path_clone = tag_group_path.clone();
@@ -69,6 +95,7 @@ impl ConfigWatcher {
                         &tag_group_clone,
                         app_config_path_clone.as_ref(),
                         tag_group_path_clone.as_ref(),
+                        &events,
                     );
                 }
                 Err(e) => {
@@ -118,6 +145,7 @@ impl ConfigWatcher {
         tag_group_manager: &Arc<RwLock<TagGroupConfigManager>>,
         app_config_path: Option<&PathBuf>,
         tag_group_path: Option<&PathBuf>,
+        events: &UnboundedSender<ConfigChangeEvent>,
     ) {
         use notify::EventKind;
 
@@ -134,16 +162,16 @@ impl ConfigWatcher {
                 && path == app_path
             {
                 info!(target: "industry_vis::config_watcher", "应用配置已变更，重新加载");
-                match AppConfig::load() {
+                match AppConfig::load_from(app_path) {
                     Ok(new_config) => {
                         *app_config.write() = new_config;
                         info!(target: "industry_vis::config_watcher", "应用配置重新加载成功");
-                        Self::emit_config_change("app", true, None);
+                        Self::emit_config_change(events, CONFIG_TYPE_APP, true, None);
                     }
                     Err(e) => {
                         let err_msg = format!("{}", e);
                         warn!(target: "industry_vis::config_watcher", "重新加载应用配置失败: {}", e);
-                        Self::emit_config_change("app", false, Some(err_msg));
+                        Self::emit_config_change(events, CONFIG_TYPE_APP, false, Some(err_msg));
                     }
                 }
             }
@@ -157,10 +185,10 @@ impl ConfigWatcher {
                 if let Err(e) = manager.reload() {
                     let err_msg = format!("{}", e);
                     warn!(target: "industry_vis::config_watcher", "重新加载分组配置失败: {}", e);
-                    Self::emit_config_change("tag_groups", false, Some(err_msg));
+                    Self::emit_config_change(events, CONFIG_TYPE_TAG_GROUPS, false, Some(err_msg));
                 } else {
                     info!(target: "industry_vis::config_watcher", "分组配置重新加载成功");
-                    Self::emit_config_change("tag_groups", true, None);
+                    Self::emit_config_change(events, CONFIG_TYPE_TAG_GROUPS, true, None);
                 }
             }
         }
@@ -169,5 +197,67 @@ impl ConfigWatcher {
 
 #[cfg(test)]
 mod tests {
-    // ConfigWatcher 测试需要文件系统操作，在集成测试中进行
+    use super::*;
+    use crate::models::TagGroupConfig;
+    use notify::EventKind;
+    use notify::event::{AccessKind, CreateKind, DataChange, ModifyKind};
+    use std::fs;
+
+    #[test]
+    fn test_tag_group_change_emits_event() {
+        let dir = std::env::temp_dir().join(format!(
+            "industry_vis_watcher_{}_{}",
+            std::process::id(),
+            chrono::Local::now()
+                .timestamp_nanos_opt()
+                .unwrap_or_default()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let app_path = dir.join("config.toml");
+        let tag_group_path = dir.join("tag_groups_v2.toml");
+        fs::write(
+            &app_path,
+            toml::to_string_pretty(&AppConfig::default()).unwrap(),
+        )
+        .unwrap();
+        let content = toml::to_string_pretty(&TagGroupConfig::new()).unwrap();
+        fs::write(&tag_group_path, &content).unwrap();
+
+        let app_config = Arc::new(RwLock::new(AppConfig::load_from(&app_path).unwrap()));
+        let manager = Arc::new(RwLock::new(
+            TagGroupConfigManager::load_from(&tag_group_path).unwrap(),
+        ));
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+
+        // 直接调用事件处理（不依赖文件系统通知的时序），模拟其他进程修改分组配置
+        let handle = |kind: EventKind| {
+            ConfigWatcher::handle_event(
+                Event::new(kind).add_path(tag_group_path.clone()),
+                &app_config,
+                &manager,
+                Some(&app_path),
+                Some(&tag_group_path),
+                &tx,
+            )
+        };
+        fs::write(&tag_group_path, &content).unwrap();
+        handle(EventKind::Modify(ModifyKind::Data(DataChange::Content)));
+        let event = rx.try_recv().expect("应收到配置变更事件");
+        assert_eq!(event.config_type, CONFIG_TYPE_TAG_GROUPS);
+        assert!(event.success);
+        assert!(event.error.is_none());
+
+        // 非修改 / 创建事件不处理
+        handle(EventKind::Access(AccessKind::Any));
+        assert!(rx.try_recv().is_err());
+
+        // 写入无效内容时重载失败并带错误信息
+        fs::write(&tag_group_path, "[[groups]\n").unwrap();
+        handle(EventKind::Create(CreateKind::File));
+        let event = rx.try_recv().expect("应收到配置变更事件");
+        assert!(!event.success);
+        assert!(event.error.is_some());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
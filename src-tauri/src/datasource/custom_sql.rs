@@ -0,0 +1,245 @@
+//! 自定义 SQL 查询校验与结果映射
+//!
+//! 仅允许 SELECT（含 CTE）语句，且结果必须为固定的四列：
+//! `DateTime, TagName, TagVal, TagQuality`。
+
+use super::profiles::{RecordTimeZone, row_local_time};
+use crate::error::{AppError, AppResult};
+use crate::models::{HistoryRecord, Interner};
+
+/// 自定义 SQL 结果必须返回的列（按顺序）
+pub const CUSTOM_QUERY_COLUMNS: [&str; 4] = ["DateTime", "TagName", "TagVal", "TagQuality"];
+
+/// 禁止出现的关键字（写操作、DDL、执行存储过程、阻塞或修改会话等）
+const FORBIDDEN_KEYWORDS: &[&str] = &[
+    "INSERT",
+    "UPDATE",
+    "DELETE",
+    "MERGE",
+    "DROP",
+    "ALTER",
+    "CREATE",
+    "TRUNCATE",
+    "EXEC",
+    "EXECUTE",
+    "GRANT",
+    "REVOKE",
+    "DENY",
+    "INTO",
+    "BACKUP",
+    "RESTORE",
+    "SHUTDOWN",
+    "DBCC",
+    "OPENROWSET",
+    "OPENQUERY",
+    "OPENDATASOURCE",
+    "BULK",
+    "KILL",
+    "USE",
+    "COMMIT",
+    "ROLLBACK",
+    // 长时间阻塞共享连接或修改会话设置
+    "WAITFOR",
+    "SET",
+];
+
+/// 校验自定义 SQL 是否为只读 SELECT 语句
+///
+/// 简单的关键字黑名单：必须以 SELECT 或 WITH 开头，不得包含写操作关键字、
+/// `xp_`/`sp_` 存储过程或多条语句。字符串字面量和方括号/双引号标识符的内容不参与检查。
+/// 执行时另有事务回滚兜底。
+pub fn validate_custom_sql(sql: &str) -> AppResult<()> {
+    let stripped = strip_quoted(sql)?;
+    let trimmed = stripped.trim().trim_end_matches(';').trim();
+    if trimmed.is_empty() {
+        return Err(AppError::Validation("SQL 不能为空".to_string()));
+    }
+    if trimmed.contains(';') {
+        return Err(AppError::Validation("不允许执行多条语句".to_string()));
+    }
+    if trimmed.contains("--") || trimmed.contains("/*") {
+        return Err(AppError::Validation("SQL 中不允许包含注释".to_string()));
+    }
+
+    let words: Vec<String> = trimmed
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_ascii_uppercase())
+        .collect();
+
+    match words.first().map(String::as_str) {
+        Some("SELECT") | Some("WITH") => {}
+        _ => {
+            return Err(AppError::Validation("仅允许 SELECT 查询语句".to_string()));
+        }
+    }
+
+    if let Some(word) = words.iter().find(|w| {
+        FORBIDDEN_KEYWORDS.contains(&w.as_str()) || w.starts_with("XP_") || w.starts_with("SP_")
+    }) {
+        return Err(AppError::Validation(format!(
+            "SQL 包含不允许的关键字: {}",
+            word
+        )));
+    }
+
+    Ok(())
+}
+
+/// 将 `'...'`、`[...]`、`"..."` 的内容替换为空格（`''`、`]]`、`""` 为转义），引号未闭合时报错
+fn strip_quoted(sql: &str) -> AppResult<String> {
+    let mut stripped = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    while let Some(c) = chars.next() {
+        let close = match c {
+            '\'' => '\'',
+            '[' => ']',
+            '"' => '"',
+            _ => {
+                stripped.push(c);
+                continue;
+            }
+        };
+        loop {
+            match chars.next() {
+                Some(ch) if ch == close => {
+                    if chars.peek() == Some(&close) {
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                Some(_) => {}
+                None => {
+                    return Err(AppError::Validation(
+                        "SQL 中的引号或方括号未闭合".to_string(),
+                    ));
+                }
+            }
+        }
+        stripped.push(' ');
+    }
+    Ok(stripped)
+}
+
+/// 校验结果列是否为固定的四列（列名不区分大小写）
+pub fn check_result_columns(columns: &[&str]) -> AppResult<()> {
+    let matches = columns.len() == CUSTOM_QUERY_COLUMNS.len()
+        && columns
+            .iter()
+            .zip(CUSTOM_QUERY_COLUMNS)
+            .all(|(actual, expected)| actual.eq_ignore_ascii_case(expected));
+
+    if matches {
+        Ok(())
+    } else {
+        Err(AppError::Validation(format!(
+            "查询结果必须依次返回列 {}，实际为 {}",
+            CUSTOM_QUERY_COLUMNS.join(", "),
+            columns.join(", ")
+        )))
+    }
+}
+
+/// 将自定义查询结果行映射为 HistoryRecord
+///
+/// 时间列与历史查询相同，兼容 DATETIME / DATETIME2 / DATETIMEOFFSET（按 `time_zone` 换算）；
+/// 数值列兼容 float/real/int，质量列兼容字符串和整数。
+pub fn map_custom_row(
+    row: &tiberius::Row,
+    time_zone: RecordTimeZone,
+    interner: &mut Interner,
+) -> HistoryRecord {
+    let date_time = row_local_time(row, 0, time_zone);
+
+    let tag_val = row
+        .try_get::<f64, _>(2)
+        .ok()
+        .flatten()
+        .or_else(|| row.try_get::<f32, _>(2).ok().flatten().map(f64::from))
+        .or_else(|| row.try_get::<i32, _>(2).ok().flatten().map(f64::from))
+        .unwrap_or(0.0);
+
+    let tag_quality = row
+        .try_get::<&str, _>(3)
+        .ok()
+        .flatten()
+        .map(|q| q.trim().to_string())
+        .or_else(|| {
+            row.try_get::<i32, _>(3)
+                .ok()
+                .flatten()
+                .map(|q| q.to_string())
+        })
+        .or_else(|| {
+            row.try_get::<i16, _>(3)
+                .ok()
+                .flatten()
+                .map(|q| q.to_string())
+        })
+        .unwrap_or_default();
+
+    HistoryRecord::interned(
+        date_time,
+        row.try_get::<&str, _>(1)
+            .ok()
+            .flatten()
+            .unwrap_or("")
+            .trim(),
+        tag_val,
+        &tag_quality,
+        interner,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_accepts_select() {
+        assert!(
+            validate_custom_sql("SELECT DateTime, TagName, TagVal, TagQuality FROM [历史表]")
+                .is_ok()
+        );
+        assert!(validate_custom_sql("  select * from t where TagName = 'updated_at';  ").is_ok());
+        assert!(validate_custom_sql("WITH x AS (SELECT * FROM t) SELECT * FROM x").is_ok());
+        // 字面量和标识符中的关键字、分号不参与检查
+        assert!(validate_custom_sql("SELECT * FROM t WHERE TagName = 'Set Point'").is_ok());
+        assert!(validate_custom_sql("SELECT * FROM [Use Log] WHERE TagName = 'a;b--''c'").is_ok());
+        assert!(validate_custom_sql("SELECT [Exec]]Time] FROM t").is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_non_select() {
+        let rejected = [
+            "DELETE FROM t",
+            "UPDATE t SET TagVal = 0",
+            "SELECT * INTO backup FROM t",
+            "SELECT 1; DROP TABLE t",
+            "EXEC xp_cmdshell 'dir'",
+            "SELECT * FROM t -- comment",
+            "WITH x AS (SELECT 1) DELETE FROM t",
+            "SELECT * FROM t WAITFOR DELAY '01:00:00'",
+            "WITH x AS (SELECT 1) SELECT * FROM x SET LOCK_TIMEOUT 0",
+            "SELECT 'x'; DELETE FROM t",
+            "SELECT * FROM t WHERE TagName = 'unterminated",
+            "",
+        ];
+        for sql in rejected {
+            assert!(
+                matches!(validate_custom_sql(sql), Err(AppError::Validation(_))),
+                "应拒绝: {}",
+                sql
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_result_columns() {
+        assert!(check_result_columns(&["DateTime", "TagName", "TagVal", "TagQuality"]).is_ok());
+        assert!(check_result_columns(&["datetime", "tagname", "tagval", "tagquality"]).is_ok());
+        assert!(check_result_columns(&["TagName", "DateTime", "TagVal", "TagQuality"]).is_err());
+        assert!(check_result_columns(&["DateTime", "TagName", "TagVal"]).is_err());
+    }
+}
@@ -0,0 +1,406 @@
+//! 主库 + 归档库联合数据源
+//!
+//! 同一机组的历史数据分布在两个库：主库只保留近期数据，归档库保存更早的数据。
+//! 以分界时刻（当前时间减去主库保留天数）路由历史查询：
+//! - 整个范围晚于分界时刻只查主库，早于分界时刻只查归档库；
+//! - 跨越分界时刻时两库各查一段，合并后按 (标签, 时间) 去重（主库优先）并按时间排序。
+//!
+//! 标签列表、搜索等元数据操作合并两库结果，对上层透明。
+
+use async_trait::async_trait;
+use chrono::{Local, NaiveDateTime, TimeDelta};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tracing::debug;
+
+use super::traits::{
+    DataSource, SourceMetadata, TableInfo, TagTableCandidate, keep_latest_per_tag,
+};
+use crate::error::AppResult;
+use crate::models::{HistoryRecord, InternedStr};
+
+/// 路由分界
+#[derive(Debug, Clone)]
+enum Boundary {
+    /// 主库保留时长（分界时刻随当前时间滚动）
+    Retention(TimeDelta),
+    /// 固定分界时刻
+    Fixed(NaiveDateTime),
+}
+
+/// 一次历史查询的路由结果
+#[derive(Debug, Clone, PartialEq)]
+enum Route {
+    Primary,
+    Archive,
+    /// 跨越分界：归档库查 [start, split]，主库查 [split, end]
+    Split(String),
+}
+
+/// 主库 + 归档库联合数据源
+#[derive(Clone)]
+pub struct FederatedSource {
+    primary: Arc<dyn DataSource>,
+    archive: Arc<dyn DataSource>,
+    boundary: Boundary,
+}
+
+impl FederatedSource {
+    /// 创建联合数据源，主库保留最近 `primary_retention_days` 天的数据
+    pub fn new(
+        primary: Arc<dyn DataSource>,
+        archive: Arc<dyn DataSource>,
+        primary_retention_days: u32,
+    ) -> Self {
+        Self {
+            primary,
+            archive,
+            boundary: Boundary::Retention(TimeDelta::days(primary_retention_days as i64)),
+        }
+    }
+
+    /// 创建以固定时刻为分界的联合数据源
+    pub fn with_split_time(
+        primary: Arc<dyn DataSource>,
+        archive: Arc<dyn DataSource>,
+        split: NaiveDateTime,
+    ) -> Self {
+        Self {
+            primary,
+            archive,
+            boundary: Boundary::Fixed(split),
+        }
+    }
+
+    /// 当前的分界时刻
+    fn split_time(&self) -> NaiveDateTime {
+        match &self.boundary {
+            Boundary::Retention(retention) => Local::now().naive_local() - *retention,
+            Boundary::Fixed(split) => *split,
+        }
+    }
+
+    /// 按时间范围决定查询哪个库（时间无法解析时两库都查）
+    fn route(&self, start_time: &str, end_time: &str) -> Route {
+        let split = self.split_time();
+        let split_text = split.format("%Y-%m-%dT%H:%M:%S").to_string();
+        match (parse_query_time(start_time), parse_query_time(end_time)) {
+            (Some(start), _) if start >= split => Route::Primary,
+            (_, Some(end)) if end < split => Route::Archive,
+            _ => Route::Split(split_text),
+        }
+    }
+
+    /// 分别查询两段并合并
+    async fn query_split(
+        &self,
+        table: &str,
+        start_time: &str,
+        end_time: &str,
+        split: &str,
+        tags: Option<&[String]>,
+    ) -> AppResult<Vec<HistoryRecord>> {
+        debug!(target: "industry_vis::datasource",
+            "跨库查询 - 归档库 {} ~ {}, 主库 {} ~ {}",
+            start_time, split, split, end_time
+        );
+        let (archive, primary) = tokio::try_join!(
+            self.archive.query_history(table, start_time, split, tags),
+            self.primary.query_history(table, split, end_time, tags),
+        )?;
+        Ok(merge_records(primary, archive))
+    }
+}
+
+/// 解析查询时间（`T` 或空格分隔，可带毫秒）
+pub(super) fn parse_query_time(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f")
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f"))
+        .ok()
+}
+
+/// 合并两库的记录：按 (标签, 时间) 去重（`preferred` 优先），结果按时间升序
+fn merge_records(preferred: Vec<HistoryRecord>, other: Vec<HistoryRecord>) -> Vec<HistoryRecord> {
+    let mut seen: HashSet<(InternedStr, String)> = HashSet::with_capacity(preferred.len());
+    let mut merged = Vec::with_capacity(preferred.len() + other.len());
+    for record in preferred.into_iter().chain(other) {
+        if seen.insert((record.tag_name.clone(), record.date_time.clone())) {
+            merged.push(record);
+        }
+    }
+    merged.sort_by(|a, b| a.date_time.cmp(&b.date_time));
+    merged
+}
+
+/// 合并两个字符串列表并去重（保持先出现的顺序）
+fn merge_names(first: Vec<String>, second: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    first
+        .into_iter()
+        .chain(second)
+        .filter(|name| seen.insert(name.clone()))
+        .collect()
+}
+
+#[async_trait]
+impl DataSource for FederatedSource {
+    async fn test_connection(&self) -> AppResult<()> {
+        self.primary.test_connection().await?;
+        self.archive.test_connection().await
+    }
+
+    fn metadata(&self) -> &SourceMetadata {
+        self.primary.metadata()
+    }
+
+    async fn list_tables(&self) -> AppResult<Vec<TableInfo>> {
+        self.primary.list_tables().await
+    }
+
+    async fn get_available_tags(&self, table: &str) -> AppResult<Vec<String>> {
+        let (primary, archive) = tokio::try_join!(
+            self.primary.get_available_tags(table),
+            self.archive.get_available_tags(table),
+        )?;
+        let mut tags = merge_names(primary, archive);
+        tags.sort();
+        Ok(tags)
+    }
+
+    async fn discover_tag_table(&self) -> AppResult<Vec<TagTableCandidate>> {
+        self.primary.discover_tag_table().await
+    }
+
+    async fn search_tags(&self, keyword: &str, limit: usize) -> AppResult<Vec<String>> {
+        let (primary, archive) = tokio::try_join!(
+            self.primary.search_tags(keyword, limit),
+            self.archive.search_tags(keyword, limit),
+        )?;
+        let mut tags = merge_names(primary, archive);
+        tags.truncate(limit);
+        Ok(tags)
+    }
+
+    async fn query_history(
+        &self,
+        table: &str,
+        start_time: &str,
+        end_time: &str,
+        tags: Option<&[String]>,
+    ) -> AppResult<Vec<HistoryRecord>> {
+        match self.route(start_time, end_time) {
+            Route::Primary => {
+                self.primary
+                    .query_history(table, start_time, end_time, tags)
+                    .await
+            }
+            Route::Archive => {
+                self.archive
+                    .query_history(table, start_time, end_time, tags)
+                    .await
+            }
+            Route::Split(split) => {
+                self.query_split(table, start_time, end_time, &split, tags)
+                    .await
+            }
+        }
+    }
+
+    async fn count_history(
+        &self,
+        table: &str,
+        start_time: &str,
+        end_time: &str,
+        tags: Option<&[String]>,
+    ) -> AppResult<u64> {
+        match self.route(start_time, end_time) {
+            Route::Primary => {
+                self.primary
+                    .count_history(table, start_time, end_time, tags)
+                    .await
+            }
+            Route::Archive => {
+                self.archive
+                    .count_history(table, start_time, end_time, tags)
+                    .await
+            }
+            Route::Split(split) => {
+                // 预估用途，分界点上的重复记录不做扣除
+                let (archive, primary) = tokio::try_join!(
+                    self.archive.count_history(table, start_time, &split, tags),
+                    self.primary.count_history(table, &split, end_time, tags),
+                )?;
+                Ok(archive + primary)
+            }
+        }
+    }
+
+    async fn query_value_neighbors(
+        &self,
+        table: &str,
+        tag: &str,
+        time: &str,
+    ) -> AppResult<Vec<HistoryRecord>> {
+        // 分界附近的前后点可能分属两库，合并后由插值取最近点
+        let (primary, archive) = tokio::try_join!(
+            self.primary.query_value_neighbors(table, tag, time),
+            self.archive.query_value_neighbors(table, tag, time),
+        )?;
+        Ok(merge_records(primary, archive))
+    }
+
+    async fn query_history_latest(
+        &self,
+        table: &str,
+        start_time: &str,
+        end_time: &str,
+        tags: Option<&[String]>,
+        per_tag_limit: usize,
+    ) -> AppResult<Vec<HistoryRecord>> {
+        match self.route(start_time, end_time) {
+            Route::Primary => {
+                self.primary
+                    .query_history_latest(table, start_time, end_time, tags, per_tag_limit)
+                    .await
+            }
+            Route::Archive => {
+                self.archive
+                    .query_history_latest(table, start_time, end_time, tags, per_tag_limit)
+                    .await
+            }
+            Route::Split(split) => {
+                let (archive, primary) = tokio::try_join!(
+                    self.archive.query_history_latest(
+                        table,
+                        start_time,
+                        &split,
+                        tags,
+                        per_tag_limit
+                    ),
+                    self.primary
+                        .query_history_latest(table, &split, end_time, tags, per_tag_limit),
+                )?;
+                Ok(keep_latest_per_tag(
+                    merge_records(primary, archive),
+                    per_tag_limit,
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasource::MemorySource;
+
+    /// 内存数据源：T1 在每个时刻一条记录（主库值 1.0，归档库值 2.0）
+    fn memory_source(name: &str, times: &[&str]) -> Arc<MemorySource> {
+        let records = times
+            .iter()
+            .map(|t| {
+                HistoryRecord::new(
+                    format!("{}.000", t),
+                    "T1".to_string(),
+                    if name == "primary" { 1.0 } else { 2.0 },
+                    "Good".to_string(),
+                )
+            })
+            .collect();
+        let only = format!("{}_only", name);
+        Arc::new(MemorySource::new(name, records).with_tags(&[&only, "T1"]))
+    }
+
+    fn split() -> NaiveDateTime {
+        parse_query_time("2024-01-10T00:00:00").unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_cross_range_merges_both_sources() {
+        // 主库保留 1-09 起的数据，归档库保存到 1-10（分界附近两库重叠）
+        let primary = memory_source(
+            "primary",
+            &[
+                "2024-01-09T12:00:00",
+                "2024-01-10T00:00:00",
+                "2024-01-11T00:00:00",
+            ],
+        );
+        let archive = memory_source(
+            "archive",
+            &[
+                "2024-01-08T00:00:00",
+                "2024-01-09T12:00:00",
+                "2024-01-10T00:00:00",
+            ],
+        );
+        let source = FederatedSource::with_split_time(primary.clone(), archive.clone(), split());
+
+        let records = source
+            .query_history("历史表", "2024-01-08T00:00:00", "2024-01-12T00:00:00", None)
+            .await
+            .unwrap();
+
+        let times: Vec<&str> = records.iter().map(|r| r.date_time.as_str()).collect();
+        assert_eq!(
+            times,
+            [
+                "2024-01-08T00:00:00.000",
+                "2024-01-09T12:00:00.000",
+                "2024-01-10T00:00:00.000",
+                "2024-01-11T00:00:00.000",
+            ]
+        );
+        // 早于分界取归档库，分界点上的重复记录以主库为准
+        assert_eq!(records[0].tag_val, 2.0);
+        assert_eq!(records[2].tag_val, 1.0);
+
+        assert_eq!(
+            archive.queries(),
+            [(
+                "2024-01-08T00:00:00".to_string(),
+                "2024-01-10T00:00:00".to_string()
+            )]
+        );
+        assert_eq!(
+            primary.queries(),
+            [(
+                "2024-01-10T00:00:00".to_string(),
+                "2024-01-12T00:00:00".to_string()
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_range_on_one_side_queries_single_source() {
+        let primary = memory_source("primary", &["2024-01-11T00:00:00"]);
+        let archive = memory_source("archive", &["2024-01-08T00:00:00"]);
+        let source = FederatedSource::with_split_time(primary.clone(), archive.clone(), split());
+
+        let recent = source
+            .query_history("历史表", "2024-01-10T08:00:00", "2024-01-12T00:00:00", None)
+            .await
+            .unwrap();
+        assert_eq!(recent.len(), 1);
+        assert!(archive.queries().is_empty());
+
+        let old = source
+            .query_history("历史表", "2024-01-07T00:00:00", "2024-01-09T00:00:00", None)
+            .await
+            .unwrap();
+        assert_eq!(old.len(), 1);
+        assert_eq!(primary.queries().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_available_tags_merged() {
+        let source = FederatedSource::with_split_time(
+            memory_source("primary", &[]),
+            memory_source("archive", &[]),
+            split(),
+        );
+        let tags = source.get_available_tags("历史表").await.unwrap();
+        assert_eq!(tags, ["T1", "archive_only", "primary_only"]);
+    }
+}
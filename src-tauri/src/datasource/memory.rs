@@ -0,0 +1,143 @@
+//! 测试用内存数据源
+//!
+//! 按时间范围和标签（不区分大小写）过滤内存中的记录，并记录收到的查询范围。
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+
+use super::federated::parse_query_time;
+use super::traits::{DataSource, SourceMetadata, TableInfo, TagTableCandidate};
+use crate::error::AppResult;
+use crate::models::HistoryRecord;
+
+/// 内存数据源
+pub struct MemorySource {
+    metadata: SourceMetadata,
+    tags: Vec<String>,
+    records: Vec<HistoryRecord>,
+    /// 收到的历史查询时间范围
+    queries: Mutex<Vec<(String, String)>>,
+}
+
+impl MemorySource {
+    /// 创建数据源，标签列表为记录中出现的标签（排序去重）
+    pub fn new(name: &str, records: Vec<HistoryRecord>) -> Self {
+        let mut tags: Vec<String> = records.iter().map(|r| r.tag_name.to_string()).collect();
+        tags.sort();
+        tags.dedup();
+        Self {
+            metadata: SourceMetadata::new(name.to_string(), name.to_string()),
+            tags,
+            records,
+            queries: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// 指定标签列表（可包含没有记录的标签）
+    pub fn with_tags(mut self, tags: &[&str]) -> Self {
+        self.tags = tags.iter().map(|t| t.to_string()).collect();
+        self
+    }
+
+    /// 收到的历史查询时间范围
+    pub fn queries(&self) -> Vec<(String, String)> {
+        self.queries.lock().clone()
+    }
+
+    fn matching(
+        &self,
+        start_time: &str,
+        end_time: &str,
+        tags: Option<&[String]>,
+    ) -> Vec<HistoryRecord> {
+        let (start, end) = (
+            parse_query_time(start_time).unwrap(),
+            parse_query_time(end_time).unwrap(),
+        );
+        self.records
+            .iter()
+            .filter(|r| {
+                let t = parse_query_time(&r.date_time).unwrap();
+                t >= start
+                    && t <= end
+                    && tags.is_none_or(|tags| {
+                        tags.iter()
+                            .any(|tag| tag.eq_ignore_ascii_case(r.tag_name.as_str()))
+                    })
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[async_trait]
+impl DataSource for MemorySource {
+    async fn test_connection(&self) -> AppResult<()> {
+        Ok(())
+    }
+
+    fn metadata(&self) -> &SourceMetadata {
+        &self.metadata
+    }
+
+    async fn list_tables(&self) -> AppResult<Vec<TableInfo>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_available_tags(&self, _table: &str) -> AppResult<Vec<String>> {
+        Ok(self.tags.clone())
+    }
+
+    async fn discover_tag_table(&self) -> AppResult<Vec<TagTableCandidate>> {
+        Ok(Vec::new())
+    }
+
+    async fn search_tags(&self, _keyword: &str, _limit: usize) -> AppResult<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    async fn query_history(
+        &self,
+        _table: &str,
+        start_time: &str,
+        end_time: &str,
+        tags: Option<&[String]>,
+    ) -> AppResult<Vec<HistoryRecord>> {
+        self.queries
+            .lock()
+            .push((start_time.to_string(), end_time.to_string()));
+        Ok(self.matching(start_time, end_time, tags))
+    }
+
+    async fn count_history(
+        &self,
+        _table: &str,
+        start_time: &str,
+        end_time: &str,
+        tags: Option<&[String]>,
+    ) -> AppResult<u64> {
+        Ok(self.matching(start_time, end_time, tags).len() as u64)
+    }
+
+    async fn query_value_neighbors(
+        &self,
+        _table: &str,
+        tag: &str,
+        time: &str,
+    ) -> AppResult<Vec<HistoryRecord>> {
+        let time = parse_query_time(time).unwrap();
+        let mut records: Vec<&HistoryRecord> = self
+            .records
+            .iter()
+            .filter(|r| r.tag_name.eq_ignore_ascii_case(tag))
+            .collect();
+        records.sort_by_key(|r| parse_query_time(&r.date_time));
+        let split = records.partition_point(|r| parse_query_time(&r.date_time).unwrap() <= time);
+        Ok(records[..split]
+            .last()
+            .into_iter()
+            .chain(records.get(split))
+            .map(|r| (*r).clone())
+            .collect())
+    }
+}
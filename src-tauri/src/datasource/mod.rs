@@ -2,14 +2,30 @@
 //!
 //! 提供数据库访问抽象和连接池管理。
 
+mod custom_sql;
+mod federated;
+#[cfg(test)]
+mod memory;
 mod pool;
 mod profiles;
+mod query_plan;
 mod schema_profile;
 mod sqlserver;
 mod traits;
 
-pub use pool::{ConnectionManager, ConnectionPool, PoolConfig, PoolState};
-pub use profiles::{DefaultProfile, ProfileRegistry};
-pub use schema_profile::SchemaProfile;
+pub use custom_sql::{CUSTOM_QUERY_COLUMNS, validate_custom_sql};
+pub use federated::FederatedSource;
+#[cfg(test)]
+pub(crate) use memory::MemorySource;
+pub use pool::{
+    ConnectionManager, ConnectionPool, POOL_SIZE_LOWER, POOL_SIZE_UPPER, PROBE_TIMEOUT_SECS,
+    PoolConfig, PoolSizeSuggestion, PoolState,
+};
+pub use profiles::{DEFAULT_TAG_TABLE, DefaultProfile, ProfileRegistry, WideTableProfile};
+pub use query_plan::QueryPlan;
+pub use schema_profile::{SchemaProfile, TagSearchTerms};
 pub use sqlserver::SqlServerSource;
-pub use traits::{DataSource, SourceMetadata, TableInfo};
+pub use traits::{
+    DataSource, SourceMetadata, TableInfo, TagTableCandidate, keep_latest_per_tag,
+    rank_tag_table_candidates,
+};
@@ -4,18 +4,53 @@
 
 use async_trait::async_trait;
 use bb8::{Pool, PooledConnection};
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::future::Future;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
 use tiberius::{AuthMethod, Client, Config};
 use tokio::net::TcpStream;
+use tokio::sync::Semaphore;
 use tokio_util::compat::{Compat, TokioAsyncWriteCompatExt};
 use tracing::{debug, info};
 
-use crate::config::DatabaseConfig;
+use crate::config::{DatabaseConfig, PoolPerformanceConfig};
 use crate::error::{AppError, AppResult};
 
 /// Tiberius 客户端类型
 pub type TiberiusClient = Client<Compat<TcpStream>>;
 
+/// 连接池中的连接
+///
+/// 会话状态无法确认干净（如回滚失败）时标记为损坏，归还时由连接池丢弃而不是复用。
+pub struct PooledClient {
+    client: TiberiusClient,
+    broken: bool,
+}
+
+impl PooledClient {
+    /// 标记连接已损坏，归还连接池时丢弃
+    pub fn mark_broken(&mut self) {
+        self.broken = true;
+    }
+}
+
+impl std::ops::Deref for PooledClient {
+    type Target = TiberiusClient;
+
+    fn deref(&self) -> &TiberiusClient {
+        &self.client
+    }
+}
+
+impl std::ops::DerefMut for PooledClient {
+    fn deref_mut(&mut self) -> &mut TiberiusClient {
+        &mut self.client
+    }
+}
+
 /// 连接池配置
 #[derive(Debug, Clone)]
 pub struct PoolConfig {
@@ -29,6 +64,11 @@ pub struct PoolConfig {
     pub idle_timeout_secs: Option<u64>,
     /// 最大生命周期（秒）
     pub max_lifetime_secs: Option<u64>,
+    /// 同时建立新连接的最大数量（`None` 不限制）
+    ///
+    /// 冷启动或重连时多个查询同时到来，bb8 会并发建连；限制后建连排队进行，
+    /// 避免对限制连接数的数据库瞬间发起大量登录。
+    pub max_concurrent_connects: Option<u32>,
 }
 
 impl Default for PoolConfig {
@@ -39,6 +79,7 @@ impl Default for PoolConfig {
             connection_timeout_secs: 30,
             idle_timeout_secs: Some(600),  // 10 分钟
             max_lifetime_secs: Some(1800), // 30 分钟
+            max_concurrent_connects: None,
         }
     }
 }
@@ -49,35 +90,133 @@ impl PoolConfig {
         Self {
             max_size: 3, // 支持并发查询（多图表场景）
             min_idle: Some(1),
-            connection_timeout_secs: 15,  // 缩短超时，快速失败
-            idle_timeout_secs: Some(300), // 5 分钟
-            max_lifetime_secs: Some(900), // 15 分钟
+            connection_timeout_secs: 15,      // 缩短超时，快速失败
+            idle_timeout_secs: Some(300),     // 5 分钟
+            max_lifetime_secs: Some(900),     // 15 分钟
+            max_concurrent_connects: Some(1), // 串行建连，避免冷启动惊群
+        }
+    }
+
+    /// 创建连接探测用配置（单连接、不预建空闲连接）
+    pub fn for_probe(timeout_secs: u64) -> Self {
+        Self {
+            max_size: 1,
+            min_idle: None,
+            connection_timeout_secs: timeout_secs,
+            idle_timeout_secs: None,
+            max_lifetime_secs: None,
+            max_concurrent_connects: None,
+        }
+    }
+}
+
+impl From<&PoolPerformanceConfig> for PoolConfig {
+    fn from(config: &PoolPerformanceConfig) -> Self {
+        Self {
+            max_size: config.max_size,
+            min_idle: Some(config.min_idle),
+            connection_timeout_secs: config.connection_timeout_secs,
+            idle_timeout_secs: Some(config.idle_timeout_secs),
+            max_lifetime_secs: Some(config.max_lifetime_secs),
+            max_concurrent_connects: Some(config.max_concurrent_connects).filter(|n| *n > 0),
         }
     }
 }
 
+/// 测试连接的默认超时（秒），远短于连接池的 connection_timeout
+pub const PROBE_TIMEOUT_SECS: u64 = 5;
+
+/// 连接池大小建议的下限
+pub const POOL_SIZE_LOWER: u32 = 1;
+/// 连接池大小建议的上限（桌面应用场景）
+pub const POOL_SIZE_UPPER: u32 = 10;
+
+/// 平均等待超过此值（毫秒）视为负载偏高
+const HIGH_WAIT_MS: f64 = 200.0;
+/// 平均等待低于此值（毫秒）视为负载偏低
+const LOW_WAIT_MS: f64 = 20.0;
+/// 排队比例超过此值视为负载偏高
+const HIGH_QUEUED_RATIO: f64 = 0.2;
+/// 排队比例低于此值视为负载偏低
+const LOW_QUEUED_RATIO: f64 = 0.05;
+
+/// 建连并发闸门
+///
+/// 限制同时进行中的建连数，并记录观测到的最大并发（用于诊断）。
+#[derive(Debug)]
+pub struct ConnectGate {
+    semaphore: Option<Semaphore>,
+    in_flight: AtomicU32,
+    peak_in_flight: AtomicU32,
+}
+
+impl ConnectGate {
+    /// 创建闸门，`limit` 为 `None` 时不限制
+    pub fn new(limit: Option<u32>) -> Self {
+        Self {
+            semaphore: limit.map(|n| Semaphore::new(n.max(1) as usize)),
+            in_flight: AtomicU32::new(0),
+            peak_in_flight: AtomicU32::new(0),
+        }
+    }
+
+    /// 在闸门内执行一次建连
+    pub async fn run<F: Future>(&self, connect: F) -> F::Output {
+        let _permit = match &self.semaphore {
+            Some(semaphore) => Some(semaphore.acquire().await.expect("建连闸门不会被关闭")),
+            None => None,
+        };
+
+        let current = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak_in_flight.fetch_max(current, Ordering::SeqCst);
+        let output = connect.await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        output
+    }
+
+    /// 观测到的最大同时建连数
+    pub fn peak_in_flight(&self) -> u32 {
+        self.peak_in_flight.load(Ordering::SeqCst)
+    }
+}
+
 /// bb8 连接管理器
 pub struct ConnectionManager {
     config: DatabaseConfig,
+    connect_gate: Arc<ConnectGate>,
 }
 
 impl ConnectionManager {
-    /// 创建新的连接管理器
+    /// 创建新的连接管理器（不限制建连并发）
     pub fn new(config: DatabaseConfig) -> Self {
-        Self { config }
+        Self::with_connect_gate(config, Arc::new(ConnectGate::new(None)))
     }
 
-    /// 创建数据库连接
-    async fn create_connection(&self) -> AppResult<TiberiusClient> {
+    /// 创建使用指定建连闸门的连接管理器
+    pub fn with_connect_gate(config: DatabaseConfig, connect_gate: Arc<ConnectGate>) -> Self {
+        Self {
+            config,
+            connect_gate,
+        }
+    }
+
+    /// 根据数据库配置构建 tiberius 连接配置
+    fn build_tiberius_config(config: &DatabaseConfig) -> Config {
         let mut tiberius_config = Config::new();
-        tiberius_config.host(&self.config.server);
-        tiberius_config.port(self.config.port);
-        tiberius_config.database(&self.config.database);
-        tiberius_config.authentication(AuthMethod::sql_server(
-            &self.config.username,
-            &self.config.password,
-        ));
+        tiberius_config.host(&config.server);
+        tiberius_config.port(config.port);
+        tiberius_config.database(&config.database);
+        tiberius_config.authentication(AuthMethod::sql_server(&config.username, &config.password));
         tiberius_config.trust_cert();
+        if config.read_only_intent() {
+            tiberius_config.readonly(true);
+        }
+        tiberius_config
+    }
+
+    /// 创建数据库连接
+    async fn create_connection(&self) -> AppResult<TiberiusClient> {
+        let tiberius_config = Self::build_tiberius_config(&self.config);
 
         debug!(target: "industry_vis::pool",
             "创建新连接 - {}:{}/{}",
@@ -93,7 +232,7 @@ impl ConnectionManager {
 
         let client = Client::connect(tiberius_config, tcp.compat_write())
             .await
-            .map_err(|e| AppError::connection_with_hint(&e.to_string(), &self.config.database))?;
+            .map_err(|e| AppError::connection_with_hint(&e, &self.config.database))?;
 
         info!(target: "industry_vis::pool", "数据库连接创建成功");
         Ok(client)
@@ -102,11 +241,15 @@ impl ConnectionManager {
 
 #[async_trait]
 impl bb8::ManageConnection for ConnectionManager {
-    type Connection = TiberiusClient;
+    type Connection = PooledClient;
     type Error = AppError;
 
     async fn connect(&self) -> Result<Self::Connection, Self::Error> {
-        self.create_connection().await
+        let client = self.connect_gate.run(self.create_connection()).await?;
+        Ok(PooledClient {
+            client,
+            broken: false,
+        })
     }
 
     async fn is_valid(&self, conn: &mut Self::Connection) -> Result<(), Self::Error> {
@@ -114,15 +257,96 @@ impl bb8::ManageConnection for ConnectionManager {
         use tiberius::Query;
         let query = Query::new("SELECT 1");
         query
-            .execute(conn)
+            .execute(&mut conn.client)
             .await
             .map_err(|e| AppError::Connection(format!("连接验证失败: {}", e)))?;
         debug!(target: "industry_vis::pool", "连接验证通过");
         Ok(())
     }
 
-    fn has_broken(&self, _conn: &mut Self::Connection) -> bool {
-        false
+    fn has_broken(&self, conn: &mut Self::Connection) -> bool {
+        conn.broken
+    }
+}
+
+/// 等待统计的时间窗口：只反映最近的负载，早先的高峰或空闲不影响当前建议
+const WAIT_STATS_WINDOW: Duration = Duration::from_secs(10 * 60);
+/// 窗口按该粒度分桶滚动
+const WAIT_STATS_BUCKET: Duration = Duration::from_secs(60);
+
+/// 连接获取等待统计（最近 `WAIT_STATS_WINDOW` 内的滑动窗口）
+#[derive(Debug, Default)]
+struct PoolWaitStats {
+    buckets: Mutex<VecDeque<WaitBucket>>,
+}
+
+/// 单个时间桶内的获取统计
+#[derive(Debug)]
+struct WaitBucket {
+    started: Instant,
+    /// 获取连接次数
+    gets: u64,
+    /// 需要排队的获取次数（无空闲连接且已达上限）
+    queued: u64,
+    /// 累计等待时间（微秒）
+    wait_us: u64,
+}
+
+impl PoolWaitStats {
+    fn record(&self, wait_us: u64, queued: bool) {
+        self.record_at(Instant::now(), wait_us, queued);
+    }
+
+    fn record_at(&self, now: Instant, wait_us: u64, queued: bool) {
+        let mut buckets = self.buckets.lock();
+        Self::expire(&mut buckets, now);
+        let fresh = buckets
+            .back()
+            .is_none_or(|last| now.duration_since(last.started) >= WAIT_STATS_BUCKET);
+        if fresh {
+            buckets.push_back(WaitBucket {
+                started: now,
+                gets: 0,
+                queued: 0,
+                wait_us: 0,
+            });
+        }
+        if let Some(bucket) = buckets.back_mut() {
+            bucket.gets += 1;
+            bucket.wait_us += wait_us;
+            bucket.queued += u64::from(queued);
+        }
+    }
+
+    /// 返回 (平均等待毫秒, 排队比例, 采样次数)
+    fn snapshot(&self) -> (f64, f64, u64) {
+        self.snapshot_at(Instant::now())
+    }
+
+    fn snapshot_at(&self, now: Instant) -> (f64, f64, u64) {
+        let mut buckets = self.buckets.lock();
+        Self::expire(&mut buckets, now);
+        let (total, queued, wait_us) = buckets.iter().fold((0, 0, 0), |acc, b| {
+            (acc.0 + b.gets, acc.1 + b.queued, acc.2 + b.wait_us)
+        });
+        if total == 0 {
+            return (0.0, 0.0, 0);
+        }
+        (
+            wait_us as f64 / total as f64 / 1000.0,
+            queued as f64 / total as f64,
+            total,
+        )
+    }
+
+    /// 丢弃整体落在窗口之外的时间桶
+    fn expire(buckets: &mut VecDeque<WaitBucket>, now: Instant) {
+        while buckets
+            .front()
+            .is_some_and(|b| now.duration_since(b.started) >= WAIT_STATS_WINDOW)
+        {
+            buckets.pop_front();
+        }
     }
 }
 
@@ -131,13 +355,18 @@ pub struct ConnectionPool {
     pool: Pool<ConnectionManager>,
     config: DatabaseConfig,
     max_size: u32,
+    wait_stats: PoolWaitStats,
+    connect_gate: Arc<ConnectGate>,
 }
 
 impl ConnectionPool {
     /// 创建新的连接池
     pub async fn new(db_config: DatabaseConfig, pool_config: PoolConfig) -> AppResult<Self> {
-        let manager = ConnectionManager::new(db_config.clone());
+        let connect_gate = Arc::new(ConnectGate::new(pool_config.max_concurrent_connects));
+        let manager =
+            ConnectionManager::with_connect_gate(db_config.clone(), Arc::clone(&connect_gate));
 
+        // `build` 会等待 min_idle 个连接建立完成，第一次真实查询直接命中已建连接
         let pool = Pool::builder()
             .max_size(pool_config.max_size)
             .min_idle(pool_config.min_idle)
@@ -156,7 +385,11 @@ impl ConnectionPool {
             )
             .build(manager)
             .await
-            .map_err(|e| AppError::Pool(format!("创建连接池失败: {}", e)))?;
+            .map_err(|e| match e {
+                // 保留已分类的连接错误（数据库不存在、认证失败等）
+                AppError::Connection(_) | AppError::ConnectionRejected { .. } => e,
+                other => AppError::Pool(format!("创建连接池失败: {}", other)),
+            })?;
 
         info!(target: "industry_vis::pool",
             "连接池已创建 - max_size={}, server={}:{}",
@@ -167,9 +400,33 @@ impl ConnectionPool {
             pool,
             config: db_config,
             max_size: pool_config.max_size,
+            wait_stats: PoolWaitStats::default(),
+            connect_gate,
         })
     }
 
+    /// 探测数据库是否可连接（用于"测试连接"）
+    ///
+    /// 整体受 `timeout` 约束，服务器不可达时到时立即返回"连接超时"错误，
+    /// 而非等满连接池的 connection_timeout。
+    pub async fn probe(db_config: DatabaseConfig, timeout: Duration) -> AppResult<()> {
+        let secs = timeout.as_secs().max(1);
+        let timeout_error = || AppError::Connection(format!("连接超时（{} 秒内未响应）", secs));
+
+        let attempt = async {
+            let pool = Self::new(db_config, PoolConfig::for_probe(secs)).await?;
+            match pool.pool.get().await {
+                Ok(_) => Ok(()),
+                Err(bb8::RunError::User(e)) => Err(e),
+                Err(bb8::RunError::TimedOut) => Err(timeout_error()),
+            }
+        };
+
+        tokio::time::timeout(timeout, attempt)
+            .await
+            .unwrap_or_else(|_| Err(timeout_error()))
+    }
+
     /// 使用默认配置创建连接池
     pub async fn with_defaults(db_config: DatabaseConfig) -> AppResult<Self> {
         Self::new(db_config, PoolConfig::for_desktop()).await
@@ -177,10 +434,35 @@ impl ConnectionPool {
 
     /// 获取一个连接
     pub async fn get(&self) -> AppResult<PooledConnection<'_, ConnectionManager>> {
-        self.pool
+        let state = self.pool.state();
+        let queued = state.idle_connections == 0 && state.connections >= self.max_size;
+        let started = Instant::now();
+
+        let result = self
+            .pool
             .get()
             .await
-            .map_err(|e| AppError::Pool(format!("获取连接失败: {}", e)))
+            .map_err(|e| AppError::Pool(format!("获取连接失败: {}", e)));
+
+        self.wait_stats
+            .record(started.elapsed().as_micros() as u64, queued);
+        result
+    }
+
+    /// 根据最近（`WAIT_STATS_WINDOW` 内）的排队等待情况给出连接池大小建议
+    pub fn suggest_pool_size(&self) -> PoolSizeSuggestion {
+        let (avg_wait_ms, queued_ratio, samples) = self.wait_stats.snapshot();
+        PoolSizeSuggestion::from_load(self.max_size, avg_wait_ms, queued_ratio, samples)
+    }
+
+    /// 最大连接数
+    pub fn max_size(&self) -> u32 {
+        self.max_size
+    }
+
+    /// 观测到的最大同时建连数
+    pub fn peak_concurrent_connects(&self) -> u32 {
+        self.connect_gate.peak_in_flight()
     }
 
     /// 获取连接池状态
@@ -213,6 +495,58 @@ pub struct PoolState {
     pub max_size: u32,
 }
 
+/// 连接池大小建议
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolSizeSuggestion {
+    /// 当前最大连接数
+    pub current: u32,
+    /// 建议最大连接数（在 [`POOL_SIZE_LOWER`, `POOL_SIZE_UPPER`] 范围内）
+    pub suggested: u32,
+    /// 平均获取等待时间（毫秒）
+    pub avg_wait_ms: f64,
+    /// 排队获取比例（0.0 ~ 1.0）
+    pub queued_ratio: f64,
+    /// 统计样本数
+    pub samples: u64,
+    /// 建议原因
+    pub reason: String,
+}
+
+impl PoolSizeSuggestion {
+    /// 根据负载指标计算建议值
+    pub fn from_load(current: u32, avg_wait_ms: f64, queued_ratio: f64, samples: u64) -> Self {
+        let (suggested, reason) = if samples == 0 {
+            (current, "暂无连接获取记录，保持当前配置".to_string())
+        } else if avg_wait_ms > HIGH_WAIT_MS || queued_ratio > HIGH_QUEUED_RATIO {
+            (
+                (current + 1).min(POOL_SIZE_UPPER),
+                format!(
+                    "平均等待 {:.1}ms，排队比例 {:.0}%，建议增大连接池",
+                    avg_wait_ms,
+                    queued_ratio * 100.0
+                ),
+            )
+        } else if avg_wait_ms < LOW_WAIT_MS && queued_ratio < LOW_QUEUED_RATIO {
+            (
+                current.saturating_sub(1).max(POOL_SIZE_LOWER),
+                format!("平均等待 {:.1}ms，几乎无排队，可减小连接池", avg_wait_ms),
+            )
+        } else {
+            (current, "负载适中，保持当前配置".to_string())
+        };
+
+        Self {
+            current,
+            suggested,
+            avg_wait_ms,
+            queued_ratio,
+            samples,
+            reason,
+        }
+    }
+}
+
 /// 可共享的连接池（用于 Tauri 状态）
 #[allow(dead_code)]
 pub type SharedPool = Arc<ConnectionPool>;
@@ -220,6 +554,7 @@ pub type SharedPool = Arc<ConnectionPool>;
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::ApplicationIntent;
 
     #[test]
     fn test_pool_config_default() {
@@ -235,6 +570,156 @@ mod tests {
         assert_eq!(config.connection_timeout_secs, 15); // 快速失败策略
     }
 
+    #[test]
+    fn test_build_config_application_intent() {
+        let default = ConnectionManager::build_tiberius_config(&DatabaseConfig::default());
+        assert!(format!("{:?}", default).contains("readonly: false"));
+
+        let read_only = DatabaseConfig {
+            application_intent: Some(ApplicationIntent::ReadOnly),
+            ..DatabaseConfig::default()
+        };
+        let config = ConnectionManager::build_tiberius_config(&read_only);
+        assert!(format!("{:?}", config).contains("readonly: true"));
+    }
+
+    #[tokio::test]
+    async fn test_probe_unreachable_times_out_quickly() {
+        // 本地监听器接受连接后不响应登录握手，模拟无响应的服务器
+        let server = SilentServer::start(None).await;
+
+        let started = Instant::now();
+        let result = ConnectionPool::probe(server.config.clone(), Duration::from_secs(1)).await;
+
+        assert!(matches!(result, Err(AppError::Connection(msg)) if msg.contains("连接超时")));
+        assert!(
+            started.elapsed() < Duration::from_secs(5),
+            "应在超时后快速返回"
+        );
+    }
+
+    /// 本地监听器：接受 TCP 连接但从不响应登录握手，不访问外部网络
+    struct SilentServer {
+        config: DatabaseConfig,
+        accepted: Arc<AtomicU32>,
+        peak_open: Arc<AtomicU32>,
+        task: tokio::task::JoinHandle<()>,
+    }
+
+    impl SilentServer {
+        /// 启动监听，每个连接保持 `hold` 后关闭（`None` 一直保持）
+        async fn start(hold: Option<Duration>) -> Self {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let config = DatabaseConfig {
+                server: "127.0.0.1".to_string(),
+                port: listener.local_addr().unwrap().port(),
+                ..DatabaseConfig::default()
+            };
+            let accepted = Arc::new(AtomicU32::new(0));
+            let peak_open = Arc::new(AtomicU32::new(0));
+            let open = Arc::new(AtomicU32::new(0));
+
+            let task = tokio::spawn({
+                let accepted = Arc::clone(&accepted);
+                let peak_open = Arc::clone(&peak_open);
+                async move {
+                    let mut held = Vec::new();
+                    while let Ok((socket, _)) = listener.accept().await {
+                        accepted.fetch_add(1, Ordering::SeqCst);
+                        let current = open.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak_open.fetch_max(current, Ordering::SeqCst);
+                        match hold {
+                            Some(hold) => {
+                                let open = Arc::clone(&open);
+                                tokio::spawn(async move {
+                                    tokio::time::sleep(hold).await;
+                                    open.fetch_sub(1, Ordering::SeqCst);
+                                    drop(socket);
+                                });
+                            }
+                            None => held.push(socket),
+                        }
+                    }
+                }
+            });
+
+            Self {
+                config,
+                accepted,
+                peak_open,
+                task,
+            }
+        }
+    }
+
+    impl Drop for SilentServer {
+        fn drop(&mut self) {
+            self.task.abort();
+        }
+    }
+
+    /// 按性能配置创建连接池，返回服务端观测到的 (建连次数, 最大同时建连数)
+    async fn pool_creation_connects(max_concurrent_connects: u32) -> (u32, u32) {
+        let server = SilentServer::start(Some(Duration::from_millis(200))).await;
+        let mut pool_config = PoolConfig::from(&PoolPerformanceConfig {
+            max_size: 3,
+            min_idle: 3,
+            max_concurrent_connects,
+            ..PoolPerformanceConfig::default()
+        });
+        pool_config.connection_timeout_secs = 1;
+
+        let result = ConnectionPool::new(server.config.clone(), pool_config).await;
+        assert!(matches!(result, Err(AppError::Connection(_))));
+        (
+            server.accepted.load(Ordering::SeqCst),
+            server.peak_open.load(Ordering::SeqCst),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_pool_creation_respects_max_concurrent_connects() {
+        // min_idle 个建连同时发起，限制为 1 时串行进行（失败后重试）
+        let (accepted, peak) = pool_creation_connects(1).await;
+        assert!(accepted >= 2, "应多次建连，实际 {}", accepted);
+        assert_eq!(peak, 1);
+
+        // 0 表示不限制
+        let (_, peak) = pool_creation_connects(0).await;
+        assert!(peak > 1, "不限制时应并发建连，实际峰值 {}", peak);
+    }
+
+    /// 模拟冷启动：`queries` 个查询同时到来，各自触发一次建连
+    async fn cold_start_peak(limit: Option<u32>, queries: usize) -> u32 {
+        let gate = Arc::new(ConnectGate::new(limit));
+        let tasks: Vec<_> = (0..queries)
+            .map(|_| {
+                let gate = Arc::clone(&gate);
+                tokio::spawn(async move {
+                    gate.run(tokio::time::sleep(Duration::from_millis(20)))
+                        .await
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        gate.peak_in_flight()
+    }
+
+    #[tokio::test]
+    async fn test_connect_gate_limits_cold_start() {
+        let max_size = PoolConfig::default().max_size;
+
+        // 限制为 max_size 时，10 个并发查询同时建连不超过 max_size
+        assert_eq!(cold_start_peak(Some(max_size), 10).await, max_size);
+        // 桌面配置串行建连
+        let desktop = PoolConfig::for_desktop().max_concurrent_connects;
+        assert_eq!(cold_start_peak(desktop, 10).await, 1);
+        // 不限制时全部同时建连
+        assert_eq!(cold_start_peak(None, 10).await, 10);
+    }
+
     #[test]
     fn test_connection_manager_creation() {
         let db_config = DatabaseConfig::default();
@@ -242,5 +727,100 @@ mod tests {
         assert_eq!(manager.config.server, db_config.server);
     }
 
+    #[test]
+    fn test_suggest_pool_size_high_load() {
+        let suggestion = PoolSizeSuggestion::from_load(3, 500.0, 0.5, 100);
+        assert!(suggestion.suggested > suggestion.current);
+    }
+
+    #[test]
+    fn test_suggest_pool_size_low_load() {
+        let suggestion = PoolSizeSuggestion::from_load(3, 2.0, 0.0, 100);
+        assert!(suggestion.suggested < suggestion.current);
+    }
+
+    #[test]
+    fn test_suggest_pool_size_bounds() {
+        let suggestion = PoolSizeSuggestion::from_load(POOL_SIZE_UPPER, 500.0, 0.5, 100);
+        assert_eq!(suggestion.suggested, POOL_SIZE_UPPER);
+
+        let suggestion = PoolSizeSuggestion::from_load(POOL_SIZE_LOWER, 1.0, 0.0, 100);
+        assert_eq!(suggestion.suggested, POOL_SIZE_LOWER);
+
+        let suggestion = PoolSizeSuggestion::from_load(3, 0.0, 0.0, 0);
+        assert_eq!(suggestion.suggested, 3);
+    }
+
+    #[test]
+    fn test_wait_stats_snapshot() {
+        let stats = PoolWaitStats::default();
+        stats.record(10_000, false);
+        stats.record(30_000, true);
+        let (avg_wait_ms, queued_ratio, samples) = stats.snapshot();
+        assert_eq!(samples, 2);
+        assert_eq!(avg_wait_ms, 20.0);
+        assert_eq!(queued_ratio, 0.5);
+    }
+
+    #[test]
+    fn test_wait_stats_forget_old_waits() {
+        let stats = PoolWaitStats::default();
+        let start = Instant::now();
+        // 早先的高峰：全部排队、等待 500ms
+        for i in 0..50 {
+            stats.record_at(start + Duration::from_secs(i), 500_000, true);
+        }
+        // 之后负载下降
+        let later = start + WAIT_STATS_WINDOW + Duration::from_secs(60);
+        for i in 0..10 {
+            stats.record_at(later + Duration::from_secs(i), 1_000, false);
+        }
+
+        let (avg_wait_ms, queued_ratio, samples) =
+            stats.snapshot_at(later + Duration::from_secs(10));
+        assert_eq!(samples, 10);
+        assert_eq!(avg_wait_ms, 1.0);
+        assert_eq!(queued_ratio, 0.0);
+        assert_eq!(stats.buckets.lock().len(), 1);
+
+        // 窗口内没有新的获取时无样本
+        let idle = later + WAIT_STATS_WINDOW + WAIT_STATS_BUCKET;
+        assert_eq!(stats.snapshot_at(idle).2, 0);
+    }
+
+    /// 无需数据库的连接管理器，记录建连次数
+    struct CountingManager(AtomicU32);
+
+    #[async_trait]
+    impl bb8::ManageConnection for CountingManager {
+        type Connection = u32;
+        type Error = AppError;
+
+        async fn connect(&self) -> Result<u32, AppError> {
+            Ok(self.0.fetch_add(1, Ordering::SeqCst))
+        }
+
+        async fn is_valid(&self, _conn: &mut u32) -> Result<(), AppError> {
+            Ok(())
+        }
+
+        fn has_broken(&self, _conn: &mut u32) -> bool {
+            false
+        }
+    }
+
+    #[tokio::test]
+    async fn test_build_establishes_min_idle() {
+        // `ConnectionPool::new` 依赖 `build` 在返回前建好 min_idle 个连接
+        let pool = Pool::builder()
+            .max_size(3)
+            .min_idle(Some(2))
+            .build(CountingManager(AtomicU32::new(0)))
+            .await
+            .unwrap();
+        assert_eq!(pool.state().idle_connections, 2);
+        assert_eq!(pool.state().connections, 2);
+    }
+
     // 连接池的集成测试需要实际的数据库连接，在集成测试中进行
 }
@@ -2,37 +2,245 @@
 //!
 //! 实现当前厂商（控制器数据库）的表结构和字段映射。
 
-use crate::datasource::SchemaProfile;
+use super::row_time::{RecordTimeZone, row_local_time};
+use crate::datasource::{QueryPlan, SchemaProfile, TagSearchTerms};
 use crate::error::AppResult;
-use crate::models::HistoryRecord;
+use crate::models::{HistoryRecord, Interner};
+
+/// 默认标签表名
+pub const DEFAULT_TAG_TABLE: &str = "TagDataBase";
 
 /// 默认 Schema Profile
 ///
 /// 适配当前厂商的数据库结构：
-/// - 标签表：`TagDataBase`，字段 `TagName`
+/// - 标签表：可配置（默认 `TagDataBase`），字段 `TagName`
 /// - 历史表：可配置（默认 `历史表`），字段 `DateTime, TagName, TagVal, TagQuality`
-#[derive(Debug, Clone, Default)]
-pub struct DefaultProfile;
+///
+/// 可选的查询计划调优：
+/// - 索引提示：`WITH (NOLOCK, INDEX([...]))`
+/// - 条件顺序：`TagName IN (...)` 在前，适配 `(TagName, DateTime)` 复合索引
+///
+/// 历史查询 SQL 统一由查询计划（`QueryPlan`）生成，各 `history_query_sql*` 方法为其特例。
+#[derive(Debug, Clone)]
+pub struct DefaultProfile {
+    tag_table: String,
+    index_hint: Option<String>,
+    tag_filter_first: bool,
+    time_zone: RecordTimeZone,
+}
+
+impl Default for DefaultProfile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl DefaultProfile {
     /// 创建新的默认 Profile
     pub fn new() -> Self {
-        Self
+        Self::with_tag_table(DEFAULT_TAG_TABLE)
+    }
+
+    /// 创建使用指定标签表的 Profile
+    pub fn with_tag_table(tag_table: impl Into<String>) -> Self {
+        Self {
+            tag_table: tag_table.into(),
+            index_hint: None,
+            tag_filter_first: false,
+            time_zone: RecordTimeZone::Local,
+        }
+    }
+
+    /// 设置历史表索引提示（空字符串视为不设置）
+    pub fn with_index_hint(mut self, index: Option<String>) -> Self {
+        self.index_hint = index
+            .map(|i| i.trim().to_string())
+            .filter(|i| !i.is_empty());
+        self
+    }
+
+    /// 设置是否将标签过滤条件放在时间条件之前
+    pub fn with_tag_filter_first(mut self, enabled: bool) -> Self {
+        self.tag_filter_first = enabled;
+        self
+    }
+
+    /// 设置 DATETIMEOFFSET 时间列换算的目标时区
+    pub fn with_time_zone(mut self, time_zone: RecordTimeZone) -> Self {
+        self.time_zone = time_zone;
+        self
+    }
+
+    /// 历史表的表提示
+    fn table_hint(&self) -> String {
+        match &self.index_hint {
+            Some(index) => format!("WITH (NOLOCK, INDEX([{}]))", index.replace(']', "]]")),
+            None => "WITH (NOLOCK)".to_string(),
+        }
+    }
+
+    /// 历史查询的 WHERE 子句（按配置决定条件顺序）
+    ///
+    /// `tag_filter` 为 `build_tag_filter` 的输出（`AND TagName IN (...)` 或空）。
+    fn history_where(&self, start_time: &str, end_time: &str, tag_filter: &str) -> String {
+        let time_cond = format!(
+            "DateTime BETWEEN '{}' AND '{}'",
+            start_time.replace('\'', "''"),
+            end_time.replace('\'', "''")
+        );
+        match tag_filter.trim().strip_prefix("AND ") {
+            Some(tag_cond) if self.tag_filter_first => {
+                format!("WHERE {} AND {}", tag_cond, time_cond)
+            }
+            _ => format!("WHERE {}\n               {}", time_cond, tag_filter),
+        }
+    }
+
+    /// 获取标签表名
+    pub fn tag_table(&self) -> &str {
+        &self.tag_table
+    }
+
+    /// 标签表在 SQL 中的表名：按方括号外的 `.` 拆分为多段（如 `db.schema.table`），
+    /// 各段分别方括号转义；已用方括号包围的段（如 `[My.Tags]`）视为一个标识符
+    fn tag_table_sql(&self) -> String {
+        split_identifier(&self.tag_table)
+            .iter()
+            .map(|part| format!("[{}]", part.replace(']', "]]")))
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    /// 原始行数据来源（时间、标签过滤）
+    fn base_stage(&self, plan: &QueryPlan, tag_filter: &str) -> SqlStage {
+        SqlStage {
+            columns: HISTORY_COLUMNS.to_string(),
+            from: format!("[{}] {}", plan.table.replace(']', "]]"), self.table_hint()),
+            filter: self
+                .history_where(&plan.start_time, &plan.end_time, tag_filter)
+                .trim_end()
+                .to_string(),
+        }
+    }
+
+    /// 按查询计划生成单条 SQL（`tag_filter` 为 `build_tag_filter` 的输出）
+    ///
+    /// 启用每标签限量时包一层派生表；最外层统一按时间排序并限行。
+    fn plan_sql(&self, plan: &QueryPlan, tag_filter: &str) -> String {
+        let mut stage = self.base_stage(plan, tag_filter);
+
+        if let Some(per_tag_limit) = plan.per_tag_limit {
+            // 按标签分区倒序编号，每个标签只保留最新的 N 个点
+            let inner = stage.select(
+                ", ROW_NUMBER() OVER (PARTITION BY TagName ORDER BY DateTime DESC) AS RowNum",
+            );
+            stage = SqlStage::derived(
+                inner,
+                "Ranked",
+                format!("WHERE RowNum <= {}", per_tag_limit),
+            );
+        }
+
+        let top = plan
+            .max_rows
+            .map(|max_rows| format!("TOP {} ", max_rows))
+            .unwrap_or_default();
+        format!("{}\nORDER BY DateTime", stage.select_with_prefix(&top, ""))
     }
 }
 
+/// 历史查询结果列（与 `map_history_row` 的列顺序一致）
+const HISTORY_COLUMNS: &str = "DateTime, TagName, TagVal, TagQuality";
+
+/// 查询计划逐层生成 SQL 时的当前数据来源
+struct SqlStage {
+    /// 选择列
+    columns: String,
+    /// FROM 目标（表或派生表）
+    from: String,
+    /// WHERE 子句（可为空）
+    filter: String,
+}
+
+impl SqlStage {
+    /// 将一条 SELECT 包装为派生表，外层只选择标准结果列
+    fn derived(inner: String, alias: &str, filter: String) -> Self {
+        Self {
+            columns: HISTORY_COLUMNS.to_string(),
+            from: format!("(\n{}\n) AS {}", inner, alias),
+            filter,
+        }
+    }
+
+    /// 从当前来源选择结果列，`extra` 追加在列之后
+    fn select(&self, extra: &str) -> String {
+        self.select_with_prefix("", extra)
+    }
+
+    /// 同 `select`，`prefix` 插在列之前（如 `TOP n `）
+    fn select_with_prefix(&self, prefix: &str, extra: &str) -> String {
+        format!(
+            "SELECT {}{}{}\nFROM {}\n{}",
+            prefix, self.columns, extra, self.from, self.filter
+        )
+    }
+}
+
+/// 拆分多段标识符，只在方括号外的 `.` 处拆分
+///
+/// 以 `[` 开头的段读到配对的 `]` 为止（`]]` 表示字面 `]`），其余字符原样保留。
+fn split_identifier(name: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut at_part_start = true;
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '[' if at_part_start => {
+                while let Some(c) = chars.next() {
+                    match c {
+                        ']' if chars.peek() == Some(&']') => {
+                            chars.next();
+                            current.push(']');
+                        }
+                        ']' => break,
+                        _ => current.push(c),
+                    }
+                }
+                at_part_start = false;
+            }
+            '.' => {
+                parts.push(std::mem::take(&mut current));
+                at_part_start = true;
+            }
+            _ => {
+                current.push(c);
+                at_part_start = false;
+            }
+        }
+    }
+    parts.push(current);
+    parts
+}
+
 impl SchemaProfile for DefaultProfile {
     fn name(&self) -> &str {
         "default"
     }
 
-    fn tag_search_sql(&self, limit: usize) -> String {
+    fn record_time_zone(&self) -> RecordTimeZone {
+        self.time_zone
+    }
+
+    fn tag_search_sql(&self, limit: usize, terms: &TagSearchTerms) -> String {
         format!(
-            r#"SELECT DISTINCT TOP {} TagName 
-               FROM [TagDataBase] 
-               WHERE TagName LIKE @P1
+            r#"SELECT DISTINCT TOP {} TagName
+               FROM {}
+               WHERE {}
                ORDER BY TagName"#,
-            limit
+            limit,
+            self.tag_table_sql(),
+            terms.where_clause("TagName")
         )
     }
 
@@ -46,30 +254,90 @@ impl SchemaProfile for DefaultProfile {
         // 优化 SQL：
         // 1. 使用 WITH (NOLOCK) 减少锁等待
         // 2. 只按 DateTime 排序，充分利用索引
-        format!(
-            r#"SELECT DateTime, TagName, TagVal, TagQuality 
-               FROM [{}] WITH (NOLOCK)
-               WHERE DateTime BETWEEN '{}' AND '{}'
-               {}
-               ORDER BY DateTime"#,
-            table.replace(']', "]]"),
-            start_time.replace('\'', "''"),
-            end_time.replace('\'', "''"),
-            tag_filter
-        )
+        // 3. 可选索引提示 / 条件顺序（见 `with_index_hint`、`with_tag_filter_first`）
+        self.plan_sql(&QueryPlan::new(table, start_time, end_time), tag_filter)
     }
 
-    fn map_history_row(&self, row: &tiberius::Row) -> AppResult<HistoryRecord> {
-        let dt: Option<chrono::NaiveDateTime> = row.get(0);
-        let date_time = dt
-            .map(|d| d.format("%Y-%m-%dT%H:%M:%S%.3f").to_string())
-            .unwrap_or_default();
+    fn history_query_sql_per_tag_limit(
+        &self,
+        table: &str,
+        start_time: &str,
+        end_time: &str,
+        tag_filter: &str,
+        per_tag_limit: usize,
+    ) -> Option<String> {
+        let plan =
+            QueryPlan::new(table, start_time, end_time).with_per_tag_limit(Some(per_tag_limit));
+        Some(self.plan_sql(&plan, tag_filter))
+    }
 
-        Ok(HistoryRecord::new(
-            date_time,
-            row.get::<&str, _>(1).unwrap_or("").trim().to_string(),
+    fn history_query_sql_max_rows(
+        &self,
+        table: &str,
+        start_time: &str,
+        end_time: &str,
+        tag_filter: &str,
+        max_rows: usize,
+    ) -> Option<String> {
+        let plan = QueryPlan::new(table, start_time, end_time).with_max_rows(Some(max_rows));
+        Some(self.plan_sql(&plan, tag_filter))
+    }
+
+    fn build_query(&self, plan: &QueryPlan) -> Option<String> {
+        let tag_filter = self.build_tag_filter(plan.tags.as_deref());
+        Some(self.plan_sql(plan, &tag_filter))
+    }
+
+    fn history_count_sql(
+        &self,
+        table: &str,
+        start_time: &str,
+        end_time: &str,
+        tag_filter: &str,
+    ) -> Option<String> {
+        let stage = self.base_stage(&QueryPlan::new(table, start_time, end_time), tag_filter);
+        Some(format!(
+            "SELECT COUNT_BIG(*)\nFROM {}\n{}",
+            stage.from, stage.filter
+        ))
+    }
+
+    fn value_neighbors_sql(&self, table: &str, tag: &str, time: &str) -> Option<String> {
+        let (table, tag, time) = (
+            table.replace(']', "]]"),
+            tag.replace('\'', "''"),
+            time.replace('\'', "''"),
+        );
+        // 前后各取 1 个点，走 (TagName, DateTime) 索引
+        Some(format!(
+            r#"SELECT DateTime, TagName, TagVal, TagQuality FROM (
+                   SELECT TOP 1 DateTime, TagName, TagVal, TagQuality
+                   FROM [{table}] {hint}
+                   WHERE TagName = '{tag}' AND DateTime <= '{time}'
+                   ORDER BY DateTime DESC
+               ) AS Before
+               UNION ALL
+               SELECT DateTime, TagName, TagVal, TagQuality FROM (
+                   SELECT TOP 1 DateTime, TagName, TagVal, TagQuality
+                   FROM [{table}] {hint}
+                   WHERE TagName = '{tag}' AND DateTime >= '{time}'
+                   ORDER BY DateTime ASC
+               ) AS After"#,
+            hint = self.table_hint(),
+        ))
+    }
+
+    fn map_history_row(
+        &self,
+        row: &tiberius::Row,
+        interner: &mut Interner,
+    ) -> AppResult<HistoryRecord> {
+        Ok(HistoryRecord::interned(
+            row_local_time(row, 0, self.time_zone),
+            row.get::<&str, _>(1).unwrap_or("").trim(),
             row.get::<f32, _>(2).unwrap_or(0.0) as f64,
-            row.get::<&str, _>(3).unwrap_or("").trim().to_string(),
+            row.get::<&str, _>(3).unwrap_or("").trim(),
+            interner,
         ))
     }
 }
@@ -84,10 +352,64 @@ mod tests {
         assert_eq!(profile.name(), "default");
     }
 
+    #[test]
+    fn test_history_sql_per_tag_limit() {
+        let profile = DefaultProfile::new();
+        let filter = profile.build_tag_filter(Some(&["A".to_string(), "B".to_string()]));
+        let sql = profile
+            .history_query_sql_per_tag_limit("History", "2024-01-01", "2024-01-02", &filter, 100)
+            .unwrap();
+
+        assert!(sql.contains("ROW_NUMBER() OVER (PARTITION BY TagName ORDER BY DateTime DESC)"));
+        assert!(sql.contains("WHERE RowNum <= 100"));
+        assert!(sql.contains(&filter));
+        assert!(sql.trim_end().ends_with("ORDER BY DateTime"));
+    }
+
+    #[test]
+    fn test_build_query_combines_options() {
+        let profile = DefaultProfile::new();
+        let tags = ["A".to_string(), "B".to_string()];
+        let plan = QueryPlan::new("History", "2024-01-01", "2024-01-02")
+            .with_tags(Some(&tags))
+            .with_per_tag_limit(Some(10))
+            .with_max_rows(Some(30));
+        let sql = profile.build_query(&plan).unwrap();
+
+        // 单条语句：每标签限量在派生表内，限行作用于最外层
+        assert!(!sql.contains(';'));
+        assert!(sql.contains("AND TagName IN ('A', 'B')"));
+        assert!(sql.contains("WHERE RowNum <= 10"));
+        assert!(sql.starts_with("SELECT TOP 30 DateTime, TagName, TagVal, TagQuality"));
+        let position = |part: &str| sql.find(part).unwrap();
+        assert!(position("TOP 30") < position(") AS Ranked"));
+        assert!(sql.ends_with("ORDER BY DateTime"));
+    }
+
+    #[test]
+    fn test_build_query_only_emits_enabled_options() {
+        let profile = DefaultProfile::new();
+        let tags = ["T1".to_string()];
+
+        // 无额外选项时与 history_query_sql 完全一致
+        let plain = QueryPlan::new("History", "2024-01-01", "2024-01-02").with_tags(Some(&tags));
+        let filter = profile.build_tag_filter(Some(&tags));
+        assert_eq!(
+            profile.build_query(&plain).unwrap(),
+            profile.history_query_sql("History", "2024-01-01", "2024-01-02", &filter)
+        );
+
+        // 仅限行：不生成编号
+        let sql = profile.build_query(&plain.with_max_rows(Some(5))).unwrap();
+        assert!(sql.starts_with("SELECT TOP 5 DateTime, TagName, TagVal, TagQuality"));
+        assert!(!sql.contains("ROW_NUMBER"));
+        assert!(sql.ends_with("ORDER BY DateTime"));
+    }
+
     #[test]
     fn test_tag_search_sql_format() {
         let profile = DefaultProfile::new();
-        let sql = profile.tag_search_sql(100);
+        let sql = profile.tag_search_sql(100, &TagSearchTerms::parse("Temp"));
 
         assert!(sql.contains("TOP 100"));
         assert!(sql.contains("[TagDataBase]"));
@@ -95,6 +417,40 @@ mod tests {
         assert!(sql.contains("ORDER BY TagName"));
     }
 
+    #[test]
+    fn test_tag_search_sql_exclude_words() {
+        let profile = DefaultProfile::new();
+        let sql = profile.tag_search_sql(100, &TagSearchTerms::parse("Temp -Setpoint"));
+        assert!(sql.contains("WHERE TagName LIKE @P1 AND TagName NOT LIKE @P2"));
+    }
+
+    #[test]
+    fn test_tag_search_sql_custom_table() {
+        let profile = DefaultProfile::with_tag_table("Tag]List");
+        let sql = profile.tag_search_sql(10, &TagSearchTerms::parse("Temp"));
+
+        assert_eq!(profile.tag_table(), "Tag]List");
+        assert!(sql.contains("[Tag]]List]"));
+        assert!(!sql.contains("TagDataBase"));
+    }
+
+    #[test]
+    fn test_tag_search_sql_schema_qualified_table() {
+        let profile = DefaultProfile::with_tag_table("sales.Tag]List");
+        let sql = profile.tag_search_sql(10, &TagSearchTerms::parse("Temp"));
+        assert!(sql.contains("FROM [sales].[Tag]]List]"));
+    }
+
+    #[test]
+    fn test_tag_table_sql_respects_brackets() {
+        let sql = |table: &str| DefaultProfile::with_tag_table(table).tag_table_sql();
+        assert_eq!(sql("[My.Tags]"), "[My.Tags]");
+        assert_eq!(sql("db.schema.TagList"), "[db].[schema].[TagList]");
+        assert_eq!(sql("sales.[Tag.List]"), "[sales].[Tag.List]");
+        assert_eq!(sql("[a]]b].[c.d]"), "[a]]b].[c.d]");
+        assert_eq!(sql("Tag]List"), "[Tag]]List]");
+    }
+
     #[test]
     fn test_history_query_sql_format() {
         let profile = DefaultProfile::new();
@@ -123,6 +479,89 @@ mod tests {
         assert!(sql.contains("'Tag2'"));
     }
 
+    #[test]
+    fn test_history_query_sql_index_hint() {
+        let profile = DefaultProfile::new().with_index_hint(Some("IX_Tag_Time".to_string()));
+        let filter = profile.build_tag_filter(Some(&["Tag1".to_string()]));
+        let sql = profile.history_query_sql("历史表", "2024-01-01", "2024-01-02", &filter);
+
+        assert!(sql.contains("FROM [历史表] WITH (NOLOCK, INDEX([IX_Tag_Time]))"));
+        // 默认仍是时间条件在前
+        assert!(sql.contains("WHERE DateTime BETWEEN '2024-01-01' AND '2024-01-02'"));
+
+        let sql = profile
+            .history_query_sql_per_tag_limit("历史表", "2024-01-01", "2024-01-02", &filter, 10)
+            .unwrap();
+        assert!(sql.contains("INDEX([IX_Tag_Time])"));
+
+        // 未设置或空白时不生成提示
+        let profile = DefaultProfile::new().with_index_hint(Some("  ".to_string()));
+        let sql = profile.history_query_sql("历史表", "2024-01-01", "2024-01-02", "");
+        assert!(!sql.contains("INDEX("));
+    }
+
+    #[test]
+    fn test_history_query_sql_tag_filter_first() {
+        let profile = DefaultProfile::new().with_tag_filter_first(true);
+        let filter = profile.build_tag_filter(Some(&["Tag1".to_string(), "Tag2".to_string()]));
+        let sql = profile.history_query_sql("历史表", "2024-01-01", "2024-01-02", &filter);
+        assert!(sql.contains(
+            "WHERE TagName IN ('Tag1', 'Tag2') AND DateTime BETWEEN '2024-01-01' AND '2024-01-02'"
+        ));
+
+        // 无标签过滤时只有时间条件
+        let sql = profile.history_query_sql("历史表", "2024-01-01", "2024-01-02", "");
+        assert!(sql.contains("WHERE DateTime BETWEEN '2024-01-01' AND '2024-01-02'"));
+        assert!(!sql.contains("AND TagName"));
+    }
+
+    #[test]
+    fn test_history_sql_max_rows() {
+        let profile = DefaultProfile::new();
+        let sql = profile
+            .history_query_sql_max_rows("History", "2024-01-01", "2024-01-02", "", 1000)
+            .unwrap();
+        assert!(sql.contains("SELECT TOP 1000 DateTime"));
+        assert!(sql.contains("ORDER BY DateTime"));
+    }
+
+    #[test]
+    fn test_history_count_sql_matches_query_conditions() {
+        let profile = DefaultProfile::new()
+            .with_index_hint(Some("IX_Tag_Time".to_string()))
+            .with_tag_filter_first(true);
+        let filter = profile.build_tag_filter(Some(&["T1".to_string(), "T2".to_string()]));
+        let args = ("History", "2024-01-01 00:00:00", "2024-01-02 00:00:00");
+
+        let count = profile
+            .history_count_sql(args.0, args.1, args.2, &filter)
+            .unwrap();
+        let select = profile.history_query_sql(args.0, args.1, args.2, &filter);
+        assert!(count.starts_with("SELECT COUNT_BIG(*)"));
+
+        // FROM ... WHERE 部分逐字相同，计数与查询的行集合一致
+        let from_where = |sql: &str| {
+            let start = sql.find("FROM").unwrap();
+            let end = sql.find("ORDER BY").unwrap_or(sql.len());
+            sql[start..end].trim().to_string()
+        };
+        assert_eq!(from_where(&count), from_where(&select));
+    }
+
+    #[test]
+    fn test_value_neighbors_sql() {
+        let profile = DefaultProfile::new();
+        let sql = profile
+            .value_neighbors_sql("历史表", "T'1", "2024-01-01T10:00:30")
+            .unwrap();
+
+        assert_eq!(sql.matches("SELECT TOP 1").count(), 2);
+        assert!(sql.contains("TagName = 'T''1' AND DateTime <= '2024-01-01T10:00:30'"));
+        assert!(sql.contains("TagName = 'T''1' AND DateTime >= '2024-01-01T10:00:30'"));
+        assert!(sql.contains("ORDER BY DateTime DESC"));
+        assert!(sql.contains("UNION ALL"));
+    }
+
     #[test]
     fn test_history_query_sql_escapes_table_name() {
         let profile = DefaultProfile::new();
@@ -4,6 +4,11 @@
 
 mod default;
 mod registry;
+mod row_time;
+mod wide;
 
-pub use default::DefaultProfile;
+pub use default::{DEFAULT_TAG_TABLE, DefaultProfile};
 pub use registry::ProfileRegistry;
+pub use row_time::RecordTimeZone;
+pub(crate) use row_time::row_local_time;
+pub use wide::WideTableProfile;
@@ -4,10 +4,12 @@
 
 use std::sync::Arc;
 
+use crate::config::SchemaConfig;
 use crate::datasource::SchemaProfile;
 use crate::error::{AppError, AppResult};
 
-use super::DefaultProfile;
+use super::row_time::RecordTimeZone;
+use super::{DefaultProfile, WideTableProfile};
 
 /// Profile 注册表
 ///
@@ -27,6 +29,8 @@ impl ProfileRegistry {
     /// # Supported Profiles
     /// - `"default"` - 默认 Profile（当前厂商）
     ///
+    /// 宽表 Profile（`"wide"`）需要列配置，请使用 `from_config` 构建。
+    ///
     /// # Example
     /// ```ignore
     /// let profile = ProfileRegistry::get("default")?;
@@ -42,6 +46,64 @@ impl ProfileRegistry {
         }
     }
 
+    /// 根据名称获取 Profile，未知名称时回退到默认 Profile
+    ///
+    /// 用于从配置选择 Profile 的场景，配置错误不应阻止查询。
+    pub fn get_or_default(name: &str) -> Arc<dyn SchemaProfile> {
+        Self::get(name).unwrap_or_else(|e| {
+            tracing::warn!(
+                target: "industry_vis::datasource",
+                error = %e,
+                profile = %name,
+                "无法获取指定的 Profile，使用默认 Profile"
+            );
+            Self::default_profile()
+        })
+    }
+
+    /// 根据 Schema 配置构建 Profile
+    ///
+    /// 在 `get_or_default` 的基础上应用配置中的标签表名（如已通过自动发现保存），
+    /// 以及宽表模式的测点列。宽表列配置无效时回退到默认 Profile。
+    pub fn from_config(config: &SchemaConfig) -> Arc<dyn SchemaProfile> {
+        let time_zone = RecordTimeZone::parse(config.time_zone.as_deref()).unwrap_or_else(|e| {
+            tracing::warn!(
+                target: "industry_vis::datasource",
+                error = %e,
+                "时区配置无效，使用系统本地时区"
+            );
+            RecordTimeZone::Local
+        });
+        match (config.profile.as_str(), config.tag_table.as_deref()) {
+            ("wide", _) => {
+                match WideTableProfile::new(config.wide_columns.clone().unwrap_or_default()) {
+                    Ok(profile) => Arc::new(profile.with_time_zone(time_zone)),
+                    Err(e) => {
+                        tracing::warn!(
+                            target: "industry_vis::datasource",
+                            error = %e,
+                            "宽表 Profile 配置无效，使用默认 Profile"
+                        );
+                        Self::default_profile()
+                    }
+                }
+            }
+            ("default", tag_table) => {
+                let profile = match tag_table.map(str::trim) {
+                    Some(t) if !t.is_empty() => DefaultProfile::with_tag_table(t),
+                    _ => DefaultProfile::new(),
+                };
+                Arc::new(
+                    profile
+                        .with_index_hint(config.index_hint.clone())
+                        .with_tag_filter_first(config.tag_filter_first)
+                        .with_time_zone(time_zone),
+                )
+            }
+            (name, _) => Self::get_or_default(name),
+        }
+    }
+
     /// 获取默认 Profile
     ///
     /// 快捷方法，等同于 `ProfileRegistry::get("default")`
@@ -51,13 +113,14 @@ impl ProfileRegistry {
 
     /// 列出所有可用的 Profile 名称
     pub fn available_profiles() -> &'static [&'static str] {
-        &["default"]
+        &["default", "wide"]
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::datasource::TagSearchTerms;
 
     #[test]
     fn test_get_default_profile() {
@@ -74,6 +137,74 @@ mod tests {
         assert!(err.to_string().contains("unknown"));
     }
 
+    #[test]
+    fn test_get_or_default() {
+        assert_eq!(ProfileRegistry::get_or_default("default").name(), "default");
+        assert_eq!(ProfileRegistry::get_or_default("unknown").name(), "default");
+    }
+
+    #[test]
+    fn test_from_config_with_tag_table() {
+        let config = SchemaConfig {
+            profile: "default".to_string(),
+            tag_table: Some("点位表".to_string()),
+            wide_columns: None,
+            ..Default::default()
+        };
+        let profile = ProfileRegistry::from_config(&config);
+        assert_eq!(profile.name(), "default");
+        assert!(
+            profile
+                .tag_search_sql(10, &TagSearchTerms::default())
+                .contains("[点位表]")
+        );
+
+        let profile = ProfileRegistry::from_config(&SchemaConfig::default());
+        assert!(
+            profile
+                .tag_search_sql(10, &TagSearchTerms::default())
+                .contains("[TagDataBase]")
+        );
+    }
+
+    #[test]
+    fn test_from_config_wide() {
+        let config = SchemaConfig {
+            profile: "wide".to_string(),
+            tag_table: None,
+            wide_columns: Some(vec!["Temp".to_string(), "Flow".to_string()]),
+            ..Default::default()
+        };
+        let profile = ProfileRegistry::from_config(&config);
+        assert_eq!(profile.name(), "wide");
+        assert_eq!(
+            profile.fixed_tags(),
+            Some(vec!["Temp".to_string(), "Flow".to_string()])
+        );
+
+        // 未配置测点列时回退到默认 Profile
+        let config = SchemaConfig {
+            wide_columns: None,
+            ..config
+        };
+        assert_eq!(ProfileRegistry::from_config(&config).name(), "default");
+    }
+
+    #[test]
+    fn test_from_config_index_hint() {
+        let config = SchemaConfig {
+            index_hint: Some("IX_History_DateTime".to_string()),
+            tag_filter_first: true,
+            ..Default::default()
+        };
+        let profile = ProfileRegistry::from_config(&config);
+        let filter = profile.build_tag_filter(Some(&["A".to_string()]));
+        let sql = profile.history_query_sql("历史表", "2024-01-01", "2024-01-02", &filter);
+
+        assert!(sql.contains("WITH (NOLOCK, INDEX([IX_History_DateTime]))"));
+        assert!(sql.contains("WHERE TagName IN ('A') AND DateTime BETWEEN"));
+    }
+
     #[test]
     fn test_default_profile_shortcut() {
         let profile = ProfileRegistry::default_profile();
@@ -0,0 +1,125 @@
+//! 结果行时间列的读取
+//!
+//! 历史表的时间列可能是 DATETIME / SMALLDATETIME / DATETIME2（不带时区，原样作为本地时间），
+//! 也可能是 DATETIMEOFFSET（带偏移，换算为配置时区的本地时间）。
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime};
+use tiberius::{ColumnData, FromSql};
+
+use crate::error::{AppError, AppResult};
+
+/// 记录时间的输出格式
+const RECORD_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3f";
+
+/// DATETIMEOFFSET 换算的目标时区
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RecordTimeZone {
+    /// 系统本地时区
+    #[default]
+    Local,
+    /// 固定 UTC 偏移
+    Fixed(FixedOffset),
+}
+
+impl RecordTimeZone {
+    /// 解析配置的时区：`+08:00` 形式的 UTC 偏移，`UTC` / `Z` 为零偏移，未设置为系统本地时区
+    pub fn parse(value: Option<&str>) -> AppResult<Self> {
+        let Some(value) = value.map(str::trim).filter(|v| !v.is_empty()) else {
+            return Ok(Self::Local);
+        };
+        if value.eq_ignore_ascii_case("utc") || value.eq_ignore_ascii_case("z") {
+            return Ok(Self::Fixed(FixedOffset::east_opt(0).unwrap()));
+        }
+        value.parse::<FixedOffset>().map(Self::Fixed).map_err(|_| {
+            AppError::Config(format!(
+                "时区无效: '{}'，应为 UTC 偏移（如 +08:00）或 UTC",
+                value
+            ))
+        })
+    }
+
+    /// 带偏移的时间换算为本时区的本地时间
+    fn to_local(self, time: DateTime<FixedOffset>) -> NaiveDateTime {
+        match self {
+            Self::Local => time.with_timezone(&Local).naive_local(),
+            Self::Fixed(offset) => time.with_timezone(&offset).naive_local(),
+        }
+    }
+}
+
+/// 时间列的值换算为本地时间（NULL 或非时间类型时返回 `None`）
+pub fn column_local_time(
+    data: &ColumnData<'static>,
+    time_zone: RecordTimeZone,
+) -> Option<NaiveDateTime> {
+    match data {
+        ColumnData::DateTimeOffset(_) => DateTime::<FixedOffset>::from_sql(data)
+            .ok()
+            .flatten()
+            .map(|time| time_zone.to_local(time)),
+        _ => NaiveDateTime::from_sql(data).ok().flatten(),
+    }
+}
+
+/// 读取第 `index` 列的时间，格式化为本地时间字符串（无法读取时为空字符串）
+pub fn row_local_time(row: &tiberius::Row, index: usize, time_zone: RecordTimeZone) -> String {
+    row.cells()
+        .nth(index)
+        .and_then(|(_, data)| column_local_time(data, time_zone))
+        .map(|time| time.format(RECORD_TIME_FORMAT).to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use tiberius::IntoSql;
+
+    #[test]
+    fn test_offset_column_converts_to_configured_local_time() {
+        // 2024-03-01 10:30:00.250 +00:00（UTC）
+        let utc = NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_milli_opt(10, 30, 0, 250)
+            .unwrap();
+        let stored = DateTime::<FixedOffset>::from_naive_utc_and_offset(
+            utc,
+            FixedOffset::west_opt(5 * 3600).unwrap(),
+        );
+        let data: ColumnData<'static> = stored.into_sql();
+        assert!(matches!(data, ColumnData::DateTimeOffset(Some(_))));
+
+        let beijing = RecordTimeZone::parse(Some("+08:00")).unwrap();
+        assert_eq!(
+            column_local_time(&data, beijing)
+                .unwrap()
+                .format(RECORD_TIME_FORMAT)
+                .to_string(),
+            "2024-03-01T18:30:00.250"
+        );
+        let utc_zone = RecordTimeZone::parse(Some("UTC")).unwrap();
+        assert_eq!(column_local_time(&data, utc_zone), Some(utc));
+        assert_eq!(
+            column_local_time(&data, RecordTimeZone::Local),
+            Some(stored.with_timezone(&Local).naive_local())
+        );
+
+        // DATETIME2 不带时区，原样作为本地时间
+        let local = NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_opt(8, 0, 0)
+            .unwrap();
+        let data: ColumnData<'static> =
+            DateTime::<chrono::Utc>::from_naive_utc_and_offset(local, chrono::Utc).into_sql();
+        assert!(matches!(data, ColumnData::DateTime2(Some(_))));
+        assert_eq!(column_local_time(&data, beijing), Some(local));
+
+        assert_eq!(
+            column_local_time(&ColumnData::DateTimeOffset(None), beijing),
+            None
+        );
+        assert!(RecordTimeZone::parse(Some("Asia/Shanghai")).is_err());
+        assert_eq!(RecordTimeZone::parse(None).unwrap(), RecordTimeZone::Local);
+    }
+}
@@ -0,0 +1,267 @@
+//! 宽表 Schema Profile
+//!
+//! 适配一行包含多个测点列的历史表（如 `DateTime, Temp, Pressure, Flow`），
+//! 每行按配置的测点列展开为多条 `HistoryRecord`，标签名即列名。
+
+use super::row_time::{RecordTimeZone, row_local_time};
+use crate::datasource::{SchemaProfile, TagSearchTerms};
+use crate::error::{AppError, AppResult};
+use crate::models::{HistoryRecord, Interner};
+
+/// 宽表记录的质量值（宽表无质量列）
+const WIDE_TABLE_QUALITY: &str = "Good";
+
+/// 宽表 Schema Profile
+#[derive(Debug, Clone)]
+pub struct WideTableProfile {
+    value_columns: Vec<String>,
+    time_zone: RecordTimeZone,
+}
+
+impl WideTableProfile {
+    /// 创建宽表 Profile
+    ///
+    /// `value_columns` 为测点列名，不能为空。
+    pub fn new(value_columns: Vec<String>) -> AppResult<Self> {
+        let value_columns: Vec<String> = value_columns
+            .into_iter()
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty())
+            .collect();
+        if value_columns.is_empty() {
+            return Err(AppError::Config(
+                "宽表 Profile 需在 schema.wide_columns 中配置测点列".to_string(),
+            ));
+        }
+        Ok(Self {
+            value_columns,
+            time_zone: RecordTimeZone::Local,
+        })
+    }
+
+    /// 设置 DATETIMEOFFSET 时间列换算的目标时区
+    pub fn with_time_zone(mut self, time_zone: RecordTimeZone) -> Self {
+        self.time_zone = time_zone;
+        self
+    }
+
+    /// 配置的测点列
+    pub fn value_columns(&self) -> &[String] {
+        &self.value_columns
+    }
+
+    /// 将一行的时间和各测点列值展开为多条记录（空值跳过）
+    pub fn expand_row(
+        date_time: &str,
+        values: &[(&str, Option<f64>)],
+        interner: &mut Interner,
+    ) -> Vec<HistoryRecord> {
+        values
+            .iter()
+            .filter_map(|(column, value)| {
+                value.map(|v| {
+                    HistoryRecord::interned(
+                        date_time.to_string(),
+                        column,
+                        v,
+                        WIDE_TABLE_QUALITY,
+                        interner,
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+/// 方括号转义列名
+fn quote_column(name: &str) -> String {
+    format!("[{}]", name.replace(']', "]]"))
+}
+
+impl SchemaProfile for WideTableProfile {
+    fn name(&self) -> &str {
+        "wide"
+    }
+
+    fn record_time_zone(&self) -> RecordTimeZone {
+        self.time_zone
+    }
+
+    /// 测点即配置的列名，直接在常量表中搜索
+    fn tag_search_sql(&self, limit: usize, terms: &TagSearchTerms) -> String {
+        let values = self
+            .value_columns
+            .iter()
+            .map(|c| format!("(N'{}')", c.replace('\'', "''")))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            "SELECT TOP {} TagName FROM (VALUES {}) AS t(TagName) WHERE {} ORDER BY TagName",
+            limit,
+            values,
+            terms.where_clause("TagName")
+        )
+    }
+
+    /// `tag_filter` 在宽表模式下为列选择片段（见 `build_tag_filter`）
+    fn history_query_sql(
+        &self,
+        table: &str,
+        start_time: &str,
+        end_time: &str,
+        tag_filter: &str,
+    ) -> String {
+        format!(
+            r#"SELECT {}, {}
+               FROM [{}] WITH (NOLOCK)
+               WHERE {} BETWEEN '{}' AND '{}'
+               ORDER BY {}"#,
+            quote_column(self.datetime_column_name()),
+            tag_filter,
+            table.replace(']', "]]"),
+            quote_column(self.datetime_column_name()),
+            start_time.replace('\'', "''"),
+            end_time.replace('\'', "''"),
+            quote_column(self.datetime_column_name()),
+        )
+    }
+
+    fn map_history_row(
+        &self,
+        row: &tiberius::Row,
+        interner: &mut Interner,
+    ) -> AppResult<HistoryRecord> {
+        self.map_history_rows(row, interner)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::DataProcessing("宽表行不含有效测点值".to_string()))
+    }
+
+    /// 按结果列名展开：第 0 列为时间，其余列为测点
+    fn map_history_rows(
+        &self,
+        row: &tiberius::Row,
+        interner: &mut Interner,
+    ) -> AppResult<Vec<HistoryRecord>> {
+        let date_time = row_local_time(row, 0, self.time_zone);
+
+        let values: Vec<(&str, Option<f64>)> = row
+            .columns()
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(i, column)| {
+                let value = row
+                    .try_get::<f64, _>(i)
+                    .ok()
+                    .flatten()
+                    .or_else(|| row.try_get::<f32, _>(i).ok().flatten().map(f64::from))
+                    .or_else(|| row.try_get::<i32, _>(i).ok().flatten().map(f64::from));
+                (column.name(), value)
+            })
+            .collect();
+
+        Ok(Self::expand_row(&date_time, &values, interner))
+    }
+
+    /// 宽表模式下返回要选择的测点列（如 `[Temp], [Flow]`），未指定标签时选择全部测点列
+    ///
+    /// 指定的标签都不在配置中时返回空字符串，调用方需先排除这种查询（见 `fixed_tags`），
+    /// 不能退化为查询全部测点列，否则并行拆分后的各路结果会重复。
+    fn build_tag_filter(&self, tags: Option<&[String]>) -> String {
+        let columns: Vec<&String> = match tags {
+            Some(t) if !t.is_empty() => self
+                .value_columns
+                .iter()
+                .filter(|c| t.contains(c))
+                .collect(),
+            _ => self.value_columns.iter().collect(),
+        };
+
+        columns
+            .iter()
+            .map(|c| quote_column(c))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn fixed_tags(&self) -> Option<Vec<String>> {
+        Some(self.value_columns.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile() -> WideTableProfile {
+        WideTableProfile::new(vec![
+            "Temp".to_string(),
+            "Pressure".to_string(),
+            "Flow".to_string(),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_expand_row() {
+        let records = WideTableProfile::expand_row(
+            "2024-01-01T00:00:00.000",
+            &[
+                ("Temp", Some(85.5)),
+                ("Pressure", Some(1.2)),
+                ("Flow", Some(300.0)),
+            ],
+            &mut Interner::new(),
+        );
+
+        assert_eq!(records.len(), 3);
+        assert!(
+            records
+                .iter()
+                .all(|r| r.date_time == "2024-01-01T00:00:00.000")
+        );
+        let names: Vec<&str> = records.iter().map(|r| r.tag_name.as_str()).collect();
+        assert_eq!(names, vec!["Temp", "Pressure", "Flow"]);
+        assert_eq!(records[1].tag_val, 1.2);
+    }
+
+    #[test]
+    fn test_expand_row_skips_null() {
+        let records = WideTableProfile::expand_row(
+            "2024-01-01T00:00:00.000",
+            &[("Temp", None), ("Flow", Some(1.0))],
+            &mut Interner::new(),
+        );
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].tag_name, "Flow");
+    }
+
+    #[test]
+    fn test_requires_columns() {
+        assert!(WideTableProfile::new(vec![]).is_err());
+        assert!(WideTableProfile::new(vec![" ".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_history_sql_selects_columns() {
+        let profile = profile();
+        let filter = profile.build_tag_filter(Some(&["Flow".to_string(), "Other".to_string()]));
+        assert_eq!(filter, "[Flow]");
+
+        let sql = profile.history_query_sql("宽表", "2024-01-01", "2024-01-02", &filter);
+        assert!(sql.contains("SELECT [DateTime], [Flow]"));
+        assert!(sql.contains("[宽表]"));
+
+        assert_eq!(profile.build_tag_filter(None), "[Temp], [Pressure], [Flow]");
+        assert_eq!(profile.build_tag_filter(Some(&["Other".to_string()])), "");
+    }
+
+    #[test]
+    fn test_tag_search_sql() {
+        let sql = profile().tag_search_sql(10, &TagSearchTerms::parse("Temp"));
+        assert!(sql.contains("TOP 10"));
+        assert!(sql.contains("(N'Temp')"));
+        assert!(sql.contains("LIKE @P1"));
+    }
+}
@@ -0,0 +1,54 @@
+//! 历史查询计划
+//!
+//! 描述一次历史查询可下推到 SQL 层的选项（标签过滤、每标签限量、限行），
+//! 由 `SchemaProfile::build_query` 统一生成单条 SQL。
+//! 各选项的叠加顺序固定，见 `QueryPlan` 文档。
+
+/// 历史查询计划
+///
+/// 选项按以下顺序生效：
+/// 1. 时间范围、标签过滤（原始行）；
+/// 2. 每标签只保留最新的 `per_tag_limit` 个点；
+/// 3. 按时间排序后只取前 `max_rows` 行。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryPlan {
+    /// 历史表名（未转义）
+    pub table: String,
+    pub start_time: String,
+    pub end_time: String,
+    /// 标签过滤（`None` 或空表示全部标签）
+    pub tags: Option<Vec<String>>,
+    pub per_tag_limit: Option<usize>,
+    /// 最大返回行数
+    pub max_rows: Option<usize>,
+}
+
+impl QueryPlan {
+    /// 创建查询计划（不下推其他选项）
+    pub fn new(table: &str, start_time: &str, end_time: &str) -> Self {
+        Self {
+            table: table.to_string(),
+            start_time: start_time.to_string(),
+            end_time: end_time.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// 设置标签过滤
+    pub fn with_tags(mut self, tags: Option<&[String]>) -> Self {
+        self.tags = tags.map(<[String]>::to_vec);
+        self
+    }
+
+    /// 设置每标签最新点数上限
+    pub fn with_per_tag_limit(mut self, per_tag_limit: Option<usize>) -> Self {
+        self.per_tag_limit = per_tag_limit;
+        self
+    }
+
+    /// 设置最大返回行数
+    pub fn with_max_rows(mut self, max_rows: Option<usize>) -> Self {
+        self.max_rows = max_rows;
+        self
+    }
+}
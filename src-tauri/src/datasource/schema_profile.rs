@@ -2,8 +2,65 @@
 //!
 //! 提供数据库 Schema 配置的抽象接口，支持不同厂商的表结构和字段映射。
 
+use super::QueryPlan;
+use super::profiles::RecordTimeZone;
 use crate::error::AppResult;
-use crate::models::HistoryRecord;
+use crate::models::{HistoryRecord, Interner};
+
+/// 标签搜索关键词
+///
+/// 关键词按空白分隔，每个词都需包含（AND）；`-` 前缀的词为排除词，
+/// 如 `Temp -Setpoint` 匹配含 Temp 且不含 Setpoint 的标签。单独的 `-` 忽略。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TagSearchTerms {
+    /// 需包含的词
+    pub include: Vec<String>,
+    /// 需排除的词
+    pub exclude: Vec<String>,
+}
+
+impl TagSearchTerms {
+    /// 解析搜索关键词
+    pub fn parse(keyword: &str) -> Self {
+        let mut terms = Self::default();
+        for word in keyword.split_whitespace() {
+            match word.strip_prefix('-') {
+                Some("") => {}
+                Some(excluded) => terms.exclude.push(excluded.to_string()),
+                None => terms.include.push(word.to_string()),
+            }
+        }
+        terms
+    }
+
+    /// 生成 WHERE 条件（包含词 `LIKE`、排除词 `NOT LIKE`，参数从 `@P1` 起编号）
+    ///
+    /// 只有排除词时匹配其余全部标签；没有任何词时匹配全部标签。
+    pub fn where_clause(&self, column: &str) -> String {
+        let conditions: Vec<String> = self
+            .include
+            .iter()
+            .map(|_| "LIKE")
+            .chain(self.exclude.iter().map(|_| "NOT LIKE"))
+            .enumerate()
+            .map(|(i, op)| format!("{} {} @P{}", column, op, i + 1))
+            .collect();
+        if conditions.is_empty() {
+            "1 = 1".to_string()
+        } else {
+            conditions.join(" AND ")
+        }
+    }
+
+    /// 与 `where_clause` 占位符顺序一致的 LIKE 模式
+    pub fn patterns(&self) -> Vec<String> {
+        self.include
+            .iter()
+            .chain(&self.exclude)
+            .map(|word| format!("%{}%", word))
+            .collect()
+    }
+}
 
 /// Schema Profile trait
 ///
@@ -19,8 +76,12 @@ use crate::models::HistoryRecord;
 ///
 /// impl SchemaProfile for CustomProfile {
 ///     fn name(&self) -> &str { "custom" }
-///     fn tag_search_sql(&self, limit: usize) -> String {
-///         format!("SELECT TOP {} TagName FROM MyTagTable WHERE TagName LIKE @P1", limit)
+///     fn tag_search_sql(&self, limit: usize, terms: &TagSearchTerms) -> String {
+///         format!(
+///             "SELECT TOP {} TagName FROM MyTagTable WHERE {}",
+///             limit,
+///             terms.where_clause("TagName")
+///         )
 ///     }
 ///     // ... 其他方法
 /// }
@@ -33,10 +94,12 @@ pub trait SchemaProfile: Send + Sync {
     ///
     /// # Arguments
     /// * `limit` - 返回结果数量限制
+    /// * `terms` - 解析后的搜索关键词
     ///
     /// # Returns
-    /// SQL 查询字符串，使用 `@P1` 作为搜索关键词的参数占位符（LIKE 模式）
-    fn tag_search_sql(&self, limit: usize) -> String;
+    /// SQL 查询字符串，过滤条件用 `terms.where_clause` 生成，
+    /// 参数占位符 `@P1..` 依次绑定 `terms.patterns()`
+    fn tag_search_sql(&self, limit: usize, terms: &TagSearchTerms) -> String;
 
     /// 生成历史数据查询 SQL
     ///
@@ -56,14 +119,103 @@ pub trait SchemaProfile: Send + Sync {
         tag_filter: &str,
     ) -> String;
 
+    /// 生成每标签限量的历史查询 SQL（每个标签只取时间范围内最新的 `per_tag_limit` 个点）
+    ///
+    /// 结果列与 `history_query_sql` 一致，按时间升序。
+    /// 返回 `None` 表示该 Profile 不支持 SQL 层限量，由调用方在内存中截取。
+    fn history_query_sql_per_tag_limit(
+        &self,
+        _table: &str,
+        _start_time: &str,
+        _end_time: &str,
+        _tag_filter: &str,
+        _per_tag_limit: usize,
+    ) -> Option<String> {
+        None
+    }
+
+    /// 生成限制最大行数的历史查询 SQL（按时间升序取最早的 `max_rows` 行）
+    ///
+    /// 结果列与 `history_query_sql` 一致。
+    /// 返回 `None` 表示该 Profile 不支持 SQL 层限行，由调用方在内存中截断。
+    fn history_query_sql_max_rows(
+        &self,
+        _table: &str,
+        _start_time: &str,
+        _end_time: &str,
+        _tag_filter: &str,
+        _max_rows: usize,
+    ) -> Option<String> {
+        None
+    }
+
+    /// 按查询计划生成单条历史查询 SQL
+    ///
+    /// 结果列与 `history_query_sql` 一致，计划中的全部选项都在 SQL 层完成。
+    /// 返回 `None` 表示该 Profile 不支持查询计划，由调用方退回 `history_query_sql*`
+    /// 并在内存中处理其余选项。
+    fn build_query(&self, _plan: &QueryPlan) -> Option<String> {
+        None
+    }
+
+    /// 生成历史数据计数 SQL（与 `history_query_sql` 相同的过滤条件）
+    ///
+    /// 结果为单行单列的行数（BIGINT）。返回 `None` 表示该 Profile 不支持预估。
+    fn history_count_sql(
+        &self,
+        _table: &str,
+        _start_time: &str,
+        _end_time: &str,
+        _tag_filter: &str,
+    ) -> Option<String> {
+        None
+    }
+
+    /// 生成取某标签在指定时刻前后最近点的 SQL
+    ///
+    /// 结果最多两行（`<= time` 的最后一点和 `>= time` 的第一点），列与 `history_query_sql` 一致。
+    /// 返回 `None` 表示该 Profile 不支持按时刻取值。
+    fn value_neighbors_sql(&self, _table: &str, _tag: &str, _time: &str) -> Option<String> {
+        None
+    }
+
     /// 将数据库行映射为 HistoryRecord
     ///
     /// # Arguments
     /// * `row` - tiberius 查询结果行
+    /// * `interner` - 本批结果共用的驻留器（标签名、质量码经它驻留）
     ///
     /// # Returns
     /// 映射后的 HistoryRecord
-    fn map_history_row(&self, row: &tiberius::Row) -> AppResult<HistoryRecord>;
+    fn map_history_row(
+        &self,
+        row: &tiberius::Row,
+        interner: &mut Interner,
+    ) -> AppResult<HistoryRecord>;
+
+    /// 将数据库行映射为一条或多条 HistoryRecord
+    ///
+    /// 长表（一行一个标签）默认委托 `map_history_row`；
+    /// 宽表（一行多个测点列）覆盖此方法将一行展开为多条记录。
+    fn map_history_rows(
+        &self,
+        row: &tiberius::Row,
+        interner: &mut Interner,
+    ) -> AppResult<Vec<HistoryRecord>> {
+        Ok(vec![self.map_history_row(row, interner)?])
+    }
+
+    /// DATETIMEOFFSET 时间列换算的目标时区（默认系统本地时区）
+    fn record_time_zone(&self) -> RecordTimeZone {
+        RecordTimeZone::Local
+    }
+
+    /// 固定的标签列表
+    ///
+    /// 宽表模式下测点即配置的列名，无需查询数据库；长表返回 `None`。
+    fn fixed_tags(&self) -> Option<Vec<String>> {
+        None
+    }
 
     /// 生成标签过滤条件
     ///
@@ -118,10 +270,11 @@ mod tests {
             "test"
         }
 
-        fn tag_search_sql(&self, limit: usize) -> String {
+        fn tag_search_sql(&self, limit: usize, terms: &TagSearchTerms) -> String {
             format!(
-                "SELECT TOP {} TagName FROM TestTags WHERE TagName LIKE @P1",
-                limit
+                "SELECT TOP {} TagName FROM TestTags WHERE {}",
+                limit,
+                terms.where_clause("TagName")
             )
         }
 
@@ -138,7 +291,11 @@ mod tests {
             )
         }
 
-        fn map_history_row(&self, _row: &tiberius::Row) -> AppResult<HistoryRecord> {
+        fn map_history_row(
+            &self,
+            _row: &tiberius::Row,
+            _interner: &mut Interner,
+        ) -> AppResult<HistoryRecord> {
             Ok(HistoryRecord::new(
                 "2024-01-01T00:00:00".to_string(),
                 "TestTag".to_string(),
@@ -157,11 +314,42 @@ mod tests {
     #[test]
     fn test_tag_search_sql() {
         let profile = TestProfile;
-        let sql = profile.tag_search_sql(50);
+        let sql = profile.tag_search_sql(50, &TagSearchTerms::parse("Temp"));
         assert!(sql.contains("TOP 50"));
         assert!(sql.contains("@P1"));
     }
 
+    #[test]
+    fn test_search_terms_exclude_words() {
+        let terms = TagSearchTerms::parse("  Temp -Setpoint ");
+        assert_eq!(terms.include, ["Temp"]);
+        assert_eq!(terms.exclude, ["Setpoint"]);
+        assert_eq!(
+            terms.where_clause("TagName"),
+            "TagName LIKE @P1 AND TagName NOT LIKE @P2"
+        );
+        assert_eq!(terms.patterns(), ["%Temp%", "%Setpoint%"]);
+    }
+
+    #[test]
+    fn test_search_terms_exclude_only_and_empty() {
+        // 只有排除词：匹配其余全部标签
+        let terms = TagSearchTerms::parse("-Setpoint -Alarm");
+        assert!(terms.include.is_empty());
+        assert_eq!(
+            terms.where_clause("TagName"),
+            "TagName NOT LIKE @P1 AND TagName NOT LIKE @P2"
+        );
+        assert_eq!(terms.patterns(), ["%Setpoint%", "%Alarm%"]);
+
+        // 单独的 "-" 和空关键词：匹配全部
+        for keyword in ["", "-", " - "] {
+            let terms = TagSearchTerms::parse(keyword);
+            assert_eq!(terms.where_clause("TagName"), "1 = 1");
+            assert!(terms.patterns().is_empty());
+        }
+    }
+
     #[test]
     fn test_build_tag_filter_empty() {
         let profile = TestProfile;
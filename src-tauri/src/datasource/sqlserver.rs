@@ -4,17 +4,24 @@
 //! 支持通过 SchemaProfile 配置不同厂商的数据库结构。
 
 use async_trait::async_trait;
+use bb8::PooledConnection;
 use std::sync::Arc;
 use tiberius::Query;
-use tracing::{debug, error, info};
+use tokio::task::JoinSet;
+use tracing::{debug, error, info, warn};
 
-use super::pool::ConnectionPool;
+use super::custom_sql::{check_result_columns, map_custom_row, validate_custom_sql};
+use super::pool::{ConnectionManager, ConnectionPool, TiberiusClient};
 use super::profiles::ProfileRegistry;
-use super::schema_profile::SchemaProfile;
-use super::traits::{DataSource, SourceMetadata, TableInfo};
-use crate::config::DatabaseConfig;
+use super::query_plan::QueryPlan;
+use super::schema_profile::{SchemaProfile, TagSearchTerms};
+use super::traits::{
+    DataSource, SourceMetadata, TableInfo, TagTableCandidate, keep_latest_per_tag,
+    rank_tag_table_candidates,
+};
+use crate::config::{DatabaseConfig, IsolationLevel};
 use crate::error::{AppError, AppResult};
-use crate::models::HistoryRecord;
+use crate::models::{HistoryRecord, Interner};
 
 /// SQL Server 数据源实现
 ///
@@ -25,6 +32,12 @@ pub struct SqlServerSource {
     pool: Arc<ConnectionPool>,
     metadata: SourceMetadata,
     profile: Arc<dyn SchemaProfile>,
+    /// 历史查询按标签拆分的并行路数（1 表示不拆分）
+    parallelism: usize,
+    /// 单次历史查询的最大行数（`None` 表示不限制）
+    max_rows: Option<usize>,
+    /// 查询前设置的会话隔离级别（`None` 表示不设置）
+    isolation_level: Option<IsolationLevel>,
 }
 
 impl SqlServerSource {
@@ -54,6 +67,9 @@ impl SqlServerSource {
             pool: Arc::new(pool),
             metadata,
             profile,
+            parallelism: 1,
+            max_rows: None,
+            isolation_level: None,
         })
     }
 
@@ -77,9 +93,44 @@ impl SqlServerSource {
             pool,
             metadata,
             profile,
+            parallelism: 1,
+            max_rows: None,
+            isolation_level: None,
         }
     }
 
+    /// 设置历史查询按标签并行拆分的路数
+    ///
+    /// 实际并发度不超过连接池最大连接数和标签数；拆分会增加数据库并发负载。
+    pub fn with_parallelism(mut self, parallelism: usize) -> Self {
+        self.parallelism = parallelism.max(1);
+        self
+    }
+
+    /// 设置单次历史查询的最大行数
+    ///
+    /// SQL 层多取一行（`TOP max_rows + 1`），调用方据此判断结果是否被截断。
+    /// 按标签并行拆分时每路各自限行，合并后由调用方统一截断。
+    pub fn with_max_result_rows(mut self, max_rows: Option<usize>) -> Self {
+        self.max_rows = max_rows;
+        self
+    }
+
+    /// 设置查询会话的事务隔离级别
+    ///
+    /// 每次从连接池取出连接后先发送设置语句再执行查询（连接归还后可能被其他会话设置改写）。
+    pub fn with_isolation_level(mut self, isolation_level: Option<IsolationLevel>) -> Self {
+        self.isolation_level = isolation_level;
+        self
+    }
+
+    /// 从连接池获取连接，并按配置设置会话隔离级别
+    async fn connection(&self) -> AppResult<PooledConnection<'_, ConnectionManager>> {
+        let mut conn = self.pool.get().await?;
+        prepare_session(&mut **conn, self.isolation_level).await?;
+        Ok(conn)
+    }
+
     /// 获取连接池引用
     pub fn pool(&self) -> &Arc<ConnectionPool> {
         &self.pool
@@ -94,17 +145,223 @@ impl SqlServerSource {
     fn database(&self) -> &str {
         &self.metadata.database
     }
+
+    /// 执行自定义只读 SQL 查询
+    ///
+    /// SQL 需通过 `validate_custom_sql` 校验，结果必须为固定的四列。
+    /// 查询在事务中执行并始终回滚，作为关键字黑名单之外的只读兜底。
+    pub async fn run_custom_query(&self, sql: &str) -> AppResult<Vec<HistoryRecord>> {
+        validate_custom_sql(sql)?;
+
+        let mut conn = self.connection().await?;
+        let database = self.database().to_string();
+
+        debug!(target: "industry_vis::datasource",
+            database = %database,
+            "执行自定义 SQL 查询"
+        );
+
+        conn.simple_query("BEGIN TRANSACTION")
+            .await
+            .map_err(|e| AppError::Query(format!("开启事务失败: {}", e)))?
+            .into_results()
+            .await
+            .map_err(|e| AppError::Query(format!("开启事务失败: {}", e)))?;
+
+        let result = async {
+            let mut stream = conn.simple_query(sql).await.map_err(|e| {
+                error!(target: "industry_vis::datasource",
+                    database = %database,
+                    error = %e,
+                    "自定义 SQL 查询失败"
+                );
+                AppError::Query(format!("自定义查询失败: {}", e))
+            })?;
+
+            let columns: Vec<String> = stream
+                .columns()
+                .await
+                .map_err(|e| AppError::Query(format!("获取结果列失败: {}", e)))?
+                .map(|cols| cols.iter().map(|c| c.name().to_string()).collect())
+                .unwrap_or_default();
+            let rows = stream
+                .into_first_result()
+                .await
+                .map_err(|e| AppError::Query(format!("获取自定义查询结果失败: {}", e)))?;
+
+            let column_names: Vec<&str> = columns.iter().map(String::as_str).collect();
+            check_result_columns(&column_names)?;
+
+            let time_zone = self.profile.record_time_zone();
+            let mut interner = Interner::new();
+            Ok::<_, AppError>(
+                rows.iter()
+                    .map(|row| map_custom_row(row, time_zone, &mut interner))
+                    .collect::<Vec<_>>(),
+            )
+        }
+        .await;
+
+        // 无论成功与否都回滚，确保不产生任何写入
+        let rollback = match conn
+            .simple_query("IF @@TRANCOUNT > 0 ROLLBACK TRANSACTION")
+            .await
+        {
+            Ok(stream) => stream.into_results().await.map(|_| ()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = rollback {
+            // 事务可能仍未结束，丢弃连接而不是带着未结束的事务归还连接池
+            warn!(target: "industry_vis::datasource", "回滚自定义查询事务失败，丢弃连接: {}", e);
+            conn.mark_broken();
+        }
+
+        let records = result?;
+        info!(target: "industry_vis::datasource",
+            database = %database,
+            records = records.len(),
+            "自定义 SQL 查询完成"
+        );
+
+        Ok(records)
+    }
+
+    /// 执行单条历史查询（一个连接）
+    ///
+    /// `per_tag_limit` 为每标签最新点数上限：Profile 支持时在 SQL 层限量，否则在内存中截取。
+    async fn query_history_single(
+        &self,
+        table: &str,
+        start_time: &str,
+        end_time: &str,
+        tags: Option<&[String]>,
+        per_tag_limit: Option<usize>,
+    ) -> AppResult<Vec<HistoryRecord>> {
+        if self.no_tag_mapped(tags) {
+            return Ok(Vec::new());
+        }
+        let mut conn = self.connection().await?;
+        let database = self.database().to_string();
+
+        let tag_count = tags.map(|t| t.len()).unwrap_or(0);
+
+        // 优先由查询计划生成单条 SQL（每标签限量与限行同时下推）
+        let plan = QueryPlan::new(table, start_time, end_time)
+            .with_tags(tags)
+            .with_per_tag_limit(per_tag_limit)
+            .with_max_rows(self.max_rows.map(|max_rows| max_rows.saturating_add(1)));
+        let (sql, sql_limited) = match self.profile.build_query(&plan) {
+            Some(sql) => (sql, true),
+            None => self.legacy_history_sql(table, start_time, end_time, tags, per_tag_limit),
+        };
+
+        debug!(target: "industry_vis::datasource",
+            database = %database,
+            table = %table,
+            start_time = %start_time,
+            end_time = %end_time,
+            tag_count = tag_count,
+            per_tag_limit = ?per_tag_limit,
+            profile = %self.profile.name(),
+            "执行历史查询"
+        );
+
+        let query = Query::new(&sql);
+        let stream = query.query(&mut **conn).await.map_err(|e| {
+            error!(target: "industry_vis::datasource",
+                database = %database,
+                error = %e,
+                "历史查询失败"
+            );
+            AppError::Query(format!("历史查询失败: {}", e))
+        })?;
+
+        let rows = stream
+            .into_first_result()
+            .await
+            .map_err(|e| AppError::Query(format!("获取历史结果失败: {}", e)))?;
+
+        // 使用 Profile 映射行数据
+        let mut records: Vec<HistoryRecord> = Vec::with_capacity(rows.len());
+        let mut interner = Interner::new();
+        for row in rows.iter() {
+            records.extend(self.profile.map_history_rows(row, &mut interner)?);
+        }
+        if let Some(limit) = per_tag_limit
+            && !sql_limited
+        {
+            records = keep_latest_per_tag(records, limit);
+        }
+
+        info!(target: "industry_vis::datasource",
+            database = %database,
+            table = %table,
+            records = records.len(),
+            "历史查询完成"
+        );
+
+        Ok(records)
+    }
+
+    /// 固定测点的 Profile（宽表）下，请求的标签都不是测点时结果为空，无需查库
+    fn no_tag_mapped(&self, tags: Option<&[String]>) -> bool {
+        match (self.profile.fixed_tags(), tags) {
+            (Some(fixed), Some(tags)) if !tags.is_empty() => {
+                !tags.iter().any(|tag| fixed.contains(tag))
+            }
+            _ => false,
+        }
+    }
+
+    /// Profile 不支持查询计划时，按各特性的单独 SQL 生成历史查询
+    ///
+    /// 返回 SQL 及每标签限量是否已在 SQL 层完成。
+    fn legacy_history_sql(
+        &self,
+        table: &str,
+        start_time: &str,
+        end_time: &str,
+        tags: Option<&[String]>,
+        per_tag_limit: Option<usize>,
+    ) -> (String, bool) {
+        let tag_filter = self.profile.build_tag_filter(tags);
+        let limited_sql = per_tag_limit.and_then(|limit| {
+            self.profile.history_query_sql_per_tag_limit(
+                table,
+                start_time,
+                end_time,
+                &tag_filter,
+                limit,
+            )
+        });
+        let sql_limited = limited_sql.is_some();
+        let max_rows_sql = match (&limited_sql, self.max_rows) {
+            (None, Some(max_rows)) => self.profile.history_query_sql_max_rows(
+                table,
+                start_time,
+                end_time,
+                &tag_filter,
+                max_rows.saturating_add(1),
+            ),
+            _ => None,
+        };
+        let sql = limited_sql.or(max_rows_sql).unwrap_or_else(|| {
+            self.profile
+                .history_query_sql(table, start_time, end_time, &tag_filter)
+        });
+        (sql, sql_limited)
+    }
 }
 
 #[async_trait]
 impl DataSource for SqlServerSource {
     async fn test_connection(&self) -> AppResult<()> {
-        let mut conn = self.pool.get().await?;
+        let mut conn = self.connection().await?;
 
         // Execute a simple query to verify connection
         let query = Query::new("SELECT 1");
         query
-            .execute(&mut *conn)
+            .execute(&mut **conn)
             .await
             .map_err(|e| AppError::Connection(format!("测试查询失败: {}", e)))?;
 
@@ -116,14 +373,14 @@ impl DataSource for SqlServerSource {
     }
 
     async fn list_tables(&self) -> AppResult<Vec<TableInfo>> {
-        let mut conn = self.pool.get().await?;
+        let mut conn = self.connection().await?;
 
         let query = Query::new(
             "SELECT TABLE_SCHEMA, TABLE_NAME FROM INFORMATION_SCHEMA.TABLES WHERE TABLE_TYPE = 'BASE TABLE'",
         );
 
         let stream = query
-            .query(&mut *conn)
+            .query(&mut **conn)
             .await
             .map_err(|e| AppError::Query(format!("获取表列表失败: {}", e)))?;
 
@@ -146,7 +403,12 @@ impl DataSource for SqlServerSource {
     }
 
     async fn get_available_tags(&self, table: &str) -> AppResult<Vec<String>> {
-        let mut conn = self.pool.get().await?;
+        if let Some(mut tags) = self.profile.fixed_tags() {
+            tags.sort();
+            return Ok(tags);
+        }
+
+        let mut conn = self.connection().await?;
 
         let sql = format!(
             "SELECT DISTINCT TagName FROM [{}] ORDER BY TagName",
@@ -155,7 +417,7 @@ impl DataSource for SqlServerSource {
 
         let query = Query::new(&sql);
         let stream = query
-            .query(&mut *conn)
+            .query(&mut **conn)
             .await
             .map_err(|e| AppError::Query(format!("获取标签失败: {}", e)))?;
 
@@ -172,26 +434,79 @@ impl DataSource for SqlServerSource {
         Ok(tags)
     }
 
+    async fn discover_tag_table(&self) -> AppResult<Vec<TagTableCandidate>> {
+        let mut conn = self.connection().await?;
+        let tag_column = self.profile.tag_column_name().to_string();
+
+        // 查询所有包含标签列的表的全部列
+        let sql = r#"SELECT c.TABLE_SCHEMA, c.TABLE_NAME, c.COLUMN_NAME
+               FROM INFORMATION_SCHEMA.COLUMNS c
+               WHERE EXISTS (
+                   SELECT 1 FROM INFORMATION_SCHEMA.COLUMNS t
+                   WHERE t.TABLE_SCHEMA = c.TABLE_SCHEMA
+                     AND t.TABLE_NAME = c.TABLE_NAME
+                     AND t.COLUMN_NAME = @P1
+               )"#;
+
+        let mut query = Query::new(sql);
+        query.bind(tag_column.as_str());
+
+        let stream = query
+            .query(&mut **conn)
+            .await
+            .map_err(|e| AppError::Query(format!("发现标签表失败: {}", e)))?;
+
+        let rows = stream
+            .into_first_result()
+            .await
+            .map_err(|e| AppError::Query(format!("获取标签表结果失败: {}", e)))?;
+
+        let columns: Vec<(String, String, String)> = rows
+            .iter()
+            .map(|row| {
+                (
+                    row.get::<&str, _>(0).unwrap_or("dbo").to_string(),
+                    row.get::<&str, _>(1).unwrap_or("").to_string(),
+                    row.get::<&str, _>(2).unwrap_or("").to_string(),
+                )
+            })
+            .collect();
+
+        let candidates =
+            rank_tag_table_candidates(&columns, &tag_column, self.profile.value_column_name());
+
+        info!(target: "industry_vis::datasource",
+            database = %self.database(),
+            count = candidates.len(),
+            "标签表发现完成"
+        );
+
+        Ok(candidates)
+    }
+
     async fn search_tags(&self, keyword: &str, limit: usize) -> AppResult<Vec<String>> {
-        let mut conn = self.pool.get().await?;
+        let mut conn = self.connection().await?;
         let database = self.database().to_string();
 
-        // 使用 Profile 生成 SQL
-        let search_pattern = format!("%{}%", keyword);
-        let sql = self.profile.tag_search_sql(limit);
+        // 使用 Profile 生成 SQL（`-` 前缀的词为排除词）
+        let terms = TagSearchTerms::parse(keyword);
+        let patterns = terms.patterns();
+        let sql = self.profile.tag_search_sql(limit, &terms);
 
         debug!(target: "industry_vis::datasource",
             database = %database,
             keyword = %keyword,
-            pattern = %search_pattern,
+            patterns = ?patterns,
             profile = %self.profile.name(),
             "执行标签搜索 SQL: {}", sql
         );
 
         let mut query = Query::new(&sql);
-        query.bind(&search_pattern);
+        for pattern in &patterns {
+            query.bind(pattern.as_str());
+        }
 
-        let stream = query.query(&mut *conn).await.map_err(|e| {
+        let stream = query.query(&mut **conn).await.map_err(|e| {
             error!(target: "industry_vis::datasource",
                 database = %database,
                 keyword = %keyword,
@@ -232,59 +547,216 @@ impl DataSource for SqlServerSource {
         end_time: &str,
         tags: Option<&[String]>,
     ) -> AppResult<Vec<HistoryRecord>> {
-        let mut conn = self.pool.get().await?;
-        let database = self.database().to_string();
+        self.query_history_partitioned(table, start_time, end_time, tags, None)
+            .await
+    }
 
-        let tag_count = tags.map(|t| t.len()).unwrap_or(0);
+    async fn query_history_latest(
+        &self,
+        table: &str,
+        start_time: &str,
+        end_time: &str,
+        tags: Option<&[String]>,
+        per_tag_limit: usize,
+    ) -> AppResult<Vec<HistoryRecord>> {
+        self.query_history_partitioned(table, start_time, end_time, tags, Some(per_tag_limit))
+            .await
+    }
 
-        // 使用 Profile 生成 SQL
+    async fn count_history(
+        &self,
+        table: &str,
+        start_time: &str,
+        end_time: &str,
+        tags: Option<&[String]>,
+    ) -> AppResult<u64> {
+        if self.no_tag_mapped(tags) {
+            return Ok(0);
+        }
         let tag_filter = self.profile.build_tag_filter(tags);
         let sql = self
             .profile
-            .history_query_sql(table, start_time, end_time, &tag_filter);
+            .history_count_sql(table, start_time, end_time, &tag_filter)
+            .ok_or_else(|| {
+                AppError::Query(format!(
+                    "Schema Profile '{}' 不支持数据量预估",
+                    self.profile.name()
+                ))
+            })?;
 
         debug!(target: "industry_vis::datasource",
-            database = %database,
             table = %table,
             start_time = %start_time,
             end_time = %end_time,
-            tag_count = tag_count,
-            profile = %self.profile.name(),
-            "执行历史查询"
+            tag_count = tags.map(|t| t.len()).unwrap_or(0),
+            "统计历史数据行数"
         );
 
-        let query = Query::new(&sql);
-        let stream = query.query(&mut *conn).await.map_err(|e| {
-            error!(target: "industry_vis::datasource",
-                database = %database,
-                error = %e,
-                "历史查询失败"
-            );
-            AppError::Query(format!("历史查询失败: {}", e))
-        })?;
+        let mut conn = self.connection().await?;
+        let row = Query::new(&sql)
+            .query(&mut **conn)
+            .await
+            .map_err(|e| AppError::Query(format!("行数统计失败: {}", e)))?
+            .into_row()
+            .await
+            .map_err(|e| AppError::Query(format!("获取行数统计结果失败: {}", e)))?;
 
-        let rows = stream
+        let count = row.and_then(|r| r.get::<i64, _>(0)).unwrap_or(0);
+        Ok(count.max(0) as u64)
+    }
+
+    async fn query_value_neighbors(
+        &self,
+        table: &str,
+        tag: &str,
+        time: &str,
+    ) -> AppResult<Vec<HistoryRecord>> {
+        let sql = self
+            .profile
+            .value_neighbors_sql(table, tag, time)
+            .ok_or_else(|| {
+                AppError::Query(format!(
+                    "Schema Profile '{}' 不支持按时刻取值",
+                    self.profile.name()
+                ))
+            })?;
+
+        debug!(target: "industry_vis::datasource",
+            table = %table,
+            tag = %tag,
+            time = %time,
+            "查询时刻前后最近点"
+        );
+
+        let mut conn = self.connection().await?;
+        let rows = Query::new(&sql)
+            .query(&mut **conn)
+            .await
+            .map_err(|e| AppError::Query(format!("时刻取值查询失败: {}", e)))?
             .into_first_result()
             .await
-            .map_err(|e| AppError::Query(format!("获取历史结果失败: {}", e)))?;
+            .map_err(|e| AppError::Query(format!("获取时刻取值结果失败: {}", e)))?;
 
-        // 使用 Profile 映射行数据
-        let mut records: Vec<HistoryRecord> = Vec::with_capacity(rows.len());
-        for row in rows.iter() {
-            records.push(self.profile.map_history_row(row)?);
+        rows.iter()
+            .map(|row| self.profile.map_history_row(row))
+            .collect()
+    }
+}
+
+impl SqlServerSource {
+    /// 历史查询入口：标签较多时按配置拆分为多路并行查询
+    ///
+    /// 每标签限量按标签分区计算，拆分后各路独立限量结果不变。
+    async fn query_history_partitioned(
+        &self,
+        table: &str,
+        start_time: &str,
+        end_time: &str,
+        tags: Option<&[String]>,
+        per_tag_limit: Option<usize>,
+    ) -> AppResult<Vec<HistoryRecord>> {
+        let tag_list = match tags {
+            Some(t) => t,
+            None => {
+                return self
+                    .query_history_single(table, start_time, end_time, None, per_tag_limit)
+                    .await;
+            }
+        };
+
+        let ways = effective_parallelism(self.parallelism, self.pool.max_size(), tag_list.len());
+        if ways <= 1 {
+            return self
+                .query_history_single(table, start_time, end_time, tags, per_tag_limit)
+                .await;
         }
 
-        info!(target: "industry_vis::datasource",
-            database = %database,
-            table = %table,
-            records = records.len(),
-            "历史查询完成"
+        debug!(target: "industry_vis::datasource",
+            tag_count = tag_list.len(),
+            ways = ways,
+            "按标签并行拆分历史查询"
         );
 
-        Ok(records)
+        // 每路一个连接并行查询，按拆分顺序合并
+        let mut tasks = JoinSet::new();
+        for (index, part) in split_tags(tag_list, ways).into_iter().enumerate() {
+            let source = self.clone();
+            let table = table.to_string();
+            let start_time = start_time.to_string();
+            let end_time = end_time.to_string();
+            tasks.spawn(async move {
+                source
+                    .query_history_single(
+                        &table,
+                        &start_time,
+                        &end_time,
+                        Some(&part),
+                        per_tag_limit,
+                    )
+                    .await
+                    .map(|records| (index, records))
+            });
+        }
+
+        let mut parts = vec![Vec::new(); ways];
+        while let Some(joined) = tasks.join_next().await {
+            let (index, records) =
+                joined.map_err(|e| AppError::Internal(format!("并行查询任务失败: {}", e)))??;
+            parts[index] = records;
+        }
+
+        Ok(merge_partitioned_results(parts))
     }
 }
 
+/// 可执行会话设置语句的连接
+trait SessionSql {
+    async fn execute_batch(&mut self, sql: &str) -> AppResult<()>;
+}
+
+impl SessionSql for TiberiusClient {
+    async fn execute_batch(&mut self, sql: &str) -> AppResult<()> {
+        self.simple_query(sql)
+            .await
+            .map_err(|e| AppError::Query(format!("设置会话失败: {}", e)))?
+            .into_results()
+            .await
+            .map_err(|e| AppError::Query(format!("设置会话失败: {}", e)))?;
+        Ok(())
+    }
+}
+
+/// 查询前准备会话：配置了隔离级别时发送设置语句
+async fn prepare_session<C: SessionSql>(
+    conn: &mut C,
+    isolation_level: Option<IsolationLevel>,
+) -> AppResult<()> {
+    if let Some(level) = isolation_level {
+        let sql = level.set_statement();
+        debug!(target: "industry_vis::datasource", "设置会话隔离级别: {}", sql);
+        conn.execute_batch(sql).await?;
+    }
+    Ok(())
+}
+
+/// 计算实际并行路数：不超过配置值、连接池最大连接数和标签数
+fn effective_parallelism(requested: usize, max_size: u32, tag_count: usize) -> usize {
+    requested.min(max_size as usize).min(tag_count).max(1)
+}
+
+/// 将标签按顺序拆分为 `ways` 组（各组数量尽量均匀）
+fn split_tags(tags: &[String], ways: usize) -> Vec<Vec<String>> {
+    let chunk_size = tags.len().div_ceil(ways.max(1)).max(1);
+    tags.chunks(chunk_size).map(|c| c.to_vec()).collect()
+}
+
+/// 合并各路查询结果，保持与单条查询一致的按时间排序
+fn merge_partitioned_results(parts: Vec<Vec<HistoryRecord>>) -> Vec<HistoryRecord> {
+    let mut records: Vec<HistoryRecord> = parts.into_iter().flatten().collect();
+    records.sort_by(|a, b| a.date_time.cmp(&b.date_time));
+    records
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -297,5 +769,94 @@ mod tests {
         assert_eq!(meta.database, "TestDB");
     }
 
-    // 数据库连接测试需要实际的数据库，在集成测试中进行
+    fn record(time: &str, tag: &str) -> HistoryRecord {
+        HistoryRecord::new(time.to_string(), tag.to_string(), 1.0, "192".to_string())
+    }
+
+    #[test]
+    fn test_effective_parallelism_limited_by_max_size() {
+        assert_eq!(effective_parallelism(8, 3, 20), 3);
+        assert_eq!(effective_parallelism(2, 3, 20), 2);
+        assert_eq!(effective_parallelism(8, 3, 2), 2);
+        assert_eq!(effective_parallelism(1, 3, 20), 1);
+        assert_eq!(effective_parallelism(0, 3, 20), 1);
+    }
+
+    #[test]
+    fn test_split_tags() {
+        let tags: Vec<String> = (0..7).map(|i| format!("T{}", i)).collect();
+        let parts = split_tags(&tags, 3);
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts.concat(), tags);
+        assert!(parts.iter().all(|p| p.len() <= 3));
+    }
+
+    #[test]
+    fn test_merge_matches_single_query() {
+        let tags: Vec<String> = (0..5).map(|i| format!("T{}", i)).collect();
+        let times = [
+            "2024-01-01T00:00:00",
+            "2024-01-01T00:01:00",
+            "2024-01-01T00:02:00",
+        ];
+
+        // 单条查询：ORDER BY DateTime
+        let mut single: Vec<HistoryRecord> = times
+            .iter()
+            .flat_map(|t| tags.iter().map(move |tag| record(t, tag)))
+            .collect();
+
+        // 拆分查询：每路各自按时间排序返回
+        let parts: Vec<Vec<HistoryRecord>> = split_tags(&tags, 3)
+            .iter()
+            .map(|part| {
+                times
+                    .iter()
+                    .flat_map(|t| part.iter().map(move |tag| record(t, tag)))
+                    .collect()
+            })
+            .collect();
+        let mut merged = merge_partitioned_results(parts);
+
+        assert_eq!(merged.len(), single.len());
+        assert!(merged.windows(2).all(|w| w[0].date_time <= w[1].date_time));
+
+        // 同一时刻的标签顺序不受 ORDER BY 约束，按 (时间, 标签) 比较
+        let key = |r: &HistoryRecord| (r.date_time.clone(), r.tag_name.clone());
+        single.sort_by_key(key);
+        merged.sort_by_key(key);
+        assert_eq!(merged, single);
+    }
+    /// 记录执行过的语句的连接
+    #[derive(Default)]
+    struct RecordingConnection(Vec<String>);
+
+    impl SessionSql for RecordingConnection {
+        async fn execute_batch(&mut self, sql: &str) -> AppResult<()> {
+            self.0.push(sql.to_string());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prepare_session_sets_isolation_level() {
+        let mut conn = RecordingConnection::default();
+        prepare_session(&mut conn, None).await.unwrap();
+        assert!(conn.0.is_empty());
+
+        prepare_session(&mut conn, Some(IsolationLevel::ReadUncommitted))
+            .await
+            .unwrap();
+        assert_eq!(conn.0, ["SET TRANSACTION ISOLATION LEVEL READ UNCOMMITTED"]);
+
+        // 配置值按 snake_case 解析
+        let config: crate::config::QueryConfig = toml::from_str(
+            r#"
+            defaultTable = "History"
+            isolationLevel = "snapshot"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.isolation_level, Some(IsolationLevel::Snapshot));
+    }
 }
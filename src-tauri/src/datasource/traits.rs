@@ -4,7 +4,7 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
 use crate::error::AppResult;
-use crate::models::HistoryRecord;
+use crate::models::{HistoryRecord, InternedStr, QueryParams};
 
 /// 数据源元数据
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +19,15 @@ impl SourceMetadata {
     }
 }
 
+/// 方括号转义的 `[schema].[name]` 形式表名
+pub(crate) fn qualified_name(schema: &str, name: &str) -> String {
+    format!(
+        "[{}].[{}]",
+        schema.replace(']', "]]"),
+        name.replace(']', "]]")
+    )
+}
+
 /// 表信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TableInfo {
@@ -31,12 +40,79 @@ impl TableInfo {
         Self { schema, name }
     }
 
-    /// 获取完整表名
+    /// 获取完整表名（方括号转义，可直接拼入 SQL）
+    pub fn full_name(&self) -> String {
+        qualified_name(&self.schema, &self.name)
+    }
+}
+
+/// 标签表候选
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TagTableCandidate {
+    pub schema: String,
+    pub name: String,
+    /// 匹配得分（越高越可能是标签表）
+    pub score: u32,
+}
+
+impl TagTableCandidate {
+    /// 获取完整表名（方括号转义，可直接拼入 SQL）
     pub fn full_name(&self) -> String {
-        format!("[{}].[{}]", self.schema, self.name)
+        qualified_name(&self.schema, &self.name)
+    }
+
+    /// 保存到 `schema.tag_table` 的表名（`schema.name` 形式，不转义）
+    pub fn config_name(&self) -> String {
+        format!("{}.{}", self.schema, self.name)
     }
 }
 
+/// 从 (schema, 表名, 列名) 列表中筛选并排序标签表候选
+///
+/// 只保留包含 `tag_column` 列的表。打分规则：
+/// - 表名包含 "tag"（不区分大小写）加分
+/// - 不包含 `value_column` 列（标签字典表而非历史表）加分
+///
+/// 结果按得分降序、表名升序排列。
+pub fn rank_tag_table_candidates(
+    columns: &[(String, String, String)],
+    tag_column: &str,
+    value_column: &str,
+) -> Vec<TagTableCandidate> {
+    use std::collections::BTreeMap;
+
+    let mut tables: BTreeMap<(&str, &str), Vec<&str>> = BTreeMap::new();
+    for (schema, table, column) in columns {
+        tables
+            .entry((schema.as_str(), table.as_str()))
+            .or_default()
+            .push(column.as_str());
+    }
+
+    let mut candidates: Vec<TagTableCandidate> = tables
+        .into_iter()
+        .filter(|(_, cols)| cols.iter().any(|c| c.eq_ignore_ascii_case(tag_column)))
+        .map(|((schema, name), cols)| {
+            let mut score = 1;
+            if name.to_ascii_lowercase().contains("tag") {
+                score += 2;
+            }
+            if !cols.iter().any(|c| c.eq_ignore_ascii_case(value_column)) {
+                score += 1;
+            }
+            TagTableCandidate {
+                schema: schema.to_string(),
+                name: name.to_string(),
+                score,
+            }
+        })
+        .collect();
+
+    candidates.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.name.cmp(&b.name)));
+    candidates
+}
+
 /// 数据源抽象 trait
 #[async_trait]
 pub trait DataSource: Send + Sync {
@@ -52,6 +128,11 @@ pub trait DataSource: Send + Sync {
     /// 获取可用标签列表
     async fn get_available_tags(&self, table: &str) -> AppResult<Vec<String>>;
 
+    /// 自动发现标签表
+    ///
+    /// 查找包含标签列的表，按可能性降序返回候选列表。
+    async fn discover_tag_table(&self) -> AppResult<Vec<TagTableCandidate>>;
+
     /// 模糊搜索标签（从 TagDatabase 表）
     async fn search_tags(&self, keyword: &str, limit: usize) -> AppResult<Vec<String>>;
 
@@ -63,12 +144,112 @@ pub trait DataSource: Send + Sync {
         end_time: &str,
         tags: Option<&[String]>,
     ) -> AppResult<Vec<HistoryRecord>>;
+
+    /// 统计历史查询将返回的行数（与 `query_history` 相同的过滤条件）
+    async fn count_history(
+        &self,
+        table: &str,
+        start_time: &str,
+        end_time: &str,
+        tags: Option<&[String]>,
+    ) -> AppResult<u64>;
+
+    /// 查询标签在指定时刻前后最近的记录（最多各一条，用于时刻插值）
+    async fn query_value_neighbors(
+        &self,
+        table: &str,
+        tag: &str,
+        time: &str,
+    ) -> AppResult<Vec<HistoryRecord>>;
+
+    /// 查询历史数据，每个标签只返回时间范围内最新的 `per_tag_limit` 个点
+    ///
+    /// 默认实现查询全部数据后在内存中截取，支持 SQL 层限量的数据源应覆盖此方法。
+    async fn query_history_latest(
+        &self,
+        table: &str,
+        start_time: &str,
+        end_time: &str,
+        tags: Option<&[String]>,
+        per_tag_limit: usize,
+    ) -> AppResult<Vec<HistoryRecord>> {
+        let records = self
+            .query_history(table, start_time, end_time, tags)
+            .await?;
+        Ok(keep_latest_per_tag(records, per_tag_limit))
+    }
+
+    /// 按查询参数查询历史数据（设置 `per_tag_limit` 时走每标签限量查询）
+    async fn query_history_with_params(
+        &self,
+        table: &str,
+        params: &QueryParams,
+    ) -> AppResult<Vec<HistoryRecord>> {
+        let tags = params.tags.as_deref();
+        match params.per_tag_limit {
+            Some(limit) => {
+                self.query_history_latest(table, &params.start_time, &params.end_time, tags, limit)
+                    .await
+            }
+            None => {
+                self.query_history(table, &params.start_time, &params.end_time, tags)
+                    .await
+            }
+        }
+    }
+}
+
+/// 每个标签只保留最新的 `per_tag_limit` 条记录，结果按时间升序
+pub fn keep_latest_per_tag(
+    mut records: Vec<HistoryRecord>,
+    per_tag_limit: usize,
+) -> Vec<HistoryRecord> {
+    use std::collections::HashMap;
+
+    // 按时间倒序遍历，每个标签计数到上限为止
+    records.sort_by(|a, b| b.date_time.cmp(&a.date_time));
+    let mut counts: HashMap<InternedStr, usize> = HashMap::new();
+    records.retain(|r| {
+        let count = counts.entry(r.tag_name.clone()).or_insert(0);
+        *count += 1;
+        *count <= per_tag_limit
+    });
+    records.reverse();
+    records
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_keep_latest_per_tag() {
+        let records: Vec<HistoryRecord> = (0..10)
+            .flat_map(|i| {
+                ["A", "B", "C"].map(|tag| {
+                    HistoryRecord::new(
+                        format!("2024-01-01T00:00:{:02}.000", i),
+                        tag.to_string(),
+                        i as f64,
+                        "Good".to_string(),
+                    )
+                })
+            })
+            .collect();
+
+        let result = keep_latest_per_tag(records, 3);
+        assert_eq!(result.len(), 9);
+        for tag in ["A", "B", "C"] {
+            let values: Vec<f64> = result
+                .iter()
+                .filter(|r| r.tag_name == tag)
+                .map(|r| r.tag_val)
+                .collect();
+            // 各自最新的 3 个点，按时间升序
+            assert_eq!(values, vec![7.0, 8.0, 9.0]);
+        }
+    }
+
     #[test]
     fn test_source_metadata() {
         let meta = SourceMetadata::new("localhost:1433".to_string(), "TestDB".to_string());
@@ -76,9 +257,34 @@ mod tests {
         assert_eq!(meta.database, "TestDB");
     }
 
+    #[test]
+    fn test_rank_tag_table_candidates() {
+        let col = |s: &str, t: &str, c: &str| (s.to_string(), t.to_string(), c.to_string());
+        let columns = vec![
+            col("dbo", "历史表", "DateTime"),
+            col("dbo", "历史表", "TagName"),
+            col("dbo", "历史表", "TagVal"),
+            col("dbo", "TagDataBase", "TagName"),
+            col("dbo", "TagDataBase", "Description"),
+            col("dbo", "Devices", "DeviceName"),
+            col("dbo", "点位表", "tagname"),
+        ];
+
+        let candidates = rank_tag_table_candidates(&columns, "TagName", "TagVal");
+        let names: Vec<&str> = candidates.iter().map(|c| c.name.as_str()).collect();
+
+        assert_eq!(names, vec!["TagDataBase", "点位表", "历史表"]);
+        assert!(!names.contains(&"Devices"));
+        assert_eq!(candidates[0].full_name(), "[dbo].[TagDataBase]");
+        assert_eq!(candidates[0].config_name(), "dbo.TagDataBase");
+    }
+
     #[test]
     fn test_table_info() {
         let info = TableInfo::new("dbo".to_string(), "History".to_string());
         assert_eq!(info.full_name(), "[dbo].[History]");
+
+        let info = TableInfo::new("dbo".to_string(), "x]; DROP TABLE t; --".to_string());
+        assert_eq!(info.full_name(), "[dbo].[x]]; DROP TABLE t; --]");
     }
 }
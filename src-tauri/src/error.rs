@@ -18,6 +18,10 @@ pub enum AppError {
     #[error("数据库连接错误: {0}")]
     Connection(String),
 
+    /// 服务端拒绝连接（带 SQL Server 错误号，如 4060、18456）
+    #[error("数据库连接错误: {message}")]
+    ConnectionRejected { number: u32, message: String },
+
     #[error("连接池错误: {0}")]
     Pool(String),
 
@@ -27,6 +31,12 @@ pub enum AppError {
     #[error("数据库未连接")]
     DatabaseNotConnected,
 
+    #[error("查询限流: {0}")]
+    Throttled(String),
+
+    #[error("数据库连接已熔断: {0}")]
+    CircuitOpen(String),
+
     // ============== 数据处理相关 ==============
     #[error("数据处理错误: {0}")]
     DataProcessing(String),
@@ -56,25 +66,72 @@ pub enum AppError {
 }
 
 impl AppError {
-    /// 创建连接错误，带友好提示
-    pub fn connection_with_hint(err: &str, database: &str) -> Self {
-        if err.contains("4060") {
-            Self::Connection(format!(
+    /// 创建连接错误，按服务端返回的 SQL Server 错误号给出友好提示
+    pub fn connection_with_hint(err: &tiberius::error::Error, database: &str) -> Self {
+        Self::connection_from_server(err.code(), &err.to_string(), database)
+    }
+
+    /// 按错误号创建连接错误（`number` 为 `None` 表示不是服务端返回的错误）
+    fn connection_from_server(number: Option<u32>, err: &str, database: &str) -> Self {
+        let message = match number {
+            Some(4060) => format!(
                 "数据库 '{}' 不存在或无访问权限。请检查数据库名称是否正确。原始错误: {}",
                 database, err
-            ))
-        } else if err.contains("18456") {
-            Self::Connection(format!("用户名或密码错误。原始错误: {}", err))
-        } else if err.contains("Login failed") {
-            Self::Connection(format!("登录失败，请检查用户名和密码。原始错误: {}", err))
-        } else {
-            Self::Connection(format!("SQL Server 连接失败: {}", err))
+            ),
+            Some(18456) => format!("用户名或密码错误。原始错误: {}", err),
+            _ if err.contains("Login failed") => {
+                format!("登录失败，请检查用户名和密码。原始错误: {}", err)
+            }
+            _ => format!("SQL Server 连接失败: {}", err),
+        };
+        match number {
+            Some(number) => Self::ConnectionRejected { number, message },
+            None => Self::Connection(message),
+        }
+    }
+
+    /// 错误分类代码（供前端区分处理）
+    ///
+    /// 连接错误会进一步区分数据库不存在、认证失败和网络问题。
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Config(_) => "CONFIG",
+            Self::ConfigWatch(_) => "CONFIG_WATCH",
+            Self::Connection(msg) if msg.contains("Login failed") => "AUTH_FAILED",
+            Self::Connection(msg) if msg.contains("TCP") => "NETWORK",
+            Self::Connection(_) => "CONNECTION",
+            Self::ConnectionRejected { number, message } => match number {
+                4060 => "DATABASE_NOT_FOUND",
+                18456 => "AUTH_FAILED",
+                _ if message.contains("Login failed") => "AUTH_FAILED",
+                _ => "CONNECTION",
+            },
+            Self::Pool(_) => "POOL",
+            Self::Query(_) => "QUERY",
+            Self::DatabaseNotConnected => "DATABASE_NOT_CONNECTED",
+            Self::Throttled(_) => "THROTTLED",
+            Self::CircuitOpen(_) => "CIRCUIT_OPEN",
+            Self::DataProcessing(_) => "DATA_PROCESSING",
+            Self::Validation(_) => "VALIDATION",
+            Self::NotFound(_) => "NOT_FOUND",
+            Self::Io(_) => "IO",
+            Self::Json(_) => "JSON",
+            Self::TomlParse(_) => "TOML_PARSE",
+            Self::TomlSerialize(_) => "TOML_SERIALIZE",
+            Self::Internal(_) => "INTERNAL",
         }
     }
 
     /// 是否为可重试错误
     pub fn is_retryable(&self) -> bool {
-        matches!(self, AppError::Connection(_) | AppError::Pool(_))
+        matches!(
+            self,
+            AppError::Connection(_)
+                | AppError::ConnectionRejected { .. }
+                | AppError::Pool(_)
+                | AppError::Throttled(_)
+                | AppError::CircuitOpen(_)
+        )
     }
 
     /// 是否为用户可见错误
@@ -83,12 +140,44 @@ impl AppError {
             self,
             AppError::Config(_)
                 | AppError::Connection(_)
+                | AppError::ConnectionRejected { .. }
                 | AppError::Validation(_)
                 | AppError::NotFound(_)
+                | AppError::Throttled(_)
+                | AppError::CircuitOpen(_)
         )
     }
 }
 
+/// 结构化错误信息（可克隆、可序列化，用于保存最近一次错误）
+#[derive(Debug, Clone, serde::Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorInfo {
+    /// 错误分类代码，见 `AppError::code`
+    pub code: String,
+    /// SQL Server 错误号（如 4060、18456）
+    pub sql_error_number: Option<u32>,
+    /// 错误信息（含友好提示）
+    pub message: String,
+    /// 是否可重试
+    pub retryable: bool,
+}
+
+impl From<&AppError> for ErrorInfo {
+    fn from(err: &AppError) -> Self {
+        let sql_error_number = match err {
+            AppError::ConnectionRejected { number, .. } => Some(*number),
+            _ => None,
+        };
+        Self {
+            code: err.code().to_string(),
+            sql_error_number,
+            message: err.to_string(),
+            retryable: err.is_retryable(),
+        }
+    }
+}
+
 // 为 Tauri 序列化错误
 impl serde::Serialize for AppError {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -118,11 +207,36 @@ mod tests {
 
     #[test]
     fn test_connection_with_hint() {
-        let err = AppError::connection_with_hint("error 4060", "TestDB");
+        let err = AppError::connection_from_server(Some(4060), "error", "TestDB");
         assert!(err.to_string().contains("不存在"));
 
-        let err = AppError::connection_with_hint("error 18456", "TestDB");
+        let err = AppError::connection_from_server(Some(18456), "error", "TestDB");
         assert!(err.to_string().contains("用户名或密码"));
+
+        // 错误号只取服务端返回值，不从消息文本中匹配
+        let err = AppError::connection_from_server(None, "timeout after 40600 ms", "TestDB");
+        assert!(matches!(err, AppError::Connection(_)));
+        assert_eq!(err.code(), "CONNECTION");
+        let err = AppError::connection_from_server(Some(233), "error 18456 4060", "TestDB");
+        assert_eq!(err.code(), "CONNECTION");
+        assert_eq!(ErrorInfo::from(&err).sql_error_number, Some(233));
+    }
+
+    #[test]
+    fn test_error_info_from_connection_hint() {
+        let err = AppError::connection_from_server(Some(4060), "Login error", "BadDB");
+        let info = ErrorInfo::from(&err);
+        assert_eq!(info.code, "DATABASE_NOT_FOUND");
+        assert_eq!(info.sql_error_number, Some(4060));
+        assert!(info.message.contains("BadDB"));
+        assert!(info.retryable);
+
+        let err = AppError::connection_from_server(Some(18456), "error", "TestDB");
+        assert_eq!(ErrorInfo::from(&err).code, "AUTH_FAILED");
+
+        let err = AppError::Connection("TCP 连接失败: refused".to_string());
+        assert_eq!(err.code(), "NETWORK");
+        assert_eq!(ErrorInfo::from(&err).sql_error_number, None);
     }
 
     #[test]
@@ -0,0 +1,66 @@
+//! Arrow IPC（Feather v2）导出实现
+//!
+//! 便于 Python（pyarrow / polars / pandas）和 R（arrow）零拷贝读取，
+//! `datetime` 列保持毫秒精度的时间类型。
+
+use polars::prelude::*;
+use std::fs;
+use std::path::Path;
+use tracing::debug;
+
+use crate::error::{AppError, AppResult};
+use crate::models::HistoryRecord;
+use crate::processing::records_to_dataframe;
+
+/// 写出 Arrow IPC 文件
+pub fn write_arrow(records: &[HistoryRecord], path: impl AsRef<Path>) -> AppResult<()> {
+    let path = path.as_ref();
+    let mut df = records_to_dataframe(records)?;
+    let file = fs::File::create(path)?;
+    IpcWriter::new(file)
+        .finish(&mut df)
+        .map_err(|e| AppError::DataProcessing(format!("写入 Arrow 失败: {}", e)))?;
+
+    debug!(target: "industry_vis::export",
+        "写出 Arrow IPC - 文件: {}, 行数: {}", path.display(), records.len()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_arrow_roundtrip() {
+        let path =
+            std::env::temp_dir().join(format!("industry_vis_test_{}.arrow", std::process::id()));
+        let records: Vec<HistoryRecord> = (0..5)
+            .map(|i| {
+                HistoryRecord::new(
+                    format!("2024-01-01T00:00:0{}.000", i),
+                    format!("T{}", i % 2),
+                    i as f64,
+                    "Good".to_string(),
+                )
+            })
+            .collect();
+
+        write_arrow(&records, &path).unwrap();
+
+        let df = IpcReader::new(fs::File::open(&path).unwrap())
+            .finish()
+            .unwrap();
+        assert_eq!(df.height(), 5);
+        let schema = df.schema();
+        assert_eq!(
+            schema.get("datetime"),
+            Some(&DataType::Datetime(TimeUnit::Milliseconds, None))
+        );
+        assert_eq!(schema.get("tag_name"), Some(&DataType::String));
+        assert_eq!(schema.get("tag_val"), Some(&DataType::Float64));
+        assert_eq!(schema.get("tag_quality"), Some(&DataType::String));
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,226 @@
+//! V2 查询结果的紧凑二进制编码
+//!
+//! 大图表的 series JSON 可达数 MB，IPC 传输和前端 `JSON.parse` 都较慢。
+//! 二进制格式将 `[f64; 2]` 数据点直接按小端 f64 打包，前端可零拷贝构造
+//! `Float64Array`。
+//!
+//! 布局（所有整数为小端 u32）：
+//!
+//! ```text
+//! "IVS1" | meta_len | meta JSON（series 为空的 QueryResultV2） | 填充至 8 字节对齐
+//! series_count
+//! 每个系列: name_len | point_count | name UTF-8 | 填充至 8 字节对齐 | point_count * 2 个 f64
+//! ```
+
+use crate::error::{AppError, AppResult};
+use crate::models::{ChartSeriesData, QueryResultV2};
+
+/// 格式标识
+const MAGIC: &[u8; 4] = b"IVS1";
+
+fn pad_to_8(buf: &mut Vec<u8>) {
+    while !buf.len().is_multiple_of(8) {
+        buf.push(0);
+    }
+}
+
+fn push_u32(buf: &mut Vec<u8>, value: usize) -> AppResult<()> {
+    let value = u32::try_from(value)
+        .map_err(|_| AppError::DataProcessing(format!("数据量过大，无法编码: {}", value)))?;
+    buf.extend_from_slice(&value.to_le_bytes());
+    Ok(())
+}
+
+/// 将 V2 查询结果编码为二进制
+pub fn encode_series_binary(result: &QueryResultV2) -> AppResult<Vec<u8>> {
+    let meta = QueryResultV2 {
+        series: Vec::new(),
+        ..result.clone()
+    };
+    let meta_json = serde_json::to_vec(&meta)?;
+
+    let points: usize = result.series.iter().map(|s| s.data.len()).sum();
+    let mut buf = Vec::with_capacity(meta_json.len() + 16 + points * 16 + result.series.len() * 64);
+
+    buf.extend_from_slice(MAGIC);
+    push_u32(&mut buf, meta_json.len())?;
+    buf.extend_from_slice(&meta_json);
+    pad_to_8(&mut buf);
+
+    push_u32(&mut buf, result.series.len())?;
+    for series in &result.series {
+        push_u32(&mut buf, series.tag_name.len())?;
+        push_u32(&mut buf, series.data.len())?;
+        buf.extend_from_slice(series.tag_name.as_bytes());
+        pad_to_8(&mut buf);
+        for [ts, value] in &series.data {
+            buf.extend_from_slice(&ts.to_le_bytes());
+            buf.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    Ok(buf)
+}
+
+/// 二进制读取游标
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> AppResult<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| AppError::DataProcessing("二进制数据被截断".to_string()))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u32(&mut self) -> AppResult<usize> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+    }
+
+    fn f64(&mut self) -> AppResult<f64> {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(f64::from_le_bytes(bytes))
+    }
+
+    fn align_8(&mut self) -> AppResult<()> {
+        let padding = (8 - self.pos % 8) % 8;
+        self.take(padding).map(|_| ())
+    }
+}
+
+/// 解码 `encode_series_binary` 生成的二进制数据
+pub fn decode_series_binary(bytes: &[u8]) -> AppResult<QueryResultV2> {
+    let mut reader = Reader { bytes, pos: 0 };
+    if reader.take(4)? != MAGIC {
+        return Err(AppError::DataProcessing("无效的二进制格式标识".to_string()));
+    }
+
+    let meta_len = reader.u32()?;
+    let mut result: QueryResultV2 = serde_json::from_slice(reader.take(meta_len)?)?;
+    reader.align_8()?;
+
+    let series_count = reader.u32()?;
+    let mut series = Vec::with_capacity(series_count);
+    for _ in 0..series_count {
+        let name_len = reader.u32()?;
+        let point_count = reader.u32()?;
+        let tag_name = String::from_utf8(reader.take(name_len)?.to_vec())
+            .map_err(|e| AppError::DataProcessing(format!("标签名编码无效: {}", e)))?;
+        reader.align_8()?;
+
+        let mut data = Vec::with_capacity(point_count);
+        for _ in 0..point_count {
+            data.push([reader.f64()?, reader.f64()?]);
+        }
+        series.push(ChartSeriesData::new(tag_name, data));
+    }
+
+    result.series = series;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::QueryTiming;
+
+    fn sample_result() -> QueryResultV2 {
+        let series = ["温度.PV", "Pressure", "Flow_01"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                ChartSeriesData::new(
+                    name.to_string(),
+                    (0..2000)
+                        .map(|j| {
+                            [
+                                1_704_067_200_000.0 + j as f64 * 1000.0,
+                                (i as f64 + 1.0) * 12.345_678_9 + (j as f64 * 0.1).sin(),
+                            ]
+                        })
+                        .collect(),
+                )
+            })
+            .collect();
+
+        QueryResultV2 {
+            series,
+            annotations: Vec::new(),
+            total_raw: 6000,
+            total_processed: 6000,
+            cache_hit: false,
+            truncated: false,
+            query_time_ms: 42,
+            timing: QueryTiming::default(),
+            query_token: None,
+            chunk_count: None,
+            empty_reason: None,
+            warnings: Vec::new(),
+            content_hash: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_binary_smaller_than_json() {
+        let result = sample_result();
+        let json = serde_json::to_vec(&result).unwrap();
+        let binary = encode_series_binary(&result).unwrap();
+
+        assert!(
+            binary.len() * 2 < json.len(),
+            "binary={} json={}",
+            binary.len(),
+            json.len()
+        );
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let result = sample_result();
+        let decoded = decode_series_binary(&encode_series_binary(&result).unwrap()).unwrap();
+
+        assert_eq!(decoded.series.len(), result.series.len());
+        for (a, b) in decoded.series.iter().zip(&result.series) {
+            assert_eq!(a.tag_name, b.tag_name);
+            assert_eq!(a.data, b.data);
+        }
+        assert_eq!(decoded.total_raw, 6000);
+        assert_eq!(decoded.query_time_ms, 42);
+    }
+
+    #[test]
+    fn test_point_blocks_aligned() {
+        let result = sample_result();
+        let binary = encode_series_binary(&result).unwrap();
+
+        // 第一个系列的数据块起始偏移必须 8 字节对齐，便于前端构造 Float64Array
+        let meta_len = u32::from_le_bytes(binary[4..8].try_into().unwrap()) as usize;
+        let series_start = (8 + meta_len).div_ceil(8) * 8;
+        let name_len = u32::from_le_bytes(
+            binary[series_start + 4..series_start + 8]
+                .try_into()
+                .unwrap(),
+        ) as usize;
+        let data_start = series_start + 12 + name_len;
+        let data_start = data_start.div_ceil(8) * 8;
+        assert_eq!(data_start % 8, 0);
+        let first_ts = f64::from_le_bytes(binary[data_start..data_start + 8].try_into().unwrap());
+        assert_eq!(first_ts, 1_704_067_200_000.0);
+    }
+
+    #[test]
+    fn test_decode_rejects_invalid() {
+        assert!(decode_series_binary(b"XXXX").is_err());
+        let binary = encode_series_binary(&sample_result()).unwrap();
+        assert!(decode_series_binary(&binary[..binary.len() - 3]).is_err());
+    }
+}
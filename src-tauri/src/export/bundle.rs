@@ -0,0 +1,194 @@
+//! 分组数据打包导出
+//!
+//! 将一个标签分组在某时间范围内的所有标签数据写入单个文件（交接班等场景）。
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::debug;
+
+use super::{CsvEncoding, write_arrow, write_csv, write_parquet};
+use crate::error::{AppError, AppResult};
+use crate::models::HistoryRecord;
+
+/// 打包导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleFormat {
+    Csv,
+    Parquet,
+    Arrow,
+}
+
+impl BundleFormat {
+    /// 从前端传入的格式名称解析（大小写不敏感）
+    pub fn parse(name: &str) -> AppResult<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "parquet" => Ok(Self::Parquet),
+            "arrow" | "feather" => Ok(Self::Arrow),
+            "xlsx" => Err(AppError::Validation(
+                "暂不支持导出 xlsx，请使用 csv（可直接用 Excel 打开）".to_string(),
+            )),
+            other => Err(AppError::Validation(format!(
+                "不支持的导出格式: '{}'，可选值: csv, parquet, arrow",
+                other
+            ))),
+        }
+    }
+
+    /// 文件扩展名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Parquet => "parquet",
+            Self::Arrow => "arrow",
+        }
+    }
+}
+
+/// 分组导出结果
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupExportResult {
+    /// 写出的文件路径
+    pub file_path: String,
+    /// 导出的记录总数
+    pub record_count: usize,
+    /// 各标签的记录数（包含无数据的标签）
+    pub tag_record_counts: BTreeMap<String, usize>,
+}
+
+/// 将名称转换为可用作文件名的片段：文件系统不允许的字符和空白替换为 `_`
+pub(crate) fn sanitize_file_name_part(name: &str) -> String {
+    name.trim()
+        .chars()
+        .map(|c| match c {
+            '\\' | '/' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_whitespace() => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// 生成分组导出文件名：`分组名_开始-结束.扩展名`
+///
+/// 分组名中文件系统不允许的字符替换为 `_`，时间只保留数字和 `T`（如 `20240101T080000`）。
+pub fn group_export_file_name(
+    group_name: &str,
+    start_time: &str,
+    end_time: &str,
+    format: BundleFormat,
+) -> String {
+    let name = sanitize_file_name_part(group_name);
+    let compact = |time: &str| -> String {
+        time.chars()
+            .filter(|c| c.is_ascii_digit() || *c == 'T')
+            .collect()
+    };
+
+    format!(
+        "{}_{}-{}.{}",
+        name,
+        compact(start_time),
+        compact(end_time),
+        format.extension()
+    )
+}
+
+/// 按格式写出记录到单个文件（`encoding` 只对 CSV 生效）
+pub fn write_records(
+    records: &[HistoryRecord],
+    path: &Path,
+    format: BundleFormat,
+    encoding: CsvEncoding,
+) -> AppResult<()> {
+    match format {
+        BundleFormat::Csv => write_csv(records, path, encoding, &[]),
+        BundleFormat::Parquet => write_parquet(records, path, false).map(|_| ()),
+        BundleFormat::Arrow => write_arrow(records, path),
+    }
+}
+
+/// 按格式写出分组数据，并统计各标签记录数
+pub fn write_group_bundle(
+    records: &[HistoryRecord],
+    tags: &[String],
+    path: &Path,
+    format: BundleFormat,
+) -> AppResult<GroupExportResult> {
+    let mut tag_record_counts: BTreeMap<String, usize> =
+        tags.iter().map(|t| (t.clone(), 0)).collect();
+    for record in records {
+        *tag_record_counts
+            .entry(record.tag_name.to_string())
+            .or_default() += 1;
+    }
+
+    write_records(records, path, format, CsvEncoding::Utf8)?;
+
+    debug!(target: "industry_vis::export",
+        "分组打包导出 - 格式: {:?}, 标签数: {}, 记录数: {}",
+        format, tag_record_counts.len(), records.len()
+    );
+
+    Ok(GroupExportResult {
+        file_path: path.to_string_lossy().to_string(),
+        record_count: records.len(),
+        tag_record_counts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_export_file_name() {
+        let name = group_export_file_name(
+            "1#锅炉/汽包 ",
+            "2024-01-01T08:00:00",
+            "2024-01-08T08:00:00",
+            BundleFormat::Parquet,
+        );
+        assert_eq!(name, "1#锅炉_汽包_20240101T080000-20240108T080000.parquet");
+
+        assert!(BundleFormat::parse("CSV").is_ok());
+        assert!(BundleFormat::parse("xlsx").is_err());
+    }
+
+    #[test]
+    fn test_write_group_bundle_contains_all_tags() {
+        let tags = vec!["T1".to_string(), "T2".to_string(), "T3".to_string()];
+        // 模拟分组查询结果：T1 3 条、T2 2 条、T3 无数据
+        let records: Vec<HistoryRecord> = [("T1", 3), ("T2", 2)]
+            .iter()
+            .flat_map(|(tag, n)| {
+                (0..*n).map(move |i| {
+                    HistoryRecord::new(
+                        format!("2024-01-01T00:0{}:00.000", i),
+                        tag.to_string(),
+                        i as f64,
+                        "Good".to_string(),
+                    )
+                })
+            })
+            .collect();
+
+        let path = std::env::temp_dir().join(format!(
+            "industry_vis_group_bundle_{}.csv",
+            std::process::id()
+        ));
+        let result = write_group_bundle(&records, &tags, &path, BundleFormat::Csv).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        // 行数（不含表头）与各标签记录数之和一致
+        let rows: Vec<&str> = content.lines().skip(1).collect();
+        assert_eq!(rows.len(), result.tag_record_counts.values().sum::<usize>());
+        assert_eq!(result.record_count, 5);
+        assert!(rows.iter().any(|r| r.contains(",T1,")));
+        assert!(rows.iter().any(|r| r.contains(",T2,")));
+        assert_eq!(result.tag_record_counts["T1"], 3);
+        assert_eq!(result.tag_record_counts["T3"], 0);
+    }
+}
@@ -0,0 +1,223 @@
+//! 处理前后对比导出
+//!
+//! 同一查询的原始数据与处理后数据按 (标签, 时间) 对齐为 `raw_value` / `processed_value`
+//! 两列，用于验证处理参数的效果。
+//!
+//! 对齐策略：每个标签的行为原始与处理后时间点的并集。
+//! - 原始值只取同一时刻的原始点；
+//! - 处理后值优先取同一时刻的点；重采样时（`hold_ms > 0`）处理后的点代表
+//!   `[窗口起点, 窗口起点 + hold_ms)` 整个窗口，原始更密时窗口内的行沿用该窗口的值；
+//! - 未重采样时被剔除的点（如离群值）处理后值留空。
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+use tracing::debug;
+
+use super::CsvEncoding;
+use crate::error::AppResult;
+use crate::models::HistoryRecord;
+
+/// 对比导出的一行
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComparisonRow {
+    pub date_time: String,
+    pub tag_name: String,
+    pub raw_value: Option<f64>,
+    pub processed_value: Option<f64>,
+}
+
+/// 解析记录时间为毫秒（仅用于同一导出内的对齐比较）
+fn record_ms(date_time: &str) -> Option<i64> {
+    chrono::NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M:%S%.f")
+        .ok()
+        .map(|dt| dt.and_utc().timestamp_millis())
+}
+
+/// 按标签分组为 时间(ms) → (时间字符串, 值)，同一时刻保留第一条
+fn index_by_tag(records: &[HistoryRecord]) -> BTreeMap<&str, BTreeMap<i64, (&str, f64)>> {
+    let mut index: BTreeMap<&str, BTreeMap<i64, (&str, f64)>> = BTreeMap::new();
+    for record in records {
+        if let Some(ms) = record_ms(&record.date_time) {
+            index
+                .entry(record.tag_name.as_str())
+                .or_default()
+                .entry(ms)
+                .or_insert((record.date_time.as_str(), record.tag_val));
+        }
+    }
+    index
+}
+
+/// 将原始与处理后记录对齐为对比行（按标签名、时间排序）
+///
+/// `hold_ms` 为处理后每个点覆盖的时长（重采样间隔），0 表示只按同一时刻对齐。
+pub fn build_comparison_rows(
+    raw: &[HistoryRecord],
+    processed: &[HistoryRecord],
+    hold_ms: i64,
+) -> Vec<ComparisonRow> {
+    let raw_index = index_by_tag(raw);
+    let processed_index = index_by_tag(processed);
+    let empty = BTreeMap::new();
+
+    let mut tags: Vec<&str> = raw_index
+        .keys()
+        .chain(processed_index.keys())
+        .copied()
+        .collect();
+    tags.sort_unstable();
+    tags.dedup();
+
+    let mut rows = Vec::new();
+    for tag in tags {
+        let raw_points = raw_index.get(tag).unwrap_or(&empty);
+        let processed_points = processed_index.get(tag).unwrap_or(&empty);
+
+        let mut times: Vec<(i64, &str)> = raw_points
+            .iter()
+            .chain(processed_points.iter())
+            .map(|(ms, (date_time, _))| (*ms, *date_time))
+            .collect();
+        // 同一时刻原始点在前，去重后保留原始时间字符串
+        times.sort_by_key(|(ms, _)| *ms);
+        times.dedup_by_key(|(ms, _)| *ms);
+
+        for (ms, date_time) in times {
+            let processed_value = match processed_points.get(&ms) {
+                Some((_, value)) => Some(*value),
+                None if hold_ms > 0 => processed_points
+                    .range(..=ms)
+                    .next_back()
+                    .filter(|(start, _)| ms < **start + hold_ms)
+                    .map(|(_, (_, value))| *value),
+                None => None,
+            };
+            rows.push(ComparisonRow {
+                date_time: date_time.to_string(),
+                tag_name: tag.to_string(),
+                raw_value: raw_points.get(&ms).map(|(_, value)| *value),
+                processed_value,
+            });
+        }
+    }
+    rows
+}
+
+/// 格式化对比值（缺失或非有限值输出为空）
+fn format_value(value: Option<f64>) -> String {
+    value
+        .filter(|v| v.is_finite())
+        .map(|v| v.to_string())
+        .unwrap_or_default()
+}
+
+/// 生成对比 CSV 文本
+pub fn build_comparison_csv(rows: &[ComparisonRow]) -> String {
+    let mut content = String::with_capacity(rows.len() * 64 + 64);
+    content.push_str("DateTime,TagName,raw_value,processed_value\n");
+    for row in rows {
+        let _ = writeln!(
+            content,
+            "{},{},{},{}",
+            row.date_time,
+            row.tag_name.replace(',', ";"),
+            format_value(row.raw_value),
+            format_value(row.processed_value)
+        );
+    }
+    content
+}
+
+/// 写出对比 CSV 文件，返回行数
+pub fn write_comparison_csv(
+    rows: &[ComparisonRow],
+    file_path: impl AsRef<Path>,
+    encoding: CsvEncoding,
+) -> AppResult<usize> {
+    let bytes = encoding.encode(&build_comparison_csv(rows));
+    debug!(target: "industry_vis::export",
+        "写出对比 CSV - 行数: {}, 字节数: {}", rows.len(), bytes.len()
+    );
+    std::fs::write(file_path, bytes)?;
+    Ok(rows.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DataProcessingConfig;
+    use crate::processing;
+
+    /// 2 分钟、每 10 秒一个原始点，值为 0..12
+    fn raw_records() -> Vec<HistoryRecord> {
+        (0..12)
+            .map(|i| {
+                HistoryRecord::good(
+                    &format!("2024-01-01T00:{:02}:{:02}.000", i / 6, (i % 6) * 10),
+                    "T1",
+                    i as f64,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_resampled_values_align_with_raw_rows() {
+        let raw = raw_records();
+        let config = DataProcessingConfig::default().with_resample(60, "mean");
+        let processed = processing::process_data(raw.clone(), &config).unwrap();
+
+        let rows = build_comparison_rows(&raw, &processed, 60_000);
+
+        // 窗口起点与原始点重合，行数等于原始点数
+        assert_eq!(rows.len(), 12);
+        assert!(rows.iter().all(|r| r.raw_value.is_some()));
+
+        // 重采样点上两列对齐
+        assert_eq!(rows[0].date_time, "2024-01-01T00:00:00.000");
+        assert_eq!(rows[0].raw_value, Some(0.0));
+        assert_eq!(rows[0].processed_value, Some(2.5));
+        assert_eq!(rows[6].date_time, "2024-01-01T00:01:00.000");
+        assert_eq!(rows[6].processed_value, Some(8.5));
+
+        // 原始更密：窗口内的行沿用所在窗口的处理后值
+        assert_eq!(rows[3].raw_value, Some(3.0));
+        assert_eq!(rows[3].processed_value, Some(2.5));
+        assert_eq!(rows[11].processed_value, Some(8.5));
+    }
+
+    #[test]
+    fn test_without_resample_removed_points_left_empty() {
+        let raw = vec![
+            HistoryRecord::good("2024-01-01T00:00:00.000", "T1", 1.0),
+            HistoryRecord::good("2024-01-01T00:00:10.000", "T1", 999.0),
+            HistoryRecord::good("2024-01-01T00:00:20.000", "T1", 3.0),
+        ];
+        let processed = vec![raw[0].clone(), raw[2].clone()];
+
+        let rows = build_comparison_rows(&raw, &processed, 0);
+        let processed_values: Vec<Option<f64>> = rows.iter().map(|r| r.processed_value).collect();
+        assert_eq!(processed_values, [Some(1.0), None, Some(3.0)]);
+    }
+
+    #[test]
+    fn test_processed_only_times_get_own_rows() {
+        // 窗口起点与原始点不重合时单独成行，原始值留空
+        let raw = vec![
+            HistoryRecord::good("2024-01-01T00:00:05.000", "T1", 1.0),
+            HistoryRecord::good("2024-01-01T00:00:35.000", "T1", 3.0),
+        ];
+        let processed = vec![HistoryRecord::good("2024-01-01T00:00:00.000", "T1", 2.0)];
+
+        let rows = build_comparison_rows(&raw, &processed, 60_000);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].raw_value, None);
+        assert_eq!(rows[0].processed_value, Some(2.0));
+        assert_eq!(rows[2].processed_value, Some(2.0));
+
+        let csv = build_comparison_csv(&rows);
+        assert!(csv.starts_with("DateTime,TagName,raw_value,processed_value\n"));
+        assert!(csv.contains("2024-01-01T00:00:00.000,T1,,2\n"));
+    }
+}
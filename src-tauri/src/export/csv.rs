@@ -0,0 +1,291 @@
+//! CSV 导出实现
+
+use encoding_rs::{EncoderResult, GBK};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tracing::debug;
+
+use crate::error::{AppError, AppResult};
+use crate::models::HistoryRecord;
+
+/// CSV 文件编码
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CsvEncoding {
+    /// UTF-8（默认）
+    #[default]
+    Utf8,
+    /// GBK（兼容老旧 MES 系统）
+    Gbk,
+}
+
+impl CsvEncoding {
+    /// 从前端传入的编码名称解析
+    ///
+    /// 支持 `"utf8"` / `"utf-8"` / `"gbk"`（大小写不敏感），未指定时使用 UTF-8。
+    pub fn parse(name: Option<&str>) -> AppResult<Self> {
+        match name.map(|n| n.trim().to_ascii_lowercase()).as_deref() {
+            None | Some("") | Some("utf8") | Some("utf-8") => Ok(Self::Utf8),
+            Some("gbk") => Ok(Self::Gbk),
+            Some(other) => Err(AppError::Validation(format!(
+                "不支持的 CSV 编码: '{}'，可选值: utf8, gbk",
+                other
+            ))),
+        }
+    }
+
+    /// 将文本内容编码为字节
+    ///
+    /// GBK 模式下无法编码的字符以 `?` 替代。
+    pub fn encode(&self, content: &str) -> Vec<u8> {
+        match self {
+            Self::Utf8 => content.as_bytes().to_vec(),
+            Self::Gbk => encode_gbk(content),
+        }
+    }
+}
+
+/// 编码为 GBK，无法编码的字符以 `?` 替代
+fn encode_gbk(content: &str) -> Vec<u8> {
+    let mut encoder = GBK.new_encoder();
+    let mut output = Vec::with_capacity(content.len());
+    let mut buffer = [0u8; 4096];
+    let mut input = content;
+
+    loop {
+        let (result, read, written) =
+            encoder.encode_from_utf8_without_replacement(input, &mut buffer, true);
+        output.extend_from_slice(&buffer[..written]);
+        input = &input[read..];
+
+        match result {
+            EncoderResult::InputEmpty => break,
+            EncoderResult::OutputFull => continue,
+            EncoderResult::Unmappable(_) => output.push(b'?'),
+        }
+    }
+
+    output
+}
+
+/// 导出时附加的计算列
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtraColumn {
+    /// 按标签的累积和（跳过 NaN）
+    Cumsum,
+    /// 与同一标签上一个有效值的差值（首个有效值为空）
+    Delta,
+}
+
+impl ExtraColumn {
+    /// 解析列名（大小写不敏感）
+    pub fn parse(name: &str) -> AppResult<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "cumsum" => Ok(Self::Cumsum),
+            "delta" => Ok(Self::Delta),
+            other => Err(AppError::Validation(format!(
+                "不支持的计算列: '{}'，可选值: cumsum, delta",
+                other
+            ))),
+        }
+    }
+
+    /// 解析列名列表
+    pub fn parse_list(names: &[String]) -> AppResult<Vec<Self>> {
+        names.iter().map(|name| Self::parse(name)).collect()
+    }
+
+    /// CSV 表头列名
+    fn header(&self) -> &'static str {
+        match self {
+            Self::Cumsum => "Cumsum",
+            Self::Delta => "Delta",
+        }
+    }
+}
+
+/// 按记录顺序为每条记录计算附加列的值（各标签独立累计）
+fn compute_extra_columns(
+    records: &[HistoryRecord],
+    columns: &[ExtraColumn],
+) -> Vec<Vec<Option<f64>>> {
+    let mut sums: HashMap<&str, f64> = HashMap::new();
+    let mut previous: HashMap<&str, f64> = HashMap::new();
+
+    records
+        .iter()
+        .map(|record| {
+            let tag = record.tag_name.as_str();
+            let value = record.tag_val;
+            let row = columns
+                .iter()
+                .map(|column| match column {
+                    ExtraColumn::Cumsum => {
+                        let sum = sums.entry(tag).or_insert(0.0);
+                        if value.is_finite() {
+                            *sum += value;
+                        }
+                        Some(*sum)
+                    }
+                    ExtraColumn::Delta => {
+                        if value.is_finite() {
+                            previous.get(tag).map(|prev| value - prev)
+                        } else {
+                            None
+                        }
+                    }
+                })
+                .collect();
+            if value.is_finite() {
+                previous.insert(tag, value);
+            }
+            row
+        })
+        .collect()
+}
+
+/// 生成 CSV 文本内容
+pub fn build_csv_content(records: &[HistoryRecord]) -> String {
+    build_csv_content_with_columns(records, &[])
+}
+
+/// 生成 CSV 文本内容，并为每个标签附加计算列
+pub fn build_csv_content_with_columns(
+    records: &[HistoryRecord],
+    extra_columns: &[ExtraColumn],
+) -> String {
+    let mut content = String::with_capacity(records.len() * 64 + 64);
+    content.push_str("DateTime,TagName,TagVal,TagQuality");
+    for column in extra_columns {
+        content.push(',');
+        content.push_str(column.header());
+    }
+    content.push('\n');
+
+    let extra_values = compute_extra_columns(records, extra_columns);
+    for (record, extra) in records.iter().zip(extra_values) {
+        content.push_str(&format!(
+            "{},{},{},{}",
+            record.date_time,
+            record.tag_name.replace(',', ";"),
+            record.tag_val,
+            record.tag_quality.replace(',', ";")
+        ));
+        for value in extra {
+            content.push(',');
+            if let Some(value) = value {
+                content.push_str(&value.to_string());
+            }
+        }
+        content.push('\n');
+    }
+
+    content
+}
+
+/// 按指定编码写出 CSV 文件
+pub fn write_csv(
+    records: &[HistoryRecord],
+    file_path: impl AsRef<Path>,
+    encoding: CsvEncoding,
+    extra_columns: &[ExtraColumn],
+) -> AppResult<()> {
+    let content = build_csv_content_with_columns(records, extra_columns);
+    let bytes = encoding.encode(&content);
+
+    debug!(target: "industry_vis::export",
+        "写出 CSV - 编码: {:?}, 字节数: {}", encoding, bytes.len()
+    );
+
+    fs::write(file_path, bytes)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_records() -> Vec<HistoryRecord> {
+        vec![HistoryRecord::new(
+            "2024-01-01T00:00:00.000".to_string(),
+            "温度传感器1".to_string(),
+            12.5,
+            "Good".to_string(),
+        )]
+    }
+
+    #[test]
+    fn test_parse_encoding() {
+        assert_eq!(CsvEncoding::parse(None).unwrap(), CsvEncoding::Utf8);
+        assert_eq!(
+            CsvEncoding::parse(Some("UTF-8")).unwrap(),
+            CsvEncoding::Utf8
+        );
+        assert_eq!(CsvEncoding::parse(Some("gbk")).unwrap(), CsvEncoding::Gbk);
+        assert!(CsvEncoding::parse(Some("latin1")).is_err());
+    }
+
+    #[test]
+    fn test_write_csv_gbk() {
+        let records = create_test_records();
+        let path = std::env::temp_dir().join("industry_vis_test_export_gbk.csv");
+
+        write_csv(&records, &path, CsvEncoding::Gbk, &[]).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        let content = build_csv_content(&records);
+        let (expected, _, _) = GBK.encode(&content);
+        assert_eq!(bytes, expected.into_owned());
+        assert_ne!(bytes, content.into_bytes());
+    }
+
+    #[test]
+    fn test_write_csv_utf8_unchanged() {
+        let records = create_test_records();
+        let path = std::env::temp_dir().join("industry_vis_test_export_utf8.csv");
+
+        write_csv(&records, &path, CsvEncoding::Utf8, &[]).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(content, build_csv_content(&records));
+        assert!(content.contains("温度传感器1"));
+    }
+
+    #[test]
+    fn test_extra_columns_cumsum_and_delta() {
+        let record = |time: &str, tag: &str, value: f64| {
+            HistoryRecord::new(time.to_string(), tag.to_string(), value, "Good".to_string())
+        };
+        let records = vec![
+            record("t1", "FT_101", 1.5),
+            record("t1", "FT_102", 10.0),
+            record("t2", "FT_101", 2.5),
+            record("t2", "FT_102", f64::NAN),
+            record("t3", "FT_101", 4.0),
+            record("t3", "FT_102", 7.0),
+        ];
+
+        let content = build_csv_content_with_columns(
+            &records,
+            &ExtraColumn::parse_list(&["cumsum".to_string(), "Delta".to_string()]).unwrap(),
+        );
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines[0], "DateTime,TagName,TagVal,TagQuality,Cumsum,Delta");
+        // 累积和按标签独立计算，NaN 不计入
+        assert_eq!(lines[1], "t1,FT_101,1.5,Good,1.5,");
+        assert_eq!(lines[3], "t2,FT_101,2.5,Good,4,1");
+        assert_eq!(lines[4], "t2,FT_102,NaN,Good,10,");
+        assert_eq!(lines[5], "t3,FT_101,4,Good,8,1.5");
+        assert_eq!(lines[6], "t3,FT_102,7,Good,17,-3");
+
+        assert!(ExtraColumn::parse("median").is_err());
+    }
+
+    #[test]
+    fn test_gbk_unmappable_replaced() {
+        let bytes = CsvEncoding::Gbk.encode("a😀b");
+        assert_eq!(bytes, b"a?b");
+    }
+}
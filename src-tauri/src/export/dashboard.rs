@@ -0,0 +1,351 @@
+//! 分组仪表盘定义导出/导入
+//!
+//! 将一个标签分组导出为自包含的仪表盘 JSON（布局、标签、别名、报警线、Y 轴、
+//! 处理配置、时间范围、标注、标签元数据），其他实例或 Web 端无需分组配置即可直接渲染；
+//! 也可导入还原为等价的分组。
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    Annotation, ChartConfig, DataProcessingConfig, TagAlarm, TagAlias, TagAxis, TagGroup,
+    TagMetadata, TagValueMap,
+};
+
+/// 仪表盘定义格式版本（不兼容变更时递增）
+pub const DASHBOARD_FORMAT_VERSION: u32 = 1;
+
+/// 布局网格列数（与分组编辑页的双列布局一致）
+const DASHBOARD_GRID_COLUMNS: u32 = 2;
+
+/// 仪表盘定义
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardDefinition {
+    /// 格式版本
+    pub format_version: u32,
+    /// 仪表盘名称（分组名称）
+    pub name: String,
+    /// 导出时间
+    pub exported_at: String,
+    /// 默认时间范围
+    pub time_range: DashboardTimeRange,
+    /// 数据处理配置
+    pub processing_config: DataProcessingConfig,
+    /// 网格布局
+    pub layout: DashboardLayout,
+    /// 图表列表（按布局位置排序）
+    pub charts: Vec<DashboardChart>,
+    /// 标注区间
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
+    /// 标签元数据（工程单位等）
+    #[serde(default)]
+    pub tag_metadata: Vec<TagMetadata>,
+}
+
+/// 仪表盘时间范围
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardTimeRange {
+    pub start: String,
+    pub end: String,
+}
+
+/// 仪表盘网格布局
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardLayout {
+    /// 网格列数
+    pub columns: u32,
+}
+
+/// 图表在网格中的位置（单位为网格格数）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChartPosition {
+    pub row: u32,
+    pub col: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// 仪表盘中的单个图表
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardChart {
+    pub id: String,
+    pub name: String,
+    pub position: ChartPosition,
+    /// 图表内的系列（顺序即图例顺序）
+    pub series: Vec<DashboardSeries>,
+}
+
+/// 图表中的单个标签系列
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardSeries {
+    /// 标签名称（查询用）
+    pub tag: String,
+    /// 显示名称（配置了别名时为别名，否则为标签名）
+    pub display_name: String,
+    /// Y 轴索引：0 左轴，1 右轴
+    #[serde(default)]
+    pub y_axis_index: u8,
+    #[serde(default)]
+    pub alarm_high: Option<f64>,
+    #[serde(default)]
+    pub alarm_low: Option<f64>,
+    /// 值映射（离散状态标签的数字码 → 显示文本）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub value_map: HashMap<String, String>,
+}
+
+/// 将分组构建为仪表盘定义
+pub fn build_dashboard(group: &TagGroup, start: &str, end: &str) -> DashboardDefinition {
+    let charts = group
+        .charts
+        .iter()
+        .enumerate()
+        .map(|(index, chart)| DashboardChart {
+            id: chart.id.clone(),
+            name: chart.name.clone(),
+            position: ChartPosition {
+                row: index as u32 / DASHBOARD_GRID_COLUMNS,
+                col: index as u32 % DASHBOARD_GRID_COLUMNS,
+                width: 1,
+                height: 1,
+            },
+            series: chart
+                .tags
+                .iter()
+                .map(|tag| {
+                    let alarm = chart.alarms.iter().find(|a| &a.tag == tag);
+                    DashboardSeries {
+                        tag: tag.clone(),
+                        display_name: chart.alias(tag).unwrap_or(tag).to_string(),
+                        y_axis_index: chart.y_axis_index(tag),
+                        alarm_high: alarm.and_then(|a| a.alarm_high),
+                        alarm_low: alarm.and_then(|a| a.alarm_low),
+                        value_map: chart.value_map(tag).cloned().unwrap_or_default(),
+                    }
+                })
+                .collect(),
+        })
+        .collect();
+
+    DashboardDefinition {
+        format_version: DASHBOARD_FORMAT_VERSION,
+        name: group.name.clone(),
+        exported_at: Local::now().format("%Y-%m-%dT%H:%M:%S").to_string(),
+        time_range: DashboardTimeRange {
+            start: start.to_string(),
+            end: end.to_string(),
+        },
+        processing_config: group.processing_config.clone(),
+        layout: DashboardLayout {
+            columns: DASHBOARD_GRID_COLUMNS,
+        },
+        charts,
+        annotations: group.annotations.clone(),
+        tag_metadata: group.tag_metadata.clone(),
+    }
+}
+
+/// 由仪表盘定义还原分组（生成新的分组 ID，图表按布局位置排序）
+pub fn dashboard_to_group(dashboard: &DashboardDefinition) -> AppResult<TagGroup> {
+    if dashboard.format_version > DASHBOARD_FORMAT_VERSION {
+        return Err(AppError::Validation(format!(
+            "仪表盘格式版本 {} 高于当前支持的版本 {}，请升级应用",
+            dashboard.format_version, DASHBOARD_FORMAT_VERSION
+        )));
+    }
+
+    let mut charts: Vec<&DashboardChart> = dashboard.charts.iter().collect();
+    charts.sort_by_key(|c| (c.position.row, c.position.col));
+    let charts = charts.into_iter().map(chart_from_dashboard).collect();
+
+    let mut group = TagGroup::new(dashboard.name.clone(), charts).map_err(AppError::Validation)?;
+    group.processing_config = dashboard.processing_config.clone();
+    for annotation in &dashboard.annotations {
+        annotation.validate().map_err(AppError::Validation)?;
+    }
+    group.annotations = dashboard.annotations.clone();
+    group
+        .set_tag_metadata(dashboard.tag_metadata.clone())
+        .map_err(AppError::Validation)?;
+    Ok(group)
+}
+
+fn chart_from_dashboard(chart: &DashboardChart) -> ChartConfig {
+    let series = &chart.series;
+    ChartConfig::with_id(chart.id.clone(), chart.name.clone())
+        .with_tags(series.iter().map(|s| s.tag.clone()).collect())
+        .with_alarms(
+            series
+                .iter()
+                .filter(|s| s.alarm_high.is_some() || s.alarm_low.is_some())
+                .map(|s| TagAlarm {
+                    tag: s.tag.clone(),
+                    alarm_high: s.alarm_high,
+                    alarm_low: s.alarm_low,
+                })
+                .collect(),
+        )
+        .with_axes(
+            series
+                .iter()
+                .filter(|s| s.y_axis_index != 0)
+                .map(|s| TagAxis {
+                    tag: s.tag.clone(),
+                    y_axis_index: s.y_axis_index,
+                })
+                .collect(),
+        )
+        .with_aliases(
+            series
+                .iter()
+                .filter(|s| !s.display_name.is_empty() && s.display_name != s.tag)
+                .map(|s| TagAlias {
+                    tag: s.tag.clone(),
+                    alias: s.display_name.clone(),
+                })
+                .collect(),
+        )
+        .with_value_maps(
+            series
+                .iter()
+                .filter(|s| !s.value_map.is_empty())
+                .map(|s| TagValueMap {
+                    tag: s.tag.clone(),
+                    value_map: s.value_map.clone(),
+                })
+                .collect(),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_group() -> TagGroup {
+        let charts = vec![
+            ChartConfig::with_id("c1".to_string(), "温度".to_string())
+                .with_tags(vec!["TT_101".to_string(), "TT_102".to_string()])
+                .with_alarms(vec![TagAlarm {
+                    tag: "TT_101".to_string(),
+                    alarm_high: Some(85.0),
+                    alarm_low: Some(10.0),
+                }])
+                .with_axes(vec![TagAxis {
+                    tag: "TT_102".to_string(),
+                    y_axis_index: 1,
+                }])
+                .with_aliases(vec![TagAlias {
+                    tag: "TT_101".to_string(),
+                    alias: "反应器入口温度".to_string(),
+                }]),
+            ChartConfig::with_id("c2".to_string(), "压力".to_string())
+                .with_tags(vec!["PT_201".to_string(), "PUMP_STATE".to_string()])
+                .with_value_maps(vec![TagValueMap {
+                    tag: "PUMP_STATE".to_string(),
+                    value_map: HashMap::from([
+                        ("0".to_string(), "停止".to_string()),
+                        ("1".to_string(), "运行".to_string()),
+                    ]),
+                }]),
+            ChartConfig::with_id("c3".to_string(), "流量".to_string())
+                .with_tags(vec!["FT_301".to_string()]),
+        ];
+        let mut group = TagGroup::with_id(
+            "g1".to_string(),
+            "1#反应器".to_string(),
+            charts,
+            "2024-01-01T00:00:00".to_string(),
+            "2024-01-01T00:00:00".to_string(),
+        );
+        group.processing_config = DataProcessingConfig::default()
+            .with_resample(60, "mean")
+            .with_baseline("first");
+        group
+            .add_annotation(Annotation {
+                id: "a1".to_string(),
+                start: "2024-01-01T08:00:00".to_string(),
+                end: "2024-01-01T09:00:00".to_string(),
+                label: "批次 A".to_string(),
+                color: None,
+            })
+            .unwrap();
+        group
+            .set_tag_metadata(vec![TagMetadata {
+                tag: "PT_201".to_string(),
+                unit: Some("MPa".to_string()),
+            }])
+            .unwrap();
+        group
+    }
+
+    #[test]
+    fn test_dashboard_contains_render_fields() {
+        let dashboard = build_dashboard(
+            &sample_group(),
+            "2024-01-01T00:00:00",
+            "2024-01-02T00:00:00",
+        );
+        let json: serde_json::Value = serde_json::to_value(&dashboard).unwrap();
+
+        assert_eq!(json["formatVersion"], DASHBOARD_FORMAT_VERSION);
+        assert_eq!(json["name"], "1#反应器");
+        assert_eq!(json["timeRange"]["start"], "2024-01-01T00:00:00");
+        assert_eq!(json["layout"]["columns"], 2);
+        assert_eq!(json["processingConfig"]["resample"]["interval"], 60);
+        assert_eq!(json["annotations"][0]["label"], "批次 A");
+        assert_eq!(json["tagMetadata"][0]["unit"], "MPa");
+
+        let first = &json["charts"][0];
+        assert_eq!(first["position"]["row"], 0);
+        assert_eq!(first["position"]["col"], 0);
+        assert_eq!(first["series"][0]["tag"], "TT_101");
+        assert_eq!(first["series"][0]["displayName"], "反应器入口温度");
+        assert_eq!(first["series"][0]["alarmHigh"], 85.0);
+        assert_eq!(first["series"][0]["alarmLow"], 10.0);
+        assert_eq!(first["series"][1]["displayName"], "TT_102");
+        assert_eq!(first["series"][1]["yAxisIndex"], 1);
+        assert_eq!(json["charts"][1]["series"][1]["valueMap"]["1"], "运行");
+
+        // 第三个图表换行到第二行
+        assert_eq!(json["charts"][2]["position"]["row"], 1);
+        assert_eq!(json["charts"][2]["position"]["col"], 0);
+    }
+
+    #[test]
+    fn test_dashboard_roundtrip_restores_equivalent_group() {
+        let group = sample_group();
+        let dashboard = build_dashboard(&group, "2024-01-01T00:00:00", "2024-01-02T00:00:00");
+        let json = serde_json::to_string(&dashboard).unwrap();
+        let parsed: DashboardDefinition = serde_json::from_str(&json).unwrap();
+        let restored = dashboard_to_group(&parsed).unwrap();
+
+        assert_eq!(restored.name, group.name);
+        assert_eq!(restored.charts, group.charts);
+        assert_eq!(restored.processing_config, group.processing_config);
+        assert_eq!(restored.annotations, group.annotations);
+        assert_eq!(restored.tag_metadata, group.tag_metadata);
+    }
+
+    #[test]
+    fn test_dashboard_rejects_newer_version() {
+        let mut dashboard = build_dashboard(
+            &sample_group(),
+            "2024-01-01T00:00:00",
+            "2024-01-02T00:00:00",
+        );
+        dashboard.format_version = DASHBOARD_FORMAT_VERSION + 1;
+        assert!(matches!(
+            dashboard_to_group(&dashboard),
+            Err(AppError::Validation(_))
+        ));
+    }
+}
@@ -0,0 +1,104 @@
+//! 导出文件名模板
+//!
+//! 导出路径中可包含占位符，由后端在导出时渲染（批量/定时导出无需前端拼接文件名）：
+//!
+//! - `{date}`：导出日期，如 `20240101`
+//! - `{time}`：导出时刻，如 `083000`
+//! - `{group}`：分组名（文件系统不允许的字符替换为 `_`）
+//! - `{tags}`：标签数
+//!
+//! 不含占位符的路径原样返回。
+
+use chrono::NaiveDateTime;
+
+use super::bundle::sanitize_file_name_part;
+use crate::error::{AppError, AppResult};
+
+/// 文件名模板渲染上下文
+#[derive(Debug, Clone, Copy)]
+pub struct FileNameContext<'a> {
+    /// 分组名（未指定时模板中不能使用 `{group}`）
+    pub group: Option<&'a str>,
+    /// 标签数
+    pub tag_count: usize,
+    /// 导出时刻
+    pub now: NaiveDateTime,
+}
+
+/// 路径中是否包含模板占位符
+pub fn has_file_name_placeholders(template: &str) -> bool {
+    template.contains('{')
+}
+
+/// 渲染导出路径模板
+pub fn render_file_name_template(template: &str, ctx: &FileNameContext) -> AppResult<String> {
+    let mut rendered = String::with_capacity(template.len() + 16);
+    let mut rest = template;
+
+    while let Some(open) = rest.find('{') {
+        rendered.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let close = after.find('}').ok_or_else(|| {
+            AppError::Validation(format!("文件名模板中的 '{{' 未闭合: {}", template))
+        })?;
+
+        match &after[..close] {
+            "date" => rendered.push_str(&ctx.now.format("%Y%m%d").to_string()),
+            "time" => rendered.push_str(&ctx.now.format("%H%M%S").to_string()),
+            "tags" => rendered.push_str(&ctx.tag_count.to_string()),
+            "group" => {
+                let group = ctx.group.filter(|g| !g.trim().is_empty()).ok_or_else(|| {
+                    AppError::Validation("文件名模板使用了 {group}，但未指定分组".to_string())
+                })?;
+                rendered.push_str(&sanitize_file_name_part(group));
+            }
+            other => {
+                return Err(AppError::Validation(format!(
+                    "未知的文件名占位符: {{{}}}，可选: {{date}}, {{time}}, {{group}}, {{tags}}",
+                    other
+                )));
+            }
+        }
+        rest = &after[close + 1..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(group: Option<&str>) -> FileNameContext<'_> {
+        FileNameContext {
+            group,
+            tag_count: 12,
+            now: NaiveDateTime::parse_from_str("2024-03-05T08:30:00", "%Y-%m-%dT%H:%M:%S").unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_render_date_group_and_tags() {
+        let path = render_file_name_template(
+            "D:/exports/{group}_{date}_{time}_{tags}tags.csv",
+            &context(Some("1#锅炉/汽包")),
+        )
+        .unwrap();
+        assert_eq!(path, "D:/exports/1#锅炉_汽包_20240305_083000_12tags.csv");
+
+        // 无占位符时原样返回
+        let plain = "D:/exports/data.csv";
+        assert!(!has_file_name_placeholders(plain));
+        assert_eq!(
+            render_file_name_template(plain, &context(None)).unwrap(),
+            plain
+        );
+    }
+
+    #[test]
+    fn test_render_rejects_invalid_templates() {
+        assert!(render_file_name_template("{group}.csv", &context(None)).is_err());
+        assert!(render_file_name_template("{user}.csv", &context(None)).is_err());
+        assert!(render_file_name_template("{date.csv", &context(None)).is_err());
+    }
+}
@@ -0,0 +1,327 @@
+//! 导出历史记录
+//!
+//! 每次导出成功后追加一条记录（保存到配置目录的 JSON），支持按原参数重新导出。
+//! 记录只保存查询参数和导出选项，重新导出时重新查询数据。
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+use super::csv::{CsvEncoding, ExtraColumn, write_csv};
+use crate::config::AppConfig;
+use crate::error::{AppError, AppResult};
+use crate::models::{DataProcessingConfig, HistoryRecord, QueryParams};
+
+/// 导出历史最多保留的条数
+const MAX_HISTORY_ENTRIES: usize = 20;
+
+/// 历史文件名
+const HISTORY_FILENAME: &str = "export_history.json";
+
+/// CSV 导出参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvExportRequest {
+    /// 导出数据对应的查询参数（重新导出时用于重新查询）
+    ///
+    /// 前端直接传入记录、没有查询参数时为 None，此类记录只留档，不能重新导出。
+    #[serde(default)]
+    pub params: Option<QueryParams>,
+    /// 数据处理配置
+    #[serde(default)]
+    pub processing_config: Option<DataProcessingConfig>,
+    /// 目标文件路径
+    pub file_path: String,
+    /// 编码名称（原样保存前端传入值）
+    pub encoding: Option<String>,
+    /// 附加计算列（`"cumsum"` / `"delta"`）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_columns: Vec<String>,
+}
+
+impl CsvExportRequest {
+    /// 按导出选项写出记录
+    pub fn execute(&self, records: &[HistoryRecord]) -> AppResult<()> {
+        let encoding = CsvEncoding::parse(self.encoding.as_deref())?;
+        let extra_columns = ExtraColumn::parse_list(&self.extra_columns)?;
+        write_csv(records, &self.file_path, encoding, &extra_columns)
+    }
+
+    /// 重新导出时使用的查询参数（查询全部数据，忽略分页），没有查询参数时返回 `Validation`
+    pub fn full_query_params(&self) -> AppResult<QueryParams> {
+        let params = self.params.as_ref().ok_or_else(|| {
+            AppError::Validation(format!(
+                "导出 '{}' 未保存查询参数，无法重新导出",
+                self.file_path
+            ))
+        })?;
+        Ok(QueryParams {
+            limit: None,
+            offset: None,
+            chunk_size: None,
+            ..params.clone()
+        })
+    }
+}
+
+/// 导出历史条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportHistoryEntry {
+    /// 条目 ID
+    pub id: String,
+    /// 导出时间
+    pub exported_at: String,
+    /// 导出的记录数
+    pub record_count: usize,
+    /// 导出参数
+    pub request: CsvExportRequest,
+}
+
+/// 导出历史摘要（返回前端）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportHistoryItem {
+    pub id: String,
+    pub exported_at: String,
+    pub file_path: String,
+    pub encoding: Option<String>,
+    pub record_count: usize,
+    /// 是否保存了查询参数（可重新导出）
+    pub repeatable: bool,
+}
+
+impl From<&ExportHistoryEntry> for ExportHistoryItem {
+    fn from(entry: &ExportHistoryEntry) -> Self {
+        Self {
+            id: entry.id.clone(),
+            exported_at: entry.exported_at.clone(),
+            file_path: entry.request.file_path.clone(),
+            encoding: entry.request.encoding.clone(),
+            record_count: entry.record_count,
+            repeatable: entry.request.params.is_some(),
+        }
+    }
+}
+
+/// 导出历史
+#[derive(Debug)]
+pub struct ExportHistory {
+    entries: Vec<ExportHistoryEntry>,
+    path: PathBuf,
+}
+
+impl ExportHistory {
+    /// 历史文件路径（与 config.toml 同目录）
+    pub fn history_path() -> AppResult<PathBuf> {
+        let config_path = AppConfig::config_path()?;
+        let dir = config_path
+            .parent()
+            .ok_or_else(|| AppError::Config("无法确定配置目录".to_string()))?;
+        Ok(dir.join(HISTORY_FILENAME))
+    }
+
+    /// 从默认位置加载
+    pub fn load() -> AppResult<Self> {
+        Self::load_from(&Self::history_path()?)
+    }
+
+    /// 从指定路径加载，文件不存在时返回空历史
+    pub fn load_from(path: &Path) -> AppResult<Self> {
+        let entries = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(path)?)?
+        } else {
+            Vec::new()
+        };
+        debug!(target: "industry_vis::export", "加载导出历史: {} 条", entries.len());
+
+        Ok(Self {
+            entries,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// 保存到文件
+    pub fn save(&self) -> AppResult<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string(&self.entries)?)?;
+        Ok(())
+    }
+
+    /// 追加一条记录并保存，超过上限时丢弃最早的记录
+    pub fn record(
+        &mut self,
+        request: CsvExportRequest,
+        record_count: usize,
+    ) -> AppResult<ExportHistoryItem> {
+        let entry = ExportHistoryEntry {
+            id: format!("e{}", Local::now().timestamp_millis()),
+            exported_at: Local::now().format("%Y-%m-%dT%H:%M:%S").to_string(),
+            record_count,
+            request,
+        };
+        let item = ExportHistoryItem::from(&entry);
+
+        self.entries.push(entry);
+        if self.entries.len() > MAX_HISTORY_ENTRIES {
+            let overflow = self.entries.len() - MAX_HISTORY_ENTRIES;
+            self.entries.drain(..overflow);
+        }
+        self.save()?;
+
+        Ok(item)
+    }
+
+    /// 历史摘要列表（最新在前）
+    pub fn list(&self) -> Vec<ExportHistoryItem> {
+        self.entries
+            .iter()
+            .rev()
+            .map(ExportHistoryItem::from)
+            .collect()
+    }
+
+    /// 获取历史条目
+    pub fn get(&self, id: &str) -> Option<&ExportHistoryEntry> {
+        self.entries.iter().find(|e| e.id == id)
+    }
+
+    /// 获取历史条目的导出参数，不存在时返回 `NotFound`
+    pub fn request(&self, id: &str) -> AppResult<&CsvExportRequest> {
+        let entry = self
+            .get(id)
+            .ok_or_else(|| AppError::NotFound(format!("导出历史 '{}' 不存在", id)))?;
+
+        info!(target: "industry_vis::export",
+            "重新导出 - 路径: {}, 时间: {:?}",
+            entry.request.file_path,
+            entry.request.params.as_ref().map(|p| (&p.start_time, &p.end_time))
+        );
+        Ok(&entry.request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_records() -> Vec<HistoryRecord> {
+        vec![HistoryRecord::new(
+            "2024-01-01T00:00:00".to_string(),
+            "温度".to_string(),
+            1.5,
+            "192".to_string(),
+        )]
+    }
+
+    fn test_request(file_name: &str) -> CsvExportRequest {
+        CsvExportRequest {
+            params: Some(
+                QueryParams::new(
+                    "2024-01-01T00:00:00".to_string(),
+                    "2024-01-02T00:00:00".to_string(),
+                )
+                .with_tags(vec!["温度".to_string()])
+                .with_pagination(100, 50),
+            ),
+            processing_config: None,
+            file_path: std::env::temp_dir()
+                .join(file_name)
+                .to_string_lossy()
+                .to_string(),
+            encoding: Some("gbk".to_string()),
+            extra_columns: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_record_appends_entry() {
+        let path = std::env::temp_dir().join("industry_vis_test_export_history_record.json");
+        let _ = fs::remove_file(&path);
+
+        let mut history = ExportHistory::load_from(&path).unwrap();
+        assert!(history.list().is_empty());
+
+        let request = test_request("industry_vis_test_history_record.csv");
+        let records = test_records();
+        request.execute(&records).unwrap();
+        let item = history.record(request, records.len()).unwrap();
+        assert_eq!(item.record_count, 1);
+
+        // 重新加载后仍然存在，且不保存导出数据
+        let reloaded = ExportHistory::load_from(&path).unwrap();
+        assert_eq!(reloaded.list().len(), 1);
+        assert_eq!(reloaded.list()[0].id, item.id);
+        assert!(!fs::read_to_string(&path).unwrap().contains("192"));
+
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(&reloaded.get(&item.id).unwrap().request.file_path);
+    }
+
+    #[test]
+    fn test_request_keeps_query_params() {
+        let path = std::env::temp_dir().join("industry_vis_test_export_history_repeat.json");
+        let _ = fs::remove_file(&path);
+
+        let mut history = ExportHistory::load_from(&path).unwrap();
+        let item = history
+            .record(test_request("industry_vis_test_history_repeat.csv"), 1)
+            .unwrap();
+
+        let reloaded = ExportHistory::load_from(&path).unwrap();
+        let request = reloaded.request(&item.id).unwrap();
+        assert_eq!(request.encoding.as_deref(), Some("gbk"));
+        let params = request.full_query_params().unwrap();
+        assert_eq!(params.tags, Some(vec!["温度".to_string()]));
+        assert_eq!(params.start_time, "2024-01-01T00:00:00");
+        assert_eq!((params.offset, params.limit), (None, None));
+
+        assert!(matches!(
+            reloaded.request("missing"),
+            Err(AppError::NotFound(_))
+        ));
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_record_without_params_is_not_repeatable() {
+        let path = std::env::temp_dir().join("industry_vis_test_export_history_no_params.json");
+        let _ = fs::remove_file(&path);
+
+        let mut history = ExportHistory::load_from(&path).unwrap();
+        let request = CsvExportRequest {
+            params: None,
+            ..test_request("industry_vis_test_history_no_params.csv")
+        };
+        let item = history.record(request, 1).unwrap();
+        assert!(!item.repeatable);
+
+        let reloaded = ExportHistory::load_from(&path).unwrap();
+        assert_eq!(reloaded.list().len(), 1);
+        assert!(matches!(
+            reloaded.request(&item.id).unwrap().full_query_params(),
+            Err(AppError::Validation(_))
+        ));
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_history_capped() {
+        let path = std::env::temp_dir().join("industry_vis_test_export_history_cap.json");
+        let _ = fs::remove_file(&path);
+
+        let mut history = ExportHistory::load_from(&path).unwrap();
+        for _ in 0..MAX_HISTORY_ENTRIES + 3 {
+            history.record(test_request("unused.csv"), 0).unwrap();
+        }
+        assert_eq!(history.list().len(), MAX_HISTORY_ENTRIES);
+
+        let _ = fs::remove_file(&path);
+    }
+}
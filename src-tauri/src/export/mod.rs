@@ -0,0 +1,42 @@
+//! 数据导出模块
+//!
+//! 提供查询结果导出功能（CSV / Parquet / Arrow IPC），支持多种文本编码，并记录导出历史；
+//! 支持按分组打包导出，以及分组仪表盘定义的导出与导入；
+//! 支持原始数据与处理后数据的对比导出，以及导出路径的文件名模板；
+//! 以及 V2 查询结果的紧凑二进制编码（用于 IPC 传输）、定时导出计划和分组 PDF 报告。
+
+mod arrow;
+mod binary;
+mod bundle;
+mod comparison;
+mod csv;
+mod dashboard;
+mod file_name;
+mod history;
+mod parquet;
+mod report_pdf;
+mod schedule;
+
+pub use arrow::write_arrow;
+pub use binary::{decode_series_binary, encode_series_binary};
+pub use bundle::{
+    BundleFormat, GroupExportResult, group_export_file_name, write_group_bundle, write_records,
+};
+pub use comparison::{
+    ComparisonRow, build_comparison_csv, build_comparison_rows, write_comparison_csv,
+};
+pub use csv::{
+    CsvEncoding, ExtraColumn, build_csv_content, build_csv_content_with_columns, write_csv,
+};
+pub use dashboard::{
+    ChartPosition, DASHBOARD_FORMAT_VERSION, DashboardChart, DashboardDefinition, DashboardLayout,
+    DashboardSeries, DashboardTimeRange, build_dashboard, dashboard_to_group,
+};
+pub use file_name::{FileNameContext, has_file_name_placeholders, render_file_name_template};
+pub use history::{CsvExportRequest, ExportHistory, ExportHistoryEntry, ExportHistoryItem};
+pub use parquet::{plan_tag_partitions, write_parquet};
+pub use report_pdf::{
+    PdfReportResult, ReportChart, ReportSeries, SeriesStatistics, build_report_charts,
+    build_report_pdf, write_report_pdf,
+};
+pub use schedule::{CronSchedule, ExportSchedule, ScheduledExport, ScheduledExportStore};
@@ -0,0 +1,196 @@
+//! Parquet 导出实现
+//!
+//! 支持按标签分区（`tag_name=xxx/` 目录），便于下游按标签裁剪读取。
+
+use polars::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{Fnv64, HistoryRecord};
+use crate::processing::records_to_dataframe;
+
+/// 分区目录下的数据文件名
+const PARTITION_FILENAME: &str = "data.parquet";
+
+/// 分区目录名：`tag_name=<标签名>`，路径非法字符替换为 `_`
+fn partition_dir_name(tag_name: &str) -> String {
+    let sanitized: String = tag_name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    format!("tag_name={}", sanitized)
+}
+
+/// 标签名的短指纹（8 位十六进制），用于区分替换非法字符后同名的分区目录
+fn tag_fingerprint(tag_name: &str) -> String {
+    let mut hasher = Fnv64::new();
+    hasher.write(tag_name.as_bytes());
+    format!("{:08x}", hasher.0 as u32)
+}
+
+/// 按标签规划分区：返回 (分区文件路径, 该标签的记录)，按标签名排序
+///
+/// 不同标签替换非法字符后目录名相同时（如 `FIC/101` 与 `FIC:101`），这些标签的目录名
+/// 追加原标签名的短指纹（`tag_name=FIC_101~1a2b3c4d`），避免后写出的文件覆盖先写出的。
+pub fn plan_tag_partitions(
+    records: &[HistoryRecord],
+    root: impl AsRef<Path>,
+) -> Vec<(PathBuf, Vec<HistoryRecord>)> {
+    let mut groups: BTreeMap<&str, Vec<HistoryRecord>> = BTreeMap::new();
+    for record in records {
+        groups
+            .entry(record.tag_name.as_str())
+            .or_default()
+            .push(record.clone());
+    }
+
+    let mut dir_counts: HashMap<String, usize> = HashMap::new();
+    for tag_name in groups.keys() {
+        *dir_counts.entry(partition_dir_name(tag_name)).or_default() += 1;
+    }
+
+    groups
+        .into_iter()
+        .map(|(tag_name, tag_records)| {
+            let mut dir = partition_dir_name(tag_name);
+            if dir_counts[&dir] > 1 {
+                dir = format!("{}~{}", dir, tag_fingerprint(tag_name));
+            }
+            let path = root.as_ref().join(dir).join(PARTITION_FILENAME);
+            (path, tag_records)
+        })
+        .collect()
+}
+
+/// 写出 Parquet
+///
+/// `partition_by_tag` 为 true 时 `path` 视为目录，每个标签写入
+/// `path/tag_name=xxx/data.parquet`；否则写入单个文件。返回写出的文件路径。
+pub fn write_parquet(
+    records: &[HistoryRecord],
+    path: impl AsRef<Path>,
+    partition_by_tag: bool,
+) -> AppResult<Vec<PathBuf>> {
+    let path = path.as_ref();
+
+    if !partition_by_tag {
+        write_parquet_file(records, path)?;
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let partitions = plan_tag_partitions(records, path);
+    let mut written = Vec::with_capacity(partitions.len());
+    for (file_path, tag_records) in partitions {
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_parquet_file(&tag_records, &file_path)?;
+        written.push(file_path);
+    }
+
+    debug!(target: "industry_vis::export",
+        "写出 Parquet 分区 - 目录: {}, 分区数: {}", path.display(), written.len()
+    );
+
+    Ok(written)
+}
+
+/// 将记录写入单个 Parquet 文件
+fn write_parquet_file(records: &[HistoryRecord], path: &Path) -> AppResult<()> {
+    let mut df = records_to_dataframe(records)?;
+    let file = fs::File::create(path)?;
+    ParquetWriter::new(file)
+        .finish(&mut df)
+        .map_err(|e| AppError::DataProcessing(format!("写入 Parquet 失败: {}", e)))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(tag: &str, val: f64) -> HistoryRecord {
+        HistoryRecord::new(
+            "2024-01-01T00:00:00.000".to_string(),
+            tag.to_string(),
+            val,
+            "Good".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_plan_tag_partitions() {
+        let records = vec![
+            record("B", 1.0),
+            record("A", 2.0),
+            record("B", 3.0),
+            record("C", 4.0),
+        ];
+
+        let partitions = plan_tag_partitions(&records, "/export");
+        assert_eq!(partitions.len(), 3);
+
+        let (path, a_records) = &partitions[0];
+        assert_eq!(path, &PathBuf::from("/export/tag_name=A/data.parquet"));
+        assert_eq!(a_records.len(), 1);
+
+        let (_, b_records) = &partitions[1];
+        assert_eq!(b_records.len(), 2);
+        assert!(b_records.iter().all(|r| r.tag_name == "B"));
+    }
+
+    #[test]
+    fn test_plan_tag_partitions_disambiguates_collisions() {
+        let records = vec![
+            record("FIC/101", 1.0),
+            record("FIC:101", 2.0),
+            record("TT_101", 3.0),
+        ];
+
+        let partitions = plan_tag_partitions(&records, "/export");
+        let paths: Vec<&PathBuf> = partitions.iter().map(|(p, _)| p).collect();
+        assert_eq!(paths.len(), 3);
+        assert_ne!(paths[0], paths[1]);
+        assert_eq!(
+            paths[0],
+            &PathBuf::from(format!(
+                "/export/tag_name=FIC_101~{}/data.parquet",
+                tag_fingerprint("FIC/101")
+            ))
+        );
+        // 没有冲突的标签保持原目录名
+        assert_eq!(
+            paths[2],
+            &PathBuf::from("/export/tag_name=TT_101/data.parquet")
+        );
+        assert!(partitions[1].1.iter().all(|r| r.tag_name == "FIC:101"));
+    }
+
+    #[test]
+    fn test_partition_dir_name_sanitized() {
+        assert_eq!(partition_dir_name("FIC/101:PV"), "tag_name=FIC_101_PV");
+        assert_eq!(partition_dir_name("温度"), "tag_name=温度");
+    }
+
+    #[test]
+    fn test_write_parquet_partitioned() {
+        let root = std::env::temp_dir().join("industry_vis_test_parquet_partition");
+        let _ = fs::remove_dir_all(&root);
+
+        let records = vec![record("T1", 1.0), record("T2", 2.0), record("T1", 3.0)];
+        let written = write_parquet(&records, &root, true).unwrap();
+
+        assert_eq!(written.len(), 2);
+        assert!(written.iter().all(|p| p.exists()));
+        assert!(root.join("tag_name=T2").join(PARTITION_FILENAME).exists());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
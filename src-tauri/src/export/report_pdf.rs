@@ -0,0 +1,866 @@
+//! 分组 PDF 报告导出
+//!
+//! 每个图表一页：标题、时间范围、图表（矢量绘制为 Form XObject，缩放不失真）
+//! 和各系列的统计表（点数、最小、最大、平均、最新值）。统计表行数超过一页时续页。
+//!
+//! PDF 直接按规范组版（无第三方依赖）：文字使用 PDF 阅读器内置的宋体
+//! （STSong-Light + UniGB-UCS2-H），不嵌入字体，中文标签名和别名可直接显示。
+
+use chrono::{Local, TimeZone};
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use tracing::debug;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{ChartSeriesData, DataProcessingConfig, ResampleConfig, TagGroup};
+use crate::processing::{
+    OHLC_COMPONENTS, STD_BAND_COMPONENTS, band_tag_name, ohlc_tag_name, stat_tag_name,
+};
+
+/// 页面尺寸（A4 横向，单位 pt）
+const PAGE_WIDTH: f64 = 842.0;
+const PAGE_HEIGHT: f64 = 595.0;
+const PAGE_MARGIN: f64 = 40.0;
+
+/// 图表区域高度
+const CHART_HEIGHT: f64 = 290.0;
+
+/// 统计表行高与每页行数（图表页 / 续页）
+const TABLE_ROW_HEIGHT: f64 = 18.0;
+const TABLE_ROWS_CHART_PAGE: usize = 8;
+const TABLE_ROWS_CONTINUATION_PAGE: usize = 24;
+
+/// 绘图区在图表区域内的边距（左、右、下、上）
+const PLOT_PADDING: [f64; 4] = [64.0, 12.0, 24.0, 10.0];
+
+/// 系列颜色（与前端 ECharts 默认调色板一致）
+const SERIES_COLORS: [[u8; 3]; 9] = [
+    [0x54, 0x70, 0xc6],
+    [0x91, 0xcc, 0x75],
+    [0xfa, 0xc8, 0x58],
+    [0xee, 0x66, 0x66],
+    [0x73, 0xc0, 0xde],
+    [0x3b, 0xa2, 0x72],
+    [0xfc, 0x84, 0x52],
+    [0x9a, 0x60, 0xb4],
+    [0xea, 0x7c, 0xcc],
+];
+
+/// 报告中的一个图表
+#[derive(Debug, Clone)]
+pub struct ReportChart {
+    pub name: String,
+    pub series: Vec<ReportSeries>,
+}
+
+/// 图表中的一个系列
+#[derive(Debug, Clone)]
+pub struct ReportSeries {
+    /// 显示名称（有别名时为"别名 (标签)"）
+    pub label: String,
+    /// 绘图用数据点 [timestamp_ms, value]（可为降采样结果），非有限值为断点
+    pub points: Vec<[f64; 2]>,
+    /// 统计表数据（由未降采样的全部数据点计算）
+    pub stats: SeriesStatistics,
+}
+
+/// 系列统计（只统计有限值）
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeriesStatistics {
+    pub count: usize,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub mean: Option<f64>,
+    pub last: Option<f64>,
+}
+
+impl SeriesStatistics {
+    pub fn compute(points: &[[f64; 2]]) -> Self {
+        let values: Vec<f64> = points
+            .iter()
+            .map(|p| p[1])
+            .filter(|v| v.is_finite())
+            .collect();
+        let count = values.len();
+        Self {
+            count,
+            min: values.iter().copied().reduce(f64::min),
+            max: values.iter().copied().reduce(f64::max),
+            mean: (count > 0).then(|| values.iter().sum::<f64>() / count as f64),
+            last: values.last().copied(),
+        }
+    }
+}
+
+/// PDF 报告导出结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfReportResult {
+    pub file_path: String,
+    pub chart_count: usize,
+    /// 总页数（每个图表至少一页，统计表过长时续页）
+    pub page_count: usize,
+    pub series_count: usize,
+    pub file_size: u64,
+}
+
+/// 标签对应的结果系列名（OHLC / 多统计重采样、波动带输出派生序列）
+fn series_names(tag: &str, config: &DataProcessingConfig) -> Vec<String> {
+    let names = resample_series_names(tag, &config.resample);
+    if !config.std_band.enabled {
+        return names;
+    }
+    names
+        .into_iter()
+        .flat_map(|name| {
+            let bands = STD_BAND_COMPONENTS.map(|c| band_tag_name(&name, c));
+            std::iter::once(name).chain(bands)
+        })
+        .collect()
+}
+
+fn resample_series_names(tag: &str, resample: &ResampleConfig) -> Vec<String> {
+    if resample.enabled && resample.is_ohlc() {
+        OHLC_COMPONENTS
+            .iter()
+            .map(|c| ohlc_tag_name(tag, c))
+            .collect()
+    } else if resample.enabled && resample.is_multi_stat() {
+        resample
+            .methods
+            .iter()
+            .map(|m| stat_tag_name(tag, m))
+            .collect()
+    } else {
+        vec![tag.to_string()]
+    }
+}
+
+/// 按分组的图表布局整理查询结果（系列顺序即图表内的标签顺序，无数据的标签保留为空系列）
+///
+/// `series` 用于绘图（通常已降采样），统计表由 `full_series`（未降采样的全部数据点）计算，
+/// 避免降采样丢失峰值、使平均值偏移。
+pub fn build_report_charts(
+    group: &TagGroup,
+    series: &[ChartSeriesData],
+    full_series: &[ChartSeriesData],
+) -> Vec<ReportChart> {
+    let config = &group.processing_config;
+    group
+        .charts
+        .iter()
+        .map(|chart| ReportChart {
+            name: chart.name.clone(),
+            series: chart
+                .tags
+                .iter()
+                .flat_map(|tag| {
+                    let alias = chart.alias(tag);
+                    series_names(tag, config).into_iter().map(move |name| {
+                        let find = |all: &[ChartSeriesData]| {
+                            all.iter()
+                                .find(|s| s.tag_name == name)
+                                .map(|s| s.data.clone())
+                                .unwrap_or_default()
+                        };
+                        let points = find(series);
+                        let stats = SeriesStatistics::compute(&find(full_series));
+                        let label = match alias {
+                            Some(alias) => format!("{} ({})", alias, name),
+                            None => name,
+                        };
+                        ReportSeries {
+                            label,
+                            points,
+                            stats,
+                        }
+                    })
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// 生成 PDF 报告，返回文件内容和页数
+pub fn build_report_pdf(
+    title: &str,
+    start_time: &str,
+    end_time: &str,
+    charts: &[ReportChart],
+) -> AppResult<(Vec<u8>, usize)> {
+    if charts.is_empty() {
+        return Err(AppError::Validation("没有图表，无法生成报告".to_string()));
+    }
+
+    // 每个图表的页：第一页含图表，后续页只有统计表
+    // (图表序号, 是否含图表, 统计表行范围)
+    let mut pages: Vec<(usize, bool, std::ops::Range<usize>)> = Vec::new();
+    for (index, chart) in charts.iter().enumerate() {
+        let rows = chart.series.len();
+        let first_end = rows.min(TABLE_ROWS_CHART_PAGE);
+        pages.push((index, true, 0..first_end));
+        let mut start = first_end;
+        while start < rows {
+            let end = (start + TABLE_ROWS_CONTINUATION_PAGE).min(rows);
+            pages.push((index, false, start..end));
+            start = end;
+        }
+    }
+
+    let mut pdf = PdfWriter::new();
+    // 1: Catalog, 2: Pages, 3-5: 字体；之后每页占 2 个对象（页面、内容），每个图表 1 个 Form XObject
+    let page_obj = |i: usize| 6 + i * 2;
+    let chart_obj = |i: usize| 6 + pages.len() * 2 + i;
+
+    pdf.object(1, "<< /Type /Catalog /Pages 2 0 R >>");
+    let kids: Vec<String> = (0..pages.len())
+        .map(|i| format!("{} 0 R", page_obj(i)))
+        .collect();
+    pdf.object(
+        2,
+        &format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            pages.len()
+        ),
+    );
+    pdf.object(
+        3,
+        "<< /Type /Font /Subtype /Type0 /BaseFont /STSong-Light /Encoding /UniGB-UCS2-H \
+         /DescendantFonts [4 0 R] >>",
+    );
+    pdf.object(
+        4,
+        "<< /Type /Font /Subtype /CIDFontType0 /BaseFont /STSong-Light \
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (GB1) /Supplement 2 >> \
+         /FontDescriptor 5 0 R /DW 1000 /W [1 95 500] >>",
+    );
+    pdf.object(
+        5,
+        "<< /Type /FontDescriptor /FontName /STSong-Light /Flags 6 \
+         /FontBBox [-25 -254 1000 880] /ItalicAngle 0 /Ascent 880 /Descent -120 \
+         /CapHeight 880 /StemV 93 >>",
+    );
+
+    let time_range = format!("时间范围: {} ~ {}", start_time, end_time);
+    for (page_index, (chart_index, with_chart, rows)) in pages.iter().enumerate() {
+        let chart = &charts[*chart_index];
+        let mut content = String::new();
+        text(
+            &mut content,
+            PAGE_MARGIN,
+            PAGE_HEIGHT - PAGE_MARGIN - 14.0,
+            16.0,
+            &format!("{} - {}", title, chart.name),
+        );
+        text(
+            &mut content,
+            PAGE_MARGIN,
+            PAGE_HEIGHT - PAGE_MARGIN - 34.0,
+            10.0,
+            &format!("{}    第 {}/{} 页", time_range, page_index + 1, pages.len()),
+        );
+
+        let mut table_top = PAGE_HEIGHT - PAGE_MARGIN - 50.0;
+        let resources = if *with_chart {
+            let chart_bottom = table_top - CHART_HEIGHT;
+            let _ = writeln!(
+                content,
+                "q 1 0 0 1 {} {} cm /Chart Do Q",
+                num(PAGE_MARGIN),
+                num(chart_bottom)
+            );
+            table_top = chart_bottom - 16.0;
+            format!("/XObject << /Chart {} 0 R >> ", chart_obj(*chart_index))
+        } else {
+            String::new()
+        };
+        statistics_table(&mut content, table_top, &chart.series, rows.clone());
+
+        pdf.object(
+            page_obj(page_index),
+            &format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                 /Resources << /Font << /F1 3 0 R >> {}>> /Contents {} 0 R >>",
+                num(PAGE_WIDTH),
+                num(PAGE_HEIGHT),
+                resources,
+                page_obj(page_index) + 1
+            ),
+        );
+        pdf.stream(page_obj(page_index) + 1, "", &content);
+    }
+
+    let chart_width = PAGE_WIDTH - PAGE_MARGIN * 2.0;
+    for (index, chart) in charts.iter().enumerate() {
+        pdf.stream(
+            chart_obj(index),
+            &format!(
+                "/Type /XObject /Subtype /Form /BBox [0 0 {} {}] \
+                 /Resources << /Font << /F1 3 0 R >> >> ",
+                num(chart_width),
+                num(CHART_HEIGHT)
+            ),
+            &render_chart(&chart.series, chart_width, CHART_HEIGHT),
+        );
+    }
+
+    let page_count = pages.len();
+    Ok((pdf.finish(chart_obj(charts.len())), page_count))
+}
+
+/// 生成 PDF 报告并写出到文件
+pub fn write_report_pdf(
+    path: impl AsRef<Path>,
+    title: &str,
+    start_time: &str,
+    end_time: &str,
+    charts: &[ReportChart],
+) -> AppResult<PdfReportResult> {
+    let path = path.as_ref();
+    let (bytes, page_count) = build_report_pdf(title, start_time, end_time, charts)?;
+    fs::write(path, &bytes)?;
+
+    debug!(target: "industry_vis::export",
+        "写出 PDF 报告 - 图表数: {}, 页数: {}, 字节数: {}",
+        charts.len(), page_count, bytes.len()
+    );
+    Ok(PdfReportResult {
+        file_path: path.to_string_lossy().to_string(),
+        chart_count: charts.len(),
+        page_count,
+        series_count: charts.iter().map(|c| c.series.len()).sum(),
+        file_size: bytes.len() as u64,
+    })
+}
+
+/// 按对象编号顺序写出的最小 PDF 文件
+struct PdfWriter {
+    buffer: Vec<u8>,
+    /// 对象编号 -> 字节偏移
+    offsets: Vec<(usize, usize)>,
+}
+
+impl PdfWriter {
+    fn new() -> Self {
+        let mut buffer = b"%PDF-1.4\n".to_vec();
+        // 二进制注释行：提示传输工具按二进制处理
+        buffer.extend_from_slice(b"%\xe2\xe3\xcf\xd3\n");
+        Self {
+            buffer,
+            offsets: Vec::new(),
+        }
+    }
+
+    fn object(&mut self, id: usize, body: &str) {
+        self.offsets.push((id, self.buffer.len()));
+        self.buffer
+            .extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", id, body).as_bytes());
+    }
+
+    /// 写出流对象，`dict` 为除 `/Length` 外的字典条目
+    fn stream(&mut self, id: usize, dict: &str, content: &str) {
+        self.offsets.push((id, self.buffer.len()));
+        self.buffer.extend_from_slice(
+            format!(
+                "{} 0 obj\n<< {}/Length {} >>\nstream\n",
+                id,
+                dict,
+                content.len()
+            )
+            .as_bytes(),
+        );
+        self.buffer.extend_from_slice(content.as_bytes());
+        self.buffer.extend_from_slice(b"\nendstream\nendobj\n");
+    }
+
+    /// 写出交叉引用表和文件尾（`size` 为最大对象编号 + 1）
+    fn finish(mut self, size: usize) -> Vec<u8> {
+        self.offsets.sort_unstable();
+        let xref = self.buffer.len();
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", size);
+        let mut offsets = self.offsets.iter().peekable();
+        for id in 1..size {
+            match offsets.next_if(|(obj, _)| *obj == id) {
+                Some((_, offset)) => {
+                    let _ = writeln!(table, "{:010} 00000 n ", offset);
+                }
+                None => table.push_str("0000000000 65535 f \n"),
+            }
+        }
+        let _ = write!(
+            table,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            size, xref
+        );
+        self.buffer.extend_from_slice(table.as_bytes());
+        self.buffer
+    }
+}
+
+/// 坐标/尺寸格式化（保留两位小数，去掉多余的零）
+fn num(value: f64) -> String {
+    let s = format!("{:.2}", value);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" {
+        "0".to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+/// 文本编码为 UCS-2（大端）十六进制串，BMP 之外的字符替换为 `?`
+fn hex_text(value: &str) -> String {
+    let mut hex = String::with_capacity(value.len() * 4 + 2);
+    hex.push('<');
+    for c in value.chars() {
+        let code = if (c as u32) <= 0xFFFF {
+            c as u32
+        } else {
+            '?' as u32
+        };
+        let _ = write!(hex, "{:04X}", code);
+    }
+    hex.push('>');
+    hex
+}
+
+/// 估算文本宽度（ASCII 半角，其余全角）
+fn text_width(value: &str, size: f64) -> f64 {
+    value
+        .chars()
+        .map(|c| if c.is_ascii() { 0.5 } else { 1.0 })
+        .sum::<f64>()
+        * size
+}
+
+/// 左下角为 (x, y) 的单行文本
+fn text(content: &mut String, x: f64, y: f64, size: f64, value: &str) {
+    let _ = writeln!(
+        content,
+        "BT /F1 {} Tf {} {} Td {} Tj ET",
+        num(size),
+        num(x),
+        num(y),
+        hex_text(value)
+    );
+}
+
+/// 按宽度截断文本（超出时以 `…` 结尾）
+fn truncate_text(value: &str, size: f64, max_width: f64) -> String {
+    if text_width(value, size) <= max_width {
+        return value.to_string();
+    }
+    let mut result = String::new();
+    let mut width = size;
+    for c in value.chars() {
+        width += if c.is_ascii() { 0.5 } else { 1.0 } * size;
+        if width > max_width {
+            break;
+        }
+        result.push(c);
+    }
+    result.push('…');
+    result
+}
+
+fn set_color(content: &mut String, color: [u8; 3], op: &str) {
+    let [r, g, b] = color.map(|c| num(c as f64 / 255.0));
+    let _ = writeln!(content, "{} {} {} {}", r, g, b, op);
+}
+
+fn format_value(value: Option<f64>) -> String {
+    match value {
+        None => "-".to_string(),
+        Some(v) if v != 0.0 && (v.abs() >= 1e7 || v.abs() < 1e-3) => format!("{:.3e}", v),
+        Some(v) => {
+            let s = format!("{:.3}", v);
+            s.trim_end_matches('0').trim_end_matches('.').to_string()
+        }
+    }
+}
+
+fn format_timestamp(timestamp_ms: f64) -> String {
+    Local
+        .timestamp_millis_opt(timestamp_ms as i64)
+        .single()
+        .map(|t| t.format("%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// 统计表（表头 + `rows` 范围内的系列）
+fn statistics_table(
+    content: &mut String,
+    top: f64,
+    series: &[ReportSeries],
+    rows: std::ops::Range<usize>,
+) {
+    const COLUMNS: [(&str, f64); 6] = [
+        ("系列", 0.0),
+        ("点数", 330.0),
+        ("最小值", 400.0),
+        ("最大值", 490.0),
+        ("平均值", 580.0),
+        ("最新值", 670.0),
+    ];
+    const FONT_SIZE: f64 = 10.0;
+    let right = PAGE_WIDTH - PAGE_MARGIN;
+
+    content.push_str("0 g\n");
+    let header_y = top - TABLE_ROW_HEIGHT + 5.0;
+    for (name, x) in COLUMNS {
+        text(content, PAGE_MARGIN + x, header_y, FONT_SIZE, name);
+    }
+    content.push_str("0.6 G 0.8 w\n");
+    let _ = writeln!(
+        content,
+        "{} {} m {} {} l S",
+        num(PAGE_MARGIN),
+        num(top - TABLE_ROW_HEIGHT),
+        num(right),
+        num(top - TABLE_ROW_HEIGHT)
+    );
+
+    if series.is_empty() {
+        text(
+            content,
+            PAGE_MARGIN + 16.0,
+            top - TABLE_ROW_HEIGHT * 2.0 + 5.0,
+            FONT_SIZE,
+            "图表没有标签",
+        );
+        return;
+    }
+
+    for (row, index) in rows.enumerate() {
+        let s = &series[index];
+        let stats = &s.stats;
+        let y = top - TABLE_ROW_HEIGHT * (row as f64 + 2.0) + 5.0;
+
+        set_color(content, SERIES_COLORS[index % SERIES_COLORS.len()], "rg");
+        let _ = writeln!(content, "{} {} 10 8 re f", num(PAGE_MARGIN), num(y));
+        content.push_str("0 g\n");
+        let label = truncate_text(&s.label, FONT_SIZE, COLUMNS[1].1 - 24.0);
+        text(content, PAGE_MARGIN + 16.0, y, FONT_SIZE, &label);
+        let cells = [
+            stats.count.to_string(),
+            format_value(stats.min),
+            format_value(stats.max),
+            format_value(stats.mean),
+            format_value(stats.last),
+        ];
+        for ((_, x), cell) in COLUMNS[1..].iter().zip(cells) {
+            text(content, PAGE_MARGIN + x, y, FONT_SIZE, &cell);
+        }
+    }
+}
+
+/// 图表内容（坐标原点为图表区域左下角）
+fn render_chart(series: &[ReportSeries], width: f64, height: f64) -> String {
+    let [pad_left, pad_right, pad_bottom, pad_top] = PLOT_PADDING;
+    let (x0, y0) = (pad_left, pad_bottom);
+    let (plot_w, plot_h) = (width - pad_left - pad_right, height - pad_bottom - pad_top);
+    let mut content = String::new();
+
+    let finite = || {
+        series
+            .iter()
+            .flat_map(|s| s.points.iter())
+            .filter(|p| p[0].is_finite() && p[1].is_finite())
+    };
+    let Some((t_min, t_max, v_min, v_max)) = finite().fold(None, |acc, p| {
+        let (t0, t1, v0, v1) = acc.unwrap_or((p[0], p[0], p[1], p[1]));
+        Some((t0.min(p[0]), t1.max(p[0]), v0.min(p[1]), v1.max(p[1])))
+    }) else {
+        content.push_str("0.6 G 0.8 w\n");
+        let _ = writeln!(
+            content,
+            "{} {} {} {} re S",
+            num(x0),
+            num(y0),
+            num(plot_w),
+            num(plot_h)
+        );
+        content.push_str("0.5 g\n");
+        text(
+            &mut content,
+            x0 + plot_w / 2.0 - 15.0,
+            y0 + plot_h / 2.0,
+            12.0,
+            "无数据",
+        );
+        return content;
+    };
+
+    let t_span = if t_max > t_min { t_max - t_min } else { 1.0 };
+    let (v_min, v_max) = if v_max > v_min {
+        let pad = (v_max - v_min) * 0.05;
+        (v_min - pad, v_max + pad)
+    } else {
+        (v_min - 1.0, v_max + 1.0)
+    };
+    let to_x = |t: f64| x0 + (t - t_min) / t_span * plot_w;
+    let to_y = |v: f64| y0 + (v - v_min) / (v_max - v_min) * plot_h;
+
+    // 网格与坐标轴标签
+    const TICKS: usize = 5;
+    const LABEL_SIZE: f64 = 8.0;
+    content.push_str("0.88 G 0.5 w\n");
+    for i in 0..=TICKS {
+        let y = y0 + plot_h * i as f64 / TICKS as f64;
+        let _ = writeln!(
+            content,
+            "{} {} m {} {} l S",
+            num(x0),
+            num(y),
+            num(x0 + plot_w),
+            num(y)
+        );
+    }
+    content.push_str("0.3 g\n");
+    for i in 0..=TICKS {
+        let ratio = i as f64 / TICKS as f64;
+        let label = format_value(Some(v_min + (v_max - v_min) * ratio));
+        let y = y0 + plot_h * ratio;
+        let x = x0 - 4.0 - text_width(&label, LABEL_SIZE);
+        text(&mut content, x, y - 3.0, LABEL_SIZE, &label);
+
+        let label = format_timestamp(t_min + t_span * ratio);
+        let x = (x0 + plot_w * ratio - text_width(&label, LABEL_SIZE) / 2.0)
+            .clamp(0.0, width - text_width(&label, LABEL_SIZE));
+        text(&mut content, x, y0 - 14.0, LABEL_SIZE, &label);
+    }
+    content.push_str("0.6 G 0.8 w\n");
+    let _ = writeln!(
+        content,
+        "{} {} {} {} re S",
+        num(x0),
+        num(y0),
+        num(plot_w),
+        num(plot_h)
+    );
+
+    // 系列折线（裁剪到绘图区；点数过多时按像素列保留最小/最大值）
+    let _ = writeln!(
+        content,
+        "q {} {} {} {} re W n 1 w 1 J 1 j",
+        num(x0),
+        num(y0),
+        num(plot_w),
+        num(plot_h)
+    );
+    for (index, s) in series.iter().enumerate() {
+        set_color(
+            &mut content,
+            SERIES_COLORS[index % SERIES_COLORS.len()],
+            "RG",
+        );
+        let path = polyline(
+            &s.points,
+            &to_x,
+            &to_y,
+            s.points.len() > plot_w as usize * 2,
+        );
+        if !path.is_empty() {
+            content.push_str(&path);
+            content.push_str("S\n");
+        }
+    }
+    content.push_str("Q\n");
+    content
+}
+
+/// 折线路径：非有限值处断开；`decimate` 时每个像素列只保留最小/最大值点（按时间顺序）
+fn polyline(
+    points: &[[f64; 2]],
+    to_x: &impl Fn(f64) -> f64,
+    to_y: &impl Fn(f64) -> f64,
+    decimate: bool,
+) -> String {
+    type Point = (usize, f64, f64);
+    fn emit(path: &mut String, pen_down: &mut bool, x: f64, y: f64) {
+        let op = if *pen_down { "l" } else { "m" };
+        let _ = writeln!(path, "{} {} {}", num(x), num(y), op);
+        *pen_down = true;
+    }
+    fn flush(path: &mut String, pen_down: &mut bool, bucket: &mut Option<(i64, Point, Point)>) {
+        if let Some((_, low, high)) = bucket.take() {
+            let (a, b) = if low.0 <= high.0 {
+                (low, high)
+            } else {
+                (high, low)
+            };
+            emit(path, pen_down, a.1, a.2);
+            if b.0 != a.0 {
+                emit(path, pen_down, b.1, b.2);
+            }
+        }
+    }
+
+    let mut path = String::new();
+    let mut pen_down = false;
+    // 当前像素列：(列号, 最小值点, 最大值点)，点为 (序号, x, y)
+    let mut bucket: Option<(i64, Point, Point)> = None;
+    for (i, p) in points.iter().enumerate() {
+        if !(p[0].is_finite() && p[1].is_finite()) {
+            flush(&mut path, &mut pen_down, &mut bucket);
+            pen_down = false;
+            continue;
+        }
+        let (x, y) = (to_x(p[0]), to_y(p[1]));
+        if !decimate {
+            emit(&mut path, &mut pen_down, x, y);
+            continue;
+        }
+        let column = x.floor() as i64;
+        match &mut bucket {
+            Some((col, low, high)) if *col == column => {
+                if y < low.2 {
+                    *low = (i, x, y);
+                }
+                if y > high.2 {
+                    *high = (i, x, y);
+                }
+            }
+            _ => {
+                flush(&mut path, &mut pen_down, &mut bucket);
+                bucket = Some((column, (i, x, y), (i, x, y)));
+            }
+        }
+    }
+    flush(&mut path, &mut pen_down, &mut bucket);
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 按交叉引用表校验 PDF 结构，返回 (页数, Form XObject 数)
+    fn verify_structure(bytes: &[u8]) -> (usize, usize) {
+        assert!(bytes.starts_with(b"%PDF-1.4\n"));
+        let text = String::from_utf8_lossy(bytes);
+        assert!(text.ends_with("%%EOF\n"));
+
+        let startxref: usize = text
+            .rsplit("startxref\n")
+            .next()
+            .and_then(|tail| tail.lines().next())
+            .and_then(|line| line.parse().ok())
+            .unwrap();
+        assert!(bytes[startxref..].starts_with(b"xref\n"));
+        let xref = String::from_utf8_lossy(&bytes[startxref..]);
+        let mut lines = xref.lines().skip(1);
+        let size: usize = lines
+            .next()
+            .and_then(|l| l.split(' ').nth(1))
+            .and_then(|n| n.parse().ok())
+            .unwrap();
+        for (id, entry) in lines.take(size).enumerate().skip(1) {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(entry.ends_with(" n "), "对象 {} 未写出", id);
+            assert!(
+                bytes[offset..].starts_with(format!("{} 0 obj\n", id).as_bytes()),
+                "对象 {} 的偏移无效",
+                id
+            );
+        }
+
+        let pages = text.matches("/Type /Page ").count();
+        assert!(text.contains(&format!("/Count {} ", pages)));
+        (pages, text.matches("/Subtype /Form").count())
+    }
+
+    fn series(label: &str, offset: f64, count: usize) -> ReportSeries {
+        let points: Vec<[f64; 2]> = (0..count)
+            .map(|i| {
+                let value = if i == 5 { f64::NAN } else { offset + i as f64 };
+                [1_704_067_200_000.0 + i as f64 * 1000.0, value]
+            })
+            .collect();
+        ReportSeries {
+            label: label.to_string(),
+            stats: SeriesStatistics::compute(&points),
+            points,
+        }
+    }
+
+    #[test]
+    fn test_report_has_one_page_and_chart_per_chart() {
+        let charts = vec![
+            ReportChart {
+                name: "温度".to_string(),
+                series: vec![
+                    series("入口温度 (TT_101)", 20.0, 50),
+                    series("TT_102", 30.0, 5000),
+                ],
+            },
+            ReportChart {
+                name: "压力".to_string(),
+                series: vec![series("PT_201", 1.0, 0)],
+            },
+            ReportChart {
+                name: "空图表".to_string(),
+                series: Vec::new(),
+            },
+        ];
+        let (bytes, page_count) =
+            build_report_pdf("一号线", "2024-01-01", "2024-01-02", &charts).unwrap();
+        assert_eq!(page_count, 3);
+        assert_eq!(verify_structure(&bytes), (3, 3));
+        // 中文按 UCS-2 编码（"温度" = 6E29 5EA6）
+        assert!(String::from_utf8_lossy(&bytes).contains("6E295EA6"));
+
+        // 统计表过长时续页，图表数不变
+        let many = vec![ReportChart {
+            name: "多统计".to_string(),
+            series: (0..20)
+                .map(|i| series(&format!("TT_{}", i), 0.0, 10))
+                .collect(),
+        }];
+        let (bytes, page_count) = build_report_pdf("一号线", "s", "e", &many).unwrap();
+        assert_eq!(page_count, 2);
+        assert_eq!(verify_structure(&bytes), (2, 1));
+
+        assert!(build_report_pdf("一号线", "s", "e", &[]).is_err());
+
+        let path = std::env::temp_dir().join("industry_vis_test_report.pdf");
+        let result = write_report_pdf(&path, "一号线", "s", "e", &charts).unwrap();
+        assert_eq!(result.chart_count, 3);
+        assert_eq!(result.series_count, 3);
+        assert!(result.file_size > 0);
+        assert_eq!(fs::metadata(&path).unwrap().len(), result.file_size);
+        let _ = fs::remove_file(&path);
+
+        let stats = &charts[0].series[0].stats;
+        assert_eq!(stats.count, 49);
+        assert_eq!(stats.min, Some(20.0));
+        assert_eq!(stats.last, Some(69.0));
+    }
+
+    #[test]
+    fn test_statistics_use_full_series() {
+        use crate::models::ChartConfig;
+
+        let chart = ChartConfig::new("温度".to_string()).with_tags(vec!["TT_101".to_string()]);
+        let group = TagGroup::new("一号线".to_string(), vec![chart]).unwrap();
+        // 绘图用降采样结果丢失了峰值
+        let drawn = vec![ChartSeriesData::new(
+            "TT_101".to_string(),
+            vec![[0.0, 1.0], [2000.0, 1.0]],
+        )];
+        let full = vec![ChartSeriesData::new(
+            "TT_101".to_string(),
+            vec![[0.0, 1.0], [1000.0, 100.0], [2000.0, 1.0]],
+        )];
+
+        let charts = build_report_charts(&group, &drawn, &full);
+        let series = &charts[0].series[0];
+        assert_eq!(series.points.len(), 2);
+        assert_eq!(series.stats.count, 3);
+        assert_eq!(series.stats.max, Some(100.0));
+        assert_eq!(series.stats.mean, Some(34.0));
+    }
+}
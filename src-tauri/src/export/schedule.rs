@@ -0,0 +1,482 @@
+//! 定时导出计划
+//!
+//! 计划格式：
+//!
+//! - 固定间隔：`30s`、`15m`、`2h`、`1d`
+//! - cron（5 段：分 时 日 月 周）：每段支持 `*`、数字、`a-b`、`*/n`、`a-b/n` 及逗号列表，
+//!   周日为 0（7 也视为周日）；日和周都有限定时满足其一即可（与标准 cron 一致）
+//!
+//! 计划定义保存到配置目录的 JSON，应用启动时恢复。
+
+use chrono::{Datelike, Duration, NaiveDateTime, Timelike};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+use super::bundle::BundleFormat;
+use super::csv::CsvEncoding;
+use super::file_name::{FileNameContext, render_file_name_template};
+use crate::config::AppConfig;
+use crate::error::{AppError, AppResult};
+use crate::models::{DataProcessingConfig, QueryParams};
+
+/// 计划文件名
+const SCHEDULES_FILENAME: &str = "scheduled_exports.json";
+
+/// 查询时间格式
+const QUERY_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// cron 查找下一次执行时刻的最大范围（天）
+const MAX_CRON_LOOKAHEAD_DAYS: i64 = 366 * 4;
+
+/// 固定间隔的上限（秒，366 天）
+const MAX_INTERVAL_SECS: u64 = 366 * 86400;
+
+/// 导出计划
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportSchedule {
+    /// 固定间隔（秒）
+    Interval(u64),
+    Cron(CronSchedule),
+}
+
+impl ExportSchedule {
+    /// 解析计划（固定间隔或 5 段 cron）
+    pub fn parse(spec: &str) -> AppResult<Self> {
+        let spec = spec.trim();
+        if spec.split_whitespace().count() == 5 {
+            return CronSchedule::parse(spec).map(Self::Cron);
+        }
+
+        let invalid = || {
+            AppError::Validation(format!(
+                "无效的导出计划: '{}'，应为间隔（如 30s / 15m / 2h / 1d）或 5 段 cron",
+                spec
+            ))
+        };
+        // 按字符取单位，非 ASCII 结尾（如 "15分"）按未知单位处理
+        let (split, unit) = spec.char_indices().last().ok_or_else(invalid)?;
+        let unit_secs = match unit {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86400,
+            _ => return Err(invalid()),
+        };
+        let amount: u64 = spec[..split].trim().parse().map_err(|_| invalid())?;
+        if amount == 0 {
+            return Err(AppError::Validation("导出间隔必须大于 0".to_string()));
+        }
+        let secs = amount.saturating_mul(unit_secs);
+        if secs > MAX_INTERVAL_SECS {
+            return Err(AppError::Validation(format!(
+                "导出间隔不能超过 {} 天: '{}'",
+                MAX_INTERVAL_SECS / 86400,
+                spec
+            )));
+        }
+        Ok(Self::Interval(secs))
+    }
+
+    /// 严格晚于 `after` 的下一次执行时刻（cron 在查找范围内无匹配或时刻溢出时为 `None`）
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        match self {
+            Self::Interval(secs) => {
+                let secs = i64::try_from(*secs).ok()?;
+                after.checked_add_signed(Duration::try_seconds(secs)?)
+            }
+            Self::Cron(cron) => cron.next_after(after),
+        }
+    }
+}
+
+/// 5 段 cron 计划（各段为允许取值的位集）
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// 日 / 周是否为 `*`
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl CronSchedule {
+    /// 解析 `分 时 日 月 周`
+    pub fn parse(spec: &str) -> AppResult<Self> {
+        let fields: Vec<&str> = spec.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(AppError::Validation(format!(
+                "cron 需要 5 段（分 时 日 月 周）: '{}'",
+                spec
+            )));
+        };
+
+        let mut weekdays = parse_cron_field(weekday, 0, 7)?;
+        // 7 与 0 都表示周日
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            minutes: parse_cron_field(minute, 0, 59)?,
+            hours: parse_cron_field(hour, 0, 23)?,
+            days: parse_cron_field(day, 1, 31)?,
+            months: parse_cron_field(month, 1, 12)?,
+            weekdays,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+
+    fn matches_date(&self, time: NaiveDateTime) -> bool {
+        let day = self.days & (1 << time.day()) != 0;
+        let weekday = self.weekdays & (1 << time.weekday().num_days_from_sunday()) != 0;
+        let day_matches = match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (false, true) => day,
+            (true, false) => weekday,
+            (false, false) => day || weekday,
+        };
+        self.months & (1 << time.month()) != 0 && day_matches
+    }
+
+    /// 严格晚于 `after` 的下一个匹配分钟
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut time = after
+            .with_second(0)?
+            .with_nanosecond(0)?
+            .checked_add_signed(Duration::minutes(1))?;
+        let limit = after
+            .checked_add_signed(Duration::days(MAX_CRON_LOOKAHEAD_DAYS))
+            .unwrap_or(NaiveDateTime::MAX);
+
+        while time <= limit {
+            if !self.matches_date(time) {
+                time = time.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if self.hours & (1 << time.hour()) == 0 {
+                time = time.with_minute(0)? + Duration::hours(1);
+            } else if self.minutes & (1 << time.minute()) == 0 {
+                time += Duration::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+}
+
+/// 解析 cron 单段为位集（第 n 位表示取值 n）
+fn parse_cron_field(field: &str, min: u32, max: u32) -> AppResult<u64> {
+    let invalid = || {
+        AppError::Validation(format!(
+            "无效的 cron 字段 '{}'（取值范围 {}-{}）",
+            field, min, max
+        ))
+    };
+
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().map_err(|_| invalid())?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(invalid());
+        }
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((a, b)) => (
+                    a.parse().map_err(|_| invalid())?,
+                    b.parse().map_err(|_| invalid())?,
+                ),
+                None => {
+                    let value: u32 = range.parse().map_err(|_| invalid())?;
+                    // `n/step` 表示从 n 到最大值
+                    (value, if part.contains('/') { max } else { value })
+                }
+            },
+        };
+        if start < min || end > max || start > end {
+            return Err(invalid());
+        }
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+/// 定时导出任务定义
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledExport {
+    /// 任务 ID
+    pub id: String,
+    /// 计划（间隔或 cron）
+    pub schedule: String,
+    /// 查询参数；每次执行时时间范围平移到以执行时刻为终点，保持原时长
+    pub params: QueryParams,
+    #[serde(default)]
+    pub processing_config: Option<DataProcessingConfig>,
+    /// 输出路径模板（支持 `{date}`、`{time}`、`{tags}` 占位符）
+    pub output_template: String,
+    /// 导出格式（csv / parquet / arrow）
+    pub format: String,
+    /// CSV 编码（utf8 / gbk）
+    #[serde(default)]
+    pub encoding: Option<String>,
+    /// 创建时间
+    pub created_at: String,
+}
+
+impl ScheduledExport {
+    /// 校验任务定义，返回解析后的计划
+    pub fn validate(&self) -> AppResult<ExportSchedule> {
+        let schedule = ExportSchedule::parse(&self.schedule)?;
+        BundleFormat::parse(&self.format)?;
+        CsvEncoding::parse(self.encoding.as_deref())?;
+        if let Some(config) = &self.processing_config {
+            config.validate()?;
+        }
+        if self.output_template.trim().is_empty() {
+            return Err(AppError::Validation("导出路径不能为空".to_string()));
+        }
+        if self.output_template.contains("{group}") {
+            return Err(AppError::Validation(
+                "定时导出不支持 {group} 占位符".to_string(),
+            ));
+        }
+        self.time_span()?;
+        Ok(schedule)
+    }
+
+    /// 查询时间范围的时长
+    fn time_span(&self) -> AppResult<Duration> {
+        let parse = |value: &str| {
+            NaiveDateTime::parse_from_str(value.trim(), "%Y-%m-%dT%H:%M:%S%.f")
+                .or_else(|_| NaiveDateTime::parse_from_str(value.trim(), "%Y-%m-%d %H:%M:%S%.f"))
+                .map_err(|_| AppError::Validation(format!("无法解析查询时间: {}", value)))
+        };
+        let span = parse(&self.params.end_time)? - parse(&self.params.start_time)?;
+        if span <= Duration::zero() {
+            return Err(AppError::Validation(
+                "查询结束时间必须晚于开始时间".to_string(),
+            ));
+        }
+        Ok(span)
+    }
+
+    /// 在 `now` 执行时的查询参数（时间范围以 `now` 为终点，忽略分页）
+    pub fn params_at(&self, now: NaiveDateTime) -> AppResult<QueryParams> {
+        let span = self.time_span()?;
+        Ok(QueryParams {
+            start_time: (now - span).format(QUERY_TIME_FORMAT).to_string(),
+            end_time: now.format(QUERY_TIME_FORMAT).to_string(),
+            limit: None,
+            offset: None,
+            chunk_size: None,
+            ..self.params.clone()
+        })
+    }
+
+    /// 在 `now` 执行时的输出路径
+    pub fn output_path_at(&self, now: NaiveDateTime) -> AppResult<String> {
+        render_file_name_template(
+            &self.output_template,
+            &FileNameContext {
+                group: None,
+                tag_count: self.params.tags.as_ref().map_or(0, Vec::len),
+                now,
+            },
+        )
+    }
+}
+
+/// 定时导出任务定义存储
+#[derive(Debug)]
+pub struct ScheduledExportStore {
+    exports: Vec<ScheduledExport>,
+    path: PathBuf,
+}
+
+impl ScheduledExportStore {
+    /// 计划文件路径（与 config.toml 同目录）
+    pub fn schedules_path() -> AppResult<PathBuf> {
+        let config_path = AppConfig::config_path()?;
+        let dir = config_path
+            .parent()
+            .ok_or_else(|| AppError::Config("无法确定配置目录".to_string()))?;
+        Ok(dir.join(SCHEDULES_FILENAME))
+    }
+
+    /// 从默认位置加载
+    pub fn load() -> AppResult<Self> {
+        Self::load_from(&Self::schedules_path()?)
+    }
+
+    /// 从指定路径加载，文件不存在时返回空存储
+    pub fn load_from(path: &Path) -> AppResult<Self> {
+        let exports = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(path)?)?
+        } else {
+            Vec::new()
+        };
+        debug!(target: "industry_vis::export", "加载定时导出任务: {} 个", exports.len());
+
+        Ok(Self {
+            exports,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// 保存到文件
+    pub fn save(&self) -> AppResult<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(&self.exports)?)?;
+        Ok(())
+    }
+
+    /// 全部任务定义
+    pub fn list(&self) -> &[ScheduledExport] {
+        &self.exports
+    }
+
+    /// 添加任务定义并保存
+    pub fn add(&mut self, export: ScheduledExport) -> AppResult<()> {
+        self.exports.push(export);
+        self.save()
+    }
+
+    /// 移除任务定义并保存，不存在时返回 false
+    pub fn remove(&mut self, id: &str) -> AppResult<bool> {
+        let before = self.exports.len();
+        self.exports.retain(|e| e.id != id);
+        if self.exports.len() == before {
+            return Ok(false);
+        }
+        self.save()?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_parse_interval_and_cron_next_run() {
+        assert_eq!(
+            ExportSchedule::parse("15m").unwrap(),
+            ExportSchedule::Interval(900)
+        );
+        assert_eq!(
+            ExportSchedule::parse("2h")
+                .unwrap()
+                .next_after(time("2024-03-05T08:00:00")),
+            Some(time("2024-03-05T10:00:00"))
+        );
+        for bad in ["", "0s", "10x", "* * *", "60 * * * *", "*/0 * * * *"] {
+            assert!(ExportSchedule::parse(bad).is_err(), "{}", bad);
+        }
+
+        // 超长间隔在解析时拒绝，构造出的超大间隔计算下一次时刻不会溢出 panic
+        assert!(ExportSchedule::parse("366d").is_ok());
+        for oversized in ["367d", "100000000d", "10000000000000000s"] {
+            assert!(
+                matches!(
+                    ExportSchedule::parse(oversized),
+                    Err(AppError::Validation(_))
+                ),
+                "应拒绝: {}",
+                oversized
+            );
+        }
+        assert_eq!(
+            ExportSchedule::Interval(u64::MAX).next_after(time("2024-03-05T08:00:00")),
+            None
+        );
+        assert_eq!(
+            ExportSchedule::Interval(60).next_after(NaiveDateTime::MAX),
+            None
+        );
+
+        // 每天 08:30
+        // 多字节结尾不会按字节切分而 panic
+        for spec in ["15分", "1天", "分", "5 ", "m"] {
+            assert!(
+                matches!(ExportSchedule::parse(spec), Err(AppError::Validation(_))),
+                "应拒绝: {}",
+                spec
+            );
+        }
+
+        let daily = ExportSchedule::parse("30 8 * * *").unwrap();
+        assert_eq!(
+            daily.next_after(time("2024-03-05T08:29:59")),
+            Some(time("2024-03-05T08:30:00"))
+        );
+        assert_eq!(
+            daily.next_after(time("2024-03-05T08:30:00")),
+            Some(time("2024-03-06T08:30:00"))
+        );
+
+        // 工作日每 15 分钟（2024-03-09 为周六）
+        let workdays = ExportSchedule::parse("*/15 9-17 * * 1-5").unwrap();
+        assert_eq!(
+            workdays.next_after(time("2024-03-08T17:50:00")),
+            Some(time("2024-03-11T09:00:00"))
+        );
+        // 周日写作 7
+        let sunday = ExportSchedule::parse("0 0 * * 7").unwrap();
+        assert_eq!(
+            sunday.next_after(time("2024-03-05T00:00:00")),
+            Some(time("2024-03-10T00:00:00"))
+        );
+    }
+
+    #[test]
+    fn test_params_shift_to_run_time() {
+        let export = ScheduledExport {
+            id: "s1".to_string(),
+            schedule: "1d".to_string(),
+            params: QueryParams::new(
+                "2024-01-01T00:00:00".to_string(),
+                "2024-01-02T00:00:00".to_string(),
+            )
+            .with_tags(vec!["T1".to_string(), "T2".to_string()])
+            .with_pagination(0, 100),
+            processing_config: None,
+            output_template: "D:/reports/daily_{date}_{tags}.csv".to_string(),
+            format: "csv".to_string(),
+            encoding: None,
+            created_at: "2024-01-01T00:00:00".to_string(),
+        };
+        assert!(export.validate().is_ok());
+
+        let now = time("2024-03-05T06:00:00");
+        let params = export.params_at(now).unwrap();
+        assert_eq!(params.start_time, "2024-03-04T06:00:00");
+        assert_eq!(params.end_time, "2024-03-05T06:00:00");
+        assert_eq!(params.limit, None);
+        assert_eq!(
+            export.output_path_at(now).unwrap(),
+            "D:/reports/daily_20240305_2.csv"
+        );
+
+        let invalid = ScheduledExport {
+            output_template: "{group}.csv".to_string(),
+            ..export
+        };
+        assert!(invalid.validate().is_err());
+    }
+}
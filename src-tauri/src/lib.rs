@@ -8,6 +8,7 @@
 //! - `config` - 配置管理（支持热更新）
 //! - `datasource` - 数据源访问（bb8 连接池）
 //! - `error` - 错误类型
+//! - `export` - 数据导出
 //! - `logging` - 日志系统
 //! - `models` - 数据模型
 //! - `processing` - 数据处理
@@ -19,6 +20,7 @@ pub mod commands;
 pub mod config;
 pub mod datasource;
 pub mod error;
+pub mod export;
 pub mod logging;
 pub mod models;
 pub mod processing;
@@ -27,12 +29,12 @@ pub mod state;
 
 use commands::*;
 use once_cell::sync::OnceCell;
-use state::AppState;
+use state::{AppState, AppStateManager, start_background_tasks};
 use std::io::{Read, Write};
 use std::net::{TcpStream, ToSocketAddrs};
 use std::sync::Arc;
 use std::time::Duration;
-use tauri::{AppHandle, Manager, RunEvent, Url, async_runtime};
+use tauri::{AppHandle, Emitter, Manager, RunEvent, Url, WindowEvent, async_runtime};
 use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
 use tokio::sync::RwLock;
 use tracing::info;
@@ -115,7 +117,11 @@ pub fn run() {
     // 使用 tokio 运行时初始化异步状态
     let app_state = async_runtime::block_on(async {
         match AppState::new().await {
-            Ok(state) => Arc::new(RwLock::new(state)),
+            Ok(state) => {
+                let state = Arc::new(RwLock::new(state));
+                start_background_tasks(&state);
+                state
+            }
             Err(e) => {
                 tracing::error!(target: "industry_vis::lib", "初始化应用状态失败: {}", e);
                 panic!("无法初始化应用状态: {}", e);
@@ -126,6 +132,10 @@ pub fn run() {
     // 克隆一份状态用于后台初始化连接池
     let app_state_for_pool = app_state.clone();
 
+    // 配置热重载事件（watcher 构造时尚无 AppHandle，在 setup 中转发）
+    let config_change_events =
+        async_runtime::block_on(async { app_state.read().await.config().take_change_events() });
+
     let app = tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
@@ -133,6 +143,18 @@ pub fn run() {
             // Store global AppHandle for event emission
             let _ = APP_HANDLE.set(app.handle().clone());
 
+            // 将配置热重载结果转发为前端事件
+            if let Some(mut events) = config_change_events {
+                let handle = app.handle().clone();
+                async_runtime::spawn(async move {
+                    while let Some(event) = events.recv().await {
+                        if let Err(e) = handle.emit("config-changed", event) {
+                            tracing::warn!(target: "industry_vis::lib", "发送配置变更事件失败: {}", e);
+                        }
+                    }
+                });
+            }
+
             // 开发环境检查 devUrl 端口占用情况并提示
             if cfg!(debug_assertions)
                 && let Some(dev_url) = app.config().build.dev_url.clone()
@@ -152,7 +174,7 @@ pub fn run() {
             }
             Ok(())
         })
-        .manage(app_state)
+        .manage(AppStateManager::new(app_state))
         .invoke_handler(tauri::generate_handler![
             // 配置相关
             load_config,
@@ -160,21 +182,77 @@ pub fn run() {
             test_connection,
             get_connection_status,
             get_pool_state,
+            suggest_pool_size,
+            get_last_pool_error,
+            get_circuit_breaker_status,
+            get_connection_health,
+            export_metrics,
+            open_window_state,
+            close_window_state,
             // 数据查询
             get_available_tags,
             search_tags,
+            get_tag_tree,
+            discover_tag_table,
+            save_tag_table,
             query_history,
             query_history_v2,
+            query_history_v2_compressed,
+            fetch_series_chunk,
+            subscribe_realtime,
+            unsubscribe_realtime,
+            preview_processing,
+            get_api_schema,
+            get_value_at,
+            estimate_query,
+            detect_sampling_interval,
+            compute_difference,
+            extract_extrema,
+            split_by_events,
+            run_benchmark,
+            verify_processing_consistency,
+            run_custom_query,
             export_to_csv,
+            export_to_parquet,
+            export_to_arrow,
+            export_comparison,
+            start_export,
+            get_export_task,
+            cancel_export,
+            schedule_export,
+            list_scheduled_exports,
+            remove_scheduled_export,
+            list_export_history,
+            repeat_export,
             // 缓存管理
             clear_cache,
             get_cache_stats,
+            suggest_cache_config,
+            set_cache_events,
             warmup_group,
             // 标签分组
             list_tag_groups,
             create_tag_group,
             update_tag_group,
+            patch_tag_group,
+            compute_alarm_stats,
+            export_group_data,
+            export_group_dashboard,
+            export_report_pdf,
+            import_group_dashboard,
+            import_groups,
+            audit_all_groups,
+            apply_processing_to_groups,
+            set_tag_group_locked,
+            set_tag_metadata,
+            get_group_usage_stats,
+            tag_usage_across_groups,
+            add_annotation,
+            update_annotation,
+            remove_annotation,
             delete_tag_group,
+            list_group_snapshots,
+            restore_group_snapshot,
         ])
         .on_window_event(|window, event| {
             if let tauri::WindowEvent::CloseRequested { .. } = event {
@@ -199,9 +277,29 @@ pub fn run() {
         }
     });
 
-    app.run(|_app_handle, event| {
-        if let RunEvent::Exit = event {
+    app.run(|app_handle, event| match event {
+        // 窗口关闭时释放其独立状态
+        RunEvent::WindowEvent {
+            label,
+            event: WindowEvent::Destroyed,
+            ..
+        } => {
+            app_handle.state::<AppStateManager>().remove(&label);
+        }
+        RunEvent::Exit => {
             info!(target: "industry_vis::lib", "应用正在退出，清理资源...");
+            // 仅主实例持久化缓存（独立实例连接的是其他数据库）
+            let state = app_handle.state::<AppStateManager>().main();
+            async_runtime::block_on(async {
+                let state = state.read().await;
+                if let Err(e) = state.persist_cache().await {
+                    tracing::warn!(target: "industry_vis::lib", "持久化缓存失败: {}", e);
+                }
+                if let Err(e) = state.tag_group_service().flush_usage() {
+                    tracing::warn!(target: "industry_vis::lib", "保存分组使用统计失败: {}", e);
+                }
+            });
         }
+        _ => {}
     });
 }
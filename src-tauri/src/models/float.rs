@@ -0,0 +1,61 @@
+//! 非有限浮点数的序列化
+//!
+//! JSON 不支持 NaN / Infinity。数值统一在序列化时输出为 `null`，
+//! 反序列化时 `null` 还原为 NaN（缺失值），保证前后端往返不出错。
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// 有限值原样输出，NaN / ±Inf 输出为 `null`
+fn finite_or_none(value: f64) -> Option<f64> {
+    value.is_finite().then_some(value)
+}
+
+/// 单个数值：`#[serde(with = "nullable_f64")]`
+pub mod nullable_f64 {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        finite_or_none(*value).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        Ok(Option::<f64>::deserialize(deserializer)?.unwrap_or(f64::NAN))
+    }
+}
+
+/// 数值列 `[v, ...]`：`#[serde(with = "nullable_values")]`
+pub mod nullable_values {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(values: &[f64], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(values.iter().map(|v| finite_or_none(*v)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<f64>, D::Error> {
+        let values = Vec::<Option<f64>>::deserialize(deserializer)?;
+        Ok(values.into_iter().map(|v| v.unwrap_or(f64::NAN)).collect())
+    }
+}
+
+/// 系列数据点 `[[timestamp_ms, value], ...]`：`#[serde(with = "nullable_points")]`
+pub mod nullable_points {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(points: &[[f64; 2]], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            points
+                .iter()
+                .map(|p| [finite_or_none(p[0]), finite_or_none(p[1])]),
+        )
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<[f64; 2]>, D::Error> {
+        let points = Vec::<[Option<f64>; 2]>::deserialize(deserializer)?;
+        Ok(points
+            .into_iter()
+            .map(|[t, v]| [t.unwrap_or(f64::NAN), v.unwrap_or(f64::NAN)])
+            .collect())
+    }
+}
@@ -1,15 +1,25 @@
 //! 历史记录数据模型
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use super::float::nullable_f64;
+use super::interned::{InternedStr, Interner};
 
 /// 历史表记录
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+///
+/// 标签名和质量码为驻留字符串，大结果集中相同标签的记录共享同一份字符串。
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct HistoryRecord {
     pub date_time: String,
-    pub tag_name: String,
+    pub tag_name: InternedStr,
+    /// 数值（NaN / Inf 序列化为 `null`）
+    #[serde(with = "nullable_f64")]
+    #[schemars(with = "Option<f64>")]
     pub tag_val: f64,
-    pub tag_quality: String,
+    pub tag_quality: InternedStr,
 }
 
 impl HistoryRecord {
@@ -17,16 +27,159 @@ impl HistoryRecord {
     pub fn new(date_time: String, tag_name: String, tag_val: f64, tag_quality: String) -> Self {
         Self {
             date_time,
-            tag_name,
+            tag_name: tag_name.into(),
             tag_val,
-            tag_quality,
+            tag_quality: tag_quality.into(),
         }
     }
+
+    /// 使用批量驻留器创建记录（逐行映射查询结果时使用，避免每行访问全局驻留池）
+    pub fn interned(
+        date_time: String,
+        tag_name: &str,
+        tag_val: f64,
+        tag_quality: &str,
+        interner: &mut Interner,
+    ) -> Self {
+        Self {
+            date_time,
+            tag_name: interner.intern(tag_name),
+            tag_val,
+            tag_quality: interner.intern(tag_quality),
+        }
+    }
+
+    /// 测试用：质量为 `Good` 的记录
+    #[cfg(test)]
+    pub(crate) fn good(date_time: &str, tag_name: &str, tag_val: f64) -> Self {
+        Self::new(
+            date_time.to_string(),
+            tag_name.to_string(),
+            tag_val,
+            "Good".to_string(),
+        )
+    }
+
+    /// 估算该记录占用的内存（字节）
+    ///
+    /// 结构体本身加上各字符串的内容长度。驻留字符串实际由多条记录共享，
+    /// 单条记录无法判断，按独占计入（偏保守）；批量估算见 `estimated_batch_size`。
+    pub fn estimated_size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.date_time.len()
+            + self.tag_name.len()
+            + self.tag_quality.len()
+    }
+
+    /// 估算一批记录占用的内存（字节），共享的驻留字符串只计一次
+    pub fn estimated_batch_size(records: &[HistoryRecord]) -> usize {
+        let mut shared: HashSet<*const u8> = HashSet::new();
+        records
+            .iter()
+            .map(|r| {
+                let strings: usize = [&r.tag_name, &r.tag_quality]
+                    .into_iter()
+                    .filter(|s| shared.insert(s.as_ptr()))
+                    .map(|s| s.len())
+                    .sum();
+                std::mem::size_of::<Self>() + r.date_time.len() + strings
+            })
+            .sum()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    /// 测试用分配器：按线程统计堆分配次数与字节数（并行测试互不干扰）
+    struct CountingAlloc;
+
+    thread_local! {
+        static ALLOCATED: Cell<(usize, usize)> = const { Cell::new((0, 0)) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAlloc {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATED.try_with(|c| {
+                let (count, bytes) = c.get();
+                c.set((count + 1, bytes + layout.size()));
+            });
+            unsafe { System.alloc(layout) }
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            unsafe { System.dealloc(ptr, layout) }
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: CountingAlloc = CountingAlloc;
+
+    /// 执行 `f`，返回结果及期间本线程的（分配次数, 分配字节数）
+    fn measure_allocations<T>(f: impl FnOnce() -> T) -> (T, usize, usize) {
+        let (count_before, bytes_before) = ALLOCATED.with(Cell::get);
+        let result = f();
+        let (count_after, bytes_after) = ALLOCATED.with(Cell::get);
+        (
+            result,
+            count_after - count_before,
+            bytes_after - bytes_before,
+        )
+    }
+
+    #[test]
+    fn test_interned_mapping_allocates_per_distinct_value() {
+        // 50 个标签 × 2000 个点，时间字符串预先分配（两种方式都需要）
+        let rows: Vec<(String, String)> = (0..2000)
+            .flat_map(|i| {
+                (0..50).map(move |t| {
+                    (
+                        format!("2024-01-01T00:{:02}:{:02}.000", i / 60 % 60, i % 60),
+                        format!("Boiler1.Drum.Pressure_{:02}", t),
+                    )
+                })
+            })
+            .collect();
+        let times = || {
+            rows.iter()
+                .map(|(time, _)| time.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let owned_times = times();
+        let (owned, _, owned_bytes) = measure_allocations(|| {
+            let mut out = Vec::with_capacity(rows.len());
+            for (time, (_, tag)) in owned_times.into_iter().zip(&rows) {
+                out.push((time, tag.to_string(), 1.0f64, "Good".to_string()));
+            }
+            out
+        });
+
+        let interned_times = times();
+        let (records, count, bytes) = measure_allocations(|| {
+            let mut interner = Interner::new();
+            let mut out = Vec::with_capacity(rows.len());
+            for (time, (_, tag)) in interned_times.into_iter().zip(&rows) {
+                out.push(HistoryRecord::interned(
+                    time,
+                    tag,
+                    1.0,
+                    "Good",
+                    &mut interner,
+                ));
+            }
+            out
+        });
+
+        assert_eq!(records.len(), owned.len());
+        // 不随行数增长：只有结果 Vec 与每个不同值的少量分配
+        assert!(count < 500, "分配次数 {}", count);
+        assert!(bytes * 10 < owned_bytes * 7, "{} vs {}", bytes, owned_bytes);
+        assert!(records[0].tag_name.ptr_eq(&records[50].tag_name));
+    }
 
     #[test]
     fn test_history_record_creation() {
@@ -55,4 +208,63 @@ mod tests {
         let parsed: HistoryRecord = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed, record);
     }
+
+    #[test]
+    fn test_interned_records_use_less_memory_and_serialize_unchanged() {
+        // 50 个标签 × 2000 个点
+        let records: Vec<HistoryRecord> = (0..2000)
+            .flat_map(|i| {
+                (0..50).map(move |t| {
+                    HistoryRecord::new(
+                        format!("2024-01-01T00:{:02}:{:02}.000", i / 60 % 60, i % 60),
+                        format!("Boiler1.Drum.Temperature_{:02}", t),
+                        i as f64,
+                        "Good".to_string(),
+                    )
+                })
+            })
+            .collect();
+
+        // 每条记录各自持有 String 时的占用
+        let owned: usize = records
+            .iter()
+            .map(|r| {
+                3 * std::mem::size_of::<String>()
+                    + std::mem::size_of::<f64>()
+                    + r.date_time.len()
+                    + r.tag_name.len()
+                    + r.tag_quality.len()
+            })
+            .sum();
+        let interned = HistoryRecord::estimated_batch_size(&records);
+        assert!(interned * 10 < owned * 7, "{} vs {}", interned, owned);
+        assert!(records[0].tag_name.ptr_eq(&records[50].tag_name));
+
+        // 序列化输出与 String 字段一致
+        let json = serde_json::to_string(&records[1]).unwrap();
+        assert_eq!(
+            json,
+            r#"{"dateTime":"2024-01-01T00:00:00.000","tagName":"Boiler1.Drum.Temperature_01","tagVal":0.0,"tagQuality":"Good"}"#
+        );
+        let parsed: HistoryRecord = serde_json::from_str(&json).unwrap();
+        assert!(parsed.tag_name.ptr_eq(&records[1].tag_name));
+    }
+
+    #[test]
+    fn test_non_finite_value_serializes_as_null() {
+        for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let record = HistoryRecord::new(
+                "2024-01-01T00:00:00.000".to_string(),
+                "Tag1".to_string(),
+                value,
+                "Good".to_string(),
+            );
+            let json = serde_json::to_string(&record).unwrap();
+            assert!(json.contains(r#""tagVal":null"#), "{}", json);
+
+            // 合法 JSON，且能反序列化回缺失值
+            let parsed: HistoryRecord = serde_json::from_str(&json).unwrap();
+            assert!(parsed.tag_val.is_nan());
+        }
+    }
 }
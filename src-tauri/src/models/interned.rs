@@ -0,0 +1,231 @@
+//! 驻留字符串
+//!
+//! 历史记录的标签名和质量码高度重复（百万条记录通常只有几十个标签），
+//! 相同内容的字符串全局共享一份 `Arc<str>`，每条记录只保存指针。
+//! 序列化与 `String` 完全一致。
+//!
+//! 全局池由互斥锁保护，批量构造记录（数据库结果映射等）时使用 `Interner`
+//! 在本地缓存，每个不同的值只访问一次全局池。
+
+use std::borrow::Borrow;
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::{Arc, LazyLock};
+
+use parking_lot::Mutex;
+use schemars::JsonSchema;
+use schemars::r#gen::SchemaGenerator;
+use schemars::schema::Schema;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// 全局驻留池
+static POOL: LazyLock<Mutex<HashSet<Arc<str>>>> = LazyLock::new(Default::default);
+
+/// 驻留字符串（内容相同的实例共享同一块内存）
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InternedStr(Arc<str>);
+
+impl InternedStr {
+    /// 驻留字符串，池中已有相同内容时复用
+    pub fn new(s: &str) -> Self {
+        let mut pool = POOL.lock();
+        if let Some(existing) = pool.get(s) {
+            return Self(Arc::clone(existing));
+        }
+        let shared: Arc<str> = Arc::from(s);
+        pool.insert(Arc::clone(&shared));
+        Self(shared)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// 两个实例是否共享同一块内存
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    /// 清理池中已无记录引用的字符串，返回清理数量
+    pub fn release_unused() -> usize {
+        let mut pool = POOL.lock();
+        let before = pool.len();
+        pool.retain(|s| Arc::strong_count(s) > 1);
+        before - pool.len()
+    }
+}
+
+/// 批量驻留器
+///
+/// 本地缓存已驻留的字符串，同一批数据中重复出现的值不再加锁访问全局池。
+#[derive(Debug, Default)]
+pub struct Interner {
+    local: HashSet<InternedStr>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 驻留字符串，本批次已出现过的值直接复用
+    pub fn intern(&mut self, s: &str) -> InternedStr {
+        if let Some(existing) = self.local.get(s) {
+            return existing.clone();
+        }
+        let interned = InternedStr::new(s);
+        self.local.insert(interned.clone());
+        interned
+    }
+}
+
+impl Default for InternedStr {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl Deref for InternedStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for InternedStr {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for InternedStr {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for InternedStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Debug for InternedStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl From<&str> for InternedStr {
+    fn from(s: &str) -> Self {
+        Self::new(s)
+    }
+}
+
+impl From<String> for InternedStr {
+    fn from(s: String) -> Self {
+        Self::new(&s)
+    }
+}
+
+impl From<&String> for InternedStr {
+    fn from(s: &String) -> Self {
+        Self::new(s)
+    }
+}
+
+impl From<InternedStr> for String {
+    fn from(s: InternedStr) -> Self {
+        s.0.to_string()
+    }
+}
+
+impl PartialEq<str> for InternedStr {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for InternedStr {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for InternedStr {
+    fn eq(&self, other: &String) -> bool {
+        *self.0 == **other
+    }
+}
+
+impl PartialEq<InternedStr> for str {
+    fn eq(&self, other: &InternedStr) -> bool {
+        self == &*other.0
+    }
+}
+
+impl PartialEq<InternedStr> for &str {
+    fn eq(&self, other: &InternedStr) -> bool {
+        *self == &*other.0
+    }
+}
+
+impl PartialEq<InternedStr> for String {
+    fn eq(&self, other: &InternedStr) -> bool {
+        **self == *other.0
+    }
+}
+
+impl Serialize for InternedStr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for InternedStr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::new(&s))
+    }
+}
+
+impl JsonSchema for InternedStr {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        String::schema_name()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        String::json_schema(generator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_content_shares_memory() {
+        let a = InternedStr::new("TT_101");
+        let b = InternedStr::from("TT_101".to_string());
+        assert!(a.ptr_eq(&b));
+        assert_eq!(a, "TT_101");
+        assert_eq!("TT_101".to_string(), b);
+        assert!(!a.ptr_eq(&InternedStr::new("TT_102")));
+    }
+
+    #[test]
+    fn test_interner_shares_with_global_pool() {
+        let mut interner = Interner::new();
+        let a = interner.intern("TT_201");
+        let b = interner.intern("TT_201");
+        assert!(a.ptr_eq(&b));
+        assert!(a.ptr_eq(&InternedStr::new("TT_201")));
+        assert_eq!(interner.local.len(), 1);
+    }
+}
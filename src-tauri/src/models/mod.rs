@@ -2,12 +2,31 @@
 //!
 //! 包含所有纯数据结构定义，不包含业务逻辑。
 
+mod float;
 mod history;
+mod interned;
 mod processing;
 mod query;
+mod schema;
 mod tag_group;
+mod tag_tree;
 
 pub use history::HistoryRecord;
-pub use processing::{DataProcessingConfig, OutlierRemovalConfig, ResampleConfig, SmoothingConfig};
-pub use query::{ChartSeriesData, ConnectionTestResult, QueryParams, QueryResult, QueryResultV2};
-pub use tag_group::{ChartConfig, TagGroup, TagGroupConfig};
+pub use interned::{InternedStr, Interner};
+pub use processing::{
+    Baseline, DataProcessingConfig, DedupConfig, OutlierRemovalConfig, RangeLimitConfig,
+    ResampleConfig, SmoothingConfig, StdBandConfig, TagRange,
+};
+pub(crate) use query::Fnv64;
+pub use query::{
+    AlarmStats, ChartSeriesData, ConnectionTestResult, EmptyReason, EventBatch, QueryEstimate,
+    QueryParams, QueryResult, QueryResultV2, QueryTiming, SamplingInterval, SeriesAlignConfig,
+    SeriesColumns, SeriesGapConfig, records_content_hash, series_content_hash,
+};
+pub use schema::{API_SCHEMA_TYPES, api_schema};
+pub use tag_group::{
+    Annotation, BatchApplyResult, ChartConfig, GroupImportResult, GroupUsageStats, TagAlarm,
+    TagAlias, TagAxis, TagGroup, TagGroupConfig, TagMetadata, TagUsage, TagUsageSummary,
+    TagValueMap,
+};
+pub use tag_tree::TagTreeNode;
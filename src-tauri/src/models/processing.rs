@@ -1,29 +1,100 @@
 //! 数据处理配置模型
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::error::{AppError, AppResult};
+
+/// 异常值剔除方法白名单
+const OUTLIER_METHODS: [&str; 1] = ["3sigma"];
+/// 异常值处理方式白名单
+const OUTLIER_MODES: [&str; 2] = ["drop", "clip"];
+/// 重采样方法白名单
+const RESAMPLE_METHODS: [&str; 2] = ["mean", "ohlc"];
+/// 多统计重采样的统计量白名单
+pub const RESAMPLE_STATS: [&str; 7] = ["mean", "max", "min", "first", "last", "sum", "count"];
+/// 夏令时歧义时间取法白名单
+const DST_AMBIGUOUS_CHOICES: [&str; 2] = ["earliest", "latest"];
+/// 平滑方法白名单
+const SMOOTHING_METHODS: [&str; 1] = ["moving_avg"];
+/// 去重保留方式白名单
+const DEDUP_KEEP_CHOICES: [&str; 2] = ["first", "last"];
+/// 量程裁剪处理方式白名单
+const RANGE_LIMIT_MODES: [&str; 2] = ["drop", "clip"];
+
+/// 重采样间隔上限（秒，7 天）
+pub const MAX_RESAMPLE_INTERVAL_SECS: u32 = 7 * 24 * 3600;
+/// 波动带窗口上限（点数）
+pub const MAX_STD_BAND_WINDOW: usize = 10_000;
+
 /// 异常值剔除配置
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct OutlierRemovalConfig {
     pub enabled: bool,
     #[serde(default = "default_outlier_method")]
     pub method: String, // "3sigma"
+    /// 滚动窗口点数：设置时在局部窗口内判断 3σ（适合有趋势/分段的信号），否则全局判断
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<usize>,
+    /// 处理方式："drop" 剔除离群点 | "clip" 夹到 μ±3σ 边界（保留点，避免时间断裂）
+    #[serde(default = "default_outlier_mode")]
+    pub mode: String,
+}
+
+impl OutlierRemovalConfig {
+    /// 有效的滚动窗口大小（至少 3 个点才有意义）
+    pub fn rolling_window(&self) -> Option<usize> {
+        self.window.filter(|&w| w >= 3)
+    }
+
+    /// 是否为夹取模式（离群值替换为边界值而不删除）
+    pub fn is_clip(&self) -> bool {
+        self.mode.eq_ignore_ascii_case("clip")
+    }
 }
 
 fn default_outlier_method() -> String {
     "3sigma".to_string()
 }
 
+fn default_outlier_mode() -> String {
+    "drop".to_string()
+}
+
 /// 重采样配置
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ResampleConfig {
     pub enabled: bool,
     #[serde(default = "default_resample_interval")]
     pub interval: u32, // 秒
     #[serde(default = "default_resample_method")]
-    pub method: String, // "mean"
+    pub method: String, // "mean" | "ohlc"
+    /// 夏令时回拨时歧义本地时间的取法："earliest"（较早，默认）| "latest"（较晚）
+    #[serde(default = "default_dst_ambiguous")]
+    pub dst_ambiguous: String,
+    /// 多统计聚合（如 `["mean", "max"]`）：非空时忽略 `method`，
+    /// 每个统计量输出一个派生序列（标签名加后缀，如 `TAG_mean`、`TAG_max`）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub methods: Vec<String>,
+}
+
+impl ResampleConfig {
+    /// 是否为 OHLC 聚合（每个窗口输出开/高/低/收四个派生序列）
+    pub fn is_ohlc(&self) -> bool {
+        self.method.eq_ignore_ascii_case("ohlc")
+    }
+
+    /// 是否为多统计聚合（每个统计量输出一个派生序列）
+    pub fn is_multi_stat(&self) -> bool {
+        !self.methods.is_empty()
+    }
+
+    /// 歧义本地时间是否取较晚的时刻
+    pub fn prefer_latest(&self) -> bool {
+        self.dst_ambiguous.eq_ignore_ascii_case("latest")
+    }
 }
 
 fn default_resample_interval() -> u32 {
@@ -34,8 +105,12 @@ fn default_resample_method() -> String {
     "mean".to_string()
 }
 
+fn default_dst_ambiguous() -> String {
+    "earliest".to_string()
+}
+
 /// 平滑滤波配置
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct SmoothingConfig {
     pub enabled: bool,
@@ -53,8 +128,123 @@ fn default_smoothing_window() -> usize {
     5
 }
 
+/// 滚动标准差波动带配置
+///
+/// 每个序列额外输出上沿 / 下沿两个派生序列（`TAG.upper` / `TAG.lower`），
+/// 取居中窗口内的滚动均值 ± 倍数 × 滚动标准差，在平滑滤波之前的数据上计算。
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StdBandConfig {
+    pub enabled: bool,
+    #[serde(default = "default_std_band_window")]
+    pub window: usize, // 窗口大小
+    /// 标准差倍数（1 即 ±1σ）
+    #[serde(default = "default_std_band_multiplier")]
+    pub multiplier: f64,
+}
+
+fn default_std_band_window() -> usize {
+    20
+}
+
+fn default_std_band_multiplier() -> f64 {
+    1.0
+}
+
+/// 重复记录去重配置（按 标签 + 时间 去重）
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DedupConfig {
+    pub enabled: bool,
+    #[serde(default = "default_dedup_keep")]
+    pub keep: String, // "first" | "last"
+}
+
+fn default_dedup_keep() -> String {
+    "first".to_string()
+}
+
+impl DedupConfig {
+    /// 是否保留重复键中的最后一条
+    pub fn keep_last(&self) -> bool {
+        self.keep.eq_ignore_ascii_case("last")
+    }
+}
+
+/// 标签工程量程
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TagRange {
+    pub tag: String,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// 工程量程裁剪配置
+///
+/// 超出量程的值视为坏数据（如传感器断线读到 -9999），未配置量程的标签不处理。
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RangeLimitConfig {
+    pub enabled: bool,
+    /// 处理方式："drop" 剔除超量程点 | "clip" 夹到量程边界
+    #[serde(default = "default_range_limit_mode")]
+    pub mode: String,
+    /// 各标签量程
+    #[serde(default)]
+    pub ranges: Vec<TagRange>,
+}
+
+impl RangeLimitConfig {
+    /// 是否为夹取模式
+    pub fn is_clip(&self) -> bool {
+        self.mode.eq_ignore_ascii_case("clip")
+    }
+
+    /// 标签的量程 `(min, max)`，未配置时返回 `None`
+    pub fn range_for(&self, tag: &str) -> Option<(f64, f64)> {
+        self.ranges
+            .iter()
+            .find(|r| r.tag == tag)
+            .map(|r| (r.min, r.max))
+    }
+}
+
+fn default_range_limit_mode() -> String {
+    "drop".to_string()
+}
+
+/// 基准值（偏差显示时从每个标签的数据中减去）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Baseline {
+    /// 首个有效值
+    First,
+    /// 有效值均值
+    Mean,
+    /// 固定数值
+    Value(f64),
+}
+
+impl Baseline {
+    /// 解析 `"first"` / `"mean"` / 数值字符串，无法识别时返回 `None`
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if value.eq_ignore_ascii_case("first") {
+            Some(Self::First)
+        } else if value.eq_ignore_ascii_case("mean") {
+            Some(Self::Mean)
+        } else {
+            value
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .map(Self::Value)
+        }
+    }
+}
+
 /// 数据处理配置
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct DataProcessingConfig {
     #[serde(default)]
@@ -63,6 +253,18 @@ pub struct DataProcessingConfig {
     pub resample: ResampleConfig,
     #[serde(default)]
     pub smoothing: SmoothingConfig,
+    /// 滚动标准差波动带（与平滑中线一起显示数据的波动范围）
+    #[serde(default)]
+    pub std_band: StdBandConfig,
+    /// 去重（在其他处理步骤之前执行）
+    #[serde(default)]
+    pub dedup: DedupConfig,
+    /// 工程量程裁剪（去重之后、异常值剔除之前执行）
+    #[serde(default)]
+    pub range_limit: RangeLimitConfig,
+    /// 相对基准显示：`"first"` / `"mean"` / 数值，按标签减去基准值（在其他处理步骤之后执行）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub baseline: Option<String>,
 }
 
 impl DataProcessingConfig {
@@ -86,6 +288,14 @@ impl DataProcessingConfig {
         self
     }
 
+    /// 启用多统计重采样
+    pub fn with_resample_stats(mut self, interval: u32, stats: &[&str]) -> Self {
+        self.resample.enabled = true;
+        self.resample.interval = interval;
+        self.resample.methods = stats.iter().map(|s| s.to_string()).collect();
+        self
+    }
+
     /// 启用平滑滤波
     pub fn with_smoothing(mut self, window: usize, method: &str) -> Self {
         self.smoothing.enabled = true;
@@ -94,9 +304,160 @@ impl DataProcessingConfig {
         self
     }
 
+    /// 启用滚动标准差波动带
+    pub fn with_std_band(mut self, window: usize, multiplier: f64) -> Self {
+        self.std_band.enabled = true;
+        self.std_band.window = window;
+        self.std_band.multiplier = multiplier;
+        self
+    }
+
+    /// 启用去重
+    pub fn with_dedup(mut self, keep: &str) -> Self {
+        self.dedup.enabled = true;
+        self.dedup.keep = keep.to_string();
+        self
+    }
+
+    /// 启用工程量程裁剪
+    pub fn with_range_limit(mut self, ranges: Vec<TagRange>, mode: &str) -> Self {
+        self.range_limit.enabled = true;
+        self.range_limit.mode = mode.to_string();
+        self.range_limit.ranges = ranges;
+        self
+    }
+
+    /// 设置相对基准
+    pub fn with_baseline(mut self, baseline: &str) -> Self {
+        self.baseline = Some(baseline.to_string());
+        self
+    }
+
+    /// 校验已启用步骤的参数
+    ///
+    /// 处理步骤遇到不合理的参数（如平滑窗口小于 2）会静默跳过，
+    /// 查询前校验可让前端知道配置未生效。未启用的步骤不校验。
+    pub fn validate(&self) -> AppResult<()> {
+        let outliers = &self.outlier_removal;
+        if outliers.enabled {
+            check_choice("异常值剔除方法", &outliers.method, &OUTLIER_METHODS)?;
+            check_choice("异常值处理方式", &outliers.mode, &OUTLIER_MODES)?;
+            if let Some(window) = outliers.window
+                && window < 3
+            {
+                return Err(AppError::Validation(format!(
+                    "异常值滚动窗口至少为 3 个点，当前 {}",
+                    window
+                )));
+            }
+        }
+
+        let resample = &self.resample;
+        if resample.enabled {
+            if resample.interval == 0 || resample.interval > MAX_RESAMPLE_INTERVAL_SECS {
+                return Err(AppError::Validation(format!(
+                    "重采样间隔必须在 1 ~ {} 秒之间，当前 {}",
+                    MAX_RESAMPLE_INTERVAL_SECS, resample.interval
+                )));
+            }
+            check_choice("重采样方法", &resample.method, &RESAMPLE_METHODS)?;
+            for (i, stat) in resample.methods.iter().enumerate() {
+                if stat.is_empty() {
+                    return Err(AppError::Validation("重采样统计量不能为空".to_string()));
+                }
+                check_choice("重采样统计量", stat, &RESAMPLE_STATS)?;
+                if resample.methods[..i]
+                    .iter()
+                    .any(|s| s.eq_ignore_ascii_case(stat))
+                {
+                    return Err(AppError::Validation(format!("重采样统计量重复: {}", stat)));
+                }
+            }
+            check_choice(
+                "夏令时歧义时间取法",
+                &resample.dst_ambiguous,
+                &DST_AMBIGUOUS_CHOICES,
+            )?;
+        }
+
+        let smoothing = &self.smoothing;
+        if smoothing.enabled {
+            if smoothing.window < 2 {
+                return Err(AppError::Validation(format!(
+                    "平滑窗口至少为 2 个点，当前 {}",
+                    smoothing.window
+                )));
+            }
+            check_choice("平滑方法", &smoothing.method, &SMOOTHING_METHODS)?;
+        }
+
+        let std_band = &self.std_band;
+        if std_band.enabled {
+            if std_band.window < 2 || std_band.window > MAX_STD_BAND_WINDOW {
+                return Err(AppError::Validation(format!(
+                    "波动带窗口必须在 2 ~ {} 个点之间，当前 {}",
+                    MAX_STD_BAND_WINDOW, std_band.window
+                )));
+            }
+            if !(std_band.multiplier.is_finite() && std_band.multiplier > 0.0) {
+                return Err(AppError::Validation(format!(
+                    "波动带标准差倍数必须为正数，当前 {}",
+                    std_band.multiplier
+                )));
+            }
+        }
+
+        if self.dedup.enabled {
+            check_choice("去重保留方式", &self.dedup.keep, &DEDUP_KEEP_CHOICES)?;
+        }
+
+        let range_limit = &self.range_limit;
+        if range_limit.enabled {
+            check_choice("量程裁剪方式", &range_limit.mode, &RANGE_LIMIT_MODES)?;
+            for range in &range_limit.ranges {
+                if !(range.min.is_finite() && range.max.is_finite() && range.min <= range.max) {
+                    return Err(AppError::Validation(format!(
+                        "标签 {} 的量程无效: [{}, {}]",
+                        range.tag, range.min, range.max
+                    )));
+                }
+            }
+        }
+
+        if let Some(baseline) = self.baseline.as_deref()
+            && Baseline::parse(baseline).is_none()
+        {
+            return Err(AppError::Validation(format!(
+                "基准值无效: '{}'，可选值: first, mean 或数值",
+                baseline
+            )));
+        }
+        Ok(())
+    }
+
     /// 检查是否有任何处理启用
     pub fn has_any_enabled(&self) -> bool {
-        self.outlier_removal.enabled || self.resample.enabled || self.smoothing.enabled
+        self.outlier_removal.enabled
+            || self.resample.enabled
+            || self.smoothing.enabled
+            || self.std_band.enabled
+            || self.dedup.enabled
+            || self.range_limit.enabled
+            || self.baseline.is_some()
+    }
+}
+
+/// 校验取值在白名单内（忽略大小写；空字符串按默认取值处理）
+fn check_choice(name: &str, value: &str, choices: &[&str]) -> AppResult<()> {
+    if value.is_empty() || choices.iter().any(|c| c.eq_ignore_ascii_case(value)) {
+        Ok(())
+    } else {
+        Err(AppError::Validation(format!(
+            "{}无效: '{}'，可选值: {}",
+            name,
+            value,
+            choices.join(", ")
+        )))
     }
 }
 
@@ -129,6 +490,66 @@ mod tests {
         assert!(config.has_any_enabled());
     }
 
+    #[test]
+    fn test_validate_rejects_invalid_parameters() {
+        let valid = DataProcessingConfig::new()
+            .with_outlier_removal("3sigma")
+            .with_resample(60, "OHLC")
+            .with_smoothing(5, "moving_avg")
+            .with_dedup("last");
+        assert!(valid.validate().is_ok());
+
+        // 未启用的步骤不校验
+        let mut disabled = DataProcessingConfig::new();
+        disabled.smoothing.window = 0;
+        assert!(disabled.validate().is_ok());
+
+        let invalid = [
+            DataProcessingConfig::new().with_smoothing(0, "moving_avg"),
+            DataProcessingConfig::new().with_smoothing(1, "moving_avg"),
+            DataProcessingConfig::new().with_resample(0, "mean"),
+            DataProcessingConfig::new().with_resample(MAX_RESAMPLE_INTERVAL_SECS + 1, "mean"),
+            DataProcessingConfig::new().with_resample(60, "median"),
+            DataProcessingConfig::new().with_resample_stats(60, &["mean", "median"]),
+            DataProcessingConfig::new().with_resample_stats(60, &["max", "MAX"]),
+            DataProcessingConfig::new().with_smoothing(5, "kalman"),
+            DataProcessingConfig::new().with_std_band(1, 1.0),
+            DataProcessingConfig::new().with_std_band(MAX_STD_BAND_WINDOW + 1, 1.0),
+            DataProcessingConfig::new().with_std_band(20, 0.0),
+            DataProcessingConfig::new().with_outlier_removal("iqr"),
+            DataProcessingConfig::new().with_baseline("median"),
+            DataProcessingConfig::new().with_range_limit(
+                vec![TagRange {
+                    tag: "TT".to_string(),
+                    min: 100.0,
+                    max: 0.0,
+                }],
+                "drop",
+            ),
+        ];
+        for config in invalid {
+            assert!(
+                matches!(config.validate(), Err(AppError::Validation(_))),
+                "应拒绝: {:?}",
+                config
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_baseline() {
+        assert_eq!(Baseline::parse("First"), Some(Baseline::First));
+        assert_eq!(Baseline::parse(" mean "), Some(Baseline::Mean));
+        assert_eq!(Baseline::parse("-1.5"), Some(Baseline::Value(-1.5)));
+        assert_eq!(Baseline::parse("median"), None);
+        assert_eq!(Baseline::parse("NaN"), None);
+        assert!(
+            DataProcessingConfig::new()
+                .with_baseline("first")
+                .has_any_enabled()
+        );
+    }
+
     #[test]
     fn test_config_serialization() {
         let config = DataProcessingConfig::new().with_outlier_removal("3sigma");
@@ -1,10 +1,14 @@
 //! 查询相关数据模型
 
-use super::HistoryRecord;
+use super::float::{nullable_points, nullable_values};
+use super::{Annotation, HistoryRecord};
+use crate::error::{AppError, AppResult};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// 查询参数
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct QueryParams {
     pub start_time: String,
@@ -15,6 +19,34 @@ pub struct QueryParams {
     pub limit: Option<usize>,
     #[serde(default)]
     pub offset: Option<usize>,
+    /// 多标签时间对齐（仅 V2 查询生效）
+    #[serde(default)]
+    pub align: Option<SeriesAlignConfig>,
+    /// 断采检测（仅 V2 查询生效）
+    #[serde(default)]
+    pub gap: Option<SeriesGapConfig>,
+    /// 每个标签最多返回的最新点数（在 SQL 层限量）
+    #[serde(default)]
+    pub per_tag_limit: Option<usize>,
+    /// 是否在系列中返回各点的质量码（仅 V2 查询生效，默认关闭以减少负载）
+    #[serde(default)]
+    pub include_quality: bool,
+    /// 分片大小（每片数据点数）：结果超过时改为分片返回（仅 V2 查询生效）
+    #[serde(default)]
+    pub chunk_size: Option<usize>,
+    /// 结果记录时间的输出格式（strftime 模式，仅 V1 查询生效），
+    /// 默认 `%Y-%m-%dT%H:%M:%S%.3f`
+    #[serde(default)]
+    pub output_time_format: Option<String>,
+    /// 不走缓存的标签：每次都查库且不写入缓存，其余标签正常缓存
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub no_cache_tags: Vec<String>,
+    /// 是否从时间最新端开始分页（结果按时间降序返回，仅 V1 查询生效）
+    #[serde(default)]
+    pub newest_first: bool,
+    /// 是否以列式返回系列数据点（`timestamps` / `values` 两个数组，仅 V2 JSON 查询生效）
+    #[serde(default)]
+    pub columnar: bool,
 }
 
 impl QueryParams {
@@ -26,6 +58,15 @@ impl QueryParams {
             tags: None,
             limit: None,
             offset: None,
+            align: None,
+            gap: None,
+            per_tag_limit: None,
+            include_quality: false,
+            chunk_size: None,
+            output_time_format: None,
+            no_cache_tags: Vec::new(),
+            newest_first: false,
+            columnar: false,
         }
     }
 
@@ -41,40 +82,411 @@ impl QueryParams {
         self.limit = Some(limit);
         self
     }
+
+    /// 设置从最新端开始分页
+    pub fn with_newest_first(mut self, newest_first: bool) -> Self {
+        self.newest_first = newest_first;
+        self
+    }
+
+    /// 设置不走缓存的标签
+    pub fn with_no_cache_tags(mut self, tags: Vec<String>) -> Self {
+        self.no_cache_tags = tags;
+        self
+    }
+
+    /// 按 `no_cache_tags` 拆分为 (可缓存部分, 绕过缓存部分)，标签集为空的部分为 `None`
+    ///
+    /// 未指定标签（查询全部标签）时无法排除单个标签，整个查询绕过缓存。
+    pub fn split_no_cache(&self) -> (Option<QueryParams>, Option<QueryParams>) {
+        if self.no_cache_tags.is_empty() {
+            return (Some(self.clone()), None);
+        }
+        let Some(tags) = self.tags.as_ref().filter(|t| !t.is_empty()) else {
+            return (None, Some(self.clone()));
+        };
+
+        let (live, cached): (Vec<String>, Vec<String>) = tags
+            .iter()
+            .cloned()
+            .partition(|tag| self.no_cache_tags.contains(tag));
+        let subset = |tags: Vec<String>| {
+            (!tags.is_empty()).then(|| QueryParams {
+                tags: Some(tags),
+                no_cache_tags: Vec::new(),
+                ..self.clone()
+            })
+        };
+        (subset(cached), subset(live))
+    }
+}
+
+/// 多标签时间对齐配置
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesAlignConfig {
+    pub enabled: bool,
+    /// 网格间隔（毫秒），未指定时取各标签中最粗的采样间隔
+    #[serde(default)]
+    pub interval_ms: Option<u64>,
+    /// 缺失点处理："linear"（线性插值）或 "none"（留空）
+    #[serde(default = "default_align_fill")]
+    pub fill: String,
+}
+
+fn default_align_fill() -> String {
+    "linear".to_string()
+}
+
+/// 对齐缺失点处理方式可选值
+pub const ALIGN_FILL_MODES: [&str; 2] = ["linear", "none"];
+
+impl SeriesAlignConfig {
+    /// 创建启用的默认对齐配置（自动间隔 + 线性插值）
+    pub fn enabled() -> Self {
+        Self {
+            enabled: true,
+            interval_ms: None,
+            fill: default_align_fill(),
+        }
+    }
+
+    /// 校验缺失点处理方式
+    pub fn validate(&self) -> AppResult<()> {
+        if ALIGN_FILL_MODES.contains(&self.fill.as_str()) {
+            Ok(())
+        } else {
+            Err(AppError::Validation(format!(
+                "对齐缺失点处理方式无效: '{}'，可选值: {}",
+                self.fill,
+                ALIGN_FILL_MODES.join(", ")
+            )))
+        }
+    }
+}
+
+/// 断采检测配置
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesGapConfig {
+    pub enabled: bool,
+    /// 断采阈值（毫秒），未指定时按正常采样间隔估算
+    #[serde(default)]
+    pub threshold_ms: Option<u64>,
+    /// 自动估算时的倍数（相邻间隔超过 正常间隔 × factor 视为断采）
+    #[serde(default = "default_gap_factor")]
+    pub factor: f64,
+}
+
+fn default_gap_factor() -> f64 {
+    5.0
+}
+
+impl SeriesGapConfig {
+    /// 创建启用的默认断采检测配置（自动阈值）
+    pub fn enabled() -> Self {
+        Self {
+            enabled: true,
+            threshold_ms: None,
+            factor: default_gap_factor(),
+        }
+    }
 }
 
 /// 查询结果 (V1 兼容格式)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct QueryResult {
     pub records: Vec<HistoryRecord>,
     pub total: usize,
+    /// 原始结果超过行数上限被截断（只保留最早的部分）
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+impl QueryResult {
+    /// 取出完整时间范围的记录，结果被截断时返回校验错误（用于导出等需要全量数据的场景）
+    pub fn into_complete_records(self) -> AppResult<Vec<HistoryRecord>> {
+        if self.truncated {
+            return Err(AppError::Validation(
+                "查询结果超过行数上限，数据不完整，请缩小时间范围或减少标签".to_string(),
+            ));
+        }
+        Ok(self.records)
+    }
 }
 
 /// 图表系列数据 (V2 格式，按标签预分组)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ChartSeriesData {
     /// 标签名称
     pub tag_name: String,
-    /// 数据点 [[timestamp_ms, value], ...]
+    /// 数据点 [[timestamp_ms, value], ...]，NaN / Inf 序列化为 `null`（缺失值）
+    #[serde(with = "nullable_points")]
+    #[schemars(with = "Vec<[Option<f64>; 2]>")]
     pub data: Vec<[f64; 2]>,
+    /// 各数据点的质量码，与 `data` 一一对应（断点为空字符串）
+    ///
+    /// 仅在 `QueryParams.include_quality` 时返回；时间对齐后和二进制格式中不返回。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality: Option<Vec<String>>,
+    /// 各数据点的文本标签，与 `data` 一一对应（离散状态标签的枚举映射）
+    ///
+    /// 仅在分组图表为该标签配置了值映射时返回；未映射的值为数值原文，缺失值为空字符串。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_labels: Option<Vec<String>>,
+    /// 列式数据点，仅在 `QueryParams.columnar` 时返回（此时 `data` 为空）
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub columns: Option<SeriesColumns>,
+}
+
+/// 列式数据点：`timestamps[i]` 与 `values[i]` 对应行式的 `data[i]`
+///
+/// 两个大数组比大量小数组序列化、解析更快，前端可直接转为 `Float64Array`。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesColumns {
+    /// 时间戳（毫秒）
+    #[serde(with = "nullable_values")]
+    #[schemars(with = "Vec<Option<f64>>")]
+    pub timestamps: Vec<f64>,
+    /// 数值，NaN / Inf 序列化为 `null`（缺失值）
+    #[serde(with = "nullable_values")]
+    #[schemars(with = "Vec<Option<f64>>")]
+    pub values: Vec<f64>,
+}
+
+impl ChartSeriesData {
+    /// 创建只含数据点的系列（质量码、文本标签、列式数据均为空）
+    pub fn new(tag_name: String, data: Vec<[f64; 2]>) -> Self {
+        Self {
+            tag_name,
+            data,
+            quality: None,
+            value_labels: None,
+            columns: None,
+        }
+    }
+
+    /// 转为列式：数据点移入 `columns`，`data` 置空（已是列式时不变）
+    pub fn to_columnar(&mut self) {
+        if self.columns.is_some() {
+            return;
+        }
+        let (timestamps, values) = std::mem::take(&mut self.data)
+            .into_iter()
+            .map(|[time, value]| (time, value))
+            .unzip();
+        self.columns = Some(SeriesColumns { timestamps, values });
+    }
+
+    /// 按值映射表（如 `"0"` → `"停止"`）生成各数据点的文本标签，数值保持不变
+    pub fn apply_value_map(&mut self, value_map: &HashMap<String, String>) {
+        let labels = self
+            .data
+            .iter()
+            .map(|[_, value]| {
+                if !value.is_finite() {
+                    return String::new();
+                }
+                let key = value.to_string();
+                value_map.get(&key).cloned().unwrap_or(key)
+            })
+            .collect();
+        self.value_labels = Some(labels);
+    }
 }
 
 /// 查询结果 V2 (预分组格式，优化前端渲染)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct QueryResultV2 {
     /// 按标签分组的系列数据
     pub series: Vec<ChartSeriesData>,
+    /// 与查询时间范围重叠的分组标注（指定分组查询时返回）
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
     /// 原始数据总量
     pub total_raw: usize,
     /// 处理后数据量
     pub total_processed: usize,
     /// 是否命中缓存
     pub cache_hit: bool,
+    /// 原始结果超过行数上限被截断（只保留最早的部分）
+    #[serde(default)]
+    pub truncated: bool,
     /// 查询耗时（毫秒）
     pub query_time_ms: u64,
+    /// 分阶段耗时
+    #[serde(default)]
+    pub timing: QueryTiming,
+    /// 分片返回时的结果令牌（`series` 为空，用 `fetch_series_chunk` 逐片拉取）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_token: Option<String>,
+    /// 分片返回时的总分片数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_count: Option<usize>,
+    /// 空结果原因（`total_raw == 0` 表示查询成功但无数据，数据库出错时返回错误而非空结果）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub empty_reason: Option<EmptyReason>,
+    /// 查询结果的提示信息（如采样不均匀导致重采样聚合可能失真）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// 系列数据指纹，数据不变时不变（前端据此判断是否需要重绘）
+    #[serde(default)]
+    pub content_hash: String,
+}
+
+/// FNV-1a 64 位哈希（实现简单且结果跨进程稳定，`DefaultHasher` 不保证这一点）
+pub(crate) struct Fnv64(pub(crate) u64);
+
+impl Fnv64 {
+    pub(crate) fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    /// 写入带长度前缀的字符串，避免相邻字段拼接产生歧义
+    pub(crate) fn write_str(&mut self, s: &str) {
+        self.write(&(s.len() as u64).to_le_bytes());
+        self.write(s.as_bytes());
+    }
+}
+
+/// 计算系列数据的稳定指纹（十六进制）
+///
+/// 只取标签名、数据点和质量码（值映射文本由数据点派生）。
+pub fn series_content_hash(series: &[ChartSeriesData]) -> String {
+    let mut hasher = Fnv64::new();
+    for s in series {
+        hasher.write_str(&s.tag_name);
+        hasher.write(&(s.data.len() as u64).to_le_bytes());
+        for [time, value] in &s.data {
+            hasher.write(&time.to_bits().to_le_bytes());
+            hasher.write(&value.to_bits().to_le_bytes());
+        }
+        for quality in s.quality.iter().flatten() {
+            hasher.write_str(quality);
+        }
+    }
+    format!("{:016x}", hasher.0)
+}
+
+/// 计算历史记录的稳定指纹（顺序敏感）
+pub fn records_content_hash(records: &[HistoryRecord]) -> u64 {
+    let mut hasher = Fnv64::new();
+    hasher.write(&(records.len() as u64).to_le_bytes());
+    for record in records {
+        hasher.write_str(&record.date_time);
+        hasher.write_str(&record.tag_name);
+        hasher.write(&record.tag_val.to_bits().to_le_bytes());
+        hasher.write_str(&record.tag_quality);
+    }
+    hasher.0
+}
+
+/// 查询成功但无数据的原因
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum EmptyReason {
+    /// 请求的标签在库中不存在（`tags` 为不存在的标签）
+    TagsNotFound { tags: Vec<String> },
+    /// 标签存在，但该时间范围内无采样
+    NoDataInRange,
+}
+
+/// 查询数据量预估
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryEstimate {
+    /// 预计返回的原始行数
+    pub estimated_rows: u64,
+    /// 按最近查询平均速率估算的数据库耗时（毫秒），尚无历史查询时为空
+    pub estimated_ms: Option<u64>,
+    /// 预计行数超过结果行数上限，结果将被截断
+    pub will_truncate: bool,
+}
+
+/// 查询分阶段耗时（毫秒）
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryTiming {
+    /// 缓存查找与写入耗时
+    pub cache_lookup_ms: u64,
+    /// 数据库查询耗时（缓存命中时为 0）
+    pub db_ms: u64,
+    /// 数据处理耗时
+    pub process_ms: u64,
+    /// 转换为 series 格式耗时
+    pub serialize_ms: u64,
+}
+
+impl QueryTiming {
+    /// 各阶段耗时之和
+    pub fn total_ms(&self) -> u64 {
+        self.cache_lookup_ms + self.db_ms + self.process_ms + self.serialize_ms
+    }
+}
+
+/// 标签越限统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AlarmStats {
+    /// 标签名称
+    pub tag_name: String,
+    /// 报警上限
+    pub alarm_high: Option<f64>,
+    /// 报警下限
+    pub alarm_low: Option<f64>,
+    /// 数据点总数
+    pub total_points: usize,
+    /// 超上限点数
+    pub high_count: usize,
+    /// 超下限点数
+    pub low_count: usize,
+    /// 超上限时长（毫秒）
+    pub high_duration_ms: f64,
+    /// 超下限时长（毫秒）
+    pub low_duration_ms: f64,
+}
+
+/// 标签的正常采样周期
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SamplingInterval {
+    /// 标签名称
+    pub tag_name: String,
+    /// 正常采样间隔（毫秒）
+    pub interval_ms: f64,
+    /// 检测方式："mode"（众数，采样规则）| "median"（中位数，采样不规则）
+    pub method: String,
+    /// 参与统计的相邻时间差个数
+    pub sample_count: usize,
+    /// 等于众数间隔的时间差占比（0 ~ 1），越接近 1 采样越规则
+    pub regularity: f64,
+}
+
+/// 按事件标签切分出的批次
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventBatch {
+    /// 批次序号（从 0 开始）
+    pub index: usize,
+    /// 开始时间（毫秒时间戳）
+    pub start_ms: f64,
+    /// 结束时间（毫秒时间戳）
+    pub end_ms: f64,
+    /// 是否已出现结束标记（数据结束时仍在进行的批次为 false）
+    pub complete: bool,
+    /// 批次时间范围内的各系列数据
+    pub series: Vec<ChartSeriesData>,
 }
 
 /// 连接测试结果
@@ -118,6 +530,98 @@ mod tests {
         assert_eq!(params.limit, Some(100));
     }
 
+    #[test]
+    fn test_truncated_result_is_not_complete() {
+        let result = QueryResult {
+            records: Vec::new(),
+            total: 0,
+            truncated: true,
+        };
+        assert!(matches!(
+            result.into_complete_records(),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_series_non_finite_values_serialize_as_null() {
+        let series = ChartSeriesData::new(
+            "T1".to_string(),
+            vec![[1000.0, 1.5], [2000.0, f64::NAN], [3000.0, f64::INFINITY]],
+        );
+
+        let json = serde_json::to_string(&series).unwrap();
+        assert_eq!(
+            json,
+            r#"{"tagName":"T1","data":[[1000.0,1.5],[2000.0,null],[3000.0,null]]}"#
+        );
+
+        // 前端回传含 null 的系列（如断点）也能解析
+        let parsed: ChartSeriesData = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.data[0], [1000.0, 1.5]);
+        assert!(parsed.data[1][1].is_nan() && parsed.data[2][1].is_nan());
+    }
+
+    #[test]
+    fn test_columnar_series_matches_rows() {
+        let data = vec![[1000.0, 1.5], [2000.0, f64::NAN], [3000.0, -2.0]];
+        let mut series = ChartSeriesData::new("T1".to_string(), data.clone());
+        series.to_columnar();
+
+        assert!(series.data.is_empty());
+        let columns = series.columns.as_ref().unwrap();
+        assert_eq!(columns.timestamps.len(), columns.values.len());
+        assert_eq!(columns.timestamps.len(), data.len());
+        for (i, [time, value]) in data.iter().enumerate() {
+            assert_eq!(columns.timestamps[i], *time);
+            assert_eq!(columns.values[i].to_bits(), value.to_bits());
+        }
+
+        let json = serde_json::to_string(&series).unwrap();
+        assert_eq!(
+            json,
+            r#"{"tagName":"T1","data":[],"timestamps":[1000.0,2000.0,3000.0],"values":[1.5,null,-2.0]}"#
+        );
+        let parsed: ChartSeriesData = serde_json::from_str(&json).unwrap();
+        assert!(parsed.columns.unwrap().values[1].is_nan());
+
+        // 行式结果不带列式字段
+        let rows: ChartSeriesData =
+            serde_json::from_str(r#"{"tagName":"T1","data":[[1000.0,1.5]]}"#).unwrap();
+        assert!(rows.columns.is_none());
+    }
+
+    #[test]
+    fn test_apply_value_map_labels_points() {
+        let mut series = ChartSeriesData::new(
+            "PUMP_STATE".to_string(),
+            vec![
+                [1000.0, 0.0],
+                [2000.0, 1.0],
+                [3000.0, 2.0],
+                [4000.0, 3.5],
+                [5000.0, f64::NAN],
+            ],
+        );
+        let value_map = HashMap::from([
+            ("0".to_string(), "停止".to_string()),
+            ("1".to_string(), "运行".to_string()),
+            ("2".to_string(), "故障".to_string()),
+        ]);
+
+        series.apply_value_map(&value_map);
+
+        // 已映射的值带文本，未映射的值保持原样，数值仍保留供绘图
+        assert_eq!(
+            series.value_labels.as_deref().unwrap(),
+            ["停止", "运行", "故障", "3.5", ""]
+        );
+        assert_eq!(series.data[1], [2000.0, 1.0]);
+
+        let json = serde_json::to_value(&series).unwrap();
+        assert_eq!(json["valueLabels"][2], "故障");
+    }
+
     #[test]
     fn test_connection_test_result() {
         let success = ConnectionTestResult::success();
@@ -0,0 +1,76 @@
+//! API 数据结构的 JSON Schema
+//!
+//! 为前端和第三方集成提供主要返回 / 参数结构的机器可读定义，
+//! 字段名与序列化结果一致（camelCase）。
+
+use schemars::schema_for;
+use serde_json::Value;
+
+use super::{
+    Annotation, ChartSeriesData, DataProcessingConfig, HistoryRecord, QueryEstimate, QueryParams,
+    QueryResult, QueryResultV2,
+};
+use crate::error::{AppError, AppResult};
+
+/// 可获取 schema 的类型名
+pub const API_SCHEMA_TYPES: &[&str] = &[
+    "QueryResultV2",
+    "QueryResult",
+    "QueryParams",
+    "DataProcessingConfig",
+    "HistoryRecord",
+    "ChartSeriesData",
+    "QueryEstimate",
+    "Annotation",
+];
+
+/// 获取类型的 JSON Schema（draft-07，嵌套类型位于 `definitions`）
+pub fn api_schema(type_name: &str) -> AppResult<Value> {
+    let schema = match type_name.trim() {
+        "QueryResultV2" => schema_for!(QueryResultV2),
+        "QueryResult" => schema_for!(QueryResult),
+        "QueryParams" => schema_for!(QueryParams),
+        "DataProcessingConfig" => schema_for!(DataProcessingConfig),
+        "HistoryRecord" => schema_for!(HistoryRecord),
+        "ChartSeriesData" => schema_for!(ChartSeriesData),
+        "QueryEstimate" => schema_for!(QueryEstimate),
+        "Annotation" => schema_for!(Annotation),
+        other => {
+            return Err(AppError::Validation(format!(
+                "未知的类型: '{}'，可选: {}",
+                other,
+                API_SCHEMA_TYPES.join(", ")
+            )));
+        }
+    };
+    Ok(serde_json::to_value(schema)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_result_v2_schema_fields() {
+        let schema = api_schema("QueryResultV2").unwrap();
+        let properties = &schema["properties"];
+        for field in ["series", "totalRaw", "totalProcessed", "cacheHit", "timing"] {
+            assert!(properties.get(field).is_some(), "缺少字段 {}", field);
+        }
+        assert_eq!(properties["totalRaw"]["type"], "integer");
+        assert_eq!(properties["series"]["type"], "array");
+        let required = schema["required"].as_array().unwrap();
+        assert!(required.iter().any(|f| f == "totalRaw"));
+
+        // 嵌套类型的定义
+        let series = &schema["definitions"]["ChartSeriesData"]["properties"];
+        assert!(series.get("tagName").is_some());
+        assert!(series.get("data").is_some());
+
+        // 所有登记的类型都能生成，未知类型返回验证错误
+        for name in API_SCHEMA_TYPES {
+            assert_eq!(api_schema(name).unwrap()["title"], *name);
+        }
+        assert!(api_schema("Unknown").is_err());
+    }
+}
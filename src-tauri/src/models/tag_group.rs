@@ -1,8 +1,133 @@
 //! 标签分组数据模型
 
 use super::DataProcessingConfig;
-use chrono::Local;
+use chrono::{Local, NaiveDateTime};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+/// 标签报警线配置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TagAlarm {
+    /// 标签名称
+    pub tag: String,
+    /// 报警上限
+    #[serde(default)]
+    pub alarm_high: Option<f64>,
+    /// 报警下限
+    #[serde(default)]
+    pub alarm_low: Option<f64>,
+}
+
+impl TagAlarm {
+    /// 是否配置了任一报警线
+    pub fn has_limits(&self) -> bool {
+        self.alarm_high.is_some() || self.alarm_low.is_some()
+    }
+}
+
+/// 最大 Y 轴索引（0 为左轴，1 为右轴）
+pub const MAX_Y_AXIS_INDEX: u8 = 1;
+
+/// 标签所用的 Y 轴（双 Y 轴图表，量纲差异大的标签分开显示）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TagAxis {
+    /// 标签名称
+    pub tag: String,
+    /// Y 轴索引：0 左轴，1 右轴
+    #[serde(default)]
+    pub y_axis_index: u8,
+}
+
+/// 标签显示别名（图例、tooltip 中替代原始标签名）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TagAlias {
+    /// 标签名称
+    pub tag: String,
+    /// 显示名称
+    pub alias: String,
+}
+
+/// 标签值映射（离散状态标签的数字码 → 显示文本，如 `"0"` → `"停止"`）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TagValueMap {
+    /// 标签名称
+    pub tag: String,
+    /// 值映射表，键为数值的文本形式
+    #[serde(default)]
+    pub value_map: HashMap<String, String>,
+}
+
+/// 标签元数据（分组内各图表共用）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TagMetadata {
+    /// 标签名称
+    pub tag: String,
+    /// 工程单位（如 `℃`、`MPa`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+}
+
+/// 图上标注区间（批次、事件等关注区间）
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Annotation {
+    /// 标注唯一标识符（新增时为空则自动生成）
+    #[serde(default)]
+    pub id: String,
+    /// 开始时间
+    pub start: String,
+    /// 结束时间
+    pub end: String,
+    /// 标注文字
+    #[serde(default)]
+    pub label: String,
+    /// 显示颜色（如 `#ff000033`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+/// 解析标注/查询时间（`%Y-%m-%dT%H:%M:%S`，可带毫秒）
+fn parse_annotation_time(value: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value.trim(), "%Y-%m-%dT%H:%M:%S%.f").ok()
+}
+
+impl Annotation {
+    /// 验证标注时间区间
+    pub fn validate(&self) -> Result<(), String> {
+        let start = parse_annotation_time(&self.start)
+            .ok_or_else(|| format!("标注开始时间格式无效: {}", self.start))?;
+        let end = parse_annotation_time(&self.end)
+            .ok_or_else(|| format!("标注结束时间格式无效: {}", self.end))?;
+        if start > end {
+            return Err(format!(
+                "标注开始时间 {} 晚于结束时间 {}",
+                self.start, self.end
+            ));
+        }
+        Ok(())
+    }
+
+    /// 是否与给定时间范围重叠（时间无法解析时视为重叠）
+    pub fn overlaps(&self, start: &str, end: &str) -> bool {
+        match (
+            parse_annotation_time(&self.start),
+            parse_annotation_time(&self.end),
+            parse_annotation_time(start),
+            parse_annotation_time(end),
+        ) {
+            (Some(a_start), Some(a_end), Some(start), Some(end)) => {
+                a_start <= end && a_end >= start
+            }
+            _ => true,
+        }
+    }
+}
 
 /// 图表配置（分组内的单个图表）
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -15,6 +140,18 @@ pub struct ChartConfig {
     /// 包含的标签列表（最多 5 个）
     #[serde(default)]
     pub tags: Vec<String>,
+    /// 标签报警线配置
+    #[serde(default)]
+    pub alarms: Vec<TagAlarm>,
+    /// 标签 Y 轴配置（未配置的标签使用左轴）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub axes: Vec<TagAxis>,
+    /// 标签显示别名（未配置的标签显示原名）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<TagAlias>,
+    /// 标签值映射（未配置的标签只返回数值）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub value_maps: Vec<TagValueMap>,
 }
 
 impl ChartConfig {
@@ -24,6 +161,10 @@ impl ChartConfig {
             id: format!("c{}", Local::now().timestamp_millis()),
             name,
             tags: Vec::new(),
+            alarms: Vec::new(),
+            axes: Vec::new(),
+            aliases: Vec::new(),
+            value_maps: Vec::new(),
         }
     }
 
@@ -33,6 +174,10 @@ impl ChartConfig {
             id,
             name,
             tags: Vec::new(),
+            alarms: Vec::new(),
+            axes: Vec::new(),
+            aliases: Vec::new(),
+            value_maps: Vec::new(),
         }
     }
 
@@ -42,6 +187,54 @@ impl ChartConfig {
         self
     }
 
+    /// 设置报警线
+    pub fn with_alarms(mut self, alarms: Vec<TagAlarm>) -> Self {
+        self.alarms = alarms;
+        self
+    }
+
+    /// 设置标签 Y 轴
+    pub fn with_axes(mut self, axes: Vec<TagAxis>) -> Self {
+        self.axes = axes;
+        self
+    }
+
+    /// 设置标签别名
+    pub fn with_aliases(mut self, aliases: Vec<TagAlias>) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
+    /// 设置标签值映射
+    pub fn with_value_maps(mut self, value_maps: Vec<TagValueMap>) -> Self {
+        self.value_maps = value_maps;
+        self
+    }
+
+    /// 获取标签的值映射表（未配置时为 `None`）
+    pub fn value_map(&self, tag: &str) -> Option<&HashMap<String, String>> {
+        self.value_maps
+            .iter()
+            .find(|m| m.tag == tag)
+            .map(|m| &m.value_map)
+    }
+
+    /// 获取标签的显示别名（未配置时为 `None`）
+    pub fn alias(&self, tag: &str) -> Option<&str> {
+        self.aliases
+            .iter()
+            .find(|a| a.tag == tag)
+            .map(|a| a.alias.as_str())
+    }
+
+    /// 获取标签所在的 Y 轴索引（未配置时为 0）
+    pub fn y_axis_index(&self, tag: &str) -> u8 {
+        self.axes
+            .iter()
+            .find(|a| a.tag == tag)
+            .map_or(0, |a| a.y_axis_index)
+    }
+
     /// 验证图表配置
     pub fn validate(&self) -> Result<(), String> {
         if self.tags.len() > 5 {
@@ -51,6 +244,24 @@ impl ChartConfig {
                 self.tags.len()
             ));
         }
+        for alarm in &self.alarms {
+            if let (Some(low), Some(high)) = (alarm.alarm_low, alarm.alarm_high)
+                && low > high
+            {
+                return Err(format!(
+                    "图表 '{}' 标签 '{}' 的报警下限 {} 大于上限 {}",
+                    self.name, alarm.tag, low, high
+                ));
+            }
+        }
+        for axis in &self.axes {
+            if axis.y_axis_index > MAX_Y_AXIS_INDEX {
+                return Err(format!(
+                    "图表 '{}' 标签 '{}' 的 Y 轴索引 {} 无效（只支持 0 或 1）",
+                    self.name, axis.tag, axis.y_axis_index
+                ));
+            }
+        }
         Ok(())
     }
 }
@@ -73,6 +284,21 @@ pub struct TagGroup {
     pub created_at: String,
     /// 更新时间
     pub updated_at: String,
+    /// 标注区间
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
+    /// 标签元数据（单位等）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tag_metadata: Vec<TagMetadata>,
+    /// 是否锁定（锁定的分组不参与批量修改）
+    #[serde(default)]
+    pub locked: bool,
+    /// 最近访问（查询）时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_accessed: Option<String>,
+    /// 累计访问（查询）次数
+    #[serde(default)]
+    pub access_count: u64,
 }
 
 impl TagGroup {
@@ -101,6 +327,11 @@ impl TagGroup {
             processing_config: DataProcessingConfig::default(),
             created_at: now.clone(),
             updated_at: now,
+            annotations: Vec::new(),
+            tag_metadata: Vec::new(),
+            locked: false,
+            last_accessed: None,
+            access_count: 0,
         })
     }
 
@@ -119,6 +350,11 @@ impl TagGroup {
             processing_config: DataProcessingConfig::default(),
             created_at,
             updated_at,
+            annotations: Vec::new(),
+            tag_metadata: Vec::new(),
+            locked: false,
+            last_accessed: None,
+            access_count: 0,
         }
     }
 
@@ -152,6 +388,184 @@ impl TagGroup {
         Ok(())
     }
 
+    /// 部分更新分组
+    ///
+    /// 仅更新提供的字段，未提供的字段保持不变。
+    pub fn patch(
+        &mut self,
+        name: Option<String>,
+        charts: Option<Vec<ChartConfig>>,
+        processing_config: Option<DataProcessingConfig>,
+    ) -> Result<(), String> {
+        let name = name.unwrap_or_else(|| self.name.clone());
+        let charts = charts.unwrap_or_else(|| self.charts.clone());
+        self.update(name, charts, processing_config)
+    }
+
+    /// 新增标注（ID 为空或重复时自动生成）
+    pub fn add_annotation(&mut self, mut annotation: Annotation) -> Result<Annotation, String> {
+        annotation.validate()?;
+        if annotation.id.is_empty() || self.annotations.iter().any(|a| a.id == annotation.id) {
+            let base = format!("a{}", Local::now().timestamp_millis());
+            let mut id = base.clone();
+            let mut suffix = 1;
+            while self.annotations.iter().any(|a| a.id == id) {
+                id = format!("{}_{}", base, suffix);
+                suffix += 1;
+            }
+            annotation.id = id;
+        }
+        self.annotations.push(annotation.clone());
+        Ok(annotation)
+    }
+
+    /// 按 ID 替换标注
+    pub fn update_annotation(&mut self, annotation: Annotation) -> Result<Annotation, String> {
+        annotation.validate()?;
+        let existing = self
+            .annotations
+            .iter_mut()
+            .find(|a| a.id == annotation.id)
+            .ok_or_else(|| format!("标注 '{}' 不存在", annotation.id))?;
+        *existing = annotation.clone();
+        Ok(annotation)
+    }
+
+    /// 按 ID 删除标注，返回是否存在
+    pub fn remove_annotation(&mut self, id: &str) -> bool {
+        let before = self.annotations.len();
+        self.annotations.retain(|a| a.id != id);
+        self.annotations.len() != before
+    }
+
+    /// 与时间范围重叠的标注
+    pub fn annotations_in_range(&self, start: &str, end: &str) -> Vec<Annotation> {
+        self.annotations
+            .iter()
+            .filter(|a| a.overlaps(start, end))
+            .cloned()
+            .collect()
+    }
+
+    /// 标签的值映射表（取第一个为该标签配置了映射的图表）
+    pub fn value_map(&self, tag: &str) -> Option<&HashMap<String, String>> {
+        self.charts.iter().find_map(|c| c.value_map(tag))
+    }
+
+    /// 替换标签元数据（标签名不能为空或重复，空白单位视为未设置）
+    pub fn set_tag_metadata(&mut self, metadata: Vec<TagMetadata>) -> Result<(), String> {
+        let mut seen = BTreeSet::new();
+        let mut cleaned = Vec::with_capacity(metadata.len());
+        for mut item in metadata {
+            item.tag = item.tag.trim().to_string();
+            if item.tag.is_empty() {
+                return Err("标签元数据的标签名不能为空".to_string());
+            }
+            if !seen.insert(item.tag.clone()) {
+                return Err(format!("标签 {} 的元数据重复", item.tag));
+            }
+            item.unit = item
+                .unit
+                .map(|u| u.trim().to_string())
+                .filter(|u| !u.is_empty());
+            cleaned.push(item);
+        }
+        self.tag_metadata = cleaned;
+        self.updated_at = Local::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+        Ok(())
+    }
+
+    /// 标签的工程单位（未配置时返回 `None`）
+    pub fn tag_unit(&self, tag: &str) -> Option<&str> {
+        self.tag_metadata
+            .iter()
+            .find(|m| m.tag == tag)
+            .and_then(|m| m.unit.as_deref())
+    }
+
+    /// 同一图表同一 Y 轴上标签单位不一致的提示
+    ///
+    /// `tags` 为本次查询的标签（`None` 表示全部），只检查其中配置了单位的标签。
+    pub fn unit_warnings(&self, tags: Option<&[String]>) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for chart in &self.charts {
+            for axis in 0..=MAX_Y_AXIS_INDEX {
+                let units: Vec<(&str, &str)> = chart
+                    .tags
+                    .iter()
+                    .filter(|tag| chart.y_axis_index(tag) == axis)
+                    .filter(|tag| tags.is_none_or(|tags| tags.contains(tag)))
+                    .filter_map(|tag| self.tag_unit(tag).map(|unit| (tag.as_str(), unit)))
+                    .collect();
+                if units.iter().all(|(_, unit)| *unit == units[0].1) {
+                    continue;
+                }
+                let detail: Vec<String> = units
+                    .iter()
+                    .map(|(tag, unit)| format!("{} ({})", tag, unit))
+                    .collect();
+                warnings.push(format!(
+                    "图表 '{}' 的{}轴上标签单位不一致: {}",
+                    chart.name,
+                    if axis == 0 { "左" } else { "右" },
+                    detail.join(", ")
+                ));
+            }
+        }
+        warnings
+    }
+
+    /// 记录一次访问（不修改 `updated_at`，访问不算配置变更）
+    pub fn record_access(&mut self) {
+        self.access_count += 1;
+        self.last_accessed = Some(Local::now().format("%Y-%m-%dT%H:%M:%S").to_string());
+    }
+
+    /// 获取所有图表中配置了报警线的标签（同一标签以首次出现为准）
+    pub fn tag_alarms(&self) -> Vec<TagAlarm> {
+        let mut alarms: Vec<TagAlarm> = Vec::new();
+        for alarm in self.charts.iter().flat_map(|c| c.alarms.iter()) {
+            if alarm.has_limits() && !alarms.iter().any(|a| a.tag == alarm.tag) {
+                alarms.push(alarm.clone());
+            }
+        }
+        alarms
+    }
+
+    /// 按映射表（旧标签名 → 新标签名）替换分组内引用的标签名
+    ///
+    /// 覆盖图表标签、报警线、Y 轴、别名、值映射、标签元数据和量程裁剪配置；映射后图表内重复的标签、
+    /// 同一标签的多条元数据和量程只保留第一个。
+    /// 返回映射表中没有的标签（保留原名，去重排序）。
+    pub fn remap_tags(&mut self, mapping: &HashMap<String, String>) -> Vec<String> {
+        let mut unmapped = BTreeSet::new();
+        let mut remap = |tag: &mut String| match mapping.get(tag.as_str()) {
+            Some(mapped) => *tag = mapped.clone(),
+            None => {
+                unmapped.insert(tag.clone());
+            }
+        };
+
+        for chart in &mut self.charts {
+            chart.tags.iter_mut().for_each(&mut remap);
+            let mut seen = BTreeSet::new();
+            chart.tags.retain(|tag| seen.insert(tag.clone()));
+            chart.alarms.iter_mut().for_each(|a| remap(&mut a.tag));
+            chart.axes.iter_mut().for_each(|a| remap(&mut a.tag));
+            chart.aliases.iter_mut().for_each(|a| remap(&mut a.tag));
+            chart.value_maps.iter_mut().for_each(|m| remap(&mut m.tag));
+        }
+        self.tag_metadata.iter_mut().for_each(|m| remap(&mut m.tag));
+        let mut seen = BTreeSet::new();
+        self.tag_metadata.retain(|m| seen.insert(m.tag.clone()));
+        let ranges = &mut self.processing_config.range_limit.ranges;
+        ranges.iter_mut().for_each(|r| remap(&mut r.tag));
+        let mut seen = BTreeSet::new();
+        ranges.retain(|r| seen.insert(r.tag.clone()));
+
+        unmapped.into_iter().collect()
+    }
+
     /// 获取所有图表中的标签（去重）
     pub fn all_tags(&self) -> Vec<String> {
         let mut tags: Vec<String> = self
@@ -165,6 +579,114 @@ impl TagGroup {
     }
 }
 
+/// 批量应用处理配置的结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchApplyResult {
+    /// 已更新的分组 ID
+    pub updated: Vec<String>,
+    /// 因锁定而跳过的分组 ID
+    pub skipped_locked: Vec<String>,
+    /// 不存在的分组 ID
+    pub not_found: Vec<String>,
+}
+
+/// 分组使用统计
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupUsageStats {
+    /// 分组 ID
+    pub id: String,
+    /// 分组名称
+    pub name: String,
+    /// 累计访问次数
+    pub access_count: u64,
+    /// 最近访问时间
+    pub last_accessed: Option<String>,
+}
+
+impl From<&TagGroup> for GroupUsageStats {
+    fn from(group: &TagGroup) -> Self {
+        Self {
+            id: group.id.clone(),
+            name: group.name.clone(),
+            access_count: group.access_count,
+            last_accessed: group.last_accessed.clone(),
+        }
+    }
+}
+
+/// 单个标签被分组/图表引用的情况
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TagUsage {
+    /// 标签名称
+    pub tag_name: String,
+    /// 引用该标签的分组数
+    pub group_count: usize,
+    /// 引用该标签的图表数
+    pub chart_count: usize,
+    /// 引用该标签的分组名称
+    pub groups: Vec<String>,
+}
+
+/// 批量导入分组的结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupImportResult {
+    /// 已导入的分组
+    pub groups: Vec<TagGroup>,
+    /// 映射表中没有的标签（保留原名，需用户处理）
+    pub unmapped_tags: Vec<String>,
+}
+
+/// 所有分组的标签引用汇总
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TagUsageSummary {
+    /// 唯一标签总数
+    pub unique_tags: usize,
+    /// 各标签引用情况（按分组数、图表数降序，再按标签名升序）
+    pub tags: Vec<TagUsage>,
+}
+
+impl TagUsageSummary {
+    /// 汇总各分组 `all_tags()` 与图表的标签引用
+    pub fn from_groups(groups: &[TagGroup]) -> Self {
+        let mut usage: HashMap<String, TagUsage> = HashMap::new();
+        for group in groups {
+            for tag in group.all_tags() {
+                let charts = group
+                    .charts
+                    .iter()
+                    .filter(|c| c.tags.contains(&tag))
+                    .count();
+                let entry = usage.entry(tag.clone()).or_insert_with(|| TagUsage {
+                    tag_name: tag,
+                    group_count: 0,
+                    chart_count: 0,
+                    groups: Vec::new(),
+                });
+                entry.group_count += 1;
+                entry.chart_count += charts;
+                entry.groups.push(group.name.clone());
+            }
+        }
+
+        let mut tags: Vec<TagUsage> = usage.into_values().collect();
+        tags.sort_by(|a, b| {
+            b.group_count
+                .cmp(&a.group_count)
+                .then_with(|| b.chart_count.cmp(&a.chart_count))
+                .then_with(|| a.tag_name.cmp(&b.tag_name))
+        });
+        Self {
+            unique_tags: tags.len(),
+            tags,
+        }
+    }
+}
+
 /// 标签分组配置文件结构
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TagGroupConfig {
@@ -173,6 +695,13 @@ pub struct TagGroupConfig {
     /// 所有分组
     #[serde(default)]
     pub groups: Vec<TagGroup>,
+    /// 分组判重时是否忽略大小写（默认开启）
+    #[serde(default = "default_case_insensitive_names")]
+    pub case_insensitive_names: bool,
+}
+
+fn default_case_insensitive_names() -> bool {
+    true
 }
 
 impl TagGroupConfig {
@@ -181,13 +710,175 @@ impl TagGroupConfig {
         Self {
             version: 1,
             groups: Vec::new(),
+            case_insensitive_names: default_case_insensitive_names(),
         }
     }
+
+    /// 判断两个分组名称是否视为重名
+    ///
+    /// 去除首尾空白并将连续空白合并为一个空格后比较，
+    /// `case_insensitive_names` 开启时忽略大小写。
+    pub fn names_conflict(&self, a: &str, b: &str) -> bool {
+        let (a, b) = (normalize_group_name(a), normalize_group_name(b));
+        if self.case_insensitive_names {
+            a.to_lowercase() == b.to_lowercase()
+        } else {
+            a == b
+        }
+    }
+}
+
+/// 规范化分组名称：去除首尾空白，连续空白合并为一个空格
+fn normalize_group_name(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::TagRange;
+
+    fn annotation(start: &str, end: &str, label: &str) -> Annotation {
+        Annotation {
+            id: String::new(),
+            start: start.to_string(),
+            end: end.to_string(),
+            label: label.to_string(),
+            color: None,
+        }
+    }
+
+    #[test]
+    fn test_tag_usage_across_groups() {
+        let chart = |name: &str, tags: &[&str]| {
+            ChartConfig::new(name.to_string())
+                .with_tags(tags.iter().map(|t| t.to_string()).collect())
+        };
+        let boiler = TagGroup::new(
+            "锅炉".to_string(),
+            vec![
+                chart("温度", &["TT_101", "TT_102"]),
+                chart("综合", &["TT_101", "PT_201"]),
+            ],
+        )
+        .unwrap();
+        let turbine = TagGroup::new(
+            "汽机".to_string(),
+            vec![chart("温度", &["TT_101"]), chart("压力", &["PT_201"])],
+        )
+        .unwrap();
+        let pump = TagGroup::new("泵".to_string(), vec![chart("流量", &["FT_301"])]).unwrap();
+
+        let summary = TagUsageSummary::from_groups(&[boiler, turbine, pump]);
+        assert_eq!(summary.unique_tags, 4);
+
+        let tt101 = &summary.tags[0];
+        assert_eq!(tt101.tag_name, "TT_101");
+        assert_eq!((tt101.group_count, tt101.chart_count), (2, 3));
+        assert_eq!(tt101.groups, vec!["锅炉", "汽机"]);
+
+        let pt201 = &summary.tags[1];
+        assert_eq!(pt201.tag_name, "PT_201");
+        assert_eq!((pt201.group_count, pt201.chart_count), (2, 2));
+
+        let names: Vec<&str> = summary.tags[2..]
+            .iter()
+            .map(|t| t.tag_name.as_str())
+            .collect();
+        assert_eq!(names, vec!["FT_301", "TT_102"]);
+        assert!(summary.tags[2..].iter().all(|t| t.group_count == 1));
+    }
+
+    #[test]
+    fn test_annotation_crud() {
+        let mut group = TagGroup::new("标注".to_string(), vec![]).unwrap();
+
+        let first = group
+            .add_annotation(annotation(
+                "2024-01-01T08:00:00",
+                "2024-01-01T10:00:00",
+                "批次1",
+            ))
+            .unwrap();
+        let second = group
+            .add_annotation(annotation(
+                "2024-01-02T08:00:00",
+                "2024-01-02T09:00:00",
+                "停机",
+            ))
+            .unwrap();
+        assert!(!first.id.is_empty());
+        assert_ne!(first.id, second.id);
+
+        let updated = Annotation {
+            label: "批次1-复检".to_string(),
+            color: Some("#ff000033".to_string()),
+            ..first.clone()
+        };
+        group.update_annotation(updated).unwrap();
+        assert_eq!(group.annotations[0].label, "批次1-复检");
+
+        assert!(group.remove_annotation(&second.id));
+        assert!(!group.remove_annotation(&second.id));
+        assert_eq!(group.annotations.len(), 1);
+
+        // 非法区间和不存在的 ID
+        assert!(
+            group
+                .add_annotation(annotation(
+                    "2024-01-02T00:00:00",
+                    "2024-01-01T00:00:00",
+                    "x"
+                ))
+                .is_err()
+        );
+        assert!(group.update_annotation(second).is_err());
+    }
+
+    #[test]
+    fn test_annotation_roundtrip() {
+        let mut group = TagGroup::new("标注".to_string(), vec![]).unwrap();
+        group
+            .add_annotation(Annotation {
+                color: Some("#00ff0033".to_string()),
+                ..annotation("2024-01-01T08:00:00.500", "2024-01-01T10:00:00", "批次1")
+            })
+            .unwrap();
+
+        let json = serde_json::to_string(&group).unwrap();
+        assert_eq!(serde_json::from_str::<TagGroup>(&json).unwrap(), group);
+
+        let config = TagGroupConfig {
+            groups: vec![group.clone()],
+            ..TagGroupConfig::new()
+        };
+        let toml_str = toml::to_string_pretty(&config).unwrap();
+        let parsed: TagGroupConfig = toml::from_str(&toml_str).unwrap();
+        assert_eq!(parsed.groups[0], group);
+    }
+
+    #[test]
+    fn test_annotations_in_range() {
+        let mut group = TagGroup::new("标注".to_string(), vec![]).unwrap();
+        group
+            .add_annotation(annotation(
+                "2024-01-01T08:00:00",
+                "2024-01-01T10:00:00",
+                "早",
+            ))
+            .unwrap();
+        group
+            .add_annotation(annotation(
+                "2024-01-03T08:00:00",
+                "2024-01-03T10:00:00",
+                "晚",
+            ))
+            .unwrap();
+
+        let hits = group.annotations_in_range("2024-01-01T09:00:00.000", "2024-01-02T00:00:00.000");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].label, "早");
+    }
 
     #[test]
     fn test_create_chart_config() {
@@ -211,6 +902,153 @@ mod tests {
         assert!(chart.validate().is_err());
     }
 
+    #[test]
+    fn test_chart_dual_y_axis() {
+        let axis = |tag: &str, index: u8| TagAxis {
+            tag: tag.to_string(),
+            y_axis_index: index,
+        };
+        let chart = ChartConfig::with_id("c1".to_string(), "温度压力".to_string())
+            .with_tags(vec!["TI_101".to_string(), "PI_101".to_string()])
+            .with_axes(vec![axis("PI_101", 1)]);
+        assert!(chart.validate().is_ok());
+        assert_eq!(chart.y_axis_index("TI_101"), 0);
+        assert_eq!(chart.y_axis_index("PI_101"), 1);
+
+        let json = serde_json::to_value(&chart).unwrap();
+        assert_eq!(
+            json["axes"],
+            serde_json::json!([{"tag": "PI_101", "yAxisIndex": 1}])
+        );
+        let parsed: ChartConfig = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, chart);
+
+        // 旧配置没有 axes 字段
+        let legacy: ChartConfig =
+            serde_json::from_str(r#"{"id":"c2","name":"旧","tags":["T1"]}"#).unwrap();
+        assert!(legacy.axes.is_empty());
+
+        let invalid = chart.with_axes(vec![axis("PI_101", 2)]);
+        let err = invalid.validate().unwrap_err();
+        assert!(err.contains("Y 轴索引 2"));
+    }
+
+    #[test]
+    fn test_unit_warnings_for_mixed_units() {
+        let chart = ChartConfig::with_id("c1".to_string(), "温度".to_string()).with_tags(vec![
+            "TI_101".to_string(),
+            "TI_102".to_string(),
+            "PI_101".to_string(),
+        ]);
+        let mut group = TagGroup::new("测试".to_string(), vec![chart]).unwrap();
+        let meta = |tag: &str, unit: &str| TagMetadata {
+            tag: tag.to_string(),
+            unit: Some(unit.to_string()),
+        };
+
+        // 单位一致（未配置单位的标签不参与比较）
+        group
+            .set_tag_metadata(vec![meta("TI_101", "℃"), meta("TI_102", " ℃ ")])
+            .unwrap();
+        assert_eq!(group.tag_unit("TI_102"), Some("℃"));
+        assert!(group.unit_warnings(None).is_empty());
+
+        // 同图同轴混用摄氏和华氏
+        group
+            .set_tag_metadata(vec![meta("TI_101", "℃"), meta("TI_102", "℉")])
+            .unwrap();
+        let warnings = group.unit_warnings(None);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("TI_101 (℃)") && warnings[0].contains("TI_102 (℉)"));
+        // 只查询其中一个标签时不提示
+        assert!(
+            group
+                .unit_warnings(Some(&["TI_101".to_string()]))
+                .is_empty()
+        );
+
+        // 不同单位分在左右两轴是正常的双轴用法
+        group.charts[0] = group.charts[0].clone().with_axes(vec![TagAxis {
+            tag: "TI_102".to_string(),
+            y_axis_index: 1,
+        }]);
+        assert!(group.unit_warnings(None).is_empty());
+
+        assert!(
+            group
+                .set_tag_metadata(vec![meta("TI_101", "℃"), meta("TI_101", "℉")])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_remap_tags_dedupes_metadata_and_ranges() {
+        let chart = ChartConfig::with_id("c1".to_string(), "温度".to_string())
+            .with_tags(vec!["TI_101_OLD".to_string(), "TI_101".to_string()]);
+        let mut group = TagGroup::new("测试".to_string(), vec![chart]).unwrap();
+        group
+            .set_tag_metadata(vec![
+                TagMetadata {
+                    tag: "TI_101_OLD".to_string(),
+                    unit: Some("℃".to_string()),
+                },
+                TagMetadata {
+                    tag: "TI_101".to_string(),
+                    unit: Some("℉".to_string()),
+                },
+            ])
+            .unwrap();
+        let range = |tag: &str, max: f64| TagRange {
+            tag: tag.to_string(),
+            min: 0.0,
+            max,
+        };
+        group.processing_config = DataProcessingConfig::new().with_range_limit(
+            vec![range("TI_101_OLD", 100.0), range("TI_101", 200.0)],
+            "drop",
+        );
+
+        let mapping = HashMap::from([("TI_101_OLD".to_string(), "TI_101".to_string())]);
+        group.remap_tags(&mapping);
+
+        assert_eq!(group.charts[0].tags, vec!["TI_101".to_string()]);
+        assert_eq!(group.tag_metadata.len(), 1);
+        assert_eq!(group.tag_unit("TI_101"), Some("℃"));
+        let ranges = &group.processing_config.range_limit.ranges;
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(ranges[0].max, 100.0);
+        // 去重后的元数据仍可原样重新设置
+        assert!(group.set_tag_metadata(group.tag_metadata.clone()).is_ok());
+    }
+
+    #[test]
+    fn test_chart_alarm_validation() {
+        let chart = ChartConfig::new("报警".to_string()).with_alarms(vec![TagAlarm {
+            tag: "T1".to_string(),
+            alarm_high: Some(10.0),
+            alarm_low: Some(20.0),
+        }]);
+        assert!(chart.validate().is_err());
+    }
+
+    #[test]
+    fn test_group_tag_alarms() {
+        let alarm = |tag: &str, high: Option<f64>| TagAlarm {
+            tag: tag.to_string(),
+            alarm_high: high,
+            alarm_low: None,
+        };
+        let chart1 = ChartConfig::new("图表1".to_string())
+            .with_alarms(vec![alarm("T1", Some(10.0)), alarm("T2", None)]);
+        let chart2 =
+            ChartConfig::new("图表2".to_string()).with_alarms(vec![alarm("T1", Some(99.0))]);
+        let group = TagGroup::new("报警分组".to_string(), vec![chart1, chart2]).unwrap();
+
+        let alarms = group.tag_alarms();
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].alarm_high, Some(10.0));
+    }
+
     #[test]
     fn test_create_tag_group() {
         let chart = ChartConfig::new("图表1".to_string());
@@ -263,4 +1101,31 @@ mod tests {
         assert_eq!(group.charts.len(), 1);
         assert_eq!(group.charts[0].name, "新图表");
     }
+
+    #[test]
+    fn test_patch_group_name_only() {
+        let chart = ChartConfig::new("图表1".to_string()).with_tags(vec!["tag1".to_string()]);
+        let mut group = TagGroup::new("原名称".to_string(), vec![chart.clone()]).unwrap();
+        group.processing_config = DataProcessingConfig::new().with_smoothing(5, "moving_avg");
+        let processing_config = group.processing_config.clone();
+
+        group.patch(Some("新名称".to_string()), None, None).unwrap();
+
+        assert_eq!(group.name, "新名称");
+        assert_eq!(group.charts, vec![chart]);
+        assert_eq!(group.processing_config, processing_config);
+    }
+
+    #[test]
+    fn test_patch_group_processing_only() {
+        let chart = ChartConfig::new("图表1".to_string());
+        let mut group = TagGroup::new("分组".to_string(), vec![chart]).unwrap();
+        let config = DataProcessingConfig::new().with_resample(60, "mean");
+
+        group.patch(None, None, Some(config.clone())).unwrap();
+
+        assert_eq!(group.name, "分组");
+        assert_eq!(group.charts.len(), 1);
+        assert_eq!(group.processing_config, config);
+    }
 }
@@ -0,0 +1,135 @@
+//! 标签层级树数据模型
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 标签树节点
+///
+/// 标签名按分隔符拆分后的层级（如 `Unit1.Boiler.Temp` → `Unit1` / `Boiler`），
+/// 最后一段作为叶子，以标签全名记录在所属节点的 `tags` 中。
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TagTreeNode {
+    /// 节点名（当前层级的片段，根节点为空）
+    pub name: String,
+    /// 节点完整路径（如 `Unit1.Boiler`，根节点为空）
+    pub path: String,
+    /// 子节点（按名称排序）
+    pub children: Vec<TagTreeNode>,
+    /// 直接属于该节点的叶子标签全名（按名称排序）
+    pub tags: Vec<String>,
+}
+
+/// 构建过程中的中间节点（BTreeMap 保证有序）
+#[derive(Default)]
+struct NodeBuilder {
+    children: BTreeMap<String, NodeBuilder>,
+    tags: Vec<String>,
+}
+
+impl NodeBuilder {
+    fn into_node(self, name: String, path: String, separator: &str) -> TagTreeNode {
+        let children = self
+            .children
+            .into_iter()
+            .map(|(child_name, child)| {
+                let child_path = if path.is_empty() {
+                    child_name.clone()
+                } else {
+                    format!("{}{}{}", path, separator, child_name)
+                };
+                child.into_node(child_name, child_path, separator)
+            })
+            .collect();
+
+        let mut tags = self.tags;
+        tags.sort();
+        tags.dedup();
+
+        TagTreeNode {
+            name,
+            path,
+            children,
+            tags,
+        }
+    }
+}
+
+impl TagTreeNode {
+    /// 按分隔符将标签列表构建为树，返回根节点
+    ///
+    /// 不含分隔符的标签作为根节点的叶子；空片段（如 `A..B`）会被忽略。
+    pub fn build(tags: &[String], separator: &str) -> Self {
+        let mut root = NodeBuilder::default();
+
+        for tag in tags {
+            let segments: Vec<&str> = if separator.is_empty() {
+                vec![tag.as_str()]
+            } else {
+                tag.split(separator).filter(|s| !s.is_empty()).collect()
+            };
+
+            let mut node = &mut root;
+            if let Some((_, parents)) = segments.split_last() {
+                for segment in parents {
+                    node = node.children.entry(segment.to_string()).or_default();
+                }
+            }
+            node.tags.push(tag.clone());
+        }
+
+        root.into_node(String::new(), String::new(), separator)
+    }
+
+    /// 子树中的叶子标签总数
+    pub fn tag_count(&self) -> usize {
+        self.tags.len() + self.children.iter().map(|c| c.tag_count()).sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_build_multi_level_tree() {
+        let tree = TagTreeNode::build(
+            &tags(&[
+                "Unit1.Boiler.Temp",
+                "Unit1.Boiler.Pressure",
+                "Unit1.Turbine.Speed",
+                "Unit2.Boiler.Temp",
+            ]),
+            ".",
+        );
+
+        assert_eq!(tree.tag_count(), 4);
+        assert_eq!(tree.children.len(), 2);
+
+        let unit1 = &tree.children[0];
+        assert_eq!(unit1.name, "Unit1");
+        assert_eq!(unit1.children.len(), 2);
+
+        let boiler = &unit1.children[0];
+        assert_eq!(boiler.name, "Boiler");
+        assert_eq!(boiler.path, "Unit1.Boiler");
+        assert_eq!(
+            boiler.tags,
+            tags(&["Unit1.Boiler.Pressure", "Unit1.Boiler.Temp"])
+        );
+        assert!(boiler.children.is_empty());
+    }
+
+    #[test]
+    fn test_tags_without_separator_are_root_leaves() {
+        let tree = TagTreeNode::build(&tags(&["FIC101", "Unit1.Temp", "AIC200"]), ".");
+
+        assert_eq!(tree.tags, tags(&["AIC200", "FIC101"]));
+        assert_eq!(tree.children.len(), 1);
+        assert_eq!(tree.children[0].tags, tags(&["Unit1.Temp"]));
+    }
+}
@@ -0,0 +1,83 @@
+//! 报警线越限统计
+
+use crate::models::{AlarmStats, ChartSeriesData, TagAlarm};
+
+/// 按报警线配置统计各标签的越限次数和越限时长
+///
+/// 越限时长按“越限点到下一个点”的时间差累计，最后一个点不计时长。
+/// 未配置报警线或无对应系列数据的标签会被跳过。
+pub fn compute_alarm_stats(series: &[ChartSeriesData], alarms: &[TagAlarm]) -> Vec<AlarmStats> {
+    alarms
+        .iter()
+        .filter(|alarm| alarm.has_limits())
+        .filter_map(|alarm| {
+            let data = &series.iter().find(|s| s.tag_name == alarm.tag)?.data;
+            let mut stats = AlarmStats {
+                tag_name: alarm.tag.clone(),
+                alarm_high: alarm.alarm_high,
+                alarm_low: alarm.alarm_low,
+                total_points: data.len(),
+                ..Default::default()
+            };
+
+            for (i, point) in data.iter().enumerate() {
+                let duration = data
+                    .get(i + 1)
+                    .map(|next| next[0] - point[0])
+                    .unwrap_or(0.0);
+                if alarm.alarm_high.is_some_and(|high| point[1] > high) {
+                    stats.high_count += 1;
+                    stats.high_duration_ms += duration;
+                } else if alarm.alarm_low.is_some_and(|low| point[1] < low) {
+                    stats.low_count += 1;
+                    stats.low_duration_ms += duration;
+                }
+            }
+
+            Some(stats)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processing::test_series as series;
+
+    #[test]
+    fn test_compute_alarm_stats() {
+        let data = vec![series("T1", &[5.0, 12.0, 15.0, 8.0, -3.0, 0.0])];
+        let alarms = vec![TagAlarm {
+            tag: "T1".to_string(),
+            alarm_high: Some(10.0),
+            alarm_low: Some(-1.0),
+        }];
+
+        let stats = compute_alarm_stats(&data, &alarms);
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].total_points, 6);
+        assert_eq!(stats[0].high_count, 2);
+        assert_eq!(stats[0].high_duration_ms, 2000.0);
+        assert_eq!(stats[0].low_count, 1);
+        assert_eq!(stats[0].low_duration_ms, 1000.0);
+    }
+
+    #[test]
+    fn test_compute_alarm_stats_skips_without_limits() {
+        let data = vec![series("T1", &[5.0, 12.0]), series("T2", &[100.0])];
+        let alarms = vec![
+            TagAlarm {
+                tag: "T1".to_string(),
+                alarm_high: None,
+                alarm_low: None,
+            },
+            TagAlarm {
+                tag: "Missing".to_string(),
+                alarm_high: Some(1.0),
+                alarm_low: None,
+            },
+        ];
+
+        assert!(compute_alarm_stats(&data, &alarms).is_empty());
+    }
+}
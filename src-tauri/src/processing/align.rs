@@ -0,0 +1,299 @@
+//! 多标签时间对齐
+//!
+//! 将各标签的系列数据重采样到共同的时间网格，便于前端联动 tooltip 取值。
+
+use crate::models::{ChartSeriesData, SeriesAlignConfig};
+
+use super::estimate_sample_interval_ms;
+
+/// 对齐网格的最大点数（超过时自动放大间隔）
+const MAX_GRID_POINTS: usize = 100_000;
+
+/// 将所有系列对齐到共同的时间网格
+///
+/// 网格间隔优先使用配置值，否则取各标签估算采样间隔中最粗的一个。
+/// 缺失点按 `fill` 处理：`"linear"` 线性插值，`"none"` 留空（NaN，序列化为 null）。
+/// 无法确定间隔时（如每个标签只有一个点），使用所有时间戳的并集作为网格。
+pub fn align_series(
+    series: Vec<ChartSeriesData>,
+    config: &SeriesAlignConfig,
+) -> Vec<ChartSeriesData> {
+    let (min_ts, max_ts) = match time_bounds(&series) {
+        Some(bounds) => bounds,
+        None => return series,
+    };
+
+    let interval = config
+        .interval_ms
+        .map(|ms| ms as f64)
+        .filter(|ms| *ms > 0.0)
+        .or_else(|| {
+            series
+                .iter()
+                .filter_map(|s| estimate_sample_interval_ms(&s.data))
+                .reduce(f64::max)
+        });
+
+    // 网格点数超限时间隔会被放大，分桶均值使用放大后的间隔
+    let (grid, interval) = match interval {
+        Some(interval) => build_grid(min_ts, max_ts, interval),
+        None => (union_timestamps(&series), 0.0),
+    };
+    let interpolate = config.fill != "none";
+
+    series
+        .into_iter()
+        .map(|s| {
+            let data = grid
+                .iter()
+                .map(|&t| {
+                    let value = if interpolate {
+                        interpolate_at(&s.data, t)
+                    } else {
+                        bucket_mean(&s.data, t, interval)
+                    };
+                    [t, value]
+                })
+                .collect();
+            ChartSeriesData::new(s.tag_name, data)
+        })
+        .collect()
+}
+
+/// 序列在 `t` 时刻的值（恰有采样点时取该点，否则前后两点线性插值）
+///
+/// `data` 需按时间升序；`t` 超出数据范围时返回 `None`。
+pub fn value_at(data: &[[f64; 2]], t: f64) -> Option<f64> {
+    Some(interpolate_at(data, t)).filter(|v| !v.is_nan())
+}
+
+/// 差值序列的标签名，如 `A-B`
+pub fn difference_tag_name(tag_a: &str, tag_b: &str) -> String {
+    format!("{}-{}", tag_a, tag_b)
+}
+
+/// 计算两条序列的逐点差值（A 减 B）
+///
+/// 取两条序列时间戳的并集中落在共同时间范围内的点，两边分别线性插值后相减，
+/// 时间完全一致时即逐点相减。无重叠时间范围时返回空序列。
+pub fn difference_series(a: &ChartSeriesData, b: &ChartSeriesData) -> ChartSeriesData {
+    let tag_name = difference_tag_name(&a.tag_name, &b.tag_name);
+    let (a_data, b_data) = (valid_points(&a.data), valid_points(&b.data));
+    let (Some(a_first), Some(a_last), Some(b_first), Some(b_last)) =
+        (a_data.first(), a_data.last(), b_data.first(), b_data.last())
+    else {
+        return ChartSeriesData::new(tag_name, Vec::new());
+    };
+    let start = a_first[0].max(b_first[0]);
+    let end = a_last[0].min(b_last[0]);
+
+    let pair = [
+        ChartSeriesData::new(String::new(), a_data.clone()),
+        ChartSeriesData::new(String::new(), b_data.clone()),
+    ];
+    let data = union_timestamps(&pair)
+        .into_iter()
+        .filter(|&t| t >= start && t <= end)
+        .map(|t| [t, interpolate_at(&a_data, t) - interpolate_at(&b_data, t)])
+        .collect();
+
+    ChartSeriesData::new(tag_name, data)
+}
+
+/// 去掉缺失值（NaN）的点，避免插值传播 NaN
+fn valid_points(data: &[[f64; 2]]) -> Vec<[f64; 2]> {
+    data.iter().copied().filter(|p| !p[1].is_nan()).collect()
+}
+
+/// 所有系列的时间范围
+fn time_bounds(series: &[ChartSeriesData]) -> Option<(f64, f64)> {
+    series
+        .iter()
+        .flat_map(|s| s.data.iter().map(|p| p[0]))
+        .fold(None, |acc, t| match acc {
+            None => Some((t, t)),
+            Some((min, max)) => Some((min.min(t), max.max(t))),
+        })
+}
+
+/// 生成等间隔网格（起点按间隔向下取整），返回网格和实际使用的间隔
+fn build_grid(min_ts: f64, max_ts: f64, interval: f64) -> (Vec<f64>, f64) {
+    let mut interval = interval;
+    let start = (min_ts / interval).floor() * interval;
+    let span = max_ts - start;
+    if span / interval > MAX_GRID_POINTS as f64 {
+        interval = span / MAX_GRID_POINTS as f64;
+    }
+
+    let count = (span / interval).floor() as usize + 1;
+    let grid = (0..count).map(|i| start + i as f64 * interval).collect();
+    (grid, interval)
+}
+
+/// 所有时间戳的并集（已排序去重）
+fn union_timestamps(series: &[ChartSeriesData]) -> Vec<f64> {
+    let mut timestamps: Vec<f64> = series
+        .iter()
+        .flat_map(|s| s.data.iter().map(|p| p[0]))
+        .collect();
+    timestamps.sort_by(|a, b| a.total_cmp(b));
+    timestamps.dedup();
+    timestamps
+}
+
+/// 在时间 t 处线性插值（超出数据范围返回 NaN）
+fn interpolate_at(data: &[[f64; 2]], t: f64) -> f64 {
+    let idx = data.partition_point(|p| p[0] < t);
+    match (idx.checked_sub(1).and_then(|i| data.get(i)), data.get(idx)) {
+        (_, Some(next)) if next[0] == t => next[1],
+        (Some(prev), Some(next)) => {
+            let ratio = (t - prev[0]) / (next[0] - prev[0]);
+            prev[1] + (next[1] - prev[1]) * ratio
+        }
+        _ => f64::NAN,
+    }
+}
+
+/// 计算 [t, t + interval) 区间内的均值（无数据返回 NaN）
+fn bucket_mean(data: &[[f64; 2]], t: f64, interval: f64) -> f64 {
+    let start = data.partition_point(|p| p[0] < t);
+    let end = if interval > 0.0 {
+        data.partition_point(|p| p[0] < t + interval)
+    } else {
+        data.partition_point(|p| p[0] <= t)
+    };
+
+    let bucket = &data[start..end];
+    if bucket.is_empty() {
+        f64::NAN
+    } else {
+        bucket.iter().map(|p| p[1]).sum::<f64>() / bucket.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_difference_same_timestamps() {
+        let a = ChartSeriesData::new(
+            "TI_IN".to_string(),
+            vec![[0.0, 80.0], [1000.0, 82.0], [2000.0, 85.0]],
+        );
+        let b = ChartSeriesData::new(
+            "TI_OUT".to_string(),
+            vec![[0.0, 60.0], [1000.0, 61.0], [2000.0, 63.0]],
+        );
+
+        let diff = difference_series(&a, &b);
+        assert_eq!(diff.tag_name, "TI_IN-TI_OUT");
+        assert_eq!(diff.data, vec![[0.0, 20.0], [1000.0, 21.0], [2000.0, 22.0]]);
+    }
+
+    #[test]
+    fn test_difference_misaligned_timestamps() {
+        // A 每秒一个点，B 错开 500ms 且每 2 秒一个点
+        let a = ChartSeriesData::new(
+            "A".to_string(),
+            vec![[0.0, 10.0], [1000.0, 20.0], [2000.0, 30.0], [3000.0, 40.0]],
+        );
+        let b = ChartSeriesData::new("B".to_string(), vec![[500.0, 0.0], [2500.0, 4.0]]);
+
+        let diff = difference_series(&a, &b);
+        // 只保留共同时间范围 [500, 2500] 内的并集时间点，两边插值后相减
+        assert_eq!(
+            diff.data,
+            vec![
+                [500.0, 15.0],
+                [1000.0, 19.0],
+                [2000.0, 27.0],
+                [2500.0, 31.0]
+            ]
+        );
+    }
+
+    fn series(tag: &str, start: f64, step: f64, count: usize) -> ChartSeriesData {
+        ChartSeriesData::new(
+            tag.to_string(),
+            (0..count)
+                .map(|i| [start + i as f64 * step, i as f64])
+                .collect(),
+        )
+    }
+
+    fn timestamps(s: &ChartSeriesData) -> Vec<f64> {
+        s.data.iter().map(|p| p[0]).collect()
+    }
+
+    #[test]
+    fn test_align_series_same_timestamps() {
+        let input = vec![
+            series("A", 0.0, 1000.0, 60),
+            series("B", 500.0, 5000.0, 12),
+            series("C", 3000.0, 2000.0, 20),
+        ];
+
+        let aligned = align_series(input, &SeriesAlignConfig::enabled());
+        let grid = timestamps(&aligned[0]);
+        assert!(!grid.is_empty());
+        for s in &aligned {
+            assert_eq!(timestamps(s), grid);
+        }
+        // 最粗间隔为 5 秒
+        assert_eq!(grid[1] - grid[0], 5000.0);
+    }
+
+    #[test]
+    fn test_align_series_with_interval() {
+        let input = vec![series("A", 0.0, 1000.0, 10), series("B", 0.0, 3000.0, 4)];
+        let config = SeriesAlignConfig {
+            interval_ms: Some(2000),
+            ..SeriesAlignConfig::enabled()
+        };
+
+        let aligned = align_series(input, &config);
+        assert_eq!(
+            timestamps(&aligned[0]),
+            vec![0.0, 2000.0, 4000.0, 6000.0, 8000.0]
+        );
+        assert_eq!(timestamps(&aligned[0]), timestamps(&aligned[1]));
+        // B 在 2000ms 处插值: 0 + (1 - 0) * 2/3
+        assert!((aligned[1].data[1][1] - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_align_series_fill_none() {
+        let input = vec![series("A", 0.0, 1000.0, 10), series("B", 0.0, 4000.0, 2)];
+        let config = SeriesAlignConfig {
+            interval_ms: Some(2000),
+            fill: "none".to_string(),
+            ..SeriesAlignConfig::enabled()
+        };
+
+        let aligned = align_series(input, &config);
+        assert_eq!(timestamps(&aligned[0]), timestamps(&aligned[1]));
+        assert!(aligned[1].data[1][1].is_nan());
+        assert_eq!(aligned[0].data[1][1], 2.5);
+
+        // 网格超限放大间隔后，分桶均值覆盖整个放大后的区间
+        let dense = vec![series("A", 0.0, 1.0, MAX_GRID_POINTS * 4 + 1)];
+        let config = SeriesAlignConfig {
+            interval_ms: Some(1),
+            fill: "none".to_string(),
+            ..SeriesAlignConfig::enabled()
+        };
+        let aligned = align_series(dense, &config);
+        assert_eq!(aligned[0].data.len(), MAX_GRID_POINTS + 1);
+        assert_eq!(aligned[0].data[1][0], 4.0);
+        // [4, 8) 内的值 4、5、6、7 的均值
+        assert_eq!(aligned[0].data[1][1], 5.5);
+
+        assert!(config.validate().is_ok());
+        let invalid = SeriesAlignConfig {
+            fill: "previous".to_string(),
+            ..SeriesAlignConfig::enabled()
+        };
+        assert!(invalid.validate().is_err());
+    }
+}
@@ -0,0 +1,162 @@
+//! 处理性能基准（现场诊断）
+//!
+//! 生成指定规模的模拟数据，分别测量各处理阶段的耗时，
+//! 与查询耗时（`QueryTiming`）对比可判断瓶颈在数据处理还是数据库。
+
+use chrono::{Duration, NaiveDate};
+use serde::Serialize;
+use std::time::Instant;
+
+use super::{
+    downsample, process_data, process_data_polars, records_to_dataframe, records_to_series,
+};
+use crate::error::{AppError, AppResult};
+use crate::models::{DataProcessingConfig, HistoryRecord};
+
+/// 基准允许的最大总行数
+pub const MAX_BENCHMARK_ROWS: usize = 2_000_000;
+
+/// 单个阶段的耗时
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkStage {
+    /// 阶段名称（对应的函数名）
+    pub name: String,
+    /// 耗时（毫秒，保留亚毫秒精度）
+    pub duration_ms: f64,
+    /// 阶段输出的行数（系列转换为数据点数）
+    pub output_rows: usize,
+}
+
+/// 基准结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkResult {
+    /// 生成的总行数
+    pub rows: usize,
+    /// 标签数
+    pub tags: usize,
+    pub stages: Vec<BenchmarkStage>,
+}
+
+/// 运行处理性能基准
+///
+/// 共生成 `rows` 条记录，均分到 `tags` 个标签（1 秒间隔），处理配置为
+/// 3σ 异常值剔除 + 1 分钟均值重采样 + 5 点平滑。
+pub fn run_benchmark(rows: usize, tags: usize) -> AppResult<BenchmarkResult> {
+    if rows == 0 || tags == 0 || tags > rows || rows > MAX_BENCHMARK_ROWS {
+        return Err(AppError::Validation(format!(
+            "基准规模无效: rows = {}, tags = {}（要求 1 ≤ tags ≤ rows ≤ {}）",
+            rows, tags, MAX_BENCHMARK_ROWS
+        )));
+    }
+
+    let records = generate_records(rows, tags);
+    let config = DataProcessingConfig::new()
+        .with_outlier_removal("3sigma")
+        .with_resample(60, "mean")
+        .with_smoothing(5, "moving_avg");
+    let mut stages = Vec::with_capacity(5);
+    let mut measure = |name: &str, started: Instant, output_rows: usize| {
+        stages.push(BenchmarkStage {
+            name: name.to_string(),
+            duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+            output_rows,
+        });
+    };
+
+    let started = Instant::now();
+    records_to_dataframe(&records)?;
+    measure("records_to_dataframe", started, records.len());
+
+    let input = records.clone();
+    let started = Instant::now();
+    let processed = process_data(input, &config)?;
+    measure("process_data", started, processed.len());
+
+    let input = records.clone();
+    let started = Instant::now();
+    let processed_polars = process_data_polars(input, &config)?;
+    measure("process_data_polars", started, processed_polars.len());
+
+    let started = Instant::now();
+    let sampled = downsample(records, 5000)?;
+    measure("downsample", started, sampled.len());
+
+    let started = Instant::now();
+    let series = records_to_series(&sampled);
+    measure(
+        "records_to_series",
+        started,
+        series.iter().map(|s| s.data.len()).sum(),
+    );
+
+    Ok(BenchmarkResult { rows, tags, stages })
+}
+
+/// 生成模拟数据：各标签为带周期波动的正弦信号，行数均分（余数分给前几个标签）
+fn generate_records(rows: usize, tags: usize) -> Vec<HistoryRecord> {
+    let start = NaiveDate::from_ymd_opt(2024, 1, 1)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .expect("valid start time");
+    let mut records = Vec::with_capacity(rows);
+    for tag in 0..tags {
+        let count = rows / tags + usize::from(tag < rows % tags);
+        let tag_name = format!("BENCH_{:03}", tag);
+        for i in 0..count {
+            let time = start + Duration::seconds(i as i64);
+            let value = 50.0 + 10.0 * (i as f64 / 300.0 + tag as f64).sin() + (i % 7) as f64 * 0.1;
+            records.push(HistoryRecord::new(
+                time.format("%Y-%m-%dT%H:%M:%S%.3f").to_string(),
+                tag_name.clone(),
+                value,
+                "Good".to_string(),
+            ));
+        }
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_benchmark_measures_every_stage_at_requested_scale() {
+        let result = run_benchmark(3001, 3).unwrap();
+        assert_eq!((result.rows, result.tags), (3001, 3));
+
+        let names: Vec<&str> = result.stages.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "records_to_dataframe",
+                "process_data",
+                "process_data_polars",
+                "downsample",
+                "records_to_series"
+            ]
+        );
+        assert!(result.stages.iter().all(|s| s.duration_ms > 0.0));
+        assert_eq!(result.stages[0].output_rows, 3001);
+
+        let records = generate_records(3001, 3);
+        assert_eq!(records.len(), 3001);
+        assert_eq!(
+            records.iter().filter(|r| r.tag_name == "BENCH_000").count(),
+            1001
+        );
+        assert_eq!(
+            records.iter().filter(|r| r.tag_name == "BENCH_002").count(),
+            1000
+        );
+    }
+
+    #[test]
+    fn test_benchmark_rejects_invalid_scale() {
+        assert!(run_benchmark(0, 1).is_err());
+        assert!(run_benchmark(10, 0).is_err());
+        assert!(run_benchmark(5, 10).is_err());
+        assert!(run_benchmark(MAX_BENCHMARK_ROWS + 1, 1).is_err());
+    }
+}
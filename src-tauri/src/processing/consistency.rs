@@ -0,0 +1,248 @@
+//! 原生 / Polars 实现一致性校验（维护诊断）
+//!
+//! 对同一样本分别运行两套处理实现，按（标签, 时间）逐点比对，
+//! 返回差异点数、最大偏差和差异点明细，用于定位时区、窗口边界等实现差异。
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use super::local_time::parse_local_timestamp_ms;
+use super::{
+    apply_baseline, dedup_records, limit_to_ranges, process_data, process_data_polars,
+    requires_native,
+};
+use crate::error::{AppError, AppResult};
+use crate::models::{DataProcessingConfig, HistoryRecord};
+
+/// 校验允许的最大样本数
+pub const MAX_CONSISTENCY_SAMPLE: usize = 100_000;
+
+/// 默认容差（绝对偏差）
+pub const DEFAULT_CONSISTENCY_TOLERANCE: f64 = 1e-6;
+
+/// 最多返回的差异点明细数
+const MAX_REPORTED_DIFFS: usize = 100;
+
+/// 差异点
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsistencyDiff {
+    pub tag_name: String,
+    pub date_time: String,
+    /// 原生实现的值（`None` 表示该点只在 Polars 结果中存在）
+    pub native_value: Option<f64>,
+    /// Polars 实现的值（`None` 表示该点只在原生结果中存在）
+    pub polars_value: Option<f64>,
+}
+
+/// 一致性校验结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsistencyReport {
+    /// 两套实现结果是否一致
+    pub consistent: bool,
+    pub native_count: usize,
+    pub polars_count: usize,
+    /// 差异点数（偏差超出容差、缺失值不一致或只在一侧存在）
+    pub diff_count: usize,
+    /// 两侧都有有限值的点的最大绝对偏差
+    pub max_deviation: f64,
+    pub tolerance: f64,
+    /// 差异点明细（按标签、时间排序，最多 100 个）
+    pub diffs: Vec<ConsistencyDiff>,
+}
+
+/// 对样本分别运行原生和 Polars 实现并比对结果
+///
+/// Polars 侧与 `process_query_result` 一致：先去重和量程裁剪，处理后减基准。
+/// 仅原生实现支持的配置（OHLC / 多统计重采样、波动带、滚动窗口或夹取模式的异常值处理）返回验证错误。
+pub fn verify_processing_consistency(
+    sample: Vec<HistoryRecord>,
+    config: &DataProcessingConfig,
+    tolerance: Option<f64>,
+) -> AppResult<ConsistencyReport> {
+    config.validate()?;
+    if sample.len() > MAX_CONSISTENCY_SAMPLE {
+        return Err(AppError::Validation(format!(
+            "校验样本最多 {} 条，当前 {}",
+            MAX_CONSISTENCY_SAMPLE,
+            sample.len()
+        )));
+    }
+    if requires_native(config) {
+        return Err(AppError::Validation(
+            "OHLC / 多统计重采样、波动带、滚动窗口和夹取模式的异常值处理仅原生实现支持，无需校验"
+                .to_string(),
+        ));
+    }
+    let tolerance = tolerance.unwrap_or(DEFAULT_CONSISTENCY_TOLERANCE);
+    if !tolerance.is_finite() || tolerance < 0.0 {
+        return Err(AppError::Validation(format!("容差无效: {}", tolerance)));
+    }
+
+    let native = process_data(sample.clone(), config)?;
+
+    let mut records = sample;
+    if config.dedup.enabled {
+        records = dedup_records(records, config.dedup.keep_last());
+    }
+    if config.range_limit.enabled {
+        records = limit_to_ranges(records, &config.range_limit);
+    }
+    let polars = apply_baseline(process_data_polars(records, config)?, config)?;
+
+    Ok(compare_processing_results(&native, &polars, tolerance))
+}
+
+/// 按（标签, 时间）逐点比对两套实现的结果
+///
+/// 时间按本地时间解析为时间戳后比较（不受毫秒位等格式差异影响）；
+/// 同一时刻有多个点时按出现顺序配对。两侧均为缺失值（NaN）视为一致。
+pub fn compare_processing_results(
+    native: &[HistoryRecord],
+    polars: &[HistoryRecord],
+    tolerance: f64,
+) -> ConsistencyReport {
+    let index = |records: &[HistoryRecord]| {
+        let mut points: BTreeMap<(String, i64), Vec<(String, f64)>> = BTreeMap::new();
+        for r in records {
+            let ts = parse_local_timestamp_ms(&r.date_time, false).map_or(i64::MIN, |(ts, _)| ts);
+            points
+                .entry((r.tag_name.to_string(), ts))
+                .or_default()
+                .push((r.date_time.clone(), r.tag_val));
+        }
+        points
+    };
+    let native_points = index(native);
+    let mut polars_points = index(polars);
+
+    let mut report = ConsistencyReport {
+        consistent: true,
+        native_count: native.len(),
+        polars_count: polars.len(),
+        diff_count: 0,
+        max_deviation: 0.0,
+        tolerance,
+        diffs: Vec::new(),
+    };
+    for ((tag_name, ts), native_values) in native_points {
+        let polars_values = polars_points
+            .remove(&(tag_name.clone(), ts))
+            .unwrap_or_default();
+        for i in 0..native_values.len().max(polars_values.len()) {
+            let native_value = native_values.get(i);
+            let polars_value = polars_values.get(i);
+            let matches = match (native_value, polars_value) {
+                (Some((_, a)), Some((_, b))) if a.is_finite() && b.is_finite() => {
+                    let deviation = (a - b).abs();
+                    report.max_deviation = report.max_deviation.max(deviation);
+                    deviation <= tolerance
+                }
+                (Some((_, a)), Some((_, b))) => a.is_nan() && b.is_nan() || a == b,
+                _ => false,
+            };
+            if !matches {
+                let date_time = native_value.or(polars_value).map(|(t, _)| t.clone());
+                record_diff(
+                    &mut report,
+                    ConsistencyDiff {
+                        tag_name: tag_name.clone(),
+                        date_time: date_time.unwrap_or_default(),
+                        native_value: native_value.map(|(_, v)| *v),
+                        polars_value: polars_value.map(|(_, v)| *v),
+                    },
+                );
+            }
+        }
+    }
+
+    // 只在 Polars 结果中存在的点
+    for ((tag_name, _), values) in polars_points {
+        for (date_time, value) in values {
+            record_diff(
+                &mut report,
+                ConsistencyDiff {
+                    tag_name: tag_name.clone(),
+                    date_time,
+                    native_value: None,
+                    polars_value: Some(value),
+                },
+            );
+        }
+    }
+
+    report.consistent = report.diff_count == 0;
+    report
+}
+
+fn record_diff(report: &mut ConsistencyReport, diff: ConsistencyDiff) {
+    report.diff_count += 1;
+    if report.diffs.len() < MAX_REPORTED_DIFFS {
+        report.diffs.push(diff);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_records() -> Vec<HistoryRecord> {
+        let mut records = Vec::new();
+        for (tag, offset) in [("TT_101", 0.0), ("TT_102", 100.0)] {
+            for i in 0..600 {
+                records.push(HistoryRecord::new(
+                    format!("2024-01-01T10:{:02}:{:02}.000", i / 60, i % 60),
+                    tag.to_string(),
+                    offset + (i as f64 / 30.0).sin() * 10.0 + (i % 7) as f64 * 0.1,
+                    "Good".to_string(),
+                ));
+            }
+        }
+        records
+    }
+
+    #[test]
+    fn test_mean_resample_consistent_within_tolerance() {
+        let config = DataProcessingConfig::new().with_resample(60, "mean");
+        let report = verify_processing_consistency(sample_records(), &config, None).unwrap();
+
+        assert!(report.consistent, "差异点: {:?}", report.diffs);
+        assert_eq!(report.diff_count, 0);
+        assert_eq!(report.native_count, 20);
+        assert_eq!(report.polars_count, 20);
+        assert!(report.max_deviation <= DEFAULT_CONSISTENCY_TOLERANCE);
+
+        let ohlc = DataProcessingConfig::new().with_resample(60, "ohlc");
+        assert!(verify_processing_consistency(sample_records(), &ohlc, None).is_err());
+    }
+
+    #[test]
+    fn test_locates_diff_points() {
+        let config = DataProcessingConfig::new().with_resample(60, "mean");
+        let native = process_data(sample_records(), &config).unwrap();
+
+        // 模拟 Polars 侧的偏差：一个点数值不同、一个点缺失、时间格式不同
+        let mut polars: Vec<HistoryRecord> = native
+            .iter()
+            .filter(|r| !(r.tag_name == "TT_102" && r.date_time.starts_with("2024-01-01T10:09")))
+            .cloned()
+            .collect();
+        for r in &mut polars {
+            r.date_time = r.date_time.trim_end_matches(".000").to_string();
+            if r.tag_name == "TT_101" && r.date_time == "2024-01-01T10:03:00" {
+                r.tag_val += 0.5;
+            }
+        }
+
+        let report = compare_processing_results(&native, &polars, 1e-6);
+        assert!(!report.consistent);
+        assert_eq!(report.diff_count, 2);
+        assert!((report.max_deviation - 0.5).abs() < 1e-9);
+        assert_eq!(report.diffs[0].tag_name, "TT_101");
+        assert_eq!(report.diffs[0].date_time, "2024-01-01T10:03:00.000");
+        assert_eq!(report.diffs[1].tag_name, "TT_102");
+        assert!(report.diffs[1].native_value.is_some());
+        assert_eq!(report.diffs[1].polars_value, None);
+    }
+}
@@ -0,0 +1,131 @@
+//! 按事件标签切分批次
+
+use crate::error::{AppError, AppResult};
+use crate::models::{ChartSeriesData, EventBatch};
+
+/// 判断事件值是否等于目标值（事件标签通常为整数状态，容忍浮点误差）
+fn matches_value(value: f64, target: f64) -> bool {
+    (value - target).abs() < 1e-9
+}
+
+/// 按事件标签的值变化把数据切成批次
+///
+/// 事件标签的值变为 `start_value` 时开始一个批次，之后变为 `end_value` 时结束；
+/// 批次进行中重复出现的开始值被忽略。数据结束时仍未结束的批次以事件标签的最后时刻
+/// 为结束时间，并标记为未完成。每个批次包含所有系列（含事件标签）在批次时间范围内的点。
+pub fn split_by_events(
+    series: &[ChartSeriesData],
+    event_tag: &str,
+    start_value: f64,
+    end_value: f64,
+) -> AppResult<Vec<EventBatch>> {
+    if matches_value(start_value, end_value) {
+        return Err(AppError::Validation(format!(
+            "批次开始值与结束值不能相同: {}",
+            start_value
+        )));
+    }
+    let event = series
+        .iter()
+        .find(|s| s.tag_name == event_tag)
+        .ok_or_else(|| AppError::NotFound(format!("事件标签 '{}' 不在数据中", event_tag)))?;
+
+    let mut points: Vec<[f64; 2]> = event
+        .data
+        .iter()
+        .copied()
+        .filter(|[t, v]| t.is_finite() && v.is_finite())
+        .collect();
+    points.sort_by(|a, b| a[0].total_cmp(&b[0]));
+
+    let mut ranges: Vec<(f64, f64, bool)> = Vec::new();
+    let mut open: Option<f64> = None;
+    for [time, value] in &points {
+        match open {
+            None if matches_value(*value, start_value) => open = Some(*time),
+            Some(start) if matches_value(*value, end_value) => {
+                ranges.push((start, *time, true));
+                open = None;
+            }
+            _ => {}
+        }
+    }
+    if let (Some(start), Some([last, _])) = (open, points.last()) {
+        ranges.push((start, *last, false));
+    }
+
+    Ok(ranges
+        .into_iter()
+        .enumerate()
+        .map(|(index, (start, end, complete))| EventBatch {
+            index,
+            start_ms: start,
+            end_ms: end,
+            complete,
+            series: series.iter().map(|s| slice_series(s, start, end)).collect(),
+        })
+        .collect())
+}
+
+/// 截取系列在 `[start, end]` 内的点（质量码和文本标签同步截取）
+fn slice_series(series: &ChartSeriesData, start: f64, end: f64) -> ChartSeriesData {
+    let keep: Vec<usize> = series
+        .data
+        .iter()
+        .enumerate()
+        .filter(|(_, [t, _])| *t >= start && *t <= end)
+        .map(|(i, _)| i)
+        .collect();
+    let pick = |values: &Vec<String>| {
+        keep.iter()
+            .filter_map(|&i| values.get(i).cloned())
+            .collect::<Vec<_>>()
+    };
+
+    ChartSeriesData {
+        quality: series.quality.as_ref().map(pick),
+        value_labels: series.value_labels.as_ref().map(pick),
+        ..ChartSeriesData::new(
+            series.tag_name.clone(),
+            keep.iter().map(|&i| series.data[i]).collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processing::test_series as series;
+
+    #[test]
+    fn test_two_start_end_pairs_make_two_batches() {
+        // 批次标记：1 = 开始，2 = 结束，0 = 空闲
+        let event = series("BATCH", &[0.0, 1.0, 1.0, 2.0, 0.0, 1.0, 1.0, 1.0, 2.0, 0.0]);
+        let temp = series(
+            "TT_101",
+            &[20.0, 21.0, 22.0, 23.0, 24.0, 25.0, 26.0, 27.0, 28.0, 29.0],
+        );
+
+        let batches = split_by_events(&[event, temp], "BATCH", 1.0, 2.0).unwrap();
+        assert_eq!(batches.len(), 2);
+
+        assert_eq!((batches[0].start_ms, batches[0].end_ms), (1000.0, 3000.0));
+        assert_eq!((batches[1].start_ms, batches[1].end_ms), (5000.0, 8000.0));
+        assert!(batches.iter().all(|b| b.complete));
+
+        let temp_values: Vec<f64> = batches[1].series[1].data.iter().map(|p| p[1]).collect();
+        assert_eq!(temp_values, vec![25.0, 26.0, 27.0, 28.0]);
+        assert_eq!(batches[0].series[1].data.len(), 3);
+    }
+
+    #[test]
+    fn test_unfinished_batch_and_missing_tag() {
+        let event = series("BATCH", &[0.0, 1.0, 1.0]);
+        let batches = split_by_events(std::slice::from_ref(&event), "BATCH", 1.0, 2.0).unwrap();
+        assert_eq!(batches.len(), 1);
+        assert!(!batches[0].complete);
+        assert_eq!(batches[0].end_ms, 2000.0);
+
+        assert!(split_by_events(&[event], "OTHER", 1.0, 2.0).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+//! 峰谷检测（关键拐点提取）
+
+use crate::models::ChartSeriesData;
+
+/// 提取各标签的局部极大/极小点
+///
+/// 每个系列只保留显著性（prominence）不低于阈值的峰和谷，按时间顺序输出。
+pub fn extract_extrema(series: &[ChartSeriesData], prominence: f64) -> Vec<ChartSeriesData> {
+    series
+        .iter()
+        .map(|s| ChartSeriesData::new(s.tag_name.clone(), find_extrema(&s.data, prominence)))
+        .collect()
+}
+
+/// 单个系列的峰谷检测
+///
+/// 先把连续相等的值合并为平台（取平台中点），平台两侧都更低为峰、都更高为谷，
+/// 因此平坦段不会产生假峰。首尾点不视为极值。
+///
+/// 峰的显著性 = 峰值 - max(左侧基线, 右侧基线)，基线为向两侧搜索到更高点之前的最小值；
+/// 谷对称处理。缺失值（NaN）会被忽略。
+fn find_extrema(data: &[[f64; 2]], prominence: f64) -> Vec<[f64; 2]> {
+    let points: Vec<[f64; 2]> = data.iter().copied().filter(|p| !p[1].is_nan()).collect();
+
+    // 平台：(起始下标, 结束下标, 值)
+    let mut plateaus: Vec<(usize, usize, f64)> = Vec::new();
+    for (i, p) in points.iter().enumerate() {
+        match plateaus.last_mut() {
+            Some(last) if last.2 == p[1] => last.1 = i,
+            _ => plateaus.push((i, i, p[1])),
+        }
+    }
+
+    let values: Vec<f64> = plateaus.iter().map(|p| p.2).collect();
+    let mut extrema = Vec::new();
+    for i in 1..values.len().saturating_sub(1) {
+        let (prev, value, next) = (values[i - 1], values[i], values[i + 1]);
+        let is_peak = value > prev && value > next;
+        let is_valley = value < prev && value < next;
+        if !is_peak && !is_valley {
+            continue;
+        }
+
+        let sign = if is_peak { 1.0 } else { -1.0 };
+        if peak_prominence(&values, i, sign) >= prominence {
+            let (start, end, _) = plateaus[i];
+            extrema.push(points[(start + end) / 2]);
+        }
+    }
+    extrema
+}
+
+/// 计算下标 `i` 处峰（`sign = 1`）或谷（`sign = -1`）的显著性
+fn peak_prominence(values: &[f64], i: usize, sign: f64) -> f64 {
+    let peak = values[i] * sign;
+    let base = |range: &mut dyn Iterator<Item = usize>| {
+        range
+            .map(|j| values[j] * sign)
+            .take_while(|v| *v <= peak)
+            .fold(peak, f64::min)
+    };
+    let left = base(&mut (0..i).rev());
+    let right = base(&mut (i + 1..values.len()));
+    peak - left.max(right)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processing::test_series;
+
+    fn series(values: &[f64]) -> ChartSeriesData {
+        test_series("T1", values)
+    }
+
+    #[test]
+    fn test_sine_wave_peaks_and_valleys() {
+        // 两个周期的正弦波，每周期 40 个点：峰在 10、50，谷在 30、70
+        let values: Vec<f64> = (0..80)
+            .map(|i| (i as f64 * std::f64::consts::PI / 20.0).sin())
+            .collect();
+        let result = extract_extrema(&[series(&values)], 0.5);
+
+        assert_eq!(result[0].tag_name, "T1");
+        let times: Vec<f64> = result[0].data.iter().map(|p| p[0]).collect();
+        assert_eq!(times, vec![10000.0, 30000.0, 50000.0, 70000.0]);
+        assert!((result[0].data[0][1] - 1.0).abs() < 1e-9);
+        assert!((result[0].data[1][1] + 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_flat_segments_produce_no_false_peaks() {
+        // 全平坦
+        let result = extract_extrema(&[series(&[5.0; 10])], 0.0);
+        assert!(result[0].data.is_empty());
+
+        // 台阶上升：平台之间没有峰谷
+        let result = extract_extrema(&[series(&[1.0, 1.0, 2.0, 2.0, 2.0, 3.0, 3.0])], 0.0);
+        assert!(result[0].data.is_empty());
+
+        // 平顶峰取平台中点
+        let result = extract_extrema(&[series(&[0.0, 4.0, 4.0, 4.0, 0.0])], 1.0);
+        assert_eq!(result[0].data, vec![[2000.0, 4.0]]);
+    }
+
+    #[test]
+    fn test_prominence_filters_small_wiggles() {
+        // 主峰 10 上叠加的小抖动（9.8 / 9.9）应被过滤
+        let values = [0.0, 9.8, 9.7, 9.9, 9.6, 10.0, 0.0];
+        let result = extract_extrema(&[series(&values)], 1.0);
+        assert_eq!(result[0].data, vec![[5000.0, 10.0]]);
+
+        let result = extract_extrema(&[series(&values)], 0.0);
+        assert_eq!(result[0].data.len(), 5);
+    }
+}
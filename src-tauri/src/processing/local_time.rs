@@ -0,0 +1,247 @@
+//! 本地时间解析（夏令时边界处理）
+//!
+//! 数据库中的时间为无时区的本地时间。夏令时切换日：
+//! - 回拨时同一本地时间出现两次（歧义），按配置取较早或较晚的时刻；
+//! - 拨快时部分本地时间不存在（空档），按切换前的偏移换算，即顺延空档长度。
+//!
+//! 两种情况都不丢点，由调用方统计受影响点数。
+
+use std::fmt::Write;
+
+use chrono::format::StrftimeItems;
+use chrono::{DateTime, Local, NaiveDateTime, Offset, TimeDelta, TimeZone};
+
+use crate::error::{AppError, AppResult};
+use crate::models::HistoryRecord;
+
+/// 本地时间解析时做的夏令时调整
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DstAdjustment {
+    /// 时间唯一，无需调整
+    None,
+    /// 歧义时间，已按配置取较早/较晚
+    Ambiguous,
+    /// 不存在的时间，已顺延
+    Skipped,
+}
+
+/// 在指定时区解析本地时间
+///
+/// `prefer_latest` 为 true 时歧义时间取较晚的时刻（切换后的偏移），否则取较早的时刻。
+pub fn resolve_local_datetime<Tz: TimeZone>(
+    tz: &Tz,
+    naive: &NaiveDateTime,
+    prefer_latest: bool,
+) -> Option<(DateTime<Tz>, DstAdjustment)> {
+    match tz.from_local_datetime(naive) {
+        chrono::LocalResult::Single(dt) => Some((dt, DstAdjustment::None)),
+        chrono::LocalResult::Ambiguous(earliest, latest) => {
+            let dt = if prefer_latest { latest } else { earliest };
+            Some((dt, DstAdjustment::Ambiguous))
+        }
+        chrono::LocalResult::None => {
+            // 取空档之前（空档不会超过一天）的偏移换算为 UTC 时刻
+            let before = tz
+                .from_local_datetime(&(*naive - TimeDelta::days(1)))
+                .earliest()?;
+            let utc = *naive - TimeDelta::seconds(before.offset().fix().local_minus_utc() as i64);
+            Some((tz.from_utc_datetime(&utc), DstAdjustment::Skipped))
+        }
+    }
+}
+
+/// 解析记录时间字符串（本地时间）为毫秒时间戳及夏令时调整
+pub fn parse_local_timestamp_ms(
+    date_time: &str,
+    prefer_latest: bool,
+) -> Option<(i64, DstAdjustment)> {
+    let naive = parse_record_naive(date_time)?;
+    resolve_local_datetime(&Local, &naive, prefer_latest)
+        .map(|(dt, adjustment)| (dt.timestamp_millis(), adjustment))
+}
+
+/// 按 strftime 模式重新格式化记录时间（用于查询结果输出）
+///
+/// 时间先按本地时区解析，因此模式中可以使用 `%z`、`%:z` 等时区说明符；
+/// 无法解析的时间保持原样。模式无效时返回校验错误。
+pub fn format_record_times(
+    mut records: Vec<HistoryRecord>,
+    format: &str,
+) -> AppResult<Vec<HistoryRecord>> {
+    let items = StrftimeItems::new(format)
+        .parse()
+        .map_err(|_| AppError::Validation(format!("无效的时间格式: {}", format)))?;
+
+    for record in &mut records {
+        let Some(naive) = parse_record_naive(&record.date_time) else {
+            continue;
+        };
+        let Some((dt, _)) = resolve_local_datetime(&Local, &naive, false) else {
+            continue;
+        };
+        let mut formatted = String::with_capacity(format.len() + 16);
+        write!(formatted, "{}", dt.format_with_items(items.iter()))
+            .map_err(|_| AppError::Validation(format!("无效的时间格式: {}", format)))?;
+        record.date_time = formatted;
+    }
+
+    Ok(records)
+}
+
+fn parse_record_naive(date_time: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M:%S%.3f")
+        .or_else(|_| NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M:%S"))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, MappedLocalTime, NaiveDate};
+
+    /// 欧盟规则的 2024 年夏令时：3-31 01:00Z 起 UTC+2，10-27 01:00Z 回到 UTC+1
+    #[derive(Debug, Clone, Copy)]
+    struct TestDstZone;
+
+    fn at(month: u32, day: u32, hour: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, month, day)
+            .unwrap()
+            .and_hms_opt(hour, min, 0)
+            .unwrap()
+    }
+
+    impl TimeZone for TestDstZone {
+        type Offset = FixedOffset;
+
+        fn from_offset(_offset: &FixedOffset) -> Self {
+            TestDstZone
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> MappedLocalTime<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_local_datetime(
+            &self,
+            local: &NaiveDateTime,
+        ) -> MappedLocalTime<FixedOffset> {
+            let candidates: Vec<FixedOffset> = [3600, 7200]
+                .into_iter()
+                .map(|secs| FixedOffset::east_opt(secs).unwrap())
+                .filter(|offset| {
+                    let utc = *local - TimeDelta::seconds(offset.local_minus_utc() as i64);
+                    self.offset_from_utc_datetime(&utc) == *offset
+                })
+                .collect();
+            match candidates.as_slice() {
+                [] => MappedLocalTime::None,
+                [single] => MappedLocalTime::Single(*single),
+                // 较早的时刻对应较大的偏移（夏令时）
+                [standard, summer] => MappedLocalTime::Ambiguous(*summer, *standard),
+                _ => unreachable!(),
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_utc_datetime(&self, utc_dt: &NaiveDateTime) -> FixedOffset {
+            let summer = *utc_dt >= at(3, 31, 1, 0) && *utc_dt < at(10, 27, 1, 0);
+            FixedOffset::east_opt(if summer { 7200 } else { 3600 }).unwrap()
+        }
+    }
+
+    fn record(date_time: &str) -> HistoryRecord {
+        HistoryRecord::new(
+            date_time.to_string(),
+            "T1".to_string(),
+            1.0,
+            "Good".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_format_record_times_custom_pattern() {
+        let records = vec![
+            record("2024-01-02T03:04:05.678"),
+            record("2024-01-02T03:04:06"),
+        ];
+        let formatted = format_record_times(records, "%Y/%m/%d %H:%M:%S").unwrap();
+        assert_eq!(formatted[0].date_time, "2024/01/02 03:04:05");
+        assert_eq!(formatted[1].date_time, "2024/01/02 03:04:06");
+
+        // 带时区偏移的格式也能输出
+        let formatted = format_record_times(
+            vec![record("2024-01-02T03:04:05.678")],
+            "%Y-%m-%dT%H:%M:%S%:z",
+        )
+        .unwrap();
+        assert!(formatted[0].date_time.starts_with("2024-01-02T03:04:05"));
+        assert!(formatted[0].date_time.len() > "2024-01-02T03:04:05".len());
+    }
+
+    #[test]
+    fn test_format_record_times_default_pattern_unchanged() {
+        let original = vec![
+            record("2024-01-02T03:04:05.678"),
+            record("2024-06-30T23:59:59.000"),
+        ];
+        let formatted = format_record_times(original.clone(), "%Y-%m-%dT%H:%M:%S%.3f").unwrap();
+        assert_eq!(formatted, original);
+    }
+
+    #[test]
+    fn test_format_record_times_rejects_invalid_pattern() {
+        let result = format_record_times(vec![record("2024-01-02T03:04:05.678")], "%Q");
+        assert!(matches!(result, Err(AppError::Validation(_))));
+    }
+
+    #[test]
+    fn test_regular_time_unchanged() {
+        let (dt, adjustment) =
+            resolve_local_datetime(&TestDstZone, &at(6, 1, 12, 0), false).unwrap();
+        assert_eq!(adjustment, DstAdjustment::None);
+        assert_eq!(dt.naive_utc(), at(6, 1, 10, 0));
+    }
+
+    #[test]
+    fn test_ambiguous_time_resolved_by_preference() {
+        // 10-27 02:30 本地时间出现两次（UTC 00:30 和 01:30）
+        let naive = at(10, 27, 2, 30);
+
+        let (earliest, adjustment) = resolve_local_datetime(&TestDstZone, &naive, false).unwrap();
+        assert_eq!(adjustment, DstAdjustment::Ambiguous);
+        assert_eq!(earliest.naive_utc(), at(10, 27, 0, 30));
+
+        let (latest, _) = resolve_local_datetime(&TestDstZone, &naive, true).unwrap();
+        assert_eq!(latest.naive_utc(), at(10, 27, 1, 30));
+    }
+
+    #[test]
+    fn test_nonexistent_time_shifted_forward() {
+        // 3-31 02:30 本地时间不存在，按切换前的 UTC+1 换算，显示为 03:30
+        let (dt, adjustment) =
+            resolve_local_datetime(&TestDstZone, &at(3, 31, 2, 30), false).unwrap();
+        assert_eq!(adjustment, DstAdjustment::Skipped);
+        assert_eq!(dt.naive_utc(), at(3, 31, 1, 30));
+        assert_eq!(dt.naive_local(), at(3, 31, 3, 30));
+    }
+
+    #[test]
+    fn test_dst_transition_day_points_not_dropped() {
+        // 切换日每 15 分钟一个点，全部能解析，受影响的点数与空档/重叠时长一致
+        for (month, day) in [(3, 31), (10, 27)] {
+            let adjusted = (0..24 * 4)
+                .map(|i| {
+                    let naive = at(month, day, i / 4, (i % 4) * 15);
+                    resolve_local_datetime(&TestDstZone, &naive, false)
+                        .unwrap_or_else(|| panic!("{} 被丢弃", naive))
+                        .1
+                })
+                .filter(|adjustment| *adjustment != DstAdjustment::None)
+                .count();
+            assert_eq!(adjusted, 4);
+        }
+    }
+}
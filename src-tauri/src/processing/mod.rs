@@ -1,31 +1,66 @@
 //! 数据处理模块
 //!
 //! 提供数据处理功能：异常值剔除、重采样、平滑滤波、降采样。
-//! 支持 Polars 和原生 Rust 两种实现。
+//! 支持 Polars 和原生 Rust 两种实现；原生实现可缓存管道中间结果。
 
+mod alarm;
+mod align;
+mod benchmark;
+mod consistency;
+mod events;
+mod extrema;
+mod local_time;
 mod native;
+mod pipeline_cache;
 mod polars_impl;
+mod sampling;
 
-pub use native::{downsample, remove_outliers, resample_data, smooth_data};
+pub use alarm::compute_alarm_stats;
+pub use align::{align_series, difference_series, difference_tag_name};
+pub use benchmark::{BenchmarkResult, BenchmarkStage, MAX_BENCHMARK_ROWS, run_benchmark};
+pub use consistency::{
+    ConsistencyDiff, ConsistencyReport, DEFAULT_CONSISTENCY_TOLERANCE, MAX_CONSISTENCY_SAMPLE,
+    compare_processing_results, verify_processing_consistency,
+};
+pub use events::split_by_events;
+pub use extrema::extract_extrema;
+pub use local_time::format_record_times;
+pub use native::{
+    OHLC_COMPONENTS, STD_BAND_COMPONENTS, band_tag_name, clip_outliers, clip_outliers_rolling,
+    dedup_records, downsample, limit_to_ranges, ohlc_tag_name, remove_outliers,
+    remove_outliers_rolling, resample_data, resample_ohlc, resample_stats, rolling_std_band,
+    smooth_data, stat_tag_name, subtract_baseline,
+};
+pub use pipeline_cache::{PipelineCache, process_data_cached};
 pub use polars_impl::{dataframe_to_records, process_data_polars, records_to_dataframe};
+pub use sampling::{
+    UNEVEN_SAMPLING_CV_THRESHOLD, detect_sampling_interval, detect_uneven_sampling,
+};
 
-use crate::error::AppResult;
-use crate::models::{ChartSeriesData, DataProcessingConfig, HistoryRecord};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    Baseline, ChartSeriesData, DataProcessingConfig, HistoryRecord, InternedStr, QueryParams,
+    SeriesGapConfig,
+};
 use std::collections::HashMap;
 use tracing::{debug, warn};
 
+/// 实时预览允许的最大样本数
+pub const PREVIEW_MAX_SAMPLE: usize = 5000;
+
 /// 处理查询结果
-/// 处理顺序：异常值剔除 → 重采样 → 平滑滤波
+/// 处理顺序：去重 → 量程裁剪 → 异常值剔除 → 重采样 → 波动带 / 平滑滤波 → 减基准
 pub fn process_data(
     records: Vec<HistoryRecord>,
     config: &DataProcessingConfig,
 ) -> AppResult<Vec<HistoryRecord>> {
+    config.validate()?;
     if records.is_empty() {
         return Ok(records);
     }
 
     // 按标签分组处理
-    let mut tag_groups: HashMap<String, Vec<HistoryRecord>> = HashMap::new();
+    let mut tag_groups: HashMap<InternedStr, Vec<HistoryRecord>> = HashMap::new();
     for record in records {
         tag_groups
             .entry(record.tag_name.clone())
@@ -48,26 +83,140 @@ pub fn process_data(
 
 /// 处理单个标签的数据
 fn process_tag_data(
-    mut records: Vec<HistoryRecord>,
+    records: Vec<HistoryRecord>,
     config: &DataProcessingConfig,
     _tag_name: &str,
 ) -> AppResult<Vec<HistoryRecord>> {
-    // 1. 异常值剔除
+    finish_tag_data(prefix_tag_data(records, config)?, config)
+}
+
+/// 单个标签的前置步骤：去重 → 量程裁剪 → 异常值剔除 → 重采样
+///
+/// 返回待平滑的序列（OHLC / 多统计重采样时为各派生序列，否则只有一个）。
+fn prefix_tag_data(
+    mut records: Vec<HistoryRecord>,
+    config: &DataProcessingConfig,
+) -> AppResult<Vec<Vec<HistoryRecord>>> {
+    // 0. 去重
+    if config.dedup.enabled {
+        records = dedup_records(records, config.dedup.keep_last());
+    }
+
+    // 0.5 工程量程裁剪（坏数据先于统计类处理剔除）
+    if config.range_limit.enabled {
+        records = limit_to_ranges(records, &config.range_limit);
+    }
+
+    // 1. 异常值剔除（设置窗口时按局部窗口判断，clip 模式夹到边界而不删点）
     if config.outlier_removal.enabled {
-        records = remove_outliers(records)?;
+        let outliers = &config.outlier_removal;
+        records = match (outliers.rolling_window(), outliers.is_clip()) {
+            (Some(window), false) => remove_outliers_rolling(records, window)?,
+            (Some(window), true) => clip_outliers_rolling(records, window)?,
+            (None, false) => remove_outliers(records)?,
+            (None, true) => clip_outliers(records)?,
+        };
     }
 
     // 2. 重采样
     if config.resample.enabled && config.resample.interval > 0 {
-        records = resample_data(records, config.resample.interval)?;
+        if config.resample.is_multi_stat() {
+            // 每个统计量输出一个派生序列，平滑滤波分别作用于各序列
+            return resample_stats(
+                records,
+                config.resample.interval,
+                &config.resample.methods,
+                config.resample.prefer_latest(),
+            );
+        }
+        if config.resample.is_ohlc() {
+            // OHLC 输出四个派生序列，平滑滤波分别作用于各序列
+            return resample_ohlc(
+                records,
+                config.resample.interval,
+                config.resample.prefer_latest(),
+            );
+        }
+        records = resample_data(
+            records,
+            config.resample.interval,
+            config.resample.prefer_latest(),
+        )?;
+    }
+
+    Ok(vec![records])
+}
+
+/// 单个标签的后续步骤：波动带 / 平滑滤波 → 减基准
+fn finish_tag_data(
+    series: Vec<Vec<HistoryRecord>>,
+    config: &DataProcessingConfig,
+) -> AppResult<Vec<HistoryRecord>> {
+    // 3. 波动带（平滑前的数据）与平滑滤波；记录各序列在结果中的位置以便波动带随基准平移
+    let mut result = Vec::new();
+    let mut bands = Vec::new();
+    for records in series {
+        if config.std_band.enabled && config.std_band.window > 1 {
+            let band =
+                rolling_std_band(&records, config.std_band.window, config.std_band.multiplier);
+            bands.push((result.len()..result.len() + records.len(), band));
+        }
+        if config.smoothing.enabled && config.smoothing.window > 1 {
+            result.extend(smooth_data(records, config.smoothing.window)?);
+        } else {
+            result.extend(records);
+        }
+    }
+    if bands.is_empty() {
+        // 4. 减去基准值
+        return apply_baseline(result, config);
     }
 
-    // 3. 平滑滤波
-    if config.smoothing.enabled && config.smoothing.window > 1 {
-        records = smooth_data(records, config.smoothing.window)?;
+    // 4. 减去基准值（波动带按中线的基准平移，而不是单独计算基准）
+    let before: Vec<f64> = result.iter().map(|r| r.tag_val).collect();
+    let mut result = apply_baseline(result, config)?;
+    for (range, band) in bands {
+        let offset = range
+            .map(|i| before[i] - result[i].tag_val)
+            .find(|offset| offset.is_finite())
+            .unwrap_or(0.0);
+        for mut record in band.into_iter().flatten() {
+            record.tag_val -= offset;
+            result.push(record);
+        }
     }
+    Ok(result)
+}
 
-    Ok(records)
+/// 按配置减去基准值（未配置时原样返回）
+fn apply_baseline(
+    records: Vec<HistoryRecord>,
+    config: &DataProcessingConfig,
+) -> AppResult<Vec<HistoryRecord>> {
+    let Some(value) = config.baseline.as_deref() else {
+        return Ok(records);
+    };
+    let baseline = Baseline::parse(value).ok_or_else(|| {
+        AppError::Validation(format!(
+            "基准值无效: '{}'，可选值: first, mean 或数值",
+            value
+        ))
+    })?;
+    Ok(subtract_baseline(records, baseline))
+}
+
+/// 图表展示时每个标签保留的最大点数（降采样目标）
+pub const CHART_MAX_POINTS_PER_TAG: usize = 5000;
+
+/// 配置是否仅原生实现支持（OHLC / 多统计重采样、歧义时间取较晚时刻的重采样、波动带、
+/// 滚动窗口或夹取模式的异常值处理）
+fn requires_native(config: &DataProcessingConfig) -> bool {
+    config.resample.is_ohlc()
+        || config.resample.is_multi_stat()
+        || (config.resample.enabled && config.resample.prefer_latest())
+        || config.std_band.enabled
+        || config.outlier_removal.rolling_window().is_some()
+        || (config.outlier_removal.enabled && config.outlier_removal.is_clip())
 }
 
 /// 完整数据处理流程
@@ -77,58 +226,139 @@ fn process_tag_data(
 pub fn process_query_result(
     records: Vec<HistoryRecord>,
     config: Option<&DataProcessingConfig>,
+) -> AppResult<Vec<HistoryRecord>> {
+    let records = match config {
+        Some(cfg) => process_records(records, cfg)?,
+        None => records,
+    };
+
+    // 最后进行降采样，避免前端渲染过多数据
+    downsample(records, CHART_MAX_POINTS_PER_TAG)
+}
+
+/// 按处理配置处理记录，不降采样（导出、统计等需要全部数据点的场景）
+///
+/// 优先使用 Polars 优化版本，失败时回退到原生实现
+pub fn process_records(
+    records: Vec<HistoryRecord>,
+    cfg: &DataProcessingConfig,
 ) -> AppResult<Vec<HistoryRecord>> {
     let record_count = records.len();
 
-    let records = if let Some(cfg) = config {
-        // 大数据量时优先使用 Polars（阈值: 1000 条）
-        if record_count > 1000 {
-            match process_data_polars(records.clone(), cfg) {
-                Ok(result) => {
-                    debug!(target: "industry_vis::processing",
-                        "Polars 处理完成: {} -> {} 条", record_count, result.len());
-                    result
-                }
-                Err(e) => {
-                    warn!(target: "industry_vis::processing",
-                        "Polars 处理失败，回退到原生实现: {}", e);
-                    process_data(records, cfg)?
-                }
-            }
+    // 大数据量时优先使用 Polars（阈值: 1000 条），
+    if record_count > 1000 && !requires_native(cfg) {
+        // Polars 管道不含去重和量程裁剪，先行处理（回退原生实现时重复执行无副作用）
+        let records = if cfg.dedup.enabled {
+            dedup_records(records, cfg.dedup.keep_last())
+        } else {
+            records
+        };
+        let records = if cfg.range_limit.enabled {
+            limit_to_ranges(records, &cfg.range_limit)
         } else {
-            // 小数据量使用原生实现（避免 Polars 开销）
-            process_data(records, cfg)?
+            records
+        };
+        match process_data_polars(records.clone(), cfg) {
+            Ok(result) => {
+                debug!(target: "industry_vis::processing",
+                    "Polars 处理完成: {} -> {} 条", record_count, result.len());
+                // Polars 管道不含减基准
+                apply_baseline(result, cfg)
+            }
+            Err(e) => {
+                warn!(target: "industry_vis::processing",
+                    "Polars 处理失败，回退到原生实现: {}", e);
+                process_data(records, cfg)
+            }
         }
     } else {
-        records
-    };
+        // 小数据量使用原生实现（避免 Polars 开销），复用未变化的前置步骤结果
+        process_data_cached(records, cfg, PipelineCache::global())
+    }
+}
 
-    // 最后进行降采样，避免前端渲染过多数据
-    downsample(records, 5000)
+/// 处理配置实时预览
+///
+/// 对前端传入的小样本直接应用处理配置，不访问数据库和缓存。
+/// 样本超过 `PREVIEW_MAX_SAMPLE` 时返回验证错误。
+pub fn preview_processing(
+    sample: Vec<HistoryRecord>,
+    config: &DataProcessingConfig,
+) -> AppResult<Vec<HistoryRecord>> {
+    if sample.len() > PREVIEW_MAX_SAMPLE {
+        return Err(AppError::Validation(format!(
+            "预览样本最多 {} 条，当前 {}",
+            PREVIEW_MAX_SAMPLE,
+            sample.len()
+        )));
+    }
+
+    process_data_cached(sample, config, PipelineCache::global())
+}
+
+/// 计算标签在指定时刻的值：用前后最近的记录线性插值
+///
+/// `records` 为该标签在 `time` 附近的记录（顺序不限）。缺少前点或后点时返回 `None`。
+pub fn interpolate_value_at(records: &[HistoryRecord], time: &str) -> AppResult<Option<f64>> {
+    let t = parse_timestamp_ms(time)
+        .ok_or_else(|| AppError::Validation(format!("时间格式无效: {}", time)))?;
+    let mut data: Vec<[f64; 2]> = records
+        .iter()
+        .filter_map(|r| parse_timestamp_ms(&r.date_time).map(|ts| [ts, r.tag_val]))
+        .collect();
+    data.sort_by(|a, b| a[0].total_cmp(&b[0]));
+    Ok(align::value_at(&data, t))
 }
 
 /// 将 HistoryRecord 列表转换为 V2 格式（按标签预分组）
 pub fn records_to_series(records: &[HistoryRecord]) -> Vec<ChartSeriesData> {
-    // 按标签分组
-    let mut tag_groups: HashMap<String, Vec<[f64; 2]>> = HashMap::new();
+    records_to_series_with_quality(records, false)
+}
+
+/// 将 HistoryRecord 列表转换为 V2 格式，`include_quality` 时附带与数据点一一对应的质量码
+pub fn records_to_series_with_quality(
+    records: &[HistoryRecord],
+    include_quality: bool,
+) -> Vec<ChartSeriesData> {
+    // 按标签分组，数据点与质量码一起保存，重排时保持对应
+    let mut tag_groups: HashMap<&str, Vec<([f64; 2], &str)>> = HashMap::new();
 
     for record in records {
         // 解析时间戳
         let timestamp_ms = parse_timestamp_ms(&record.date_time).unwrap_or(0.0);
+        // ±Inf 统一视为缺失值（NaN，序列化为 null）
+        let value = if record.tag_val.is_finite() {
+            record.tag_val
+        } else {
+            f64::NAN
+        };
 
         tag_groups
-            .entry(record.tag_name.clone())
+            .entry(record.tag_name.as_str())
             .or_default()
-            .push([timestamp_ms, record.tag_val]);
+            .push(([timestamp_ms, value], record.tag_quality.as_str()));
     }
 
     // 转换为 Vec<ChartSeriesData>，按标签名排序
     let mut series: Vec<ChartSeriesData> = tag_groups
         .into_iter()
-        .map(|(tag_name, mut data)| {
-            // 按时间戳排序
-            data.sort_by(|a, b| a[0].partial_cmp(&b[0]).unwrap_or(std::cmp::Ordering::Equal));
-            ChartSeriesData { tag_name, data }
+        .map(|(tag_name, mut points)| {
+            // 保证时间单调递增（ECharts tooltip 和缩放依赖有序数据）
+            let out_of_order = points.windows(2).filter(|w| w[1].0[0] < w[0].0[0]).count();
+            if out_of_order > 0 {
+                points.sort_by(|a, b| a.0[0].total_cmp(&b.0[0]));
+                warn!(target: "industry_vis::processing",
+                    "标签 {} 检测到 {} 个时间乱序点，已重排", tag_name, out_of_order);
+            }
+            let quality =
+                include_quality.then(|| points.iter().map(|(_, q)| q.to_string()).collect());
+            ChartSeriesData {
+                quality,
+                ..ChartSeriesData::new(
+                    tag_name.to_string(),
+                    points.into_iter().map(|(p, _)| p).collect(),
+                )
+            }
         })
         .collect();
 
@@ -136,22 +366,121 @@ pub fn records_to_series(records: &[HistoryRecord]) -> Vec<ChartSeriesData> {
     series
 }
 
-/// 解析时间字符串为毫秒时间戳
-fn parse_timestamp_ms(date_time: &str) -> Option<f64> {
-    use chrono::{Local, TimeZone};
+/// 检查数据点时间是否单调不减，乱序时按时间稳定重排
+///
+/// 返回乱序点数（时间早于前一个点的点数），0 表示输入已有序。
+pub fn ensure_monotonic_time(data: &mut [[f64; 2]]) -> usize {
+    let out_of_order = data.windows(2).filter(|w| w[1][0] < w[0][0]).count();
+    if out_of_order > 0 {
+        data.sort_by(|a, b| a[0].total_cmp(&b[0]));
+    }
+    out_of_order
+}
+
+/// 将记录转换为 V2 系列，并按查询参数插入断点、进行多标签时间对齐
+///
+/// 断点在对齐之前插入，使对齐插值在断采区间内同样留空。
+/// `include_quality` 时附带质量码；时间对齐后的点为插值结果，不再返回质量码。
+pub fn build_series(records: &[HistoryRecord], params: &QueryParams) -> Vec<ChartSeriesData> {
+    let mut series = records_to_series_with_quality(records, params.include_quality);
+
+    if let Some(gap) = params.gap.as_ref().filter(|g| g.enabled) {
+        for s in &mut series {
+            insert_series_gap_markers(s, gap);
+        }
+    }
 
-    // 尝试多种格式
-    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M:%S%.3f")
-        && let Some(local_dt) = Local.from_local_datetime(&dt).single()
-    {
-        return Some(local_dt.timestamp_millis() as f64);
+    match params.align.as_ref() {
+        Some(cfg) if cfg.enabled => align_series(series, cfg),
+        _ => series,
     }
-    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M:%S")
-        && let Some(local_dt) = Local.from_local_datetime(&dt).single()
-    {
-        return Some(local_dt.timestamp_millis() as f64);
+}
+
+/// 在断采位置插入 `[timestamp, NaN]` 断点（序列化为 null，前端折线自然断开）
+///
+/// 阈值优先使用 `threshold_ms`，否则按 `factor × 正常采样间隔` 估算。
+/// 无法确定阈值时不做处理。
+pub fn insert_gap_markers(data: &mut Vec<[f64; 2]>, config: &SeriesGapConfig) {
+    let positions = gap_positions(data, config);
+    insert_before(data, &positions, |prev, next| {
+        [(prev[0] + next[0]) / 2.0, f64::NAN]
+    });
+}
+
+/// 在系列中插入断点，质量码在相同位置插入空字符串以保持一一对应
+pub fn insert_series_gap_markers(series: &mut ChartSeriesData, config: &SeriesGapConfig) {
+    let positions = gap_positions(&series.data, config);
+    if let Some(quality) = series.quality.as_mut() {
+        insert_before(quality, &positions, |_, _| String::new());
     }
-    None
+    insert_before(&mut series.data, &positions, |prev, next| {
+        [(prev[0] + next[0]) / 2.0, f64::NAN]
+    });
+}
+
+/// 需要在其前插入断点的下标（与前一点的时间差超过阈值）
+fn gap_positions(data: &[[f64; 2]], config: &SeriesGapConfig) -> Vec<usize> {
+    let threshold = config
+        .threshold_ms
+        .map(|ms| ms as f64)
+        .or_else(|| estimate_sample_interval_ms(data).map(|ms| ms * config.factor));
+    match threshold {
+        Some(t) if t > 0.0 => (1..data.len())
+            .filter(|&i| data[i][0] - data[i - 1][0] > t)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// 在 `positions`（升序）指定的各下标之前插入由前后元素生成的新元素
+fn insert_before<T: Clone>(items: &mut Vec<T>, positions: &[usize], make: impl Fn(&T, &T) -> T) {
+    if positions.is_empty() {
+        return;
+    }
+    let mut result = Vec::with_capacity(items.len() + positions.len());
+    let mut pending = positions.iter().peekable();
+    for (i, item) in items.iter().enumerate() {
+        if pending.next_if_eq(&&i).is_some() {
+            result.push(make(&items[i - 1], item));
+        }
+        result.push(item.clone());
+    }
+    *items = result;
+}
+
+/// 估算系列的正常采样间隔（毫秒）
+///
+/// 取相邻点正时间差的中位数，数据点不足时返回 None。
+pub fn estimate_sample_interval_ms(data: &[[f64; 2]]) -> Option<f64> {
+    let mut diffs: Vec<f64> = data
+        .windows(2)
+        .map(|w| w[1][0] - w[0][0])
+        .filter(|d| *d > 0.0)
+        .collect();
+    if diffs.is_empty() {
+        return None;
+    }
+
+    diffs.sort_by(|a, b| a.total_cmp(b));
+    Some(diffs[diffs.len() / 2])
+}
+
+/// 解析时间字符串为毫秒时间戳（夏令时歧义取较早时刻，不存在的时间顺延）
+fn parse_timestamp_ms(date_time: &str) -> Option<f64> {
+    local_time::parse_local_timestamp_ms(date_time, false).map(|(ts, _)| ts as f64)
+}
+
+/// 测试用系列：数据点时间间隔 1 秒，从 0 开始
+#[cfg(test)]
+pub(crate) fn test_series(tag: &str, values: &[f64]) -> ChartSeriesData {
+    ChartSeriesData::new(
+        tag.to_string(),
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| [i as f64 * 1000.0, *v])
+            .collect(),
+    )
 }
 
 #[cfg(test)]
@@ -171,6 +500,17 @@ mod tests {
             .collect()
     }
 
+    #[test]
+    fn test_dst_latest_resample_requires_native() {
+        let mut config = DataProcessingConfig::default();
+        config.resample.enabled = true;
+        assert!(!requires_native(&config));
+
+        // Polars 管道按较早时刻解析歧义时间，取较晚时刻时必须走原生实现
+        config.resample.dst_ambiguous = "latest".to_string();
+        assert!(requires_native(&config));
+    }
+
     #[test]
     fn test_process_data_empty() {
         let records: Vec<HistoryRecord> = vec![];
@@ -179,6 +519,230 @@ mod tests {
         assert!(result.is_empty());
     }
 
+    #[test]
+    fn test_preview_processing_matches_process_data() {
+        let records = create_test_records(30);
+        let config = DataProcessingConfig::new()
+            .with_outlier_removal("3sigma")
+            .with_smoothing(5, "moving_avg");
+
+        let preview = preview_processing(records.clone(), &config).unwrap();
+        let expected = process_data(records, &config).unwrap();
+        assert_eq!(preview, expected);
+    }
+
+    #[test]
+    fn test_preview_processing_rejects_large_sample() {
+        let records = create_test_records(1)
+            .into_iter()
+            .cycle()
+            .take(PREVIEW_MAX_SAMPLE + 1)
+            .collect();
+        let config = DataProcessingConfig::default();
+        assert!(preview_processing(records, &config).is_err());
+    }
+
+    #[test]
+    fn test_process_data_range_limit() {
+        let mut records = create_test_records(5);
+        records[2].tag_val = -9999.0;
+        records.push(HistoryRecord::new(
+            "2024-01-01T00:00:30.000".to_string(),
+            "Tag2".to_string(),
+            -9999.0,
+            "Good".to_string(),
+        ));
+        let config = DataProcessingConfig::new().with_range_limit(
+            vec![crate::models::TagRange {
+                tag: "Tag1".to_string(),
+                min: 0.0,
+                max: 100.0,
+            }],
+            "drop",
+        );
+
+        let result = process_data(records.clone(), &config).unwrap();
+        // Tag1 超量程的点被剔除
+        let tag1: Vec<f64> = result
+            .iter()
+            .filter(|r| r.tag_name == "Tag1")
+            .map(|r| r.tag_val)
+            .collect();
+        assert_eq!(tag1, vec![10.0, 11.0, 13.0, 14.0]);
+        // Tag2 没有量程信息，原样保留
+        assert!(
+            result
+                .iter()
+                .any(|r| r.tag_name == "Tag2" && r.tag_val == -9999.0)
+        );
+
+        // 关闭开关时不裁剪
+        let mut disabled = config.clone();
+        disabled.range_limit.enabled = false;
+        let result = process_data(records, &disabled).unwrap();
+        assert_eq!(result.len(), 6);
+    }
+
+    #[test]
+    fn test_process_data_ohlc_series() {
+        let records = create_test_records(10);
+        let config = DataProcessingConfig::new().with_resample(300, "ohlc");
+
+        let result = process_data(records, &config).unwrap();
+        let series = records_to_series(&result);
+        let names: Vec<&str> = series.iter().map(|s| s.tag_name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["Tag1.close", "Tag1.high", "Tag1.low", "Tag1.open"]
+        );
+        assert!(series.iter().all(|s| s.data.len() == 2));
+    }
+
+    #[test]
+    fn test_process_data_multi_stat_series() {
+        let records = create_test_records(10);
+        let config = DataProcessingConfig::new().with_resample_stats(300, &["mean", "max"]);
+
+        let result = process_data(records.clone(), &config).unwrap();
+        let series = records_to_series(&result);
+        let names: Vec<&str> = series.iter().map(|s| s.tag_name.as_str()).collect();
+        assert_eq!(names, vec!["Tag1_max", "Tag1_mean"]);
+        assert!(series.iter().all(|s| s.data.len() == 2));
+
+        // 各派生序列与单统计重采样一致
+        let mean = process_data(
+            records,
+            &DataProcessingConfig::new().with_resample(300, "mean"),
+        )
+        .unwrap();
+        let mean_values: Vec<f64> = mean.iter().map(|r| r.tag_val).collect();
+        assert_eq!(
+            series[1].data.iter().map(|p| p[1]).collect::<Vec<_>>(),
+            mean_values
+        );
+        for ([_, max], [_, mean]) in series[0].data.iter().zip(&series[1].data) {
+            assert!(max >= mean);
+        }
+    }
+
+    #[test]
+    fn test_process_data_std_band() {
+        let record = |i: usize, value: f64| {
+            HistoryRecord::new(
+                format!("2024-01-01T00:{:02}:00.000", i),
+                "Tag1".to_string(),
+                value,
+                "Good".to_string(),
+            )
+        };
+        let config = DataProcessingConfig::new()
+            .with_smoothing(5, "moving_avg")
+            .with_std_band(5, 2.0);
+
+        // 常数序列：带宽为 0
+        let constant: Vec<HistoryRecord> = (0..20).map(|i| record(i, 42.0)).collect();
+        let series = records_to_series(&process_data(constant, &config).unwrap());
+        let names: Vec<&str> = series.iter().map(|s| s.tag_name.as_str()).collect();
+        assert_eq!(names, vec!["Tag1", "Tag1.lower", "Tag1.upper"]);
+        for ([_, lower], [_, upper]) in series[1].data.iter().zip(&series[2].data) {
+            assert_eq!(*upper - *lower, 0.0);
+            assert_eq!(*upper, 42.0);
+        }
+
+        // 波动序列：带宽 > 0，且包住中线；减基准时随中线平移
+        let wavy: Vec<HistoryRecord> = (0..20)
+            .map(|i| record(i, if i % 2 == 0 { 10.0 } else { 20.0 }))
+            .collect();
+        let series = records_to_series(&process_data(wavy.clone(), &config).unwrap());
+        for (([_, mid], [_, lower]), [_, upper]) in series[0]
+            .data
+            .iter()
+            .zip(&series[1].data)
+            .zip(&series[2].data)
+        {
+            assert!(upper - lower > 0.0);
+            assert!(lower < mid && mid < upper);
+        }
+        let shifted =
+            records_to_series(&process_data(wavy, &config.clone().with_baseline("first")).unwrap());
+        let offset = series[0].data[0][1] - shifted[0].data[0][1];
+        assert!((series[2].data[3][1] - shifted[2].data[3][1] - offset).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_interpolate_value_at() {
+        let record = |time: &str, value: f64| {
+            HistoryRecord::new(
+                time.to_string(),
+                "T1".to_string(),
+                value,
+                "Good".to_string(),
+            )
+        };
+        // 前后点（数据库返回顺序不保证）
+        let records = vec![
+            record("2024-01-01T10:01:00.000", 20.0),
+            record("2024-01-01T10:00:00.000", 10.0),
+        ];
+
+        let mid = interpolate_value_at(&records, "2024-01-01T10:00:45").unwrap();
+        assert_eq!(mid, Some(17.5));
+        let exact = interpolate_value_at(&records, "2024-01-01T10:01:00").unwrap();
+        assert_eq!(exact, Some(20.0));
+
+        // 超出数据范围（缺少后点或前点）
+        assert_eq!(
+            interpolate_value_at(&records, "2024-01-01T10:05:00").unwrap(),
+            None
+        );
+        assert_eq!(
+            interpolate_value_at(&records[..1], "2024-01-01T09:59:00").unwrap(),
+            None
+        );
+        assert!(interpolate_value_at(&records, "昨天").is_err());
+    }
+
+    #[test]
+    fn test_records_to_series_non_finite_to_null() {
+        let mut records = create_test_records(4);
+        records[1].tag_val = f64::NAN;
+        records[2].tag_val = f64::INFINITY;
+
+        let series = records_to_series(&records);
+        assert!(series[0].data[1][1].is_nan());
+        assert!(series[0].data[2][1].is_nan());
+
+        let json = serde_json::to_string(&series).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let data = value[0]["data"].as_array().unwrap();
+        assert!(data[1][1].is_null() && data[2][1].is_null());
+        assert_eq!(data[3][1], 13.0);
+    }
+
+    #[test]
+    fn test_ensure_monotonic_time() {
+        let mut data = vec![[3000.0, 3.0], [1000.0, 1.0], [2000.0, 2.0], [4000.0, 4.0]];
+        let out_of_order = ensure_monotonic_time(&mut data);
+        assert!(out_of_order > 0);
+        let times: Vec<f64> = data.iter().map(|p| p[0]).collect();
+        assert_eq!(times, vec![1000.0, 2000.0, 3000.0, 4000.0]);
+        // 值跟随时间一起移动
+        assert!(data.iter().all(|p| p[0] == p[1] * 1000.0));
+
+        // 已有序时不计数
+        assert_eq!(ensure_monotonic_time(&mut data), 0);
+    }
+
+    #[test]
+    fn test_records_to_series_reorders_shuffled_input() {
+        let mut records = create_test_records(10);
+        records.reverse();
+        records.swap(2, 7);
+
+        let series = records_to_series(&records);
+        assert!(series[0].data.windows(2).all(|w| w[0][0] <= w[1][0]));
+    }
+
     #[test]
     fn test_records_to_series() {
         let records = create_test_records(5);
@@ -188,6 +752,85 @@ mod tests {
         assert_eq!(series[0].data.len(), 5);
     }
 
+    #[test]
+    fn test_insert_gap_markers() {
+        // 每分钟一个点，挖掉 00:10 ~ 00:19
+        let records: Vec<HistoryRecord> = create_test_records(30)
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| !(10..20).contains(i))
+            .map(|(_, r)| r)
+            .collect();
+        let mut data = records_to_series(&records).remove(0).data;
+        let before = data.len();
+
+        insert_gap_markers(&mut data, &SeriesGapConfig::enabled());
+
+        assert_eq!(data.len(), before + 1);
+        let markers: Vec<usize> = (0..data.len()).filter(|&i| data[i][1].is_nan()).collect();
+        assert_eq!(markers, vec![10]);
+        assert!(data[10][0] > data[9][0] && data[10][0] < data[11][0]);
+    }
+
+    #[test]
+    fn test_series_quality_matches_points() {
+        // 乱序输入 + 断采：质量码需随数据点一起重排，断点处为空
+        let mut records: Vec<HistoryRecord> = create_test_records(30)
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| !(10..20).contains(i))
+            .map(|(i, mut r)| {
+                if i.is_multiple_of(7) {
+                    r.tag_quality = "Bad".into();
+                }
+                r
+            })
+            .collect();
+        records.reverse();
+
+        let mut params = QueryParams::new(
+            "2024-01-01T00:00:00".to_string(),
+            "2024-01-01T01:00:00".to_string(),
+        );
+        assert!(build_series(&records, &params)[0].quality.is_none());
+
+        params.include_quality = true;
+        params.gap = Some(SeriesGapConfig::enabled());
+        let series = build_series(&records, &params).remove(0);
+        let quality = series.quality.unwrap();
+        assert_eq!(quality.len(), series.data.len());
+
+        for (point, q) in series.data.iter().zip(&quality) {
+            if point[1].is_nan() {
+                assert_eq!(q, "");
+                continue;
+            }
+            // 测试数据的值为 分钟 + 10
+            let minute = point[1] as usize - 10;
+            let expected = if minute.is_multiple_of(7) {
+                "Bad"
+            } else {
+                "Good"
+            };
+            assert_eq!(q, expected, "minute {}", minute);
+        }
+    }
+
+    #[test]
+    fn test_insert_gap_markers_no_gap() {
+        let mut data = records_to_series(&create_test_records(10)).remove(0).data;
+        insert_gap_markers(&mut data, &SeriesGapConfig::enabled());
+        assert_eq!(data.len(), 10);
+        assert!(data.iter().all(|p| !p[1].is_nan()));
+    }
+
+    #[test]
+    fn test_estimate_sample_interval() {
+        let data = vec![[0.0, 1.0], [1000.0, 1.0], [2000.0, 1.0], [9000.0, 1.0]];
+        assert_eq!(estimate_sample_interval_ms(&data), Some(1000.0));
+        assert_eq!(estimate_sample_interval_ms(&data[..1]), None);
+    }
+
     #[test]
     fn test_parse_timestamp() {
         let ts = parse_timestamp_ms("2024-01-01T00:00:00.000");
@@ -2,8 +2,10 @@
 
 use std::collections::HashMap;
 
-use crate::error::AppResult;
-use crate::models::HistoryRecord;
+use crate::error::{AppError, AppResult};
+use crate::models::{Baseline, HistoryRecord, InternedStr, RangeLimitConfig};
+
+use super::local_time::{DstAdjustment, parse_local_timestamp_ms};
 
 /// 3σ法则异常值剔除
 /// 移除超出 μ±3σ 范围的数据点
@@ -12,16 +14,7 @@ pub fn remove_outliers(records: Vec<HistoryRecord>) -> AppResult<Vec<HistoryReco
         return Ok(records);
     }
 
-    // 计算均值和标准差
-    let values: Vec<f64> = records.iter().map(|r| r.tag_val).collect();
-    let n = values.len() as f64;
-    let mean = values.iter().sum::<f64>() / n;
-    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
-    let std_dev = variance.sqrt();
-
-    // 3σ 边界
-    let lower = mean - 3.0 * std_dev;
-    let upper = mean + 3.0 * std_dev;
+    let (lower, upper) = sigma_bounds(&records);
 
     // 过滤异常值
     let result: Vec<HistoryRecord> = records
@@ -32,66 +25,499 @@ pub fn remove_outliers(records: Vec<HistoryRecord>) -> AppResult<Vec<HistoryReco
     Ok(result)
 }
 
-/// 时间序列重采样（均值聚合）
-/// interval: 重采样间隔（秒）
-pub fn resample_data(records: Vec<HistoryRecord>, interval: u32) -> AppResult<Vec<HistoryRecord>> {
-    use chrono::{Local, TimeZone};
+/// 3σ法则异常值夹取
+/// 超出 μ±3σ 的值替换为边界值，点数不变（缺失值保持不变）
+pub fn clip_outliers(records: Vec<HistoryRecord>) -> AppResult<Vec<HistoryRecord>> {
+    if records.len() < 3 {
+        return Ok(records);
+    }
 
-    if records.is_empty() {
+    let (lower, upper) = sigma_bounds(&records);
+    Ok(records
+        .into_iter()
+        .map(|mut r| {
+            r.tag_val = clip_value(r.tag_val, lower, upper);
+            r
+        })
+        .collect())
+}
+
+/// 将值夹到 `[lower, upper]`（值或边界为 NaN 时原样返回）
+fn clip_value(value: f64, lower: f64, upper: f64) -> f64 {
+    if value < lower {
+        lower
+    } else if value > upper {
+        upper
+    } else {
+        value
+    }
+}
+
+/// 全局 μ±3σ 边界
+fn sigma_bounds(records: &[HistoryRecord]) -> (f64, f64) {
+    let values: Vec<f64> = records.iter().map(|r| r.tag_val).collect();
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+    let std_dev = variance.sqrt();
+
+    (mean - 3.0 * std_dev, mean + 3.0 * std_dev)
+}
+
+/// 滚动窗口 3σ 异常值剔除（局部均值/标准差，Hampel 滤波思想）
+///
+/// 对每个点取以其为中心、共 `window` 个点的邻域（边界处截断），
+/// 用除该点外的邻居计算均值和标准差，超出 μ±3σ 即视为离群。
+/// 排除自身可避免尖峰拉大局部标准差而漏判。输入应为单个标签按时间排序的数据。
+pub fn remove_outliers_rolling(
+    records: Vec<HistoryRecord>,
+    window: usize,
+) -> AppResult<Vec<HistoryRecord>> {
+    if records.len() < 3 || window < 3 {
+        return Ok(records);
+    }
+
+    let bounds = rolling_sigma_bounds(&records, window);
+    Ok(records
+        .into_iter()
+        .zip(bounds)
+        .filter(|(r, bound)| {
+            bound.is_none_or(|(lower, upper)| r.tag_val >= lower && r.tag_val <= upper)
+        })
+        .map(|(r, _)| r)
+        .collect())
+}
+
+/// 滚动窗口 3σ 异常值夹取：离群值替换为局部边界值，点数不变
+pub fn clip_outliers_rolling(
+    records: Vec<HistoryRecord>,
+    window: usize,
+) -> AppResult<Vec<HistoryRecord>> {
+    if records.len() < 3 || window < 3 {
         return Ok(records);
     }
 
-    // 解析时间并按时间窗口分组
+    let bounds = rolling_sigma_bounds(&records, window);
+    Ok(records
+        .into_iter()
+        .zip(bounds)
+        .map(|(mut r, bound)| {
+            if let Some((lower, upper)) = bound {
+                r.tag_val = clip_value(r.tag_val, lower, upper);
+            }
+            r
+        })
+        .collect())
+}
+
+/// 每个点的局部 μ±3σ 边界（邻居不足 2 个时为 `None`，不做判断）
+fn rolling_sigma_bounds(records: &[HistoryRecord], window: usize) -> Vec<Option<(f64, f64)>> {
+    let values: Vec<f64> = records.iter().map(|r| r.tag_val).collect();
+    let half = window / 2;
+    (0..values.len())
+        .map(|i| {
+            let start = i.saturating_sub(half);
+            let end = (i + half + 1).min(values.len());
+            let neighbors: Vec<f64> = (start..end)
+                .filter(|&j| j != i && !values[j].is_nan())
+                .map(|j| values[j])
+                .collect();
+            if neighbors.len() < 2 {
+                return None;
+            }
+
+            let n = neighbors.len() as f64;
+            let mean = neighbors.iter().sum::<f64>() / n;
+            let std_dev = (neighbors.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt();
+            Some((mean - 3.0 * std_dev, mean + 3.0 * std_dev))
+        })
+        .collect()
+}
+
+/// OHLC 重采样输出的派生序列后缀，顺序即 `resample_ohlc` 的返回顺序
+pub const OHLC_COMPONENTS: [&str; 4] = ["open", "high", "low", "close"];
+
+/// OHLC 派生序列的标签名，如 `TAG.open`
+pub fn ohlc_tag_name(tag_name: &str, component: &str) -> String {
+    format!("{}.{}", tag_name, component)
+}
+
+/// 波动带输出的派生序列后缀，顺序即 `rolling_std_band` 的返回顺序
+pub const STD_BAND_COMPONENTS: [&str; 2] = ["upper", "lower"];
+
+/// 波动带派生序列的标签名，如 `TAG.upper`
+pub fn band_tag_name(tag_name: &str, component: &str) -> String {
+    format!("{}.{}", tag_name, component)
+}
+
+/// 多统计重采样的派生序列标签名，如 `TAG_max`
+pub fn stat_tag_name(tag_name: &str, stat: &str) -> String {
+    format!("{}_{}", tag_name, stat.to_ascii_lowercase())
+}
+
+/// 多统计重采样的统计量（见 `RESAMPLE_STATS`）
+#[derive(Debug, Clone, Copy)]
+enum WindowStat {
+    Mean,
+    Max,
+    Min,
+    First,
+    Last,
+    Sum,
+    Count,
+}
+
+impl WindowStat {
+    fn parse(stat: &str) -> Option<Self> {
+        match stat.to_ascii_lowercase().as_str() {
+            "mean" => Some(Self::Mean),
+            "max" => Some(Self::Max),
+            "min" => Some(Self::Min),
+            "first" => Some(Self::First),
+            "last" => Some(Self::Last),
+            "sum" => Some(Self::Sum),
+            "count" => Some(Self::Count),
+            _ => None,
+        }
+    }
+
+    /// 窗口内的统计值（NaN 不参与统计，没有有效值时为 NaN，`Count` 为 0）
+    fn apply(self, values: &[f64]) -> f64 {
+        let mut valid = values.iter().copied().filter(|v| !v.is_nan());
+        let or_nan = |value: Option<f64>| value.unwrap_or(f64::NAN);
+        match self {
+            Self::Mean => nan_mean(valid),
+            Self::Max => or_nan(valid.reduce(f64::max)),
+            Self::Min => or_nan(valid.reduce(f64::min)),
+            Self::First => or_nan(valid.next()),
+            Self::Last => or_nan(valid.next_back()),
+            Self::Sum => or_nan(valid.reduce(|a, b| a + b)),
+            Self::Count => valid.count() as f64,
+        }
+    }
+}
+
+/// 按时间窗口分组，返回按窗口起点排序的 (窗口起点, 窗口内按时间排序的记录)
+///
+/// 夏令时切换日的歧义/不存在时间按 `prefer_latest` 解析，不会丢点。
+fn group_by_window(
+    records: &[HistoryRecord],
+    interval: u32,
+    prefer_latest: bool,
+) -> Vec<(i64, Vec<&HistoryRecord>)> {
     let interval_ms = interval as i64 * 1000;
-    let mut windows: HashMap<i64, Vec<&HistoryRecord>> = HashMap::new();
+    let mut windows: HashMap<i64, Vec<(i64, &HistoryRecord)>> = HashMap::new();
+    let (mut ambiguous, mut skipped) = (0usize, 0usize);
 
-    for record in &records {
-        // 解析 ISO 时间字符串（本地时间）
-        if let Ok(dt) =
-            chrono::NaiveDateTime::parse_from_str(&record.date_time, "%Y-%m-%dT%H:%M:%S%.3f")
+    for record in records {
+        if let Some((timestamp_ms, adjustment)) =
+            parse_local_timestamp_ms(&record.date_time, prefer_latest)
         {
-            if let Some(local_dt) = Local.from_local_datetime(&dt).single() {
-                let timestamp_ms = local_dt.timestamp_millis();
-                let window_key = (timestamp_ms / interval_ms) * interval_ms;
-                windows.entry(window_key).or_default().push(record);
+            match adjustment {
+                DstAdjustment::None => {}
+                DstAdjustment::Ambiguous => ambiguous += 1,
+                DstAdjustment::Skipped => skipped += 1,
             }
-        } else if let Ok(dt) =
-            chrono::NaiveDateTime::parse_from_str(&record.date_time, "%Y-%m-%dT%H:%M:%S")
-            && let Some(local_dt) = Local.from_local_datetime(&dt).single()
-        {
-            let timestamp_ms = local_dt.timestamp_millis();
             let window_key = (timestamp_ms / interval_ms) * interval_ms;
-            windows.entry(window_key).or_default().push(record);
+            windows
+                .entry(window_key)
+                .or_default()
+                .push((timestamp_ms, record));
         }
     }
 
-    // 对每个窗口计算均值
-    let mut result: Vec<HistoryRecord> = windows
+    if ambiguous + skipped > 0 {
+        tracing::info!(
+            target: "industry_vis::processing",
+            "重采样遇到夏令时切换: 歧义时间 {} 点（取{}）, 不存在时间 {} 点（已顺延）",
+            ambiguous,
+            if prefer_latest { "较晚" } else { "较早" },
+            skipped
+        );
+    }
+
+    let mut windows: Vec<(i64, Vec<&HistoryRecord>)> = windows
         .into_iter()
-        .map(|(window_key, window_records)| {
-            let avg_val =
-                window_records.iter().map(|r| r.tag_val).sum::<f64>() / window_records.len() as f64;
-
-            // 使用窗口开始时间作为时间戳
-            let dt = chrono::DateTime::from_timestamp_millis(window_key)
-                .map(|utc| utc.with_timezone(&Local).naive_local())
-                .unwrap_or_default();
-
-            HistoryRecord::new(
-                dt.format("%Y-%m-%dT%H:%M:%S%.3f").to_string(),
-                window_records[0].tag_name.clone(),
-                avg_val,
-                window_records[0].tag_quality.clone(),
+        .map(|(window_key, mut window_records)| {
+            window_records.sort_by_key(|(ts, _)| *ts);
+            (
+                window_key,
+                window_records.into_iter().map(|(_, r)| r).collect(),
             )
         })
         .collect();
+    windows.sort_by_key(|(window_key, _)| *window_key);
+    windows
+}
 
-    // 按时间排序
-    result.sort_by(|a, b| a.date_time.cmp(&b.date_time));
+/// 窗口起点格式化为本地时间字符串
+fn format_window_time(window_key: i64) -> String {
+    use chrono::Local;
+
+    chrono::DateTime::from_timestamp_millis(window_key)
+        .map(|utc| utc.with_timezone(&Local).naive_local())
+        .unwrap_or_default()
+        .format("%Y-%m-%dT%H:%M:%S%.3f")
+        .to_string()
+}
+
+/// 按 (标签, 时间) 去重
+///
+/// `keep_last` 为 true 时保留最后一条记录的值，否则保留第一条；
+/// 结果保持各键首次出现的顺序。
+pub fn dedup_records(records: Vec<HistoryRecord>, keep_last: bool) -> Vec<HistoryRecord> {
+    let mut positions: HashMap<(InternedStr, String), usize> =
+        HashMap::with_capacity(records.len());
+    let mut result: Vec<HistoryRecord> = Vec::with_capacity(records.len());
+
+    for record in records {
+        let key = (record.tag_name.clone(), record.date_time.clone());
+        match positions.get(&key) {
+            Some(&index) => {
+                if keep_last {
+                    result[index] = record;
+                }
+            }
+            None => {
+                positions.insert(key, result.len());
+                result.push(record);
+            }
+        }
+    }
+
+    result
+}
+
+/// 按标签工程量程剔除或夹取超量程的值
+///
+/// 未配置量程的标签原样保留；NaN 不视为超量程。
+pub fn limit_to_ranges(
+    records: Vec<HistoryRecord>,
+    config: &RangeLimitConfig,
+) -> Vec<HistoryRecord> {
+    if config.ranges.is_empty() {
+        return records;
+    }
+    let clip = config.is_clip();
+    records
+        .into_iter()
+        .filter_map(|mut record| {
+            let Some((min, max)) = config.range_for(&record.tag_name) else {
+                return Some(record);
+            };
+            if record.tag_val < min || record.tag_val > max {
+                if !clip {
+                    return None;
+                }
+                record.tag_val = record.tag_val.clamp(min, max);
+            }
+            Some(record)
+        })
+        .collect()
+}
+
+/// 忽略 NaN 的均值（全部为 NaN 时返回 NaN）
+fn nan_mean(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values
+        .filter(|v| !v.is_nan())
+        .fold((0.0, 0usize), |(sum, count), v| (sum + v, count + 1));
+    if count == 0 {
+        f64::NAN
+    } else {
+        sum / count as f64
+    }
+}
+
+/// 按标签减去基准值（相对基准的偏差显示）
+///
+/// 首值取该标签时间最早的有效值，均值忽略 NaN；没有有效值的标签保持不变。
+pub fn subtract_baseline(
+    mut records: Vec<HistoryRecord>,
+    baseline: Baseline,
+) -> Vec<HistoryRecord> {
+    let mut bases: HashMap<String, f64> = HashMap::new();
+    match baseline {
+        Baseline::First => {
+            let mut firsts: HashMap<&str, &HistoryRecord> = HashMap::new();
+            for record in records.iter().filter(|r| r.tag_val.is_finite()) {
+                firsts
+                    .entry(record.tag_name.as_str())
+                    .and_modify(|first| {
+                        if record.date_time < first.date_time {
+                            *first = record;
+                        }
+                    })
+                    .or_insert(record);
+            }
+            bases.extend(
+                firsts
+                    .into_iter()
+                    .map(|(tag, r)| (tag.to_string(), r.tag_val)),
+            );
+        }
+        Baseline::Mean => {
+            let mut values: HashMap<&str, Vec<f64>> = HashMap::new();
+            for record in &records {
+                values
+                    .entry(record.tag_name.as_str())
+                    .or_default()
+                    .push(record.tag_val);
+            }
+            bases.extend(
+                values
+                    .into_iter()
+                    .map(|(tag, v)| (tag.to_string(), nan_mean(v.into_iter())))
+                    .filter(|(_, mean)| mean.is_finite()),
+            );
+        }
+        Baseline::Value(value) => {
+            for record in &mut records {
+                record.tag_val -= value;
+            }
+            return records;
+        }
+    }
+
+    for record in &mut records {
+        if let Some(base) = bases.get(record.tag_name.as_str()) {
+            record.tag_val -= base;
+        }
+    }
+    records
+}
+
+/// 时间序列重采样（均值聚合）
+/// interval: 重采样间隔（秒）
+///
+/// 与 Polars 的 mean 一致，窗口内的 NaN（空值或坏质量）不参与均值计算，
+/// 仅当整个窗口都是 NaN 时结果为 NaN。
+/// 夏令时回拨的歧义时间按 `prefer_latest` 取较晚/较早的时刻。
+pub fn resample_data(
+    records: Vec<HistoryRecord>,
+    interval: u32,
+    prefer_latest: bool,
+) -> AppResult<Vec<HistoryRecord>> {
+    if records.is_empty() {
+        return Ok(records);
+    }
+
+    // 对每个窗口计算均值，使用窗口开始时间作为时间戳
+    let result = group_by_window(&records, interval, prefer_latest)
+        .into_iter()
+        .map(|(window_key, window_records)| {
+            let avg_val = nan_mean(window_records.iter().map(|r| r.tag_val));
+
+            HistoryRecord {
+                date_time: format_window_time(window_key),
+                tag_name: window_records[0].tag_name.clone(),
+                tag_val: avg_val,
+                tag_quality: window_records[0].tag_quality.clone(),
+            }
+        })
+        .collect();
 
     Ok(result)
 }
 
+/// 时间序列重采样（OHLC 聚合）
+///
+/// 每个窗口输出开/高/低/收四个值，按 `OHLC_COMPONENTS` 顺序返回四个派生序列，
+/// 派生序列标签名见 `ohlc_tag_name`。输入应为单个标签的数据。
+pub fn resample_ohlc(
+    records: Vec<HistoryRecord>,
+    interval: u32,
+    prefer_latest: bool,
+) -> AppResult<Vec<Vec<HistoryRecord>>> {
+    let windows = group_by_window(&records, interval, prefer_latest);
+    let mut components: Vec<Vec<HistoryRecord>> = OHLC_COMPONENTS
+        .iter()
+        .map(|_| Vec::with_capacity(windows.len()))
+        .collect();
+
+    for (window_key, window_records) in windows {
+        let first = window_records[0];
+        let last = window_records[window_records.len() - 1];
+        let values = [
+            first.tag_val,
+            window_records
+                .iter()
+                .map(|r| r.tag_val)
+                .fold(f64::MIN, f64::max),
+            window_records
+                .iter()
+                .map(|r| r.tag_val)
+                .fold(f64::MAX, f64::min),
+            last.tag_val,
+        ];
+        let date_time = format_window_time(window_key);
+
+        for ((series, component), value) in components.iter_mut().zip(OHLC_COMPONENTS).zip(values) {
+            series.push(HistoryRecord {
+                date_time: date_time.clone(),
+                tag_name: ohlc_tag_name(&first.tag_name, component).into(),
+                tag_val: value,
+                tag_quality: last.tag_quality.clone(),
+            });
+        }
+    }
+
+    Ok(components)
+}
+
+/// 时间序列重采样（多统计聚合）
+///
+/// 每个统计量输出一个派生序列（顺序同 `stats`），派生序列标签名见 `stat_tag_name`。
+/// 与 `resample_data` 一致，窗口内的 NaN 不参与统计。输入应为单个标签的数据。
+pub fn resample_stats(
+    records: Vec<HistoryRecord>,
+    interval: u32,
+    stats: &[String],
+    prefer_latest: bool,
+) -> AppResult<Vec<Vec<HistoryRecord>>> {
+    let parsed = stats
+        .iter()
+        .map(|stat| {
+            WindowStat::parse(stat)
+                .map(|parsed| (parsed, stat.as_str()))
+                .ok_or_else(|| AppError::Validation(format!("重采样统计量无效: '{}'", stat)))
+        })
+        .collect::<AppResult<Vec<_>>>()?;
+
+    let windows = group_by_window(&records, interval, prefer_latest);
+    let mut series: Vec<Vec<HistoryRecord>> = parsed
+        .iter()
+        .map(|_| Vec::with_capacity(windows.len()))
+        .collect();
+
+    for (window_key, window_records) in windows {
+        let last = window_records[window_records.len() - 1];
+        let values: Vec<f64> = window_records.iter().map(|r| r.tag_val).collect();
+        let date_time = format_window_time(window_key);
+
+        for (output, (stat, name)) in series.iter_mut().zip(&parsed) {
+            output.push(HistoryRecord {
+                date_time: date_time.clone(),
+                tag_name: stat_tag_name(&last.tag_name, name).into(),
+                tag_val: stat.apply(&values),
+                tag_quality: last.tag_quality.clone(),
+            });
+        }
+    }
+
+    Ok(series)
+}
+
+/// 从 `count` 个点中均匀选取 `max_points`（至少 2）个下标，首尾两点必定保留
+fn downsample_indices(count: usize, max_points: usize) -> std::collections::BTreeSet<usize> {
+    let max_points = max_points.max(2).min(count);
+    if max_points < 2 {
+        return (0..count).collect();
+    }
+    (0..max_points)
+        .map(|k| (k * (count - 1) + (max_points - 1) / 2) / (max_points - 1))
+        .collect()
+}
+
 /// 移动平均平滑滤波
 pub fn smooth_data(records: Vec<HistoryRecord>, window: usize) -> AppResult<Vec<HistoryRecord>> {
     if records.len() < window || window < 2 {
@@ -123,7 +549,89 @@ pub fn smooth_data(records: Vec<HistoryRecord>, window: usize) -> AppResult<Vec<
     Ok(result)
 }
 
+/// 滚动标准差波动带
+///
+/// 居中窗口（与移动平均一致）内有效值的均值 ± `multiplier` × 样本标准差，
+/// 按 `STD_BAND_COMPONENTS` 顺序返回上沿、下沿两个派生序列（标签名见 `band_tag_name`）。
+/// 窗口内有效值不足 2 个时为 NaN（缺失值）。输入应为单个标签的数据。
+///
+/// 窗口滑动时增量维护有效值的个数、和与平方和，整体 O(n)。
+/// 累加前减去首个有效值作为偏移，减小大数值下平方和相减的精度损失。
+pub fn rolling_std_band(
+    records: &[HistoryRecord],
+    window: usize,
+    multiplier: f64,
+) -> Vec<Vec<HistoryRecord>> {
+    let mut components: Vec<Vec<HistoryRecord>> = STD_BAND_COMPONENTS
+        .iter()
+        .map(|_| Vec::with_capacity(records.len()))
+        .collect();
+
+    let shift = records
+        .iter()
+        .map(|r| r.tag_val)
+        .find(|v| !v.is_nan())
+        .unwrap_or(0.0);
+    let half = window / 2;
+    let (mut count, mut sum, mut sum_sq) = (0usize, 0.0f64, 0.0f64);
+    let (mut lo, mut hi) = (0usize, 0usize);
+
+    for (i, record) in records.iter().enumerate() {
+        let start = i.saturating_sub(half);
+        let end = (i + half + 1).min(records.len());
+        while hi < end {
+            let v = records[hi].tag_val;
+            if !v.is_nan() {
+                let d = v - shift;
+                count += 1;
+                sum += d;
+                sum_sq += d * d;
+            }
+            hi += 1;
+        }
+        while lo < start {
+            let v = records[lo].tag_val;
+            if !v.is_nan() {
+                let d = v - shift;
+                count -= 1;
+                sum -= d;
+                sum_sq -= d * d;
+            }
+            lo += 1;
+        }
+
+        let (upper, lower) = if count < 2 {
+            (f64::NAN, f64::NAN)
+        } else {
+            let n = count as f64;
+            let mean = sum / n;
+            let variance = ((sum_sq - sum * mean) / (n - 1.0)).max(0.0);
+            let std_dev = variance.sqrt();
+            let mean = mean + shift;
+            (mean + multiplier * std_dev, mean - multiplier * std_dev)
+        };
+
+        for ((series, component), value) in components
+            .iter_mut()
+            .zip(STD_BAND_COMPONENTS)
+            .zip([upper, lower])
+        {
+            series.push(HistoryRecord {
+                date_time: record.date_time.clone(),
+                tag_name: band_tag_name(&record.tag_name, component).into(),
+                tag_val: value,
+                tag_quality: record.tag_quality.clone(),
+            });
+        }
+    }
+
+    components
+}
+
 /// 降采样
+///
+/// 每个标签按时间均匀抽取不超过 `max_points_per_tag` 个点，首尾两点始终保留，
+/// 图表两端与查询时间范围对齐。
 pub fn downsample(
     records: Vec<HistoryRecord>,
     max_points_per_tag: usize,
@@ -133,7 +641,7 @@ pub fn downsample(
     }
 
     // 按标签名分组
-    let mut tag_groups: HashMap<String, Vec<HistoryRecord>> = HashMap::new();
+    let mut tag_groups: HashMap<InternedStr, Vec<HistoryRecord>> = HashMap::new();
     for record in records {
         tag_groups
             .entry(record.tag_name.clone())
@@ -150,12 +658,16 @@ pub fn downsample(
         if count <= max_points_per_tag {
             result.extend(tag_records);
         } else {
-            let step = count / max_points_per_tag;
-            for (i, record) in tag_records.into_iter().enumerate() {
-                if i % step == 0 {
-                    result.push(record);
-                }
-            }
+            let mut tag_records = tag_records;
+            tag_records.sort_by(|a, b| a.date_time.cmp(&b.date_time));
+            let keep = downsample_indices(count, max_points_per_tag);
+            result.extend(
+                tag_records
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(i, _)| keep.contains(i))
+                    .map(|(_, record)| record),
+            );
         }
     }
 
@@ -182,6 +694,52 @@ mod tests {
             .collect()
     }
 
+    #[test]
+    fn test_rolling_std_band_matches_direct_computation() {
+        let mut records = create_test_records(40);
+        for (i, record) in records.iter_mut().enumerate() {
+            record.tag_val = 1.0e6 + ((i * 7) % 11) as f64;
+        }
+        records[5].tag_val = f64::NAN;
+        records[6].tag_val = f64::NAN;
+
+        let window = 7;
+        let band = rolling_std_band(&records, window, 2.0);
+        for (i, (upper, lower)) in band[0].iter().zip(&band[1]).enumerate() {
+            let start = i.saturating_sub(window / 2);
+            let end = (i + window / 2 + 1).min(records.len());
+            let values: Vec<f64> = records[start..end]
+                .iter()
+                .map(|r| r.tag_val)
+                .filter(|v| !v.is_nan())
+                .collect();
+            let n = values.len() as f64;
+            let mean = values.iter().sum::<f64>() / n;
+            let std_dev =
+                (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+            assert!(
+                (upper.tag_val - (mean + 2.0 * std_dev)).abs() < 1e-6,
+                "i={}",
+                i
+            );
+            assert!(
+                (lower.tag_val - (mean - 2.0 * std_dev)).abs() < 1e-6,
+                "i={}",
+                i
+            );
+            assert_eq!(upper.tag_name.as_str(), "Tag1.upper");
+        }
+
+        // 窗口内有效值不足 2 个时为缺失值
+        let sparse = vec![records[0].clone(), {
+            let mut r = records[1].clone();
+            r.tag_val = f64::NAN;
+            r
+        }];
+        let band = rolling_std_band(&sparse, 2, 2.0);
+        assert!(band[0][0].tag_val.is_nan());
+    }
+
     #[test]
     fn test_remove_outliers() {
         let mut records = create_test_records(10);
@@ -198,6 +756,112 @@ mod tests {
         assert!(result.iter().all(|r| r.tag_val < 100.0));
     }
 
+    #[test]
+    fn test_clip_outliers_keeps_points() {
+        let mut records = create_test_records(30);
+        records[15].tag_val = 1000.0;
+
+        let dropped = remove_outliers(records.clone()).unwrap();
+        assert_eq!(dropped.len(), 29);
+
+        let clipped = clip_outliers(records.clone()).unwrap();
+        assert_eq!(clipped.len(), 30);
+        let (_, upper) = sigma_bounds(&records);
+        assert_eq!(clipped[15].tag_val, upper);
+        assert!(clipped[15].tag_val < 1000.0);
+        // 正常值不受影响，时间不变
+        assert_eq!(clipped[3].tag_val, records[3].tag_val);
+        assert_eq!(clipped[15].date_time, records[15].date_time);
+    }
+
+    #[test]
+    fn test_clip_outliers_rolling() {
+        let mut records = create_test_records(30);
+        records[15].tag_val = 1000.0;
+
+        let dropped = remove_outliers_rolling(records.clone(), 7).unwrap();
+        assert_eq!(dropped.len(), 29);
+
+        let clipped = clip_outliers_rolling(records, 7).unwrap();
+        assert_eq!(clipped.len(), 30);
+        assert!(clipped[15].tag_val < 100.0);
+        assert_eq!(clipped[14].tag_val, 24.0);
+    }
+
+    #[test]
+    fn test_rolling_outliers_on_trend() {
+        // 线性爬升的信号中夹一个局部尖峰
+        let mut records: Vec<HistoryRecord> = (0..100)
+            .map(|i| {
+                HistoryRecord::new(
+                    format!("2024-01-01T00:{:02}:{:02}.000", i / 60, i % 60),
+                    "Tag1".to_string(),
+                    i as f64,
+                    "Good".to_string(),
+                )
+            })
+            .collect();
+        records[50].tag_val = 80.0;
+
+        // 全局 3σ 被趋势拉大标准差，尖峰漏判
+        let global = remove_outliers(records.clone()).unwrap();
+        assert_eq!(global.len(), 100);
+
+        // 滚动模式只剔除尖峰，趋势上的正常点全部保留
+        let rolling = remove_outliers_rolling(records, 11).unwrap();
+        assert_eq!(rolling.len(), 99);
+        assert!(
+            rolling
+                .iter()
+                .all(|r| r.date_time != "2024-01-01T00:00:50.000")
+        );
+        assert!(rolling.iter().any(|r| r.tag_val == 99.0));
+    }
+
+    #[test]
+    fn test_dedup_records() {
+        let records = vec![
+            HistoryRecord::new("t1".into(), "A".into(), 1.0, "Good".into()),
+            HistoryRecord::new("t1".into(), "A".into(), 2.0, "Good".into()),
+            HistoryRecord::new("t1".into(), "B".into(), 3.0, "Good".into()),
+            HistoryRecord::new("t2".into(), "A".into(), 4.0, "Good".into()),
+            HistoryRecord::new("t1".into(), "A".into(), 5.0, "Good".into()),
+        ];
+
+        let first = dedup_records(records.clone(), false);
+        assert_eq!(first.len(), 3);
+        let values: Vec<f64> = first.iter().map(|r| r.tag_val).collect();
+        assert_eq!(values, vec![1.0, 3.0, 4.0]);
+
+        let last = dedup_records(records, true);
+        let values: Vec<f64> = last.iter().map(|r| r.tag_val).collect();
+        assert_eq!(values, vec![5.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_limit_to_ranges_clip() {
+        let records = vec![
+            HistoryRecord::new("t1".into(), "TT".into(), -9999.0, "Good".into()),
+            HistoryRecord::new("t2".into(), "TT".into(), 25.0, "Good".into()),
+            HistoryRecord::new("t3".into(), "TT".into(), 180.0, "Good".into()),
+        ];
+        let config = RangeLimitConfig {
+            enabled: true,
+            mode: "clip".to_string(),
+            ranges: vec![crate::models::TagRange {
+                tag: "TT".to_string(),
+                min: 0.0,
+                max: 150.0,
+            }],
+        };
+
+        let values: Vec<f64> = limit_to_ranges(records, &config)
+            .iter()
+            .map(|r| r.tag_val)
+            .collect();
+        assert_eq!(values, vec![0.0, 25.0, 150.0]);
+    }
+
     #[test]
     fn test_smooth_data() {
         let records = create_test_records(10);
@@ -208,15 +872,158 @@ mod tests {
     #[test]
     fn test_resample_data() {
         let records = create_test_records(10);
-        let result = resample_data(records, 120).unwrap(); // 2分钟间隔
+        let result = resample_data(records, 120, false).unwrap(); // 2分钟间隔
         // 10分钟数据，2分钟间隔，应该约5个点
         assert!(result.len() <= 6);
     }
 
+    #[test]
+    fn test_resample_ignores_nan() {
+        let values = [10.0, f64::NAN, 20.0, f64::NAN, f64::NAN];
+        let records: Vec<HistoryRecord> = values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                HistoryRecord::new(
+                    // 前 3 个点在第 0 分钟，后 2 个点在第 1 分钟
+                    format!("2024-01-01T00:0{}:{:02}.000", i / 3, (i % 3) * 10),
+                    "Tag1".to_string(),
+                    *v,
+                    "Good".to_string(),
+                )
+            })
+            .collect();
+
+        let result = resample_data(records, 60, false).unwrap();
+        assert_eq!(result.len(), 2);
+        // 含 NaN 的窗口得到有效均值
+        assert_eq!(result[0].tag_val, 15.0);
+        // 全部为 NaN 的窗口仍为 NaN
+        assert!(result[1].tag_val.is_nan());
+    }
+
+    #[test]
+    fn test_resample_ohlc() {
+        // 同一分钟内 4 个点，倒序输入验证开/收按时间取值
+        let values = [12.0, 5.0, 20.0, 8.0];
+        let mut records: Vec<HistoryRecord> = values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                HistoryRecord::new(
+                    format!("2024-01-01T00:00:{:02}.000", i * 10),
+                    "Tag1".to_string(),
+                    *v,
+                    "Good".to_string(),
+                )
+            })
+            .collect();
+        records.reverse();
+
+        let result = resample_ohlc(records, 60, false).unwrap();
+        assert_eq!(result.len(), 4);
+
+        let window_values: Vec<f64> = result.iter().map(|s| s[0].tag_val).collect();
+        assert_eq!(window_values, vec![12.0, 20.0, 5.0, 8.0]);
+
+        let names: Vec<&str> = result.iter().map(|s| s[0].tag_name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["Tag1.open", "Tag1.high", "Tag1.low", "Tag1.close"]
+        );
+    }
+
+    #[test]
+    fn test_resample_ohlc_multiple_windows() {
+        let records = create_test_records(10);
+        let result = resample_ohlc(records, 120, false).unwrap();
+        // 每个窗口包含 2 个点：开=首值，收=末值
+        for (open, close) in result[0].iter().zip(&result[3]) {
+            assert_eq!(close.tag_val - open.tag_val, 1.0);
+        }
+        assert!(result.iter().all(|s| s.len() == result[0].len()));
+    }
+
+    fn record_at(second: usize, tag: &str, value: f64) -> HistoryRecord {
+        HistoryRecord::new(
+            format!("2024-01-01T00:00:{:02}.000", second),
+            tag.to_string(),
+            value,
+            "Good".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_subtract_baseline_first() {
+        let records: Vec<HistoryRecord> = [("T1", 10.0), ("T2", 100.0), ("T1", 12.0), ("T2", 95.0)]
+            .iter()
+            .enumerate()
+            .map(|(i, (tag, v))| record_at(i, tag, *v))
+            .collect();
+        let result = subtract_baseline(records, Baseline::First);
+
+        for tag in ["T1", "T2"] {
+            let first = result.iter().find(|r| r.tag_name == tag).unwrap();
+            assert_eq!(first.tag_val, 0.0);
+        }
+        assert_eq!(result[2].tag_val, 2.0);
+        assert_eq!(result[3].tag_val, -5.0);
+    }
+
+    #[test]
+    fn test_subtract_baseline_mean() {
+        let records: Vec<HistoryRecord> = [1.0, 2.0, f64::NAN, 6.0]
+            .iter()
+            .enumerate()
+            .map(|(i, v)| record_at(i, "T1", *v))
+            .collect();
+        let result = subtract_baseline(records, Baseline::Mean);
+
+        let valid: Vec<f64> = result
+            .iter()
+            .map(|r| r.tag_val)
+            .filter(|v| !v.is_nan())
+            .collect();
+        assert_eq!(valid, vec![-2.0, -1.0, 3.0]);
+        assert!(valid.iter().sum::<f64>().abs() < 1e-9);
+    }
+
     #[test]
     fn test_downsample() {
         let records = create_test_records(100);
         let result = downsample(records, 10).unwrap();
         assert!(result.len() <= 10);
     }
+
+    #[test]
+    fn test_downsample_keeps_first_and_last_per_tag() {
+        // 两个标签各 97 个点（不能整除），输入乱序
+        let mut records: Vec<HistoryRecord> = (0..97)
+            .flat_map(|i| {
+                ["A", "B"].map(|tag| {
+                    HistoryRecord::new(
+                        format!("2024-01-01T{:02}:{:02}:00.000", i / 60, i % 60),
+                        tag.to_string(),
+                        i as f64,
+                        "Good".to_string(),
+                    )
+                })
+            })
+            .collect();
+        records.reverse();
+
+        for max_points in [1, 2, 10, 50] {
+            let result = downsample(records.clone(), max_points).unwrap();
+            for tag in ["A", "B"] {
+                let values: Vec<f64> = result
+                    .iter()
+                    .filter(|r| r.tag_name == tag)
+                    .map(|r| r.tag_val)
+                    .collect();
+                assert!(values.len() <= max_points.max(2));
+                assert_eq!(values.first(), Some(&0.0));
+                assert_eq!(values.last(), Some(&96.0));
+            }
+        }
+    }
 }
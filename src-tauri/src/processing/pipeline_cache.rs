@@ -0,0 +1,227 @@
+//! 处理管道中间结果缓存
+//!
+//! 调参时通常只改平滑窗口或基准，去重、量程裁剪、异常值剔除和重采样（前置步骤）
+//! 的结果不变。按"原始数据指纹 + 前置步骤配置"缓存前置步骤的输出，
+//! 后续只改平滑 / 基准时直接复用，不再从头重算整条管道。
+
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock};
+use tracing::debug;
+
+use super::{finish_tag_data, prefix_tag_data};
+use crate::error::AppResult;
+use crate::models::{DataProcessingConfig, HistoryRecord, InternedStr, records_content_hash};
+
+/// 默认最多缓存的中间结果数
+const DEFAULT_CAPACITY: usize = 8;
+
+/// 超过该记录数的数据不缓存（避免常驻过多内存）
+const MAX_CACHED_RECORDS: usize = 200_000;
+
+/// 全局管道缓存
+static GLOBAL: LazyLock<PipelineCache> = LazyLock::new(|| PipelineCache::new(DEFAULT_CAPACITY));
+
+/// 中间结果的键：原始数据指纹 + 前置步骤配置
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PrefixKey {
+    data_hash: u64,
+    config: String,
+}
+
+impl PrefixKey {
+    fn new(records: &[HistoryRecord], config: &DataProcessingConfig) -> AppResult<Self> {
+        Ok(Self {
+            data_hash: records_content_hash(records),
+            config: serde_json::to_string(&(
+                &config.dedup,
+                &config.range_limit,
+                &config.outlier_removal,
+                &config.resample,
+            ))?,
+        })
+    }
+}
+
+/// 前置步骤的输出：每个标签待平滑的序列
+type PrefixOutput = Arc<Vec<Vec<Vec<HistoryRecord>>>>;
+
+/// 处理管道中间结果缓存（按最近使用淘汰）
+pub struct PipelineCache {
+    entries: Mutex<VecDeque<(PrefixKey, PrefixOutput)>>,
+    capacity: usize,
+    /// 命中次数
+    hits: AtomicU64,
+    /// 前置步骤实际执行次数
+    prefix_runs: AtomicU64,
+}
+
+impl PipelineCache {
+    /// 创建最多缓存 `capacity` 个中间结果的缓存
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(VecDeque::new()),
+            capacity: capacity.max(1),
+            hits: AtomicU64::new(0),
+            prefix_runs: AtomicU64::new(0),
+        }
+    }
+
+    /// 全局缓存（查询处理与实时预览共用）
+    pub fn global() -> &'static Self {
+        &GLOBAL
+    }
+
+    /// 命中次数
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// 前置步骤实际执行次数
+    pub fn prefix_runs(&self) -> u64 {
+        self.prefix_runs.load(Ordering::Relaxed)
+    }
+
+    /// 清空缓存
+    pub fn clear(&self) {
+        self.entries.lock().clear();
+    }
+
+    fn get(&self, key: &PrefixKey) -> Option<PrefixOutput> {
+        let mut entries = self.entries.lock();
+        let pos = entries.iter().position(|(k, _)| k == key)?;
+        let entry = entries.remove(pos)?;
+        let output = Arc::clone(&entry.1);
+        entries.push_back(entry);
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Some(output)
+    }
+
+    fn put(&self, key: PrefixKey, output: PrefixOutput) {
+        let mut entries = self.entries.lock();
+        entries.retain(|(k, _)| *k != key);
+        if entries.len() >= self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back((key, output));
+    }
+}
+
+/// 前置步骤是否有任何一步启用（全部关闭时无需缓存）
+fn has_prefix_steps(config: &DataProcessingConfig) -> bool {
+    config.dedup.enabled
+        || config.range_limit.enabled
+        || config.outlier_removal.enabled
+        || (config.resample.enabled && config.resample.interval > 0)
+}
+
+/// 处理查询结果（结果与 `process_data` 一致），前置步骤的结果经 `cache` 复用
+pub fn process_data_cached(
+    records: Vec<HistoryRecord>,
+    config: &DataProcessingConfig,
+    cache: &PipelineCache,
+) -> AppResult<Vec<HistoryRecord>> {
+    config.validate()?;
+    if records.is_empty() || records.len() > MAX_CACHED_RECORDS || !has_prefix_steps(config) {
+        return super::process_data(records, config);
+    }
+
+    let key = PrefixKey::new(&records, config)?;
+    let prefix = match cache.get(&key) {
+        Some(prefix) => {
+            debug!(target: "industry_vis::processing", "复用管道中间结果（前置步骤未变化）");
+            prefix
+        }
+        None => {
+            cache.prefix_runs.fetch_add(1, Ordering::Relaxed);
+            let mut tag_groups: HashMap<InternedStr, Vec<HistoryRecord>> = HashMap::new();
+            for record in records {
+                tag_groups
+                    .entry(record.tag_name.clone())
+                    .or_default()
+                    .push(record);
+            }
+            let prefix: PrefixOutput = Arc::new(
+                tag_groups
+                    .into_values()
+                    .map(|tag_records| prefix_tag_data(tag_records, config))
+                    .collect::<AppResult<_>>()?,
+            );
+            cache.put(key, Arc::clone(&prefix));
+            prefix
+        }
+    };
+
+    let mut result = Vec::new();
+    for series in prefix.iter() {
+        result.extend(finish_tag_data(series.clone(), config)?);
+    }
+    result.sort_by(|a, b| a.date_time.cmp(&b.date_time));
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_records() -> Vec<HistoryRecord> {
+        let mut records = Vec::new();
+        for tag in ["TT_101", "TT_102"] {
+            for i in 0..60 {
+                let value = if i == 30 {
+                    500.0
+                } else {
+                    20.0 + (i % 7) as f64
+                };
+                records.push(HistoryRecord::new(
+                    format!("2024-01-01T00:{:02}:00", i),
+                    tag.to_string(),
+                    value,
+                    "Good".to_string(),
+                ));
+            }
+        }
+        records
+    }
+
+    fn sorted(mut records: Vec<HistoryRecord>) -> Vec<HistoryRecord> {
+        records.sort_by(|a, b| {
+            (a.tag_name.as_str(), &a.date_time).cmp(&(b.tag_name.as_str(), &b.date_time))
+        });
+        records
+    }
+
+    #[test]
+    fn test_only_smoothing_change_reuses_prefix() {
+        let cache = PipelineCache::new(4);
+        let base = DataProcessingConfig::new()
+            .with_outlier_removal("3sigma")
+            .with_resample(120, "mean");
+
+        for window in [3, 5, 7] {
+            let config = base.clone().with_smoothing(window, "moving_avg");
+            let cached = process_data_cached(sample_records(), &config, &cache).unwrap();
+            let expected = super::super::process_data(sample_records(), &config).unwrap();
+            assert_eq!(sorted(cached), sorted(expected));
+        }
+        // 只改平滑窗口：前置步骤只执行一次
+        assert_eq!(cache.prefix_runs(), 1);
+        assert_eq!(cache.hits(), 2);
+
+        // 改基准也复用
+        let mut config = base.clone();
+        config.baseline = Some("first".to_string());
+        process_data_cached(sample_records(), &config, &cache).unwrap();
+        assert_eq!(cache.prefix_runs(), 1);
+
+        // 改前置步骤配置或原始数据则重算
+        let config = base.clone().with_resample(300, "mean");
+        process_data_cached(sample_records(), &config, &cache).unwrap();
+        assert_eq!(cache.prefix_runs(), 2);
+        let mut changed = sample_records();
+        changed[0].tag_val = 21.5;
+        process_data_cached(changed, &base, &cache).unwrap();
+        assert_eq!(cache.prefix_runs(), 3);
+    }
+}
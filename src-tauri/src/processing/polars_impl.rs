@@ -5,7 +5,7 @@ use std::collections::HashMap;
 use tracing::{debug, warn};
 
 use crate::error::{AppError, AppResult};
-use crate::models::{DataProcessingConfig, HistoryRecord};
+use crate::models::{DataProcessingConfig, HistoryRecord, InternedStr, Interner};
 
 /// 将 HistoryRecord 列表转换为 Polars DataFrame
 pub fn records_to_dataframe(records: &[HistoryRecord]) -> AppResult<DataFrame> {
@@ -79,6 +79,7 @@ pub fn dataframe_to_records(df: &DataFrame) -> AppResult<Vec<HistoryRecord>> {
         .map_err(|e| AppError::DataProcessing(format!("tag_quality 列类型错误: {}", e)))?;
 
     let mut records = Vec::with_capacity(df.height());
+    let mut interner = Interner::new();
 
     for i in 0..df.height() {
         let ts_ms = datetimes.get(i).unwrap_or(0);
@@ -86,11 +87,12 @@ pub fn dataframe_to_records(df: &DataFrame) -> AppResult<Vec<HistoryRecord>> {
             .map(|utc| utc.with_timezone(&chrono::Local).naive_local())
             .unwrap_or_default();
 
-        records.push(HistoryRecord::new(
+        records.push(HistoryRecord::interned(
             dt.format("%Y-%m-%dT%H:%M:%S%.3f").to_string(),
-            tag_names.get(i).unwrap_or("").to_string(),
+            tag_names.get(i).unwrap_or(""),
             tag_vals.get(i).unwrap_or(0.0),
-            tag_qualities.get(i).unwrap_or("").to_string(),
+            tag_qualities.get(i).unwrap_or(""),
+            &mut interner,
         ));
     }
 
@@ -216,7 +218,7 @@ pub fn process_data_polars_legacy(
     }
 
     // 按标签分组处理（原有实现）
-    let mut tag_groups: HashMap<String, Vec<HistoryRecord>> = HashMap::new();
+    let mut tag_groups: HashMap<InternedStr, Vec<HistoryRecord>> = HashMap::new();
     for record in records {
         tag_groups
             .entry(record.tag_name.clone())
@@ -325,7 +327,12 @@ fn resample_data_polars(df: &DataFrame, interval_seconds: u32) -> AppResult<Data
         .alias("datetime")])
         .group_by([col("datetime"), col("tag_name")])
         .agg([
-            col("tag_val").mean().alias("tag_val"),
+            // NaN 转为 null 后 mean 自动忽略，全空窗口还原为 NaN（与 native 一致）
+            col("tag_val")
+                .fill_nan(lit(NULL))
+                .mean()
+                .fill_null(lit(f64::NAN))
+                .alias("tag_val"),
             col("tag_quality").first().alias("tag_quality"),
         ])
         .sort(["datetime"], Default::default())
@@ -335,21 +342,9 @@ fn resample_data_polars(df: &DataFrame, interval_seconds: u32) -> AppResult<Data
     Ok(result)
 }
 
-/// 解析时间字符串为毫秒时间戳
+/// 解析时间字符串为毫秒时间戳（夏令时歧义取较早时刻，不存在的时间顺延）
 fn parse_timestamp_ms(date_time: &str) -> Option<i64> {
-    use chrono::{Local, TimeZone};
-
-    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M:%S%.3f")
-        && let Some(local_dt) = Local.from_local_datetime(&dt).single()
-    {
-        return Some(local_dt.timestamp_millis());
-    }
-    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M:%S")
-        && let Some(local_dt) = Local.from_local_datetime(&dt).single()
-    {
-        return Some(local_dt.timestamp_millis());
-    }
-    None
+    super::local_time::parse_local_timestamp_ms(date_time, false).map(|(ts, _)| ts)
 }
 
 #[cfg(test)]
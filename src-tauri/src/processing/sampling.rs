@@ -0,0 +1,194 @@
+//! 标签正常采样周期检测
+//!
+//! 断采检测、死区配置等都需要标签的正常采样间隔。对相邻时间差取众数：
+//! 规则采样时众数占多数，直接作为采样周期；不规则采样时退化为中位数。
+//!
+//! 另提供采样均匀性检查：重采样按窗口聚合，采样间隔差异极大（如 1 秒与 1 小时混杂）时
+//! 均值会被密集段主导，需提示用户。
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::models::{HistoryRecord, SamplingInterval};
+
+use super::local_time::parse_local_timestamp_ms;
+
+/// 众数间隔占比达到该值时视为规则采样
+const MODE_MIN_SHARE: f64 = 0.5;
+
+/// 采样间隔变异系数（标准差 / 均值）超过该值时视为采样不均匀
+pub const UNEVEN_SAMPLING_CV_THRESHOLD: f64 = 1.0;
+
+/// 均匀性检查所需的最少间隔数
+const UNEVEN_MIN_DIFFS: usize = 3;
+
+/// 按标签（按名称排序）收集相邻正时间差（毫秒）
+fn diffs_by_tag(records: &[HistoryRecord]) -> BTreeMap<&str, Vec<i64>> {
+    let mut by_tag: BTreeMap<&str, Vec<i64>> = BTreeMap::new();
+    for record in records {
+        if let Some((ts, _)) = parse_local_timestamp_ms(&record.date_time, false) {
+            by_tag.entry(record.tag_name.as_str()).or_default().push(ts);
+        }
+    }
+    for timestamps in by_tag.values_mut() {
+        timestamps.sort_unstable();
+        *timestamps = timestamps
+            .windows(2)
+            .map(|w| w[1] - w[0])
+            .filter(|d| *d > 0)
+            .collect();
+    }
+    by_tag
+}
+
+/// 检测各标签的正常采样周期（按标签名排序；少于 2 个有效时间点的标签不输出）
+pub fn detect_sampling_interval(records: &[HistoryRecord]) -> Vec<SamplingInterval> {
+    diffs_by_tag(records)
+        .into_iter()
+        .filter_map(|(tag, diffs)| {
+            detect_from_diffs(diffs).map(|(interval_ms, method, regularity, sample_count)| {
+                SamplingInterval {
+                    tag_name: tag.to_string(),
+                    interval_ms,
+                    method: method.to_string(),
+                    sample_count,
+                    regularity,
+                }
+            })
+        })
+        .collect()
+}
+
+/// 检查各标签采样是否均匀，返回采样不均匀标签的告警（按标签名排序）
+pub fn detect_uneven_sampling(records: &[HistoryRecord]) -> Vec<String> {
+    diffs_by_tag(records)
+        .into_iter()
+        .filter(|(_, diffs)| diffs.len() >= UNEVEN_MIN_DIFFS)
+        .filter_map(|(tag, diffs)| {
+            let n = diffs.len() as f64;
+            let mean = diffs.iter().sum::<i64>() as f64 / n;
+            let variance = diffs
+                .iter()
+                .map(|d| (*d as f64 - mean).powi(2))
+                .sum::<f64>()
+                / n;
+            let cv = variance.sqrt() / mean;
+            (cv > UNEVEN_SAMPLING_CV_THRESHOLD).then(|| {
+                format!(
+                    "标签 {} 采样不均匀（间隔变异系数 {:.2}），聚合结果可能失真",
+                    tag, cv
+                )
+            })
+        })
+        .collect()
+}
+
+/// 由相邻时间差得到 (间隔, 检测方式, 规则度, 样本数)
+fn detect_from_diffs(mut diffs: Vec<i64>) -> Option<(f64, &'static str, f64, usize)> {
+    if diffs.is_empty() {
+        return None;
+    }
+
+    let mut counts: HashMap<i64, usize> = HashMap::new();
+    for diff in &diffs {
+        *counts.entry(*diff).or_default() += 1;
+    }
+    // 次数相同时取较小的间隔
+    let (mode, mode_count) = counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))?;
+    let regularity = mode_count as f64 / diffs.len() as f64;
+
+    if regularity >= MODE_MIN_SHARE {
+        return Some((mode as f64, "mode", regularity, diffs.len()));
+    }
+
+    diffs.sort_unstable();
+    let mid = diffs.len() / 2;
+    let median = if diffs.len().is_multiple_of(2) {
+        (diffs[mid - 1] + diffs[mid]) as f64 / 2.0
+    } else {
+        diffs[mid] as f64
+    };
+    Some((median, "median", regularity, diffs.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records_at(tag: &str, offsets_ms: &[i64]) -> Vec<HistoryRecord> {
+        let base =
+            chrono::NaiveDateTime::parse_from_str("2024-01-01T00:00:00", "%Y-%m-%dT%H:%M:%S")
+                .unwrap();
+        offsets_ms
+            .iter()
+            .map(|ms| {
+                HistoryRecord::new(
+                    (base + chrono::TimeDelta::milliseconds(*ms))
+                        .format("%Y-%m-%dT%H:%M:%S%.3f")
+                        .to_string(),
+                    tag.to_string(),
+                    1.0,
+                    "Good".to_string(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_one_second_sampling_detected() {
+        // 1 秒采样，偶有 1 个点丢失
+        let mut offsets: Vec<i64> = (0..600).map(|i| i * 1000).collect();
+        offsets.remove(300);
+        let result = detect_sampling_interval(&records_at("T1", &offsets));
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].tag_name, "T1");
+        assert_eq!(result[0].interval_ms, 1000.0);
+        assert_eq!(result[0].method, "mode");
+        assert!(result[0].regularity > 0.99);
+    }
+
+    #[test]
+    fn test_irregular_sampling_returns_median() {
+        let result =
+            detect_sampling_interval(&records_at("T1", &[0, 1000, 4000, 6000, 11000, 15000]));
+
+        // 时间差 1000, 3000, 2000, 5000, 4000 各不相同 → 中位数 3000
+        assert_eq!(result[0].interval_ms, 3000.0);
+        assert_eq!(result[0].method, "median");
+        assert_eq!(result[0].sample_count, 5);
+    }
+
+    #[test]
+    fn test_uneven_sampling_warning() {
+        // 均匀 1 秒采样（偶有丢点）无告警
+        let mut offsets: Vec<i64> = (0..600).map(|i| i * 1000).collect();
+        offsets.remove(300);
+        assert!(detect_uneven_sampling(&records_at("T1", &offsets)).is_empty());
+
+        // 前 10 分钟每秒一个点，之后每小时一个点
+        let mut offsets: Vec<i64> = (0..600).map(|i| i * 1000).collect();
+        offsets.extend((1..6).map(|h| h * 3_600_000));
+        let mut records = records_at("T2", &offsets);
+        records.extend(records_at("T1", &[0, 1000, 2000, 3000]));
+
+        let warnings = detect_uneven_sampling(&records);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("T2"));
+        assert!(warnings[0].contains("采样不均匀"));
+    }
+
+    #[test]
+    fn test_per_tag_and_unordered_input() {
+        let mut records = records_at("B", &[0, 5000, 10000]);
+        records.extend(records_at("A", &[2000, 0, 1000]));
+        records.extend(records_at("C", &[0]));
+
+        let result = detect_sampling_interval(&records);
+        let tags: Vec<&str> = result.iter().map(|r| r.tag_name.as_str()).collect();
+        assert_eq!(tags, ["A", "B"]);
+        assert_eq!(result[0].interval_ms, 1000.0);
+        assert_eq!(result[1].interval_ms, 5000.0);
+    }
+}
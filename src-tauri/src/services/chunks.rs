@@ -0,0 +1,278 @@
+//! V2 查询结果分片
+//!
+//! 超大结果一次性通过 IPC 返回会阻塞前端。设置分片大小后，结果暂存在服务端，
+//! 查询只返回令牌和分片数，前端按序号逐片拉取后按标签拼接。
+
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use lru::LruCache;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{ChartSeriesData, QueryResultV2};
+
+/// 同时暂存的分片结果数（超出时淘汰最久未访问的）
+const MAX_STORED_RESULTS: usize = 8;
+
+/// 暂存结果的有效期
+const STORED_RESULT_TTL: Duration = Duration::from_secs(600);
+
+/// 单个分片
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesChunk {
+    /// 分片序号（从 0 开始）
+    pub chunk_index: usize,
+    /// 总分片数
+    pub chunk_count: usize,
+    /// 本分片包含的系列片段，同一标签的片段按分片顺序拼接即为完整系列
+    pub series: Vec<ChartSeriesData>,
+}
+
+/// 将系列按数据点数切分为分片
+///
+/// 按标签顺序依次填充，每片最多 `chunk_size` 个点，一个系列可跨多个分片。
+pub fn split_series_chunks(
+    series: &[ChartSeriesData],
+    chunk_size: usize,
+) -> Vec<Vec<ChartSeriesData>> {
+    let chunk_size = chunk_size.max(1);
+    let mut chunks: Vec<Vec<ChartSeriesData>> = vec![Vec::new()];
+    let mut filled = 0;
+
+    for s in series {
+        let mut offset = 0;
+        loop {
+            if filled == chunk_size {
+                chunks.push(Vec::new());
+                filled = 0;
+            }
+            let end = (offset + chunk_size - filled).min(s.data.len());
+            chunks.last_mut().unwrap().push(ChartSeriesData {
+                quality: s.quality.as_ref().map(|q| q[offset..end].to_vec()),
+                value_labels: s.value_labels.as_ref().map(|l| l[offset..end].to_vec()),
+                ..ChartSeriesData::new(s.tag_name.clone(), s.data[offset..end].to_vec())
+            });
+            filled += end - offset;
+            offset = end;
+            if offset >= s.data.len() {
+                break;
+            }
+        }
+    }
+    chunks
+}
+
+/// 将分片按顺序拼接回完整系列
+pub fn merge_series_chunks(chunks: &[SeriesChunk]) -> Vec<ChartSeriesData> {
+    let mut merged: Vec<ChartSeriesData> = Vec::new();
+    for part in chunks.iter().flat_map(|c| &c.series) {
+        match merged.last_mut() {
+            Some(last) if last.tag_name == part.tag_name => {
+                last.data.extend_from_slice(&part.data);
+                if let (Some(quality), Some(more)) = (last.quality.as_mut(), &part.quality) {
+                    quality.extend_from_slice(more);
+                }
+                if let (Some(labels), Some(more)) = (last.value_labels.as_mut(), &part.value_labels)
+                {
+                    labels.extend_from_slice(more);
+                }
+                if let (Some(columns), Some(more)) = (last.columns.as_mut(), &part.columns) {
+                    columns.timestamps.extend_from_slice(&more.timestamps);
+                    columns.values.extend_from_slice(&more.values);
+                }
+            }
+            _ => merged.push(part.clone()),
+        }
+    }
+    merged
+}
+
+/// 暂存的分片结果
+struct StoredChunks {
+    chunks: Arc<Vec<Vec<ChartSeriesData>>>,
+    created_at: Instant,
+}
+
+/// 分片结果暂存
+pub struct SeriesChunkStore {
+    results: Mutex<LruCache<String, StoredChunks>>,
+    counter: AtomicU64,
+}
+
+impl SeriesChunkStore {
+    /// 创建空的暂存
+    pub fn new() -> Self {
+        Self {
+            results: Mutex::new(LruCache::new(
+                NonZeroUsize::new(MAX_STORED_RESULTS).unwrap(),
+            )),
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    /// 数据点数超过 `chunk_size` 时暂存系列并改为分片返回
+    ///
+    /// 分片后结果中 `series` 为空，通过 `query_token` 和 `chunk_count` 拉取；
+    /// 点数不超过分片大小时原样返回。`columnar` 时各分片的系列以列式暂存。
+    pub fn chunk_result(
+        &self,
+        mut result: QueryResultV2,
+        chunk_size: usize,
+        columnar: bool,
+    ) -> QueryResultV2 {
+        let points: usize = result.series.iter().map(|s| s.data.len()).sum();
+        if points <= chunk_size {
+            return result;
+        }
+
+        let mut chunks = split_series_chunks(&result.series, chunk_size);
+        if columnar {
+            chunks
+                .iter_mut()
+                .flatten()
+                .for_each(ChartSeriesData::to_columnar);
+        }
+        let token = self.next_token();
+        debug!(target: "industry_vis::query_service",
+            "结果分片暂存 - 点数: {}, 分片数: {}, token: {}", points, chunks.len(), token
+        );
+
+        result.chunk_count = Some(chunks.len());
+        result.query_token = Some(token.clone());
+        result.series = Vec::new();
+        self.results.lock().put(
+            token,
+            StoredChunks {
+                chunks: Arc::new(chunks),
+                created_at: Instant::now(),
+            },
+        );
+        result
+    }
+
+    /// 获取指定分片
+    pub fn fetch_chunk(&self, token: &str, chunk_index: usize) -> AppResult<SeriesChunk> {
+        let chunks = {
+            let mut results = self.results.lock();
+            match results.get(token) {
+                Some(stored) if stored.created_at.elapsed() <= STORED_RESULT_TTL => {
+                    Arc::clone(&stored.chunks)
+                }
+                Some(_) => {
+                    results.pop(token);
+                    return Err(AppError::NotFound("分片结果已过期，请重新查询".to_string()));
+                }
+                None => {
+                    return Err(AppError::NotFound(format!("分片结果不存在: {}", token)));
+                }
+            }
+        };
+
+        let series = chunks.get(chunk_index).cloned().ok_or_else(|| {
+            AppError::Validation(format!(
+                "分片序号超出范围: {}（共 {} 片）",
+                chunk_index,
+                chunks.len()
+            ))
+        })?;
+        Ok(SeriesChunk {
+            chunk_index,
+            chunk_count: chunks.len(),
+            series,
+        })
+    }
+
+    /// 生成唯一令牌（时间戳 + 递增序号）
+    fn next_token(&self) -> String {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        let seq = self.counter.fetch_add(1, Ordering::Relaxed);
+        format!("{:x}-{:x}", nanos, seq)
+    }
+}
+
+impl Default for SeriesChunkStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::QueryTiming;
+
+    fn sample_result() -> QueryResultV2 {
+        let series = [("A", 7), ("B", 1), ("C", 12)]
+            .iter()
+            .map(|(tag, n)| ChartSeriesData {
+                quality: Some((0..*n).map(|i| format!("Q{}", i)).collect()),
+                ..ChartSeriesData::new(
+                    tag.to_string(),
+                    (0..*n).map(|i| [i as f64 * 1000.0, i as f64]).collect(),
+                )
+            })
+            .collect();
+        QueryResultV2 {
+            series,
+            annotations: Vec::new(),
+            total_raw: 20,
+            total_processed: 20,
+            cache_hit: false,
+            truncated: false,
+            query_time_ms: 0,
+            timing: QueryTiming::default(),
+            query_token: None,
+            chunk_count: None,
+            empty_reason: None,
+            warnings: Vec::new(),
+            content_hash: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_chunks_merge_to_full_result() {
+        let store = SeriesChunkStore::new();
+        let full = sample_result();
+        let chunked = store.chunk_result(full.clone(), 5, false);
+
+        assert!(chunked.series.is_empty());
+        let token = chunked.query_token.unwrap();
+        let count = chunked.chunk_count.unwrap();
+        assert_eq!(count, 4);
+
+        let chunks: Vec<SeriesChunk> = (0..count)
+            .map(|i| store.fetch_chunk(&token, i).unwrap())
+            .collect();
+        assert!(
+            chunks
+                .iter()
+                .all(|c| { c.series.iter().map(|s| s.data.len()).sum::<usize>() <= 5 })
+        );
+
+        let merged = merge_series_chunks(&chunks);
+        assert_eq!(
+            serde_json::to_string(&merged).unwrap(),
+            serde_json::to_string(&full.series).unwrap()
+        );
+
+        assert!(store.fetch_chunk(&token, count).is_err());
+        assert!(store.fetch_chunk("unknown", 0).is_err());
+    }
+
+    #[test]
+    fn test_small_result_not_chunked() {
+        let store = SeriesChunkStore::new();
+        let result = store.chunk_result(sample_result(), 100, false);
+        assert_eq!(result.series.len(), 3);
+        assert!(result.query_token.is_none());
+    }
+}
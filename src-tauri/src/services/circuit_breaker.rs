@@ -0,0 +1,309 @@
+//! 数据库连接断路器
+//!
+//! 数据库彻底不可用时，每个查询都要等满连接超时才失败。断路器在连续
+//! `failure_threshold` 次连接失败后进入"断开"状态，冷却期内直接快速失败、
+//! 不再尝试连接；冷却结束后进入"半开"状态，只放行一次试探查询：
+//! 试探成功恢复闭合，失败则重新断开并开始新一轮冷却。
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::config::CircuitBreakerPerformanceConfig;
+use crate::error::{AppError, AppResult};
+
+/// 断路器状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CircuitState {
+    /// 闭合（正常放行）
+    Closed,
+    /// 断开（冷却期内快速失败）
+    Open,
+    /// 半开（冷却结束，等待试探结果）
+    HalfOpen,
+}
+
+/// 断路器状态快照（返回前端）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CircuitBreakerStatus {
+    pub state: CircuitState,
+    /// 连续连接失败次数
+    pub consecutive_failures: u32,
+    /// 距冷却结束的剩余秒数（仅断开状态）
+    pub retry_after_secs: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+struct BreakerInner {
+    consecutive_failures: u32,
+    /// 断开状态的冷却结束时刻
+    open_until: Option<Instant>,
+    /// 半开状态下是否已有试探查询在执行
+    probing: bool,
+}
+
+/// 数据库连接断路器
+///
+/// 只有连接类错误（`Connection` / `Pool`）计入失败；查询语句错误说明数据库可达，
+/// 不影响断路器状态。
+pub struct CircuitBreaker {
+    config: CircuitBreakerPerformanceConfig,
+    inner: Mutex<BreakerInner>,
+}
+
+impl CircuitBreaker {
+    /// 根据配置创建断路器
+    pub fn new(config: CircuitBreakerPerformanceConfig) -> Self {
+        Self {
+            config,
+            inner: Mutex::new(BreakerInner::default()),
+        }
+    }
+
+    /// 在断路器保护下执行一次数据库操作
+    pub async fn call<T, F>(&self, operation: F) -> AppResult<T>
+    where
+        F: Future<Output = AppResult<T>>,
+    {
+        let probe = self.check_at(Instant::now())?;
+        let result = operation.await;
+        match &result {
+            Ok(_) => self.record_success(),
+            Err(e) if is_connection_failure(e) => self.record_failure(),
+            // 与连接无关的错误：释放试探名额（保持半开）
+            Err(_) => return result,
+        }
+        if let Some(probe) = probe {
+            probe.disarm();
+        }
+        result
+    }
+
+    /// 检查是否放行（断开状态冷却期内返回 `AppError::CircuitOpen`）
+    ///
+    /// 半开状态下放行的试探查询返回试探名额，名额释放前其他查询快速失败。
+    fn check_at(&self, now: Instant) -> AppResult<Option<ProbeGuard<'_>>> {
+        if !self.config.enabled {
+            return Ok(None);
+        }
+
+        let mut inner = self.inner.lock();
+        let Some(until) = inner.open_until else {
+            return Ok(None);
+        };
+        if now < until {
+            return Err(AppError::CircuitOpen(format!(
+                "数据库连续 {} 次连接失败，{} 秒后重试",
+                inner.consecutive_failures,
+                until.saturating_duration_since(now).as_secs().max(1)
+            )));
+        }
+        if inner.probing {
+            return Err(AppError::CircuitOpen(
+                "正在试探数据库连接，请稍后再试".to_string(),
+            ));
+        }
+        inner.probing = true;
+        info!(target: "industry_vis::circuit_breaker", "冷却结束，试探数据库连接");
+        Ok(Some(ProbeGuard {
+            breaker: self,
+            armed: true,
+        }))
+    }
+
+    /// 记录一次成功（恢复闭合）
+    pub fn record_success(&self) {
+        let mut inner = self.inner.lock();
+        if inner.open_until.is_some() {
+            info!(target: "industry_vis::circuit_breaker", "数据库连接恢复，断路器闭合");
+        }
+        *inner = BreakerInner::default();
+    }
+
+    /// 记录一次连接失败
+    pub fn record_failure(&self) {
+        self.record_failure_at(Instant::now());
+    }
+
+    fn record_failure_at(&self, now: Instant) {
+        if !self.config.enabled {
+            return;
+        }
+
+        let mut inner = self.inner.lock();
+        inner.consecutive_failures = inner.consecutive_failures.saturating_add(1);
+        if inner.probing || inner.consecutive_failures >= self.config.failure_threshold {
+            inner.open_until = Some(now + Duration::from_secs(self.config.cooldown_secs));
+            inner.probing = false;
+            warn!(target: "industry_vis::circuit_breaker",
+                "数据库连续 {} 次连接失败，断路器断开 {} 秒",
+                inner.consecutive_failures, self.config.cooldown_secs
+            );
+        }
+    }
+
+    /// 试探结果与连接无关或试探被取消时释放试探名额（保持半开）
+    fn release_probe(&self) {
+        self.inner.lock().probing = false;
+    }
+
+    /// 当前状态快照
+    pub fn status(&self) -> CircuitBreakerStatus {
+        self.status_at(Instant::now())
+    }
+
+    fn status_at(&self, now: Instant) -> CircuitBreakerStatus {
+        let inner = self.inner.lock();
+        let (state, retry_after_secs) = match inner.open_until {
+            None => (CircuitState::Closed, None),
+            Some(until) if now < until => (
+                CircuitState::Open,
+                Some(until.saturating_duration_since(now).as_secs()),
+            ),
+            Some(_) => (CircuitState::HalfOpen, None),
+        };
+        CircuitBreakerStatus {
+            state,
+            consecutive_failures: inner.consecutive_failures,
+            retry_after_secs,
+        }
+    }
+}
+
+/// 半开状态的试探名额
+///
+/// 试探结果记录前被释放（如调用方取消了查询）时归还名额，避免断路器一直拒绝后续查询。
+struct ProbeGuard<'a> {
+    breaker: &'a CircuitBreaker,
+    armed: bool,
+}
+
+impl ProbeGuard<'_> {
+    /// 试探结果已记录，不再归还名额
+    fn disarm(mut self) {
+        self.armed = false;
+    }
+}
+
+impl Drop for ProbeGuard<'_> {
+    fn drop(&mut self) {
+        if self.armed {
+            self.breaker.release_probe();
+        }
+    }
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(CircuitBreakerPerformanceConfig::default())
+    }
+}
+
+/// 是否为计入断路器的连接类错误
+fn is_connection_failure(err: &AppError) -> bool {
+    matches!(
+        err,
+        AppError::Connection(_) | AppError::ConnectionRejected { .. } | AppError::Pool(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn new_breaker(failure_threshold: u32, cooldown_secs: u64) -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerPerformanceConfig {
+            enabled: true,
+            failure_threshold,
+            cooldown_secs,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_opens_after_consecutive_failures_and_fails_fast() {
+        let breaker = new_breaker(3, 30);
+        let attempts = AtomicUsize::new(0);
+        let failing = || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>(AppError::Connection("TCP 连接超时".to_string()))
+        };
+
+        for _ in 0..3 {
+            let err = breaker.call(failing()).await.unwrap_err();
+            assert!(matches!(err, AppError::Connection(_)));
+        }
+        assert_eq!(breaker.status().state, CircuitState::Open);
+
+        // 断开后不再尝试连接，直接快速失败
+        let err = breaker.call(failing()).await.unwrap_err();
+        assert_eq!(err.code(), "CIRCUIT_OPEN");
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        // 查询语句错误不计入失败
+        let breaker = new_breaker(2, 30);
+        for _ in 0..3 {
+            let _ = breaker
+                .call(async { Err::<(), _>(AppError::Query("语法错误".to_string())) })
+                .await;
+        }
+        assert_eq!(breaker.status().state, CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_half_open_probe_after_cooldown() {
+        let breaker = new_breaker(2, 30);
+        let start = Instant::now();
+        breaker.record_failure_at(start);
+        assert!(breaker.check_at(start).is_ok());
+        breaker.record_failure_at(start);
+        assert!(breaker.check_at(start + Duration::from_secs(29)).is_err());
+
+        // 冷却结束只放行一次试探
+        let after = start + Duration::from_secs(30);
+        assert_eq!(breaker.status_at(after).state, CircuitState::HalfOpen);
+        let probe = breaker.check_at(after).unwrap().unwrap();
+        assert!(breaker.check_at(after).is_err());
+
+        // 试探失败立即重新断开
+        breaker.record_failure_at(after);
+        probe.disarm();
+        assert!(breaker.check_at(after + Duration::from_secs(29)).is_err());
+
+        // 再次试探成功后恢复闭合
+        let later = after + Duration::from_secs(30);
+        let probe = breaker.check_at(later).unwrap().unwrap();
+        breaker.record_success();
+        probe.disarm();
+        let status = breaker.status_at(later);
+        assert_eq!(status.state, CircuitState::Closed);
+        assert_eq!(status.consecutive_failures, 0);
+        assert!(breaker.check_at(later).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_dropped_probe_releases_slot() {
+        let breaker = new_breaker(1, 0);
+        let _ = breaker
+            .call(async { Err::<(), _>(AppError::Connection("TCP 连接超时".to_string())) })
+            .await;
+        assert_eq!(breaker.status().state, CircuitState::HalfOpen);
+
+        // 试探查询执行中被取消（future 被丢弃）
+        let probe = breaker.call(std::future::pending::<AppResult<()>>());
+        assert!(
+            tokio::time::timeout(Duration::from_millis(10), probe)
+                .await
+                .is_err()
+        );
+
+        // 名额已归还，下一次查询可以继续试探
+        breaker.call(async { Ok(()) }).await.unwrap();
+        assert_eq!(breaker.status().state, CircuitState::Closed);
+    }
+}
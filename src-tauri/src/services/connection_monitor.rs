@@ -0,0 +1,280 @@
+//! 数据库连通性监控
+//!
+//! 后台低频执行轻量检查（`SELECT 1`），维护"健康 / 降级 / 断开"状态：
+//! 检查失败即降级并改用较短的重试间隔，连续失败达到阈值后判定为断开，
+//! 任一次检查成功回到健康。状态变化时通过通知回调上报，由状态层转为
+//! `connection-health` 事件，前端据此显示状态指示灯。
+
+use chrono::Local;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::config::ConnectionMonitorPerformanceConfig;
+use crate::error::AppResult;
+
+/// 连通性状态变化事件名
+pub const CONNECTION_HEALTH_EVENT: &str = "connection-health";
+
+/// 时间格式
+const CHECK_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// 连通性状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConnectionHealth {
+    /// 最近一次检查成功
+    Healthy,
+    /// 检查失败，但未达到断开阈值
+    Degraded,
+    /// 连续失败达到阈值（或尚未检查成功过）
+    Disconnected,
+}
+
+/// 连通性状态快照（返回前端 / 随事件推送）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionHealthStatus {
+    pub health: ConnectionHealth,
+    /// 连续失败次数
+    pub consecutive_failures: u32,
+    /// 最近一次检查时刻
+    pub last_checked: Option<String>,
+    /// 最近一次成功检查的耗时（毫秒）
+    pub latency_ms: Option<u64>,
+    /// 最近一次失败的原因（成功后清空）
+    pub last_error: Option<String>,
+}
+
+impl Default for ConnectionHealthStatus {
+    fn default() -> Self {
+        Self {
+            health: ConnectionHealth::Disconnected,
+            consecutive_failures: 0,
+            last_checked: None,
+            latency_ms: None,
+            last_error: None,
+        }
+    }
+}
+
+impl ConnectionHealthStatus {
+    /// 记录一次检查结果，返回状态是否变化
+    fn record(&mut self, result: Result<u64, String>, failure_threshold: u32) -> bool {
+        let previous = self.health;
+        self.last_checked = Some(Local::now().format(CHECK_TIME_FORMAT).to_string());
+        match result {
+            Ok(latency_ms) => {
+                self.health = ConnectionHealth::Healthy;
+                self.consecutive_failures = 0;
+                self.latency_ms = Some(latency_ms);
+                self.last_error = None;
+            }
+            Err(e) => {
+                self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+                self.last_error = Some(e);
+                if self.consecutive_failures >= failure_threshold {
+                    self.health = ConnectionHealth::Disconnected;
+                } else if previous == ConnectionHealth::Healthy {
+                    self.health = ConnectionHealth::Degraded;
+                }
+            }
+        }
+        self.health != previous
+    }
+}
+
+/// 连通性检查（成功返回 `Ok(())`）
+pub type ConnectionProbe =
+    Arc<dyn Fn() -> Pin<Box<dyn Future<Output = AppResult<()>> + Send>> + Send + Sync>;
+
+/// 连通性状态变化通知回调
+pub type ConnectionHealthNotifier = Arc<dyn Fn(&ConnectionHealthStatus) + Send + Sync>;
+
+/// 数据库连通性监控
+pub struct ConnectionMonitor {
+    enabled: bool,
+    /// 连通时的检查间隔
+    interval: Duration,
+    /// 检查失败后的重试间隔
+    retry_interval: Duration,
+    failure_threshold: u32,
+    status: Arc<Mutex<ConnectionHealthStatus>>,
+    notifier: Option<ConnectionHealthNotifier>,
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl ConnectionMonitor {
+    /// 创建监控（需调用 `start` 开始检查）
+    pub fn new(
+        config: ConnectionMonitorPerformanceConfig,
+        notifier: Option<ConnectionHealthNotifier>,
+    ) -> Self {
+        Self {
+            enabled: config.enabled,
+            interval: Duration::from_secs(config.interval_secs),
+            retry_interval: Duration::from_secs(config.retry_interval_secs),
+            failure_threshold: config.failure_threshold.max(1),
+            status: Arc::new(Mutex::new(ConnectionHealthStatus::default())),
+            notifier,
+            handle: Mutex::new(None),
+        }
+    }
+
+    /// 启动后台检查（需在 tokio 运行时中调用；重复调用时替换之前的检查任务）
+    pub fn start(&self, probe: ConnectionProbe) {
+        if !self.enabled {
+            return;
+        }
+        let interval = self.interval;
+        let retry_interval = self.retry_interval;
+        let failure_threshold = self.failure_threshold;
+        let status = Arc::clone(&self.status);
+        let notifier = self.notifier.clone();
+
+        let handle = tokio::spawn(async move {
+            loop {
+                let started = Instant::now();
+                let result = probe()
+                    .await
+                    .map(|_| started.elapsed().as_millis() as u64)
+                    .map_err(|e| e.to_string());
+
+                let (changed, snapshot) = {
+                    let mut status = status.lock();
+                    let changed = status.record(result, failure_threshold);
+                    (changed, status.clone())
+                };
+                if changed {
+                    match snapshot.health {
+                        ConnectionHealth::Healthy => info!(target: "industry_vis::connection",
+                            "数据库连通性恢复 - 耗时: {:?}ms", snapshot.latency_ms
+                        ),
+                        health => warn!(target: "industry_vis::connection",
+                            "数据库连通性变为 {:?} - 连续失败: {}, 原因: {}",
+                            health,
+                            snapshot.consecutive_failures,
+                            snapshot.last_error.as_deref().unwrap_or("")
+                        ),
+                    }
+                    if let Some(notifier) = &notifier {
+                        notifier(&snapshot);
+                    }
+                }
+
+                let wait = if snapshot.health == ConnectionHealth::Healthy {
+                    interval
+                } else {
+                    retry_interval
+                };
+                tokio::time::sleep(wait).await;
+            }
+        });
+        if let Some(previous) = self.handle.lock().replace(handle) {
+            previous.abort();
+        }
+    }
+
+    /// 停止后台检查
+    pub fn stop(&self) {
+        if let Some(handle) = self.handle.lock().take() {
+            handle.abort();
+        }
+    }
+
+    /// 当前状态
+    pub fn status(&self) -> ConnectionHealthStatus {
+        self.status.lock().clone()
+    }
+}
+
+impl Drop for ConnectionMonitor {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AppError;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// 以小步推进暂停的时钟，让后台检查任务按各自的定时器依次运行
+    async fn advance_by(total: Duration) {
+        let step = Duration::from_millis(10);
+        let mut elapsed = Duration::ZERO;
+        while elapsed < total {
+            tokio::time::advance(step).await;
+            tokio::task::yield_now().await;
+            elapsed += step;
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_health_follows_connectivity() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&events);
+        let mut monitor = ConnectionMonitor::new(
+            ConnectionMonitorPerformanceConfig {
+                failure_threshold: 2,
+                ..Default::default()
+            },
+            Some(Arc::new(move |status: &ConnectionHealthStatus| {
+                recorded.lock().push(status.health);
+            })),
+        );
+        monitor.interval = Duration::from_millis(100);
+        monitor.retry_interval = Duration::from_millis(20);
+
+        let connected = Arc::new(AtomicBool::new(true));
+        let flag = Arc::clone(&connected);
+        monitor.start(Arc::new(move || {
+            let up = flag.load(Ordering::SeqCst);
+            Box::pin(async move {
+                if up {
+                    Ok(())
+                } else {
+                    Err(AppError::Connection("连接被拒绝".to_string()))
+                }
+            })
+        }));
+
+        advance_by(Duration::from_millis(50)).await;
+        let status = monitor.status();
+        assert_eq!(status.health, ConnectionHealth::Healthy);
+        assert!(status.latency_ms.is_some());
+
+        // 断开：先降级，失败后按重试间隔检查，很快判定为断开
+        connected.store(false, Ordering::SeqCst);
+        advance_by(Duration::from_millis(250)).await;
+        let status = monitor.status();
+        assert_eq!(status.health, ConnectionHealth::Disconnected);
+        assert!(status.consecutive_failures >= 2);
+        assert!(status.last_error.unwrap().contains("连接被拒绝"));
+
+        // 恢复：按重试间隔检查，回到健康
+        connected.store(true, Ordering::SeqCst);
+        advance_by(Duration::from_millis(100)).await;
+        let status = monitor.status();
+        assert_eq!(status.health, ConnectionHealth::Healthy);
+        assert_eq!(status.consecutive_failures, 0);
+        assert!(status.last_error.is_none());
+
+        monitor.stop();
+        assert_eq!(
+            *events.lock(),
+            vec![
+                ConnectionHealth::Healthy,
+                ConnectionHealth::Degraded,
+                ConnectionHealth::Disconnected,
+                ConnectionHealth::Healthy,
+            ]
+        );
+    }
+}
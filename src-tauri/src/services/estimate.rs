@@ -0,0 +1,74 @@
+//! 查询预估
+//!
+//! 记录最近数据库查询的行数与耗时，按平均速率估算新查询的耗时。
+
+use std::collections::VecDeque;
+
+use parking_lot::Mutex;
+
+/// 参与速率计算的最近样本数
+const MAX_SAMPLES: usize = 20;
+
+/// 行数过少的查询以固定开销为主，不参与速率计算
+const MIN_SAMPLE_ROWS: usize = 1000;
+
+/// 数据库查询速率统计
+#[derive(Default)]
+pub struct QueryRateTracker {
+    /// 最近的 (行数, 耗时毫秒)
+    samples: Mutex<VecDeque<(usize, u64)>>,
+}
+
+impl QueryRateTracker {
+    /// 创建空的速率统计
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一次数据库查询
+    pub fn record(&self, rows: usize, elapsed_ms: u64) {
+        if rows < MIN_SAMPLE_ROWS {
+            return;
+        }
+        let mut samples = self.samples.lock();
+        if samples.len() == MAX_SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back((rows, elapsed_ms.max(1)));
+    }
+
+    /// 最近查询的平均速率（行/秒），无样本时返回 `None`
+    pub fn rows_per_second(&self) -> Option<f64> {
+        let samples = self.samples.lock();
+        let (rows, ms) = samples
+            .iter()
+            .fold((0usize, 0u64), |(rows, ms), (r, m)| (rows + r, ms + m));
+        (ms > 0).then(|| rows as f64 * 1000.0 / ms as f64)
+    }
+
+    /// 按平均速率估算查询指定行数的耗时（毫秒）
+    pub fn estimate_ms(&self, rows: u64) -> Option<u64> {
+        self.rows_per_second()
+            .map(|rate| (rows as f64 / rate * 1000.0).ceil() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_tracker_estimate() {
+        let tracker = QueryRateTracker::new();
+        assert_eq!(tracker.estimate_ms(10_000), None);
+
+        // 小查询不参与统计
+        tracker.record(10, 200);
+        assert_eq!(tracker.rows_per_second(), None);
+
+        tracker.record(10_000, 1000);
+        tracker.record(30_000, 1000);
+        assert_eq!(tracker.rows_per_second(), Some(20_000.0));
+        assert_eq!(tracker.estimate_ms(50_000), Some(2500));
+    }
+}
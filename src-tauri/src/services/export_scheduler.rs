@@ -0,0 +1,333 @@
+//! 定时导出调度
+//!
+//! 每个定时导出任务是一个后台 tokio 任务，按计划等待到下一次执行时刻后调用执行回调
+//! （通常是提交到后台导出任务队列）。任务定义保存在 `ScheduledExportStore`，
+//! 应用启动时恢复；移除任务时中止对应的后台任务。
+
+use chrono::{Local, NaiveDateTime};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::error::{AppError, AppResult};
+use crate::export::{ExportSchedule, ScheduledExport, ScheduledExportStore};
+
+/// 时间格式
+const SCHEDULE_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// 定时导出执行回调（参数为任务定义和本次计划执行时刻）
+pub type ScheduledExportRunner = Arc<
+    dyn Fn(ScheduledExport, NaiveDateTime) -> Pin<Box<dyn Future<Output = AppResult<()>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// 定时导出任务状态（返回前端）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledExportInfo {
+    #[serde(flatten)]
+    pub export: ScheduledExport,
+    /// 下一次执行时刻（调度器未启动时为空）
+    pub next_run: Option<String>,
+    /// 最近一次执行时刻
+    pub last_run: Option<String>,
+    /// 最近一次执行失败的原因
+    pub last_error: Option<String>,
+    /// 本次运行期间已执行次数
+    pub run_count: u64,
+}
+
+#[derive(Debug, Default)]
+struct RunStatus {
+    next_run: Option<String>,
+    last_run: Option<String>,
+    last_error: Option<String>,
+    run_count: u64,
+}
+
+struct ScheduledJob {
+    export: ScheduledExport,
+    schedule: ExportSchedule,
+    status: Arc<Mutex<RunStatus>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+/// 定时导出调度器
+///
+/// `start` 之前添加的任务只登记不执行；`start` 后为全部任务启动后台任务。
+pub struct ExportScheduler {
+    jobs: Mutex<HashMap<String, ScheduledJob>>,
+    /// 任务定义存储（`None` 时不持久化）
+    store: Option<Mutex<ScheduledExportStore>>,
+    runner: Mutex<Option<ScheduledExportRunner>>,
+}
+
+impl ExportScheduler {
+    /// 创建不持久化的调度器
+    pub fn new() -> Self {
+        Self {
+            jobs: Mutex::new(HashMap::new()),
+            store: None,
+            runner: Mutex::new(None),
+        }
+    }
+
+    /// 创建调度器并登记存储中的任务（无效的任务定义跳过）
+    pub fn with_store(store: ScheduledExportStore) -> Self {
+        let mut jobs = HashMap::new();
+        for export in store.list() {
+            match export.validate() {
+                Ok(schedule) => {
+                    jobs.insert(
+                        export.id.clone(),
+                        ScheduledJob::new(export.clone(), schedule),
+                    );
+                }
+                Err(e) => warn!(target: "industry_vis::export",
+                    "跳过无效的定时导出任务 {}: {}", export.id, e
+                ),
+            }
+        }
+        Self {
+            jobs: Mutex::new(jobs),
+            store: Some(Mutex::new(store)),
+            runner: Mutex::new(None),
+        }
+    }
+
+    /// 设置执行回调并启动全部任务（需在 tokio 运行时中调用）
+    pub fn start(&self, runner: ScheduledExportRunner) {
+        let mut jobs = self.jobs.lock();
+        for job in jobs.values_mut() {
+            job.spawn(&runner);
+        }
+        if !jobs.is_empty() {
+            info!(target: "industry_vis::export", "启动定时导出任务: {} 个", jobs.len());
+        }
+        *self.runner.lock() = Some(runner);
+    }
+
+    /// 添加任务（ID 与创建时间由调度器生成），已启动时立即开始调度
+    pub fn add(&self, mut export: ScheduledExport) -> AppResult<ScheduledExportInfo> {
+        let schedule = export.validate()?;
+
+        let mut jobs = self.jobs.lock();
+        let now = Local::now();
+        let mut millis = now.timestamp_millis();
+        while jobs.contains_key(&format!("e{}", millis)) {
+            millis += 1;
+        }
+        export.id = format!("e{}", millis);
+        export.created_at = now.format(SCHEDULE_TIME_FORMAT).to_string();
+
+        if let Some(store) = &self.store {
+            store.lock().add(export.clone())?;
+        }
+        let mut job = ScheduledJob::new(export, schedule);
+        if let Some(runner) = self.runner.lock().as_ref() {
+            job.spawn(runner);
+        }
+        info!(target: "industry_vis::export",
+            "添加定时导出任务 - ID: {}, 计划: {}, 路径: {}",
+            job.export.id, job.export.schedule, job.export.output_template
+        );
+        let info = job.info();
+        jobs.insert(job.export.id.clone(), job);
+        Ok(info)
+    }
+
+    /// 移除任务并停止调度
+    pub fn remove(&self, id: &str) -> AppResult<()> {
+        let Some(job) = self.jobs.lock().remove(id) else {
+            return Err(AppError::NotFound(format!("定时导出任务不存在: {}", id)));
+        };
+        if let Some(handle) = job.handle {
+            handle.abort();
+        }
+        if let Some(store) = &self.store {
+            store.lock().remove(id)?;
+        }
+        info!(target: "industry_vis::export", "移除定时导出任务: {}", id);
+        Ok(())
+    }
+
+    /// 全部任务（按创建时间排序）
+    pub fn list(&self) -> Vec<ScheduledExportInfo> {
+        let mut infos: Vec<ScheduledExportInfo> =
+            self.jobs.lock().values().map(ScheduledJob::info).collect();
+        infos.sort_by(|a, b| a.export.id.cmp(&b.export.id));
+        infos
+    }
+}
+
+impl Default for ExportScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ExportScheduler {
+    fn drop(&mut self) {
+        for job in self.jobs.get_mut().values() {
+            if let Some(handle) = &job.handle {
+                handle.abort();
+            }
+        }
+    }
+}
+
+impl ScheduledJob {
+    fn new(export: ScheduledExport, schedule: ExportSchedule) -> Self {
+        Self {
+            export,
+            schedule,
+            status: Arc::new(Mutex::new(RunStatus::default())),
+            handle: None,
+        }
+    }
+
+    fn spawn(&mut self, runner: &ScheduledExportRunner) {
+        let export = self.export.clone();
+        let schedule = self.schedule.clone();
+        let status = Arc::clone(&self.status);
+        let runner = Arc::clone(runner);
+
+        self.handle = Some(tokio::spawn(async move {
+            loop {
+                let now = Local::now().naive_local();
+                let Some(next) = schedule.next_after(now) else {
+                    warn!(target: "industry_vis::export",
+                        "定时导出任务 {} 没有下一次执行时刻，停止调度", export.id
+                    );
+                    status.lock().next_run = None;
+                    break;
+                };
+                status.lock().next_run = Some(next.format(SCHEDULE_TIME_FORMAT).to_string());
+                tokio::time::sleep((next - now).to_std().unwrap_or_default()).await;
+
+                let result = runner(export.clone(), next).await;
+                if let Err(e) = &result {
+                    warn!(target: "industry_vis::export",
+                        "定时导出任务 {} 执行失败: {}", export.id, e
+                    );
+                }
+                let mut status = status.lock();
+                status.last_run = Some(next.format(SCHEDULE_TIME_FORMAT).to_string());
+                status.last_error = result.err().map(|e| e.to_string());
+                status.run_count += 1;
+            }
+        }));
+    }
+
+    fn info(&self) -> ScheduledExportInfo {
+        let status = self.status.lock();
+        ScheduledExportInfo {
+            export: self.export.clone(),
+            next_run: status.next_run.clone(),
+            last_run: status.last_run.clone(),
+            last_error: status.last_error.clone(),
+            run_count: status.run_count,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::QueryParams;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn sample_export(schedule: &str) -> ScheduledExport {
+        ScheduledExport {
+            id: String::new(),
+            schedule: schedule.to_string(),
+            params: QueryParams::new(
+                "2024-01-01T00:00:00".to_string(),
+                "2024-01-01T01:00:00".to_string(),
+            ),
+            processing_config: None,
+            output_template: "D:/exports/{date}_{time}.csv".to_string(),
+            format: "csv".to_string(),
+            encoding: None,
+            created_at: String::new(),
+        }
+    }
+
+    /// 分步推进暂停的时钟，让定时任务在每个间隔到期后都能运行
+    async fn advance_by(total: Duration) {
+        let step = Duration::from_millis(100);
+        let mut elapsed = Duration::ZERO;
+        while elapsed < total {
+            tokio::time::advance(step).await;
+            tokio::task::yield_now().await;
+            elapsed += step;
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_interval_export_fires_and_stops_after_remove() {
+        let scheduler = ExportScheduler::new();
+        let fired = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&fired);
+        scheduler.start(Arc::new(move |export, now| {
+            let counter = Arc::clone(&counter);
+            Box::pin(async move {
+                // 每次执行使用以执行时刻为终点的时间范围
+                assert_eq!(
+                    export.params_at(now)?.end_time,
+                    now.format(SCHEDULE_TIME_FORMAT).to_string()
+                );
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            })
+        }));
+
+        let info = scheduler.add(sample_export("1s")).unwrap();
+        assert!(info.export.id.starts_with('e'));
+        advance_by(Duration::from_millis(2500)).await;
+        let count = fired.load(Ordering::SeqCst);
+        assert!(count >= 2, "1s 间隔 2.5s 内应至少执行 2 次，实际 {}", count);
+        let listed = scheduler.list();
+        assert_eq!(listed.len(), 1);
+        assert!(listed[0].last_run.is_some());
+        assert!(listed[0].next_run.is_some());
+
+        // 移除后不再执行
+        scheduler.remove(&info.export.id).unwrap();
+        advance_by(Duration::from_millis(1500)).await;
+        assert_eq!(fired.load(Ordering::SeqCst), count);
+        assert!(scheduler.list().is_empty());
+        assert!(scheduler.remove(&info.export.id).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_definitions_persist_across_restart() {
+        let path = std::env::temp_dir().join("industry_vis_test_scheduled_exports.json");
+        let _ = std::fs::remove_file(&path);
+
+        let scheduler =
+            ExportScheduler::with_store(ScheduledExportStore::load_from(&path).unwrap());
+        let kept = scheduler.add(sample_export("0 8 * * *")).unwrap();
+        let removed = scheduler.add(sample_export("15m")).unwrap();
+        assert!(scheduler.add(sample_export("every hour")).is_err());
+        scheduler.remove(&removed.export.id).unwrap();
+        drop(scheduler);
+
+        let restored = ExportScheduler::with_store(ScheduledExportStore::load_from(&path).unwrap());
+        let listed = restored.list();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].export.id, kept.export.id);
+        assert_eq!(listed[0].export.schedule, "0 8 * * *");
+        // 未启动时不计算下一次执行时刻
+        assert!(listed[0].next_run.is_none());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
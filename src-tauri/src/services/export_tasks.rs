@@ -0,0 +1,376 @@
+//! 后台导出任务队列
+//!
+//! 导出任务提交后立即返回任务 ID，按提交顺序排队在后台执行（同时只执行一个，
+//! 避免多个大导出同时占用数据库和磁盘）。任务状态变化时通过通知回调上报，
+//! 由命令层转为 `export-progress` 事件。
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use tokio::sync::Semaphore;
+use tracing::{debug, warn};
+
+use crate::error::{AppError, AppResult};
+
+/// 导出进度事件名
+pub const EXPORT_PROGRESS_EVENT: &str = "export-progress";
+
+/// 同时执行的导出任务数
+const MAX_RUNNING_EXPORTS: usize = 1;
+
+/// 保留的已结束任务数（超出时移除最早结束的）
+const MAX_FINISHED_TASKS: usize = 50;
+
+/// 导出任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportTaskStatus {
+    /// 排队等待
+    Queued,
+    /// 执行中
+    Running,
+    /// 已完成
+    Done,
+    /// 执行失败
+    Failed,
+    /// 已取消
+    Cancelled,
+}
+
+impl ExportTaskStatus {
+    /// 是否已结束（完成、失败或取消）
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Done | Self::Failed | Self::Cancelled)
+    }
+}
+
+/// 导出任务信息
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportTaskInfo {
+    /// 任务 ID
+    pub task_id: String,
+    /// 导出文件路径
+    pub file_path: String,
+    /// 任务状态
+    pub status: ExportTaskStatus,
+    /// 进度（0.0 ~ 1.0）
+    pub progress: f64,
+    /// 导出的记录数（完成后返回）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub record_count: Option<usize>,
+    /// 失败原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 任务状态变化通知
+pub type ExportTaskNotifier = Arc<dyn Fn(&ExportTaskInfo) + Send + Sync>;
+
+struct TaskEntry {
+    info: ExportTaskInfo,
+    cancelled: Arc<AtomicBool>,
+    /// 结束顺序（用于淘汰最早结束的任务）
+    finished_seq: Option<u64>,
+}
+
+struct Inner {
+    tasks: Mutex<HashMap<String, TaskEntry>>,
+    notifier: Option<ExportTaskNotifier>,
+    finished_seq: AtomicU64,
+}
+
+impl Inner {
+    /// 更新任务信息并通知（任务已取消时不再覆盖状态）
+    fn update(&self, task_id: &str, apply: impl FnOnce(&mut ExportTaskInfo)) {
+        let info = {
+            let mut tasks = self.tasks.lock();
+            let Some(entry) = tasks.get_mut(task_id) else {
+                return;
+            };
+            if entry.info.status == ExportTaskStatus::Cancelled {
+                return;
+            }
+            apply(&mut entry.info);
+            if entry.info.status.is_finished() {
+                entry.finished_seq = Some(self.finished_seq.fetch_add(1, Ordering::Relaxed));
+                prune_finished(&mut tasks);
+            }
+            tasks.get(task_id).map(|e| e.info.clone())
+        };
+        if let Some(info) = info {
+            self.notify(&info);
+        }
+    }
+
+    fn notify(&self, info: &ExportTaskInfo) {
+        if let Some(notifier) = &self.notifier {
+            notifier(info);
+        }
+    }
+}
+
+/// 移除超出保留数的最早结束任务
+fn prune_finished(tasks: &mut HashMap<String, TaskEntry>) {
+    let mut finished: Vec<(u64, String)> = tasks
+        .iter()
+        .filter_map(|(id, e)| e.finished_seq.map(|seq| (seq, id.clone())))
+        .collect();
+    if finished.len() <= MAX_FINISHED_TASKS {
+        return;
+    }
+    finished.sort_unstable();
+    for (_, id) in &finished[..finished.len() - MAX_FINISHED_TASKS] {
+        tasks.remove(id);
+    }
+}
+
+/// 任务执行上下文（供导出过程上报进度、检查取消）
+#[derive(Clone)]
+pub struct ExportTaskContext {
+    task_id: String,
+    cancelled: Arc<AtomicBool>,
+    inner: Arc<Inner>,
+}
+
+impl ExportTaskContext {
+    /// 任务是否已被取消（导出过程应在各阶段之间检查并尽早返回）
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// 上报进度（0.0 ~ 1.0）
+    pub fn set_progress(&self, progress: f64) {
+        let progress = progress.clamp(0.0, 1.0);
+        self.inner
+            .update(&self.task_id, |info| info.progress = progress);
+    }
+}
+
+/// 导出任务队列
+pub struct ExportTaskQueue {
+    inner: Arc<Inner>,
+    slots: Arc<Semaphore>,
+    next_id: AtomicU64,
+}
+
+impl ExportTaskQueue {
+    /// 创建队列，`notifier` 在任务状态或进度变化时调用
+    pub fn new(notifier: Option<ExportTaskNotifier>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                tasks: Mutex::new(HashMap::new()),
+                notifier,
+                finished_seq: AtomicU64::new(0),
+            }),
+            slots: Arc::new(Semaphore::new(MAX_RUNNING_EXPORTS)),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// 提交导出任务，立即返回任务 ID
+    ///
+    /// `job` 返回导出的记录数；取消后 `job` 的结果被忽略，状态保持为已取消。
+    pub fn submit<F, Fut>(&self, file_path: String, job: F) -> String
+    where
+        F: FnOnce(ExportTaskContext) -> Fut + Send + 'static,
+        Fut: Future<Output = AppResult<usize>> + Send + 'static,
+    {
+        let task_id = format!("export{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let info = ExportTaskInfo {
+            task_id: task_id.clone(),
+            file_path,
+            status: ExportTaskStatus::Queued,
+            progress: 0.0,
+            record_count: None,
+            error: None,
+        };
+        self.inner.tasks.lock().insert(
+            task_id.clone(),
+            TaskEntry {
+                info: info.clone(),
+                cancelled: Arc::clone(&cancelled),
+                finished_seq: None,
+            },
+        );
+        self.inner.notify(&info);
+
+        let context = ExportTaskContext {
+            task_id: task_id.clone(),
+            cancelled,
+            inner: Arc::clone(&self.inner),
+        };
+        let slots = Arc::clone(&self.slots);
+        tokio::spawn(async move {
+            // 按提交顺序排队（Semaphore 公平获取）
+            let Ok(_permit) = slots.acquire_owned().await else {
+                return;
+            };
+            if context.is_cancelled() {
+                return;
+            }
+
+            let inner = Arc::clone(&context.inner);
+            let id = context.task_id.clone();
+            inner.update(&id, |info| info.status = ExportTaskStatus::Running);
+            debug!(target: "industry_vis::export", "导出任务开始 - {}", id);
+
+            match job(context).await {
+                Ok(count) => inner.update(&id, |info| {
+                    info.status = ExportTaskStatus::Done;
+                    info.progress = 1.0;
+                    info.record_count = Some(count);
+                }),
+                Err(e) => {
+                    warn!(target: "industry_vis::export", "导出任务失败 - {}: {}", id, e);
+                    inner.update(&id, |info| {
+                        info.status = ExportTaskStatus::Failed;
+                        info.error = Some(e.to_string());
+                    });
+                }
+            }
+        });
+
+        task_id
+    }
+
+    /// 查询任务状态
+    pub fn get(&self, task_id: &str) -> Option<ExportTaskInfo> {
+        self.inner.tasks.lock().get(task_id).map(|e| e.info.clone())
+    }
+
+    /// 取消任务（排队中的不再执行，执行中的在下一个检查点停止）
+    pub fn cancel(&self, task_id: &str) -> AppResult<ExportTaskInfo> {
+        let info = {
+            let mut tasks = self.inner.tasks.lock();
+            let entry = tasks
+                .get_mut(task_id)
+                .ok_or_else(|| AppError::NotFound(format!("导出任务 '{}' 不存在", task_id)))?;
+            if entry.info.status.is_finished() {
+                return Err(AppError::Validation(format!(
+                    "导出任务 '{}' 已结束，无法取消",
+                    task_id
+                )));
+            }
+            entry.cancelled.store(true, Ordering::Relaxed);
+            entry.info.status = ExportTaskStatus::Cancelled;
+            entry.finished_seq = Some(self.inner.finished_seq.fetch_add(1, Ordering::Relaxed));
+            entry.info.clone()
+        };
+        self.inner.notify(&info);
+        Ok(info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::sync::oneshot;
+
+    async fn wait_for_status(
+        queue: &ExportTaskQueue,
+        task_id: &str,
+        status: ExportTaskStatus,
+    ) -> ExportTaskInfo {
+        tokio::time::timeout(Duration::from_secs(2), async {
+            loop {
+                let info = queue.get(task_id).unwrap();
+                if info.status == status {
+                    return info;
+                }
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("任务 {} 未进入 {:?}", task_id, status))
+    }
+
+    #[tokio::test]
+    async fn test_submit_returns_immediately_and_completes() {
+        let (events_tx, mut events_rx) = tokio::sync::mpsc::unbounded_channel();
+        let queue = ExportTaskQueue::new(Some(Arc::new(move |info: &ExportTaskInfo| {
+            let _ = events_tx.send(info.status);
+        })));
+        let (release_tx, release_rx) = oneshot::channel::<()>();
+
+        let id = queue.submit("a.csv".to_string(), |ctx| async move {
+            let _ = release_rx.await;
+            ctx.set_progress(0.5);
+            Ok(42)
+        });
+
+        // 提交后立即返回，任务尚未完成
+        assert!(!queue.get(&id).unwrap().status.is_finished());
+
+        release_tx.send(()).unwrap();
+        let info = wait_for_status(&queue, &id, ExportTaskStatus::Done).await;
+        assert_eq!(info.record_count, Some(42));
+        assert_eq!(info.progress, 1.0);
+
+        // 状态变化均已通知
+        let mut statuses = Vec::new();
+        while let Ok(status) = events_rx.try_recv() {
+            statuses.push(status);
+        }
+        assert_eq!(statuses.first(), Some(&ExportTaskStatus::Queued));
+        assert_eq!(statuses.last(), Some(&ExportTaskStatus::Done));
+        assert!(statuses.contains(&ExportTaskStatus::Running));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_queued_and_running_tasks() {
+        let queue = ExportTaskQueue::new(None);
+        let (release_tx, release_rx) = oneshot::channel::<()>();
+
+        // 第一个任务占住执行位，直到被取消
+        let running = queue.submit("a.csv".to_string(), |ctx| async move {
+            let _ = release_rx.await;
+            while !ctx.is_cancelled() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+            Ok(1)
+        });
+        let ran = Arc::new(AtomicBool::new(false));
+        let ran_flag = Arc::clone(&ran);
+        let queued = queue.submit("b.csv".to_string(), move |_ctx| async move {
+            ran_flag.store(true, Ordering::SeqCst);
+            Ok(2)
+        });
+
+        wait_for_status(&queue, &running, ExportTaskStatus::Running).await;
+        assert_eq!(queue.get(&queued).unwrap().status, ExportTaskStatus::Queued);
+
+        // 取消排队中的任务：不再执行
+        let info = queue.cancel(&queued).unwrap();
+        assert_eq!(info.status, ExportTaskStatus::Cancelled);
+
+        // 取消执行中的任务：结果被忽略，状态保持已取消
+        assert_eq!(
+            queue.cancel(&running).unwrap().status,
+            ExportTaskStatus::Cancelled
+        );
+        release_tx.send(()).unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(
+            queue.get(&running).unwrap().status,
+            ExportTaskStatus::Cancelled
+        );
+        assert_eq!(queue.get(&running).unwrap().record_count, None);
+        assert!(!ran.load(Ordering::SeqCst));
+
+        // 已结束的任务不能再取消，不存在的任务返回 NotFound
+        assert!(matches!(
+            queue.cancel(&running),
+            Err(AppError::Validation(_))
+        ));
+        assert!(matches!(
+            queue.cancel("missing"),
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
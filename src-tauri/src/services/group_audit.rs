@@ -0,0 +1,253 @@
+//! 标签分组批量健康检查
+//!
+//! 一次性检查所有分组：标签是否存在于标签表、最近一段时间内是否有数据。
+//! 全部分组的标签合并去重后只查询一次标签列表和一次"每标签最新一条"
+//! （数据源内部按标签并行），结果按分组汇总并缓存一段时间。
+
+use chrono::{Duration as ChronoDuration, Local, NaiveDateTime};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashSet};
+use std::time::{Duration, Instant};
+use tracing::info;
+
+use crate::datasource::DataSource;
+use crate::error::{AppError, AppResult};
+use crate::models::TagGroup;
+
+/// 时间格式
+const QUERY_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// 默认"最近"的时间窗口（小时）
+pub const DEFAULT_AUDIT_RECENT_HOURS: u64 = 24;
+
+/// 时间窗口上限（小时）
+const MAX_AUDIT_RECENT_HOURS: u64 = 24 * 30;
+
+/// 检查结果的缓存有效期
+const AUDIT_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// 有问题的分组
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupAuditIssue {
+    pub group_id: String,
+    pub group_name: String,
+    /// 标签表中不存在的标签
+    pub missing_tags: Vec<String>,
+    /// 存在但时间窗口内没有数据的标签
+    pub stale_tags: Vec<String>,
+}
+
+/// 批量健康检查结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupAuditReport {
+    /// 有问题的分组（健康分组不出现），按分组名称排序
+    pub issues: Vec<GroupAuditIssue>,
+    pub group_count: usize,
+    /// 检查的标签数（去重）
+    pub tag_count: usize,
+    /// 判断"最近无数据"的时间窗口（小时）
+    pub recent_hours: u64,
+    /// 检查时刻
+    pub audited_at: String,
+    /// 结果是否来自缓存
+    pub cached: bool,
+}
+
+/// 检查所有分组的标签存在性和最近数据
+///
+/// 标签存在性不区分大小写（与空结果诊断一致）。
+pub async fn audit_groups(
+    source: &dyn DataSource,
+    table: &str,
+    groups: &[TagGroup],
+    now: NaiveDateTime,
+    recent_hours: u64,
+) -> AppResult<GroupAuditReport> {
+    if recent_hours == 0 || recent_hours > MAX_AUDIT_RECENT_HOURS {
+        return Err(AppError::Validation(format!(
+            "时间窗口取值范围为 1-{} 小时",
+            MAX_AUDIT_RECENT_HOURS
+        )));
+    }
+
+    let tags: Vec<String> = groups
+        .iter()
+        .flat_map(TagGroup::all_tags)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    let (available, recent) = if tags.is_empty() {
+        (HashSet::new(), HashSet::new())
+    } else {
+        let start = (now - ChronoDuration::hours(recent_hours as i64))
+            .format(QUERY_TIME_FORMAT)
+            .to_string();
+        let end = now.format(QUERY_TIME_FORMAT).to_string();
+        let (available, latest) = tokio::try_join!(
+            source.get_available_tags(table),
+            source.query_history_latest(table, &start, &end, Some(&tags), 1),
+        )?;
+        (
+            available
+                .iter()
+                .map(|t| t.to_ascii_lowercase())
+                .collect::<HashSet<_>>(),
+            latest
+                .iter()
+                .map(|r| r.tag_name.to_ascii_lowercase())
+                .collect::<HashSet<_>>(),
+        )
+    };
+
+    let mut issues: Vec<GroupAuditIssue> = groups
+        .iter()
+        .filter_map(|group| {
+            let (mut missing_tags, mut stale_tags) = (Vec::new(), Vec::new());
+            for tag in group.all_tags() {
+                let key = tag.to_ascii_lowercase();
+                if !available.contains(&key) {
+                    missing_tags.push(tag);
+                } else if !recent.contains(&key) {
+                    stale_tags.push(tag);
+                }
+            }
+            (!missing_tags.is_empty() || !stale_tags.is_empty()).then(|| GroupAuditIssue {
+                group_id: group.id.clone(),
+                group_name: group.name.clone(),
+                missing_tags,
+                stale_tags,
+            })
+        })
+        .collect();
+    issues.sort_by(|a, b| a.group_name.cmp(&b.group_name));
+
+    info!(target: "industry_vis::tag_group",
+        "分组健康检查完成 - 分组: {}, 标签: {}, 有问题的分组: {}",
+        groups.len(), tags.len(), issues.len()
+    );
+    Ok(GroupAuditReport {
+        issues,
+        group_count: groups.len(),
+        tag_count: tags.len(),
+        recent_hours,
+        audited_at: Local::now().format(QUERY_TIME_FORMAT).to_string(),
+        cached: false,
+    })
+}
+
+/// 健康检查结果缓存（只保留最近一次，分组或时间窗口变化时失效）
+pub struct GroupAuditCache {
+    entry: Mutex<Option<(String, Instant, GroupAuditReport)>>,
+}
+
+impl GroupAuditCache {
+    pub fn new() -> Self {
+        Self {
+            entry: Mutex::new(None),
+        }
+    }
+
+    /// 缓存键：各分组的 ID 和标签 + 时间窗口
+    pub fn key(groups: &[TagGroup], recent_hours: u64) -> String {
+        let mut key = format!("{}h", recent_hours);
+        for group in groups {
+            key.push('|');
+            key.push_str(&group.id);
+            key.push(':');
+            key.push_str(&group.all_tags().join(","));
+        }
+        key
+    }
+
+    /// 获取未过期的缓存结果
+    pub fn get(&self, key: &str) -> Option<GroupAuditReport> {
+        let entry = self.entry.lock();
+        let (cached_key, created_at, report) = entry.as_ref()?;
+        (cached_key == key && created_at.elapsed() <= AUDIT_CACHE_TTL).then(|| GroupAuditReport {
+            cached: true,
+            ..report.clone()
+        })
+    }
+
+    pub fn put(&self, key: String, report: GroupAuditReport) {
+        *self.entry.lock() = Some((key, Instant::now(), report));
+    }
+
+    pub fn clear(&self) {
+        *self.entry.lock() = None;
+    }
+}
+
+impl Default for GroupAuditCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasource::MemorySource;
+    use crate::models::{ChartConfig, HistoryRecord};
+
+    fn group(id: &str, name: &str, tags: &[&str]) -> TagGroup {
+        let chart = ChartConfig::new("图表".to_string())
+            .with_tags(tags.iter().map(|t| t.to_string()).collect());
+        let mut group = TagGroup::new(name.to_string(), vec![chart]).unwrap();
+        group.id = id.to_string();
+        group
+    }
+
+    #[tokio::test]
+    async fn test_audit_reports_only_problem_groups() {
+        let record = |time: &str, tag: &str| {
+            HistoryRecord::new(time.to_string(), tag.to_string(), 1.0, "Good".to_string())
+        };
+        // TT_101 / TT_102 有最近数据，TT_OLD 只有很早的数据
+        let source = MemorySource::new(
+            "memory",
+            vec![
+                record("2024-01-01T00:00:00", "TT_OLD"),
+                record("2024-01-10T08:00:00", "TT_101"),
+                record("2024-01-10T09:00:00", "TT_102"),
+            ],
+        );
+        let groups = vec![
+            group("g1", "健康分组", &["TT_101", "tt_102"]),
+            group("g2", "失效分组", &["TT_101", "TT_GONE", "TT_OLD"]),
+        ];
+        let now = NaiveDateTime::parse_from_str("2024-01-10T12:00:00", QUERY_TIME_FORMAT).unwrap();
+
+        let report = audit_groups(&source, "History", &groups, now, 24)
+            .await
+            .unwrap();
+        assert_eq!(report.group_count, 2);
+        assert_eq!(report.tag_count, 4);
+        assert_eq!(
+            report.issues,
+            vec![GroupAuditIssue {
+                group_id: "g2".to_string(),
+                group_name: "失效分组".to_string(),
+                missing_tags: vec!["TT_GONE".to_string()],
+                stale_tags: vec!["TT_OLD".to_string()],
+            }]
+        );
+        assert!(
+            audit_groups(&source, "History", &groups, now, 0)
+                .await
+                .is_err()
+        );
+
+        // 缓存：同一分组和时间窗口命中，分组变化后失效
+        let cache = GroupAuditCache::new();
+        let key = GroupAuditCache::key(&groups, 24);
+        cache.put(key.clone(), report);
+        assert!(cache.get(&key).unwrap().cached);
+        assert!(cache.get(&GroupAuditCache::key(&groups[..1], 24)).is_none());
+        assert!(cache.get(&GroupAuditCache::key(&groups, 48)).is_none());
+    }
+}
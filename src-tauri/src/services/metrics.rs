@@ -0,0 +1,350 @@
+//! 运行指标导出
+//!
+//! 汇总连接池、查询缓存、数据库查询计数、断路器和连通性状态，
+//! 按 Prometheus exposition 文本格式输出，便于接入企业监控体系。
+
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::{CircuitBreakerStatus, CircuitState, ConnectionHealth, ConnectionHealthStatus};
+use crate::cache::CacheStats;
+use crate::datasource::PoolState;
+
+/// 指标名前缀
+const METRIC_PREFIX: &str = "industry_vis";
+
+/// 数据库查询计数（只统计实际访问数据库的历史查询，缓存命中不计入）
+#[derive(Debug, Default)]
+pub struct QueryCounters {
+    queries: AtomicU64,
+    errors: AtomicU64,
+    rows: AtomicU64,
+    duration_ms: AtomicU64,
+}
+
+/// 数据库查询计数快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryCounterSnapshot {
+    pub queries: u64,
+    pub errors: u64,
+    pub rows: u64,
+    pub duration_ms: u64,
+}
+
+impl QueryCounters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一次成功的查询
+    pub fn record(&self, rows: usize, elapsed_ms: u64) {
+        self.queries.fetch_add(1, Ordering::Relaxed);
+        self.rows.fetch_add(rows as u64, Ordering::Relaxed);
+        self.duration_ms.fetch_add(elapsed_ms, Ordering::Relaxed);
+    }
+
+    /// 记录一次失败的查询
+    pub fn record_error(&self) {
+        self.queries.fetch_add(1, Ordering::Relaxed);
+        self.errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> QueryCounterSnapshot {
+        QueryCounterSnapshot {
+            queries: self.queries.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            rows: self.rows.load(Ordering::Relaxed),
+            duration_ms: self.duration_ms.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 导出时刻的各项运行状态
+#[derive(Debug, Clone)]
+pub struct MetricsSnapshot {
+    /// 连接池状态（未初始化时为 `None`）
+    pub pool: Option<PoolState>,
+    pub cache: CacheStats,
+    pub queries: QueryCounterSnapshot,
+    pub circuit_breaker: CircuitBreakerStatus,
+    pub connection: ConnectionHealthStatus,
+}
+
+/// Prometheus 文本格式的指标输出
+struct Exposition {
+    output: String,
+}
+
+impl Exposition {
+    /// 写出一个指标族：`# HELP` / `# TYPE` 行和各样本行（样本可带一个 `(名称, 值)` 标签）
+    fn family(
+        &mut self,
+        name: &str,
+        kind: &str,
+        help: &str,
+        samples: &[(Option<(&str, &str)>, f64)],
+    ) {
+        let name = format!("{}_{}", METRIC_PREFIX, name);
+        let _ = writeln!(self.output, "# HELP {} {}", name, help);
+        let _ = writeln!(self.output, "# TYPE {} {}", name, kind);
+        for (label, value) in samples {
+            self.output.push_str(&name);
+            if let Some((key, label_value)) = label {
+                let _ = write!(
+                    self.output,
+                    "{{{}=\"{}\"}}",
+                    key,
+                    escape_label_value(label_value)
+                );
+            }
+            let _ = writeln!(self.output, " {}", format_sample_value(*value));
+        }
+    }
+
+    fn gauge(&mut self, name: &str, help: &str, value: f64) {
+        self.family(name, "gauge", help, &[(None, value)]);
+    }
+
+    fn counter(&mut self, name: &str, help: &str, value: f64) {
+        self.family(name, "counter", help, &[(None, value)]);
+    }
+}
+
+/// 标签值转义（反斜杠、双引号、换行）
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// 样本值格式（非有限值按 Prometheus 约定写为 `NaN` / `+Inf` / `-Inf`）
+fn format_sample_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// 按 Prometheus exposition 文本格式输出指标
+pub fn render_prometheus(snapshot: &MetricsSnapshot) -> String {
+    let mut out = Exposition {
+        output: String::new(),
+    };
+    let flag = |value: bool| if value { 1.0 } else { 0.0 };
+
+    // 连接池
+    let pool = snapshot.pool.as_ref();
+    out.gauge(
+        "pool_initialized",
+        "连接池是否已初始化（1 为是）",
+        flag(pool.is_some()),
+    );
+    out.gauge(
+        "pool_connections",
+        "连接池总连接数",
+        pool.map_or(0.0, |p| p.connections as f64),
+    );
+    out.gauge(
+        "pool_idle_connections",
+        "连接池空闲连接数",
+        pool.map_or(0.0, |p| p.idle_connections as f64),
+    );
+    out.gauge(
+        "pool_active_connections",
+        "连接池活跃连接数",
+        pool.map_or(0.0, |p| p.active_connections as f64),
+    );
+    out.gauge(
+        "pool_max_size",
+        "连接池最大连接数",
+        pool.map_or(0.0, |p| p.max_size as f64),
+    );
+
+    // 查询缓存
+    let cache = &snapshot.cache;
+    out.counter("cache_hits_total", "查询缓存命中次数", cache.hits as f64);
+    out.counter(
+        "cache_misses_total",
+        "查询缓存未命中次数",
+        cache.misses as f64,
+    );
+    out.gauge(
+        "cache_hit_ratio",
+        "查询缓存命中率（0 ~ 1）",
+        cache.hit_rate / 100.0,
+    );
+    out.gauge("cache_entries", "查询缓存当前条目数", cache.entries as f64);
+    out.gauge(
+        "cache_max_entries",
+        "查询缓存最大条目数",
+        cache.max_entries as f64,
+    );
+    out.gauge(
+        "cache_memory_bytes",
+        "查询缓存估计内存占用（字节）",
+        cache.estimated_memory_bytes as f64,
+    );
+
+    // 数据库查询
+    let queries = &snapshot.queries;
+    out.counter(
+        "queries_total",
+        "访问数据库的历史查询次数",
+        queries.queries as f64,
+    );
+    out.counter(
+        "query_errors_total",
+        "失败的历史查询次数",
+        queries.errors as f64,
+    );
+    out.counter(
+        "query_rows_total",
+        "历史查询返回的总行数",
+        queries.rows as f64,
+    );
+    out.counter(
+        "query_duration_seconds_total",
+        "成功的历史查询累计耗时（秒）",
+        queries.duration_ms as f64 / 1000.0,
+    );
+
+    // 断路器与连通性（每个状态一个样本，当前状态为 1）
+    let breaker = &snapshot.circuit_breaker;
+    let samples = [
+        ("closed", CircuitState::Closed),
+        ("open", CircuitState::Open),
+        ("half_open", CircuitState::HalfOpen),
+    ]
+    .map(|(label, state)| (Some(("state", label)), flag(breaker.state == state)));
+    out.family(
+        "circuit_breaker_state",
+        "gauge",
+        "断路器状态（当前状态为 1）",
+        &samples,
+    );
+    out.gauge(
+        "circuit_breaker_consecutive_failures",
+        "断路器记录的连续连接失败次数",
+        breaker.consecutive_failures as f64,
+    );
+
+    let connection = &snapshot.connection;
+    let samples = [
+        ("healthy", ConnectionHealth::Healthy),
+        ("degraded", ConnectionHealth::Degraded),
+        ("disconnected", ConnectionHealth::Disconnected),
+    ]
+    .map(|(label, health)| (Some(("status", label)), flag(connection.health == health)));
+    out.family(
+        "connection_health",
+        "gauge",
+        "数据库连通性状态（当前状态为 1）",
+        &samples,
+    );
+    out.gauge(
+        "connection_latency_seconds",
+        "最近一次成功的连通性检查耗时（秒）",
+        connection
+            .latency_ms
+            .map_or(f64::NAN, |ms| ms as f64 / 1000.0),
+    );
+
+    out.output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_prometheus_exposition() {
+        let counters = QueryCounters::new();
+        counters.record(1200, 350);
+        counters.record(800, 150);
+        counters.record_error();
+
+        let snapshot = MetricsSnapshot {
+            pool: Some(PoolState {
+                connections: 4,
+                idle_connections: 3,
+                active_connections: 1,
+                max_size: 10,
+            }),
+            cache: CacheStats {
+                hits: 30,
+                misses: 10,
+                hit_rate: 75.0,
+                entries: 12,
+                max_entries: 100,
+                estimated_memory_bytes: 4096,
+            },
+            queries: counters.snapshot(),
+            circuit_breaker: CircuitBreakerStatus {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                retry_after_secs: None,
+            },
+            connection: ConnectionHealthStatus::default(),
+        };
+        let text = render_prometheus(&snapshot);
+        let lines: Vec<&str> = text.lines().collect();
+
+        for expected in [
+            "industry_vis_cache_hits_total 30",
+            "industry_vis_cache_hit_ratio 0.75",
+            "industry_vis_pool_connections 4",
+            "industry_vis_pool_active_connections 1",
+            "industry_vis_queries_total 3",
+            "industry_vis_query_errors_total 1",
+            "industry_vis_query_rows_total 2000",
+            "industry_vis_query_duration_seconds_total 0.5",
+            "industry_vis_circuit_breaker_state{state=\"closed\"} 1",
+            "industry_vis_connection_health{status=\"disconnected\"} 1",
+            "industry_vis_connection_latency_seconds NaN",
+        ] {
+            assert!(lines.contains(&expected), "缺少指标行: {}", expected);
+        }
+
+        // 格式：每个样本行属于前面声明过 TYPE 的指标族，指标名合法，值可解析
+        assert!(text.ends_with('\n'));
+        let mut declared: Vec<&str> = Vec::new();
+        for line in lines {
+            if let Some(rest) = line.strip_prefix("# TYPE ") {
+                let (name, kind) = rest.split_once(' ').unwrap();
+                assert!(kind == "gauge" || kind == "counter");
+                assert!(!declared.contains(&name), "指标族重复: {}", name);
+                declared.push(name);
+                continue;
+            }
+            if line.starts_with("# HELP ") {
+                continue;
+            }
+            let (series, value) = line.rsplit_once(' ').unwrap();
+            let name = series.split('{').next().unwrap();
+            assert!(
+                name.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+                    && !name.starts_with(|c: char| c.is_ascii_digit())
+            );
+            assert_eq!(
+                declared.last(),
+                Some(&name),
+                "样本行缺少 TYPE 声明: {}",
+                line
+            );
+            assert!(
+                value.parse::<f64>().is_ok() || value == "NaN",
+                "值无效: {}",
+                line
+            );
+        }
+        assert_eq!(declared.len(), 19);
+
+        // 标签值转义
+        assert_eq!(escape_label_value("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
@@ -2,8 +2,40 @@
 //!
 //! 封装核心业务逻辑，协调数据源、缓存、处理等模块。
 
+mod chunks;
+mod circuit_breaker;
+mod connection_monitor;
+mod estimate;
+mod export_scheduler;
+mod export_tasks;
+mod group_audit;
+mod metrics;
 mod query_service;
+mod realtime;
 mod tag_group_service;
+mod throttle;
 
+pub use chunks::{SeriesChunk, SeriesChunkStore, merge_series_chunks, split_series_chunks};
+pub use circuit_breaker::{CircuitBreaker, CircuitBreakerStatus, CircuitState};
+pub use connection_monitor::{
+    CONNECTION_HEALTH_EVENT, ConnectionHealth, ConnectionHealthNotifier, ConnectionHealthStatus,
+    ConnectionMonitor, ConnectionProbe,
+};
+pub use estimate::QueryRateTracker;
+pub use export_scheduler::{ExportScheduler, ScheduledExportInfo, ScheduledExportRunner};
+pub use export_tasks::{
+    EXPORT_PROGRESS_EVENT, ExportTaskContext, ExportTaskInfo, ExportTaskNotifier, ExportTaskQueue,
+    ExportTaskStatus,
+};
+pub use group_audit::{
+    DEFAULT_AUDIT_RECENT_HOURS, GroupAuditCache, GroupAuditIssue, GroupAuditReport, audit_groups,
+};
+pub use metrics::{MetricsSnapshot, QueryCounterSnapshot, QueryCounters, render_prometheus};
 pub use query_service::QueryService;
+pub(crate) use query_service::{
+    LoadedRecords, StageClock, cap_result_rows, diagnose_empty_result, format_output_records,
+    load_processed_records,
+};
+pub use realtime::{REALTIME_EVENT, RealtimeSubscriptions, RealtimeUpdate};
 pub use tag_group_service::TagGroupService;
+pub use throttle::{QueryThrottle, ThrottlePermit};
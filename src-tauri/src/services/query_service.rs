@@ -2,14 +2,19 @@
 //!
 //! 整合数据源、缓存、数据处理，提供统一的查询接口。
 
+use std::collections::HashSet;
+use std::future::Future;
 use std::sync::Arc;
 use std::time::Instant;
-use tracing::info;
+use tracing::{debug, info, warn};
 
 use crate::cache::{CacheKey, QueryCache};
 use crate::datasource::{ConnectionPool, DataSource, SqlServerSource};
 use crate::error::AppResult;
-use crate::models::{DataProcessingConfig, HistoryRecord, QueryParams, QueryResult, QueryResultV2};
+use crate::models::{
+    DataProcessingConfig, EmptyReason, HistoryRecord, QueryParams, QueryResult, QueryResultV2,
+    QueryTiming, series_content_hash,
+};
 use crate::processing;
 
 /// 查询服务
@@ -62,16 +67,8 @@ impl QueryService {
         processing_config: Option<&DataProcessingConfig>,
         force_refresh: bool,
     ) -> AppResult<QueryResult> {
-        let tags_ref = params.tags.as_deref();
-
-        // 构建缓存键
-        let cache_key = CacheKey::new(
-            &self.default_table,
-            &params.start_time,
-            &params.end_time,
-            tags_ref,
-            processing_config,
-        );
+        // 构建缓存键（不含分页参数，各页共享同一缓存条目）
+        let cache_key = CacheKey::for_query(&self.default_table, params, processing_config);
 
         // 检查缓存（非强制刷新时）
         if !force_refresh && let Some(cached_records) = self.cache.get(&cache_key).await {
@@ -80,19 +77,24 @@ impl QueryService {
             );
 
             let total = cached_records.len();
-            let records = apply_pagination(cached_records, params.offset, params.limit);
-            return Ok(QueryResult { records, total });
+            let records = apply_pagination(
+                cached_records,
+                params.offset,
+                params.limit,
+                params.newest_first,
+            );
+            let records = format_output_records(records, params)?;
+            return Ok(QueryResult {
+                records,
+                total,
+                truncated: false,
+            });
         }
 
         // 从数据库查询
         let records = self
             .source
-            .query_history(
-                &self.default_table,
-                &params.start_time,
-                &params.end_time,
-                tags_ref,
-            )
+            .query_history_with_params(&self.default_table, params)
             .await?;
 
         let total = records.len();
@@ -105,9 +107,19 @@ impl QueryService {
         self.cache.put(cache_key, processed_records.clone()).await;
 
         // 应用分页
-        let records = apply_pagination(processed_records, params.offset, params.limit);
+        let records = apply_pagination(
+            processed_records,
+            params.offset,
+            params.limit,
+            params.newest_first,
+        );
+        let records = format_output_records(records, params)?;
 
-        Ok(QueryResult { records, total })
+        Ok(QueryResult {
+            records,
+            total,
+            truncated: false,
+        })
     }
 
     /// 查询历史数据 V2 (预分组格式)
@@ -117,84 +129,335 @@ impl QueryService {
         processing_config: Option<&DataProcessingConfig>,
         force_refresh: bool,
     ) -> AppResult<QueryResultV2> {
-        let start_time = Instant::now();
-        let tags_ref = params.tags.as_deref();
+        if let Some(align) = &params.align {
+            align.validate()?;
+        }
+        let mut clock = StageClock::start();
 
         // 构建缓存键
-        let cache_key = CacheKey::new(
-            &self.default_table,
-            &params.start_time,
-            &params.end_time,
-            tags_ref,
-            processing_config,
-        );
+        let cache_key = CacheKey::for_query(&self.default_table, params, processing_config);
 
         // 检查缓存
-        if !force_refresh && let Some(cached_records) = self.cache.get(&cache_key).await {
-            let query_time_ms = start_time.elapsed().as_millis() as u64;
-            let total_processed = cached_records.len();
-
+        if !force_refresh
+            && let Some(result) =
+                lookup_cached_v2(&self.cache, &cache_key, params, &mut clock).await
+        {
             info!(target: "industry_vis::query_service",
                 "缓存命中 V2，返回 {} 条记录，耗时 {}ms",
-                total_processed, query_time_ms
+                result.total_processed, result.query_time_ms
             );
-
-            let series = processing::records_to_series(&cached_records);
-            return Ok(QueryResultV2 {
-                series,
-                total_raw: total_processed,
-                total_processed,
-                cache_hit: true,
-                query_time_ms,
-            });
+            return Ok(result);
         }
+        let mut cache_lookup_ms = clock.lap_ms();
 
         // 从数据库查询
         let records = self
             .source
-            .query_history(
-                &self.default_table,
-                &params.start_time,
-                &params.end_time,
-                tags_ref,
-            )
+            .query_history_with_params(&self.default_table, params)
             .await?;
+        let db_ms = clock.lap_ms();
 
         let total_raw = records.len();
         info!(target: "industry_vis::query_service", "查询到 {} 条原始记录", total_raw);
 
+        // 启用重采样时检查原始数据采样均匀性，告警随缓存条目保存
+        let warnings = if processing_config.is_some_and(|c| c.resample.enabled) {
+            processing::detect_uneven_sampling(&records)
+        } else {
+            Vec::new()
+        };
+
         // 数据处理
         let processed_records = processing::process_query_result(records, processing_config)?;
         let total_processed = processed_records.len();
+        let process_ms = clock.lap_ms();
 
         // 存入缓存
-        self.cache.put(cache_key, processed_records.clone()).await;
+        self.cache
+            .put_with_warnings(cache_key, processed_records.clone(), warnings.clone())
+            .await;
+        cache_lookup_ms += clock.lap_ms();
 
         // 转换为 series 格式
-        let series = processing::records_to_series(&processed_records);
-        let query_time_ms = start_time.elapsed().as_millis() as u64;
+        let series = processing::build_series(&processed_records, params);
+        let serialize_ms = clock.lap_ms();
+        let query_time_ms = clock.total_ms();
 
         info!(target: "industry_vis::query_service",
-            "处理后返回 {} 条记录，{} 个系列，耗时 {}ms",
-            total_processed, series.len(), query_time_ms
+            "处理后返回 {} 条记录，{} 个系列，耗时 {}ms (DB {}ms, 处理 {}ms, 序列化 {}ms)",
+            total_processed, series.len(), query_time_ms, db_ms, process_ms, serialize_ms
         );
 
         Ok(QueryResultV2 {
+            content_hash: series_content_hash(&series),
             series,
+            annotations: Vec::new(),
             total_raw,
             total_processed,
             cache_hit: false,
+            truncated: false,
             query_time_ms,
+            timing: QueryTiming {
+                cache_lookup_ms,
+                db_ms,
+                process_ms,
+                serialize_ms,
+            },
+            query_token: None,
+            chunk_count: None,
+            empty_reason: None,
+            warnings,
         })
     }
 }
 
+/// 分阶段计时器
+///
+/// 用于将查询总耗时拆分为缓存、数据库、处理、序列化等阶段。
+pub(crate) struct StageClock {
+    started: Instant,
+    last: Instant,
+}
+
+impl StageClock {
+    /// 开始计时
+    pub(crate) fn start() -> Self {
+        let now = Instant::now();
+        Self {
+            started: now,
+            last: now,
+        }
+    }
+
+    /// 返回距上一阶段结束的耗时（毫秒），并开始下一阶段
+    pub(crate) fn lap_ms(&mut self) -> u64 {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last).as_millis() as u64;
+        self.last = now;
+        elapsed
+    }
+
+    /// 返回总耗时（毫秒）
+    pub(crate) fn total_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+}
+
+/// 尝试从缓存构建 V2 查询结果
+///
+/// 命中时返回完整结果（`db_ms`、`process_ms` 为 0，附带缓存条目保存的告警），未命中返回 None。
+pub(crate) async fn lookup_cached_v2(
+    cache: &QueryCache,
+    key: &CacheKey,
+    params: &QueryParams,
+    clock: &mut StageClock,
+) -> Option<QueryResultV2> {
+    let (cached_records, warnings) = cache.get_with_warnings(key).await?;
+    let cache_lookup_ms = clock.lap_ms();
+
+    let total_processed = cached_records.len();
+    let series = processing::build_series(&cached_records, params);
+    let serialize_ms = clock.lap_ms();
+
+    Some(QueryResultV2 {
+        content_hash: series_content_hash(&series),
+        series,
+        annotations: Vec::new(),
+        total_raw: total_processed,
+        total_processed,
+        cache_hit: true,
+        truncated: false,
+        query_time_ms: clock.total_ms(),
+        timing: QueryTiming {
+            cache_lookup_ms,
+            db_ms: 0,
+            process_ms: 0,
+            serialize_ms,
+        },
+        query_token: None,
+        chunk_count: None,
+        empty_reason: None,
+        warnings,
+    })
+}
+
+/// 处理后的历史记录（未分页）
+pub(crate) struct LoadedRecords {
+    pub records: Vec<HistoryRecord>,
+    /// 原始记录数（缓存命中部分按处理后记录数计）
+    pub total_raw: usize,
+    /// 是否全部来自缓存
+    pub cache_hit: bool,
+    pub truncated: bool,
+    /// 查询提示（启用重采样时的采样不均匀告警）
+    pub warnings: Vec<String>,
+    /// 各阶段耗时（`serialize_ms` 由调用方填写）
+    pub timing: QueryTiming,
+}
+
+/// 获取处理后的历史记录
+///
+/// 可缓存的标签先查缓存，未命中时查库并写入缓存；`no_cache_tags` 中的标签每次都查库
+/// 且不写入缓存。两部分分别处理后按时间合并。`fetch` 执行一次数据库查询，
+/// 返回原始记录和是否截断；截断的结果不写入缓存。
+///
+/// 启用重采样时检查原始数据的采样均匀性，告警随缓存条目保存，命中缓存时同样返回。
+pub(crate) async fn load_processed_records<F, Fut>(
+    cache: &QueryCache,
+    table: &str,
+    params: &QueryParams,
+    processing_config: Option<&DataProcessingConfig>,
+    force_refresh: bool,
+    clock: &mut StageClock,
+    fetch: F,
+) -> AppResult<LoadedRecords>
+where
+    F: Fn(QueryParams) -> Fut,
+    Fut: Future<Output = AppResult<(Vec<HistoryRecord>, bool)>>,
+{
+    let (cached_params, live_params) = params.split_no_cache();
+    let mut loaded = LoadedRecords {
+        records: Vec::new(),
+        total_raw: 0,
+        cache_hit: live_params.is_none(),
+        truncated: false,
+        warnings: Vec::new(),
+        timing: QueryTiming::default(),
+    };
+    let check_sampling = processing_config.is_some_and(|c| c.resample.enabled);
+    let sampling_warnings = |records: &[HistoryRecord]| {
+        if check_sampling {
+            processing::detect_uneven_sampling(records)
+        } else {
+            Vec::new()
+        }
+    };
+
+    let mut parts = Vec::with_capacity(2);
+    if let Some(params) = cached_params {
+        let key = CacheKey::for_query(table, &params, processing_config);
+        let cached = if force_refresh {
+            None
+        } else {
+            cache.get_with_warnings(&key).await
+        };
+        loaded.timing.cache_lookup_ms += clock.lap_ms();
+
+        match cached {
+            Some((records, warnings)) => {
+                loaded.total_raw += records.len();
+                loaded.warnings.extend(warnings);
+                parts.push(records);
+            }
+            None => {
+                loaded.cache_hit = false;
+                let (records, truncated) = fetch(params).await?;
+                loaded.timing.db_ms += clock.lap_ms();
+                loaded.total_raw += records.len();
+                loaded.truncated |= truncated;
+                let warnings = sampling_warnings(&records);
+                let processed = processing::process_query_result(records, processing_config)?;
+                loaded.timing.process_ms += clock.lap_ms();
+                if !truncated {
+                    cache
+                        .put_with_warnings(key, processed.clone(), warnings.clone())
+                        .await;
+                }
+                loaded.warnings.extend(warnings);
+                loaded.timing.cache_lookup_ms += clock.lap_ms();
+                parts.push(processed);
+            }
+        }
+    }
+
+    if let Some(params) = live_params {
+        debug!(target: "industry_vis::query_service",
+            "绕过缓存查询标签: {:?}", params.tags
+        );
+        let (records, truncated) = fetch(params).await?;
+        loaded.timing.db_ms += clock.lap_ms();
+        loaded.total_raw += records.len();
+        loaded.truncated |= truncated;
+        loaded.warnings.extend(sampling_warnings(&records));
+        parts.push(processing::process_query_result(
+            records,
+            processing_config,
+        )?);
+        loaded.timing.process_ms += clock.lap_ms();
+    }
+
+    loaded.records = if parts.len() == 1 {
+        parts.pop().unwrap_or_default()
+    } else {
+        let mut merged: Vec<HistoryRecord> = parts.into_iter().flatten().collect();
+        merged.sort_by(|a, b| a.date_time.cmp(&b.date_time));
+        merged
+    };
+    Ok(loaded)
+}
+
+/// 按行数上限截断原始查询结果，返回截断后的记录和是否发生截断
+///
+/// 数据库结果按时间升序，截断后保留最早的 `max_rows` 条。
+pub(crate) fn cap_result_rows(
+    mut records: Vec<HistoryRecord>,
+    max_rows: Option<usize>,
+) -> (Vec<HistoryRecord>, bool) {
+    let Some(max_rows) = max_rows else {
+        return (records, false);
+    };
+    if records.len() <= max_rows {
+        return (records, false);
+    }
+
+    warn!(target: "industry_vis::query_service",
+        "查询结果超过行数上限 {}，已截断，请缩小时间范围或减少标签", max_rows
+    );
+    records.truncate(max_rows);
+    (records, true)
+}
+
+/// 判断空结果的原因：请求的标签有不存在于库中的则指向标签，否则指向时间范围
+///
+/// SQL Server 默认排序规则不区分大小写，标签名按 ASCII 忽略大小写比较。
+pub(crate) fn diagnose_empty_result(requested: &[String], available: &[String]) -> EmptyReason {
+    let available: HashSet<String> = available.iter().map(|t| t.to_ascii_lowercase()).collect();
+    let missing: Vec<String> = requested
+        .iter()
+        .filter(|tag| !available.contains(&tag.to_ascii_lowercase()))
+        .cloned()
+        .collect();
+    if missing.is_empty() {
+        EmptyReason::NoDataInRange
+    } else {
+        EmptyReason::TagsNotFound { tags: missing }
+    }
+}
+
+/// 按 `QueryParams.output_time_format` 格式化 V1 结果记录时间（未指定时保持默认格式）
+pub(crate) fn format_output_records(
+    records: Vec<HistoryRecord>,
+    params: &QueryParams,
+) -> AppResult<Vec<HistoryRecord>> {
+    match params.output_time_format.as_deref() {
+        Some(format) => processing::format_record_times(records, format),
+        None => Ok(records),
+    }
+}
+
 /// 应用分页参数
+///
+/// `newest_first` 为 true 时先按时间降序排列，第一页为时间最新的记录。
 fn apply_pagination(
-    records: Vec<HistoryRecord>,
+    mut records: Vec<HistoryRecord>,
     offset: Option<usize>,
     limit: Option<usize>,
+    newest_first: bool,
 ) -> Vec<HistoryRecord> {
+    if newest_first {
+        // 记录按时间升序排列，稳定排序保证同一时刻的记录顺序不变
+        records.sort_by(|a, b| b.date_time.cmp(&a.date_time));
+    }
     match (offset, limit) {
         (Some(offset), Some(limit)) => records.into_iter().skip(offset).take(limit).collect(),
         (Some(offset), None) => records.into_iter().skip(offset).collect(),
@@ -208,6 +471,28 @@ mod tests {
     use super::*;
     use crate::models::HistoryRecord;
 
+    #[test]
+    fn test_diagnose_empty_result() {
+        let available = vec!["TT_101".to_string(), "PT_201".to_string()];
+
+        // 标签不存在：指向标签问题，只列出不存在的标签
+        let reason =
+            diagnose_empty_result(&["TT_101".to_string(), "TT_999".to_string()], &available);
+        assert_eq!(
+            reason,
+            EmptyReason::TagsNotFound {
+                tags: vec!["TT_999".to_string()]
+            }
+        );
+
+        // 标签都存在（忽略大小写）：指向时间范围
+        let reason = diagnose_empty_result(&["tt_101".to_string()], &available);
+        assert_eq!(reason, EmptyReason::NoDataInRange);
+
+        let json = serde_json::to_value(EmptyReason::NoDataInRange).unwrap();
+        assert_eq!(json["kind"], "noDataInRange");
+    }
+
     #[test]
     fn test_apply_pagination() {
         let records: Vec<HistoryRecord> = (0..10)
@@ -222,19 +507,318 @@ mod tests {
             .collect();
 
         // 无分页
-        let result = apply_pagination(records.clone(), None, None);
+        let result = apply_pagination(records.clone(), None, None, false);
         assert_eq!(result.len(), 10);
 
         // 只有 limit
-        let result = apply_pagination(records.clone(), None, Some(5));
+        let result = apply_pagination(records.clone(), None, Some(5), false);
         assert_eq!(result.len(), 5);
 
         // 只有 offset
-        let result = apply_pagination(records.clone(), Some(3), None);
+        let result = apply_pagination(records.clone(), Some(3), None, false);
         assert_eq!(result.len(), 7);
 
         // 有 offset 和 limit
-        let result = apply_pagination(records.clone(), Some(2), Some(3));
+        let result = apply_pagination(records.clone(), Some(2), Some(3), false);
         assert_eq!(result.len(), 3);
     }
+
+    #[test]
+    fn test_apply_pagination_newest_first() {
+        let records: Vec<HistoryRecord> = (0..10)
+            .map(|i| {
+                HistoryRecord::new(
+                    format!("2024-01-01T00:{:02}:00", i),
+                    "Tag1".to_string(),
+                    i as f64,
+                    "Good".to_string(),
+                )
+            })
+            .collect();
+
+        // 第一页是时间最大的那批记录（降序）
+        let first = apply_pagination(records.clone(), Some(0), Some(3), true);
+        let values: Vec<f64> = first.iter().map(|r| r.tag_val).collect();
+        assert_eq!(values, [9.0, 8.0, 7.0]);
+
+        let second = apply_pagination(records, Some(3), Some(3), true);
+        let values: Vec<f64> = second.iter().map(|r| r.tag_val).collect();
+        assert_eq!(values, [6.0, 5.0, 4.0]);
+    }
+
+    #[test]
+    fn test_cap_result_rows_truncates_and_marks() {
+        let records: Vec<HistoryRecord> = (0..10)
+            .map(|i| {
+                HistoryRecord::new(
+                    format!("2024-01-01T00:{:02}:00", i),
+                    "Tag1".to_string(),
+                    i as f64,
+                    "Good".to_string(),
+                )
+            })
+            .collect();
+
+        let (capped, truncated) = cap_result_rows(records.clone(), Some(4));
+        assert!(truncated);
+        assert_eq!(capped.len(), 4);
+        assert_eq!(capped[0].date_time, records[0].date_time);
+
+        let (capped, truncated) = cap_result_rows(records.clone(), Some(10));
+        assert!(!truncated);
+        assert_eq!(capped.len(), 10);
+
+        let (_, truncated) = cap_result_rows(records, None);
+        assert!(!truncated);
+    }
+
+    #[test]
+    fn test_stage_clock_sum_matches_total() {
+        let mut clock = StageClock::start();
+        std::thread::sleep(std::time::Duration::from_millis(15));
+        let first = clock.lap_ms();
+        std::thread::sleep(std::time::Duration::from_millis(25));
+        let second = clock.lap_ms();
+
+        let total = clock.total_ms();
+        assert!(first >= 15 && second >= 25);
+        assert!(total.abs_diff(first + second) <= 2);
+    }
+
+    #[tokio::test]
+    async fn test_next_page_served_from_first_page_cache() {
+        let cache = QueryCache::with_defaults();
+        let first_page = QueryParams::new(
+            "2024-01-01T00:00:00".to_string(),
+            "2024-01-02T00:00:00".to_string(),
+        )
+        .with_tags(vec!["Tag1".to_string()])
+        .with_pagination(0, 4);
+        let next_page = first_page.clone().with_pagination(4, 4);
+
+        // 第一页查询后缓存的是整个时间范围的结果
+        let records: Vec<HistoryRecord> = (0..10)
+            .map(|i| {
+                HistoryRecord::new(
+                    format!("2024-01-01T00:{:02}:00", i),
+                    "Tag1".to_string(),
+                    i as f64,
+                    "Good".to_string(),
+                )
+            })
+            .collect();
+        cache
+            .put(CacheKey::for_query("History", &first_page, None), records)
+            .await;
+
+        let key = CacheKey::for_query("History", &next_page, None);
+        let cached = cache.get(&key).await.expect("下一页应命中缓存");
+        let page = apply_pagination(cached, next_page.offset, next_page.limit, false);
+        let values: Vec<f64> = page.iter().map(|r| r.tag_val).collect();
+        assert_eq!(values, [4.0, 5.0, 6.0, 7.0]);
+    }
+
+    #[tokio::test]
+    async fn test_lookup_cached_v2_timing() {
+        let cache = QueryCache::with_defaults();
+        let params = QueryParams::new(
+            "2024-01-01T00:00:00".to_string(),
+            "2024-01-02T00:00:00".to_string(),
+        );
+        let key = CacheKey::new("History", &params.start_time, &params.end_time, None, None);
+        let records: Vec<HistoryRecord> = (0..10)
+            .map(|i| {
+                HistoryRecord::new(
+                    format!("2024-01-01T00:{:02}:00", i),
+                    "Tag1".to_string(),
+                    i as f64,
+                    "Good".to_string(),
+                )
+            })
+            .collect();
+
+        let mut clock = StageClock::start();
+        assert!(
+            lookup_cached_v2(&cache, &key, &params, &mut clock)
+                .await
+                .is_none()
+        );
+
+        cache.put(key.clone(), records).await;
+        let mut clock = StageClock::start();
+        let result = lookup_cached_v2(&cache, &key, &params, &mut clock)
+            .await
+            .unwrap();
+
+        assert!(result.cache_hit);
+        assert_eq!(result.timing.db_ms, 0);
+        assert_eq!(result.timing.process_ms, 0);
+        assert!(result.query_time_ms.abs_diff(result.timing.total_ms()) <= 2);
+        assert!(result.warnings.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_lookup_cached_v2_keeps_warnings() {
+        let cache = QueryCache::with_defaults();
+        let params = QueryParams::new(
+            "2024-01-01T00:00:00".to_string(),
+            "2024-01-02T00:00:00".to_string(),
+        );
+        let key = CacheKey::new("History", &params.start_time, &params.end_time, None, None);
+        let records = vec![HistoryRecord::good("2024-01-01T00:00:00", "Tag1", 1.0)];
+        let warning = "标签 Tag1 采样不均匀".to_string();
+        cache
+            .put_with_warnings(key.clone(), records, vec![warning.clone()])
+            .await;
+
+        let mut clock = StageClock::start();
+        let result = lookup_cached_v2(&cache, &key, &params, &mut clock)
+            .await
+            .unwrap();
+        assert!(result.cache_hit);
+        assert_eq!(result.warnings, vec![warning]);
+    }
+
+    #[tokio::test]
+    async fn test_content_hash_stable_across_cache_hits() {
+        let cache = QueryCache::with_defaults();
+        let params = QueryParams::new(
+            "2024-01-01T00:00:00".to_string(),
+            "2024-01-02T00:00:00".to_string(),
+        );
+        let key = CacheKey::new("History", &params.start_time, &params.end_time, None, None);
+        let records: Vec<HistoryRecord> = (0..10)
+            .map(|i| {
+                HistoryRecord::new(
+                    format!("2024-01-01T00:{:02}:00", i),
+                    "Tag1".to_string(),
+                    i as f64,
+                    "Good".to_string(),
+                )
+            })
+            .collect();
+        cache.put(key.clone(), records.clone()).await;
+
+        let mut clock = StageClock::start();
+        let first = lookup_cached_v2(&cache, &key, &params, &mut clock)
+            .await
+            .unwrap();
+        let second = lookup_cached_v2(&cache, &key, &params, &mut clock)
+            .await
+            .unwrap();
+        assert_eq!(first.content_hash.len(), 16);
+        assert_eq!(first.content_hash, second.content_hash);
+
+        // 数据变化后指纹不同
+        let mut changed = records;
+        changed[3].tag_val = 3.5;
+        cache.put(key.clone(), changed).await;
+        let third = lookup_cached_v2(&cache, &key, &params, &mut clock)
+            .await
+            .unwrap();
+        assert_ne!(first.content_hash, third.content_hash);
+    }
+
+    #[tokio::test]
+    async fn test_no_cache_tags_always_hit_database() {
+        use std::sync::Mutex;
+
+        let cache = QueryCache::with_defaults();
+        let fetched: Mutex<Vec<Vec<String>>> = Mutex::new(Vec::new());
+        let fetch = |params: QueryParams| {
+            let tags = params.tags.clone().unwrap_or_default();
+            fetched.lock().unwrap().push(tags.clone());
+            async move {
+                let records = tags
+                    .iter()
+                    .map(|tag| {
+                        HistoryRecord::new(
+                            "2024-01-01T00:00:00".to_string(),
+                            tag.clone(),
+                            1.0,
+                            "Good".to_string(),
+                        )
+                    })
+                    .collect();
+                Ok((records, false))
+            }
+        };
+
+        let params = QueryParams::new("2024-01-01".to_string(), "2024-01-02".to_string())
+            .with_tags(vec!["Output".to_string(), "TT_101".to_string()])
+            .with_no_cache_tags(vec!["Output".to_string()]);
+
+        for round in 0..3 {
+            let mut clock = StageClock::start();
+            let loaded =
+                load_processed_records(&cache, "History", &params, None, false, &mut clock, fetch)
+                    .await
+                    .unwrap();
+            assert_eq!(loaded.records.len(), 2, "第 {} 轮", round);
+            assert!(!loaded.cache_hit);
+        }
+
+        // 普通标签只在第一轮查库，no_cache 标签每轮都查库
+        let fetched = fetched.into_inner().unwrap();
+        let normal = fetched.iter().filter(|t| *t == &["TT_101"]).count();
+        let live = fetched.iter().filter(|t| *t == &["Output"]).count();
+        assert_eq!((normal, live), (1, 3));
+
+        // no_cache 标签不写入缓存
+        let live_key = CacheKey::for_query(
+            "History",
+            &params.clone().with_tags(vec!["Output".to_string()]),
+            None,
+        );
+        assert!(cache.get(&live_key).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_uneven_sampling_warning_survives_cache_hit() {
+        let cache = QueryCache::with_defaults();
+        // 前 5 分钟每秒一个点，之后每小时一个点
+        let fetch = |_params: QueryParams| async {
+            let base =
+                chrono::NaiveDateTime::parse_from_str("2024-01-01T00:00:00", "%Y-%m-%dT%H:%M:%S")
+                    .unwrap();
+            let offsets = (0..300).chain((1..6).map(|h| h * 3600));
+            let records = offsets
+                .map(|secs| {
+                    HistoryRecord::new(
+                        (base + chrono::TimeDelta::seconds(secs))
+                            .format("%Y-%m-%dT%H:%M:%S")
+                            .to_string(),
+                        "TT_101".to_string(),
+                        1.0,
+                        "Good".to_string(),
+                    )
+                })
+                .collect();
+            Ok((records, false))
+        };
+        let params = QueryParams::new(
+            "2024-01-01T00:00:00".to_string(),
+            "2024-01-01T06:00:00".to_string(),
+        )
+        .with_tags(vec!["TT_101".to_string()]);
+        let config = DataProcessingConfig::default().with_resample(600, "mean");
+
+        for expect_hit in [false, true] {
+            let mut clock = StageClock::start();
+            let loaded = load_processed_records(
+                &cache,
+                "History",
+                &params,
+                Some(&config),
+                false,
+                &mut clock,
+                fetch,
+            )
+            .await
+            .unwrap();
+            assert_eq!(loaded.cache_hit, expect_hit);
+            assert_eq!(loaded.warnings.len(), 1);
+            assert!(loaded.warnings[0].contains("TT_101"));
+        }
+    }
 }
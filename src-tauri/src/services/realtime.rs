@@ -0,0 +1,238 @@
+//! 实时数据订阅（轮询式）
+//!
+//! 每个订阅对应一个 tokio 定时任务：按间隔查询最近一段时间的数据，
+//! 过滤掉已推送过的点后将增量推送给前端。
+
+use chrono::{Local, NaiveDateTime, TimeDelta};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use crate::error::AppResult;
+use crate::models::HistoryRecord;
+
+/// 实时数据推送事件名
+pub const REALTIME_EVENT: &str = "realtime-data";
+
+/// 查询时间格式
+const QUERY_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// 一次实时推送的增量数据
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RealtimeUpdate {
+    /// 订阅 ID
+    pub subscription_id: String,
+    /// 上次推送之后的新数据点（按时间升序）
+    pub records: Vec<HistoryRecord>,
+}
+
+/// 实时订阅管理
+///
+/// 订阅在退订或管理器销毁时停止。
+#[derive(Default)]
+pub struct RealtimeSubscriptions {
+    next_id: AtomicU64,
+    tasks: Mutex<HashMap<String, JoinHandle<()>>>,
+}
+
+impl RealtimeSubscriptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 启动一个订阅，返回订阅 ID
+    ///
+    /// 每隔 `interval` 调用 `poll(start, end)` 查询 `[上次查询结束 - interval, 现在]`
+    /// 的数据（多查一个间隔，兼容迟到入库的点），每个标签只推送比已推送最新点更晚的数据，
+    /// 有增量时调用 `emit`。查询失败只记录日志，下个间隔继续。
+    pub fn subscribe<P, Fut, E>(&self, interval: Duration, poll: P, emit: E) -> String
+    where
+        P: Fn(String, String) -> Fut + Send + 'static,
+        Fut: Future<Output = AppResult<Vec<HistoryRecord>>> + Send + 'static,
+        E: Fn(RealtimeUpdate) + Send + 'static,
+    {
+        let id = format!("rt{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        let subscription_id = id.clone();
+        let lookback = TimeDelta::from_std(interval).unwrap_or(TimeDelta::seconds(1));
+
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            let mut since = Local::now().naive_local() - lookback;
+            let mut last_seen: HashMap<String, NaiveDateTime> = HashMap::new();
+
+            loop {
+                ticker.tick().await;
+                let now = Local::now().naive_local();
+                let start = since.format(QUERY_TIME_FORMAT).to_string();
+                let end = now.format(QUERY_TIME_FORMAT).to_string();
+
+                match poll(start, end).await {
+                    Ok(records) => {
+                        let records = take_new_records(records, &mut last_seen);
+                        if !records.is_empty() {
+                            debug!(target: "industry_vis::realtime",
+                                "实时推送 - 订阅: {}, 新数据: {}", subscription_id, records.len()
+                            );
+                            emit(RealtimeUpdate {
+                                subscription_id: subscription_id.clone(),
+                                records,
+                            });
+                        }
+                    }
+                    Err(e) => {
+                        warn!(target: "industry_vis::realtime",
+                            "实时轮询失败 - 订阅: {}, 错误: {}", subscription_id, e
+                        );
+                    }
+                }
+                since = now - lookback;
+            }
+        });
+
+        self.tasks.lock().insert(id.clone(), task);
+        id
+    }
+
+    /// 停止订阅，订阅不存在时返回 false
+    pub fn unsubscribe(&self, subscription_id: &str) -> bool {
+        match self.tasks.lock().remove(subscription_id) {
+            Some(task) => {
+                task.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 当前订阅数
+    pub fn len(&self) -> usize {
+        self.tasks.lock().len()
+    }
+
+    /// 是否没有订阅
+    pub fn is_empty(&self) -> bool {
+        self.tasks.lock().is_empty()
+    }
+}
+
+impl Drop for RealtimeSubscriptions {
+    fn drop(&mut self) {
+        for (_, task) in self.tasks.lock().drain() {
+            task.abort();
+        }
+    }
+}
+
+/// 过滤出每个标签比已推送最新点更晚的记录，并更新已推送位置
+fn take_new_records(
+    mut records: Vec<HistoryRecord>,
+    last_seen: &mut HashMap<String, NaiveDateTime>,
+) -> Vec<HistoryRecord> {
+    let parse = |r: &HistoryRecord| {
+        NaiveDateTime::parse_from_str(&r.date_time, "%Y-%m-%dT%H:%M:%S%.f").ok()
+    };
+    records.sort_by_key(parse);
+
+    let mut fresh = Vec::new();
+    for record in records {
+        let Some(time) = parse(&record) else {
+            continue;
+        };
+        if last_seen
+            .get(record.tag_name.as_str())
+            .is_none_or(|seen| time > *seen)
+        {
+            last_seen.insert(record.tag_name.to_string(), time);
+            fresh.push(record);
+        }
+    }
+    fresh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicU32;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn test_take_new_records_skips_pushed_points() {
+        let mut last_seen = HashMap::new();
+        let first = take_new_records(
+            vec![
+                HistoryRecord::good("2024-01-01T00:00:10", "T1", 2.0),
+                HistoryRecord::good("2024-01-01T00:00:00", "T1", 1.0),
+            ],
+            &mut last_seen,
+        );
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0].tag_val, 1.0);
+
+        // 查询窗口重叠，只推送更晚的点
+        let second = take_new_records(
+            vec![
+                HistoryRecord::good("2024-01-01T00:00:10", "T1", 2.0),
+                HistoryRecord::good("2024-01-01T00:00:20", "T1", 3.0),
+                HistoryRecord::good("2024-01-01T00:00:05", "T2", 9.0),
+            ],
+            &mut last_seen,
+        );
+        let values: Vec<f64> = second.iter().map(|r| r.tag_val).collect();
+        assert_eq!(values, [9.0, 3.0]);
+    }
+
+    #[tokio::test]
+    async fn test_subscription_pushes_until_unsubscribed() {
+        let subscriptions = RealtimeSubscriptions::new();
+        let polls = Arc::new(AtomicU32::new(0));
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        // 每次轮询返回一个新时刻的点
+        let counter = Arc::clone(&polls);
+        let id = subscriptions.subscribe(
+            Duration::from_millis(20),
+            move |_start, _end| {
+                let n = counter.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    Ok(vec![HistoryRecord::good(
+                        &format!("2024-01-01T00:00:{:02}", n),
+                        "T1",
+                        n as f64,
+                    )])
+                }
+            },
+            move |update| {
+                let _ = tx.send(update);
+            },
+        );
+        assert_eq!(subscriptions.len(), 1);
+
+        for expected in 0..3 {
+            let update = tokio::time::timeout(Duration::from_secs(2), rx.recv())
+                .await
+                .expect("应按间隔推送")
+                .unwrap();
+            assert_eq!(update.subscription_id, id);
+            assert_eq!(update.records.len(), 1);
+            assert_eq!(update.records[0].tag_val, expected as f64);
+        }
+
+        assert!(subscriptions.unsubscribe(&id));
+        assert!(!subscriptions.unsubscribe(&id));
+        assert!(subscriptions.is_empty());
+
+        // 退订后不再轮询
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        let polled = polls.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(polls.load(Ordering::SeqCst), polled);
+    }
+}
@@ -1,12 +1,16 @@
 //! 标签分组服务
 
 use parking_lot::RwLock;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
-use tracing::info;
+use tracing::{info, warn};
 
-use crate::config::TagGroupConfigManager;
-use crate::error::AppResult;
-use crate::models::{ChartConfig, DataProcessingConfig, TagGroup};
+use crate::config::{GroupSnapshotItem, GroupSnapshotStore, TagGroupConfigManager};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    Annotation, BatchApplyResult, ChartConfig, DataProcessingConfig, GroupImportResult,
+    GroupUsageStats, TagGroup, TagMetadata, TagUsageSummary,
+};
 
 /// 标签分组服务
 pub struct TagGroupService {
@@ -29,6 +33,135 @@ impl TagGroupService {
         self.manager.read().get_group(id).cloned()
     }
 
+    /// 记录分组被访问（查询）
+    pub fn record_access(&self, id: &str) -> AppResult<()> {
+        self.manager.write().record_access(id)
+    }
+
+    /// 保存尚未写盘的分组使用统计
+    pub fn flush_usage(&self) -> AppResult<()> {
+        self.manager.write().flush_usage()
+    }
+
+    /// 在修改分组的操作成功后保存该分组修改前的快照
+    ///
+    /// 快照文件的读取—追加—写回在分组写锁内完成，并发修改不会互相覆盖快照。
+    /// 快照保存失败只记录日志，不影响操作结果。
+    fn with_snapshot<T>(
+        &self,
+        id: &str,
+        action: &str,
+        op: impl FnOnce(&mut TagGroupConfigManager) -> AppResult<T>,
+    ) -> AppResult<T> {
+        let mut manager = self.manager.write();
+        let before = manager.get_group(id).cloned();
+        let result = op(&mut manager)?;
+        if let Some(before) = before
+            && let Err(e) = GroupSnapshotStore::load().and_then(|mut s| s.record(&before, action))
+        {
+            warn!(target: "industry_vis::tag_group_service",
+                "保存分组快照失败 - ID: {}, 错误: {}", id, e
+            );
+        }
+        Ok(result)
+    }
+
+    /// 获取分组的快照列表（最新在前）
+    pub fn list_snapshots(&self, group_id: &str) -> AppResult<Vec<GroupSnapshotItem>> {
+        // 持有读锁，避免读到正在写回的快照文件
+        let _manager = self.manager.read();
+        Ok(GroupSnapshotStore::load()?.list(group_id))
+    }
+
+    /// 从快照恢复分组（恢复前同样保存当前状态的快照，便于撤销恢复）
+    pub fn restore_snapshot(&self, snapshot_id: &str) -> AppResult<TagGroup> {
+        let store = {
+            let _manager = self.manager.read();
+            GroupSnapshotStore::load()?
+        };
+        let snapshot = store
+            .get(snapshot_id)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("分组快照 '{}' 不存在", snapshot_id)))?;
+        info!(target: "industry_vis::tag_group_service",
+            "恢复分组快照 - 快照: {}, 分组: {}", snapshot_id, snapshot.group.id
+        );
+        let group_id = snapshot.group.id.clone();
+        self.with_snapshot(&group_id, "restore", |manager| {
+            manager.restore_group(snapshot.group)
+        })
+    }
+
+    /// 获取分组使用统计（按访问次数降序）
+    pub fn usage_stats(&self) -> Vec<GroupUsageStats> {
+        self.manager.read().usage_stats()
+    }
+
+    /// 所有分组的标签引用汇总
+    pub fn tag_usage(&self) -> TagUsageSummary {
+        TagUsageSummary::from_groups(self.manager.read().list_groups())
+    }
+
+    /// 批量应用处理配置到多个分组（锁定的分组跳过）
+    pub fn apply_processing_to_groups(
+        &self,
+        group_ids: &[String],
+        config: &DataProcessingConfig,
+    ) -> AppResult<BatchApplyResult> {
+        info!(target: "industry_vis::tag_group_service",
+            "批量应用处理配置 - 分组数: {}", group_ids.len()
+        );
+        self.manager
+            .write()
+            .apply_processing_to_groups(group_ids, config)
+    }
+
+    /// 设置分组锁定状态
+    pub fn set_group_locked(&self, id: &str, locked: bool) -> AppResult<TagGroup> {
+        info!(target: "industry_vis::tag_group_service",
+            "设置分组锁定 - ID: {}, 锁定: {}", id, locked
+        );
+        self.manager.write().set_group_locked(id, locked)
+    }
+
+    /// 设置标签元数据
+    pub fn set_tag_metadata(&self, id: &str, metadata: Vec<TagMetadata>) -> AppResult<TagGroup> {
+        info!(target: "industry_vis::tag_group_service",
+            "设置标签元数据 - ID: {}, 标签数: {}", id, metadata.len()
+        );
+        self.manager.write().set_tag_metadata(id, metadata)
+    }
+
+    /// 新增标注区间
+    pub fn add_annotation(&self, group_id: &str, annotation: Annotation) -> AppResult<Annotation> {
+        info!(target: "industry_vis::tag_group_service",
+            "新增标注 - 分组: {}, 区间: {} ~ {}", group_id, annotation.start, annotation.end
+        );
+        self.manager.write().add_annotation(group_id, annotation)
+    }
+
+    /// 更新标注区间
+    pub fn update_annotation(
+        &self,
+        group_id: &str,
+        annotation: Annotation,
+    ) -> AppResult<Annotation> {
+        info!(target: "industry_vis::tag_group_service",
+            "更新标注 - 分组: {}, 标注: {}", group_id, annotation.id
+        );
+        self.manager.write().update_annotation(group_id, annotation)
+    }
+
+    /// 删除标注区间
+    pub fn remove_annotation(&self, group_id: &str, annotation_id: &str) -> AppResult<()> {
+        info!(target: "industry_vis::tag_group_service",
+            "删除标注 - 分组: {}, 标注: {}", group_id, annotation_id
+        );
+        self.manager
+            .write()
+            .remove_annotation(group_id, annotation_id)
+    }
+
     /// 创建分组
     pub fn create_group(&self, name: String, charts: Vec<ChartConfig>) -> AppResult<TagGroup> {
         info!(target: "industry_vis::tag_group_service",
@@ -37,6 +170,36 @@ impl TagGroupService {
         self.manager.write().create_group(name, charts)
     }
 
+    /// 导入完整分组
+    pub fn import_group(&self, group: TagGroup) -> AppResult<TagGroup> {
+        info!(target: "industry_vis::tag_group_service",
+            "导入分组 - 名称: {}, 图表数: {}", group.name, group.charts.len()
+        );
+        self.manager.write().import_group(group)
+    }
+
+    /// 批量导入分组，按映射表（旧标签名 → 本库标签名）替换标签
+    pub fn import_groups(
+        &self,
+        mut groups: Vec<TagGroup>,
+        tag_mapping: &HashMap<String, String>,
+    ) -> AppResult<GroupImportResult> {
+        let mut unmapped = BTreeSet::new();
+        for group in &mut groups {
+            unmapped.extend(group.remap_tags(tag_mapping));
+        }
+        info!(target: "industry_vis::tag_group_service",
+            "批量导入分组 - 分组数: {}, 映射数: {}, 未映射标签: {}",
+            groups.len(), tag_mapping.len(), unmapped.len()
+        );
+
+        let groups = self.manager.write().import_groups(groups)?;
+        Ok(GroupImportResult {
+            groups,
+            unmapped_tags: unmapped.into_iter().collect(),
+        })
+    }
+
     /// 更新分组
     pub fn update_group(
         &self,
@@ -48,15 +211,35 @@ impl TagGroupService {
         info!(target: "industry_vis::tag_group_service",
             "更新分组 - ID: {}, 名称: {}, 图表数: {}", id, name, charts.len()
         );
-        self.manager
-            .write()
-            .update_group(id, name, charts, processing_config)
+        self.with_snapshot(id, "update", |manager| {
+            manager.update_group(id, name, charts, processing_config)
+        })
+    }
+
+    /// 部分更新分组
+    pub fn patch_group(
+        &self,
+        id: &str,
+        name: Option<String>,
+        charts: Option<Vec<ChartConfig>>,
+        processing_config: Option<DataProcessingConfig>,
+    ) -> AppResult<TagGroup> {
+        info!(target: "industry_vis::tag_group_service",
+            "部分更新分组 - ID: {}, 名称: {:?}, 图表: {}, 处理配置: {}",
+            id,
+            name,
+            charts.is_some(),
+            processing_config.is_some()
+        );
+        self.with_snapshot(id, "patch", |manager| {
+            manager.patch_group(id, name, charts, processing_config)
+        })
     }
 
     /// 删除分组
     pub fn delete_group(&self, id: &str) -> AppResult<()> {
         info!(target: "industry_vis::tag_group_service", "删除分组 - ID: {}", id);
-        self.manager.write().delete_group(id)
+        self.with_snapshot(id, "delete", |manager| manager.delete_group(id))
     }
 }
 
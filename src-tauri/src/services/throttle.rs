@@ -0,0 +1,201 @@
+//! 查询限流
+//!
+//! 限制同时打到数据库的查询数，避免生产高峰时段的大查询影响其他系统（如 MES）。
+//! 高峰时段可配置更低的并发限额；超限的查询排队等待，等待超时返回"请稍后"。
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{Local, Timelike};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::{debug, warn};
+
+use crate::config::ThrottlePerformanceConfig;
+use crate::error::{AppError, AppResult};
+
+/// 全局查询限流器
+///
+/// 所有查询都占用总并发信号量（`max_concurrent`）；高峰时段的查询先获取高峰信号量
+/// （`peak_max_concurrent`），再获取总并发许可。按固定顺序获取，跨越高峰边界时
+/// 之前开始的查询仍计入总并发，同时在库的查询数不会超过 `max_concurrent`。
+/// 许可在返回的 guard 释放时归还。
+pub struct QueryThrottle {
+    config: ThrottlePerformanceConfig,
+    normal: Arc<Semaphore>,
+    peak: Arc<Semaphore>,
+}
+
+impl QueryThrottle {
+    /// 根据配置创建限流器
+    pub fn new(config: ThrottlePerformanceConfig) -> Self {
+        Self {
+            normal: Arc::new(Semaphore::new(config.max_concurrent.max(1))),
+            peak: Arc::new(Semaphore::new(config.peak_max_concurrent.max(1))),
+            config,
+        }
+    }
+
+    /// 是否启用限流
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// 获取一个查询许可（未启用时直接返回 `None`）
+    ///
+    /// 超过并发限额时排队；排队超过 `queue_timeout_secs` 返回 `AppError::Throttled`。
+    pub async fn acquire(&self) -> AppResult<Option<ThrottlePermit>> {
+        self.acquire_at_hour(Local::now().hour()).await
+    }
+
+    /// 按指定小时选择限额并获取许可
+    async fn acquire_at_hour(&self, hour: u32) -> AppResult<Option<ThrottlePermit>> {
+        if !self.config.enabled {
+            return Ok(None);
+        }
+
+        let peak = self.config.is_peak_hour(hour);
+        if self.normal.available_permits() == 0 || (peak && self.peak.available_permits() == 0) {
+            debug!(target: "industry_vis::throttle", "查询并发已达上限，排队等待");
+        }
+
+        let acquire = async {
+            let peak = if peak {
+                Some(Arc::clone(&self.peak).acquire_owned().await?)
+            } else {
+                None
+            };
+            let normal = Arc::clone(&self.normal).acquire_owned().await?;
+            Ok::<_, tokio::sync::AcquireError>(ThrottlePermit {
+                _peak: peak,
+                _normal: normal,
+            })
+        };
+        let permit = match self.config.queue_timeout_secs {
+            0 => acquire.await,
+            secs => tokio::time::timeout(Duration::from_secs(secs), acquire)
+                .await
+                .map_err(|_| {
+                    warn!(target: "industry_vis::throttle", "查询排队超时（{} 秒）", secs);
+                    AppError::Throttled(format!("数据库查询繁忙，请稍后再试（已排队 {} 秒）", secs))
+                })?,
+        }
+        .map_err(|e| AppError::Internal(format!("查询限流器已关闭: {}", e)))?;
+
+        Ok(Some(permit))
+    }
+}
+
+/// 查询许可，释放时归还占用的信号量
+pub struct ThrottlePermit {
+    _peak: Option<OwnedSemaphorePermit>,
+    _normal: OwnedSemaphorePermit,
+}
+
+impl Default for QueryThrottle {
+    fn default() -> Self {
+        Self::new(ThrottlePerformanceConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn config(max_concurrent: usize, peak_max_concurrent: usize) -> ThrottlePerformanceConfig {
+        ThrottlePerformanceConfig {
+            enabled: true,
+            max_concurrent,
+            peak_max_concurrent,
+            peak_start_hour: Some(8),
+            peak_end_hour: Some(18),
+            queue_timeout_secs: 0,
+        }
+    }
+
+    /// 并发执行 `tasks` 个模拟查询，返回同时在"打库"的最大数量
+    async fn max_in_flight(throttle: Arc<QueryThrottle>, hour: u32, tasks: usize) -> usize {
+        let current = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..tasks)
+            .map(|_| {
+                let (throttle, current, peak) = (
+                    Arc::clone(&throttle),
+                    Arc::clone(&current),
+                    Arc::clone(&peak),
+                );
+                tokio::spawn(async move {
+                    let _permit = throttle.acquire_at_hour(hour).await.unwrap();
+                    let now = current.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    current.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        peak.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_excess_queries_are_queued() {
+        let throttle = Arc::new(QueryThrottle::new(config(2, 1)));
+
+        // 非高峰：最多 2 个同时执行，其余排队后全部完成
+        assert_eq!(max_in_flight(Arc::clone(&throttle), 20, 6).await, 2);
+        // 高峰时段：限额降为 1
+        assert_eq!(max_in_flight(throttle, 9, 4).await, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_disabled_throttle_does_not_limit() {
+        let throttle = Arc::new(QueryThrottle::default());
+        assert!(!throttle.is_enabled());
+        assert_eq!(max_in_flight(throttle, 9, 4).await, 4);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_queue_timeout_returns_throttled() {
+        let throttle = QueryThrottle::new(ThrottlePerformanceConfig {
+            queue_timeout_secs: 1,
+            ..config(1, 1)
+        });
+        let _held = throttle.acquire_at_hour(20).await.unwrap();
+
+        let err = throttle.acquire_at_hour(20).await.unwrap_err();
+        assert!(matches!(err, AppError::Throttled(_)));
+        assert!(err.is_retryable());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_peak_boundary_keeps_total_limit() {
+        let throttle = QueryThrottle::new(ThrottlePerformanceConfig {
+            queue_timeout_secs: 1,
+            ..config(2, 1)
+        });
+
+        // 高峰开始前已占满总并发，高峰时段的新查询仍需排队
+        let first = throttle.acquire_at_hour(7).await.unwrap();
+        let second = throttle.acquire_at_hour(7).await.unwrap();
+        assert!(matches!(
+            throttle.acquire_at_hour(8).await,
+            Err(AppError::Throttled(_))
+        ));
+
+        // 释放一个后高峰查询可执行，但高峰限额为 1
+        drop(first);
+        let peak = throttle.acquire_at_hour(9).await.unwrap();
+        drop(second);
+        assert!(matches!(
+            throttle.acquire_at_hour(9).await,
+            Err(AppError::Throttled(_))
+        ));
+        // 高峰结束后的查询只受总并发限制
+        let _after = throttle.acquire_at_hour(18).await.unwrap();
+        drop(peak);
+    }
+}
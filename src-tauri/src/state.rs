@@ -1,21 +1,40 @@
 //! 应用状态管理
 //!
-//! 统一管理应用的共享状态。
+//! 统一管理应用的共享状态；`AppStateManager` 按窗口持有相互隔离的多个实例。
 
+use chrono::Local;
 use parking_lot::RwLock;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Instant;
+use tauri::ipc::{CommandArg, CommandItem, InvokeError};
+use tauri::{Emitter, Manager};
+use tracing::{info, warn};
 
 use crate::cache::{
     CacheConfig, CacheWarmer, QueryCache, RecentTimeRangeStrategy, SharedCache, WarmupStrategy,
+    WarmupTask, default_snapshot_path,
 };
-use crate::config::ConfigState;
+use crate::config::{AppConfig, ConfigState};
 use crate::datasource::{
-    ConnectionPool, DataSource, PoolConfig, ProfileRegistry, SchemaProfile, SqlServerSource,
+    ConnectionPool, DataSource, FederatedSource, PoolConfig, ProfileRegistry, SchemaProfile,
+    SqlServerSource, TagTableCandidate,
+};
+use crate::error::{AppError, AppResult, ErrorInfo};
+use crate::export::{self, BundleFormat, CsvEncoding, ScheduledExportStore};
+use crate::models::{
+    DataProcessingConfig, HistoryRecord, InternedStr, QueryEstimate, QueryParams, QueryResult,
+    QueryResultV2, QueryTiming, TagGroup, series_content_hash,
 };
-use crate::error::AppResult;
-use crate::models::{DataProcessingConfig, QueryParams, QueryResult, QueryResultV2};
 use crate::processing;
-use crate::services::{QueryService, TagGroupService};
+use crate::services::{
+    CONNECTION_HEALTH_EVENT, CircuitBreaker, CircuitBreakerStatus, ConnectionHealthStatus,
+    ConnectionMonitor, ConnectionProbe, EXPORT_PROGRESS_EVENT, ExportScheduler, ExportTaskInfo,
+    ExportTaskQueue, GroupAuditCache, GroupAuditReport, LoadedRecords, MetricsSnapshot,
+    QueryCounters, QueryRateTracker, QueryService, QueryThrottle, RealtimeSubscriptions,
+    ScheduledExportRunner, SeriesChunkStore, StageClock, TagGroupService, cap_result_rows,
+    diagnose_empty_result, format_output_records, load_processed_records,
+};
 
 /// 应用状态
 pub struct AppState {
@@ -25,10 +44,34 @@ pub struct AppState {
     cache: SharedCache,
     /// 连接池
     pool: Option<Arc<ConnectionPool>>,
+    /// 归档库连接池（配置了归档库时）
+    archive_pool: Option<Arc<ConnectionPool>>,
     /// 查询服务
     query_service: RwLock<Option<QueryService>>,
     /// 标签分组服务
     tag_group_service: TagGroupService,
+    /// 全局查询限流器
+    throttle: Arc<QueryThrottle>,
+    /// 数据库连接断路器
+    circuit_breaker: Arc<CircuitBreaker>,
+    /// 数据库查询速率统计（用于耗时预估）
+    rate_tracker: Arc<QueryRateTracker>,
+    /// 数据库查询计数（指标导出）
+    query_counters: Arc<QueryCounters>,
+    /// V2 查询分片结果暂存
+    chunk_store: SeriesChunkStore,
+    /// 实时数据订阅
+    realtime: RealtimeSubscriptions,
+    /// 后台导出任务队列
+    export_tasks: ExportTaskQueue,
+    /// 定时导出调度
+    export_scheduler: ExportScheduler,
+    /// 数据库连通性监控
+    connection_monitor: ConnectionMonitor,
+    /// 分组健康检查结果缓存
+    group_audit_cache: GroupAuditCache,
+    /// 最近一次连接池初始化失败的原因（成功后清空）
+    last_pool_error: RwLock<Option<ErrorInfo>>,
 }
 
 impl AppState {
@@ -36,42 +79,147 @@ impl AppState {
     pub async fn new() -> AppResult<Self> {
         // 加载配置（带热更新）
         let config = ConfigState::with_hot_reload()?;
+        let mut state = Self::with_config(config);
+
+        // 恢复定时导出任务（仅主实例持久化）
+        match ScheduledExportStore::load() {
+            Ok(store) => state.export_scheduler = ExportScheduler::with_store(store),
+            Err(e) => warn!(target: "industry_vis::state", "加载定时导出任务失败: {}", e),
+        }
+
+        // 恢复持久化缓存（数据格式版本不符时丢弃）
+        if state.config.app_config().performance.cache.persist_enabled
+            && let Some(path) = default_snapshot_path()
+        {
+            match state.cache.load_snapshot(&path).await {
+                Ok(count) => info!(target: "industry_vis::state", "恢复持久化缓存 {} 条", count),
+                Err(e) => warn!(target: "industry_vis::state", "恢复持久化缓存失败: {}", e),
+            }
+        }
 
-        // 创建缓存
-        let cache = Arc::new(QueryCache::new(CacheConfig::default()));
+        Ok(state)
+    }
 
-        // 启动缓存自动清理
-        let cache_clone = Arc::clone(&cache);
+    /// 使用给定配置创建应用状态（独立的缓存、限流器和任务队列，连接池需另行初始化）
+    ///
+    /// 定时导出调度器不持久化；定时导出和连通性监控需在包装为 `SharedAppState` 后
+    /// 调用 `start_background_tasks` 启动。
+    pub fn with_config(config: ConfigState) -> Self {
+        // 创建缓存（容量、过期时间和滑动过期取自性能配置）
+        let cache_config = config.app_config().performance.cache;
+        let cache = Arc::new(QueryCache::new(
+            CacheConfig::new(cache_config.max_entries, cache_config.ttl_seconds)
+                .with_sliding_expiration(cache_config.sliding_expiration),
+        ));
+
+        // 启动缓存自动清理（实例释放后停止）
+        let cache_weak = Arc::downgrade(&cache);
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
             loop {
                 interval.tick().await;
-                cache_clone.evict_expired().await;
+                let Some(cache) = cache_weak.upgrade() else {
+                    break;
+                };
+                cache.evict_expired().await;
+                // 释放过期条目后不再被引用的标签名 / 质量码
+                InternedStr::release_unused();
             }
         });
 
         // 创建标签分组服务
         let tag_group_service = TagGroupService::new(config.tag_group_manager());
 
-        Ok(Self {
+        // 创建查询限流器（限额在启动时确定）
+        let throttle = Arc::new(QueryThrottle::new(
+            config.app_config().performance.throttle.clone(),
+        ));
+        let circuit_breaker = Arc::new(CircuitBreaker::new(
+            config.app_config().performance.circuit_breaker.clone(),
+        ));
+        let connection_monitor = ConnectionMonitor::new(
+            config.app_config().performance.connection_monitor.clone(),
+            Some(Arc::new(|status: &ConnectionHealthStatus| {
+                if let Some(handle) = crate::get_app_handle()
+                    && let Err(e) = handle.emit(CONNECTION_HEALTH_EVENT, status)
+                {
+                    warn!(target: "industry_vis::state", "发送连通性状态事件失败: {}", e);
+                }
+            })),
+        );
+
+        Self {
             config,
             cache,
             pool: None,
+            archive_pool: None,
             query_service: RwLock::new(None),
             tag_group_service,
-        })
+            throttle,
+            circuit_breaker,
+            rate_tracker: Arc::new(QueryRateTracker::new()),
+            query_counters: Arc::new(QueryCounters::new()),
+            chunk_store: SeriesChunkStore::new(),
+            realtime: RealtimeSubscriptions::new(),
+            export_tasks: ExportTaskQueue::new(Some(Arc::new(|info: &ExportTaskInfo| {
+                if let Some(handle) = crate::get_app_handle()
+                    && let Err(e) = handle.emit(EXPORT_PROGRESS_EVENT, info)
+                {
+                    warn!(target: "industry_vis::state", "发送导出进度事件失败: {}", e);
+                }
+            }))),
+            export_scheduler: ExportScheduler::new(),
+            connection_monitor,
+            group_audit_cache: GroupAuditCache::new(),
+            last_pool_error: RwLock::new(None),
+        }
+    }
+
+    /// 持久化缓存到磁盘（未启用持久化时跳过）
+    pub async fn persist_cache(&self) -> AppResult<()> {
+        if !self.config.app_config().performance.cache.persist_enabled {
+            return Ok(());
+        }
+        if let Some(path) = default_snapshot_path() {
+            self.cache.save_snapshot(&path).await?;
+        }
+        Ok(())
     }
 
     /// 初始化连接池和查询服务
+    ///
+    /// 失败时记录结构化原因，可通过 `last_pool_error` 查询。
     pub async fn init_pool(&mut self) -> AppResult<()> {
         let db_config = self.config.database_config();
-        let pool = ConnectionPool::new(db_config, PoolConfig::for_desktop()).await?;
-        let pool = Arc::new(pool);
+        let pool_config = PoolConfig::from(&self.config.app_config().performance.pool);
+        let pool = match ConnectionPool::new(db_config, pool_config.clone()).await {
+            Ok(pool) => Arc::new(pool),
+            Err(e) => {
+                *self.last_pool_error.write() = Some(ErrorInfo::from(&e));
+                return Err(e);
+            }
+        };
+        *self.last_pool_error.write() = None;
+        self.circuit_breaker.record_success();
 
         let default_table = self.config.app_config().query.default_table.clone();
         let query_service =
             QueryService::new(Arc::clone(&pool), Arc::clone(&self.cache), default_table);
 
+        // 归档库连接失败不影响主库查询
+        self.archive_pool = match self.config.app_config().query.archive {
+            Some(archive) => match ConnectionPool::new(archive.database, pool_config).await {
+                Ok(pool) => Some(Arc::new(pool)),
+                Err(e) => {
+                    warn!(target: "industry_vis::state",
+                        "归档库连接失败，仅查询主库: {}", e
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+
         self.pool = Some(pool);
         *self.query_service.write() = Some(query_service);
 
@@ -94,28 +242,45 @@ impl AppState {
         let guard = self.query_service.read();
         let service = guard.as_ref()?;
 
-        // 从配置获取 Profile
+        // 从配置获取 Profile 和查询并行路数
         let profile = self.get_schema_profile();
+        let parallelism = self.config.app_config().performance.pool.query_parallelism as usize;
+        let max_result_rows = self.config.app_config().query.result_row_limit();
+        let isolation_level = self.config.app_config().query.isolation_level;
+
+        let build_source = |pool: &Arc<ConnectionPool>| {
+            SqlServerSource::from_pool_with_profile(Arc::clone(pool), Arc::clone(&profile))
+                .with_parallelism(parallelism)
+                .with_max_result_rows(max_result_rows)
+                .with_isolation_level(isolation_level)
+        };
+        let source = build_source(service.pool());
+        let history: Arc<dyn DataSource> =
+            match (&self.archive_pool, &self.config.app_config().query.archive) {
+                (Some(archive_pool), Some(archive)) => Arc::new(FederatedSource::new(
+                    Arc::new(source.clone()),
+                    Arc::new(build_source(archive_pool)),
+                    archive.primary_retention_days,
+                )),
+                _ => Arc::new(source.clone()),
+            };
 
         Some(QueryServiceHandle {
-            source: SqlServerSource::from_pool_with_profile(Arc::clone(service.pool()), profile),
+            source,
+            history,
+            max_result_rows,
             cache: Arc::clone(&self.cache),
             default_table: service.default_table().to_string(),
+            throttle: Arc::clone(&self.throttle),
+            circuit_breaker: Arc::clone(&self.circuit_breaker),
+            rate_tracker: Arc::clone(&self.rate_tracker),
+            query_counters: Arc::clone(&self.query_counters),
         })
     }
 
     /// 获取当前配置的 Schema Profile
     fn get_schema_profile(&self) -> Arc<dyn SchemaProfile> {
-        let profile_name = &self.config.app_config().schema.profile;
-        ProfileRegistry::get(profile_name).unwrap_or_else(|e| {
-            tracing::warn!(
-                target: "industry_vis::state",
-                error = %e,
-                profile = %profile_name,
-                "无法获取指定的 Profile，使用默认 Profile"
-            );
-            ProfileRegistry::default_profile()
-        })
+        ProfileRegistry::from_config(&self.config.app_config().schema)
     }
 
     /// 检查连接池是否已初始化
@@ -128,6 +293,88 @@ impl AppState {
         &self.tag_group_service
     }
 
+    /// 获取 V2 查询分片结果暂存
+    pub fn chunk_store(&self) -> &SeriesChunkStore {
+        &self.chunk_store
+    }
+
+    /// 获取实时数据订阅
+    pub fn realtime(&self) -> &RealtimeSubscriptions {
+        &self.realtime
+    }
+
+    /// 获取后台导出任务队列
+    pub fn export_tasks(&self) -> &ExportTaskQueue {
+        &self.export_tasks
+    }
+
+    /// 获取定时导出调度器
+    pub fn export_scheduler(&self) -> &ExportScheduler {
+        &self.export_scheduler
+    }
+
+    /// 获取分组健康检查结果缓存
+    pub fn group_audit_cache(&self) -> &GroupAuditCache {
+        &self.group_audit_cache
+    }
+
+    /// 检查所有分组的标签存在性和最近数据（结果缓存 5 分钟，分组变化时重新检查）
+    pub async fn audit_all_groups(
+        &self,
+        recent_hours: u64,
+        force_refresh: bool,
+    ) -> AppResult<GroupAuditReport> {
+        let groups = self.tag_group_service.list_groups();
+        let key = GroupAuditCache::key(&groups, recent_hours);
+        if !force_refresh && let Some(report) = self.group_audit_cache.get(&key) {
+            return Ok(report);
+        }
+        let service = self.query_service().ok_or(AppError::DatabaseNotConnected)?;
+        let report = service.audit_groups(&groups, recent_hours).await?;
+        self.group_audit_cache.put(key, report.clone());
+        Ok(report)
+    }
+
+    /// 数据库连通性状态
+    pub fn connection_health(&self) -> ConnectionHealthStatus {
+        self.connection_monitor.status()
+    }
+
+    /// 提交后台导出任务：查询全部数据（忽略分页、不降采样），按 `processing_config` 处理后写出
+    ///
+    /// 结果超过行数上限被截断时任务失败，不写出不完整的文件。
+    pub fn submit_export(
+        &self,
+        params: QueryParams,
+        processing_config: Option<DataProcessingConfig>,
+        file_path: String,
+        format: BundleFormat,
+        encoding: CsvEncoding,
+    ) -> AppResult<String> {
+        let service = self.query_service().ok_or(AppError::DatabaseNotConnected)?;
+
+        let path = file_path.clone();
+        Ok(self.export_tasks.submit(file_path, move |ctx| async move {
+            let result = service
+                .query_history_raw(&params, processing_config.as_ref())
+                .await?;
+            // 查询期间被取消的任务不再写文件
+            if ctx.is_cancelled() {
+                return Ok(0);
+            }
+            let records = result.into_complete_records()?;
+            ctx.set_progress(0.5);
+
+            let count = records.len();
+            tokio::task::spawn_blocking(move || {
+                export::write_records(&records, std::path::Path::new(&path), format, encoding)
+            })
+            .await
+            .map_err(|e| AppError::Internal(format!("导出任务执行失败: {}", e)))??;
+            Ok(count)
+        }))
+    }
+
     /// 重新初始化连接池（配置变更时）
     pub async fn reinit_pool(&mut self) -> AppResult<()> {
         self.init_pool().await
@@ -138,13 +385,37 @@ impl AppState {
         self.pool.as_ref().map(|p| p.state())
     }
 
+    /// 最近一次连接池初始化失败的原因
+    pub fn last_pool_error(&self) -> Option<ErrorInfo> {
+        self.last_pool_error.read().clone()
+    }
+
+    /// 数据库连接断路器状态
+    pub fn circuit_breaker_status(&self) -> CircuitBreakerStatus {
+        self.circuit_breaker.status()
+    }
+
+    /// 汇总当前运行指标（连接池、缓存、查询计数、断路器、连通性）
+    pub async fn metrics_snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            pool: self.get_pool_state(),
+            cache: self.cache.get_stats().await,
+            queries: self.query_counters.snapshot(),
+            circuit_breaker: self.circuit_breaker_status(),
+            connection: self.connection_health(),
+        }
+    }
+
+    /// 获取连接池大小建议
+    pub fn suggest_pool_size(&self) -> Option<crate::datasource::PoolSizeSuggestion> {
+        self.pool.as_ref().map(|p| p.suggest_pool_size())
+    }
+
     /// 执行缓存预热
     ///
     /// 根据配置和已保存的标签分组，预热最近几天的数据。
     /// 仅在 `performance.cache.warmup_enabled` 为 true 时执行。
     pub async fn warmup_cache(&self) -> AppResult<()> {
-        use tracing::{info, warn};
-
         // Check if warmup is enabled
         let warmup_enabled = self.config.app_config().performance.cache.warmup_enabled;
         if !warmup_enabled {
@@ -203,18 +474,7 @@ impl AppState {
         // Execute warmup
         let warmer = CacheWarmer::new(Arc::clone(&self.cache));
         let progress = warmer
-            .warmup(all_tasks, |task| {
-                let source = query_handle.source.clone();
-                let table = task.table.clone();
-                let start = task.start_time.clone();
-                let end = task.end_time.clone();
-                let tags = task.tags.clone();
-                async move {
-                    source
-                        .query_history(&table, &start, &end, tags.as_deref())
-                        .await
-                }
-            })
+            .warmup(all_tasks, |task| query_handle.fetch_warmup(task))
             .await?;
 
         info!(target: "industry_vis::state",
@@ -229,7 +489,7 @@ impl AppState {
     ///
     /// 用于进入分组时异步预热，不阻塞用户操作。
     pub async fn warmup_group(&self, group_id: &str) -> AppResult<()> {
-        use tracing::{debug, info, warn};
+        use tracing::debug;
 
         // Get query service handle
         let query_handle = match self.query_service() {
@@ -280,18 +540,7 @@ impl AppState {
         // Execute warmup
         let warmer = CacheWarmer::new(Arc::clone(&self.cache));
         let progress = warmer
-            .warmup(tasks, |task| {
-                let source = query_handle.source.clone();
-                let table = task.table.clone();
-                let start = task.start_time.clone();
-                let end = task.end_time.clone();
-                let tags = task.tags.clone();
-                async move {
-                    source
-                        .query_history(&table, &start, &end, tags.as_deref())
-                        .await
-                }
-            })
+            .warmup(tasks, |task| query_handle.fetch_warmup(task))
             .await?;
 
         info!(target: "industry_vis::state",
@@ -303,6 +552,180 @@ impl AppState {
     }
 }
 
+/// 共享的应用状态
+pub type SharedAppState = Arc<tokio::sync::RwLock<AppState>>;
+
+/// 启动实例的后台任务（定时导出调度和数据库连通性监控）
+///
+/// 需在 tokio 运行时中、实例刚包装为 `SharedAppState` 时调用；回调只持有弱引用，
+/// 实例释放后随之停止。
+pub fn start_background_tasks(state: &SharedAppState) {
+    start_export_scheduler(state);
+    start_connection_monitor(state);
+}
+
+/// 启动定时导出调度（到点后提交到该实例的后台导出任务队列）
+fn start_export_scheduler(state: &SharedAppState) {
+    let weak = Arc::downgrade(state);
+    let runner: ScheduledExportRunner = Arc::new(move |scheduled, now| {
+        let weak = weak.clone();
+        Box::pin(async move {
+            let state = weak
+                .upgrade()
+                .ok_or_else(|| AppError::Internal("应用状态已释放".to_string()))?;
+            let state = state.read().await;
+            let file_path = scheduled.output_path_at(now)?;
+            let task_id = state.submit_export(
+                scheduled.params_at(now)?,
+                scheduled.processing_config.clone(),
+                file_path.clone(),
+                BundleFormat::parse(&scheduled.format)?,
+                CsvEncoding::parse(scheduled.encoding.as_deref())?,
+            )?;
+            info!(target: "industry_vis::state",
+                "定时导出 {} 已提交 - 任务: {}, 路径: {}",
+                scheduled.id, task_id, file_path
+            );
+            Ok(())
+        })
+    });
+
+    match state.try_read() {
+        Ok(state) => state.export_scheduler.start(runner),
+        Err(_) => warn!(target: "industry_vis::state", "应用状态被占用，定时导出未启动"),
+    }
+}
+
+/// 启动数据库连通性监控（每次检查取当前连接池，重建连接池后自动跟随）
+fn start_connection_monitor(state: &SharedAppState) {
+    let weak = Arc::downgrade(state);
+    let probe: ConnectionProbe = Arc::new(move || {
+        let weak = weak.clone();
+        Box::pin(async move {
+            let state = weak
+                .upgrade()
+                .ok_or_else(|| AppError::Internal("应用状态已释放".to_string()))?;
+            // 检查期间不持有状态锁，避免阻塞重建连接池
+            let service = state.read().await.query_service();
+            drop(state);
+            service
+                .ok_or(AppError::DatabaseNotConnected)?
+                .test_connection()
+                .await
+        })
+    });
+
+    match state.try_read() {
+        Ok(state) => state.connection_monitor.start(probe),
+        Err(_) => warn!(target: "industry_vis::state", "应用状态被占用，连通性监控未启动"),
+    }
+}
+
+/// 主实例的 key（主窗口 label）
+pub const MAIN_STATE_KEY: &str = "main";
+
+/// 多实例应用状态管理
+///
+/// 按 key（窗口 label）持有相互隔离的 `AppState`，各实例有独立的连接池、缓存和查询服务；
+/// 没有独立实例的窗口使用主实例。
+pub struct AppStateManager {
+    main: SharedAppState,
+    instances: RwLock<HashMap<String, SharedAppState>>,
+}
+
+impl AppStateManager {
+    /// 以主实例创建
+    pub fn new(main: SharedAppState) -> Self {
+        Self {
+            main,
+            instances: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 主实例
+    pub fn main(&self) -> SharedAppState {
+        Arc::clone(&self.main)
+    }
+
+    /// 获取 key 对应的独立实例
+    pub fn get(&self, key: &str) -> Option<SharedAppState> {
+        self.instances.read().get(key).cloned()
+    }
+
+    /// 窗口对应的实例（没有独立实例时返回主实例）
+    pub fn for_window(&self, label: &str) -> SharedAppState {
+        self.get(label).unwrap_or_else(|| self.main())
+    }
+
+    /// 登记独立实例（替换同 key 的已有实例）
+    pub fn insert(&self, key: &str, state: AppState) -> AppResult<SharedAppState> {
+        if key == MAIN_STATE_KEY {
+            return Err(AppError::Validation(format!(
+                "'{}' 为主实例保留，不能创建独立实例",
+                MAIN_STATE_KEY
+            )));
+        }
+        let state = Arc::new(tokio::sync::RwLock::new(state));
+        start_background_tasks(&state);
+        self.instances
+            .write()
+            .insert(key.to_string(), Arc::clone(&state));
+        info!(target: "industry_vis::state", "创建独立应用状态: {}", key);
+        Ok(state)
+    }
+
+    /// 为窗口创建使用指定配置的独立实例并初始化连接池
+    ///
+    /// 标签分组与主实例共享；连接失败时实例仍保留，原因可通过 `last_pool_error` 查询。
+    pub async fn open(&self, key: &str, app_config: AppConfig) -> AppResult<SharedAppState> {
+        let tag_groups = self.main.read().await.config().tag_group_manager();
+        let mut state = AppState::with_config(ConfigState::isolated(app_config, tag_groups));
+        if let Err(e) = state.init_pool().await {
+            warn!(target: "industry_vis::state", "独立实例 {} 初始化连接池失败: {}", key, e);
+        }
+        self.insert(key, state)
+    }
+
+    /// 移除独立实例（窗口关闭时）
+    pub fn remove(&self, key: &str) -> Option<SharedAppState> {
+        let removed = self.instances.write().remove(key);
+        if removed.is_some() {
+            info!(target: "industry_vis::state", "释放独立应用状态: {}", key);
+        }
+        removed
+    }
+
+    /// 已创建独立实例的 key
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.instances.read().keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+}
+
+/// 调用窗口对应的应用状态（命令参数）
+///
+/// 按发起调用的窗口 label 从 `AppStateManager` 中路由到对应实例。
+pub struct WindowState(SharedAppState);
+
+impl std::ops::Deref for WindowState {
+    type Target = SharedAppState;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'de, R: tauri::Runtime> CommandArg<'de, R> for WindowState {
+    fn from_command(command: CommandItem<'de, R>) -> Result<Self, InvokeError> {
+        let webview = command.message.webview_ref();
+        let manager = webview
+            .try_state::<AppStateManager>()
+            .ok_or_else(|| InvokeError::from(AppError::Internal("应用状态未初始化".to_string())))?;
+        Ok(Self(manager.for_window(webview.label())))
+    }
+}
+
 /// 简化的应用状态（用于无需连接池的场景）
 pub struct AppStateSimple {
     config: ConfigState,
@@ -338,137 +761,343 @@ impl AppStateSimple {
 
 /// 查询服务句柄（独立于 AppState 的生命周期）
 pub struct QueryServiceHandle {
+    /// 主库数据源（自定义 SQL 只在主库执行）
     source: SqlServerSource,
+    /// 历史数据源（配置了归档库时为主库 + 归档库联合数据源）
+    history: Arc<dyn DataSource>,
     cache: SharedCache,
     default_table: String,
+    throttle: Arc<QueryThrottle>,
+    circuit_breaker: Arc<CircuitBreaker>,
+    rate_tracker: Arc<QueryRateTracker>,
+    query_counters: Arc<QueryCounters>,
+    /// 单次历史查询的最大原始行数
+    max_result_rows: Option<usize>,
 }
 
 impl QueryServiceHandle {
+    /// 检查分组的标签存在性和最近数据
+    pub async fn audit_groups(
+        &self,
+        groups: &[TagGroup],
+        recent_hours: u64,
+    ) -> AppResult<GroupAuditReport> {
+        // 每个标签一次计数查询，与其他重查询共用限流
+        let _permit = self.throttle.acquire().await?;
+        self.circuit_breaker
+            .call(crate::services::audit_groups(
+                self.history.as_ref(),
+                &self.default_table,
+                groups,
+                Local::now().naive_local(),
+                recent_hours,
+            ))
+            .await
+    }
+
+    /// 检查数据库连通性（不经断路器，断开期间也能发现恢复）
+    pub async fn test_connection(&self) -> AppResult<()> {
+        self.history.test_connection().await
+    }
+
     /// 获取可用标签列表
     pub async fn get_available_tags(&self) -> AppResult<Vec<String>> {
-        self.source.get_available_tags(&self.default_table).await
+        self.circuit_breaker
+            .call(self.history.get_available_tags(&self.default_table))
+            .await
+    }
+
+    /// 自动发现标签表
+    pub async fn discover_tag_table(&self) -> AppResult<Vec<TagTableCandidate>> {
+        self.circuit_breaker
+            .call(self.history.discover_tag_table())
+            .await
     }
 
     /// 搜索标签
     pub async fn search_tags(&self, keyword: &str, limit: usize) -> AppResult<Vec<String>> {
-        self.source.search_tags(keyword, limit).await
+        self.circuit_breaker
+            .call(self.history.search_tags(keyword, limit))
+            .await
     }
 
-    /// 查询历史数据 (V1 格式)
-    pub async fn query_history(
+    /// 执行自定义 SQL 查询，结果走标准处理流程（不缓存）
+    ///
+    /// 与历史查询相同的行数上限，超出时截断并在结果中标记 `truncated`。
+    pub async fn run_custom_query(
         &self,
-        params: &QueryParams,
+        sql: &str,
         processing_config: Option<&DataProcessingConfig>,
-        force_refresh: bool,
     ) -> AppResult<QueryResult> {
-        use crate::cache::CacheKey;
+        let records = self
+            .circuit_breaker
+            .call(async {
+                let _permit = self.throttle.acquire().await?;
+                self.source.run_custom_query(sql).await
+            })
+            .await?;
+        let (records, truncated) = cap_result_rows(records, self.max_result_rows);
+        let total = records.len();
+        Ok(QueryResult {
+            records: processing::process_query_result(records, processing_config)?,
+            total,
+            truncated,
+        })
+    }
+
+    /// 查询标签在时间范围内的原始数据（不经缓存和处理，用于实时轮询）
+    pub async fn query_recent(
+        &self,
+        tags: &[String],
+        start_time: &str,
+        end_time: &str,
+    ) -> AppResult<Vec<HistoryRecord>> {
+        self.circuit_breaker
+            .call(async {
+                let _permit = self.throttle.acquire().await?;
+                self.history
+                    .query_history(&self.default_table, start_time, end_time, Some(tags))
+                    .await
+            })
+            .await
+    }
 
-        use tracing::info;
+    /// 获取标签在指定时刻的值（前后最近点线性插值，无前点或后点时为 `None`）
+    pub async fn get_value_at(&self, tag: &str, time: &str) -> AppResult<Option<f64>> {
+        let records = self
+            .circuit_breaker
+            .call(async {
+                let _permit = self.throttle.acquire().await?;
+                self.history
+                    .query_value_neighbors(&self.default_table, tag, time)
+                    .await
+            })
+            .await?;
+        processing::interpolate_value_at(&records, time)
+    }
 
-        let tags_ref = params.tags.as_deref();
+    /// 预估查询数据量和耗时
+    ///
+    /// 行数用与查询相同条件的 `COUNT` 统计；设置每标签限量时按限量封顶。
+    pub async fn estimate_query(&self, params: &QueryParams) -> AppResult<QueryEstimate> {
+        let tags = params.tags.as_deref().filter(|t| !t.is_empty());
+        let count = self
+            .circuit_breaker
+            .call(async {
+                let _permit = self.throttle.acquire().await?;
+                self.history
+                    .count_history(
+                        &self.default_table,
+                        &params.start_time,
+                        &params.end_time,
+                        tags,
+                    )
+                    .await
+            })
+            .await?;
 
-        let cache_key = CacheKey::new(
-            &self.default_table,
-            &params.start_time,
-            &params.end_time,
-            tags_ref,
-            processing_config,
-        );
+        let estimated_rows = match (params.per_tag_limit, tags) {
+            (Some(limit), Some(tags)) => count.min(limit.saturating_mul(tags.len()) as u64),
+            _ => count,
+        };
 
-        if !force_refresh && let Some(cached_records) = self.cache.get(&cache_key).await {
-            info!(target: "industry_vis::query_service",
-                "缓存命中，返回 {} 条记录", cached_records.len()
-            );
-            let total = cached_records.len();
-            let records = apply_pagination(cached_records, params.offset, params.limit);
-            return Ok(QueryResult { records, total });
-        }
+        Ok(QueryEstimate {
+            estimated_rows,
+            estimated_ms: self.rate_tracker.estimate_ms(estimated_rows),
+            will_truncate: self
+                .max_result_rows
+                .is_some_and(|max| estimated_rows > max as u64),
+        })
+    }
 
+    /// 执行一次历史数据库查询（断路器保护、限流、记录速率和计数、按行数上限截断）
+    async fn fetch_history(&self, params: QueryParams) -> AppResult<(Vec<HistoryRecord>, bool)> {
         let records = self
-            .source
-            .query_history(
-                &self.default_table,
-                &params.start_time,
-                &params.end_time,
-                tags_ref,
-            )
+            .circuit_breaker
+            .call(async {
+                let _permit = self.throttle.acquire().await?;
+                let db_started = Instant::now();
+                let records = self
+                    .history
+                    .query_history_with_params(&self.default_table, &params)
+                    .await
+                    .inspect_err(|_| self.query_counters.record_error())?;
+                let elapsed_ms = db_started.elapsed().as_millis() as u64;
+                self.rate_tracker.record(records.len(), elapsed_ms);
+                self.query_counters.record(records.len(), elapsed_ms);
+                Ok(records)
+            })
             .await?;
+        Ok(cap_result_rows(records, self.max_result_rows))
+    }
 
+    /// 查询时间范围内的全部数据（不经缓存、不降采样，用于文件导出和统计）
+    ///
+    /// 与实时查询相同的断路器、限流和行数上限，忽略分页参数；传入 `processing_config`
+    /// 时按配置处理，但不做面向图表渲染的降采样。
+    pub async fn query_history_raw(
+        &self,
+        params: &QueryParams,
+        processing_config: Option<&DataProcessingConfig>,
+    ) -> AppResult<QueryResult> {
+        if let Some(config) = processing_config {
+            config.validate()?;
+        }
+        let params = QueryParams {
+            limit: None,
+            offset: None,
+            chunk_size: None,
+            ..params.clone()
+        };
+        let (records, truncated) = self.fetch_history(params.clone()).await?;
         let total = records.len();
-        let processed_records = processing::process_query_result(records, processing_config)?;
-        self.cache.put(cache_key, processed_records.clone()).await;
-        let records = apply_pagination(processed_records, params.offset, params.limit);
+        let records = match processing_config {
+            Some(config) => processing::process_records(records, config)?,
+            None => records,
+        };
+        Ok(QueryResult {
+            records: format_output_records(records, &params)?,
+            total,
+            truncated,
+        })
+    }
 
-        Ok(QueryResult { records, total })
+    /// 执行一次预热查询（与实时查询相同的保护和行数上限，返回是否截断）
+    async fn fetch_warmup(&self, task: WarmupTask) -> AppResult<(Vec<HistoryRecord>, bool)> {
+        let mut params = QueryParams::new(task.start_time, task.end_time);
+        params.tags = task.tags;
+        self.fetch_history(params).await
     }
 
-    /// 查询历史数据 V2
-    pub async fn query_history_v2(
+    /// 获取处理后的记录（缓存 + 按 `no_cache_tags` 绕过缓存）
+    async fn load_records(
         &self,
         params: &QueryParams,
         processing_config: Option<&DataProcessingConfig>,
         force_refresh: bool,
-    ) -> AppResult<QueryResultV2> {
-        use crate::cache::CacheKey;
-        use std::time::Instant;
-
-        let start_time = Instant::now();
-        let tags_ref = params.tags.as_deref();
-
-        let cache_key = CacheKey::new(
+        clock: &mut StageClock,
+    ) -> AppResult<LoadedRecords> {
+        if let Some(config) = processing_config {
+            config.validate()?;
+        }
+        load_processed_records(
+            &self.cache,
             &self.default_table,
-            &params.start_time,
-            &params.end_time,
-            tags_ref,
+            params,
             processing_config,
-        );
+            force_refresh,
+            clock,
+            |params| self.fetch_history(params),
+        )
+        .await
+    }
 
-        if !force_refresh && let Some(cached_records) = self.cache.get(&cache_key).await {
-            let query_time_ms = start_time.elapsed().as_millis() as u64;
-            let total_processed = cached_records.len();
-            let series = processing::records_to_series(&cached_records);
-            return Ok(QueryResultV2 {
-                series,
-                total_raw: total_processed,
-                total_processed,
-                cache_hit: true,
-                query_time_ms,
-            });
+    /// 查询历史数据 (V1 格式)
+    pub async fn query_history(
+        &self,
+        params: &QueryParams,
+        processing_config: Option<&DataProcessingConfig>,
+        force_refresh: bool,
+    ) -> AppResult<QueryResult> {
+        let mut clock = StageClock::start();
+        let loaded = self
+            .load_records(params, processing_config, force_refresh, &mut clock)
+            .await?;
+        if loaded.cache_hit {
+            info!(target: "industry_vis::query_service",
+                "缓存命中，返回 {} 条记录", loaded.records.len()
+            );
         }
 
-        let records = self
-            .source
-            .query_history(
-                &self.default_table,
-                &params.start_time,
-                &params.end_time,
-                tags_ref,
-            )
+        let records = apply_pagination(
+            loaded.records,
+            params.offset,
+            params.limit,
+            params.newest_first,
+        );
+        let records = format_output_records(records, params)?;
+        Ok(QueryResult {
+            records,
+            total: loaded.total_raw,
+            truncated: loaded.truncated,
+        })
+    }
+
+    /// 查询历史数据 V2
+    pub async fn query_history_v2(
+        &self,
+        params: &QueryParams,
+        processing_config: Option<&DataProcessingConfig>,
+        force_refresh: bool,
+    ) -> AppResult<QueryResultV2> {
+        if let Some(align) = &params.align {
+            align.validate()?;
+        }
+        let mut clock = StageClock::start();
+        let loaded = self
+            .load_records(params, processing_config, force_refresh, &mut clock)
             .await?;
 
-        let total_raw = records.len();
-        let processed_records = processing::process_query_result(records, processing_config)?;
-        let total_processed = processed_records.len();
-        self.cache.put(cache_key, processed_records.clone()).await;
-        let series = processing::records_to_series(&processed_records);
-        let query_time_ms = start_time.elapsed().as_millis() as u64;
+        let total_processed = loaded.records.len();
+        let series = processing::build_series(&loaded.records, params);
+        let serialize_ms = clock.lap_ms();
 
-        Ok(QueryResultV2 {
+        let mut result = QueryResultV2 {
+            content_hash: series_content_hash(&series),
             series,
-            total_raw,
+            annotations: Vec::new(),
+            total_raw: loaded.total_raw,
             total_processed,
-            cache_hit: false,
-            query_time_ms,
-        })
+            cache_hit: loaded.cache_hit,
+            truncated: loaded.truncated,
+            query_time_ms: clock.total_ms(),
+            timing: QueryTiming {
+                serialize_ms,
+                ..loaded.timing
+            },
+            query_token: None,
+            chunk_count: None,
+            empty_reason: None,
+            warnings: loaded.warnings,
+        };
+        self.explain_empty(&mut result, params).await;
+        Ok(result)
+    }
+
+    /// 空结果时说明原因（标签不存在 / 时间范围内无采样）
+    ///
+    /// 只在无数据时比对标签是否存在；获取标签列表失败时不附带原因。
+    async fn explain_empty(&self, result: &mut QueryResultV2, params: &QueryParams) {
+        if result.total_raw > 0 {
+            return;
+        }
+        let requested = params.tags.as_deref().unwrap_or_default();
+        let available = if requested.is_empty() {
+            Vec::new()
+        } else {
+            match self.history.get_available_tags(&self.default_table).await {
+                Ok(tags) => tags,
+                Err(e) => {
+                    warn!(target: "industry_vis::query_service", "获取标签列表失败，无法判断空结果原因: {}", e);
+                    return;
+                }
+            }
+        };
+        result.empty_reason = Some(diagnose_empty_result(requested, &available));
     }
 }
 
-/// 应用分页参数
+/// 应用分页参数（`newest_first` 时按时间降序分页）
 fn apply_pagination(
-    records: Vec<crate::models::HistoryRecord>,
+    mut records: Vec<crate::models::HistoryRecord>,
     offset: Option<usize>,
     limit: Option<usize>,
+    newest_first: bool,
 ) -> Vec<crate::models::HistoryRecord> {
+    if newest_first {
+        // 记录按时间升序排列，稳定排序保证同一时刻的记录顺序不变
+        records.sort_by(|a, b| b.date_time.cmp(&a.date_time));
+    }
     match (offset, limit) {
         (Some(offset), Some(limit)) => records.into_iter().skip(offset).take(limit).collect(),
         (Some(offset), None) => records.into_iter().skip(offset).collect(),
@@ -476,3 +1105,84 @@ fn apply_pagination(
         (None, None) => records,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheKey;
+    use crate::config::TagGroupConfigManager;
+
+    fn isolated_state(database: &str, groups: &Arc<RwLock<TagGroupConfigManager>>) -> AppState {
+        let mut config = AppConfig::default();
+        config.database.database = database.to_string();
+        AppState::with_config(ConfigState::isolated(config, Arc::clone(groups)))
+    }
+
+    #[tokio::test]
+    async fn test_instances_keep_separate_connections_and_caches() {
+        let groups_path = std::env::temp_dir().join("industry_vis_test_state_manager_groups.toml");
+        let groups = Arc::new(RwLock::new(
+            TagGroupConfigManager::load_from(&groups_path).unwrap(),
+        ));
+        let manager = AppStateManager::new(Arc::new(tokio::sync::RwLock::new(isolated_state(
+            "main_db", &groups,
+        ))));
+        let a = manager
+            .insert("window-a", isolated_state("plant_a", &groups))
+            .unwrap();
+        let b = manager
+            .insert("window-b", isolated_state("plant_b", &groups))
+            .unwrap();
+
+        // 各窗口路由到自己的实例，未登记的窗口使用主实例
+        assert!(Arc::ptr_eq(&manager.for_window("window-a"), &a));
+        assert!(Arc::ptr_eq(&manager.for_window("window-b"), &b));
+        assert!(Arc::ptr_eq(&manager.for_window("other"), &manager.main()));
+
+        // 各自的连接配置，修改只影响本实例（且不写盘）
+        let mut changed = a.read().await.config().app_config();
+        changed.database.server = "10.0.0.2".to_string();
+        a.read().await.config().update_app_config(changed).unwrap();
+        assert_eq!(a.read().await.config().database_config().server, "10.0.0.2");
+        assert_ne!(b.read().await.config().database_config().server, "10.0.0.2");
+        assert_eq!(
+            b.read().await.config().database_config().database,
+            "plant_b"
+        );
+
+        // 各自的缓存
+        let key = CacheKey::new("History", "2024-01-01", "2024-01-02", None, None);
+        let records = vec![HistoryRecord::new(
+            "2024-01-01T00:00:00".to_string(),
+            "TT_101".to_string(),
+            1.0,
+            "Good".to_string(),
+        )];
+        a.read().await.cache().put(key.clone(), records).await;
+        assert!(a.read().await.cache().get(&key).await.is_some());
+        assert!(b.read().await.cache().get(&key).await.is_none());
+        assert!(
+            manager
+                .main()
+                .read()
+                .await
+                .cache()
+                .get(&key)
+                .await
+                .is_none()
+        );
+
+        // 主实例 key 保留；移除后该窗口回落到主实例
+        assert!(
+            manager
+                .insert(MAIN_STATE_KEY, isolated_state("other_db", &groups))
+                .is_err()
+        );
+        assert!(manager.remove("window-a").is_some());
+        assert!(Arc::ptr_eq(
+            &manager.for_window("window-a"),
+            &manager.main()
+        ));
+        assert_eq!(manager.keys(), vec!["window-b".to_string()]);
+    }
+}
@@ -25,7 +25,11 @@ export const useDataStore = defineStore('data', () => {
   const total = ref(0)
   const totalProcessed = ref(0)
   const cacheHit = ref(false)
+  const truncated = ref(false)  // 结果超过行数上限被截断
+  const showQuality = ref(false)  // 查询时返回质量码，图上区分坏质量点
   const queryTimeMs = ref(0)
+  // 最近一次 V1 查询的参数（导出时随导出历史保存，用于重新导出）
+  let lastQuery: { params: QueryParams, processingConfig: DataProcessingConfig | null } | null = null
 
   // Actions
   const setTimeRange = (start: Date, end: Date) => {
@@ -50,7 +54,7 @@ export const useDataStore = defineStore('data', () => {
   /**
    * 使用 V1 接口获取数据（保持向后兼容）
    */
-  const fetchData = async (processingConfig?: DataProcessingConfig, forceRefresh = false) => {
+  const fetchData = async (processingConfig?: DataProcessingConfig, forceRefresh = false, groupId?: string) => {
     loading.value = true
     error.value = null
     
@@ -65,9 +69,11 @@ export const useDataStore = defineStore('data', () => {
         params,
         processingConfig: processingConfig || null,
         forceRefresh,
+        groupId: groupId || null,
       })
       records.value = result.records
       total.value = result.total
+      lastQuery = { params, processingConfig: processingConfig || null }
     } catch (e) {
       console.error('Failed to fetch data:', e)
       error.value = String(e)
@@ -80,7 +86,7 @@ export const useDataStore = defineStore('data', () => {
   /**
    * 使用 V2 接口获取数据（预分组格式，优化渲染）
    */
-  const fetchDataV2 = async (processingConfig?: DataProcessingConfig, forceRefresh = false) => {
+  const fetchDataV2 = async (processingConfig?: DataProcessingConfig, forceRefresh = false, groupId?: string) => {
     loading.value = true
     error.value = null
     
@@ -89,18 +95,21 @@ export const useDataStore = defineStore('data', () => {
         startTime: formatLocalDateTime(startTime.value),
         endTime: formatLocalDateTime(endTime.value),
         tags: selectedTags.value.length > 0 ? selectedTags.value : undefined,
+        includeQuality: showQuality.value || undefined,
       }
       
       const result = await invoke<QueryResultV2>('query_history_v2', { 
         params,
         processingConfig: processingConfig || null,
         forceRefresh,
+        groupId: groupId || null,
       })
       
       chartSeries.value = result.series
       total.value = result.totalRaw
       totalProcessed.value = result.totalProcessed
       cacheHit.value = result.cacheHit
+      truncated.value = result.truncated ?? false
       queryTimeMs.value = result.queryTimeMs
       
       // 同时更新 records 以保持兼容（如果需要）
@@ -162,6 +171,8 @@ export const useDataStore = defineStore('data', () => {
         await invoke('export_to_csv', {
           records: records.value,
           filePath,
+          params: lastQuery?.params ?? null,
+          processingConfig: lastQuery?.processingConfig ?? null,
         })
       }
     } catch (e) {
@@ -183,6 +194,8 @@ export const useDataStore = defineStore('data', () => {
     total,
     totalProcessed,
     cacheHit,
+    truncated,
+    showQuality,
     queryTimeMs,
     // Actions
     setTimeRange,
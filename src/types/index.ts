@@ -2,7 +2,7 @@
 export interface HistoryRecord {
   dateTime: string
   tagName: string
-  tagVal: number
+  tagVal: number | null  // NaN / Inf 由后端序列化为 null
   tagQuality: string
 }
 
@@ -13,17 +13,54 @@ export interface DatabaseConfig {
   database: string
   username: string
   password: string
+  application_intent?: 'ReadOnly' | 'ReadWrite'  // "ReadOnly" 时路由到只读副本（AlwaysOn），默认不设置
 }
 
 // 查询配置
 export interface QueryConfig {
   defaultTable: string
+  allowCustomSql?: boolean  // 是否允许自定义 SQL 查询（默认关闭）
+  maxResultRows?: number  // 单次查询最大原始行数（0 不限制）
+  archive?: ArchiveConfig  // 归档库：早于主库保留期的历史查询自动路由到归档库
+  isolationLevel?: 'read_uncommitted' | 'read_committed' | 'snapshot'  // 查询前设置的会话隔离级别
+}
+
+// 归档库配置
+export interface ArchiveConfig {
+  database: DatabaseConfig
+  primaryRetentionDays?: number  // 主库保留天数（默认 30）
+}
+
+// Schema 配置（选择数据库厂商 Profile）
+export interface SchemaConfig {
+  profile: string  // 默认 "default"
+  tag_table?: string  // 标签表名（未设置时使用 Profile 默认值）
+  wide_columns?: string[]  // 宽表模式测点列（profile 为 "wide" 时必填）
+  index_hint?: string  // 历史表索引提示（索引名，仅 default profile）
+  tag_filter_first?: boolean  // TagName 条件放在 DateTime 条件之前（适配 TagName 在前的复合索引）
+  time_zone?: string  // DATETIMEOFFSET 时间列换算到的时区（如 "+08:00" / "UTC"），默认系统本地时区
+}
+
+// 标签表候选（自动发现）
+export interface TagTableCandidate {
+  schema: string
+  name: string
+  score: number  // 越高越可能是标签表
 }
 
 // 完整应用配置
 export interface AppConfig {
   database: DatabaseConfig
   query: QueryConfig
+  schema?: SchemaConfig  // 缺失时后端使用 default profile
+}
+
+// 标签层级树节点
+export interface TagTreeNode {
+  name: string        // 当前层级片段，根节点为空
+  path: string        // 完整路径，如 "Unit1.Boiler"
+  children: TagTreeNode[]
+  tags: string[]      // 直接属于该节点的叶子标签全名
 }
 
 // 查询参数
@@ -33,27 +70,145 @@ export interface QueryParams {
   tags?: string[]
   limit?: number
   offset?: number
+  align?: SeriesAlignConfig  // 多标签时间对齐（仅 V2）
+  gap?: SeriesGapConfig      // 断采检测（仅 V2）
+  perTagLimit?: number      // 每个标签最多返回的最新点数（SQL 层限量）
+  includeQuality?: boolean  // 返回各点质量码（仅 V2，默认关闭）
+  chunkSize?: number  // 每片数据点数，结果超过时分片返回（仅 V2）
+  columnar?: boolean  // 以 timestamps / values 两个数组返回系列数据点（仅 V2 JSON 查询）
+  outputTimeFormat?: string  // 结果时间格式（strftime，仅 V1），默认 %Y-%m-%dT%H:%M:%S%.3f
+  noCacheTags?: string[]  // 每次都查库、不写入缓存的标签（未指定 tags 时整个查询绕过缓存）
+  newestFirst?: boolean  // 从时间最新端开始分页，结果按时间降序（仅 V1）
+}
+
+// 多标签时间对齐配置
+export interface SeriesAlignConfig {
+  enabled: boolean
+  intervalMs?: number  // 未指定时取最粗采样间隔
+  fill: 'linear' | 'none'
+}
+
+// 断采检测配置（超过阈值的相邻点之间插入 null 断点）
+export interface SeriesGapConfig {
+  enabled: boolean
+  thresholdMs?: number  // 未指定时按正常采样间隔 × factor 估算
+  factor: number
 }
 
 // 查询结果 (V1 兼容格式)
 export interface QueryResult {
   records: HistoryRecord[]
   total: number
+  truncated?: boolean  // 超过行数上限被截断
 }
 
 // 图表系列数据 (V2 格式，按标签预分组)
 export interface ChartSeriesData {
   tagName: string
-  data: [number, number][]  // [[timestamp_ms, value], ...]
+  data: [number, number | null][]  // [[timestamp_ms, value], ...]，null 表示缺失
+  quality?: string[]  // 与 data 一一对应的质量码（includeQuality 时返回）
+  valueLabels?: string[]  // 与 data 一一对应的文本标签（分组配置了值映射时返回）
+  // 列式数据点（columnar 时返回，此时 data 为空），timestamps[i] 与 values[i] 对应
+  timestamps?: (number | null)[]
+  values?: (number | null)[]
 }
 
 // 查询结果 V2 (预分组格式，优化前端渲染)
 export interface QueryResultV2 {
   series: ChartSeriesData[]
+  annotations?: Annotation[]  // 指定分组查询时返回与时间范围重叠的标注
   totalRaw: number
   totalProcessed: number
   cacheHit: boolean
+  truncated?: boolean  // 超过行数上限被截断（只保留最早的部分）
   queryTimeMs: number
+  timing: QueryTiming
+  queryToken?: string  // 分片返回时的令牌（series 为空，用 fetch_series_chunk 拉取）
+  chunkCount?: number  // 分片返回时的总分片数
+  emptyReason?: EmptyReason  // totalRaw 为 0 时的原因（查询成功但无数据）
+  warnings?: string[]  // 提示信息（如采样不均匀，重采样聚合可能失真；同轴标签单位不一致）
+  contentHash: string  // 系列数据指纹，数据不变时不变（据此判断是否需要重绘）
+}
+
+// 空结果原因：标签在库中不存在 / 时间范围内无采样
+export type EmptyReason =
+  | { kind: 'tagsNotFound'; tags: string[] }
+  | { kind: 'noDataInRange' }
+
+// 后台导出任务（start_export 返回 taskId，状态通过 export-progress 事件推送）
+export type ExportTaskStatus = 'queued' | 'running' | 'done' | 'failed' | 'cancelled'
+
+export interface ExportTaskInfo {
+  taskId: string
+  filePath: string
+  status: ExportTaskStatus
+  progress: number  // 0 ~ 1
+  recordCount?: number  // 完成后返回
+  error?: string  // 失败原因
+}
+
+// get_api_schema 支持的类型名（返回 draft-07 JSON Schema）
+export type ApiSchemaType =
+  | 'QueryResultV2'
+  | 'QueryResult'
+  | 'QueryParams'
+  | 'DataProcessingConfig'
+  | 'HistoryRecord'
+  | 'ChartSeriesData'
+  | 'QueryEstimate'
+  | 'Annotation'
+
+// 定时导出任务（schedule_export 注册，schedule 为间隔如 15m 或 5 段 cron）
+export interface ScheduledExportInfo {
+  id: string
+  schedule: string
+  params: QueryParams  // 执行时时间范围平移到以执行时刻为终点
+  processingConfig?: DataProcessingConfig
+  outputTemplate: string  // 支持 {date}、{time}、{tags}
+  format: string  // csv / parquet / arrow
+  encoding?: string
+  createdAt: string
+  nextRun?: string
+  lastRun?: string
+  lastError?: string
+  runCount: number
+}
+
+// 实时订阅推送（realtime-data 事件）
+export interface RealtimeUpdate {
+  subscriptionId: string
+  records: HistoryRecord[]  // 上次推送之后的新数据点
+}
+
+// V2 查询结果分片（同一标签的片段按分片顺序拼接）
+export interface SeriesChunk {
+  chunkIndex: number
+  chunkCount: number
+  series: ChartSeriesData[]
+}
+
+// 查询数据量预估
+export interface QueryEstimate {
+  estimatedRows: number
+  estimatedMs?: number | null  // 尚无历史查询时为空
+  willTruncate: boolean  // 超过结果行数上限
+}
+
+// 标签正常采样周期（detect_sampling_interval）
+export interface SamplingInterval {
+  tagName: string
+  intervalMs: number
+  method: 'mode' | 'median'  // 规则采样取众数，不规则时取中位数
+  sampleCount: number
+  regularity: number  // 等于众数间隔的时间差占比（0 ~ 1）
+}
+
+// 查询分阶段耗时（毫秒）
+export interface QueryTiming {
+  cacheLookupMs: number
+  dbMs: number         // 缓存命中时为 0
+  processMs: number
+  serializeMs: number
 }
 
 // 缓存统计信息
@@ -66,6 +221,24 @@ export interface CacheStats {
   estimatedMemoryBytes: number
 }
 
+// 缓存配置建议（仅供参考，不会自动修改配置）
+export interface CacheConfigSuggestion {
+  currentMaxEntries: number
+  suggestedMaxEntries: number
+  currentTtlSeconds: number
+  suggestedTtlSeconds: number
+  hitRate: number
+  estimatedMemoryBytes: number
+  samples: number
+  reason: string
+}
+
+// 缓存访问事件（`cache-access`，需通过 set_cache_events 开启）
+export interface CacheEvent {
+  keySummary: string
+  hit: boolean
+}
+
 // 连接测试结果
 export interface ConnectionTestResult {
   success: boolean
@@ -80,17 +253,77 @@ export interface PoolState {
   maxSize: number
 }
 
+// 数据库连接断路器状态
+export type CircuitState = 'closed' | 'open' | 'halfOpen'
+
+export interface CircuitBreakerStatus {
+  state: CircuitState
+  consecutiveFailures: number
+  retryAfterSecs: number | null  // 距冷却结束的剩余秒数（仅断开状态）
+}
+
+// 数据库连通性状态（get_connection_health / connection-health 事件）
+export type ConnectionHealth = 'healthy' | 'degraded' | 'disconnected'
+
+export interface ConnectionHealthStatus {
+  health: ConnectionHealth
+  consecutiveFailures: number
+  lastChecked: string | null
+  latencyMs: number | null  // 最近一次成功检查的耗时
+  lastError: string | null
+}
+
+// 分组健康检查（audit_all_groups）
+export interface GroupAuditIssue {
+  groupId: string
+  groupName: string
+  missingTags: string[]  // 标签表中不存在的标签
+  staleTags: string[]  // 时间窗口内没有数据的标签
+}
+
+export interface GroupAuditReport {
+  issues: GroupAuditIssue[]  // 只包含有问题的分组
+  groupCount: number
+  tagCount: number
+  recentHours: number
+  auditedAt: string
+  cached: boolean  // 结果来自缓存（5 分钟内）
+}
+
+// 结构化错误信息（如连接池初始化失败原因）
+export interface ErrorInfo {
+  code: string  // 如 "DATABASE_NOT_FOUND" / "AUTH_FAILED" / "NETWORK"
+  sqlErrorNumber: number | null
+  message: string
+  retryable: boolean
+}
+
+// 连接池大小建议
+export interface PoolSizeSuggestion {
+  current: number
+  suggested: number
+  avgWaitMs: number
+  queuedRatio: number
+  samples: number
+  reason: string
+}
+
 // 异常值剔除配置
 export interface OutlierRemovalConfig {
   enabled: boolean
   method: string  // "3sigma"
+  window?: number  // 滚动窗口点数（设置时按局部窗口判断离群，适合有趋势的信号）
+  mode?: 'drop' | 'clip'  // drop 剔除离群点（默认）| clip 夹到边界保留点
 }
 
 // 重采样配置
 export interface ResampleConfig {
   enabled: boolean
   interval: number  // 秒
-  method: string    // "mean"
+  method: string    // "mean" | "ohlc"（输出 TAG.open/high/low/close 四个派生序列）
+  dstAmbiguous?: 'earliest' | 'latest'  // 夏令时回拨时歧义时间取较早（默认）| 较晚的时刻
+  // 多统计聚合，非空时忽略 method，每个统计量输出 TAG_<统计量> 派生序列
+  methods?: ('mean' | 'max' | 'min' | 'first' | 'last' | 'sum' | 'count')[]
 }
 
 // 平滑滤波配置
@@ -100,11 +333,43 @@ export interface SmoothingConfig {
   window: number   // 窗口大小
 }
 
+// 滚动标准差波动带配置：每个序列额外输出 TAG.upper / TAG.lower 派生序列
+// （居中窗口内的滚动均值 ± multiplier × 滚动标准差，在平滑前的数据上计算）
+export interface StdBandConfig {
+  enabled: boolean
+  window: number      // 窗口大小
+  multiplier: number  // 标准差倍数（1 即 ±1σ）
+}
+
+// 去重配置（按 标签 + 时间）
+export interface DedupConfig {
+  enabled: boolean
+  keep: string     // "first" | "last"
+}
+
+// 标签工程量程
+export interface TagRange {
+  tag: string
+  min: number
+  max: number
+}
+
+// 工程量程裁剪配置
+export interface RangeLimitConfig {
+  enabled: boolean
+  mode: string     // "drop" | "clip"
+  ranges: TagRange[]
+}
+
 // 数据处理配置
 export interface DataProcessingConfig {
   outlierRemoval: OutlierRemovalConfig
   resample: ResampleConfig
   smoothing: SmoothingConfig
+  stdBand?: StdBandConfig
+  dedup?: DedupConfig
+  rangeLimit?: RangeLimitConfig
+  baseline?: string  // 相对基准显示: 'first' | 'mean' | 数值
 }
 
 // 创建默认数据处理配置
@@ -123,6 +388,20 @@ export function createDefaultProcessingConfig(): DataProcessingConfig {
       enabled: false,
       method: 'moving_avg',
       window: 5
+    },
+    stdBand: {
+      enabled: false,
+      window: 20,
+      multiplier: 1
+    },
+    dedup: {
+      enabled: false,
+      keep: 'first'
+    },
+    rangeLimit: {
+      enabled: false,
+      mode: 'drop',
+      ranges: []
     }
   }
 }
@@ -132,6 +411,103 @@ export interface ChartConfig {
   id: string
   name: string
   tags: string[]  // 最多 5 个标签
+  alarms?: TagAlarm[]
+  axes?: TagAxis[]  // 双 Y 轴：未配置的标签使用左轴
+  aliases?: TagAlias[]  // 标签显示别名：未配置的标签显示原名
+  valueMaps?: TagValueMap[]  // 标签值映射：离散状态码 → 显示文本
+}
+
+// 标签显示别名
+export interface TagAlias {
+  tag: string
+  alias: string
+}
+
+// 标签值映射（如 "0" → "停止"）
+export interface TagValueMap {
+  tag: string
+  valueMap: Record<string, string>
+}
+
+// 标签 Y 轴配置
+export interface TagAxis {
+  tag: string
+  yAxisIndex: 0 | 1  // 0 左轴，1 右轴
+}
+
+// 标签报警线配置
+export interface TagAlarm {
+  tag: string
+  alarmHigh?: number | null
+  alarmLow?: number | null
+}
+
+// 标签越限统计
+export interface AlarmStats {
+  tagName: string
+  alarmHigh: number | null
+  alarmLow: number | null
+  totalPoints: number
+  highCount: number
+  lowCount: number
+  highDurationMs: number
+  lowDurationMs: number
+}
+
+// 按事件标签切分出的批次
+export interface EventBatch {
+  index: number
+  startMs: number
+  endMs: number
+  complete: boolean  // 数据结束时仍在进行的批次为 false
+  series: ChartSeriesData[]
+}
+
+// 处理性能基准
+export interface BenchmarkStage {
+  name: string
+  durationMs: number
+  outputRows: number
+}
+
+export interface BenchmarkResult {
+  rows: number
+  tags: number
+  stages: BenchmarkStage[]
+}
+
+// 原生 / Polars 实现一致性校验（verify_processing_consistency）
+export interface ConsistencyDiff {
+  tagName: string
+  dateTime: string
+  nativeValue: number | null  // null 表示只在 Polars 结果中存在
+  polarsValue: number | null  // null 表示只在原生结果中存在
+}
+
+export interface ConsistencyReport {
+  consistent: boolean
+  nativeCount: number
+  polarsCount: number
+  diffCount: number
+  maxDeviation: number
+  tolerance: number
+  diffs: ConsistencyDiff[]  // 最多 100 个
+}
+
+// 导出历史摘要
+export interface ExportHistoryItem {
+  id: string
+  exportedAt: string
+  filePath: string
+  encoding: string | null
+  recordCount: number
+  repeatable: boolean  // 有查询参数时可重新导出
+}
+
+// 标签元数据
+export interface TagMetadata {
+  tag: string
+  unit?: string  // 工程单位（如 ℃、MPa）
 }
 
 // 标签分组（包含多个图表）
@@ -142,6 +518,112 @@ export interface TagGroup {
   processingConfig: DataProcessingConfig
   createdAt: string
   updatedAt: string
+  annotations?: Annotation[]  // 标注区间
+  tagMetadata?: TagMetadata[]  // 标签元数据（同图同轴单位不一致时查询结果给出提示）
+  locked?: boolean  // 锁定的分组不参与批量修改
+  lastAccessed?: string  // 最近访问时间
+  accessCount?: number  // 累计访问次数
+}
+
+// 分组版本快照（更新/删除前自动保存，可用 restore_group_snapshot 恢复）
+export interface GroupSnapshotItem {
+  id: string
+  groupId: string
+  groupName: string
+  createdAt: string
+  action: 'update' | 'patch' | 'delete' | 'restore'
+  chartCount: number
+}
+
+// 图上标注区间（批次、事件等）
+export interface Annotation {
+  id: string  // 新增时可为空，由后端生成
+  start: string
+  end: string
+  label: string
+  color?: string
+}
+
+// 批量应用处理配置结果
+export interface BatchApplyResult {
+  updated: string[]
+  skippedLocked: string[]
+  notFound: string[]
+}
+
+// 批量导入分组结果（import_groups，tagMapping 为旧标签名 → 本库标签名）
+export interface GroupImportResult {
+  groups: TagGroup[]
+  unmappedTags: string[]  // 未映射的标签（保留原名）
+}
+
+// 分组打包导出结果（export_group_data）
+export interface GroupExportResult {
+  filePath: string
+  recordCount: number
+  tagRecordCounts: Record<string, number>  // 各标签记录数（含无数据的标签）
+}
+
+// 分组 PDF 报告导出结果（每个图表一页，统计表过长时续页）
+export interface PdfReportResult {
+  filePath: string
+  chartCount: number
+  pageCount: number
+  seriesCount: number
+  fileSize: number  // 字节
+}
+
+// 仪表盘定义（分组可视化定义的自包含导出，可导入还原为分组）
+export interface DashboardDefinition {
+  formatVersion: number
+  name: string
+  exportedAt: string
+  timeRange: { start: string; end: string }
+  processingConfig: DataProcessingConfig
+  layout: { columns: number }  // 网格列数
+  charts: DashboardChart[]
+  annotations: Annotation[]
+  tagMetadata: TagMetadata[]  // 标签元数据（工程单位等）
+}
+
+// 仪表盘中的单个图表
+export interface DashboardChart {
+  id: string
+  name: string
+  position: { row: number; col: number; width: number; height: number }  // 网格格数
+  series: DashboardSeries[]
+}
+
+// 图表中的单个标签系列
+export interface DashboardSeries {
+  tag: string
+  displayName: string  // 别名或标签名
+  yAxisIndex: 0 | 1
+  alarmHigh?: number | null
+  alarmLow?: number | null
+  valueMap?: Record<string, string>  // 值映射
+}
+
+// 分组使用统计
+export interface GroupUsageStats {
+  id: string
+  name: string
+  accessCount: number
+  lastAccessed: string | null
+}
+
+// 标签被分组/图表引用的情况
+export interface TagUsage {
+  tagName: string
+  groupCount: number
+  chartCount: number
+  groups: string[]
+}
+
+// 所有分组的标签引用汇总
+export interface TagUsageSummary {
+  uniqueTags: number
+  tags: TagUsage[]
 }
 
 // 创建默认图表配置
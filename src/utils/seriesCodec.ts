@@ -0,0 +1,52 @@
+import type { ChartSeriesData, QueryResultV2 } from '@/types'
+
+// query_history_v2_compressed 返回的二进制格式解码
+// 布局见后端 export/binary.rs：
+// "IVS1" | metaLen | meta JSON | 对齐 8
+// seriesCount | (nameLen | pointCount | name | 对齐 8 | f64 * pointCount * 2)*
+
+const MAGIC = 'IVS1'
+
+const align8 = (offset: number) => Math.ceil(offset / 8) * 8
+
+export function decodeSeriesBinary(buffer: ArrayBuffer): QueryResultV2 {
+  const view = new DataView(buffer)
+  const decoder = new TextDecoder()
+
+  const magic = decoder.decode(new Uint8Array(buffer, 0, 4))
+  if (magic !== MAGIC) {
+    throw new Error(`无效的二进制格式标识: ${magic}`)
+  }
+
+  let offset = 4
+  const metaLen = view.getUint32(offset, true)
+  offset += 4
+  const meta = JSON.parse(decoder.decode(new Uint8Array(buffer, offset, metaLen))) as QueryResultV2
+  offset = align8(offset + metaLen)
+
+  const seriesCount = view.getUint32(offset, true)
+  offset += 4
+
+  const series: ChartSeriesData[] = []
+  for (let i = 0; i < seriesCount; i++) {
+    const nameLen = view.getUint32(offset, true)
+    const pointCount = view.getUint32(offset + 4, true)
+    offset += 8
+    const tagName = decoder.decode(new Uint8Array(buffer, offset, nameLen))
+    offset = align8(offset + nameLen)
+
+    // 数据块已 8 字节对齐，可直接构造 Float64Array（小端平台）
+    const values = new Float64Array(buffer, offset, pointCount * 2)
+    offset += pointCount * 16
+
+    // NaN 对应 JSON 接口中的 null（缺失值）
+    const data: [number, number | null][] = new Array(pointCount)
+    for (let j = 0; j < pointCount; j++) {
+      const value = values[j * 2 + 1]
+      data[j] = [values[j * 2], Number.isNaN(value) ? null : value]
+    }
+    series.push({ tagName, data })
+  }
+
+  return { ...meta, series }
+}